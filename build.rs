@@ -0,0 +1,47 @@
+fn main() {
+    #[cfg(feature = "grpc")]
+    grpc::generate();
+}
+
+/// The `MatchingEngine` service of `proto/matching_engine.proto`, with
+/// messages encoded by `crate::codec` rather than prost.
+#[cfg(feature = "grpc")]
+mod grpc {
+    use tonic_build::manual::{Builder, Method, Service};
+
+    fn method(name: &str, route: &str, input: &str, output: &str) -> tonic_build::manual::MethodBuilder {
+        Method::builder()
+            .name(name)
+            .route_name(route)
+            .input_type(input)
+            .output_type(output)
+            .codec_path("crate::grpc::ProtoCodec")
+    }
+
+    pub fn generate() {
+        println!("cargo:rerun-if-changed=build.rs");
+        let events = "crate::grpc::Events";
+        let service = Service::builder()
+            .name("MatchingEngine")
+            .package("matching_engine")
+            .method(method("place_order", "PlaceOrder", "crate::commands::PlaceOrderCommand", events).build())
+            .method(method("cancel_order", "CancelOrder", "crate::commands::CancelOrderCommand", events).build())
+            .method(
+                method(
+                    "subscribe_events",
+                    "SubscribeEvents",
+                    "crate::subscription::EventFilter",
+                    "crate::events::OrderEvent",
+                )
+                .server_streaming()
+                .build(),
+            )
+            .method(
+                method("subscribe_depth", "SubscribeDepth", "crate::grpc::DepthRequest", "crate::depth::L2Snapshot")
+                    .server_streaming()
+                    .build(),
+            )
+            .build();
+        Builder::new().compile(&[service]);
+    }
+}
@@ -0,0 +1,108 @@
+#ifndef MATCHING_ENGINE_H
+#define MATCHING_ENGINE_H
+
+/* Generated by cbindgen from src/ffi.rs. Do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#define ME_OK 0
+
+#define ME_ERR_NULL_ARGUMENT -1
+
+#define ME_ERR_INVALID_ARGUMENT -2
+
+#define ME_ERR_REJECTED -3
+
+#define ME_ERR_DUPLICATE_ORDER -4
+
+#define ME_ERR_INTERNAL -5
+
+// Nothing was written; the needed length, NUL included, was.
+#define ME_ERR_BUFFER_TOO_SMALL -6
+
+#define ME_SIDE_BUY 0
+
+#define ME_SIDE_SELL 1
+
+#define ME_ORDER_MARKET 0
+
+#define ME_ORDER_LIMIT 1
+
+#define ME_ORDER_STOP_LOSS 2
+
+#define ME_ORDER_TAKE_PROFIT 3
+
+// An engine with an in-memory event store and its own runtime. Calls on
+// one engine may come from any thread.
+typedef struct MeEngine MeEngine;
+
+typedef struct MeOrder {
+  uint8_t order_id[16];
+  uint8_t user_id[16];
+  const char *symbol;
+  // An `ME_SIDE_` value.
+  int32_t side;
+  // An `ME_ORDER_` value.
+  int32_t order_type;
+  // NULL for market orders.
+  const char *price;
+  const char *quantity;
+  // NULL unless a stop order.
+  const char *stop_price;
+} MeOrder;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// A new engine, or NULL if its runtime could not be started. Free it with
+// `engine_destroy`.
+MeEngine *engine_create(void);
+
+// # Safety
+//
+// `engine` must come from `engine_create` and not be used afterwards.
+void engine_destroy(MeEngine *engine);
+
+// # Safety
+//
+// `engine` must come from `engine_create`; `order` must point to an
+// `MeOrder` whose strings are NUL-terminated or NULL.
+int32_t engine_place_order(MeEngine *engine, const MeOrder *order);
+
+// # Safety
+//
+// `engine` must come from `engine_create`; `order_id` and `user_id` must
+// point to 16 bytes each and `symbol` to a NUL-terminated string.
+int32_t engine_cancel_order(MeEngine *engine,
+                            const uint8_t *order_id,
+                            const uint8_t *user_id,
+                            const char *symbol);
+
+// Writes the events of the commands run since the last poll to `buffer`
+// as a NUL-terminated JSON array, oldest first, and forgets them. When
+// `buffer` is too small they are kept; `length` gets the size needed
+// either way.
+//
+// # Safety
+//
+// `engine` must come from `engine_create`; `buffer` must have room for
+// `capacity` bytes and `length` may be NULL.
+int32_t engine_poll_events(MeEngine *engine, char *buffer, size_t capacity, size_t *length);
+
+// Writes why the last failing call on `engine` failed, NUL-terminated.
+//
+// # Safety
+//
+// As for `engine_poll_events`.
+int32_t engine_last_error(MeEngine *engine, char *buffer, size_t capacity, size_t *length);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* MATCHING_ENGINE_H */
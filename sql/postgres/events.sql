@@ -0,0 +1,34 @@
+-- Event store schema of PostgresEventStore, which creates it on connecting.
+-- One row per event; a batch from save_events goes in a single
+-- transaction.
+CREATE TABLE IF NOT EXISTS order_events (
+    -- Assigned by the store from 1 with no gaps, so a sequence less one is
+    -- the event's offset.
+    sequence   BIGINT PRIMARY KEY,
+    -- Saves skip events whose id is already stored, so a retried batch is
+    -- written once. Events without an id have none here.
+    event_id   UUID        UNIQUE,
+    -- Hash of the event before, assigned at save time; the payload holds
+    -- it too, this column is for verify_integrity.
+    prev_hash  TEXT,
+    order_id   UUID,
+    symbol     TEXT,
+    kind       TEXT        NOT NULL,
+    timestamp  TIMESTAMPTZ NOT NULL,
+    payload    JSONB       NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS order_events_order_id ON order_events (order_id, sequence)
+    WHERE order_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS order_events_symbol ON order_events (symbol, sequence)
+    WHERE symbol IS NOT NULL;
+CREATE INDEX IF NOT EXISTS order_events_kind ON order_events (kind, sequence);
+CREATE INDEX IF NOT EXISTS order_events_timestamp ON order_events (timestamp);
+
+-- Every user an event concerns, both sides of a match included, for
+-- get_events_by_user.
+CREATE TABLE IF NOT EXISTS order_event_users (
+    user_id    UUID   NOT NULL,
+    sequence   BIGINT NOT NULL REFERENCES order_events (sequence),
+    PRIMARY KEY (user_id, sequence)
+);
@@ -0,0 +1,303 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::commands::PlaceOrderCommand;
+use crate::engine::MatchingEngine;
+use crate::error::RejectReason;
+use crate::types::{Order, OrderSide, Trade};
+
+/// A user's holding of one asset. What open orders have reserved is held
+/// and cannot be withdrawn or reserved again.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Balance {
+    pub available: Decimal,
+    pub held: Decimal,
+}
+
+impl Balance {
+    pub fn total(&self) -> Decimal {
+        self.available + self.held
+    }
+}
+
+/// What one open order has reserved.
+#[derive(Debug)]
+struct Hold {
+    user_id: Uuid,
+    asset: String,
+    amount: Decimal,
+}
+
+#[derive(Debug, Default)]
+struct Ledger {
+    balances: HashMap<(Uuid, String), Balance>,
+    holds: HashMap<Uuid, Hold>,
+}
+
+impl Ledger {
+    fn balance(&mut self, user_id: Uuid, asset: &str) -> &mut Balance {
+        self.balances.entry((user_id, asset.to_string())).or_default()
+    }
+
+    fn held_by(&self, order_id: Uuid) -> Decimal {
+        self.holds.get(&order_id).map_or(Decimal::ZERO, |hold| hold.amount)
+    }
+
+    /// Moves funds between the user's available balance and what the order
+    /// holds until it holds `target`, or all that is available.
+    fn set_hold(&mut self, order_id: Uuid, user_id: Uuid, asset: &str, target: Decimal) {
+        let current = self.held_by(order_id);
+        let balance = self.balance(user_id, asset);
+        let change = (target - current).min(balance.available.max(Decimal::ZERO));
+        balance.available -= change;
+        balance.held += change;
+        let amount = current + change;
+        if amount.is_zero() {
+            self.holds.remove(&order_id);
+        } else {
+            let asset = asset.to_string();
+            self.holds.insert(order_id, Hold { user_id, asset, amount });
+        }
+    }
+
+    fn release(&mut self, order_id: Uuid) {
+        if let Some(hold) = self.holds.remove(&order_id) {
+            let balance = self.balance(hold.user_id, &hold.asset);
+            balance.held -= hold.amount;
+            balance.available += hold.amount;
+        }
+    }
+
+    /// Takes `amount` out of what the order holds, then out of the user's
+    /// available balance for whatever the hold does not cover.
+    fn pay(&mut self, order_id: Uuid, user_id: Uuid, asset: &str, amount: Decimal) {
+        let from_hold = match self.holds.get_mut(&order_id) {
+            Some(hold) if hold.asset == asset => {
+                let taken = hold.amount.min(amount);
+                hold.amount -= taken;
+                taken
+            }
+            _ => Decimal::ZERO,
+        };
+        let balance = self.balance(user_id, asset);
+        balance.held -= from_hold;
+        balance.available -= amount - from_hold;
+    }
+}
+
+/// Per-user balances of each asset and what open orders hold of them. In
+/// memory only: snapshots and replays do not carry them.
+#[derive(Debug, Default)]
+pub(crate) struct Accounts {
+    ledger: Mutex<Ledger>,
+}
+
+impl Accounts {
+    fn ledger(&self) -> std::sync::MutexGuard<'_, Ledger> {
+        self.ledger.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// The base and quote assets of a `BASE/QUOTE` symbol.
+pub(crate) fn symbol_assets(symbol: &str) -> Result<(&str, &str), RejectReason> {
+    symbol
+        .split_once('/')
+        .filter(|(base, quote)| !base.is_empty() && !quote.is_empty())
+        .ok_or_else(|| RejectReason::SymbolWithoutAssets(symbol.to_string()))
+}
+
+impl MatchingEngine {
+    /// Keeps a balance per user and asset, symbols being named
+    /// `BASE/QUOTE`. Orders hold what they could spend, the quote asset for
+    /// buys and the base asset for sells, and are rejected with
+    /// `InsufficientBalance` when it is not available. Trades settle both
+    /// sides as they execute; cancels release what was held. Stop-loss and
+    /// take-profit children of a bracket hold nothing, and synthetic pairs
+    /// are not accepted.
+    pub fn with_accounts(mut self) -> Self {
+        self.accounts = Some(Accounts::default());
+        self
+    }
+
+    fn accounts(&self) -> Result<&Accounts, String> {
+        self.accounts.as_ref().ok_or_else(|| "Accounts are not enabled".to_string())
+    }
+
+    pub fn deposit(&self, user_id: Uuid, asset: &str, amount: Decimal) -> Result<Balance, String> {
+        if amount <= Decimal::ZERO {
+            return Err(format!("Deposit amount must be positive, got {}", amount));
+        }
+        let mut ledger = self.accounts()?.ledger();
+        let balance = ledger.balance(user_id, asset);
+        balance.available += amount;
+        Ok(*balance)
+    }
+
+    pub fn withdraw(&self, user_id: Uuid, asset: &str, amount: Decimal) -> Result<Balance, String> {
+        if amount <= Decimal::ZERO {
+            return Err(format!("Withdrawal amount must be positive, got {}", amount));
+        }
+        let mut ledger = self.accounts()?.ledger();
+        let balance = ledger.balance(user_id, asset);
+        if balance.available < amount {
+            return Err(RejectReason::InsufficientBalance {
+                asset: asset.to_string(),
+                required: amount,
+                available: balance.available,
+            }
+            .to_string());
+        }
+        balance.available -= amount;
+        Ok(*balance)
+    }
+
+    pub fn get_balance(&self, user_id: Uuid, asset: &str) -> Result<Balance, String> {
+        let ledger = self.accounts()?.ledger();
+        Ok(ledger.balances.get(&(user_id, asset.to_string())).copied().unwrap_or_default())
+    }
+
+    /// Every asset the user has held, by name.
+    pub fn get_balances(&self, user_id: Uuid) -> Result<BTreeMap<String, Balance>, String> {
+        let ledger = self.accounts()?.ledger();
+        Ok(ledger
+            .balances
+            .iter()
+            .filter(|((owner, _), _)| *owner == user_id)
+            .map(|((_, asset), balance)| (asset.clone(), *balance))
+            .collect())
+    }
+
+    /// The asset `order` holds and how much of it, for what is left of it.
+    /// Unpriced buys hold what sweeping the book for them costs now.
+    fn required_hold(&self, order: &Order) -> Result<(String, Decimal), RejectReason> {
+        let (base, quote) = symbol_assets(&order.symbol)?;
+        let remaining = order.remaining_quantity();
+        let exempt = !order.is_open() || self.is_bracket_exit(order.id);
+        let amount = match order.side {
+            _ if exempt => Decimal::ZERO,
+            OrderSide::Sell => remaining,
+            OrderSide::Buy => match order.price.or(order.stop_price).or(order.trailing_stop_price) {
+                Some(price) => price.checked_mul(remaining).unwrap_or(Decimal::MAX),
+                None => self
+                    .cost_to_fill(&order.symbol, OrderSide::Buy, remaining)
+                    .map_or(Decimal::MAX, |estimate| estimate.notional),
+            },
+        };
+        let asset = match order.side {
+            OrderSide::Buy => quote,
+            OrderSide::Sell => base,
+        };
+        Ok((asset.to_string(), amount))
+    }
+
+    /// Rejects `order` unless its user has what it must hold available,
+    /// counting `headroom` as available too: funds the same command frees
+    /// first or, when negative, holds for its other orders.
+    pub(crate) fn check_order_funds(&self, order: &Order, headroom: Decimal) -> Result<(), RejectReason> {
+        let Some(accounts) = &self.accounts else {
+            return Ok(());
+        };
+        if self.get_synthetic_pair(&order.symbol).is_some() {
+            return Ok(());
+        }
+        let (asset, required) = self.required_hold(order)?;
+        let available = accounts.ledger().balance(order.user_id, &asset).available + headroom;
+        if required > available {
+            return Err(RejectReason::InsufficientBalance { asset, required, available });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn check_funds(&self, cmd: &PlaceOrderCommand, headroom: Decimal) -> Result<(), RejectReason> {
+        self.check_order_funds(&Self::order_from_command(cmd), headroom)
+    }
+
+    /// What `cmd` would hold of the asset `other` holds, if they belong to
+    /// the same user.
+    pub(crate) fn shared_funds(&self, cmd: &PlaceOrderCommand, other: &PlaceOrderCommand) -> Decimal {
+        if self.accounts.is_none() || cmd.user_id != other.user_id {
+            return Decimal::ZERO;
+        }
+        let hold = |cmd| self.required_hold(&Self::order_from_command(cmd)).ok();
+        match (hold(cmd), hold(other)) {
+            (Some((asset, amount)), Some((other_asset, _))) if asset == other_asset => amount,
+            _ => Decimal::ZERO,
+        }
+    }
+
+    /// What the order currently holds.
+    pub(crate) fn held_by(&self, order_id: Uuid) -> Decimal {
+        self.accounts
+            .as_ref()
+            .map_or(Decimal::ZERO, |accounts| accounts.ledger().held_by(order_id))
+    }
+
+    /// Brings what `order` holds in line with what is left of it, releasing
+    /// everything once it is closed.
+    pub(crate) fn sync_hold(&self, order: &Order) {
+        let Some(accounts) = &self.accounts else {
+            return;
+        };
+        if let Ok((asset, target)) = self.required_hold(order) {
+            accounts.ledger().set_hold(order.id, order.user_id, &asset, target);
+        }
+    }
+
+    pub(crate) fn release_hold(&self, order_id: Uuid) {
+        if let Some(accounts) = &self.accounts {
+            accounts.ledger().release(order_id);
+        }
+    }
+
+    /// The most an unpriced buy may spend: what it holds.
+    pub(crate) fn match_budget(&self, order: &Order) -> Option<Decimal> {
+        let holds = self.accounts.is_some() && !self.is_bracket_exit(order.id);
+        (holds && order.side == OrderSide::Buy && order.price.is_none()).then(|| self.held_by(order.id))
+    }
+
+    /// Moves the trade's notional from buyer to seller and its quantity
+    /// the other way, each paying out of what their order holds first.
+    pub(crate) fn settle_trade(&self, trade: &Trade, taker_user_id: Uuid, maker_user_id: Option<Uuid>) {
+        let (Some(accounts), Some(maker_user_id)) = (&self.accounts, maker_user_id) else {
+            return;
+        };
+        let Ok((base, quote)) = symbol_assets(&trade.symbol) else {
+            return;
+        };
+        let notional = trade.price * trade.quantity;
+        let ((buy_order, buyer), (sell_order, seller)) = match trade.side {
+            OrderSide::Buy => ((trade.taker_order_id, taker_user_id), (trade.maker_order_id, maker_user_id)),
+            OrderSide::Sell => ((trade.maker_order_id, maker_user_id), (trade.taker_order_id, taker_user_id)),
+        };
+        let mut ledger = accounts.ledger();
+        ledger.pay(buy_order, buyer, quote, notional);
+        ledger.balance(buyer, base).available += trade.quantity;
+        ledger.pay(sell_order, seller, base, trade.quantity);
+        ledger.balance(seller, quote).available += notional;
+    }
+
+    /// Takes back what `settle_trade` moved, into available balances; the
+    /// holds are restored with the orders.
+    pub(crate) fn unsettle_trade(&self, trade: &Trade, taker_user_id: Uuid, maker_user_id: Option<Uuid>) {
+        let (Some(accounts), Some(maker_user_id)) = (&self.accounts, maker_user_id) else {
+            return;
+        };
+        let Ok((base, quote)) = symbol_assets(&trade.symbol) else {
+            return;
+        };
+        let (buyer, seller) = match trade.side {
+            OrderSide::Buy => (taker_user_id, maker_user_id),
+            OrderSide::Sell => (maker_user_id, taker_user_id),
+        };
+        let notional = trade.price * trade.quantity;
+        let mut ledger = accounts.ledger();
+        ledger.balance(buyer, base).available -= trade.quantity;
+        ledger.balance(buyer, quote).available += notional;
+        ledger.balance(seller, base).available += trade.quantity;
+        ledger.balance(seller, quote).available -= notional;
+    }
+}
@@ -0,0 +1,150 @@
+use rust_decimal::Decimal;
+
+use crate::commands::{AmendOrderCommand, OrderCommand};
+use crate::draws::new_event_id;
+use crate::engine::MatchingEngine;
+use crate::error::EngineError;
+use crate::events::{OrderEvent, OrderUpdatedEvent};
+use crate::precision::checked_notional;
+use crate::trading_state::Admission;
+use crate::types::{Order, OrderStatus};
+
+/// The new terms of an amended order; None leaves a term unchanged.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Amendment {
+    pub price: Option<Decimal>,
+    pub quantity: Option<Decimal>,
+}
+
+impl Amendment {
+    pub fn changes_price(&self, order: &Order) -> bool {
+        self.price.is_some_and(|p| Some(p) != order.price)
+    }
+
+    pub fn increases_quantity(&self, order: &Order) -> bool {
+        self.quantity.is_some_and(|q| q > order.quantity)
+    }
+}
+
+/// Decides whether an amended order keeps its place in the queue.
+pub trait PriorityPolicy: Send + Sync {
+    fn retains_priority(&self, order: &Order, amendment: &Amendment) -> bool;
+}
+
+/// The common venue rule: only reducing the quantity keeps priority; a
+/// price change or a quantity increase goes to the back of the queue.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StandardPriorityPolicy;
+
+impl PriorityPolicy for StandardPriorityPolicy {
+    fn retains_priority(&self, order: &Order, amendment: &Amendment) -> bool {
+        !amendment.changes_price(order) && !amendment.increases_quantity(order)
+    }
+}
+
+impl MatchingEngine {
+    pub fn with_priority_policy(mut self, policy: impl PriorityPolicy + 'static) -> Self {
+        self.priority_policy = Box::new(policy);
+        self
+    }
+
+    /// Changes the price and/or total quantity of a resting limit order in
+    /// place. Whether it keeps its queue position is up to the priority
+    /// policy, except that a new price always means a new level, where the
+    /// order is matched first if it now crosses.
+    pub async fn handle_amend_order(&self, cmd: AmendOrderCommand) -> Result<Vec<OrderEvent>, EngineError> {
+        let order_id = self.resolve_order_ref(cmd.user_id, cmd.client_order_id.as_deref(), cmd.order_id)?;
+        let order = self
+            .get_order(order_id)
+            .ok_or_else(|| format!("Order {} not found", order_id))?;
+        if order.user_id != cmd.user_id || order.symbol != cmd.symbol {
+            return Err(format!("Order {} does not belong to this user and symbol", order_id).into());
+        }
+        let Some(old_price) = order.price.filter(|_| order.is_open() && order.status != OrderStatus::Pending) else {
+            return Err(format!("Order {} is not a resting limit order", order_id).into());
+        };
+        let amendment = Amendment {
+            price: cmd.new_price.map(|p| self.normalize_limit_price(&cmd.symbol, order.side, p)),
+            quantity: cmd
+                .new_quantity
+                .map(|q| self.normalize_quantity(&cmd.symbol, q))
+                .transpose()?,
+        };
+        self.check_price_ladder(&cmd.symbol, amendment.price)?;
+        self.check_price_band(&cmd.symbol, amendment.price)?;
+        if let Some(quantity) = amendment.quantity {
+            if quantity <= order.filled_quantity {
+                return Err(format!(
+                    "New quantity {} does not exceed the {} already filled",
+                    quantity, order.filled_quantity
+                ).into());
+            }
+        }
+        checked_notional(
+            &cmd.symbol,
+            amendment.price.unwrap_or(old_price),
+            amendment.quantity.unwrap_or(order.quantity),
+        )?;
+        self.check_order_size(
+            &cmd.symbol,
+            Some(amendment.price.unwrap_or(old_price)),
+            amendment.quantity.unwrap_or(order.quantity),
+        )?;
+        let terms = Order {
+            price: amendment.price.or(order.price),
+            quantity: amendment.quantity.unwrap_or(order.quantity),
+            ..order.clone()
+        };
+        self.check_order_funds(&terms, self.held_by(order_id))?;
+        self.check_exposure(order.user_id, &order.symbol, order.side, terms.remaining_quantity(), Some(order_id))?;
+        let remaining = terms.remaining_quantity();
+        self.check_margin(order.user_id, &order.symbol, order.side, remaining, terms.price, Some(order_id))?;
+        self.check_credit(order.user_id, self.order_credit(&terms), Some(order_id))?;
+
+        let (admission, mut events) = self
+            .admit_command(&cmd.symbol, false, || OrderCommand::AmendOrder(cmd.clone()))
+            .await?;
+        if let Admission::Queued = admission {
+            return Ok(events);
+        }
+        let resumed = self.check_persistence_halt(&cmd.symbol).await?;
+
+        let retains_priority = !amendment.changes_price(&order)
+            && self.priority_policy.retains_priority(&order, &amendment);
+        let mut amended = order.clone();
+        amended.price = amendment.price.or(order.price);
+        amended.quantity = amendment.quantity.unwrap_or(order.quantity);
+        amended.updated_at = self.clock.now();
+        events.push(OrderEvent::OrderUpdated(OrderUpdatedEvent {
+            event_id: new_event_id(),
+            prev_hash: None,
+            order_id,
+            user_id: order.user_id,
+            symbol: order.symbol.clone(),
+            new_price: amendment.price,
+            new_quantity: amendment.quantity,
+            timestamp: amended.updated_at,
+            retained_priority: retains_priority,
+        }));
+
+        if retains_priority {
+            if let Some(mut book) = self.order_books.get_mut(&order.symbol) {
+                book.side_mut(order.side).update(&amended);
+                self.publish_book_delta(book.side(order.side), &amended);
+            }
+            self.sync_hold(&amended);
+            self.orders.insert(order_id, amended);
+        } else {
+            if let Some(mut book) = self.order_books.get_mut(&order.symbol) {
+                if let Some(removed) = book.remove(order_id) {
+                    self.publish_book_delta(book.side(removed.side), &removed);
+                }
+            }
+            self.execute_order(amended, &mut events);
+        }
+
+        self.persist_events(&mut events).await?;
+
+        Ok(resumed.into_iter().chain(events).collect())
+    }
+}
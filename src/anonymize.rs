@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+
+use chrono::{DateTime, Duration, Utc};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::commands::OrderCommand;
+use crate::event_store::{chain_events, event_hash};
+use crate::events::OrderEvent;
+
+/// Fields of commands and events whose values say nothing about who
+/// traded: prices, quantities, symbols and the states and kinds of things.
+const KEEP_FIELDS: &[&str] = &[
+    "amount", "asset", "best_ask", "best_bid", "buffered_events", "cap", "close", "displayed", "equity",
+    "execution", "filled_quantity", "flushed_events", "high", "iceberg_visible_quantity", "imbalance", "limit",
+    "liquidity", "low", "maintenance_margin", "max_fills", "max_orders_per_second", "move_percent", "nanos",
+    "new_price", "new_quantity", "open", "order_type", "previous_state", "price", "quantity", "quote_quantity",
+    "reference_price", "referral_amount", "remaining_quantity", "retained_priority", "scope", "secs", "side",
+    "state", "status", "stop_loss_price", "stop_price", "symbol", "Symbol", "symbols", "take_profit_price",
+    "trade_count", "trailing_stop_price", "trigger_price", "validation", "value", "volume",
+];
+
+/// Times other than `timestamp` and `*_at` fields.
+const TIME_FIELDS: &[&str] = &["halted_until", "open_time", "session_start"];
+
+/// Rewrites serialized commands and events so they can leave production.
+///
+/// Works on the JSON form by field name: user accounts, in `*user_id` and
+/// `referrer_id` fields and in `User` variants such as kill switch
+/// targets, are always pseudonymized, other `id`/`*_id`/`*_ids` UUIDs are
+/// remapped when `remap_ids` is set, times are shifted by `time_shift` and
+/// free-form `token_fields` such as client order ids are replaced by opaque
+/// tokens. Any other value is blanked unless its field is in `keep_fields`,
+/// so a field added later stays out of the output until it is listed.
+/// Every UUID and token maps to the same pseudonym for the lifetime of the
+/// anonymizer, so order linkage and relative timing survive.
+///
+/// Hash chains are rebuilt over the anonymized events, as the original
+/// hashes would tie them back to the production log.
+pub struct Anonymizer {
+    pub remap_ids: bool,
+    pub time_shift: Duration,
+    pub keep_fields: Vec<String>,
+    pub token_fields: Vec<String>,
+    pseudonyms: HashMap<Uuid, Uuid>,
+    tokens: HashMap<String, String>,
+    /// Hash of the last anonymized event.
+    chain_head: Option<String>,
+    rng: StdRng,
+}
+
+impl Default for Anonymizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Anonymizer {
+    pub fn new() -> Self {
+        Self::with_rng(StdRng::from_os_rng())
+    }
+
+    /// Reproducible pseudonyms: the same seed and input give the same output.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(rng: StdRng) -> Self {
+        Self {
+            remap_ids: true,
+            time_shift: Duration::zero(),
+            keep_fields: KEEP_FIELDS.iter().map(|field| field.to_string()).collect(),
+            token_fields: ["client_order_id", "new_client_order_id", "Firm"].map(String::from).to_vec(),
+            pseudonyms: HashMap::new(),
+            tokens: HashMap::new(),
+            chain_head: None,
+            rng,
+        }
+    }
+
+    pub fn anonymize_command(&mut self, command: &OrderCommand) -> Result<OrderCommand, String> {
+        self.anonymize(command)
+    }
+
+    /// Anonymizes the next event of a log. An event that carried the hash
+    /// of the one before it carries the hash of the anonymized one instead.
+    pub fn anonymize_event(&mut self, event: &OrderEvent) -> Result<OrderEvent, String> {
+        let mut anonymized = self.anonymize(event)?;
+        if event.prev_hash().is_some() {
+            chain_events(std::slice::from_mut(&mut anonymized), &mut self.chain_head);
+        } else {
+            self.chain_head = Some(event_hash(&anonymized));
+        }
+        Ok(anonymized)
+    }
+
+    fn anonymize<T: Serialize + DeserializeOwned>(&mut self, item: &T) -> Result<T, String> {
+        let mut value = serde_json::to_value(item).map_err(|e| e.to_string())?;
+        self.rewrite_value(&mut value);
+        serde_json::from_value(value).map_err(|e| e.to_string())
+    }
+
+    /// Anonymizes a JSON Lines log of commands or events, line by line.
+    /// Returns the number of records written.
+    pub fn anonymize_jsonl(&mut self, input: impl BufRead, mut output: impl Write) -> Result<usize, String> {
+        let mut written = 0;
+        for (number, line) in input.lines().enumerate() {
+            let line = line.map_err(|e| e.to_string())?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let mut value: Value = serde_json::from_str(&line)
+                .map_err(|e| format!("Line {}: {}", number + 1, e))?;
+            match serde_json::from_value::<OrderEvent>(value.clone()) {
+                Ok(event) => value = serde_json::to_value(self.anonymize_event(&event)?).map_err(|e| e.to_string())?,
+                Err(_) => self.rewrite_value(&mut value),
+            }
+            serde_json::to_writer(&mut output, &value).map_err(|e| e.to_string())?;
+            output.write_all(b"\n").map_err(|e| e.to_string())?;
+            written += 1;
+        }
+        Ok(written)
+    }
+
+    /// Rewrites the fields of `value`. Hashes are blanked rather than
+    /// relinked, as only `anonymize_event` knows the order of a log.
+    pub fn rewrite_value(&mut self, value: &mut Value) {
+        match value {
+            Value::Object(fields) => {
+                for (key, field) in fields.iter_mut() {
+                    self.rewrite_field(key, field);
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.rewrite_value(item)),
+            _ => {}
+        }
+    }
+
+    fn rewrite_field(&mut self, key: &str, field: &mut Value) {
+        match field {
+            Value::Object(_) => return self.rewrite_value(field),
+            Value::Array(items) => return items.iter_mut().for_each(|item| self.rewrite_field(key, item)),
+            _ => {}
+        }
+
+        if self.token_fields.iter().any(|f| f == key) {
+            if let Value::String(text) = field {
+                *text = self.token(text);
+            }
+            return;
+        }
+
+        let is_user = key.ends_with("user_id") || key == "referrer_id" || key == "User";
+        let is_id = key == "id" || key.ends_with("_id") || key.ends_with("_ids");
+        if is_user || is_id {
+            if is_user || self.remap_ids {
+                self.rewrite_ids(field);
+            }
+            return;
+        }
+
+        if key == "timestamp" || key.ends_with("_at") || TIME_FIELDS.contains(&key) {
+            if let Value::String(text) = field {
+                if let Ok(time) = text.parse::<DateTime<Utc>>() {
+                    *text = (time + self.time_shift).to_rfc3339();
+                }
+            }
+            return;
+        }
+
+        if !self.keep_fields.iter().any(|f| f == key) {
+            *field = match field {
+                Value::String(_) => Value::String(String::new()),
+                _ => Value::Null,
+            };
+        }
+    }
+
+    fn rewrite_ids(&mut self, field: &mut Value) {
+        if let Value::String(text) = field {
+            if let Ok(id) = text.parse::<Uuid>() {
+                *text = self.pseudonym(id).to_string();
+            }
+        }
+    }
+
+    fn token(&mut self, value: &str) -> String {
+        if let Some(token) = self.tokens.get(value) {
+            return token.clone();
+        }
+        let token = format!("anon-{:016x}", self.rng.random::<u64>());
+        self.tokens.insert(value.to_string(), token.clone());
+        token
+    }
+
+    fn pseudonym(&mut self, id: Uuid) -> Uuid {
+        if id.is_nil() {
+            return id;
+        }
+        let rng = &mut self.rng;
+        *self
+            .pseudonyms
+            .entry(id)
+            .or_insert_with(|| uuid::Builder::from_random_bytes(rng.random()).into_uuid())
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+
+use crate::file_store::segment_file;
+
+const MANIFEST_FILE: &str = "archived";
+
+/// Somewhere closed event log segments can be moved to, keyed by name.
+/// With the `s3` feature, `S3ObjectStore` archives to a bucket.
+#[async_trait]
+pub trait ObjectStore: Send + Sync {
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<(), String>;
+    /// None if nothing is stored under `key`.
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, String>;
+}
+
+#[derive(Debug, Default)]
+pub struct InMemoryObjectStore {
+    objects: Mutex<HashMap<String, Vec<u8>>>,
+}
+
+impl InMemoryObjectStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ObjectStore for InMemoryObjectStore {
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<(), String> {
+        self.objects.lock().unwrap().insert(key.to_string(), bytes);
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        Ok(self.objects.lock().unwrap().get(key).cloned())
+    }
+}
+
+/// Objects as files in a directory, for archives on a mounted volume.
+pub struct DirObjectStore {
+    dir: PathBuf,
+}
+
+impl DirObjectStore {
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, String> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create archive {}: {}", dir.display(), e))?;
+        Ok(Self { dir })
+    }
+}
+
+#[async_trait]
+impl ObjectStore for DirObjectStore {
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<(), String> {
+        let staged = self.dir.join(format!("{}.tmp", key));
+        let mut file = File::create(&staged).map_err(|e| e.to_string())?;
+        file.write_all(&bytes).and_then(|_| file.sync_data()).map_err(|e| e.to_string())?;
+        fs::rename(&staged, self.dir.join(key)).map_err(|e| e.to_string())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        match fs::read(self.dir.join(key)) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+/// A file event store's archived segments and where they went. The
+/// manifest in the log directory lists them, so reopening knows to fetch
+/// them rather than treat them as lost.
+pub(crate) struct SegmentArchive {
+    store: Box<dyn ObjectStore>,
+    dir: PathBuf,
+    /// Closed segments kept on local disk as well.
+    keep_local: u64,
+    archived: Mutex<BTreeSet<u64>>,
+    /// One archiving pass at a time.
+    running: tokio::sync::Mutex<()>,
+}
+
+impl SegmentArchive {
+    pub(crate) fn new(store: Box<dyn ObjectStore>, dir: &Path, keep_local: usize) -> Result<Self, String> {
+        Ok(Self {
+            store,
+            dir: dir.to_path_buf(),
+            keep_local: keep_local as u64,
+            archived: Mutex::new(read_manifest(dir)?),
+            running: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    pub(crate) fn archived(&self) -> BTreeSet<u64> {
+        self.archived.lock().unwrap().clone()
+    }
+
+    pub(crate) async fn fetch(&self, segment: u64) -> Result<Vec<u8>, String> {
+        self.store
+            .get(&segment_file(segment))
+            .await?
+            .ok_or_else(|| format!("Archived event log segment {} is missing", segment))
+    }
+
+    /// Archives the closed segments before `current` that are not among
+    /// the last `keep_local`, deleting the local copies once the manifest
+    /// lists them. Returns how many were archived.
+    pub(crate) async fn archive_before(&self, current: u64) -> Result<usize, String> {
+        let _running = self.running.lock().await;
+        let mut archived = 0;
+        for segment in 0..current.saturating_sub(self.keep_local) {
+            if self.archived.lock().unwrap().contains(&segment) {
+                continue;
+            }
+            let path = self.dir.join(segment_file(segment));
+            let bytes = match fs::read(&path) {
+                Ok(bytes) => bytes,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.to_string()),
+            };
+            self.store.put(&segment_file(segment), bytes).await?;
+            let manifest = {
+                let mut listed = self.archived.lock().unwrap();
+                listed.insert(segment);
+                listed.clone()
+            };
+            write_manifest(&self.dir, &manifest)?;
+            fs::remove_file(&path).map_err(|e| e.to_string())?;
+            archived += 1;
+        }
+        Ok(archived)
+    }
+}
+
+pub(crate) fn read_manifest(dir: &Path) -> Result<BTreeSet<u64>, String> {
+    match fs::read_to_string(dir.join(MANIFEST_FILE)) {
+        Ok(listed) => listed
+            .lines()
+            .map(|line| line.trim().parse().map_err(|_| format!("Corrupt {}", MANIFEST_FILE)))
+            .collect(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeSet::new()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn write_manifest(dir: &Path, archived: &BTreeSet<u64>) -> Result<(), String> {
+    let staged = dir.join(format!("{}.tmp", MANIFEST_FILE));
+    let mut file = File::create(&staged).map_err(|e| e.to_string())?;
+    for segment in archived {
+        writeln!(file, "{}", segment).map_err(|e| e.to_string())?;
+    }
+    file.sync_data().map_err(|e| e.to_string())?;
+    fs::rename(&staged, dir.join(MANIFEST_FILE)).map_err(|e| e.to_string())
+}
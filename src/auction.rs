@@ -0,0 +1,172 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::draws::new_event_id;
+use crate::engine::MatchingEngine;
+use crate::events::{AuctionPriceDeterminedEvent, OrderEvent};
+use crate::trading_state::SymbolState;
+use crate::types::{OrderSide, OrderStatus, Trade};
+
+/// Outcome of an auction price calculation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuctionResult {
+    pub symbol: String,
+    pub price: Decimal,
+    pub volume: Decimal,
+    /// Bid quantity at or above `price` minus ask quantity at or below it.
+    pub imbalance: Decimal,
+}
+
+impl MatchingEngine {
+    /// Puts a symbol into auction: orders keep resting but nothing matches
+    /// until `run_auction` uncrosses the book.
+    pub async fn start_auction(&self, symbol: &str) -> Result<Vec<OrderEvent>, String> {
+        self.set_symbol_state(symbol, SymbolState::AuctionOnly).await
+    }
+
+    pub fn is_in_auction(&self, symbol: &str) -> bool {
+        self.symbol_state(symbol) == SymbolState::AuctionOnly
+    }
+
+    /// The price the auction would uncross at right now: the one with the
+    /// maximum executable volume, then the smallest imbalance, then the
+    /// highest (buy surplus) or lowest (sell surplus) price, then the one
+    /// closest to the last traded price. None if the book does not cross.
+    pub fn indicative_auction_price(&self, symbol: &str) -> Option<AuctionResult> {
+        let book = self.order_books.get(symbol)?;
+        let reference = self.last_prices.get(symbol).map(|p| *p);
+
+        let level_quantities = |side| -> Vec<(Decimal, Decimal)> {
+            book.side(side)
+                .iter_levels()
+                .map(|(price, level)| (price, level.total_quantity()))
+                .collect()
+        };
+        let bids = level_quantities(OrderSide::Buy);
+        let asks = level_quantities(OrderSide::Sell);
+        drop(book);
+
+        let mut best: Option<AuctionResult> = None;
+        for &(price, _) in bids.iter().chain(asks.iter()) {
+            let demand: Decimal = bids.iter().filter(|(p, _)| *p >= price).map(|(_, q)| *q).sum();
+            let supply: Decimal = asks.iter().filter(|(p, _)| *p <= price).map(|(_, q)| *q).sum();
+            let volume = demand.min(supply);
+            if volume == Decimal::ZERO {
+                continue;
+            }
+            let candidate = AuctionResult {
+                symbol: symbol.to_string(),
+                price,
+                volume,
+                imbalance: demand - supply,
+            };
+
+            let better = match &best {
+                None => true,
+                Some(current) => {
+                    let pressure = |r: &AuctionResult| match r.imbalance.cmp(&Decimal::ZERO) {
+                        std::cmp::Ordering::Greater => r.price,
+                        std::cmp::Ordering::Less => -r.price,
+                        std::cmp::Ordering::Equal => Decimal::ZERO,
+                    };
+                    let distance = |p: Decimal| reference.map(|r| (p - r).abs()).unwrap_or_default();
+                    let rank = |r: &AuctionResult| (r.volume, -r.imbalance.abs(), pressure(r), -distance(r.price));
+                    rank(&candidate) > rank(current)
+                }
+            };
+            if better {
+                best = Some(candidate);
+            }
+        }
+        best
+    }
+
+    /// Ends the auction on `symbol`, executing all crossing interest at the
+    /// single equilibrium price before continuous trading resumes.
+    pub async fn run_auction(&self, symbol: &str) -> Result<Vec<OrderEvent>, String> {
+        if !self.is_in_auction(symbol) {
+            return Err(format!("{} is not in auction", symbol));
+        }
+        let resumed = self.check_persistence_halt(symbol).await?;
+        let mut events = Vec::new();
+
+        let result = self.indicative_auction_price(symbol);
+
+        if let Some(result) = result {
+            events.push(OrderEvent::AuctionPriceDetermined(AuctionPriceDeterminedEvent {
+                event_id: new_event_id(),
+                prev_hash: None,
+                symbol: symbol.to_string(),
+                price: result.price,
+                volume: result.volume,
+                imbalance: result.imbalance,
+                timestamp: self.clock.now(),
+            }));
+
+            let trades = self.uncross(symbol, result.price, result.volume);
+            for triggered in self.process_trades(symbol, &trades, &mut events) {
+                self.execute_order(triggered, &mut events);
+            }
+        }
+        self.transition_symbol_state(symbol, SymbolState::Trading, &mut events);
+
+        self.persist_events(&mut events).await?;
+
+        Ok(resumed.into_iter().chain(events).collect())
+    }
+
+    /// Pairs the best bids with the best asks in time priority, all at
+    /// `price`. The later of the two orders is recorded as the taker.
+    fn uncross(&self, symbol: &str, price: Decimal, volume: Decimal) -> Vec<Trade> {
+        let mut trades = Vec::new();
+        let Some(mut book) = self.order_books.get_mut(symbol) else {
+            return trades;
+        };
+
+        let mut remaining = volume;
+        while remaining > Decimal::ZERO {
+            let front = |side| {
+                let best = book.best(side).filter(|best| match side {
+                    OrderSide::Buy => *best >= price,
+                    OrderSide::Sell => *best <= price,
+                })?;
+                book.side(side).level(best)?.peek_front().map(|o| o.id)
+            };
+            let (Some(bid_id), Some(ask_id)) = (front(OrderSide::Buy), front(OrderSide::Sell)) else {
+                break;
+            };
+            let (Some(bid), Some(ask)) = (self.get_order(bid_id), self.get_order(ask_id)) else {
+                break;
+            };
+
+            let quantity = remaining.min(bid.remaining_quantity()).min(ask.remaining_quantity());
+            remaining -= quantity;
+            let now = self.clock.now();
+            for (order_id, side) in [(bid_id, OrderSide::Buy), (ask_id, OrderSide::Sell)] {
+                if let Some(mut order) = self.orders.get_mut(&order_id) {
+                    order.filled_quantity += quantity;
+                    order.updated_at = now;
+                    if order.remaining_quantity() == Decimal::ZERO {
+                        order.status = OrderStatus::Filled;
+                        self.order_index.close(&order);
+                    } else {
+                        order.status = OrderStatus::PartiallyFilled;
+                    }
+                    book.side_mut(side).update(&order);
+                    self.publish_book_delta(book.side(side), &order);
+                }
+            }
+
+            let (taker, maker_id) = if bid.created_at > ask.created_at {
+                (bid, ask_id)
+            } else {
+                (ask, bid_id)
+            };
+            trades.push(self.create_trade(&taker, maker_id, price, quantity));
+            for order in [bid_id, ask_id].into_iter().filter_map(|order_id| self.get_order(order_id)) {
+                self.sync_hold(&order);
+            }
+        }
+        trades
+    }
+}
@@ -0,0 +1,42 @@
+//! Who a front end's client is. The servers take a bearer token from each
+//! client and act only for the user the token maps to.
+
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+/// Maps the bearer token a client presents to the user it acts for.
+pub trait Authenticator: Send + Sync {
+    /// The user `token` belongs to, or None if it is not a valid token.
+    fn authenticate(&self, token: &str) -> Option<Uuid>;
+}
+
+/// A fixed set of tokens, for tests and deployments that hand them out
+/// by other means.
+#[derive(Debug, Clone, Default)]
+pub struct StaticTokens {
+    tokens: HashMap<String, Uuid>,
+}
+
+impl StaticTokens {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_token(mut self, token: impl Into<String>, user_id: Uuid) -> Self {
+        self.tokens.insert(token.into(), user_id);
+        self
+    }
+}
+
+impl Authenticator for StaticTokens {
+    fn authenticate(&self, token: &str) -> Option<Uuid> {
+        self.tokens.get(token).copied()
+    }
+}
+
+/// The token in an `Authorization: Bearer <token>` header value.
+pub(crate) fn bearer_token(authorization: &str) -> Option<&str> {
+    let (scheme, token) = authorization.trim().split_once(' ')?;
+    scheme.eq_ignore_ascii_case("bearer").then(|| token.trim())
+}
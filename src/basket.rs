@@ -0,0 +1,114 @@
+use rust_decimal::Decimal;
+
+use crate::commands::{BasketExecution, BasketValidation, PlaceBasketCommand, PlaceOrderCommand};
+use crate::engine::MatchingEngine;
+use crate::error::{EngineError, LegRejection};
+use crate::events::OrderEvent;
+use crate::trading_state::SymbolState;
+use crate::types::{OrderStatus, OrderType};
+
+impl MatchingEngine {
+    /// Places the legs of a basket, each through the normal order path.
+    /// Legs are checked up front against everything that does not depend on
+    /// the other legs' executions; a leg that still fails once placed is
+    /// recorded as rejected in either validation mode.
+    pub async fn handle_place_basket(&self, cmd: PlaceBasketCommand) -> Result<Vec<OrderEvent>, EngineError> {
+        if cmd.legs.is_empty() {
+            return Err(format!("Basket {} has no legs", cmd.basket_id).into());
+        }
+
+        let mut events = Vec::new();
+        let mut accepted: Vec<PlaceOrderCommand> = Vec::new();
+        let mut rejected = Vec::new();
+        for mut leg in cmd.legs.clone() {
+            events.extend(self.lift_expired_circuit_breaker(&leg.symbol).await?);
+            let checked = match self.check_basket_leg(&mut leg, &accepted) {
+                Ok(()) => self.run_risk_checks(&leg, None).await.map_err(EngineError::from),
+                Err(error) => Err(error),
+            };
+            match checked {
+                Ok(()) => accepted.push(leg),
+                Err(error) => rejected.push((leg, error)),
+            }
+        }
+        if cmd.validation == BasketValidation::AllOrNothing && !rejected.is_empty() {
+            return Err(EngineError::BasketRejected {
+                basket_id: cmd.basket_id,
+                legs: rejected
+                    .into_iter()
+                    .map(|(leg, error)| LegRejection {
+                        order_id: leg.order_id,
+                        error,
+                    })
+                    .collect(),
+            });
+        }
+
+        let mut legs = accepted.into_iter();
+        while let Some(leg) = legs.next() {
+            let order_id = leg.order_id;
+            match self.handle_place_order(leg.clone()).await {
+                Ok(placed) => events.extend(placed),
+                Err(error) => {
+                    rejected.push((leg, error));
+                    continue;
+                }
+            }
+            let filled = self.get_order(order_id).is_some_and(|o| o.status == OrderStatus::Filled);
+            if cmd.execution == BasketExecution::Contingent && !filled {
+                let error = EngineError::Rejected(format!(
+                    "Basket {} stopped: leg {} did not fill",
+                    cmd.basket_id, order_id
+                ));
+                rejected.extend(legs.by_ref().map(|leg| (leg, error.clone())));
+            }
+        }
+
+        let mut rejections: Vec<OrderEvent> = rejected
+            .iter()
+            .map(|(leg, error)| self.order_rejected(leg.order_id, leg.user_id, &leg.symbol, error))
+            .collect();
+        if !rejections.is_empty() {
+            self.persist_events(&mut rejections).await?;
+            events.extend(rejections);
+        }
+
+        Ok(events)
+    }
+
+    /// What `place_order` would refuse before touching the book, with the
+    /// legs already accepted counting as placed.
+    fn check_basket_leg(&self, leg: &mut PlaceOrderCommand, accepted: &[PlaceOrderCommand]) -> Result<(), EngineError> {
+        self.normalize_order(leg)?;
+        self.validate_order(leg)?;
+        if self.orders.contains_key(&leg.order_id) || accepted.iter().any(|l| l.order_id == leg.order_id) {
+            return Err(EngineError::DuplicateOrderId(leg.order_id));
+        }
+        let pending = accepted.iter().filter(|l| l.user_id == leg.user_id).count();
+        self.check_open_order_limit(leg.user_id, pending + 1)?;
+        let committed: Decimal = accepted.iter().map(|l| self.shared_funds(l, leg)).sum();
+        self.check_funds(leg, -committed)?;
+        let same_side: Decimal = accepted
+            .iter()
+            .filter(|l| l.user_id == leg.user_id && l.symbol == leg.symbol && l.side == leg.side)
+            .map(|l| l.quantity)
+            .sum();
+        self.check_exposure(leg.user_id, &leg.symbol, leg.side, same_side + leg.quantity, None)?;
+        self.check_margin(leg.user_id, &leg.symbol, leg.side, same_side + leg.quantity, leg.price, None)?;
+        let pending_credit: Decimal = accepted
+            .iter()
+            .filter(|l| l.user_id == leg.user_id)
+            .map(|l| self.command_credit(l))
+            .sum();
+        self.check_credit(leg.user_id, pending_credit + self.command_credit(leg), None)?;
+
+        let state = self.symbol_state(&leg.symbol);
+        if !matches!(state, SymbolState::Trading | SymbolState::AuctionOnly) {
+            return Err(format!("{} is {:?}: command rejected", leg.symbol, state).into());
+        }
+        if leg.order_type == OrderType::Market && state == SymbolState::AuctionOnly {
+            return Err(format!("{} is in auction: market orders are not accepted", leg.symbol).into());
+        }
+        Ok(())
+    }
+}
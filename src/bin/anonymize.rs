@@ -0,0 +1,58 @@
+//! Pseudonymizes a JSON Lines command or event log.
+//!
+//! Usage: anonymize [INPUT] [--seed N] [--shift-seconds N] [--keep-ids]
+//!
+//! Reads from INPUT or stdin and writes the anonymized log to stdout.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+use chrono::Duration;
+use matching_engine::Anonymizer;
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("anonymize: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<(), String> {
+    let mut input: Option<String> = None;
+    let mut seed: Option<u64> = None;
+    let mut shift_seconds = 0i64;
+    let mut keep_ids = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => seed = Some(parse_value(&arg, args.next())?),
+            "--shift-seconds" => shift_seconds = parse_value(&arg, args.next())?,
+            "--keep-ids" => keep_ids = true,
+            _ if input.is_none() && !arg.starts_with("--") => input = Some(arg),
+            _ => return Err(format!("unexpected argument {}", arg)),
+        }
+    }
+
+    let mut anonymizer = match seed {
+        Some(seed) => Anonymizer::with_seed(seed),
+        None => Anonymizer::new(),
+    };
+    anonymizer.remap_ids = !keep_ids;
+    anonymizer.time_shift = Duration::seconds(shift_seconds);
+
+    let reader: Box<dyn BufRead> = match input {
+        Some(path) => Box::new(BufReader::new(
+            File::open(&path).map_err(|e| format!("{}: {}", path, e))?,
+        )),
+        None => Box::new(BufReader::new(io::stdin())),
+    };
+    anonymizer.anonymize_jsonl(reader, io::stdout().lock())?;
+    Ok(())
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    value
+        .and_then(|v| v.parse().ok())
+        .ok_or_else(|| format!("{} needs a numeric value", flag))
+}
@@ -0,0 +1,58 @@
+//! Runs a JSON Lines command log through a fresh engine.
+//!
+//! Usage: replay [INPUT] [--output FILE] [--trades | --all]
+//!
+//! Reads commands from INPUT or stdin and writes the events they produce,
+//! the trades with `--trades`, or both with `--all`, to FILE or stdout. A
+//! summary goes to stderr.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+
+use matching_engine::{InMemoryEventStore, MatchingEngine, ReplayOutput};
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("replay: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<(), String> {
+    let mut input: Option<String> = None;
+    let mut output: Option<String> = None;
+    let mut mode = ReplayOutput::Events;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--output" => output = Some(args.next().ok_or("--output needs a path")?),
+            "--trades" => mode = ReplayOutput::Trades,
+            "--all" => mode = ReplayOutput::EventsAndTrades,
+            _ if input.is_none() && !arg.starts_with("--") => input = Some(arg),
+            _ => return Err(format!("unexpected argument {}", arg)),
+        }
+    }
+
+    let reader: Box<dyn BufRead> = match input {
+        Some(path) => Box::new(BufReader::new(
+            File::open(&path).map_err(|e| format!("{}: {}", path, e))?,
+        )),
+        None => Box::new(BufReader::new(io::stdin())),
+    };
+    let writer: Box<dyn Write> = match output {
+        Some(path) => Box::new(BufWriter::new(
+            File::create(&path).map_err(|e| format!("{}: {}", path, e))?,
+        )),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+
+    let runtime = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let summary = runtime.block_on(engine.replay_jsonl(reader, writer, mode))?;
+    eprintln!(
+        "replay: {} commands, {} events, {} trades, {} refused",
+        summary.commands, summary.events, summary.trades, summary.errors
+    );
+    Ok(())
+}
@@ -0,0 +1,106 @@
+//! Runs synthetic order flow against an engine and reports how it held up.
+//!
+//! Usage: simulator [--orders N] [--rate R] [--seed N] [--symbol S] [--levels N] [--realtime]
+//!
+//! Submits N commands (100000 by default) arriving at R per second of
+//! simulated time, as fast as the engine takes them unless `--realtime`
+//! paces them to their arrival times. Prints throughput, latency and the
+//! final book.
+
+use std::time::{Duration, Instant};
+
+use matching_engine::{DepthLevel, FlowConfig, InMemoryEventStore, MatchingEngine, OrderEvent, OrderFlow};
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("simulator: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<(), String> {
+    let mut config = FlowConfig::default();
+    let mut orders = 100_000usize;
+    let mut seed: Option<u64> = None;
+    let mut levels = 5usize;
+    let mut realtime = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--orders" => orders = parse_value(&arg, args.next())?,
+            "--rate" => config.arrival_rate = parse_value(&arg, args.next())?,
+            "--seed" => seed = Some(parse_value(&arg, args.next())?),
+            "--symbol" => config.symbol = args.next().ok_or("--symbol needs a value")?,
+            "--levels" => levels = parse_value(&arg, args.next())?,
+            "--realtime" => realtime = true,
+            _ => return Err(format!("unexpected argument {}", arg)),
+        }
+    }
+    if config.arrival_rate.is_nan() || config.arrival_rate <= 0.0 {
+        return Err("--rate must be positive".to_string());
+    }
+
+    let symbol = config.symbol.clone();
+    let mut flow = match seed {
+        Some(seed) => OrderFlow::with_seed(config, seed),
+        None => OrderFlow::new(config),
+    };
+    let runtime = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+
+    let (mut refused, mut trades) = (0usize, 0usize);
+    let first_arrival = flow.now();
+    let started = Instant::now();
+    runtime.block_on(async {
+        for _ in 0..orders {
+            let command = flow.next_command();
+            if realtime {
+                // Timers are no finer than a millisecond; arrivals closer
+                // than that go out together.
+                let due = (flow.now() - first_arrival).to_std().unwrap_or_default();
+                let wait = due.saturating_sub(started.elapsed());
+                if wait >= Duration::from_millis(1) {
+                    tokio::time::sleep(wait).await;
+                }
+            }
+            match engine.handle_command(command).await {
+                Ok(events) => trades += events.iter().filter(|e| matches!(e, OrderEvent::OrderMatched(_))).count(),
+                Err(_) => refused += 1,
+            }
+        }
+    });
+    let elapsed = started.elapsed();
+    let metrics = engine.metrics_snapshot();
+
+    println!(
+        "{} commands in {:.2?} ({:.0}/s), {} refused, {} trades",
+        orders,
+        elapsed,
+        orders as f64 / elapsed.max(Duration::from_nanos(1)).as_secs_f64(),
+        refused,
+        trades
+    );
+    println!(
+        "latency p50 {}us p90 {}us p99 {}us max {}us",
+        metrics.latency_p50_us, metrics.latency_p90_us, metrics.latency_p99_us, metrics.latency_max_us
+    );
+    println!("reference price {}", flow.price());
+
+    let book = engine.get_l2_snapshot(&symbol, levels);
+    println!("{:>24} | {:<24}", "bids", "asks");
+    for i in 0..book.bids.len().max(book.asks.len()) {
+        let level = |levels: &[DepthLevel]| {
+            levels.get(i).map(|l| format!("{} @ {}", l.quantity, l.price)).unwrap_or_default()
+        };
+        println!("{:>24} | {:<24}", level(&book.bids), level(&book.asks));
+    }
+
+    engine.check_invariants(&symbol).map_err(|e| e.to_string())
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    value
+        .and_then(|v| v.parse().ok())
+        .ok_or_else(|| format!("{} needs a numeric value", flag))
+}
@@ -0,0 +1,316 @@
+use std::fmt::Debug;
+
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::footprint::MemoryFootprint;
+use crate::matching::crosses;
+use crate::orderbook::{LevelIter, OrderBookSide, OrderIter, SkipListOrderBook};
+use crate::sides::{Asks, Bids};
+use crate::types::{Order, OrderBookEntry, OrderSide};
+
+/// What sweeping the book for a quantity would fill and cost.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FillEstimate {
+    /// Less than asked for if the book runs out.
+    pub quantity: Decimal,
+    pub notional: Decimal,
+    /// None when nothing would fill.
+    pub average_price: Option<Decimal>,
+}
+
+/// Top-of-book figures over displayed liquidity, as a market maker sees the
+/// book. Prices are None when a side has nothing displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BookStats {
+    pub best_bid: Option<Decimal>,
+    pub best_ask: Option<Decimal>,
+    pub spread: Option<Decimal>,
+    pub midpoint: Option<Decimal>,
+    /// The midpoint weighted towards the thinner side of the top level.
+    pub microprice: Option<Decimal>,
+    /// Displayed quantity over the levels asked for.
+    pub bid_depth: Decimal,
+    pub ask_depth: Decimal,
+    /// (bid_depth - ask_depth) / (bid_depth + ask_depth), from -1 (all
+    /// asks) to 1 (all bids).
+    pub imbalance: Option<Decimal>,
+}
+
+/// Every resting order of a book, each side lowest price first and each
+/// level in queue order, so inserting them in sequence rebuilds the book
+/// with the same priorities.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookSnapshot {
+    pub bids: Vec<Order>,
+    pub asks: Vec<Order>,
+}
+
+/// A symbol's two-sided book. The engine only talks to books through this
+/// trait, so the data structure behind each side can be swapped by handing
+/// the builder a different `BookFactory`.
+pub trait OrderBookOps: Debug + Send + Sync {
+    fn side(&self, side: OrderSide) -> &dyn OrderBookSide;
+    fn side_mut(&mut self, side: OrderSide) -> &mut dyn OrderBookSide;
+
+    /// The best bid and ask if the bid has reached the ask.
+    fn crossed(&self) -> Option<(Decimal, Decimal)>;
+
+    fn add(&mut self, order: Order) {
+        self.side_mut(order.side).insert(order);
+    }
+
+    /// Takes a resting order out of whichever side holds it.
+    fn remove(&mut self, order_id: Uuid) -> Option<Order> {
+        self.side_mut(OrderSide::Buy)
+            .remove(order_id)
+            .or_else(|| self.side_mut(OrderSide::Sell).remove(order_id))
+    }
+
+    /// The highest bid or the lowest ask.
+    fn best(&self, side: OrderSide) -> Option<Decimal> {
+        match side {
+            OrderSide::Buy => self.side(side).highest_price(),
+            OrderSide::Sell => self.side(side).lowest_price(),
+        }
+    }
+
+    /// The best opposite price a taker on `side` can trade at, if it is
+    /// within `limit_price`. No limit crosses any price.
+    fn match_against(&self, side: OrderSide, limit_price: Option<Decimal>) -> Option<Decimal> {
+        let best = self.best(side.opposite())?;
+        crosses(side, limit_price, best).then_some(best)
+    }
+
+    /// Levels best first: bids descending, asks ascending.
+    fn iter_levels(&self, side: OrderSide) -> LevelIter<'_> {
+        match side {
+            OrderSide::Buy => Box::new(self.side(side).iter_levels().rev()),
+            OrderSide::Sell => self.side(side).iter_levels(),
+        }
+    }
+
+    /// Resting orders best level first, each level in priority order.
+    fn iter_orders(&self, side: OrderSide) -> OrderIter<'_> {
+        Box::new(self.iter_levels(side).flat_map(|(_, level)| level.iter()))
+    }
+
+    /// Quantity a taker on `side` could trade at `limit_price` or better,
+    /// hidden orders included.
+    fn volume_within(&self, side: OrderSide, limit_price: Decimal) -> Decimal {
+        self.iter_levels(side.opposite())
+            .take_while(|(price, _)| match side {
+                OrderSide::Buy => *price <= limit_price,
+                OrderSide::Sell => *price >= limit_price,
+            })
+            .map(|(_, level)| level.total_quantity())
+            .sum()
+    }
+
+    /// Walks the levels a taker on `side` would hit until `quantity` is
+    /// filled, without touching the book. None if the notional overflows.
+    fn cost_to_fill(&self, side: OrderSide, quantity: Decimal) -> Option<FillEstimate> {
+        let (mut filled, mut notional) = (Decimal::ZERO, Decimal::ZERO);
+        for (price, level) in self.iter_levels(side.opposite()) {
+            if filled >= quantity {
+                break;
+            }
+            let take = (quantity - filled).min(level.total_quantity());
+            filled += take;
+            notional = notional.checked_add(take.checked_mul(price)?)?;
+        }
+        Some(FillEstimate {
+            quantity: filled,
+            notional,
+            average_price: (filled > Decimal::ZERO).then(|| notional / filled),
+        })
+    }
+
+    /// Walks the levels a taker on `side` would hit until `budget` is
+    /// spent, without touching the book. What is taken at each level is
+    /// rounded down to `scale` decimal places.
+    fn fill_for_notional(&self, side: OrderSide, budget: Decimal, scale: u32) -> FillEstimate {
+        let (mut filled, mut notional) = (Decimal::ZERO, Decimal::ZERO);
+        for (price, level) in self.iter_levels(side.opposite()) {
+            let affordable = ((budget - notional) / price).round_dp_with_strategy(scale, RoundingStrategy::ToZero);
+            let take = affordable.min(level.total_quantity());
+            if take <= Decimal::ZERO {
+                break;
+            }
+            filled += take;
+            notional += take * price;
+        }
+        FillEstimate {
+            quantity: filled,
+            notional,
+            average_price: (filled > Decimal::ZERO).then(|| notional / filled),
+        }
+    }
+
+    /// Spread, midpoint, microprice and imbalance over the best `levels`
+    /// displayed levels of each side.
+    fn stats(&self, levels: usize) -> BookStats {
+        let bids = self.depth(OrderSide::Buy, levels);
+        let asks = self.depth(OrderSide::Sell, levels);
+        let bid_depth: Decimal = bids.iter().map(|level| level.quantity).sum();
+        let ask_depth: Decimal = asks.iter().map(|level| level.quantity).sum();
+        let (bid, ask) = (bids.first(), asks.first());
+        let total = bid_depth + ask_depth;
+        BookStats {
+            best_bid: bid.map(|level| level.price),
+            best_ask: ask.map(|level| level.price),
+            spread: bid.zip(ask).map(|(bid, ask)| ask.price - bid.price),
+            midpoint: bid.zip(ask).map(|(bid, ask)| (bid.price + ask.price) / Decimal::TWO),
+            microprice: bid.zip(ask).map(|(bid, ask)| {
+                (bid.price * ask.quantity + ask.price * bid.quantity) / (bid.quantity + ask.quantity)
+            }),
+            bid_depth,
+            ask_depth,
+            imbalance: (total > Decimal::ZERO).then(|| (bid_depth - ask_depth) / total),
+        }
+    }
+
+    fn snapshot(&self) -> BookSnapshot {
+        let orders = |side| self.side(side).iter_orders().cloned().collect();
+        BookSnapshot {
+            bids: orders(OrderSide::Buy),
+            asks: orders(OrderSide::Sell),
+        }
+    }
+
+    fn serialize_snapshot(&self) -> Result<Vec<u8>, String> {
+        serde_json::to_vec(&self.snapshot()).map_err(|e| e.to_string())
+    }
+
+    /// Replaces everything resting on the book with the snapshot's orders.
+    /// The book is left untouched if the bytes do not parse.
+    fn restore_snapshot(&mut self, bytes: &[u8]) -> Result<(), String> {
+        let snapshot: BookSnapshot = serde_json::from_slice(bytes).map_err(|e| e.to_string())?;
+        for (side, orders) in [(OrderSide::Buy, snapshot.bids), (OrderSide::Sell, snapshot.asks)] {
+            let book = self.side_mut(side);
+            book.clear();
+            for order in orders {
+                book.insert(order);
+            }
+        }
+        Ok(())
+    }
+
+    /// `OrderBookSide::retain_orders` on both sides, bids' removals first.
+    fn retain_orders(&mut self, keep: &mut dyn FnMut(&Order) -> bool) -> Vec<Order> {
+        let mut removed = self.side_mut(OrderSide::Buy).retain_orders(keep);
+        removed.extend(self.side_mut(OrderSide::Sell).retain_orders(keep));
+        removed
+    }
+
+    fn memory_footprint(&self) -> MemoryFootprint {
+        self.side(OrderSide::Buy).memory_footprint() + self.side(OrderSide::Sell).memory_footprint()
+    }
+
+    /// Displayed liquidity on the best `levels` levels of `side`.
+    fn depth(&self, side: OrderSide, levels: usize) -> Vec<OrderBookEntry> {
+        self.side(side).get_depth_by_side(side, levels)
+    }
+}
+
+/// Bids and asks each kept in an `S`.
+#[derive(Debug, Clone, Default)]
+pub struct SymbolBook<S> {
+    bids: Bids<S>,
+    asks: Asks<S>,
+}
+
+impl<S> SymbolBook<S> {
+    pub fn new(bids: S, asks: S) -> Self {
+        Self {
+            bids: Bids(bids),
+            asks: Asks(asks),
+        }
+    }
+
+    pub fn bids(&self) -> &Bids<S> {
+        &self.bids
+    }
+
+    pub fn asks(&self) -> &Asks<S> {
+        &self.asks
+    }
+}
+
+impl<S: OrderBookSide + Debug + Send + Sync> OrderBookOps for SymbolBook<S> {
+    fn side(&self, side: OrderSide) -> &dyn OrderBookSide {
+        match side {
+            OrderSide::Buy => &*self.bids,
+            OrderSide::Sell => &*self.asks,
+        }
+    }
+
+    fn side_mut(&mut self, side: OrderSide) -> &mut dyn OrderBookSide {
+        match side {
+            OrderSide::Buy => &mut *self.bids,
+            OrderSide::Sell => &mut *self.asks,
+        }
+    }
+
+    fn crossed(&self) -> Option<(Decimal, Decimal)> {
+        self.bids.crosses(&self.asks)
+    }
+
+    fn best(&self, side: OrderSide) -> Option<Decimal> {
+        match side {
+            OrderSide::Buy => self.bids.best(),
+            OrderSide::Sell => self.asks.best(),
+        }
+    }
+
+    fn iter_levels(&self, side: OrderSide) -> LevelIter<'_> {
+        match side {
+            OrderSide::Buy => self.bids.levels(),
+            OrderSide::Sell => self.asks.levels(),
+        }
+    }
+}
+
+/// Creates the book for a symbol the first time an order rests on it.
+pub trait BookFactory: Send + Sync {
+    fn new_book(&self, symbol: &str) -> Box<dyn OrderBookOps>;
+}
+
+/// Skip lists on both sides.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SkipListBooks;
+
+impl BookFactory for SkipListBooks {
+    fn new_book(&self, _symbol: &str) -> Box<dyn OrderBookOps> {
+        Box::new(SymbolBook::<SkipListOrderBook>::default())
+    }
+}
+
+/// Skip lists on both sides, their node heights drawn from `seed` and the
+/// symbol, so replaying the same commands rebuilds the same books.
+#[derive(Debug, Clone, Copy)]
+pub struct SeededSkipListBooks {
+    pub seed: u64,
+}
+
+impl SeededSkipListBooks {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+}
+
+impl BookFactory for SeededSkipListBooks {
+    fn new_book(&self, symbol: &str) -> Box<dyn OrderBookOps> {
+        // FNV-1a, so the seed does not change with the std hasher.
+        let seed = symbol
+            .bytes()
+            .fold(self.seed ^ 0xcbf2_9ce4_8422_2325, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+            });
+        Box::new(SymbolBook::new(
+            SkipListOrderBook::with_seed(seed),
+            SkipListOrderBook::with_seed(seed.rotate_left(32)),
+        ))
+    }
+}
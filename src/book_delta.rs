@@ -0,0 +1,78 @@
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+use crate::engine::MatchingEngine;
+use crate::orderbook::OrderBookSide;
+use crate::types::{Order, OrderSide};
+
+/// Deltas a subscriber may fall behind by before it starts missing them.
+const DELTA_CHANNEL_CAPACITY: usize = 4096;
+
+/// The new displayed state of one price level after an order was added
+/// to it, traded against, amended or removed. A quantity of zero means
+/// the level is gone.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BookDelta {
+    pub symbol: String,
+    pub side: OrderSide,
+    pub price: Decimal,
+    pub quantity: Decimal,
+    pub order_count: u64,
+    /// Per symbol, without gaps.
+    pub sequence: u64,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub(crate) struct BookDeltaFeed {
+    sender: broadcast::Sender<BookDelta>,
+    sequences: DashMap<String, u64>,
+}
+
+impl Default for BookDeltaFeed {
+    fn default() -> Self {
+        Self {
+            sender: broadcast::channel(DELTA_CHANNEL_CAPACITY).0,
+            sequences: DashMap::new(),
+        }
+    }
+}
+
+impl MatchingEngine {
+    /// Every book delta from now on, across symbols. A receiver that falls
+    /// more than a few thousand deltas behind gets `RecvError::Lagged` and
+    /// should resynchronise from a snapshot.
+    pub fn subscribe_book_deltas(&self) -> broadcast::Receiver<BookDelta> {
+        self.book_deltas.sender.subscribe()
+    }
+
+    /// Publishes the level `order` rests (or rested) at, as `book` now has
+    /// it. Hidden orders do not change the displayed level and publish
+    /// nothing.
+    pub(crate) fn publish_book_delta(&self, book: &dyn OrderBookSide, order: &Order) {
+        let Some(price) = order.price.filter(|_| order.displayed) else {
+            return;
+        };
+        if self.book_deltas.sender.receiver_count() == 0 {
+            return;
+        }
+        let displayed: Vec<&Order> = book
+            .level(price)
+            .map(|level| level.iter().filter(|o| o.displayed).collect())
+            .unwrap_or_default();
+        let mut sequence = self.book_deltas.sequences.entry(order.symbol.clone()).or_default();
+        *sequence += 1;
+        let _ = self.book_deltas.sender.send(BookDelta {
+            symbol: order.symbol.clone(),
+            side: order.side,
+            price,
+            quantity: displayed.iter().map(|o| o.remaining_quantity()).sum(),
+            order_count: displayed.len() as u64,
+            sequence: *sequence,
+            timestamp: self.clock.now(),
+        });
+    }
+}
@@ -0,0 +1,105 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::commands::PlaceOrderCommand;
+use crate::draws::new_event_id;
+use crate::engine::MatchingEngine;
+use crate::error::RejectReason;
+use crate::events::{BookLevelEvictedEvent, OrderEvent};
+use crate::types::{Order, OrderSide, OrderType};
+
+/// What happens to an order that would take a side past
+/// `max_levels_per_side`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum BookLimitPolicy {
+    #[default]
+    Reject,
+    /// Cancel every order on the side's worst level to make room, unless
+    /// the new order would itself be on the worst level.
+    EvictWorstLevel,
+}
+
+/// Caps on how far a book may grow, against memory exhaustion from order
+/// spam. A full level always refuses further orders.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct BookLimits {
+    pub max_levels_per_side: Option<usize>,
+    pub max_orders_per_level: Option<usize>,
+    pub policy: BookLimitPolicy,
+}
+
+impl MatchingEngine {
+    /// Whether an order on `side` could rest at `price`: Ok(None) if it
+    /// fits, Ok(Some(worst)) if the level at `worst` has to go first.
+    fn book_room(&self, symbol: &str, side: OrderSide, price: Decimal) -> Result<Option<Decimal>, RejectReason> {
+        let limits = self.config.book_limits;
+        let Some(book) = self.order_books.get(symbol) else {
+            return Ok(None);
+        };
+        let orders = book.side(side);
+        if let Some(level) = orders.level(price) {
+            return match limits.max_orders_per_level {
+                Some(limit) if level.len() >= limit => Err(RejectReason::PriceLevelFull { price, limit }),
+                _ => Ok(None),
+            };
+        }
+        let Some(limit) = limits.max_levels_per_side.filter(|limit| orders.level_count() >= *limit) else {
+            return Ok(None);
+        };
+        let worst = match side {
+            OrderSide::Buy => orders.lowest_price().filter(|worst| *worst < price),
+            OrderSide::Sell => orders.highest_price().filter(|worst| *worst > price),
+        };
+        match (limits.policy, worst) {
+            (BookLimitPolicy::EvictWorstLevel, Some(worst)) if limit > 0 => Ok(Some(worst)),
+            _ => Err(RejectReason::TooManyPriceLevels { limit }),
+        }
+    }
+
+    /// Refuses a limit order up front if it would rest in full and there
+    /// is no room for it. Orders that trade first are checked again when
+    /// their remainder comes to rest.
+    pub(crate) fn check_book_limits(&self, cmd: &PlaceOrderCommand) -> Result<(), RejectReason> {
+        let (OrderType::Limit, Some(price)) = (cmd.order_type, cmd.price) else {
+            return Ok(());
+        };
+        let crosses = self
+            .order_books
+            .get(&cmd.symbol)
+            .is_some_and(|book| book.match_against(cmd.side, Some(price)).is_some());
+        if crosses {
+            return Ok(());
+        }
+        self.book_room(&cmd.symbol, cmd.side, price).map(|_| ())
+    }
+
+    /// Makes room for `order` to rest, evicting the worst level if the
+    /// policy allows it.
+    pub(crate) fn make_room(&self, order: &Order, events: &mut Vec<OrderEvent>) -> Result<(), RejectReason> {
+        let Some(price) = order.price else {
+            return Ok(());
+        };
+        let Some(worst) = self.book_room(&order.symbol, order.side, price)? else {
+            return Ok(());
+        };
+        let order_ids: Vec<Uuid> = self
+            .order_books
+            .get(&order.symbol)
+            .and_then(|book| book.side(order.side).level(worst).map(|level| level.iter().map(|o| o.id).collect()))
+            .unwrap_or_default();
+        for order_id in &order_ids {
+            self.cancel_order(*order_id, events);
+        }
+        events.push(OrderEvent::BookLevelEvicted(BookLevelEvictedEvent {
+            event_id: new_event_id(),
+            prev_hash: None,
+            symbol: order.symbol.clone(),
+            side: order.side,
+            price: worst,
+            order_ids,
+            timestamp: self.clock.now(),
+        }));
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use tokio::sync::watch;
+
+use crate::depth::L2Snapshot;
+use crate::engine::MatchingEngine;
+
+const DEFAULT_VIEW_DEPTH: usize = 10;
+
+/// Read-only top-N views of each book, republished after every command so
+/// market-data readers never touch the live book.
+#[derive(Debug)]
+pub(crate) struct BookViews {
+    senders: DashMap<String, watch::Sender<Arc<L2Snapshot>>>,
+    depth: usize,
+}
+
+impl Default for BookViews {
+    fn default() -> Self {
+        Self {
+            senders: DashMap::new(),
+            depth: DEFAULT_VIEW_DEPTH,
+        }
+    }
+}
+
+impl MatchingEngine {
+    /// Levels per side in the views handed out by `book_view`.
+    pub fn with_book_view_depth(mut self, levels: usize) -> Self {
+        self.book_views.depth = levels;
+        self
+    }
+
+    /// A handle on `symbol`'s depth as of the last command handled through
+    /// `handle_command`. `borrow()` costs an Arc clone and never waits on
+    /// the matching side.
+    pub fn book_view(&self, symbol: &str) -> watch::Receiver<Arc<L2Snapshot>> {
+        if let Some(sender) = self.book_views.senders.get(symbol) {
+            return sender.subscribe();
+        }
+        let snapshot = self.get_l2_snapshot(symbol, self.book_views.depth);
+        self.book_views
+            .senders
+            .entry(symbol.to_string())
+            .or_insert_with(|| watch::channel(Arc::new(snapshot)).0)
+            .subscribe()
+    }
+
+    /// Republishes the views of `symbols` that someone is watching, waking
+    /// readers only if the depth changed.
+    pub(crate) fn refresh_book_views(&self, symbols: &[String]) {
+        for symbol in symbols {
+            let Some(sender) = self.book_views.senders.get(symbol) else {
+                continue;
+            };
+            if sender.receiver_count() == 0 {
+                continue;
+            }
+            let snapshot = self.get_l2_snapshot(symbol, self.book_views.depth);
+            sender.send_if_modified(|view| {
+                if view.bids == snapshot.bids && view.asks == snapshot.asks {
+                    return false;
+                }
+                *view = Arc::new(snapshot);
+                true
+            });
+        }
+    }
+}
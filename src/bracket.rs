@@ -0,0 +1,277 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::commands::{OrderCommand, PlaceBracketOrderCommand};
+use crate::draws::new_event_id;
+use crate::engine::MatchingEngine;
+use crate::error::{EngineError, RejectReason};
+use crate::events::{
+    BracketOrderActivatedEvent, BracketOrderCompletedEvent, BracketOrderPlacedEvent, OrderEvent,
+};
+use crate::precision::checked_notional;
+use crate::trading_state::Admission;
+use crate::types::{Order, OrderSide, OrderStatus, OrderType, Trade};
+
+/// The orders of a bracket and how far it has got.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BracketGroup {
+    pub bracket_id: Uuid,
+    pub entry_order_id: Uuid,
+    pub stop_loss_order_id: Uuid,
+    pub take_profit_order_id: Uuid,
+    pub symbol: String,
+    /// The entry has filled and the exits are parked.
+    pub activated: bool,
+    /// An exit has executed or the entry was canceled.
+    pub completed: bool,
+}
+
+impl BracketGroup {
+    fn sibling_of(&self, order_id: Uuid) -> Option<Uuid> {
+        if order_id == self.stop_loss_order_id {
+            Some(self.take_profit_order_id)
+        } else if order_id == self.take_profit_order_id {
+            Some(self.stop_loss_order_id)
+        } else {
+            None
+        }
+    }
+}
+
+impl MatchingEngine {
+    /// Places a bracket. A client order id on the entry makes the whole
+    /// bracket idempotent.
+    pub async fn handle_place_bracket_order(
+        &self,
+        cmd: PlaceBracketOrderCommand,
+    ) -> Result<Vec<OrderEvent>, EngineError> {
+        let client_order_id = cmd.entry.client_order_id.clone();
+        self.place_idempotently(cmd.entry.user_id, client_order_id.as_deref(), self.place_bracket_order(cmd))
+            .await
+    }
+
+    async fn place_bracket_order(&self, mut cmd: PlaceBracketOrderCommand) -> Result<Vec<OrderEvent>, EngineError> {
+        self.normalize_order(&mut cmd.entry)?;
+        cmd.stop_loss_price = self.normalize_trigger_price(&cmd.entry.symbol, cmd.stop_loss_price);
+        cmd.take_profit_price = self.normalize_trigger_price(&cmd.entry.symbol, cmd.take_profit_price);
+        for price in [cmd.stop_loss_price, cmd.take_profit_price] {
+            checked_notional(&cmd.entry.symbol, price, cmd.entry.quantity)?;
+        }
+        self.validate_bracket_order(&cmd)?;
+        for order_id in [cmd.entry.order_id, cmd.stop_loss_order_id, cmd.take_profit_order_id] {
+            if self.orders.contains_key(&order_id) {
+                return Err(self.reject_duplicate_order_id(order_id, cmd.entry.user_id, &cmd.entry.symbol).await);
+            }
+        }
+        self.check_open_order_limit(cmd.entry.user_id, 3)?;
+        self.check_funds(&cmd.entry, Decimal::ZERO)?;
+        self.check_exposure(cmd.entry.user_id, &cmd.entry.symbol, cmd.entry.side, cmd.entry.quantity, None)?;
+        let entry = &cmd.entry;
+        self.check_margin(entry.user_id, &entry.symbol, entry.side, entry.quantity, entry.price, None)?;
+        self.check_credit(entry.user_id, self.command_credit(entry), None)?;
+        self.run_risk_checks(&cmd.entry, None).await?;
+        let (admission, mut events) = self
+            .admit_command(&cmd.entry.symbol, false, || OrderCommand::PlaceBracketOrder(cmd.clone()))
+            .await?;
+        if let Admission::Queued = admission {
+            return Ok(events);
+        }
+        let resumed = self.check_persistence_halt(&cmd.entry.symbol).await?;
+
+        let entry = Self::order_from_command(&cmd.entry);
+        let exit_side = match entry.side {
+            OrderSide::Buy => OrderSide::Sell,
+            OrderSide::Sell => OrderSide::Buy,
+        };
+        let stop_loss = Order {
+            id: cmd.stop_loss_order_id,
+            order_type: OrderType::StopLoss,
+            side: exit_side,
+            price: None,
+            stop_price: Some(cmd.stop_loss_price),
+            iceberg_visible_quantity: None,
+            trailing_stop_price: None,
+            client_order_id: None,
+            ..entry.clone()
+        };
+        let take_profit = Order {
+            id: cmd.take_profit_order_id,
+            order_type: OrderType::TakeProfit,
+            stop_price: Some(cmd.take_profit_price),
+            ..stop_loss.clone()
+        };
+
+        let group = BracketGroup {
+            bracket_id: cmd.bracket_id,
+            entry_order_id: entry.id,
+            stop_loss_order_id: stop_loss.id,
+            take_profit_order_id: take_profit.id,
+            symbol: entry.symbol.clone(),
+            activated: false,
+            completed: false,
+        };
+        for order_id in [entry.id, stop_loss.id, take_profit.id] {
+            self.order_brackets.insert(order_id, cmd.bracket_id);
+        }
+        self.brackets.insert(cmd.bracket_id, group);
+
+        // Children are recorded but stay dormant until the entry fills
+        self.store_order(&stop_loss, &mut events);
+        self.store_order(&take_profit, &mut events);
+        events.push(OrderEvent::BracketOrderPlaced(BracketOrderPlacedEvent {
+            event_id: new_event_id(),
+            prev_hash: None,
+            bracket_id: cmd.bracket_id,
+            entry_order_id: entry.id,
+            stop_loss_order_id: stop_loss.id,
+            take_profit_order_id: take_profit.id,
+            user_id: entry.user_id,
+            symbol: entry.symbol.clone(),
+            stop_loss_price: cmd.stop_loss_price,
+            take_profit_price: cmd.take_profit_price,
+            timestamp: entry.created_at,
+        }));
+        self.submit_order(entry, &mut events);
+
+        self.persist_events(&mut events).await?;
+
+        Ok(resumed.into_iter().chain(events).collect())
+    }
+
+    fn validate_bracket_order(&self, cmd: &PlaceBracketOrderCommand) -> Result<(), EngineError> {
+        self.validate_order(&cmd.entry)?;
+
+        if !matches!(cmd.entry.order_type, OrderType::Market | OrderType::Limit) {
+            return Err(EngineError::Rejected("Bracket entry must be a market or limit order".to_string()));
+        }
+        let ids = [cmd.entry.order_id, cmd.stop_loss_order_id, cmd.take_profit_order_id];
+        if ids[0] == ids[1] || ids[0] == ids[2] || ids[1] == ids[2] {
+            return Err(EngineError::Rejected("Bracket orders must have distinct order ids".to_string()));
+        }
+        if self.brackets.contains_key(&cmd.bracket_id) {
+            return Err(format!("Bracket {} already exists", cmd.bracket_id).into());
+        }
+
+        for price in [cmd.stop_loss_price, cmd.take_profit_price] {
+            if price <= Decimal::ZERO {
+                return Err(RejectReason::NonPositiveStopPrice(price).into());
+            }
+        }
+
+        let (low, high) = match cmd.entry.side {
+            OrderSide::Buy => (cmd.stop_loss_price, cmd.take_profit_price),
+            OrderSide::Sell => (cmd.take_profit_price, cmd.stop_loss_price),
+        };
+        let ordered = match cmd.entry.price {
+            Some(price) => low < price && price < high,
+            None => low < high,
+        };
+        if !ordered {
+            return Err(EngineError::Rejected(
+                "Stop-loss and take-profit prices must bracket the entry price".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether the order is the stop-loss or take-profit of a bracket.
+    pub(crate) fn is_bracket_exit(&self, order_id: Uuid) -> bool {
+        let Some(bracket_id) = self.order_brackets.get(&order_id).map(|b| *b) else {
+            return false;
+        };
+        self.brackets
+            .get(&bracket_id)
+            .is_some_and(|group| group.entry_order_id != order_id)
+    }
+
+    /// Activates exits once an entry is fully filled and cancels the sibling
+    /// as soon as one exit executes.
+    pub(crate) fn on_bracket_fills(&self, trades: &[Trade], events: &mut Vec<OrderEvent>) {
+        for trade in trades {
+            for order_id in [trade.taker_order_id, trade.maker_order_id] {
+                let Some(bracket_id) = self.order_brackets.get(&order_id).map(|b| *b) else {
+                    continue;
+                };
+                let Some(group) = self.brackets.get(&bracket_id).map(|g| g.clone()) else {
+                    continue;
+                };
+
+                if order_id == group.entry_order_id {
+                    let filled = self
+                        .get_order(order_id)
+                        .is_some_and(|o| o.status == OrderStatus::Filled);
+                    if filled && !group.activated {
+                        self.activate_bracket(&group, events);
+                    }
+                } else if let Some(sibling_id) = group.sibling_of(order_id) {
+                    if !group.completed {
+                        self.complete_bracket(&group, order_id, sibling_id, events);
+                    }
+                }
+            }
+        }
+    }
+
+    fn activate_bracket(&self, group: &BracketGroup, events: &mut Vec<OrderEvent>) {
+        if let Some(mut entry) = self.brackets.get_mut(&group.bracket_id) {
+            entry.activated = true;
+        }
+        for child_id in [group.stop_loss_order_id, group.take_profit_order_id] {
+            if let Some(child) = self.get_order(child_id).filter(|o| o.is_open()) {
+                self.park_stop_order(&child);
+            }
+        }
+        events.push(OrderEvent::BracketOrderActivated(BracketOrderActivatedEvent {
+            event_id: new_event_id(),
+            prev_hash: None,
+            bracket_id: group.bracket_id,
+            entry_order_id: group.entry_order_id,
+            stop_loss_order_id: group.stop_loss_order_id,
+            take_profit_order_id: group.take_profit_order_id,
+            symbol: group.symbol.clone(),
+            timestamp: self.clock.now(),
+        }));
+    }
+
+    fn complete_bracket(
+        &self,
+        group: &BracketGroup,
+        executed_order_id: Uuid,
+        canceled_order_id: Uuid,
+        events: &mut Vec<OrderEvent>,
+    ) {
+        if let Some(mut entry) = self.brackets.get_mut(&group.bracket_id) {
+            entry.completed = true;
+        }
+        self.cancel_order(canceled_order_id, events);
+        events.push(OrderEvent::BracketOrderCompleted(BracketOrderCompletedEvent {
+            event_id: new_event_id(),
+            prev_hash: None,
+            bracket_id: group.bracket_id,
+            entry_order_id: group.entry_order_id,
+            executed_order_id,
+            canceled_order_id,
+            symbol: group.symbol.clone(),
+            timestamp: self.clock.now(),
+        }));
+    }
+
+    /// Canceling an entry before it fills takes its dormant exits with it.
+    pub(crate) fn cancel_bracket_children(&self, order_id: Uuid, events: &mut Vec<OrderEvent>) {
+        let Some(bracket_id) = self.order_brackets.get(&order_id).map(|b| *b) else {
+            return;
+        };
+        let Some(group) = self.brackets.get(&bracket_id).map(|g| g.clone()) else {
+            return;
+        };
+        if order_id != group.entry_order_id || group.activated {
+            return;
+        }
+        self.cancel_order(group.stop_loss_order_id, events);
+        self.cancel_order(group.take_profit_order_id, events);
+        if let Some(mut entry) = self.brackets.get_mut(&bracket_id) {
+            entry.completed = true;
+        }
+    }
+}
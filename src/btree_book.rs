@@ -0,0 +1,225 @@
+use std::collections::{BTreeMap, HashMap};
+use std::mem::size_of;
+
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::book::{BookFactory, OrderBookOps, SymbolBook};
+use crate::footprint::{map_bytes, MemoryFootprint};
+use crate::orderbook::{LevelIter, OrderBookSide};
+use crate::price_level::PriceLevel;
+use crate::types::{Order, OrderBookEntry, OrderSide};
+
+/// Price levels in a BTreeMap. Same interface as `SkipListOrderBook`.
+#[derive(Debug, Clone, Default)]
+pub struct BTreeOrderBook {
+    levels: BTreeMap<Decimal, PriceLevel>,
+    order_prices: HashMap<Uuid, Decimal>,
+}
+
+impl BTreeOrderBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_order(&mut self, order: Order) {
+        let price = order.price.unwrap_or(Decimal::MAX);
+        self.order_prices.insert(order.id, price);
+        self.levels.entry(price).or_default().push(order);
+    }
+
+    pub fn remove_order(&mut self, order_id: Uuid, price: Decimal) -> Option<Order> {
+        let level = self.levels.get_mut(&price)?;
+        let order = level.remove(order_id)?;
+        if level.is_empty() {
+            self.levels.remove(&price);
+        }
+        self.order_prices.remove(&order_id);
+        Some(order)
+    }
+
+    pub fn remove_order_by_id(&mut self, order_id: Uuid) -> Option<Order> {
+        let price = *self.order_prices.get(&order_id)?;
+        self.remove_order(order_id, price)
+    }
+
+    pub fn get_order(&self, order_id: Uuid) -> Option<&Order> {
+        let price = self.order_prices.get(&order_id)?;
+        self.levels.get(price)?.get(order_id)
+    }
+
+    /// Replaces the resting copy of `order`, dropping it once it has
+    /// nothing left to trade.
+    pub fn update_order(&mut self, order: &Order) {
+        let Some(&price) = self.order_prices.get(&order.id) else {
+            return;
+        };
+        if !order.is_open() || order.remaining_quantity() <= Decimal::ZERO {
+            self.remove_order(order.id, price);
+            return;
+        }
+        if let Some(resting) = self.levels.get_mut(&price).and_then(|level| level.get_mut(order.id)) {
+            *resting = order.clone();
+        }
+    }
+
+    /// The best price for a book holding `side`'s orders: the highest bid
+    /// or the lowest ask.
+    pub fn get_best_price(&self, side: OrderSide) -> Option<Decimal> {
+        match side {
+            OrderSide::Buy => self.levels.keys().next_back().copied(),
+            OrderSide::Sell => self.levels.keys().next().copied(),
+        }
+    }
+
+    pub fn get_orders_at_price(&self, price: Decimal) -> Option<&PriceLevel> {
+        self.levels.get(&price)
+    }
+
+    pub fn get_depth(&self, depth: usize) -> Vec<OrderBookEntry> {
+        self.levels
+            .iter()
+            .take(depth)
+            .map(|(price, level)| OrderBookEntry {
+                price: *price,
+                quantity: level.iter().map(|o| o.quantity).sum(),
+                order_count: level.len() as u64,
+            })
+            .collect()
+    }
+}
+
+impl OrderBookSide for BTreeOrderBook {
+    fn insert(&mut self, order: Order) {
+        self.add_order(order);
+    }
+
+    fn remove(&mut self, order_id: Uuid) -> Option<Order> {
+        self.remove_order_by_id(order_id)
+    }
+
+    fn get(&self, order_id: Uuid) -> Option<&Order> {
+        self.get_order(order_id)
+    }
+
+    fn update(&mut self, order: &Order) {
+        self.update_order(order);
+    }
+
+    fn level(&self, price: Decimal) -> Option<&PriceLevel> {
+        self.levels.get(&price)
+    }
+
+    fn iter_levels(&self) -> LevelIter<'_> {
+        Box::new(self.levels.iter().map(|(price, level)| (*price, level)))
+    }
+
+    fn lowest_price(&self) -> Option<Decimal> {
+        self.get_best_price(OrderSide::Sell)
+    }
+
+    fn highest_price(&self) -> Option<Decimal> {
+        self.get_best_price(OrderSide::Buy)
+    }
+
+    fn level_count(&self) -> usize {
+        self.levels.len()
+    }
+
+    fn order_count(&self) -> usize {
+        self.order_prices.len()
+    }
+
+    fn retain_orders(&mut self, keep: &mut dyn FnMut(&Order) -> bool) -> Vec<Order> {
+        let mut removed = Vec::new();
+        self.levels.retain(|_, level| {
+            removed.extend(level.retain(&mut *keep));
+            !level.is_empty()
+        });
+        for order in &removed {
+            self.order_prices.remove(&order.id);
+        }
+        removed
+    }
+
+    fn memory_footprint(&self) -> MemoryFootprint {
+        let mut footprint = MemoryFootprint {
+            level_bytes: self.levels.len() * size_of::<(Decimal, PriceLevel)>(),
+            index_bytes: map_bytes(&self.order_prices),
+            levels: self.levels.len(),
+            orders: self.order_prices.len(),
+            ..MemoryFootprint::default()
+        };
+        for level in self.levels.values() {
+            let (orders, index) = level.heap_bytes();
+            footprint.order_bytes += orders;
+            footprint.index_bytes += index;
+        }
+        footprint
+    }
+}
+
+/// BTreeMaps on both sides.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BTreeBooks;
+
+impl BookFactory for BTreeBooks {
+    fn new_book(&self, _symbol: &str) -> Box<dyn OrderBookOps> {
+        Box::new(SymbolBook::<BTreeOrderBook>::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{OrderStatus, OrderType};
+    use chrono::Utc;
+
+    fn create_test_order(price: Decimal) -> Order {
+        Order {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            symbol: "BTC/USDT".to_string(),
+            order_type: OrderType::Limit,
+            side: OrderSide::Buy,
+            price: Some(price),
+            quantity: Decimal::from(1),
+            filled_quantity: Decimal::ZERO,
+            status: OrderStatus::Active,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            iceberg_visible_quantity: None,
+            stop_price: None,
+            trailing_stop_price: None,
+            client_order_id: None,
+            displayed: true,
+            max_fills: None,
+            referrer_id: None,
+            quote_quantity: None,
+        }
+    }
+
+    #[test]
+    fn test_add_and_remove_order() {
+        let mut orderbook = BTreeOrderBook::new();
+        let order = create_test_order(Decimal::from(100));
+        let order_id = order.id;
+
+        orderbook.add_order(order);
+        assert_eq!(orderbook.order_count(), 1);
+
+        assert!(orderbook.remove_order_by_id(order_id).is_some());
+        assert_eq!(orderbook.order_count(), 0);
+        assert_eq!(orderbook.level_count(), 0);
+    }
+
+    #[test]
+    fn test_get_best_price() {
+        let mut orderbook = BTreeOrderBook::new();
+        orderbook.add_order(create_test_order(Decimal::from(100)));
+        orderbook.add_order(create_test_order(Decimal::from(200)));
+
+        assert_eq!(orderbook.get_best_price(OrderSide::Buy), Some(Decimal::from(200)));
+        assert_eq!(orderbook.get_best_price(OrderSide::Sell), Some(Decimal::from(100)));
+    }
+}
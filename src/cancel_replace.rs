@@ -0,0 +1,96 @@
+use crate::commands::{CancelReplaceCommand, OrderCommand, PlaceOrderCommand};
+use crate::engine::MatchingEngine;
+use crate::error::EngineError;
+use crate::events::OrderEvent;
+use crate::trading_state::Admission;
+
+impl MatchingEngine {
+    /// Cancels an open order and places its replacement. Everything that
+    /// can reject the command is checked before the original is touched,
+    /// and the cancel and the placement happen with no suspension point in
+    /// between, so there is always exactly one of the two orders open.
+    pub async fn handle_cancel_replace(&self, cmd: CancelReplaceCommand) -> Result<Vec<OrderEvent>, EngineError> {
+        let client_order_id = cmd.new_client_order_id.clone();
+        self.place_idempotently(cmd.user_id, client_order_id.as_deref(), self.cancel_replace(cmd))
+            .await
+    }
+
+    async fn cancel_replace(&self, cmd: CancelReplaceCommand) -> Result<Vec<OrderEvent>, EngineError> {
+        let order_id = self.resolve_order_ref(cmd.user_id, cmd.client_order_id.as_deref(), cmd.order_id)?;
+        let original = self
+            .get_order(order_id)
+            .ok_or_else(|| format!("Order {} not found", order_id))?;
+        if original.user_id != cmd.user_id || original.symbol != cmd.symbol {
+            return Err(format!("Order {} does not belong to this user and symbol", order_id).into());
+        }
+        if !original.is_open() {
+            return Err(format!("Order {} is no longer open", order_id).into());
+        }
+        if self.order_brackets.contains_key(&order_id) {
+            return Err(format!("Order {} is part of a bracket and cannot be replaced", order_id).into());
+        }
+        if self.orders.contains_key(&cmd.new_order_id) {
+            return Err(self.reject_duplicate_order_id(cmd.new_order_id, cmd.user_id, &cmd.symbol).await);
+        }
+        if cmd.new_quantity <= original.filled_quantity {
+            return Err(format!(
+                "New quantity {} does not exceed the {} already filled",
+                cmd.new_quantity, original.filled_quantity
+            ).into());
+        }
+
+        let mut replacement = PlaceOrderCommand {
+            order_id: cmd.new_order_id,
+            client_order_id: cmd.new_client_order_id.clone(),
+            user_id: cmd.user_id,
+            symbol: cmd.symbol.clone(),
+            order_type: original.order_type,
+            side: original.side,
+            price: cmd.new_price,
+            quantity: cmd.new_quantity - original.filled_quantity,
+            iceberg_visible_quantity: original.iceberg_visible_quantity,
+            stop_price: original.stop_price,
+            trailing_stop_price: original.trailing_stop_price,
+            displayed: original.displayed,
+            max_fills: original.max_fills,
+            referrer_id: original.referrer_id,
+            quote_quantity: original.quote_quantity,
+            timestamp: cmd.timestamp,
+        };
+        self.normalize_order(&mut replacement)?;
+        self.validate_order(&replacement)?;
+        self.check_funds(&replacement, self.held_by(order_id))?;
+        self.check_exposure(cmd.user_id, &cmd.symbol, original.side, replacement.quantity, Some(order_id))?;
+        let (side, quantity, price) = (original.side, replacement.quantity, replacement.price);
+        self.check_margin(cmd.user_id, &cmd.symbol, side, quantity, price, Some(order_id))?;
+        self.check_credit(cmd.user_id, self.command_credit(&replacement), Some(order_id))?;
+        self.run_risk_checks(&replacement, Some(order_id)).await?;
+
+        let (admission, mut events) = self
+            .admit_command(&cmd.symbol, false, || OrderCommand::CancelReplace(cmd.clone()))
+            .await?;
+        if let Admission::Queued = admission {
+            return Ok(events);
+        }
+        let resumed = self.check_persistence_halt(&cmd.symbol).await?;
+
+        let canceled_at = events.len();
+        self.cancel_order(order_id, &mut events);
+        self.submit_order(Self::order_from_command(&replacement), &mut events);
+        for event in &mut events[canceled_at..] {
+            match event {
+                OrderEvent::OrderCanceled(e) if e.order_id == order_id => {
+                    e.replaced_by_order_id = Some(replacement.order_id);
+                }
+                OrderEvent::OrderPlaced(e) if e.order_id == replacement.order_id => {
+                    e.replaces_order_id = Some(order_id);
+                }
+                _ => {}
+            }
+        }
+
+        self.persist_events(&mut events).await?;
+
+        Ok(resumed.into_iter().chain(events).collect())
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, TimeZone, Utc};
+use dashmap::DashMap;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::draws::new_event_id;
+use crate::engine::MatchingEngine;
+use crate::event_store::TimeRange;
+use crate::events::{CandleClosedEvent, OrderEvent};
+use crate::types::Trade;
+
+const DEFAULT_INTERVALS: [Duration; 4] = [
+    Duration::from_secs(1),
+    Duration::from_secs(60),
+    Duration::from_secs(300),
+    Duration::from_secs(3600),
+];
+/// Closed bars kept per symbol and interval.
+const CLOSED_CAPACITY: usize = 1000;
+
+/// An OHLCV bar of the trades on a symbol from `open_time` for one
+/// interval.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Candle {
+    pub symbol: String,
+    pub interval: Duration,
+    pub open_time: DateTime<Utc>,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    pub volume: Decimal,
+    pub trade_count: usize,
+}
+
+impl Candle {
+    fn new(trade: &Trade, interval: Duration, open_time: DateTime<Utc>) -> Self {
+        Self {
+            symbol: trade.symbol.clone(),
+            interval,
+            open_time,
+            open: trade.price,
+            high: trade.price,
+            low: trade.price,
+            close: trade.price,
+            volume: trade.quantity,
+            trade_count: 1,
+        }
+    }
+
+    fn add(&mut self, trade: &Trade) {
+        self.high = self.high.max(trade.price);
+        self.low = self.low.min(trade.price);
+        self.close = trade.price;
+        self.volume += trade.quantity;
+        self.trade_count += 1;
+    }
+}
+
+/// Start of the bar of `interval` that `at` falls in.
+fn bar_start(at: DateTime<Utc>, interval: Duration) -> DateTime<Utc> {
+    let width = (interval.as_millis() as i64).max(1);
+    let millis = at.timestamp_millis();
+    Utc.timestamp_millis_opt(millis - millis.rem_euclid(width)).unwrap()
+}
+
+/// Bars of one symbol, indexed like the configured intervals.
+#[derive(Clone)]
+pub(crate) struct SymbolCandles {
+    open: Vec<Option<Candle>>,
+    closed: Vec<VecDeque<Candle>>,
+}
+
+pub(crate) struct Candles {
+    intervals: Vec<Duration>,
+    symbols: DashMap<String, SymbolCandles>,
+    /// Bars closed since the last command's events were persisted.
+    just_closed: Mutex<Vec<Candle>>,
+}
+
+impl Default for Candles {
+    fn default() -> Self {
+        Self {
+            intervals: DEFAULT_INTERVALS.to_vec(),
+            symbols: DashMap::new(),
+            just_closed: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl Candles {
+    /// Adds `trade` to the open bar of each interval, first closing a bar
+    /// the trade falls after.
+    pub(crate) fn record(&self, trade: &Trade) {
+        let mut bars = self.symbols.entry(trade.symbol.clone()).or_insert_with(|| SymbolCandles {
+            open: vec![None; self.intervals.len()],
+            closed: vec![VecDeque::new(); self.intervals.len()],
+        });
+        let bars = &mut *bars;
+        for (i, interval) in self.intervals.iter().enumerate() {
+            let start = bar_start(trade.created_at, *interval);
+            match &mut bars.open[i] {
+                // A trade stamped before the open bar, from a clock step
+                // back, counts towards it
+                Some(bar) if start <= bar.open_time => bar.add(trade),
+                open => {
+                    if let Some(bar) = open.replace(Candle::new(trade, *interval, start)) {
+                        let closed = &mut bars.closed[i];
+                        if closed.len() == CLOSED_CAPACITY {
+                            closed.pop_front();
+                        }
+                        closed.push_back(bar.clone());
+                        self.just_closed.lock().unwrap().push(bar);
+                    }
+                }
+            }
+        }
+    }
+
+    pub(crate) fn checkpoint(&self, symbol: &str) -> Option<SymbolCandles> {
+        self.symbols.get(symbol).map(|bars| bars.clone())
+    }
+
+    pub(crate) fn restore(&self, symbol: &str, checkpoint: Option<SymbolCandles>) {
+        match checkpoint {
+            Some(bars) => self.symbols.insert(symbol.to_string(), bars),
+            None => self.symbols.remove(symbol).map(|(_, bars)| bars),
+        };
+        self.just_closed.lock().unwrap().retain(|bar| bar.symbol != symbol);
+    }
+}
+
+impl MatchingEngine {
+    /// Builds bars of these intervals instead of 1s, 1m, 5m and 1h.
+    pub fn with_candle_intervals(mut self, intervals: &[Duration]) -> Self {
+        self.candles.intervals = intervals.to_vec();
+        self
+    }
+
+    /// `symbol`'s bars of `interval` opening within `range`, oldest first,
+    /// ending with the bar still open. A bar closes with the first trade
+    /// after it, which also emits CandleClosed; intervals without trades
+    /// have no bar. Up to 1000 closed bars are kept per interval.
+    pub fn get_candles(&self, symbol: &str, interval: Duration, range: TimeRange) -> Vec<Candle> {
+        let Some(i) = self.candles.intervals.iter().position(|configured| *configured == interval) else {
+            return Vec::new();
+        };
+        let Some(bars) = self.candles.symbols.get(symbol) else {
+            return Vec::new();
+        };
+        bars.closed[i]
+            .iter()
+            .chain(bars.open[i].as_ref())
+            .filter(|bar| range.contains(bar.open_time))
+            .cloned()
+            .collect()
+    }
+
+    /// CandleClosed events for the bars closed since the last call.
+    pub(crate) fn take_closed_candles(&self) -> Vec<OrderEvent> {
+        let closed = std::mem::take(&mut *self.candles.just_closed.lock().unwrap());
+        closed
+            .into_iter()
+            .map(|candle| {
+                OrderEvent::CandleClosed(CandleClosedEvent {
+                    event_id: new_event_id(),
+                    prev_hash: None,
+                    timestamp: self.clock.now(),
+                    candle,
+                })
+            })
+            .collect()
+    }
+}
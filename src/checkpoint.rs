@@ -0,0 +1,38 @@
+use crate::engine::MatchingEngine;
+use crate::types::OrderSide;
+
+impl MatchingEngine {
+    /// `symbol`'s resting orders in a form `restore_book` can load, so a
+    /// restart can skip replaying the symbol's event history.
+    pub fn checkpoint_book(&self, symbol: &str) -> Result<Vec<u8>, String> {
+        match self.order_books.get(symbol) {
+            Some(book) => book.serialize_snapshot(),
+            None => self.book_factory.new_book(symbol).serialize_snapshot(),
+        }
+    }
+
+    /// Loads a checkpoint into `symbol`'s book and registers its orders
+    /// with the engine. Refused if anything already rests on the symbol.
+    pub fn restore_book(&self, symbol: &str, bytes: &[u8]) -> Result<(), String> {
+        let mut book = self.book_mut(symbol);
+        if [OrderSide::Buy, OrderSide::Sell].iter().any(|&side| book.side(side).order_count() > 0) {
+            return Err(format!("{} already has resting orders", symbol));
+        }
+        book.restore_snapshot(bytes)?;
+
+        let snapshot = book.snapshot();
+        for order in snapshot.bids.iter().chain(&snapshot.asks) {
+            if order.symbol != symbol {
+                book.side_mut(OrderSide::Buy).clear();
+                book.side_mut(OrderSide::Sell).clear();
+                return Err(format!("Order {} belongs to {}, not {}", order.id, order.symbol, symbol));
+            }
+        }
+        for order in snapshot.bids.iter().chain(&snapshot.asks) {
+            self.orders.insert(order.id, order.clone());
+            self.order_index.insert(order);
+            self.register_client_order_id(order);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::draws::new_event_id;
+use crate::engine::MatchingEngine;
+use crate::events::{CircuitBreakerTriggeredEvent, OrderEvent};
+use crate::trading_state::SymbolState;
+use crate::types::Trade;
+
+/// Halts a symbol for `cooldown` when a trade prints more than
+/// `max_move_percent` away from the oldest trade price seen within `window`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CircuitBreakerConfig {
+    pub max_move_percent: Decimal,
+    pub window: Duration,
+    pub cooldown: Duration,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct BreakerState {
+    prices: VecDeque<(DateTime<Utc>, Decimal)>,
+    halted_until: Option<DateTime<Utc>>,
+}
+
+impl MatchingEngine {
+    /// When the current circuit-breaker halt on `symbol` ends, if any.
+    pub fn circuit_breaker_halted_until(&self, symbol: &str) -> Option<DateTime<Utc>> {
+        self.circuit_breakers.get(symbol).and_then(|b| b.halted_until)
+    }
+
+    /// Feeds new trades to the symbol's breaker, halting the symbol on the
+    /// first one that moves too far. Auction prints only extend the window.
+    pub(crate) fn check_circuit_breaker(&self, symbol: &str, trades: &[Trade], events: &mut Vec<OrderEvent>) {
+        let Some(config) = self.symbols.get(symbol).and_then(|c| c.circuit_breaker) else {
+            return;
+        };
+        let mut breaker = self.circuit_breakers.entry(symbol.to_string()).or_default();
+
+        for trade in trades {
+            let cutoff = trade.created_at - config.window;
+            while breaker.prices.front().is_some_and(|(at, _)| *at < cutoff) {
+                breaker.prices.pop_front();
+            }
+            let reference = breaker.prices.front().map(|(_, price)| *price);
+            breaker.prices.push_back((trade.created_at, trade.price));
+
+            let Some(reference) = reference.filter(|r| *r > Decimal::ZERO) else {
+                continue;
+            };
+            if self.symbol_state(symbol) != SymbolState::Trading {
+                continue;
+            }
+            // A move too large to represent is past any limit
+            let move_percent = ((trade.price - reference).abs() / reference)
+                .checked_mul(Decimal::ONE_HUNDRED)
+                .unwrap_or(Decimal::MAX);
+            if move_percent <= config.max_move_percent {
+                continue;
+            }
+
+            let halted_until = trade.created_at + config.cooldown;
+            breaker.halted_until = Some(halted_until);
+            events.push(OrderEvent::CircuitBreakerTriggered(CircuitBreakerTriggeredEvent {
+                event_id: new_event_id(),
+                prev_hash: None,
+                symbol: symbol.to_string(),
+                reference_price: reference,
+                trigger_price: trade.price,
+                move_percent,
+                halted_until,
+                timestamp: self.clock.now(),
+            }));
+            self.transition_symbol_state(symbol, SymbolState::Halted, events);
+        }
+    }
+
+    /// Resumes `symbol` once its circuit-breaker cool-down has elapsed.
+    pub(crate) async fn lift_expired_circuit_breaker(&self, symbol: &str) -> Result<Vec<OrderEvent>, String> {
+        let expired = self
+            .circuit_breaker_halted_until(symbol)
+            .is_some_and(|until| self.clock.now() >= until);
+        if !expired || self.symbol_state(symbol) != SymbolState::Halted {
+            return Ok(Vec::new());
+        }
+        // Boxed because resuming replays queued commands through the
+        // handlers that called us
+        Box::pin(self.resume(symbol)).await
+    }
+
+    /// Forgets the breaker's halt and price window; the reference price
+    /// starts over after any resume.
+    pub(crate) fn reset_circuit_breaker(&self, symbol: &str) {
+        self.circuit_breakers.remove(symbol);
+    }
+}
@@ -0,0 +1,1458 @@
+//! Protobuf encoding of commands, events and trades, following
+//! `proto/matching_engine.proto`, for systems that would rather not depend
+//! on the serde JSON shape. Every command and event has a message.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::candles::Candle;
+use crate::commands::{
+    AmendOrderCommand, BasketExecution, BasketValidation, CancelOrderCommand, CancelReplaceCommand, OrderCommand,
+    PlaceBasketCommand, PlaceBracketOrderCommand, PlaceOrderCommand,
+};
+use crate::daily_limits::DailyLimitKind;
+use crate::events::{
+    AuctionPriceDeterminedEvent, BookLevelEvictedEvent, BracketOrderActivatedEvent, BracketOrderCompletedEvent,
+    BracketOrderPlacedEvent, CandleClosedEvent, CircuitBreakerTriggeredEvent, DailyLimitReachedEvent, FeeChargedEvent,
+    InvariantViolatedEvent, KillSwitchActivatedEvent, KillSwitchReleasedEvent, LiquidationTriggeredEvent,
+    OrderCanceledEvent, OrderEvent, OrderFilledEvent, OrderMatchedEvent, OrderPartiallyFilledEvent, OrderPlacedEvent,
+    OrderRejectedEvent, OrderUpdatedEvent, PersistenceHaltedEvent, PersistenceResumedEvent, RateLimitExceededEvent,
+    SymbolStateChangedEvent, SyntheticTradeExecutedEvent, TradeBustedEvent,
+};
+use crate::fees::Liquidity;
+use crate::invariants::InvariantViolation;
+use crate::kill_switch::KillSwitchTarget;
+use crate::persistence::HaltScope;
+use crate::trading_state::SymbolState;
+use crate::types::{OrderSide, OrderStatus, OrderType, Trade};
+
+const VARINT: u8 = 0;
+const FIXED64: u8 = 1;
+const LENGTH_DELIMITED: u8 = 2;
+const FIXED32: u8 = 5;
+
+pub fn encode_command(command: &OrderCommand) -> Vec<u8> {
+    let mut out = Writer::default();
+    match command {
+        OrderCommand::PlaceOrder(cmd) => out.message(1, place_order(cmd)),
+        OrderCommand::CancelOrder(cmd) => out.message(2, cancel_order(cmd)),
+        OrderCommand::PlaceBracketOrder(cmd) => out.message(3, place_bracket_order(cmd)),
+        OrderCommand::CancelReplace(cmd) => out.message(4, cancel_replace(cmd)),
+        OrderCommand::AmendOrder(cmd) => out.message(5, amend_order(cmd)),
+        OrderCommand::PlaceBasket(cmd) => out.message(6, place_basket(cmd)),
+    }
+    out.buf
+}
+
+pub fn decode_command(bytes: &[u8]) -> Result<OrderCommand, String> {
+    let (field, body) = oneof(bytes, "OrderCommand")?;
+    let fields = Fields::parse(body)?;
+    match field {
+        1 => Ok(OrderCommand::PlaceOrder(place_order_from(&fields)?)),
+        2 => Ok(OrderCommand::CancelOrder(cancel_order_from(&fields)?)),
+        3 => Ok(OrderCommand::PlaceBracketOrder(PlaceBracketOrderCommand {
+            bracket_id: fields.uuid(1)?,
+            entry: place_order_from(&fields.message(2)?)?,
+            stop_loss_order_id: fields.uuid(3)?,
+            stop_loss_price: fields.decimal(4)?,
+            take_profit_order_id: fields.uuid(5)?,
+            take_profit_price: fields.decimal(6)?,
+        })),
+        4 => Ok(OrderCommand::CancelReplace(CancelReplaceCommand {
+            order_id: fields.uuid(1)?,
+            client_order_id: fields.opt_string(2)?,
+            user_id: fields.uuid(3)?,
+            symbol: fields.string(4)?,
+            new_order_id: fields.uuid(5)?,
+            new_client_order_id: fields.opt_string(6)?,
+            new_price: fields.opt_decimal(7)?,
+            new_quantity: fields.decimal(8)?,
+            timestamp: fields.timestamp(9),
+        })),
+        5 => Ok(OrderCommand::AmendOrder(AmendOrderCommand {
+            order_id: fields.uuid(1)?,
+            client_order_id: fields.opt_string(2)?,
+            user_id: fields.uuid(3)?,
+            symbol: fields.string(4)?,
+            new_price: fields.opt_decimal(5)?,
+            new_quantity: fields.opt_decimal(6)?,
+            timestamp: fields.timestamp(7),
+        })),
+        6 => Ok(OrderCommand::PlaceBasket(PlaceBasketCommand {
+            basket_id: fields.uuid(1)?,
+            legs: repeated(body, 2)?
+                .into_iter()
+                .map(|leg| place_order_from(&Fields::parse(leg)?))
+                .collect::<Result<_, _>>()?,
+            validation: basket_validation_from(fields.varint(3))?,
+            execution: basket_execution_from(fields.varint(4))?,
+            timestamp: fields.timestamp(5),
+        })),
+        other => Err(format!("Unknown OrderCommand field {}", other)),
+    }
+}
+
+pub fn encode_event(event: &OrderEvent) -> Vec<u8> {
+    let mut out = Writer::default();
+    match event {
+        OrderEvent::OrderPlaced(e) => out.message(1, order_placed(e)),
+        OrderEvent::OrderCanceled(e) => out.message(2, order_canceled(e)),
+        OrderEvent::OrderUpdated(e) => out.message(3, order_updated(e)),
+        OrderEvent::OrderMatched(e) => out.message(4, order_matched(e)),
+        OrderEvent::OrderRejected(e) => out.message(5, order_rejected(e)),
+        OrderEvent::OrderPartiallyFilled(e) => out.message(6, order_partially_filled(e)),
+        OrderEvent::OrderFilled(e) => out.message(7, order_filled(e)),
+        OrderEvent::BracketOrderPlaced(e) => out.message(8, bracket_order_placed(e)),
+        OrderEvent::BracketOrderActivated(e) => out.message(9, bracket_order_activated(e)),
+        OrderEvent::BracketOrderCompleted(e) => out.message(10, bracket_order_completed(e)),
+        OrderEvent::PersistenceHalted(e) => out.message(11, persistence_halted(e)),
+        OrderEvent::PersistenceResumed(e) => out.message(12, persistence_resumed(e)),
+        OrderEvent::SyntheticTradeExecuted(e) => out.message(13, synthetic_trade_executed(e)),
+        OrderEvent::AuctionPriceDetermined(e) => out.message(14, auction_price_determined(e)),
+        OrderEvent::SymbolStateChanged(e) => out.message(15, symbol_state_changed(e)),
+        OrderEvent::CircuitBreakerTriggered(e) => out.message(16, circuit_breaker_triggered(e)),
+        OrderEvent::InvariantViolated(e) => out.message(17, invariant_violated(e)),
+        OrderEvent::RateLimitExceeded(e) => out.message(18, rate_limit_exceeded(e)),
+        OrderEvent::BookLevelEvicted(e) => out.message(19, book_level_evicted(e)),
+        OrderEvent::CandleClosed(e) => out.message(20, candle_closed(e)),
+        OrderEvent::KillSwitchActivated(e) => out.message(21, kill_switch_activated(e)),
+        OrderEvent::KillSwitchReleased(e) => out.message(22, kill_switch_released(e)),
+        OrderEvent::LiquidationTriggered(e) => out.message(23, liquidation_triggered(e)),
+        OrderEvent::TradeBusted(e) => out.message(24, trade_busted(e)),
+        OrderEvent::FeeCharged(e) => out.message(25, fee_charged(e)),
+        OrderEvent::DailyLimitReached(e) => out.message(26, daily_limit_reached(e)),
+    }
+    out.buf
+}
+
+pub fn decode_event(bytes: &[u8]) -> Result<OrderEvent, String> {
+    let (field, body) = oneof(bytes, "OrderEvent")?;
+    let fields = Fields::parse(body)?;
+    match field {
+        1 => Ok(OrderEvent::OrderPlaced(OrderPlacedEvent {
+            event_id: fields.opt_uuid(11)?.unwrap_or_default(),
+            prev_hash: fields.opt_string(12)?,
+            order_id: fields.uuid(1)?,
+            user_id: fields.uuid(2)?,
+            symbol: fields.string(3)?,
+            order_type: order_type_from(fields.varint(4))?,
+            side: side_from(fields.varint(5))?,
+            price: fields.opt_decimal(6)?,
+            quantity: fields.decimal(7)?,
+            status: status_from(fields.varint(8))?,
+            timestamp: fields.timestamp(9),
+            replaces_order_id: fields.opt_uuid(10)?,
+        })),
+        2 => Ok(OrderEvent::OrderCanceled(OrderCanceledEvent {
+            event_id: fields.opt_uuid(6)?.unwrap_or_default(),
+            prev_hash: fields.opt_string(7)?,
+            order_id: fields.uuid(1)?,
+            user_id: fields.uuid(2)?,
+            symbol: fields.string(3)?,
+            timestamp: fields.timestamp(4),
+            replaced_by_order_id: fields.opt_uuid(5)?,
+        })),
+        3 => Ok(OrderEvent::OrderUpdated(OrderUpdatedEvent {
+            event_id: fields.opt_uuid(8)?.unwrap_or_default(),
+            prev_hash: fields.opt_string(9)?,
+            order_id: fields.uuid(1)?,
+            user_id: fields.uuid(2)?,
+            symbol: fields.string(3)?,
+            new_price: fields.opt_decimal(4)?,
+            new_quantity: fields.opt_decimal(5)?,
+            timestamp: fields.timestamp(6),
+            retained_priority: fields.varint(7) != 0,
+        })),
+        4 => Ok(OrderEvent::OrderMatched(OrderMatchedEvent {
+            event_id: fields.opt_uuid(8)?.unwrap_or_default(),
+            prev_hash: fields.opt_string(9)?,
+            order_id: fields.uuid(1)?,
+            matched_order_id: fields.uuid(2)?,
+            symbol: fields.string(3)?,
+            price: fields.decimal(4)?,
+            quantity: fields.decimal(5)?,
+            side: side_from(fields.varint(6))?,
+            timestamp: fields.timestamp(7),
+        })),
+        5 => Ok(OrderEvent::OrderRejected(OrderRejectedEvent {
+            event_id: fields.opt_uuid(6)?.unwrap_or_default(),
+            prev_hash: fields.opt_string(7)?,
+            order_id: fields.uuid(1)?,
+            user_id: fields.uuid(2)?,
+            symbol: fields.string(3)?,
+            reason: fields.string(4)?,
+            timestamp: fields.timestamp(5),
+        })),
+        6 => Ok(OrderEvent::OrderPartiallyFilled(OrderPartiallyFilledEvent {
+            event_id: fields.opt_uuid(6)?.unwrap_or_default(),
+            prev_hash: fields.opt_string(7)?,
+            order_id: fields.uuid(1)?,
+            symbol: fields.string(2)?,
+            filled_quantity: fields.decimal(3)?,
+            remaining_quantity: fields.decimal(4)?,
+            timestamp: fields.timestamp(5),
+        })),
+        7 => Ok(OrderEvent::OrderFilled(OrderFilledEvent {
+            event_id: fields.opt_uuid(5)?.unwrap_or_default(),
+            prev_hash: fields.opt_string(6)?,
+            order_id: fields.uuid(1)?,
+            symbol: fields.string(2)?,
+            filled_quantity: fields.decimal(3)?,
+            timestamp: fields.timestamp(4),
+        })),
+        8 => Ok(OrderEvent::BracketOrderPlaced(BracketOrderPlacedEvent {
+            event_id: fields.opt_uuid(10)?.unwrap_or_default(),
+            prev_hash: fields.opt_string(11)?,
+            bracket_id: fields.uuid(1)?,
+            entry_order_id: fields.uuid(2)?,
+            stop_loss_order_id: fields.uuid(3)?,
+            take_profit_order_id: fields.uuid(4)?,
+            user_id: fields.uuid(5)?,
+            symbol: fields.string(6)?,
+            stop_loss_price: fields.decimal(7)?,
+            take_profit_price: fields.decimal(8)?,
+            timestamp: fields.timestamp(9),
+        })),
+        9 => Ok(OrderEvent::BracketOrderActivated(BracketOrderActivatedEvent {
+            event_id: fields.opt_uuid(7)?.unwrap_or_default(),
+            prev_hash: fields.opt_string(8)?,
+            bracket_id: fields.uuid(1)?,
+            entry_order_id: fields.uuid(2)?,
+            stop_loss_order_id: fields.uuid(3)?,
+            take_profit_order_id: fields.uuid(4)?,
+            symbol: fields.string(5)?,
+            timestamp: fields.timestamp(6),
+        })),
+        10 => Ok(OrderEvent::BracketOrderCompleted(BracketOrderCompletedEvent {
+            event_id: fields.opt_uuid(7)?.unwrap_or_default(),
+            prev_hash: fields.opt_string(8)?,
+            bracket_id: fields.uuid(1)?,
+            entry_order_id: fields.uuid(2)?,
+            executed_order_id: fields.uuid(3)?,
+            canceled_order_id: fields.uuid(4)?,
+            symbol: fields.string(5)?,
+            timestamp: fields.timestamp(6),
+        })),
+        11 => Ok(OrderEvent::PersistenceHalted(PersistenceHaltedEvent {
+            event_id: fields.opt_uuid(6)?.unwrap_or_default(),
+            prev_hash: fields.opt_string(7)?,
+            scope: halt_scope_from(fields.varint(1))?,
+            symbol: fields.opt_string(2)?,
+            reason: fields.string(3)?,
+            buffered_events: fields.count(4)?,
+            timestamp: fields.timestamp(5),
+        })),
+        12 => Ok(OrderEvent::PersistenceResumed(PersistenceResumedEvent {
+            event_id: fields.opt_uuid(5)?.unwrap_or_default(),
+            prev_hash: fields.opt_string(6)?,
+            scope: halt_scope_from(fields.varint(1))?,
+            symbol: fields.opt_string(2)?,
+            flushed_events: fields.count(3)?,
+            timestamp: fields.timestamp(4),
+        })),
+        13 => Ok(OrderEvent::SyntheticTradeExecuted(SyntheticTradeExecutedEvent {
+            event_id: fields.opt_uuid(11)?.unwrap_or_default(),
+            prev_hash: fields.opt_string(12)?,
+            order_id: fields.uuid(1)?,
+            user_id: fields.uuid(2)?,
+            symbol: fields.string(3)?,
+            side: side_from(fields.varint(4))?,
+            price: fields.decimal(5)?,
+            quantity: fields.decimal(6)?,
+            base_leg_order_id: fields.uuid(7)?,
+            quote_leg_order_id: fields.uuid(8)?,
+            leg_trade_ids: repeated_uuids(body, 9)?,
+            timestamp: fields.timestamp(10),
+        })),
+        14 => Ok(OrderEvent::AuctionPriceDetermined(AuctionPriceDeterminedEvent {
+            event_id: fields.opt_uuid(6)?.unwrap_or_default(),
+            prev_hash: fields.opt_string(7)?,
+            symbol: fields.string(1)?,
+            price: fields.decimal(2)?,
+            volume: fields.decimal(3)?,
+            imbalance: fields.decimal(4)?,
+            timestamp: fields.timestamp(5),
+        })),
+        15 => Ok(OrderEvent::SymbolStateChanged(SymbolStateChangedEvent {
+            event_id: fields.opt_uuid(5)?.unwrap_or_default(),
+            prev_hash: fields.opt_string(6)?,
+            symbol: fields.string(1)?,
+            previous_state: symbol_state_from(fields.varint(2))?,
+            state: symbol_state_from(fields.varint(3))?,
+            timestamp: fields.timestamp(4),
+        })),
+        16 => Ok(OrderEvent::CircuitBreakerTriggered(CircuitBreakerTriggeredEvent {
+            event_id: fields.opt_uuid(7)?.unwrap_or_default(),
+            prev_hash: fields.opt_string(8)?,
+            symbol: fields.string(1)?,
+            reference_price: fields.decimal(2)?,
+            trigger_price: fields.decimal(3)?,
+            move_percent: fields.decimal(4)?,
+            halted_until: fields.timestamp(5),
+            timestamp: fields.timestamp(6),
+        })),
+        17 => Ok(OrderEvent::InvariantViolated(InvariantViolatedEvent {
+            event_id: fields.opt_uuid(4)?.unwrap_or_default(),
+            prev_hash: fields.opt_string(5)?,
+            symbol: fields.string(1)?,
+            violation: invariant_violation_from(fields.required_bytes(2)?)?,
+            timestamp: fields.timestamp(3),
+        })),
+        18 => Ok(OrderEvent::RateLimitExceeded(RateLimitExceededEvent {
+            event_id: fields.opt_uuid(5)?.unwrap_or_default(),
+            prev_hash: fields.opt_string(6)?,
+            user_id: fields.uuid(1)?,
+            symbol: fields.string(2)?,
+            max_orders_per_second: u32::try_from(fields.varint(3)).map_err(|e| e.to_string())?,
+            timestamp: fields.timestamp(4),
+        })),
+        19 => Ok(OrderEvent::BookLevelEvicted(BookLevelEvictedEvent {
+            event_id: fields.opt_uuid(6)?.unwrap_or_default(),
+            prev_hash: fields.opt_string(7)?,
+            symbol: fields.string(1)?,
+            side: side_from(fields.varint(2))?,
+            price: fields.decimal(3)?,
+            order_ids: repeated_uuids(body, 4)?,
+            timestamp: fields.timestamp(5),
+        })),
+        20 => Ok(OrderEvent::CandleClosed(CandleClosedEvent {
+            event_id: fields.opt_uuid(3)?.unwrap_or_default(),
+            prev_hash: fields.opt_string(4)?,
+            candle: candle_from(&fields.message(1)?)?,
+            timestamp: fields.timestamp(2),
+        })),
+        21 => Ok(OrderEvent::KillSwitchActivated(KillSwitchActivatedEvent {
+            event_id: fields.opt_uuid(5)?.unwrap_or_default(),
+            prev_hash: fields.opt_string(6)?,
+            target: kill_switch_target_from(fields.required_bytes(1)?)?,
+            reason: fields.string(2)?,
+            canceled_order_ids: repeated_uuids(body, 3)?,
+            timestamp: fields.timestamp(4),
+        })),
+        22 => Ok(OrderEvent::KillSwitchReleased(KillSwitchReleasedEvent {
+            event_id: fields.opt_uuid(3)?.unwrap_or_default(),
+            prev_hash: fields.opt_string(4)?,
+            target: kill_switch_target_from(fields.required_bytes(1)?)?,
+            timestamp: fields.timestamp(2),
+        })),
+        23 => Ok(OrderEvent::LiquidationTriggered(LiquidationTriggeredEvent {
+            event_id: fields.opt_uuid(6)?.unwrap_or_default(),
+            prev_hash: fields.opt_string(7)?,
+            user_id: fields.uuid(1)?,
+            equity: fields.decimal(2)?,
+            maintenance_margin: fields.decimal(3)?,
+            symbols: repeated_strings(body, 4)?,
+            timestamp: fields.timestamp(5),
+        })),
+        24 => Ok(OrderEvent::TradeBusted(TradeBustedEvent {
+            event_id: fields.opt_uuid(10)?.unwrap_or_default(),
+            prev_hash: fields.opt_string(11)?,
+            trade_id: fields.uuid(1)?,
+            symbol: fields.string(2)?,
+            price: fields.decimal(3)?,
+            quantity: fields.decimal(4)?,
+            side: side_from(fields.varint(5))?,
+            taker_order_id: fields.uuid(6)?,
+            maker_order_id: fields.uuid(7)?,
+            reason: fields.string(8)?,
+            timestamp: fields.timestamp(9),
+        })),
+        25 => Ok(OrderEvent::FeeCharged(FeeChargedEvent {
+            event_id: fields.opt_uuid(11)?.unwrap_or_default(),
+            prev_hash: fields.opt_string(12)?,
+            trade_id: fields.uuid(1)?,
+            order_id: fields.uuid(2)?,
+            user_id: fields.uuid(3)?,
+            symbol: fields.string(4)?,
+            liquidity: liquidity_from(fields.varint(5))?,
+            asset: fields.string(6)?,
+            amount: fields.decimal(7)?,
+            referrer_id: fields.opt_uuid(8)?,
+            referral_amount: fields.decimal(9)?,
+            timestamp: fields.timestamp(10),
+        })),
+        26 => Ok(OrderEvent::DailyLimitReached(DailyLimitReachedEvent {
+            event_id: fields.opt_uuid(7)?.unwrap_or_default(),
+            prev_hash: fields.opt_string(8)?,
+            user_id: fields.uuid(1)?,
+            limit: daily_limit_kind_from(fields.varint(2))?,
+            value: fields.decimal(3)?,
+            cap: fields.decimal(4)?,
+            session_start: fields.timestamp(5),
+            timestamp: fields.timestamp(6),
+        })),
+        other => Err(format!("Unknown OrderEvent field {}", other)),
+    }
+}
+
+pub fn encode_trade(trade: &Trade) -> Vec<u8> {
+    let mut out = Writer::default();
+    out.uuid(1, trade.id);
+    out.string(2, &trade.symbol);
+    out.decimal(3, trade.price);
+    out.decimal(4, trade.quantity);
+    out.varint(5, side_code(trade.side));
+    out.uuid(6, trade.taker_order_id);
+    out.uuid(7, trade.maker_order_id);
+    out.timestamp(8, trade.created_at);
+    out.buf
+}
+
+pub fn decode_trade(bytes: &[u8]) -> Result<Trade, String> {
+    let fields = Fields::parse(bytes)?;
+    Ok(Trade {
+        id: fields.uuid(1)?,
+        symbol: fields.string(2)?,
+        price: fields.decimal(3)?,
+        quantity: fields.decimal(4)?,
+        side: side_from(fields.varint(5))?,
+        taker_order_id: fields.uuid(6)?,
+        maker_order_id: fields.uuid(7)?,
+        created_at: fields.timestamp(8),
+    })
+}
+
+/// Request and response messages of `service MatchingEngine`, for
+/// `crate::grpc`.
+#[cfg(feature = "grpc")]
+pub(crate) mod rpc {
+    use super::{
+        cancel_order, cancel_order_from, place_order, place_order_from, repeated, repeated_strings, Fields, Writer,
+    };
+    use crate::commands::{CancelOrderCommand, PlaceOrderCommand};
+    use crate::depth::{DepthLevel, L2Snapshot};
+    use crate::events::OrderEvent;
+    use crate::subscription::EventFilter;
+
+    pub(crate) fn encode_place_order(cmd: &PlaceOrderCommand) -> Vec<u8> {
+        place_order(cmd).buf
+    }
+
+    pub(crate) fn decode_place_order(bytes: &[u8]) -> Result<PlaceOrderCommand, String> {
+        place_order_from(&Fields::parse(bytes)?)
+    }
+
+    pub(crate) fn encode_cancel_order(cmd: &CancelOrderCommand) -> Vec<u8> {
+        cancel_order(cmd).buf
+    }
+
+    pub(crate) fn decode_cancel_order(bytes: &[u8]) -> Result<CancelOrderCommand, String> {
+        cancel_order_from(&Fields::parse(bytes)?)
+    }
+
+    /// `PlaceOrderResponse` and `CancelOrderResponse`.
+    pub(crate) fn encode_events(events: &[OrderEvent]) -> Vec<u8> {
+        let mut out = Writer::default();
+        for event in events {
+            out.bytes(1, &super::encode_event(event));
+        }
+        out.buf
+    }
+
+    pub(crate) fn decode_events(bytes: &[u8]) -> Result<Vec<OrderEvent>, String> {
+        repeated(bytes, 1)?.into_iter().map(super::decode_event).collect()
+    }
+
+    /// `SubscribeEventsRequest`.
+    pub(crate) fn encode_event_filter(filter: &EventFilter) -> Vec<u8> {
+        let mut out = Writer::default();
+        if let Some(symbol) = &filter.symbol {
+            out.string(1, symbol);
+        }
+        if let Some(user_id) = filter.user_id {
+            out.uuid(2, user_id);
+        }
+        for kind in filter.kinds.iter().flatten() {
+            out.string(3, kind);
+        }
+        out.buf
+    }
+
+    pub(crate) fn decode_event_filter(bytes: &[u8]) -> Result<EventFilter, String> {
+        let fields = Fields::parse(bytes)?;
+        let kinds = repeated_strings(bytes, 3)?;
+        Ok(EventFilter {
+            symbol: fields.opt_string(1)?,
+            user_id: fields.opt_uuid(2)?,
+            kinds: (!kinds.is_empty()).then_some(kinds),
+        })
+    }
+
+    /// `SubscribeDepthRequest`.
+    pub(crate) fn encode_depth_request(symbol: &str, levels: usize) -> Vec<u8> {
+        let mut out = Writer::default();
+        out.string(1, symbol);
+        out.varint(2, levels as u64);
+        out.buf
+    }
+
+    pub(crate) fn decode_depth_request(bytes: &[u8]) -> Result<(String, usize), String> {
+        let fields = Fields::parse(bytes)?;
+        let levels = usize::try_from(fields.varint(2)).map_err(|e| e.to_string())?;
+        Ok((fields.string(1)?, levels))
+    }
+
+    /// `DepthSnapshot`. Cumulative quantities are not carried.
+    pub(crate) fn encode_depth(snapshot: &L2Snapshot) -> Vec<u8> {
+        let mut out = Writer::default();
+        out.string(1, &snapshot.symbol);
+        for (field, side) in [(2, &snapshot.bids), (3, &snapshot.asks)] {
+            for level in side {
+                let mut entry = Writer::default();
+                entry.decimal(1, level.price);
+                entry.decimal(2, level.quantity);
+                entry.varint(3, level.order_count);
+                out.message(field, entry);
+            }
+        }
+        out.timestamp(4, snapshot.timestamp);
+        out.buf
+    }
+
+    pub(crate) fn decode_depth(bytes: &[u8]) -> Result<L2Snapshot, String> {
+        let fields = Fields::parse(bytes)?;
+        let side = |field| -> Result<Vec<DepthLevel>, String> {
+            repeated(bytes, field)?
+                .into_iter()
+                .map(|level| {
+                    let level = Fields::parse(level)?;
+                    Ok(DepthLevel {
+                        price: level.decimal(1)?,
+                        quantity: level.decimal(2)?,
+                        order_count: level.varint(3),
+                        cumulative_quantity: None,
+                    })
+                })
+                .collect()
+        };
+        Ok(L2Snapshot {
+            symbol: fields.string(1)?,
+            bids: side(2)?,
+            asks: side(3)?,
+            timestamp: fields.timestamp(4),
+        })
+    }
+}
+
+fn place_order_from(fields: &Fields) -> Result<PlaceOrderCommand, String> {
+    Ok(PlaceOrderCommand {
+        order_id: fields.uuid(1)?,
+        client_order_id: fields.opt_string(2)?,
+        user_id: fields.uuid(3)?,
+        symbol: fields.string(4)?,
+        order_type: order_type_from(fields.varint(5))?,
+        side: side_from(fields.varint(6))?,
+        price: fields.opt_decimal(7)?,
+        quantity: fields.decimal(8)?,
+        iceberg_visible_quantity: fields.opt_decimal(9)?,
+        stop_price: fields.opt_decimal(10)?,
+        trailing_stop_price: fields.opt_decimal(11)?,
+        displayed: fields.varint(12) == 0,
+        max_fills: fields.opt_varint(13).map(u32::try_from).transpose().map_err(|e| e.to_string())?,
+        referrer_id: fields.opt_uuid(16)?,
+        quote_quantity: fields.opt_decimal(17)?,
+        timestamp: fields.timestamp(14),
+    })
+}
+
+fn cancel_order_from(fields: &Fields) -> Result<CancelOrderCommand, String> {
+    Ok(CancelOrderCommand {
+        order_id: fields.uuid(1)?,
+        client_order_id: fields.opt_string(2)?,
+        user_id: fields.uuid(3)?,
+        symbol: fields.string(4)?,
+        timestamp: fields.timestamp(5),
+    })
+}
+
+fn candle_from(fields: &Fields) -> Result<Candle, String> {
+    Ok(Candle {
+        symbol: fields.string(1)?,
+        interval: Duration::from_nanos(fields.varint(2)),
+        open_time: fields.timestamp(3),
+        open: fields.decimal(4)?,
+        high: fields.decimal(5)?,
+        low: fields.decimal(6)?,
+        close: fields.decimal(7)?,
+        volume: fields.decimal(8)?,
+        trade_count: fields.count(9)?,
+    })
+}
+
+fn kill_switch_target_from(bytes: &[u8]) -> Result<KillSwitchTarget, String> {
+    let (field, value) = oneof(bytes, "KillSwitchTarget")?;
+    match field {
+        1 => Uuid::from_slice(value).map(KillSwitchTarget::User).map_err(|e| format!("Field 1: {}", e)),
+        2 => String::from_utf8(value.to_vec()).map(KillSwitchTarget::Symbol).map_err(|e| format!("Field 2: {}", e)),
+        other => Err(format!("Unknown KillSwitchTarget field {}", other)),
+    }
+}
+
+fn invariant_violation_from(bytes: &[u8]) -> Result<InvariantViolation, String> {
+    let (field, body) = oneof(bytes, "InvariantViolation")?;
+    let fields = Fields::parse(body)?;
+    match field {
+        1 => Ok(InvariantViolation::CrossedBook {
+            symbol: fields.string(1)?,
+            best_bid: fields.decimal(2)?,
+            best_ask: fields.decimal(3)?,
+        }),
+        2 => Ok(InvariantViolation::NegativeQuantity {
+            symbol: fields.string(1)?,
+            side: side_from(fields.varint(2))?,
+            price: fields.decimal(3)?,
+            quantity: fields.decimal(4)?,
+        }),
+        other => Err(format!("Unknown InvariantViolation field {}", other)),
+    }
+}
+
+fn place_order(cmd: &PlaceOrderCommand) -> Writer {
+    let mut out = Writer::default();
+    out.uuid(1, cmd.order_id);
+    out.opt_string(2, &cmd.client_order_id);
+    out.uuid(3, cmd.user_id);
+    out.string(4, &cmd.symbol);
+    out.varint(5, order_type_code(cmd.order_type));
+    out.varint(6, side_code(cmd.side));
+    out.opt_decimal(7, cmd.price);
+    out.decimal(8, cmd.quantity);
+    out.opt_decimal(9, cmd.iceberg_visible_quantity);
+    out.opt_decimal(10, cmd.stop_price);
+    out.opt_decimal(11, cmd.trailing_stop_price);
+    if !cmd.displayed {
+        out.varint(12, 1);
+    }
+    if let Some(max_fills) = cmd.max_fills {
+        out.varint(13, max_fills.into());
+    }
+    out.timestamp(14, cmd.timestamp);
+    out.opt_uuid(16, cmd.referrer_id);
+    out.opt_decimal(17, cmd.quote_quantity);
+    out
+}
+
+fn cancel_order(cmd: &CancelOrderCommand) -> Writer {
+    let mut out = Writer::default();
+    out.uuid(1, cmd.order_id);
+    out.opt_string(2, &cmd.client_order_id);
+    out.uuid(3, cmd.user_id);
+    out.string(4, &cmd.symbol);
+    out.timestamp(5, cmd.timestamp);
+    out
+}
+
+fn place_bracket_order(cmd: &PlaceBracketOrderCommand) -> Writer {
+    let mut out = Writer::default();
+    out.uuid(1, cmd.bracket_id);
+    out.message(2, place_order(&cmd.entry));
+    out.uuid(3, cmd.stop_loss_order_id);
+    out.decimal(4, cmd.stop_loss_price);
+    out.uuid(5, cmd.take_profit_order_id);
+    out.decimal(6, cmd.take_profit_price);
+    out
+}
+
+fn cancel_replace(cmd: &CancelReplaceCommand) -> Writer {
+    let mut out = Writer::default();
+    out.uuid(1, cmd.order_id);
+    out.opt_string(2, &cmd.client_order_id);
+    out.uuid(3, cmd.user_id);
+    out.string(4, &cmd.symbol);
+    out.uuid(5, cmd.new_order_id);
+    out.opt_string(6, &cmd.new_client_order_id);
+    out.opt_decimal(7, cmd.new_price);
+    out.decimal(8, cmd.new_quantity);
+    out.timestamp(9, cmd.timestamp);
+    out
+}
+
+fn amend_order(cmd: &AmendOrderCommand) -> Writer {
+    let mut out = Writer::default();
+    out.uuid(1, cmd.order_id);
+    out.opt_string(2, &cmd.client_order_id);
+    out.uuid(3, cmd.user_id);
+    out.string(4, &cmd.symbol);
+    out.opt_decimal(5, cmd.new_price);
+    out.opt_decimal(6, cmd.new_quantity);
+    out.timestamp(7, cmd.timestamp);
+    out
+}
+
+fn place_basket(cmd: &PlaceBasketCommand) -> Writer {
+    let mut out = Writer::default();
+    out.uuid(1, cmd.basket_id);
+    for leg in &cmd.legs {
+        out.message(2, place_order(leg));
+    }
+    out.varint(3, basket_validation_code(cmd.validation));
+    out.varint(4, basket_execution_code(cmd.execution));
+    out.timestamp(5, cmd.timestamp);
+    out
+}
+
+fn order_placed(e: &OrderPlacedEvent) -> Writer {
+    let mut out = Writer::default();
+    out.uuid(1, e.order_id);
+    out.uuid(2, e.user_id);
+    out.string(3, &e.symbol);
+    out.varint(4, order_type_code(e.order_type));
+    out.varint(5, side_code(e.side));
+    out.opt_decimal(6, e.price);
+    out.decimal(7, e.quantity);
+    out.varint(8, status_code(e.status));
+    out.timestamp(9, e.timestamp);
+    out.opt_uuid(10, e.replaces_order_id);
+    out.chain(11, e.event_id, &e.prev_hash);
+    out
+}
+
+fn order_canceled(e: &OrderCanceledEvent) -> Writer {
+    let mut out = Writer::default();
+    out.uuid(1, e.order_id);
+    out.uuid(2, e.user_id);
+    out.string(3, &e.symbol);
+    out.timestamp(4, e.timestamp);
+    out.opt_uuid(5, e.replaced_by_order_id);
+    out.chain(6, e.event_id, &e.prev_hash);
+    out
+}
+
+fn order_updated(e: &OrderUpdatedEvent) -> Writer {
+    let mut out = Writer::default();
+    out.uuid(1, e.order_id);
+    out.uuid(2, e.user_id);
+    out.string(3, &e.symbol);
+    out.opt_decimal(4, e.new_price);
+    out.opt_decimal(5, e.new_quantity);
+    out.timestamp(6, e.timestamp);
+    out.varint(7, e.retained_priority.into());
+    out.chain(8, e.event_id, &e.prev_hash);
+    out
+}
+
+fn order_matched(e: &OrderMatchedEvent) -> Writer {
+    let mut out = Writer::default();
+    out.uuid(1, e.order_id);
+    out.uuid(2, e.matched_order_id);
+    out.string(3, &e.symbol);
+    out.decimal(4, e.price);
+    out.decimal(5, e.quantity);
+    out.varint(6, side_code(e.side));
+    out.timestamp(7, e.timestamp);
+    out.chain(8, e.event_id, &e.prev_hash);
+    out
+}
+
+fn order_rejected(e: &OrderRejectedEvent) -> Writer {
+    let mut out = Writer::default();
+    out.uuid(1, e.order_id);
+    out.uuid(2, e.user_id);
+    out.string(3, &e.symbol);
+    out.string(4, &e.reason);
+    out.timestamp(5, e.timestamp);
+    out.chain(6, e.event_id, &e.prev_hash);
+    out
+}
+
+fn order_partially_filled(e: &OrderPartiallyFilledEvent) -> Writer {
+    let mut out = Writer::default();
+    out.uuid(1, e.order_id);
+    out.string(2, &e.symbol);
+    out.decimal(3, e.filled_quantity);
+    out.decimal(4, e.remaining_quantity);
+    out.timestamp(5, e.timestamp);
+    out.chain(6, e.event_id, &e.prev_hash);
+    out
+}
+
+fn order_filled(e: &OrderFilledEvent) -> Writer {
+    let mut out = Writer::default();
+    out.uuid(1, e.order_id);
+    out.string(2, &e.symbol);
+    out.decimal(3, e.filled_quantity);
+    out.timestamp(4, e.timestamp);
+    out.chain(5, e.event_id, &e.prev_hash);
+    out
+}
+
+fn bracket_order_placed(e: &BracketOrderPlacedEvent) -> Writer {
+    let mut out = Writer::default();
+    out.uuid(1, e.bracket_id);
+    out.uuid(2, e.entry_order_id);
+    out.uuid(3, e.stop_loss_order_id);
+    out.uuid(4, e.take_profit_order_id);
+    out.uuid(5, e.user_id);
+    out.string(6, &e.symbol);
+    out.decimal(7, e.stop_loss_price);
+    out.decimal(8, e.take_profit_price);
+    out.timestamp(9, e.timestamp);
+    out.chain(10, e.event_id, &e.prev_hash);
+    out
+}
+
+fn bracket_order_activated(e: &BracketOrderActivatedEvent) -> Writer {
+    let mut out = Writer::default();
+    out.uuid(1, e.bracket_id);
+    out.uuid(2, e.entry_order_id);
+    out.uuid(3, e.stop_loss_order_id);
+    out.uuid(4, e.take_profit_order_id);
+    out.string(5, &e.symbol);
+    out.timestamp(6, e.timestamp);
+    out.chain(7, e.event_id, &e.prev_hash);
+    out
+}
+
+fn bracket_order_completed(e: &BracketOrderCompletedEvent) -> Writer {
+    let mut out = Writer::default();
+    out.uuid(1, e.bracket_id);
+    out.uuid(2, e.entry_order_id);
+    out.uuid(3, e.executed_order_id);
+    out.uuid(4, e.canceled_order_id);
+    out.string(5, &e.symbol);
+    out.timestamp(6, e.timestamp);
+    out.chain(7, e.event_id, &e.prev_hash);
+    out
+}
+
+fn persistence_halted(e: &PersistenceHaltedEvent) -> Writer {
+    let mut out = Writer::default();
+    out.varint(1, halt_scope_code(e.scope));
+    out.opt_string(2, &e.symbol);
+    out.string(3, &e.reason);
+    out.varint(4, e.buffered_events as u64);
+    out.timestamp(5, e.timestamp);
+    out.chain(6, e.event_id, &e.prev_hash);
+    out
+}
+
+fn persistence_resumed(e: &PersistenceResumedEvent) -> Writer {
+    let mut out = Writer::default();
+    out.varint(1, halt_scope_code(e.scope));
+    out.opt_string(2, &e.symbol);
+    out.varint(3, e.flushed_events as u64);
+    out.timestamp(4, e.timestamp);
+    out.chain(5, e.event_id, &e.prev_hash);
+    out
+}
+
+fn synthetic_trade_executed(e: &SyntheticTradeExecutedEvent) -> Writer {
+    let mut out = Writer::default();
+    out.uuid(1, e.order_id);
+    out.uuid(2, e.user_id);
+    out.string(3, &e.symbol);
+    out.varint(4, side_code(e.side));
+    out.decimal(5, e.price);
+    out.decimal(6, e.quantity);
+    out.uuid(7, e.base_leg_order_id);
+    out.uuid(8, e.quote_leg_order_id);
+    for trade_id in &e.leg_trade_ids {
+        out.uuid(9, *trade_id);
+    }
+    out.timestamp(10, e.timestamp);
+    out.chain(11, e.event_id, &e.prev_hash);
+    out
+}
+
+fn auction_price_determined(e: &AuctionPriceDeterminedEvent) -> Writer {
+    let mut out = Writer::default();
+    out.string(1, &e.symbol);
+    out.decimal(2, e.price);
+    out.decimal(3, e.volume);
+    out.decimal(4, e.imbalance);
+    out.timestamp(5, e.timestamp);
+    out.chain(6, e.event_id, &e.prev_hash);
+    out
+}
+
+fn symbol_state_changed(e: &SymbolStateChangedEvent) -> Writer {
+    let mut out = Writer::default();
+    out.string(1, &e.symbol);
+    out.varint(2, symbol_state_code(e.previous_state));
+    out.varint(3, symbol_state_code(e.state));
+    out.timestamp(4, e.timestamp);
+    out.chain(5, e.event_id, &e.prev_hash);
+    out
+}
+
+fn circuit_breaker_triggered(e: &CircuitBreakerTriggeredEvent) -> Writer {
+    let mut out = Writer::default();
+    out.string(1, &e.symbol);
+    out.decimal(2, e.reference_price);
+    out.decimal(3, e.trigger_price);
+    out.decimal(4, e.move_percent);
+    out.timestamp(5, e.halted_until);
+    out.timestamp(6, e.timestamp);
+    out.chain(7, e.event_id, &e.prev_hash);
+    out
+}
+
+fn invariant_violated(e: &InvariantViolatedEvent) -> Writer {
+    let mut violation = Writer::default();
+    match &e.violation {
+        InvariantViolation::CrossedBook { symbol, best_bid, best_ask } => {
+            let mut crossed = Writer::default();
+            crossed.string(1, symbol);
+            crossed.decimal(2, *best_bid);
+            crossed.decimal(3, *best_ask);
+            violation.message(1, crossed);
+        }
+        InvariantViolation::NegativeQuantity { symbol, side, price, quantity } => {
+            let mut negative = Writer::default();
+            negative.string(1, symbol);
+            negative.varint(2, side_code(*side));
+            negative.decimal(3, *price);
+            negative.decimal(4, *quantity);
+            violation.message(2, negative);
+        }
+    }
+    let mut out = Writer::default();
+    out.string(1, &e.symbol);
+    out.message(2, violation);
+    out.timestamp(3, e.timestamp);
+    out.chain(4, e.event_id, &e.prev_hash);
+    out
+}
+
+fn rate_limit_exceeded(e: &RateLimitExceededEvent) -> Writer {
+    let mut out = Writer::default();
+    out.uuid(1, e.user_id);
+    out.string(2, &e.symbol);
+    out.varint(3, e.max_orders_per_second.into());
+    out.timestamp(4, e.timestamp);
+    out.chain(5, e.event_id, &e.prev_hash);
+    out
+}
+
+fn book_level_evicted(e: &BookLevelEvictedEvent) -> Writer {
+    let mut out = Writer::default();
+    out.string(1, &e.symbol);
+    out.varint(2, side_code(e.side));
+    out.decimal(3, e.price);
+    for order_id in &e.order_ids {
+        out.uuid(4, *order_id);
+    }
+    out.timestamp(5, e.timestamp);
+    out.chain(6, e.event_id, &e.prev_hash);
+    out
+}
+
+fn candle_closed(e: &CandleClosedEvent) -> Writer {
+    let candle = &e.candle;
+    let mut message = Writer::default();
+    message.string(1, &candle.symbol);
+    message.varint(2, candle.interval.as_nanos() as u64);
+    message.timestamp(3, candle.open_time);
+    message.decimal(4, candle.open);
+    message.decimal(5, candle.high);
+    message.decimal(6, candle.low);
+    message.decimal(7, candle.close);
+    message.decimal(8, candle.volume);
+    message.varint(9, candle.trade_count as u64);
+    let mut out = Writer::default();
+    out.message(1, message);
+    out.timestamp(2, e.timestamp);
+    out.chain(3, e.event_id, &e.prev_hash);
+    out
+}
+
+fn kill_switch_target(target: &KillSwitchTarget) -> Writer {
+    let mut out = Writer::default();
+    match target {
+        KillSwitchTarget::User(user_id) => out.uuid(1, *user_id),
+        KillSwitchTarget::Symbol(symbol) => out.string(2, symbol),
+    }
+    out
+}
+
+fn kill_switch_activated(e: &KillSwitchActivatedEvent) -> Writer {
+    let mut out = Writer::default();
+    out.message(1, kill_switch_target(&e.target));
+    out.string(2, &e.reason);
+    for order_id in &e.canceled_order_ids {
+        out.uuid(3, *order_id);
+    }
+    out.timestamp(4, e.timestamp);
+    out.chain(5, e.event_id, &e.prev_hash);
+    out
+}
+
+fn kill_switch_released(e: &KillSwitchReleasedEvent) -> Writer {
+    let mut out = Writer::default();
+    out.message(1, kill_switch_target(&e.target));
+    out.timestamp(2, e.timestamp);
+    out.chain(3, e.event_id, &e.prev_hash);
+    out
+}
+
+fn liquidation_triggered(e: &LiquidationTriggeredEvent) -> Writer {
+    let mut out = Writer::default();
+    out.uuid(1, e.user_id);
+    out.decimal(2, e.equity);
+    out.decimal(3, e.maintenance_margin);
+    for symbol in &e.symbols {
+        out.string(4, symbol);
+    }
+    out.timestamp(5, e.timestamp);
+    out.chain(6, e.event_id, &e.prev_hash);
+    out
+}
+
+fn trade_busted(e: &TradeBustedEvent) -> Writer {
+    let mut out = Writer::default();
+    out.uuid(1, e.trade_id);
+    out.string(2, &e.symbol);
+    out.decimal(3, e.price);
+    out.decimal(4, e.quantity);
+    out.varint(5, side_code(e.side));
+    out.uuid(6, e.taker_order_id);
+    out.uuid(7, e.maker_order_id);
+    out.string(8, &e.reason);
+    out.timestamp(9, e.timestamp);
+    out.chain(10, e.event_id, &e.prev_hash);
+    out
+}
+
+fn fee_charged(e: &FeeChargedEvent) -> Writer {
+    let mut out = Writer::default();
+    out.uuid(1, e.trade_id);
+    out.uuid(2, e.order_id);
+    out.uuid(3, e.user_id);
+    out.string(4, &e.symbol);
+    out.varint(5, liquidity_code(e.liquidity));
+    out.string(6, &e.asset);
+    out.decimal(7, e.amount);
+    out.opt_uuid(8, e.referrer_id);
+    out.decimal(9, e.referral_amount);
+    out.timestamp(10, e.timestamp);
+    out.chain(11, e.event_id, &e.prev_hash);
+    out
+}
+
+fn daily_limit_reached(e: &DailyLimitReachedEvent) -> Writer {
+    let mut out = Writer::default();
+    out.uuid(1, e.user_id);
+    out.varint(2, daily_limit_kind_code(e.limit));
+    out.decimal(3, e.value);
+    out.decimal(4, e.cap);
+    out.timestamp(5, e.session_start);
+    out.timestamp(6, e.timestamp);
+    out.chain(7, e.event_id, &e.prev_hash);
+    out
+}
+
+// Enum values are offset by one so that 0 stays UNSPECIFIED
+fn order_type_code(order_type: OrderType) -> u64 {
+    match order_type {
+        OrderType::Market => 1,
+        OrderType::Limit => 2,
+        OrderType::StopLoss => 3,
+        OrderType::TakeProfit => 4,
+        OrderType::Iceberg => 5,
+        OrderType::TrailingStop => 6,
+    }
+}
+
+fn order_type_from(code: u64) -> Result<OrderType, String> {
+    match code {
+        1 => Ok(OrderType::Market),
+        2 => Ok(OrderType::Limit),
+        3 => Ok(OrderType::StopLoss),
+        4 => Ok(OrderType::TakeProfit),
+        5 => Ok(OrderType::Iceberg),
+        6 => Ok(OrderType::TrailingStop),
+        other => Err(format!("Invalid order type {}", other)),
+    }
+}
+
+fn side_code(side: OrderSide) -> u64 {
+    match side {
+        OrderSide::Buy => 1,
+        OrderSide::Sell => 2,
+    }
+}
+
+fn side_from(code: u64) -> Result<OrderSide, String> {
+    match code {
+        1 => Ok(OrderSide::Buy),
+        2 => Ok(OrderSide::Sell),
+        other => Err(format!("Invalid order side {}", other)),
+    }
+}
+
+fn status_code(status: OrderStatus) -> u64 {
+    match status {
+        OrderStatus::Pending => 1,
+        OrderStatus::Active => 2,
+        OrderStatus::PartiallyFilled => 3,
+        OrderStatus::Filled => 4,
+        OrderStatus::Canceled => 5,
+        OrderStatus::Rejected => 6,
+    }
+}
+
+fn status_from(code: u64) -> Result<OrderStatus, String> {
+    match code {
+        1 => Ok(OrderStatus::Pending),
+        2 => Ok(OrderStatus::Active),
+        3 => Ok(OrderStatus::PartiallyFilled),
+        4 => Ok(OrderStatus::Filled),
+        5 => Ok(OrderStatus::Canceled),
+        6 => Ok(OrderStatus::Rejected),
+        other => Err(format!("Invalid order status {}", other)),
+    }
+}
+
+fn halt_scope_code(scope: HaltScope) -> u64 {
+    match scope {
+        HaltScope::Symbol => 1,
+        HaltScope::Engine => 2,
+    }
+}
+
+fn halt_scope_from(code: u64) -> Result<HaltScope, String> {
+    match code {
+        1 => Ok(HaltScope::Symbol),
+        2 => Ok(HaltScope::Engine),
+        other => Err(format!("Invalid halt scope {}", other)),
+    }
+}
+
+fn symbol_state_code(state: SymbolState) -> u64 {
+    match state {
+        SymbolState::Trading => 1,
+        SymbolState::Halted => 2,
+        SymbolState::AuctionOnly => 3,
+        SymbolState::CancelOnly => 4,
+        SymbolState::Closed => 5,
+        SymbolState::Delisted => 6,
+    }
+}
+
+fn symbol_state_from(code: u64) -> Result<SymbolState, String> {
+    match code {
+        1 => Ok(SymbolState::Trading),
+        2 => Ok(SymbolState::Halted),
+        3 => Ok(SymbolState::AuctionOnly),
+        4 => Ok(SymbolState::CancelOnly),
+        5 => Ok(SymbolState::Closed),
+        6 => Ok(SymbolState::Delisted),
+        other => Err(format!("Invalid symbol state {}", other)),
+    }
+}
+
+fn liquidity_code(liquidity: Liquidity) -> u64 {
+    match liquidity {
+        Liquidity::Maker => 1,
+        Liquidity::Taker => 2,
+    }
+}
+
+fn liquidity_from(code: u64) -> Result<Liquidity, String> {
+    match code {
+        1 => Ok(Liquidity::Maker),
+        2 => Ok(Liquidity::Taker),
+        other => Err(format!("Invalid liquidity {}", other)),
+    }
+}
+
+fn daily_limit_kind_code(kind: DailyLimitKind) -> u64 {
+    match kind {
+        DailyLimitKind::TradedNotional => 1,
+        DailyLimitKind::Loss => 2,
+    }
+}
+
+fn daily_limit_kind_from(code: u64) -> Result<DailyLimitKind, String> {
+    match code {
+        1 => Ok(DailyLimitKind::TradedNotional),
+        2 => Ok(DailyLimitKind::Loss),
+        other => Err(format!("Invalid daily limit {}", other)),
+    }
+}
+
+fn basket_validation_code(validation: BasketValidation) -> u64 {
+    match validation {
+        BasketValidation::AllOrNothing => 1,
+        BasketValidation::BestEffort => 2,
+    }
+}
+
+fn basket_validation_from(code: u64) -> Result<BasketValidation, String> {
+    match code {
+        1 => Ok(BasketValidation::AllOrNothing),
+        2 => Ok(BasketValidation::BestEffort),
+        other => Err(format!("Invalid basket validation {}", other)),
+    }
+}
+
+fn basket_execution_code(execution: BasketExecution) -> u64 {
+    match execution {
+        BasketExecution::Independent => 1,
+        BasketExecution::Contingent => 2,
+    }
+}
+
+fn basket_execution_from(code: u64) -> Result<BasketExecution, String> {
+    match code {
+        1 => Ok(BasketExecution::Independent),
+        2 => Ok(BasketExecution::Contingent),
+        other => Err(format!("Invalid basket execution {}", other)),
+    }
+}
+
+#[derive(Default)]
+struct Writer {
+    buf: Vec<u8>,
+}
+
+impl Writer {
+    fn raw_varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.buf.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        self.buf.push(value as u8);
+    }
+
+    fn tag(&mut self, field: u32, wire_type: u8) {
+        self.raw_varint(u64::from(field) << 3 | u64::from(wire_type));
+    }
+
+    fn varint(&mut self, field: u32, value: u64) {
+        self.tag(field, VARINT);
+        self.raw_varint(value);
+    }
+
+    fn bytes(&mut self, field: u32, bytes: &[u8]) {
+        self.tag(field, LENGTH_DELIMITED);
+        self.raw_varint(bytes.len() as u64);
+        self.buf.extend_from_slice(bytes);
+    }
+
+    fn string(&mut self, field: u32, value: &str) {
+        self.bytes(field, value.as_bytes());
+    }
+
+    fn uuid(&mut self, field: u32, id: Uuid) {
+        self.bytes(field, id.as_bytes());
+    }
+
+    fn decimal(&mut self, field: u32, value: Decimal) {
+        self.string(field, &value.to_string());
+    }
+
+    fn opt_decimal(&mut self, field: u32, value: Option<Decimal>) {
+        if let Some(value) = value {
+            self.decimal(field, value);
+        }
+    }
+
+    fn opt_string(&mut self, field: u32, value: &Option<String>) {
+        if let Some(value) = value {
+            self.string(field, value);
+        }
+    }
+
+    fn opt_uuid(&mut self, field: u32, id: Option<Uuid>) {
+        if let Some(id) = id {
+            self.uuid(field, id);
+        }
+    }
+
+    /// Nanoseconds since the epoch, as an int64.
+    fn timestamp(&mut self, field: u32, at: DateTime<Utc>) {
+        self.varint(field, at.timestamp_nanos_opt().unwrap_or_default() as u64);
+    }
+
+    fn message(&mut self, field: u32, message: Writer) {
+        self.bytes(field, &message.buf);
+    }
+
+    /// An event's id at `field` and the hash chaining it to the event
+    /// before, when it has one, at the field after.
+    fn chain(&mut self, field: u32, event_id: Uuid, prev_hash: &Option<String>) {
+        self.uuid(field, event_id);
+        self.opt_string(field + 1, prev_hash);
+    }
+}
+
+enum Value<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+}
+
+/// The fields of one message by number. A repeated field keeps its last
+/// value, as protobuf parsers do for singular fields, and fields this
+/// version does not know are skipped.
+struct Fields<'a> {
+    values: HashMap<u32, Value<'a>>,
+}
+
+impl<'a> Fields<'a> {
+    fn parse(bytes: &'a [u8]) -> Result<Self, String> {
+        let mut values = HashMap::new();
+        each_field(bytes, |field, value| {
+            values.insert(field, value);
+        })?;
+        Ok(Self { values })
+    }
+
+    fn opt_bytes(&self, field: u32) -> Result<Option<&'a [u8]>, String> {
+        match self.values.get(&field) {
+            Some(Value::Bytes(bytes)) => Ok(Some(bytes)),
+            Some(Value::Varint(_)) => Err(format!("Field {} should be length-delimited", field)),
+            None => Ok(None),
+        }
+    }
+
+    fn opt_string(&self, field: u32) -> Result<Option<String>, String> {
+        self.opt_bytes(field)?
+            .map(|bytes| String::from_utf8(bytes.to_vec()).map_err(|e| format!("Field {}: {}", field, e)))
+            .transpose()
+    }
+
+    fn string(&self, field: u32) -> Result<String, String> {
+        Ok(self.opt_string(field)?.unwrap_or_default())
+    }
+
+    fn opt_uuid(&self, field: u32) -> Result<Option<Uuid>, String> {
+        self.opt_bytes(field)?
+            .map(|bytes| Uuid::from_slice(bytes).map_err(|e| format!("Field {}: {}", field, e)))
+            .transpose()
+    }
+
+    fn required_bytes(&self, field: u32) -> Result<&'a [u8], String> {
+        self.opt_bytes(field)?.ok_or_else(|| format!("Missing field {}", field))
+    }
+
+    fn message(&self, field: u32) -> Result<Fields<'a>, String> {
+        Fields::parse(self.required_bytes(field)?)
+    }
+
+    fn uuid(&self, field: u32) -> Result<Uuid, String> {
+        self.opt_uuid(field)?.ok_or_else(|| format!("Missing id field {}", field))
+    }
+
+    fn opt_decimal(&self, field: u32) -> Result<Option<Decimal>, String> {
+        self.opt_string(field)?
+            .map(|value| value.parse().map_err(|e| format!("Field {}: {}", field, e)))
+            .transpose()
+    }
+
+    fn decimal(&self, field: u32) -> Result<Decimal, String> {
+        self.opt_decimal(field)?.ok_or_else(|| format!("Missing decimal field {}", field))
+    }
+
+    fn opt_varint(&self, field: u32) -> Option<u64> {
+        match self.values.get(&field) {
+            Some(Value::Varint(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Absent scalars read as 0, their protobuf default.
+    fn varint(&self, field: u32) -> u64 {
+        self.opt_varint(field).unwrap_or(0)
+    }
+
+    fn count(&self, field: u32) -> Result<usize, String> {
+        usize::try_from(self.varint(field)).map_err(|e| format!("Field {}: {}", field, e))
+    }
+
+    fn timestamp(&self, field: u32) -> DateTime<Utc> {
+        DateTime::from_timestamp_nanos(self.varint(field) as i64)
+    }
+}
+
+/// Calls `f` with each varint and length-delimited field in order, skipping
+/// fixed-width ones.
+fn each_field<'a>(mut bytes: &'a [u8], mut f: impl FnMut(u32, Value<'a>)) -> Result<(), String> {
+    while !bytes.is_empty() {
+        let key = read_varint(&mut bytes)?;
+        let field = u32::try_from(key >> 3).map_err(|_| "Field number out of range".to_string())?;
+        let value = match (key & 0x7) as u8 {
+            VARINT => Value::Varint(read_varint(&mut bytes)?),
+            LENGTH_DELIMITED => {
+                let len = usize::try_from(read_varint(&mut bytes)?).map_err(|e| e.to_string())?;
+                Value::Bytes(take(&mut bytes, len)?)
+            }
+            FIXED64 => {
+                take(&mut bytes, 8)?;
+                continue;
+            }
+            FIXED32 => {
+                take(&mut bytes, 4)?;
+                continue;
+            }
+            other => return Err(format!("Unsupported wire type {}", other)),
+        };
+        f(field, value);
+    }
+    Ok(())
+}
+
+/// The one field set in a message holding a oneof.
+fn oneof<'a>(bytes: &'a [u8], message: &str) -> Result<(u32, &'a [u8]), String> {
+    let fields = Fields::parse(bytes)?;
+    let mut set = fields.values.iter().filter_map(|(field, value)| match value {
+        Value::Bytes(body) => Some((*field, *body)),
+        Value::Varint(_) => None,
+    });
+    match (set.next(), set.next()) {
+        (Some(only), None) => Ok(only),
+        (None, _) => Err(format!("Empty {}", message)),
+        _ => Err(format!("More than one variant set in {}", message)),
+    }
+}
+
+/// Every value of a repeated length-delimited field, in order.
+fn repeated(bytes: &[u8], wanted: u32) -> Result<Vec<&[u8]>, String> {
+    let mut values = Vec::new();
+    each_field(bytes, |field, value| {
+        if let (true, Value::Bytes(value)) = (field == wanted, value) {
+            values.push(value);
+        }
+    })?;
+    Ok(values)
+}
+
+fn repeated_uuids(bytes: &[u8], field: u32) -> Result<Vec<Uuid>, String> {
+    repeated(bytes, field)?
+        .into_iter()
+        .map(|id| Uuid::from_slice(id).map_err(|e| format!("Field {}: {}", field, e)))
+        .collect()
+}
+
+fn repeated_strings(bytes: &[u8], field: u32) -> Result<Vec<String>, String> {
+    repeated(bytes, field)?
+        .into_iter()
+        .map(|value| String::from_utf8(value.to_vec()).map_err(|e| format!("Field {}: {}", field, e)))
+        .collect()
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Result<u64, String> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = bytes.split_first().ok_or("Truncated varint")?;
+        *bytes = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err("Varint longer than ten bytes".to_string())
+}
+
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], String> {
+    if bytes.len() < len {
+        return Err("Truncated message".to_string());
+    }
+    let (taken, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Ok(taken)
+}
@@ -3,17 +3,26 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::types::{OrderSide, OrderType};
+use crate::types::{displayed_by_default, OrderSide, OrderType};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum OrderCommand {
     PlaceOrder(PlaceOrderCommand),
     CancelOrder(CancelOrderCommand),
+    PlaceBracketOrder(PlaceBracketOrderCommand),
+    CancelReplace(CancelReplaceCommand),
+    AmendOrder(AmendOrderCommand),
+    PlaceBasket(PlaceBasketCommand),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlaceOrderCommand {
     pub order_id: Uuid,
+    /// Caller-assigned id, unique per user. Resubmitting a command with a
+    /// client order id that already created an order returns the original
+    /// events instead of placing a duplicate.
+    #[serde(default)]
+    pub client_order_id: Option<String>,
     pub user_id: Uuid,
     pub symbol: String,
     pub order_type: OrderType,
@@ -23,13 +32,226 @@ pub struct PlaceOrderCommand {
     pub iceberg_visible_quantity: Option<Decimal>,
     pub stop_price: Option<Decimal>,
     pub trailing_stop_price: Option<Decimal>,
+    /// False to rest the order hidden from the book.
+    #[serde(default = "displayed_by_default")]
+    pub displayed: bool,
+    /// Bounds the executions the order takes as a taker. A priced remainder
+    /// rests if it no longer crosses the book and is canceled otherwise.
+    #[serde(default)]
+    pub max_fills: Option<u32>,
+    /// Account a share of the order's taker fees is attributed to, under
+    /// the symbol's fee schedule.
+    #[serde(default)]
+    pub referrer_id: Option<Uuid>,
+    /// Quote to spend on a market buy. Set, `quantity` is replaced by what
+    /// it buys walking the book when the order is entered, and the order
+    /// never spends more than this.
+    #[serde(default)]
+    pub quote_quantity: Option<Decimal>,
     pub timestamp: DateTime<Utc>,
 }
 
+impl PlaceOrderCommand {
+    /// An order with a fresh id, stamped now. Everything optional is left
+    /// at its default: no client order id, stop, iceberg, fill cap,
+    /// referrer or quote quantity, and displayed. Set those with struct
+    /// update syntax.
+    pub fn new(
+        user_id: Uuid,
+        symbol: impl Into<String>,
+        order_type: OrderType,
+        side: OrderSide,
+        price: Option<Decimal>,
+        quantity: Decimal,
+    ) -> Self {
+        Self {
+            order_id: Uuid::new_v4(),
+            client_order_id: None,
+            user_id,
+            symbol: symbol.into(),
+            order_type,
+            side,
+            price,
+            quantity,
+            iceberg_visible_quantity: None,
+            stop_price: None,
+            trailing_stop_price: None,
+            displayed: true,
+            max_fills: None,
+            referrer_id: None,
+            quote_quantity: None,
+            timestamp: Utc::now(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CancelOrderCommand {
     pub order_id: Uuid,
+    /// When set, the order is looked up by the user's client order id and
+    /// `order_id` is ignored.
+    #[serde(default)]
+    pub client_order_id: Option<String>,
+    pub user_id: Uuid,
+    pub symbol: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// An entry order with attached stop-loss and take-profit exits. The exits
+/// are opposite-side stop orders for the entry quantity; they stay dormant
+/// until the entry is completely filled, and a fill on one cancels the other.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaceBracketOrderCommand {
+    pub bracket_id: Uuid,
+    pub entry: PlaceOrderCommand,
+    pub stop_loss_order_id: Uuid,
+    pub stop_loss_price: Decimal,
+    pub take_profit_order_id: Uuid,
+    pub take_profit_price: Decimal,
+}
+
+/// Replaces an open order with a new one at a new price and/or quantity in
+/// a single step. `new_quantity` is the new total: what the original has
+/// already filled counts against it, so the replacement only carries the
+/// difference.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CancelReplaceCommand {
+    pub order_id: Uuid,
+    /// When set, the original is looked up by the user's client order id
+    /// and `order_id` is ignored.
+    #[serde(default)]
+    pub client_order_id: Option<String>,
     pub user_id: Uuid,
     pub symbol: String,
+    pub new_order_id: Uuid,
+    #[serde(default)]
+    pub new_client_order_id: Option<String>,
+    pub new_price: Option<Decimal>,
+    pub new_quantity: Decimal,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Changes a resting order without replacing it. `new_quantity` is the new
+/// total, including what has already been filled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AmendOrderCommand {
+    pub order_id: Uuid,
+    #[serde(default)]
+    pub client_order_id: Option<String>,
+    pub user_id: Uuid,
+    pub symbol: String,
+    pub new_price: Option<Decimal>,
+    pub new_quantity: Option<Decimal>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Whether one bad leg sinks the whole basket.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum BasketValidation {
+    /// Every leg must pass validation and be admitted, otherwise nothing is
+    /// placed.
+    #[default]
+    AllOrNothing,
+    /// Legs that fail are recorded as rejected and the rest are placed.
+    BestEffort,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum BasketExecution {
+    /// Every leg is placed and matches on its own.
+    #[default]
+    Independent,
+    /// Legs are placed in order, each only once the one before it has
+    /// filled completely; the rest are dropped as rejected.
+    Contingent,
+}
+
+/// Orders across any number of symbols submitted as one command.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaceBasketCommand {
+    pub basket_id: Uuid,
+    pub legs: Vec<PlaceOrderCommand>,
+    #[serde(default)]
+    pub validation: BasketValidation,
+    #[serde(default)]
+    pub execution: BasketExecution,
     pub timestamp: DateTime<Utc>,
 }
+
+/// An order as a client of one of the engine's front ends submits it.
+/// The order id is assigned unless one is given.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlaceRequest {
+    #[serde(default)]
+    pub order_id: Option<Uuid>,
+    #[serde(default)]
+    pub client_order_id: Option<String>,
+    pub user_id: Uuid,
+    pub symbol: String,
+    pub order_type: OrderType,
+    pub side: OrderSide,
+    #[serde(default)]
+    pub price: Option<Decimal>,
+    /// May be left out when `quote_quantity` is given.
+    #[serde(default)]
+    pub quantity: Decimal,
+    #[serde(default)]
+    pub iceberg_visible_quantity: Option<Decimal>,
+    #[serde(default)]
+    pub stop_price: Option<Decimal>,
+    #[serde(default)]
+    pub trailing_stop_price: Option<Decimal>,
+    #[serde(default = "displayed_by_default")]
+    pub displayed: bool,
+    #[serde(default)]
+    pub max_fills: Option<u32>,
+    #[serde(default)]
+    pub referrer_id: Option<Uuid>,
+    #[serde(default)]
+    pub quote_quantity: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CancelRequest {
+    #[serde(default)]
+    pub order_id: Option<Uuid>,
+    #[serde(default)]
+    pub client_order_id: Option<String>,
+    pub user_id: Uuid,
+    pub symbol: String,
+}
+
+impl From<PlaceRequest> for PlaceOrderCommand {
+    fn from(request: PlaceRequest) -> Self {
+        Self {
+            order_id: request.order_id.unwrap_or_else(Uuid::new_v4),
+            client_order_id: request.client_order_id,
+            user_id: request.user_id,
+            symbol: request.symbol,
+            order_type: request.order_type,
+            side: request.side,
+            price: request.price,
+            quantity: request.quantity,
+            iceberg_visible_quantity: request.iceberg_visible_quantity,
+            stop_price: request.stop_price,
+            trailing_stop_price: request.trailing_stop_price,
+            displayed: request.displayed,
+            max_fills: request.max_fills,
+            referrer_id: request.referrer_id,
+            quote_quantity: request.quote_quantity,
+            timestamp: Utc::now(),
+        }
+    }
+}
+
+impl From<CancelRequest> for CancelOrderCommand {
+    fn from(request: CancelRequest) -> Self {
+        Self {
+            order_id: request.order_id.unwrap_or_default(),
+            client_order_id: request.client_order_id,
+            user_id: request.user_id,
+            symbol: request.symbol,
+            timestamp: Utc::now(),
+        }
+    }
+}
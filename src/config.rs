@@ -0,0 +1,128 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::book::{BookFactory, SkipListBooks};
+use crate::book_limits::BookLimits;
+use crate::engine::MatchingEngine;
+use crate::event_store::EventStore;
+use crate::matching::MatchingAlgorithm;
+
+/// What happens when an incoming order would trade with a resting order
+/// of the same user.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SelfTradePolicy {
+    #[default]
+    Allow,
+    /// The incoming order stops before the first level holding one of the
+    /// user's resting orders and its remainder is canceled.
+    CancelIncoming,
+    /// The user's resting orders are canceled as the incoming order
+    /// reaches their level.
+    CancelResting,
+}
+
+/// Engine-wide settings. Per-symbol settings in the `SymbolRegistry` take
+/// precedence where both exist.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct EngineConfig {
+    /// Matching algorithm of symbols that were never registered.
+    pub matching_algorithm: MatchingAlgorithm,
+    pub self_trade_policy: SelfTradePolicy,
+    /// How far, in percent of the best opposite price on arrival, a market
+    /// order may sweep the book. None lets it sweep everything.
+    pub market_protection_percent: Option<Decimal>,
+    pub max_open_orders_per_user: Option<usize>,
+    /// Order entry rate per user; see `UserLimits`.
+    pub max_orders_per_second: Option<u32>,
+    /// Most events handed to the event store in one call. None saves each
+    /// command's events in a single call. Batches already saved stay saved
+    /// if a later one fails.
+    pub event_batch_size: Option<usize>,
+    #[serde(default)]
+    pub book_limits: BookLimits,
+}
+
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Source of the ids the engine assigns itself, such as trade ids.
+pub trait IdGenerator: Send + Sync {
+    fn next_id(&self) -> Uuid;
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RandomIds;
+
+impl IdGenerator for RandomIds {
+    fn next_id(&self) -> Uuid {
+        Uuid::new_v4()
+    }
+}
+
+pub struct MatchingEngineBuilder {
+    event_store: Box<dyn EventStore>,
+    config: EngineConfig,
+    clock: Box<dyn Clock>,
+    ids: Box<dyn IdGenerator>,
+    book_factory: Box<dyn BookFactory>,
+}
+
+impl MatchingEngineBuilder {
+    pub fn new(event_store: Box<dyn EventStore>) -> Self {
+        Self {
+            event_store,
+            config: EngineConfig::default(),
+            clock: Box::new(SystemClock),
+            ids: Box::new(RandomIds),
+            book_factory: Box::new(SkipListBooks),
+        }
+    }
+
+    pub fn config(mut self, config: EngineConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    pub fn id_generator(mut self, ids: impl IdGenerator + 'static) -> Self {
+        self.ids = Box::new(ids);
+        self
+    }
+
+    /// How each symbol's book is built; skip lists on both sides unless
+    /// set.
+    pub fn book_factory(mut self, factory: impl BookFactory + 'static) -> Self {
+        self.book_factory = Box::new(factory);
+        self
+    }
+
+    pub fn build(self) -> MatchingEngine {
+        MatchingEngine::from_parts(self.event_store, self.config, self.clock, self.ids, self.book_factory)
+    }
+}
+
+impl MatchingEngine {
+    pub fn builder(event_store: Box<dyn EventStore>) -> MatchingEngineBuilder {
+        MatchingEngineBuilder::new(event_store)
+    }
+
+    pub fn config(&self) -> &EngineConfig {
+        &self.config
+    }
+}
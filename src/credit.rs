@@ -0,0 +1,165 @@
+use dashmap::DashMap;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::commands::{CancelOrderCommand, OrderCommand, PlaceOrderCommand};
+use crate::engine::MatchingEngine;
+use crate::error::RejectReason;
+use crate::events::OrderEvent;
+use crate::types::{Order, OrderSide};
+
+#[derive(Debug, Default)]
+pub(crate) struct CreditState {
+    limits: DashMap<Uuid, Decimal>,
+    cull: bool,
+}
+
+impl MatchingEngine {
+    /// Cancels a user's resting orders once they consume more credit than
+    /// their limit, those priced furthest from the mark first, until the
+    /// rest fit. Runs after each command entering an order for the user and
+    /// when their limit is set.
+    pub fn with_credit_culling(mut self) -> Self {
+        self.credit.cull = true;
+        self
+    }
+
+    pub(crate) fn credit_culling(&self) -> bool {
+        self.credit.cull && !self.credit.limits.is_empty()
+    }
+
+    /// Sets or, with None, removes the most credit the user's open orders
+    /// and positions may consume. Takes effect for the next command; with
+    /// culling on, returns the events of the cancels it made.
+    pub async fn set_credit_limit(&self, user_id: Uuid, limit: Option<Decimal>) -> Result<Vec<OrderEvent>, String> {
+        self.check_following().map_err(|e| e.to_string())?;
+        match limit {
+            Some(limit) if limit < Decimal::ZERO => {
+                return Err(format!("Credit limit must not be negative, got {}", limit));
+            }
+            Some(limit) => {
+                self.credit.limits.insert(user_id, limit);
+            }
+            None => {
+                self.credit.limits.remove(&user_id);
+            }
+        }
+        Ok(self.cull_credit(user_id).await)
+    }
+
+    pub fn credit_limit(&self, user_id: Uuid) -> Option<Decimal> {
+        self.credit.limits.get(&user_id).map(|l| *l)
+    }
+
+    /// Credit the user's open orders consume at their prices and their
+    /// positions at mark. Unpriced orders count at mark too; bracket exits
+    /// only reduce a position and count for nothing.
+    pub fn credit_used(&self, user_id: Uuid) -> Decimal {
+        self.credit_used_excluding(user_id, None)
+    }
+
+    fn credit_used_excluding(&self, user_id: Uuid, excluding: Option<Uuid>) -> Decimal {
+        let orders: Decimal = self
+            .get_open_orders(user_id)
+            .iter()
+            .filter(|o| Some(o.id) != excluding)
+            .map(|o| self.order_credit(o))
+            .sum();
+        let positions: Decimal = self
+            .get_positions(user_id)
+            .iter()
+            .map(|(symbol, position)| position.quantity().abs() * self.mark_price(symbol).unwrap_or_default())
+            .sum();
+        orders + positions
+    }
+
+    pub(crate) fn order_credit(&self, order: &Order) -> Decimal {
+        if self.is_bracket_exit(order.id) {
+            return Decimal::ZERO;
+        }
+        let price = order.price.or(order.stop_price).or(order.trailing_stop_price);
+        self.credit_notional(&order.symbol, order.remaining_quantity(), price)
+    }
+
+    pub(crate) fn command_credit(&self, cmd: &PlaceOrderCommand) -> Decimal {
+        let price = cmd.price.or(cmd.stop_price).or(cmd.trailing_stop_price);
+        self.credit_notional(&cmd.symbol, cmd.quantity, price)
+    }
+
+    /// What `quantity` at `price` consumes, at mark when unpriced.
+    fn credit_notional(&self, symbol: &str, quantity: Decimal, price: Option<Decimal>) -> Decimal {
+        quantity * price.or_else(|| self.mark_price(symbol)).unwrap_or_default()
+    }
+
+    /// Rejects orders consuming `notional` more credit than the user has
+    /// left. `excluding` is an open order the command takes the place of.
+    pub(crate) fn check_credit(
+        &self,
+        user_id: Uuid,
+        notional: Decimal,
+        excluding: Option<Uuid>,
+    ) -> Result<(), RejectReason> {
+        let Some(limit) = self.credit_limit(user_id) else {
+            return Ok(());
+        };
+        let required = self.credit_used_excluding(user_id, excluding) + notional;
+        if required > limit {
+            return Err(RejectReason::CreditLimitExceeded { required, limit });
+        }
+        Ok(())
+    }
+
+    /// Cancels the user's worst priced resting orders while they are over
+    /// their limit, if culling is on.
+    pub(crate) async fn cull_credit(&self, user_id: Uuid) -> Vec<OrderEvent> {
+        let mut events = Vec::new();
+        if !self.credit_culling() || self.is_replaying() {
+            return events;
+        }
+        let Some(limit) = self.credit_limit(user_id) else {
+            return events;
+        };
+        let mut used = self.credit_used(user_id);
+        if used <= limit {
+            return events;
+        }
+        let mut orders: Vec<(Decimal, Order)> = self
+            .get_open_orders(user_id)
+            .into_iter()
+            .filter(|o| !self.order_credit(o).is_zero())
+            .map(|o| (self.distance_from_mark(&o), o))
+            .collect();
+        orders.sort_by_key(|(distance, _)| std::cmp::Reverse(*distance));
+        for (_, order) in orders {
+            if used <= limit {
+                break;
+            }
+            used -= self.order_credit(&order);
+            let cancel = OrderCommand::CancelOrder(CancelOrderCommand {
+                order_id: order.id,
+                client_order_id: None,
+                user_id,
+                symbol: order.symbol,
+                timestamp: self.clock.now(),
+            });
+            if let Ok(cancel_events) = self.submit_forced(cancel).await {
+                events.extend(cancel_events);
+            }
+        }
+        events
+    }
+
+    /// How much worse than the mark the order is priced: positive for bids
+    /// below it and asks above it. Unpriced orders and symbols without a
+    /// mark count as at the mark.
+    fn distance_from_mark(&self, order: &Order) -> Decimal {
+        let price = order.price.or(order.stop_price).or(order.trailing_stop_price);
+        match (price, self.mark_price(&order.symbol)) {
+            (Some(price), Some(mark)) => match order.side {
+                OrderSide::Buy => mark - price,
+                OrderSide::Sell => price - mark,
+            },
+            _ => Decimal::ZERO,
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use dashmap::DashMap;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::commands::OrderCommand;
+use crate::draws::new_event_id;
+use crate::engine::MatchingEngine;
+use crate::error::RejectReason;
+use crate::events::{DailyLimitReachedEvent, OrderEvent};
+use crate::limits::order_entries;
+use crate::types::{OrderSide, Trade};
+
+/// Caps on what a user may do in one trading session. Unset is unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct DailyLimits {
+    pub max_traded_notional: Option<Decimal>,
+    /// Most the session's trades may lose, valued at mark prices.
+    pub max_loss: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DailyLimitKind {
+    TradedNotional,
+    Loss,
+}
+
+/// What a user has done in the current session.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct DailyUsage {
+    pub session_start: DateTime<Utc>,
+    pub traded_notional: Decimal,
+    /// Loss on the session's trades at mark prices; zero when they are up.
+    pub loss: Decimal,
+    /// The cap the user crossed, leaving them cancel-only until the next
+    /// session.
+    pub reached: Option<DailyLimitKind>,
+}
+
+#[derive(Debug)]
+struct SessionActivity {
+    session_start: DateTime<Utc>,
+    traded_notional: Decimal,
+    /// Net quantity and quote cash traded per symbol.
+    symbols: HashMap<String, (Decimal, Decimal)>,
+    reached: Option<DailyLimitKind>,
+}
+
+impl SessionActivity {
+    fn new(session_start: DateTime<Utc>) -> Self {
+        Self {
+            session_start,
+            traded_notional: Decimal::ZERO,
+            symbols: HashMap::new(),
+            reached: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct DailyLimitState {
+    session_start: NaiveTime,
+    limits: DashMap<Uuid, DailyLimits>,
+    activity: DashMap<Uuid, SessionActivity>,
+}
+
+impl MatchingEngine {
+    /// Holds users with daily limits to them, in sessions that begin every
+    /// day at `session_start` UTC. A user who crosses a cap is put in
+    /// cancel-only for the rest of the session, recording
+    /// DailyLimitReached: commands entering orders for them are rejected
+    /// with `DailyLimitReached`. Caps are checked after each command for the
+    /// users it traded and before one entering orders.
+    pub fn with_daily_limits(mut self, session_start: NaiveTime) -> Self {
+        self.daily_limits = Some(DailyLimitState {
+            session_start,
+            limits: DashMap::new(),
+            activity: DashMap::new(),
+        });
+        self
+    }
+
+    fn daily_limit_state(&self) -> Result<&DailyLimitState, String> {
+        self.daily_limits.as_ref().ok_or_else(|| "Daily limits are not enabled".to_string())
+    }
+
+    /// Sets the user's caps; trades before this in the session count.
+    pub fn set_daily_limits(&self, user_id: Uuid, limits: DailyLimits) -> Result<(), String> {
+        self.daily_limit_state()?.limits.insert(user_id, limits);
+        Ok(())
+    }
+
+    pub fn daily_limits(&self, user_id: Uuid) -> Option<DailyLimits> {
+        self.daily_limits.as_ref()?.limits.get(&user_id).map(|l| *l)
+    }
+
+    /// The user's activity in the session running now. Only users with
+    /// daily limits are tracked.
+    pub fn daily_usage(&self, user_id: Uuid) -> Result<DailyUsage, String> {
+        let state = self.daily_limit_state()?;
+        let session_start = session_start(state.session_start, self.clock.now());
+        let Some(activity) = state.activity.get(&user_id).filter(|a| a.session_start == session_start) else {
+            return Ok(DailyUsage {
+                session_start,
+                ..Default::default()
+            });
+        };
+        let profit: Decimal = activity
+            .symbols
+            .iter()
+            .filter_map(|(symbol, (quantity, cash))| Some(cash + quantity * self.mark_price(symbol)?))
+            .sum();
+        Ok(DailyUsage {
+            session_start,
+            traded_notional: activity.traded_notional,
+            loss: (-profit).max(Decimal::ZERO),
+            reached: activity.reached,
+        })
+    }
+
+    /// Adds the trade to its users' session activity, or with `undo` takes
+    /// it back out. Trades from an earlier session are left alone.
+    pub(crate) fn record_session_trade(
+        &self,
+        trade: &Trade,
+        taker_user_id: Uuid,
+        maker_user_id: Option<Uuid>,
+        undo: bool,
+    ) {
+        let Some(state) = &self.daily_limits else {
+            return;
+        };
+        let session = session_start(state.session_start, trade.created_at);
+        let (quantity, notional) = match undo {
+            true => (-trade.quantity, -trade.price * trade.quantity),
+            false => (trade.quantity, trade.price * trade.quantity),
+        };
+        let apply = |user_id: Uuid, side: OrderSide| {
+            if !state.limits.contains_key(&user_id) {
+                return;
+            }
+            let mut activity = state.activity.entry(user_id).or_insert_with(|| SessionActivity::new(session));
+            if activity.session_start < session {
+                *activity = SessionActivity::new(session);
+            }
+            if activity.session_start != session {
+                return;
+            }
+            activity.traded_notional += notional;
+            let (position, cash) = activity.symbols.entry(trade.symbol.clone()).or_default();
+            match side {
+                OrderSide::Buy => {
+                    *position += quantity;
+                    *cash -= notional;
+                }
+                OrderSide::Sell => {
+                    *position -= quantity;
+                    *cash += notional;
+                }
+            }
+        };
+        apply(taker_user_id, trade.side);
+        if let Some(maker_user_id) = maker_user_id {
+            apply(maker_user_id, trade.side.opposite());
+        }
+    }
+
+    /// Puts each of `users` over a cap in cancel-only for the session,
+    /// returning DailyLimitReached for those who were not already.
+    pub(crate) async fn enforce_daily_limits(&self, users: &[Uuid]) -> Vec<OrderEvent> {
+        let Some(state) = &self.daily_limits else {
+            return Vec::new();
+        };
+        let mut events = Vec::new();
+        for &user_id in users {
+            let (Some(limits), Ok(usage)) = (self.daily_limits(user_id), self.daily_usage(user_id)) else {
+                continue;
+            };
+            if usage.reached.is_some() {
+                continue;
+            }
+            let crossed = [
+                (DailyLimitKind::TradedNotional, usage.traded_notional, limits.max_traded_notional),
+                (DailyLimitKind::Loss, usage.loss, limits.max_loss),
+            ]
+            .into_iter()
+            .find_map(|(kind, value, cap)| cap.filter(|cap| value > *cap).map(|cap| (kind, value, cap)));
+            let Some((limit, value, cap)) = crossed else {
+                continue;
+            };
+            if let Some(mut activity) = state.activity.get_mut(&user_id) {
+                activity.reached = Some(limit);
+            }
+            events.push(OrderEvent::DailyLimitReached(DailyLimitReachedEvent {
+                event_id: new_event_id(),
+                prev_hash: None,
+                user_id,
+                limit,
+                value,
+                cap,
+                session_start: usage.session_start,
+                timestamp: self.clock.now(),
+            }));
+        }
+        if !events.is_empty() {
+            // The user is cancel-only whether or not it could be recorded
+            let _ = self.persist_events(&mut events).await;
+        }
+        events
+    }
+
+    /// Users with an order on either side of a trade in `events`.
+    pub(crate) fn traded_users(&self, events: &[OrderEvent]) -> Vec<Uuid> {
+        let mut users: Vec<Uuid> = events
+            .iter()
+            .filter_map(|e| match e {
+                OrderEvent::OrderMatched(e) => Some([e.order_id, e.matched_order_id]),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|order_id| self.orders.get(&order_id).map(|o| o.user_id))
+            .collect();
+        users.sort();
+        users.dedup();
+        users
+    }
+
+    /// Caps the users the command enters orders for, then rejects it if
+    /// any of them is cancel-only.
+    pub(crate) async fn check_daily_limits(&self, command: &OrderCommand) -> Result<(), RejectReason> {
+        if self.daily_limits.is_none() {
+            return Ok(());
+        }
+        let mut users: Vec<Uuid> = order_entries(command).into_iter().map(|(user_id, _)| user_id).collect();
+        users.sort();
+        users.dedup();
+        self.enforce_daily_limits(&users).await;
+        for user_id in users {
+            if let Some(limit) = self.daily_usage(user_id).ok().and_then(|usage| usage.reached) {
+                return Err(RejectReason::DailyLimitReached(limit));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Start of the session `at` falls in.
+fn session_start(open: NaiveTime, at: DateTime<Utc>) -> DateTime<Utc> {
+    let start = at.date_naive().and_time(open).and_utc();
+    if start > at {
+        start - Duration::days(1)
+    } else {
+        start
+    }
+}
@@ -0,0 +1,153 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::book::{BookStats, FillEstimate};
+use crate::engine::MatchingEngine;
+use crate::types::{OrderBookEntry, OrderSide};
+
+/// One aggregated price level in an L2 snapshot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DepthLevel {
+    pub price: Decimal,
+    pub quantity: Decimal,
+    pub order_count: u64,
+    /// Quantity on this level and every better one; filled in by
+    /// `L2Snapshot::with_cumulative`.
+    pub cumulative_quantity: Option<Decimal>,
+}
+
+impl From<OrderBookEntry> for DepthLevel {
+    fn from(entry: OrderBookEntry) -> Self {
+        Self {
+            price: entry.price,
+            quantity: entry.quantity,
+            order_count: entry.order_count,
+            cumulative_quantity: None,
+        }
+    }
+}
+
+/// Displayed liquidity per price level, bids descending and asks ascending.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct L2Snapshot {
+    pub symbol: String,
+    pub bids: Vec<DepthLevel>,
+    pub asks: Vec<DepthLevel>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl L2Snapshot {
+    /// Fills in each level's running total from the top of its side, as
+    /// depth charts plot it.
+    pub fn with_cumulative(mut self) -> Self {
+        for side in [&mut self.bids, &mut self.asks] {
+            let mut total = Decimal::ZERO;
+            for level in side.iter_mut() {
+                total += level.quantity;
+                level.cumulative_quantity = Some(total);
+            }
+        }
+        self
+    }
+}
+
+/// One resting order in an L3 snapshot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct L3Order {
+    pub order_id: Uuid,
+    pub price: Decimal,
+    pub remaining_quantity: Decimal,
+    /// Place in the level's queue, 0 being next to trade.
+    pub priority: usize,
+    /// Hidden orders are included for surveillance; public feeds should
+    /// drop them.
+    pub displayed: bool,
+}
+
+/// Every resting order, bids best first then asks best first, each level
+/// in queue order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct L3Snapshot {
+    pub symbol: String,
+    pub bids: Vec<L3Order>,
+    pub asks: Vec<L3Order>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl MatchingEngine {
+    /// The best `levels` displayed levels of one side of `symbol`'s book.
+    pub fn get_depth_by_side(&self, symbol: &str, side: OrderSide, levels: usize) -> Vec<OrderBookEntry> {
+        self.order_books
+            .get(symbol)
+            .map(|book| book.depth(side, levels))
+            .unwrap_or_default()
+    }
+
+    /// See `OrderBookOps::volume_within`.
+    pub fn volume_within(&self, symbol: &str, side: OrderSide, limit_price: Decimal) -> Decimal {
+        self.order_books
+            .get(symbol)
+            .map(|book| book.volume_within(side, limit_price))
+            .unwrap_or_default()
+    }
+
+    /// See `OrderBookOps::cost_to_fill`.
+    pub fn cost_to_fill(&self, symbol: &str, side: OrderSide, quantity: Decimal) -> Option<FillEstimate> {
+        match self.order_books.get(symbol) {
+            Some(book) => book.cost_to_fill(side, quantity),
+            None => Some(FillEstimate {
+                quantity: Decimal::ZERO,
+                notional: Decimal::ZERO,
+                average_price: None,
+            }),
+        }
+    }
+
+    /// See `OrderBookOps::stats`. None until `symbol` has a book.
+    pub fn get_book_stats(&self, symbol: &str, levels: usize) -> Option<BookStats> {
+        self.order_books.get(symbol).map(|book| book.stats(levels))
+    }
+
+    pub fn get_l2_snapshot(&self, symbol: &str, levels: usize) -> L2Snapshot {
+        let depth = |side| {
+            self.get_depth_by_side(symbol, side, levels)
+                .into_iter()
+                .map(DepthLevel::from)
+                .collect()
+        };
+        L2Snapshot {
+            symbol: symbol.to_string(),
+            bids: depth(OrderSide::Buy),
+            asks: depth(OrderSide::Sell),
+            timestamp: self.clock.now(),
+        }
+    }
+
+    pub fn get_l3_snapshot(&self, symbol: &str) -> L3Snapshot {
+        let book = self.order_books.get(symbol);
+        let orders = |side| -> Vec<L3Order> {
+            let Some(book) = &book else {
+                return Vec::new();
+            };
+            book.iter_levels(side)
+                .flat_map(|(price, level)| {
+                    level.iter().enumerate().map(move |(priority, order)| L3Order {
+                        order_id: order.id,
+                        price,
+                        remaining_quantity: order.remaining_quantity(),
+                        priority,
+                        displayed: order.displayed,
+                    })
+                })
+                .collect()
+        };
+        L3Snapshot {
+            symbol: symbol.to_string(),
+            bids: orders(OrderSide::Buy),
+            asks: orders(OrderSide::Sell),
+            timestamp: self.clock.now(),
+        }
+    }
+}
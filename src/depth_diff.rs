@@ -0,0 +1,195 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+use crate::depth::{DepthLevel, L2Snapshot};
+use crate::engine::MatchingEngine;
+
+/// Diffs kept per symbol for clients catching up from a snapshot.
+const DIFF_BUFFER_CAPACITY: usize = 1024;
+
+/// The levels one command changed on a symbol, each numbered with an
+/// update id, so this diff covers `first_update_id..=last_update_id`. A
+/// level with a quantity of zero is gone.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DepthDiff {
+    pub symbol: String,
+    pub first_update_id: u64,
+    pub last_update_id: u64,
+    pub bids: Vec<DepthLevel>,
+    pub asks: Vec<DepthLevel>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// A symbol's full depth as of `last_update_id`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VersionedDepth {
+    pub last_update_id: u64,
+    pub book: L2Snapshot,
+}
+
+impl VersionedDepth {
+    /// Brings the book up to date with `diff`. Diffs the snapshot already
+    /// covers are skipped; one that starts past `last_update_id + 1` means
+    /// updates were missed, and the book must be fetched again.
+    pub fn apply(&mut self, diff: &DepthDiff) -> Result<(), String> {
+        if diff.last_update_id <= self.last_update_id {
+            return Ok(());
+        }
+        if diff.first_update_id > self.last_update_id + 1 {
+            return Err(format!(
+                "Missed updates {} to {}",
+                self.last_update_id + 1,
+                diff.first_update_id - 1
+            ));
+        }
+        apply_levels(&mut self.book.bids, &diff.bids, true);
+        apply_levels(&mut self.book.asks, &diff.asks, false);
+        self.last_update_id = diff.last_update_id;
+        self.book.timestamp = diff.timestamp;
+        Ok(())
+    }
+}
+
+fn apply_levels(side: &mut Vec<DepthLevel>, changes: &[DepthLevel], descending: bool) {
+    for change in changes {
+        let position = side.binary_search_by(|level| {
+            if descending {
+                change.price.cmp(&level.price)
+            } else {
+                level.price.cmp(&change.price)
+            }
+        });
+        match position {
+            Ok(i) if change.quantity.is_zero() => {
+                side.remove(i);
+            }
+            Ok(i) => side[i] = change.clone(),
+            Err(i) if !change.quantity.is_zero() => side.insert(i, change.clone()),
+            Err(_) => {}
+        }
+    }
+}
+
+struct DiffFeed {
+    sender: broadcast::Sender<Arc<DepthDiff>>,
+    state: Mutex<DiffState>,
+}
+
+struct DiffState {
+    /// The book as the published diffs leave it.
+    depth: VersionedDepth,
+    recent: VecDeque<Arc<DepthDiff>>,
+}
+
+/// Symbols someone asked for diffs of, from then on kept up to date after
+/// every command.
+#[derive(Default)]
+pub(crate) struct DepthDiffFeeds {
+    feeds: DashMap<String, Arc<DiffFeed>>,
+}
+
+impl MatchingEngine {
+    /// `symbol`'s depth diffs from now on. Join by buffering these, then
+    /// fetching `get_versioned_depth` and applying what follows it; a
+    /// receiver that lags can catch up with `depth_diffs_since`.
+    pub fn subscribe_depth_diffs(&self, symbol: &str) -> broadcast::Receiver<Arc<DepthDiff>> {
+        self.depth_diff_feed(symbol).sender.subscribe()
+    }
+
+    /// `symbol`'s full depth, versioned against its diffs.
+    pub fn get_versioned_depth(&self, symbol: &str) -> VersionedDepth {
+        self.depth_diff_feed(symbol).state.lock().unwrap().depth.clone()
+    }
+
+    /// The buffered diffs after `update_id`, or None if the buffer no
+    /// longer reaches back that far.
+    pub fn depth_diffs_since(&self, symbol: &str, update_id: u64) -> Option<Vec<Arc<DepthDiff>>> {
+        let feed = self.depth_diffs.feeds.get(symbol)?.clone();
+        let state = feed.state.lock().unwrap();
+        if state.depth.last_update_id <= update_id {
+            return Some(Vec::new());
+        }
+        let oldest = state.recent.front()?;
+        if oldest.first_update_id > update_id + 1 {
+            return None;
+        }
+        Some(state.recent.iter().filter(|d| d.last_update_id > update_id).cloned().collect())
+    }
+
+    fn depth_diff_feed(&self, symbol: &str) -> Arc<DiffFeed> {
+        self.depth_diffs
+            .feeds
+            .entry(symbol.to_string())
+            .or_insert_with(|| {
+                Arc::new(DiffFeed {
+                    sender: broadcast::channel(DIFF_BUFFER_CAPACITY).0,
+                    state: Mutex::new(DiffState {
+                        depth: VersionedDepth {
+                            last_update_id: 0,
+                            book: self.get_l2_snapshot(symbol, usize::MAX),
+                        },
+                        recent: VecDeque::new(),
+                    }),
+                })
+            })
+            .clone()
+    }
+
+    /// Publishes how the last command changed `symbol`'s depth, if anyone
+    /// follows it.
+    pub(crate) fn publish_depth_diff(&self, symbol: &str) {
+        let Some(feed) = self.depth_diffs.feeds.get(symbol).map(|feed| feed.clone()) else {
+            return;
+        };
+        let mut state = feed.state.lock().unwrap();
+        let book = self.get_l2_snapshot(symbol, usize::MAX);
+        let bids = changed_levels(&state.depth.book.bids, &book.bids);
+        let asks = changed_levels(&state.depth.book.asks, &book.asks);
+        if bids.is_empty() && asks.is_empty() {
+            return;
+        }
+        let first_update_id = state.depth.last_update_id + 1;
+        let diff = Arc::new(DepthDiff {
+            symbol: symbol.to_string(),
+            first_update_id,
+            last_update_id: first_update_id + (bids.len() + asks.len()) as u64 - 1,
+            bids,
+            asks,
+            timestamp: book.timestamp,
+        });
+        state.depth = VersionedDepth {
+            last_update_id: diff.last_update_id,
+            book,
+        };
+        if state.recent.len() == DIFF_BUFFER_CAPACITY {
+            state.recent.pop_front();
+        }
+        state.recent.push_back(diff.clone());
+        let _ = feed.sender.send(diff);
+    }
+}
+
+/// The levels of `new` that differ from `old`, and `old`'s levels that
+/// are gone, with a quantity of zero.
+fn changed_levels(old: &[DepthLevel], new: &[DepthLevel]) -> Vec<DepthLevel> {
+    let before: HashMap<Decimal, &DepthLevel> = old.iter().map(|level| (level.price, level)).collect();
+    let mut changed: Vec<DepthLevel> = new
+        .iter()
+        .filter(|level| before.get(&level.price) != Some(level))
+        .cloned()
+        .collect();
+    let after: HashMap<Decimal, &DepthLevel> = new.iter().map(|level| (level.price, level)).collect();
+    changed.extend(old.iter().filter(|level| !after.contains_key(&level.price)).map(|level| DepthLevel {
+        price: level.price,
+        quantity: Decimal::ZERO,
+        order_count: 0,
+        cumulative_quantity: None,
+    }));
+    changed
+}
@@ -0,0 +1,96 @@
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::config::{Clock, IdGenerator};
+
+/// The trade and event ids and the times a command drew, in the order it
+/// drew them. Running the command again with
+/// the same draws assigns the same trade ids, event ids and timestamps.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Draws {
+    pub ids: Vec<Uuid>,
+    pub times: Vec<DateTime<Utc>>,
+}
+
+impl Draws {
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty() && self.times.is_empty()
+    }
+}
+
+#[derive(Default)]
+struct Tape {
+    drawn: Draws,
+    replay_ids: VecDeque<Uuid>,
+    replay_times: VecDeque<DateTime<Utc>>,
+}
+
+tokio::task_local! {
+    static TAPE: Arc<Mutex<Tape>>;
+}
+
+/// Runs `run`, recording what it draws. Values in `replay` are handed out
+/// before fresh ones are drawn. A run nested in another is recorded by
+/// the outer one and returns no draws of its own.
+pub(crate) async fn record_draws<F: Future>(replay: Draws, run: F) -> (F::Output, Draws) {
+    if TAPE.try_with(|_| ()).is_ok() {
+        return (run.await, Draws::default());
+    }
+    let tape = Arc::new(Mutex::new(Tape {
+        drawn: Draws::default(),
+        replay_ids: replay.ids.into(),
+        replay_times: replay.times.into(),
+    }));
+    let output = TAPE.scope(tape.clone(), run).await;
+    let drawn = std::mem::take(&mut tape.lock().unwrap().drawn);
+    (output, drawn)
+}
+
+/// What the running command has drawn so far, if it is being recorded.
+pub(crate) fn drawn_so_far() -> Option<Draws> {
+    TAPE.try_with(|tape| tape.lock().unwrap().drawn.clone()).ok()
+}
+
+/// An id from `draw`, or from the tape while a command replays.
+fn taped_id(draw: impl Fn() -> Uuid) -> Uuid {
+    TAPE.try_with(|tape| {
+        let mut tape = tape.lock().unwrap();
+        let id = tape.replay_ids.pop_front().unwrap_or_else(&draw);
+        tape.drawn.ids.push(id);
+        id
+    })
+    .unwrap_or_else(|_| draw())
+}
+
+pub(crate) fn new_event_id() -> Uuid {
+    taped_id(Uuid::new_v4)
+}
+
+/// The engine's `IdGenerator`, taped while a command runs.
+pub(crate) struct RecordedIds(pub(crate) Box<dyn IdGenerator>);
+
+impl IdGenerator for RecordedIds {
+    fn next_id(&self) -> Uuid {
+        taped_id(|| self.0.next_id())
+    }
+}
+
+/// The engine's `Clock`, taped while a command runs.
+pub(crate) struct RecordedClock(pub(crate) Box<dyn Clock>);
+
+impl Clock for RecordedClock {
+    fn now(&self) -> DateTime<Utc> {
+        TAPE.try_with(|tape| {
+            let mut tape = tape.lock().unwrap();
+            let now = tape.replay_times.pop_front().unwrap_or_else(|| self.0.now());
+            tape.drawn.times.push(now);
+            now
+        })
+        .unwrap_or_else(|_| self.0.now())
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::events::OrderEvent;
+use crate::projection::Projection;
+
+/// Whose activity a drop copy follows.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Participant {
+    User(Uuid),
+    Firm(String),
+}
+
+/// An execution or state change of one of a participant's orders.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DropCopyRecord {
+    /// Position in the participant's drop copy, from 1.
+    pub sequence: u64,
+    pub user_id: Uuid,
+    /// The participant's order. For a match that is the maker order when
+    /// the participant was the maker.
+    pub order_id: Uuid,
+    pub event: OrderEvent,
+}
+
+/// Every execution and order state change of each user, and of each firm
+/// across its users, as the event log has them. Nothing depends on the
+/// users' own connections, so back offices can read a complete copy at
+/// any time from the last sequence they have. Firm membership applies to
+/// whatever is applied after it is set, a rebuild included.
+#[derive(Debug, Default)]
+pub struct DropCopyView {
+    firms: HashMap<Uuid, String>,
+    owners: HashMap<Uuid, Uuid>,
+    copies: HashMap<Participant, Vec<DropCopyRecord>>,
+}
+
+impl DropCopyView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_firm(mut self, firm: &str, users: &[Uuid]) -> Self {
+        for user_id in users {
+            self.firms.insert(*user_id, firm.to_string());
+        }
+        self
+    }
+
+    /// The participant's records after `sequence`, oldest first; 0 for all
+    /// of them.
+    pub fn records_since(&self, participant: &Participant, sequence: u64) -> &[DropCopyRecord] {
+        let records = self.copies.get(participant).map(Vec::as_slice).unwrap_or_default();
+        &records[(sequence as usize).min(records.len())..]
+    }
+
+    /// The users and their orders `event` concerns.
+    fn concerned(&self, event: &OrderEvent) -> Vec<(Uuid, Uuid)> {
+        let owned = |order_id: Uuid| self.owners.get(&order_id).map(|user_id| (*user_id, order_id));
+        match event {
+            OrderEvent::OrderMatched(e) => [e.order_id, e.matched_order_id].into_iter().filter_map(owned).collect(),
+            OrderEvent::TradeBusted(e) => [e.taker_order_id, e.maker_order_id].into_iter().filter_map(owned).collect(),
+            OrderEvent::BookLevelEvicted(e) => e.order_ids.iter().filter_map(|id| owned(*id)).collect(),
+            _ => match (event.order_id(), event.user_id()) {
+                (Some(order_id), Some(user_id)) => vec![(user_id, order_id)],
+                (Some(order_id), None) => owned(order_id).into_iter().collect(),
+                _ => Vec::new(),
+            },
+        }
+    }
+
+    fn append(&mut self, participant: Participant, user_id: Uuid, order_id: Uuid, event: &OrderEvent) {
+        let records = self.copies.entry(participant).or_default();
+        records.push(DropCopyRecord {
+            sequence: records.len() as u64 + 1,
+            user_id,
+            order_id,
+            event: event.clone(),
+        });
+    }
+}
+
+impl Projection for DropCopyView {
+    fn apply(&mut self, event: &OrderEvent) {
+        if let OrderEvent::OrderPlaced(e) = event {
+            self.owners.insert(e.order_id, e.user_id);
+        }
+        for (user_id, order_id) in self.concerned(event) {
+            self.append(Participant::User(user_id), user_id, order_id, event);
+            if let Some(firm) = self.firms.get(&user_id).cloned() {
+                self.append(Participant::Firm(firm), user_id, order_id, event);
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.owners.clear();
+        self.copies.clear();
+    }
+}
@@ -1,45 +1,444 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Utc};
+use dashmap::mapref::one::RefMut;
 use dashmap::DashMap;
 use rust_decimal::Decimal;
 use uuid::Uuid;
 
-use crate::commands::{OrderCommand, PlaceOrderCommand};
+use crate::accounts::Accounts;
+use crate::amend::{PriorityPolicy, StandardPriorityPolicy};
+use crate::book::{BookFactory, OrderBookOps, SymbolBook};
+use crate::book_delta::BookDeltaFeed;
+use crate::book_view::BookViews;
+use crate::depth_diff::DepthDiffFeeds;
+use crate::draws::{new_event_id, Draws, RecordedClock, RecordedIds};
+use crate::top_of_book::TopOfBookFeed;
+use crate::ladder_book::LadderOrderBook;
+use crate::bracket::BracketGroup;
+use crate::candles::Candles;
+use crate::circuit_breaker::BreakerState;
+use crate::config::{Clock, EngineConfig, IdGenerator, MatchingEngineBuilder};
+use crate::error::{EngineError, RejectReason};
+use crate::invariants::InvariantChecks;
+use crate::kill_switch::KillSwitchTarget;
+use crate::instant::Instant;
+use crate::limits::{order_entries, UserLimitState};
+use crate::margin::MarginState;
+use crate::market_data::MarketData;
+use crate::credit::CreditState;
+use crate::daily_limits::DailyLimitState;
+use crate::commands::{CancelOrderCommand, OrderCommand, PlaceOrderCommand};
 use crate::event_store::EventStore;
-use crate::events::{OrderEvent, OrderMatchedEvent, OrderPlacedEvent};
-use crate::types::{Order, OrderBook, OrderSide, OrderType, Trade};
+use crate::feeds::MarketFeeds;
+use crate::matching::MatchPolicy;
+use crate::metrics::EngineMetrics;
+use crate::middleware::CommandMiddleware;
+use crate::risk::RiskCheck;
+use crate::idempotency::ClientOrderIds;
+use crate::queries::OrderIndex;
+use crate::trade_log::TradeLog;
+use crate::persistence::{PersistenceFailurePolicy, PersistenceState};
+use crate::positions::PositionState;
+use crate::replication::Replication;
+use crate::snapshot::SnapshotState;
+use crate::settlement::Settlement;
+use crate::trade_store::TradeCapture;
+use crate::event_writer::EventWriter;
+use crate::wal::WriteAheadLog;
+use crate::symbols::SymbolRegistry;
+use crate::synthetic::SyntheticPair;
+use crate::trading_state::{Admission, HaltedCommandPolicy, SymbolState};
+use crate::events::{OrderCanceledEvent, OrderEvent, OrderMatchedEvent, OrderPlacedEvent, OrderRejectedEvent};
+use crate::types::{Order, OrderBook, OrderSide, OrderStatus, OrderType, Trade};
+
+/// Whether a stop order with `stop_price` fires at `last_price`.
+fn stop_triggered(order_type: OrderType, side: OrderSide, stop_price: Decimal, last_price: Decimal) -> bool {
+    match (order_type, side) {
+        (OrderType::StopLoss, OrderSide::Sell) | (OrderType::TakeProfit, OrderSide::Buy) => last_price <= stop_price,
+        (OrderType::StopLoss, OrderSide::Buy) | (OrderType::TakeProfit, OrderSide::Sell) => last_price >= stop_price,
+        _ => false,
+    }
+}
 
 pub struct MatchingEngine {
-    pub(crate) order_books: DashMap<String, OrderBook>,
+    /// Per-symbol books. They hold copies of the orders in `orders`, kept
+    /// in step as they fill, amend and cancel.
+    pub(crate) order_books: DashMap<String, Box<dyn OrderBookOps>>,
+    pub(crate) book_factory: Box<dyn BookFactory>,
+    pub(crate) book_deltas: BookDeltaFeed,
+    pub(crate) book_views: BookViews,
+    pub(crate) top_of_book: TopOfBookFeed,
     pub(crate) orders: DashMap<Uuid, Order>,
+    pub(crate) order_index: OrderIndex,
+    pub(crate) client_order_ids: ClientOrderIds,
     pub(crate) trades: DashMap<Uuid, Trade>,
-    event_store: Box<dyn EventStore>,
+    pub(crate) trade_log: TradeLog,
+    pub(crate) market_data: MarketData,
+    pub(crate) candles: Candles,
+    pub(crate) feeds: MarketFeeds,
+    pub(crate) depth_diffs: DepthDiffFeeds,
+    pub(crate) stop_orders: DashMap<String, Vec<Uuid>>,
+    pub(crate) last_prices: DashMap<String, Decimal>,
+    pub(crate) brackets: DashMap<Uuid, BracketGroup>,
+    pub(crate) order_brackets: DashMap<Uuid, Uuid>,
+    pub(crate) synthetic_pairs: DashMap<String, SyntheticPair>,
+    pub(crate) symbols: SymbolRegistry,
+    pub(crate) symbol_states: DashMap<String, SymbolState>,
+    /// The state each scheduled symbol was last moved into by its schedule.
+    pub(crate) scheduled_states: DashMap<String, SymbolState>,
+    pub(crate) queued_commands: DashMap<String, VecDeque<OrderCommand>>,
+    pub(crate) halted_command_policy: HaltedCommandPolicy,
+    pub(crate) circuit_breakers: DashMap<String, BreakerState>,
+    pub(crate) invariant_checks: InvariantChecks,
+    pub(crate) priority_policy: Box<dyn PriorityPolicy>,
+    pub(crate) middleware: Vec<Box<dyn CommandMiddleware>>,
+    pub(crate) risk_checks: Vec<Box<dyn RiskCheck>>,
+    pub(crate) metrics: EngineMetrics,
+    pub(crate) event_store: Box<dyn EventStore>,
+    pub(crate) persistence_policy: PersistenceFailurePolicy,
+    pub(crate) persistence: PersistenceState,
+    pub(crate) wal: Option<WriteAheadLog>,
+    pub(crate) snapshots: Option<SnapshotState>,
+    pub(crate) trade_capture: Option<TradeCapture>,
+    pub(crate) settlement: Option<Settlement>,
+    pub(crate) event_writer: Option<EventWriter>,
+    pub(crate) replication: Replication,
+    /// Held shared by each command from logging to the end of processing,
+    /// and exclusively while snapshotting or recovering.
+    pub(crate) command_gate: tokio::sync::RwLock<()>,
+    pub(crate) user_limits: UserLimitState,
+    pub(crate) accounts: Option<Accounts>,
+    pub(crate) positions: PositionState,
+    pub(crate) margin: Option<MarginState>,
+    pub(crate) credit: CreditState,
+    pub(crate) daily_limits: Option<DailyLimitState>,
+    pub(crate) kill_switches: DashMap<KillSwitchTarget, DateTime<Utc>>,
+    pub(crate) busted_trades: DashMap<Uuid, DateTime<Utc>>,
+    pub(crate) config: EngineConfig,
+    pub(crate) clock: Box<dyn Clock>,
+    pub(crate) ids: Box<dyn IdGenerator>,
 }
 
 impl MatchingEngine {
+    /// An engine with the default configuration; see `builder` for more.
     pub fn new(event_store: Box<dyn EventStore>) -> Self {
+        MatchingEngineBuilder::new(event_store).build()
+    }
+
+    pub(crate) fn from_parts(
+        event_store: Box<dyn EventStore>,
+        config: EngineConfig,
+        clock: Box<dyn Clock>,
+        ids: Box<dyn IdGenerator>,
+        book_factory: Box<dyn BookFactory>,
+    ) -> Self {
         Self {
             order_books: DashMap::new(),
+            book_factory,
+            book_deltas: BookDeltaFeed::default(),
+            book_views: BookViews::default(),
+            top_of_book: TopOfBookFeed::default(),
             orders: DashMap::new(),
+            order_index: OrderIndex::default(),
+            client_order_ids: ClientOrderIds::default(),
             trades: DashMap::new(),
+            trade_log: TradeLog::default(),
+            market_data: MarketData::default(),
+            candles: Candles::default(),
+            feeds: MarketFeeds::default(),
+            depth_diffs: DepthDiffFeeds::default(),
+            stop_orders: DashMap::new(),
+            last_prices: DashMap::new(),
+            brackets: DashMap::new(),
+            order_brackets: DashMap::new(),
+            synthetic_pairs: DashMap::new(),
+            symbols: SymbolRegistry::new(),
+            symbol_states: DashMap::new(),
+            scheduled_states: DashMap::new(),
+            queued_commands: DashMap::new(),
+            halted_command_policy: HaltedCommandPolicy::default(),
+            circuit_breakers: DashMap::new(),
+            invariant_checks: InvariantChecks::default(),
+            priority_policy: Box::new(StandardPriorityPolicy),
+            middleware: Vec::new(),
+            risk_checks: Vec::new(),
+            metrics: EngineMetrics::default(),
             event_store,
+            persistence_policy: PersistenceFailurePolicy::default(),
+            persistence: PersistenceState::default(),
+            wal: None,
+            snapshots: None,
+            trade_capture: None,
+            settlement: None,
+            event_writer: None,
+            replication: Replication::default(),
+            command_gate: tokio::sync::RwLock::new(()),
+            user_limits: UserLimitState::default(),
+            accounts: None,
+            positions: PositionState::default(),
+            margin: None,
+            credit: CreditState::default(),
+            daily_limits: None,
+            kill_switches: DashMap::new(),
+            busted_trades: DashMap::new(),
+            config,
+            clock: Box::new(RecordedClock(clock)),
+            ids: Box::new(RecordedIds(ids)),
+        }
+    }
+
+    pub async fn handle_command(&self, mut command: OrderCommand) -> Result<Vec<OrderEvent>, EngineError> {
+        self.check_following()?;
+        self.run_middleware(&mut command)?;
+        self.check_rate_limits(&command).await?;
+        self.check_event_queue()?;
+        self.check_replication_backlog()?;
+        let margined = self.margin.is_some().then(|| self.command_symbols(&command));
+        let credited = self.credit_culling().then(|| {
+            let mut users: Vec<Uuid> = order_entries(&command).into_iter().map(|(user_id, _)| user_id).collect();
+            users.sort();
+            users.dedup();
+            users
+        });
+        let result = self.run_command(command).await;
+        if let Some(symbols) = margined {
+            self.liquidate_breaches(&symbols).await;
+        }
+        for user_id in credited.into_iter().flatten() {
+            self.cull_credit(user_id).await;
+        }
+        self.snapshot_if_due().await;
+        result
+    }
+
+    /// Logs, processes and replicates a command the engine issues itself,
+    /// which skips the middleware and rate limits.
+    pub(crate) async fn submit_forced(&self, command: OrderCommand) -> Result<Vec<OrderEvent>, EngineError> {
+        self.run_command(command).await
+    }
+
+    /// Logs and processes an admitted command, then queues it for the
+    /// replica with the ids and times it drew. A command that could not be logged
+    /// is neither run nor shipped.
+    async fn run_command(&self, command: OrderCommand) -> Result<Vec<OrderEvent>, EngineError> {
+        let replicated = self.is_replicating().then(|| command.clone());
+        let _admitted = self.command_gate.read().await;
+        let (sequence, shipped) = self.log_replicated(&command)?;
+        let (result, draws) = self.run_logged(sequence, Draws::default(), self.process_command(command)).await;
+        if let (Some(shipped), Some(command)) = (shipped, replicated) {
+            self.replicate(shipped, command, &result, draws);
         }
+        result
     }
 
-    pub async fn handle_command(&self, command: OrderCommand) -> Result<Vec<OrderEvent>, String> {
-        match command {
+    /// Runs a command that has passed the middleware and the per-user rate
+    /// limits.
+    pub(crate) async fn process_command(&self, command: OrderCommand) -> Result<Vec<OrderEvent>, EngineError> {
+        self.check_kill_switches(&command)?;
+        self.check_daily_limits(&command).await?;
+        let started = Instant::now();
+        let symbols = self.command_symbols(&command);
+        let checkpoints = self.rolls_back_on_failure().then(|| self.checkpoint_symbols(&symbols));
+        let mut result = match command {
             OrderCommand::PlaceOrder(cmd) => self.handle_place_order(cmd).await,
-            OrderCommand::CancelOrder(cmd) => self.handle_cancel_order(cmd).await,
+            OrderCommand::CancelOrder(cmd) => self.handle_cancel_order(cmd).await.map_err(EngineError::from),
+            OrderCommand::PlaceBracketOrder(cmd) => self.handle_place_bracket_order(cmd).await,
+            OrderCommand::CancelReplace(cmd) => self.handle_cancel_replace(cmd).await,
+            OrderCommand::AmendOrder(cmd) => self.handle_amend_order(cmd).await,
+            OrderCommand::PlaceBasket(cmd) => self.handle_place_basket(cmd).await,
+        };
+        if let (Err(_), Some(checkpoints)) = (&result, checkpoints) {
+            self.roll_back(checkpoints);
+        }
+        if let (Ok(events), Some(_)) = (&mut result, &self.daily_limits) {
+            let reached = self.enforce_daily_limits(&self.traded_users(events)).await;
+            events.extend(reached);
         }
+        self.metrics.record_command(started.elapsed());
+        self.refresh_book_views(&symbols);
+        self.refresh_top_of_book(&symbols);
+        self.publish_market_feeds(&symbols);
+
+        self.enforce_invariants(&symbols)
+            .await
+            .map_err(EngineError::InternalInvariantViolation)?;
+        result
     }
 
     pub async fn handle_place_order(
         &self,
         cmd: PlaceOrderCommand,
-    ) -> Result<Vec<OrderEvent>, String> {
+    ) -> Result<Vec<OrderEvent>, EngineError> {
+        let client_order_id = cmd.client_order_id.clone();
+        self.place_idempotently(cmd.user_id, client_order_id.as_deref(), self.place_order(cmd))
+            .await
+    }
+
+    async fn place_order(&self, mut cmd: PlaceOrderCommand) -> Result<Vec<OrderEvent>, EngineError> {
         // Validate order
+        self.normalize_order(&mut cmd)?;
         self.validate_order(&cmd)?;
+        if self.orders.contains_key(&cmd.order_id) {
+            return Err(self.reject_duplicate_order_id(cmd.order_id, cmd.user_id, &cmd.symbol).await);
+        }
+        self.check_open_order_limit(cmd.user_id, 1)?;
+        self.check_book_limits(&cmd)?;
+        self.check_funds(&cmd, Decimal::ZERO)?;
+        self.check_exposure(cmd.user_id, &cmd.symbol, cmd.side, cmd.quantity, None)?;
+        self.check_margin(cmd.user_id, &cmd.symbol, cmd.side, cmd.quantity, cmd.price, None)?;
+        self.check_credit(cmd.user_id, self.command_credit(&cmd), None)?;
+        self.run_risk_checks(&cmd, None).await?;
+        let (admission, mut events) = self
+            .admit_command(&cmd.symbol, false, || OrderCommand::PlaceOrder(cmd.clone()))
+            .await?;
+        if let Admission::Queued = admission {
+            return Ok(events);
+        }
+        if cmd.order_type == OrderType::Market && self.is_in_auction(&cmd.symbol) {
+            return Err(format!("{} is in auction: market orders are not accepted", cmd.symbol).into());
+        }
+        if let Some(pair) = self.synthetic_route(&cmd.symbol)? {
+            return Ok(self.handle_synthetic_order(pair, cmd, events).await?);
+        }
+        let resumed = self.check_persistence_halt(&cmd.symbol).await?;
+
+        // Create, store and match order
+        let order = Self::order_from_command(&cmd);
+        self.submit_order(order, &mut events);
+
+        // Save all events
+        self.persist_events(&mut events).await?;
+
+        Ok(resumed.into_iter().chain(events).collect())
+    }
+
+    async fn handle_cancel_order(&self, cmd: CancelOrderCommand) -> Result<Vec<OrderEvent>, String> {
+        let order_id = self.resolve_order_ref(cmd.user_id, cmd.client_order_id.as_deref(), cmd.order_id)?;
+        let order = self
+            .get_order(order_id)
+            .ok_or_else(|| format!("Order {} not found", order_id))?;
+        if order.user_id != cmd.user_id || order.symbol != cmd.symbol {
+            return Err(format!("Order {} does not belong to this user and symbol", order_id));
+        }
+        if !order.is_open() {
+            return Err(format!("Order {} is no longer open", order_id));
+        }
+        let (admission, mut events) = self
+            .admit_command(&cmd.symbol, true, || OrderCommand::CancelOrder(cmd.clone()))
+            .await?;
+        if let Admission::Queued = admission {
+            return Ok(events);
+        }
+
+        let resumed = self.check_persistence_halt(&cmd.symbol).await?;
+        self.cancel_order(order_id, &mut events);
+        self.cancel_bracket_children(order_id, &mut events);
+
+        self.persist_events(&mut events).await?;
+
+        Ok(resumed.into_iter().chain(events).collect())
+    }
+
+    /// Records an OrderRejected for a reused order id, leaving the existing
+    /// order untouched.
+    pub(crate) async fn reject_duplicate_order_id(&self, order_id: Uuid, user_id: Uuid, symbol: &str) -> EngineError {
+        let error = EngineError::DuplicateOrderId(order_id);
+        let mut events = vec![self.order_rejected(order_id, user_id, symbol, &error)];
+        // The caller gets the rejection whether or not it could be recorded
+        let _ = self.persist_events(&mut events).await;
+        error
+    }
+
+    pub(crate) fn order_rejected(&self, order_id: Uuid, user_id: Uuid, symbol: &str, error: &EngineError) -> OrderEvent {
+        OrderEvent::OrderRejected(OrderRejectedEvent {
+            event_id: new_event_id(),
+            prev_hash: None,
+            order_id,
+            user_id,
+            symbol: symbol.to_string(),
+            reason: error.to_string(),
+            timestamp: self.clock.now(),
+        })
+    }
+
+    /// Rejects `new_orders` more open orders for `user_id` if they would
+    /// take the user past the configured maximum.
+    pub(crate) fn check_open_order_limit(&self, user_id: Uuid, new_orders: usize) -> Result<(), RejectReason> {
+        let Some(limit) = self.user_limits(user_id).max_open_orders else {
+            return Ok(());
+        };
+        if self.order_index.open_order_count(user_id) + new_orders > limit {
+            return Err(RejectReason::TooManyOpenOrders { limit });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn validate_order(&self, cmd: &PlaceOrderCommand) -> Result<(), RejectReason> {
+        match cmd.order_type {
+            OrderType::Market => {
+                if cmd.price.is_some() {
+                    return Err(RejectReason::MarketOrderWithPrice);
+                }
+            }
+            OrderType::Limit => {
+                if cmd.price.is_none() {
+                    return Err(RejectReason::MissingPrice);
+                }
+            }
+            OrderType::StopLoss | OrderType::TakeProfit => {
+                if cmd.stop_price.is_none() {
+                    return Err(RejectReason::MissingStopPrice);
+                }
+            }
+            OrderType::Iceberg => {
+                if cmd.iceberg_visible_quantity.is_none() {
+                    return Err(RejectReason::MissingVisibleQuantity);
+                }
+            }
+            OrderType::TrailingStop => {
+                if cmd.trailing_stop_price.is_none() {
+                    return Err(RejectReason::MissingTrailingStopPrice);
+                }
+            }
+        }
+
+        if cmd.quantity <= Decimal::ZERO {
+            return Err(RejectReason::NonPositiveQuantity(cmd.quantity));
+        }
+        if let Some(price) = cmd.price.filter(|p| *p <= Decimal::ZERO) {
+            return Err(RejectReason::NonPositivePrice(price));
+        }
+        self.check_price_ladder(&cmd.symbol, cmd.price)?;
+        self.check_price_band(&cmd.symbol, cmd.price)?;
+        for stop_price in [cmd.stop_price, cmd.trailing_stop_price].into_iter().flatten() {
+            if stop_price <= Decimal::ZERO {
+                return Err(RejectReason::NonPositiveStopPrice(stop_price));
+            }
+        }
+        self.check_order_size(&cmd.symbol, cmd.price.or(cmd.stop_price), cmd.quantity)?;
+        if let Some(visible) = cmd.iceberg_visible_quantity {
+            if visible <= Decimal::ZERO {
+                return Err(RejectReason::NonPositiveVisibleQuantity(visible));
+            }
+            if visible > cmd.quantity {
+                return Err(RejectReason::IcebergVisibleExceedsTotal {
+                    visible,
+                    total: cmd.quantity,
+                });
+            }
+        }
+        if let (Some(stop_price), Some(last_price)) = (cmd.stop_price, self.last_prices.get(&cmd.symbol)) {
+            let last_price = *last_price;
+            if stop_triggered(cmd.order_type, cmd.side, stop_price, last_price) {
+                return Err(RejectReason::StopPriceOnWrongSide { stop_price, last_price });
+            }
+        }
+        Ok(())
+    }
 
-        // Create order
-        let order = Order {
+    pub(crate) fn order_from_command(cmd: &PlaceOrderCommand) -> Order {
+        Order {
             id: cmd.order_id,
             user_id: cmd.user_id,
             symbol: cmd.symbol.clone(),
@@ -48,19 +447,48 @@ impl MatchingEngine {
             price: cmd.price,
             quantity: cmd.quantity,
             filled_quantity: Decimal::ZERO,
-            status: crate::types::OrderStatus::Pending,
+            status: OrderStatus::Pending,
             created_at: cmd.timestamp,
             updated_at: cmd.timestamp,
             iceberg_visible_quantity: cmd.iceberg_visible_quantity,
             stop_price: cmd.stop_price,
             trailing_stop_price: cmd.trailing_stop_price,
-        };
+            client_order_id: cmd.client_order_id.clone(),
+            displayed: cmd.displayed,
+            max_fills: cmd.max_fills,
+            referrer_id: cmd.referrer_id,
+            quote_quantity: cmd.quote_quantity,
+        }
+    }
+
+    /// Stores a new order, emits OrderPlaced and either parks it (stop
+    /// orders) or runs it against the book.
+    pub(crate) fn submit_order(&self, order: Order, events: &mut Vec<OrderEvent>) -> Vec<Trade> {
+        self.store_order(&order, events);
+
+        match order.order_type {
+            OrderType::Market | OrderType::Limit => self.execute_order(order, events),
+            OrderType::StopLoss | OrderType::TakeProfit => {
+                self.park_stop_order(&order);
+                Vec::new()
+            }
+            OrderType::Iceberg | OrderType::TrailingStop => Vec::new(),
+        }
+    }
 
-        // Store order
+    pub(crate) fn store_order(&self, order: &Order, events: &mut Vec<OrderEvent>) {
+        self.metrics.record_order();
         self.orders.insert(order.id, order.clone());
+        self.order_index.insert(order);
+        self.register_client_order_id(order);
+        self.sync_hold(order);
+        events.push(self.order_placed_event(order));
+    }
 
-        // Create and save OrderPlaced event
-        let placed_event = OrderPlacedEvent {
+    pub(crate) fn order_placed_event(&self, order: &Order) -> OrderEvent {
+        OrderEvent::OrderPlaced(OrderPlacedEvent {
+            event_id: new_event_id(),
+            prev_hash: None,
             order_id: order.id,
             user_id: order.user_id,
             symbol: order.symbol.clone(),
@@ -70,14 +498,106 @@ impl MatchingEngine {
             quantity: order.quantity,
             status: order.status,
             timestamp: order.created_at,
+            replaces_order_id: None,
+        })
+    }
+
+    pub(crate) fn park_stop_order(&self, order: &Order) {
+        self.stop_orders
+            .entry(order.symbol.clone())
+            .or_default()
+            .push(order.id);
+    }
+
+    /// Matches an order against the book, rests or expires what is left and
+    /// then runs any stop orders triggered by the resulting trades. Returns
+    /// every trade executed along the way.
+    pub(crate) fn execute_order(&self, order: Order, events: &mut Vec<OrderEvent>) -> Vec<Trade> {
+        let mut queue = VecDeque::from([order]);
+        let mut executed = Vec::new();
+
+        while let Some(mut order) = queue.pop_front() {
+            let trades = if self.symbol_state(&order.symbol) == SymbolState::Trading {
+                self.match_order(&mut order, events)
+            } else {
+                Vec::new()
+            };
+
+            let fill_limit_hit = order.max_fills.is_some_and(|max| trades.len() >= max as usize);
+            if order.remaining_quantity() > Decimal::ZERO && order.status != OrderStatus::Canceled {
+                match order.price {
+                    // Resting would cross the book
+                    Some(price) if fill_limit_hit && self.crosses_book(&order, price) => {
+                        order.status = OrderStatus::Canceled;
+                    }
+                    // No room left on the book
+                    Some(_) if self.make_room(&order, events).is_err() => {
+                        order.status = OrderStatus::Canceled;
+                    }
+                    Some(_) => {
+                        if order.filled_quantity == Decimal::ZERO {
+                            order.status = OrderStatus::Active;
+                        }
+                        self.rest_order(&order);
+                    }
+                    // Unpriced remainders never rest
+                    None => order.status = OrderStatus::Canceled,
+                }
+            }
+            self.orders.insert(order.id, order.clone());
+            if !order.is_open() {
+                self.order_index.close(&order);
+            }
+            self.sync_hold(&order);
+
+            queue.extend(self.process_trades(&order.symbol, &trades, events));
+            executed.extend(trades);
+        }
+        executed
+    }
+
+    fn crosses_book(&self, order: &Order, price: Decimal) -> bool {
+        let Some(book) = self.order_books.get(&order.symbol) else {
+            return false;
         };
+        book.match_against(order.side, Some(price)).is_some()
+    }
 
-        let mut events = vec![OrderEvent::OrderPlaced(placed_event)];
+    fn rest_order(&self, order: &Order) {
+        let mut book = self.book_mut(&order.symbol);
+        book.add(order.clone());
+        self.publish_book_delta(book.side(order.side), order);
+    }
+
+    /// `symbol`'s book, created if nothing has rested there yet: on the
+    /// symbol's price ladder if it has one, otherwise by the book factory.
+    pub(crate) fn book_mut(&self, symbol: &str) -> RefMut<'_, String, Box<dyn OrderBookOps>> {
+        self.order_books
+            .entry(symbol.to_string())
+            .or_insert_with(|| match self.symbols.get(symbol).and_then(|c| c.ladder) {
+                Some(ladder) => Box::new(SymbolBook::new(
+                    LadderOrderBook::new(ladder),
+                    LadderOrderBook::new(ladder),
+                )),
+                None => self.book_factory.new_book(symbol),
+            })
+    }
+
+    pub(crate) fn check_price_ladder(&self, symbol: &str, price: Option<Decimal>) -> Result<(), RejectReason> {
+        match (price, self.symbols.get(symbol).and_then(|c| c.ladder)) {
+            (Some(price), Some(ladder)) => ladder.check(price),
+            _ => Ok(()),
+        }
+    }
 
-        // Match order and generate events
-        let trades = self.match_order(order).await?;
+    /// Emits OrderMatched and any fees for each trade, updates the last price, checks the
+    /// circuit breaker and resolves brackets, returning the stop orders the
+    /// trades triggered. Stops stay parked while the symbol is halted.
+    pub(crate) fn process_trades(&self, symbol: &str, trades: &[Trade], events: &mut Vec<OrderEvent>) -> Vec<Order> {
         for trade in trades {
-            let matched_event = OrderMatchedEvent {
+            events.push(OrderEvent::OrderMatched(OrderMatchedEvent {
+                event_id: new_event_id(),
+                prev_hash: None,
                 order_id: trade.taker_order_id,
                 matched_order_id: trade.maker_order_id,
                 symbol: trade.symbol.clone(),
@@ -85,176 +605,215 @@ impl MatchingEngine {
                 quantity: trade.quantity,
                 side: trade.side,
                 timestamp: trade.created_at,
-            };
-            events.push(OrderEvent::OrderMatched(matched_event));
+            }));
+            self.charge_fees(trade, events);
         }
 
-        // Save all events
-        self.event_store.save_events(events.clone()).await?;
-
-        Ok(events)
+        let Some(last) = trades.last() else {
+            return Vec::new();
+        };
+        self.metrics.record_trades(trades.len());
+        self.last_prices.insert(symbol.to_string(), last.price);
+        self.check_circuit_breaker(symbol, trades, events);
+        self.on_bracket_fills(trades, events);
+        match self.symbol_state(symbol) {
+            SymbolState::Halted | SymbolState::CancelOnly | SymbolState::Closed | SymbolState::Delisted => Vec::new(),
+            SymbolState::Trading | SymbolState::AuctionOnly => self.take_triggered_orders(symbol),
+        }
     }
 
-    async fn handle_cancel_order(
-        &self,
-        _cmd: crate::commands::CancelOrderCommand,
-    ) -> Result<Vec<OrderEvent>, String> {
-        // Implementation for cancel order
-        todo!()
-    }
+    /// Matches `order` against the opposite side of its book. An order the
+    /// self-trade policy stops is left Canceled.
+    fn match_order(&self, order: &mut Order, events: &mut Vec<OrderEvent>) -> Vec<Trade> {
+        let algorithm = self
+            .symbols
+            .get(&order.symbol)
+            .map(|c| c.matching_algorithm)
+            .unwrap_or(self.config.matching_algorithm);
+        let mut book = self.book_mut(&order.symbol);
+        let policy = MatchPolicy {
+            algorithm,
+            limit_price: order.price.or_else(|| self.market_protection_price(order.side, book.as_ref())),
+            max_fills: order.max_fills.map(|max| max as usize),
+            budget: match (order.quote_quantity, self.match_budget(order)) {
+                (Some(quote), Some(held)) => Some(quote.min(held)),
+                (quote, held) => quote.or(held),
+            },
+            self_trade: self.config.self_trade_policy,
+            now: self.clock.now(),
+        };
+        let makers = book.side_mut(order.side.opposite());
+        let fills = makers.match_incoming(order, &policy);
 
-    pub(crate) fn validate_order(&self, cmd: &PlaceOrderCommand) -> Result<(), String> {
-        match cmd.order_type {
-            OrderType::Market => {
-                if cmd.price.is_some() {
-                    return Err("Market orders should not have a price".to_string());
-                }
-            }
-            OrderType::Limit => {
-                if cmd.price.is_none() {
-                    return Err("Limit orders must have a price".to_string());
-                }
-            }
-            OrderType::StopLoss | OrderType::TakeProfit => {
-                if cmd.stop_price.is_none() {
-                    return Err("Stop orders must have a stop price".to_string());
-                }
-            }
-            OrderType::Iceberg => {
-                if cmd.iceberg_visible_quantity.is_none() {
-                    return Err("Iceberg orders must have a visible quantity".to_string());
-                }
-            }
-            OrderType::TrailingStop => {
-                if cmd.trailing_stop_price.is_none() {
-                    return Err("Trailing stop orders must have a trailing stop price".to_string());
-                }
+        // One delta per displayed level touched, once it has settled
+        let mut touched: Vec<&Order> = Vec::new();
+        for fill in fills.iter().filter(|f| f.maker.displayed) {
+            if touched.last().map(|o| o.price) != Some(fill.maker.price) {
+                touched.push(&fill.maker);
             }
         }
-        Ok(())
-    }
+        for maker in touched {
+            self.publish_book_delta(makers, maker);
+        }
+        drop(book);
 
-    async fn match_order(&self, order: Order) -> Result<Vec<Trade>, String> {
         let mut trades = Vec::new();
-        let mut remaining_quantity = order.quantity;
-
-        match order.side {
-            OrderSide::Buy => {
-                // Match against asks (sell orders)
-                if let Some(order_book) = self.order_books.get_mut(&order.symbol) {
-                    while remaining_quantity > Decimal::ZERO {
-                        if let Some(best_ask) = order_book.asks.first() {
-                            match order.order_type {
-                                OrderType::Market => {
-                                    // Market orders match at any price
-                                    let trade_quantity = remaining_quantity.min(best_ask.quantity);
-                                    let trade = self.create_trade(
-                                        &order,
-                                        best_ask.price,
-                                        trade_quantity,
-                                        OrderSide::Buy,
-                                    );
-                                    trades.push(trade);
-                                    remaining_quantity -= trade_quantity;
-                                }
-                                OrderType::Limit => {
-                                    if let Some(price) = order.price {
-                                        if price >= best_ask.price {
-                                            let trade_quantity =
-                                                remaining_quantity.min(best_ask.quantity);
-                                            let trade = self.create_trade(
-                                                &order,
-                                                best_ask.price,
-                                                trade_quantity,
-                                                OrderSide::Buy,
-                                            );
-                                            trades.push(trade);
-                                            remaining_quantity -= trade_quantity;
-                                        } else {
-                                            break;
-                                        }
-                                    }
-                                }
-                                _ => break,
-                            }
-                        } else {
-                            break;
-                        }
-                    }
+        for fill in fills {
+            if fill.quantity == Decimal::ZERO {
+                if fill.maker.status == OrderStatus::Canceled {
+                    self.mark_canceled(fill.maker.id, events);
                 }
+                continue;
             }
-            OrderSide::Sell => {
-                // Match against bids (buy orders)
-                if let Some(order_book) = self.order_books.get_mut(&order.symbol) {
-                    while remaining_quantity > Decimal::ZERO {
-                        if let Some(best_bid) = order_book.bids.first() {
-                            match order.order_type {
-                                OrderType::Market => {
-                                    // Market orders match at any price
-                                    let trade_quantity = remaining_quantity.min(best_bid.quantity);
-                                    let trade = self.create_trade(
-                                        &order,
-                                        best_bid.price,
-                                        trade_quantity,
-                                        OrderSide::Sell,
-                                    );
-                                    trades.push(trade);
-                                    remaining_quantity -= trade_quantity;
-                                }
-                                OrderType::Limit => {
-                                    if let Some(price) = order.price {
-                                        if price <= best_bid.price {
-                                            let trade_quantity =
-                                                remaining_quantity.min(best_bid.quantity);
-                                            let trade = self.create_trade(
-                                                &order,
-                                                best_bid.price,
-                                                trade_quantity,
-                                                OrderSide::Sell,
-                                            );
-                                            trades.push(trade);
-                                            remaining_quantity -= trade_quantity;
-                                        } else {
-                                            break;
-                                        }
-                                    }
-                                }
-                                _ => break,
-                            }
-                        } else {
-                            break;
-                        }
-                    }
+            trades.push(self.create_trade(order, fill.maker.id, fill.price, fill.quantity));
+            if let Some(mut maker) = self.orders.get_mut(&fill.maker.id) {
+                maker.filled_quantity = fill.maker.filled_quantity;
+                maker.status = fill.maker.status;
+                maker.updated_at = fill.maker.updated_at;
+                if !maker.is_open() {
+                    self.order_index.close(&maker);
                 }
             }
+            self.sync_hold(&fill.maker);
+        }
+        if order.status == OrderStatus::Canceled {
+            events.push(OrderEvent::OrderCanceled(OrderCanceledEvent {
+                event_id: new_event_id(),
+                prev_hash: None,
+                order_id: order.id,
+                user_id: order.user_id,
+                symbol: order.symbol.clone(),
+                timestamp: order.updated_at,
+                replaced_by_order_id: None,
+            }));
         }
 
-        Ok(trades)
+        trades
+    }
+
+    /// The furthest a market order may trade from the best opposite price,
+    /// if market protection is configured and the book has that side.
+    fn market_protection_price(&self, side: OrderSide, book: &dyn OrderBookOps) -> Option<Decimal> {
+        let percent = self.config.market_protection_percent?;
+        let band = percent / Decimal::ONE_HUNDRED;
+        let best = book.best(side.opposite())?;
+        Some(match side {
+            OrderSide::Buy => best * (Decimal::ONE + band),
+            OrderSide::Sell => best * (Decimal::ONE - band),
+        })
     }
 
-    fn create_trade(
+    pub(crate) fn create_trade(
         &self,
         order: &Order,
+        maker_order_id: Uuid,
         price: Decimal,
         quantity: Decimal,
-        side: OrderSide,
     ) -> Trade {
         let trade = Trade {
-            id: Uuid::new_v4(),
+            id: self.ids.next_id(),
             symbol: order.symbol.clone(),
             price,
             quantity,
-            side,
+            side: order.side,
             taker_order_id: order.id,
-            maker_order_id: Uuid::new_v4(), // This should be the matched order's ID
-            created_at: chrono::Utc::now(),
+            maker_order_id,
+            created_at: self.clock.now(),
         };
         self.trades.insert(trade.id, trade.clone());
+        let maker_user_id = self.orders.get(&maker_order_id).map(|o| o.user_id);
+        self.trade_log.append(&trade, order.user_id, maker_user_id);
+        self.settle_trade(&trade, order.user_id, maker_user_id);
+        self.record_position(&trade, order.user_id, maker_user_id, false);
+        self.market_data.record(&trade);
+        self.candles.record(&trade);
+        self.feeds.queue_trade(&trade);
+        self.capture_trade(&trade, order.user_id, maker_user_id);
         trade
     }
 
+    /// Pulls every parked stop order on `symbol` whose trigger condition is
+    /// met by the last traded price.
+    fn take_triggered_orders(&self, symbol: &str) -> Vec<Order> {
+        let Some(last_price) = self.last_prices.get(symbol).map(|p| *p) else {
+            return Vec::new();
+        };
+        let Some(mut parked) = self.stop_orders.get_mut(symbol) else {
+            return Vec::new();
+        };
+
+        let mut triggered = Vec::new();
+        parked.retain(|order_id| {
+            let Some(order) = self.get_order(*order_id) else {
+                return false;
+            };
+            let Some(stop_price) = order.stop_price else {
+                return true;
+            };
+            let fires = stop_triggered(order.order_type, order.side, stop_price, last_price);
+            if fires {
+                triggered.push(order);
+            }
+            !fires
+        });
+        triggered
+    }
+
+    /// Pulls an open order out of the book (or the stop list) and marks it
+    /// canceled, emitting OrderCanceled.
+    pub(crate) fn cancel_order(&self, order_id: Uuid, events: &mut Vec<OrderEvent>) -> Option<Order> {
+        let order = self.get_order(order_id).filter(|o| o.is_open())?;
+
+        if let Some(mut book) = self.order_books.get_mut(&order.symbol) {
+            if let Some(removed) = book.remove(order_id) {
+                self.publish_book_delta(book.side(removed.side), &removed);
+            }
+        }
+        if let Some(mut parked) = self.stop_orders.get_mut(&order.symbol) {
+            parked.retain(|id| *id != order_id);
+        }
+        self.mark_canceled(order_id, events)
+    }
+
+    /// Marks an order canceled and emits OrderCanceled, leaving it to the
+    /// caller to take it out of the book.
+    pub(crate) fn mark_canceled(&self, order_id: Uuid, events: &mut Vec<OrderEvent>) -> Option<Order> {
+        let now = self.clock.now();
+        let mut entry = self.orders.get_mut(&order_id)?;
+        entry.status = OrderStatus::Canceled;
+        entry.updated_at = now;
+        let canceled = entry.clone();
+        drop(entry);
+        self.order_index.close(&canceled);
+        self.sync_hold(&canceled);
+
+        events.push(OrderEvent::OrderCanceled(OrderCanceledEvent {
+            event_id: new_event_id(),
+            prev_hash: None,
+            order_id,
+            user_id: canceled.user_id,
+            symbol: canceled.symbol.clone(),
+            timestamp: now,
+            replaced_by_order_id: None,
+        }));
+        Some(canceled)
+    }
+
+    pub fn event_store(&self) -> &dyn EventStore {
+        self.event_store.as_ref()
+    }
+
+    pub fn symbols(&self) -> &SymbolRegistry {
+        &self.symbols
+    }
+
+    /// Displayed liquidity per price level; hidden orders are left out.
     pub fn get_order_book(&self, symbol: &str) -> Option<OrderBook> {
-        self.order_books.get(symbol).map(|ob| ob.clone())
+        let book = self.order_books.get(symbol)?;
+        let mut order_book = OrderBook::new(symbol.to_string());
+        order_book.bids = book.depth(OrderSide::Buy, usize::MAX);
+        order_book.asks = book.depth(OrderSide::Sell, usize::MAX);
+        Some(order_book)
     }
 
     pub fn get_order(&self, order_id: Uuid) -> Option<Order> {
@@ -0,0 +1,229 @@
+use std::fmt;
+
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::daily_limits::DailyLimitKind;
+use crate::invariants::InvariantViolation;
+use crate::kill_switch::KillSwitchTarget;
+use crate::types::OrderSide;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EngineError {
+    /// The command was refused or could not be completed.
+    Rejected(String),
+    /// The engine left a book in a state that should be impossible.
+    InternalInvariantViolation(InvariantViolation),
+    /// A new order reused the id of an existing one.
+    DuplicateOrderId(Uuid),
+    /// `price * quantity` does not fit in a Decimal.
+    NotionalOverflow {
+        symbol: String,
+        price: Decimal,
+        quantity: Decimal,
+    },
+    /// The order failed validation or a pre-trade check.
+    InvalidOrder(RejectReason),
+    /// An all-or-nothing basket had legs that could not be placed.
+    BasketRejected {
+        basket_id: Uuid,
+        legs: Vec<LegRejection>,
+    },
+}
+
+/// Why one leg of a basket was refused.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegRejection {
+    pub order_id: Uuid,
+    pub error: EngineError,
+}
+
+/// Why an order failed validation or a pre-trade check.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RejectReason {
+    MarketOrderWithPrice,
+    MissingPrice,
+    MissingStopPrice,
+    MissingVisibleQuantity,
+    MissingTrailingStopPrice,
+    NonPositiveQuantity(Decimal),
+    NonPositivePrice(Decimal),
+    NonPositiveStopPrice(Decimal),
+    NonPositiveVisibleQuantity(Decimal),
+    IcebergVisibleExceedsTotal { visible: Decimal, total: Decimal },
+    /// Quote quantities are only accepted on market buys.
+    QuoteQuantityNotMarketBuy,
+    /// The quote quantity does not buy anything at the book's prices.
+    QuoteQuantityUnfillable(Decimal),
+    /// The stop would trigger straight away at the last traded price.
+    StopPriceOnWrongSide { stop_price: Decimal, last_price: Decimal },
+    TooManyOpenOrders { limit: usize },
+    QuantityBelowMinimum { quantity: Decimal, minimum: Decimal },
+    NotionalBelowMinimum { notional: Decimal, minimum: Decimal },
+    RateLimitExceeded { max_orders_per_second: u32 },
+    PriceNotOnTick { price: Decimal, tick_size: Decimal },
+    PriceOutOfRange { price: Decimal, min_price: Decimal, max_price: Decimal },
+    TooManyPriceLevels { limit: usize },
+    PriceLevelFull { price: Decimal, limit: usize },
+    /// The user does not have `required` of `asset` available to hold.
+    InsufficientBalance { asset: String, required: Decimal, available: Decimal },
+    /// Filled, the order and the user's other open orders on its side would
+    /// take their position beyond `limit`.
+    ExposureLimitExceeded { side: OrderSide, exposure: Decimal, limit: Decimal },
+    /// The user's equity does not cover the initial margin their positions
+    /// and open orders would need with this order.
+    InsufficientMargin { required: Decimal, equity: Decimal },
+    /// The user's open orders and positions with this order would consume
+    /// more credit than their limit.
+    CreditLimitExceeded { required: Decimal, limit: Decimal },
+    /// Balances are kept but the symbol is not named `BASE/QUOTE`.
+    SymbolWithoutAssets(String),
+    /// A limit price outside the symbol's price band.
+    PriceOutOfBand { price: Decimal, reference_price: Decimal, band_percent: Decimal },
+    /// A limit price too far from the reference price; see
+    /// `PriceDeviationCheck`.
+    PriceDeviationTooLarge { price: Decimal, reference_price: Decimal, max_deviation_percent: Decimal },
+    OrderNotionalTooLarge { notional: Decimal, limit: Decimal },
+    /// The user's open orders with this one would be worth more than `limit`.
+    OpenNotionalTooLarge { notional: Decimal, limit: Decimal },
+    /// Trading is stopped for the order's user or symbol.
+    KillSwitchActive(KillSwitchTarget),
+    /// The user crossed a daily limit and is cancel-only until the next
+    /// session.
+    DailyLimitReached(DailyLimitKind),
+    /// Refused by a `CommandMiddleware` or `RiskCheck`.
+    PreTradeCheck(String),
+}
+
+impl fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RejectReason::MarketOrderWithPrice => write!(f, "Market orders should not have a price"),
+            RejectReason::MissingPrice => write!(f, "Limit orders must have a price"),
+            RejectReason::MissingStopPrice => write!(f, "Stop orders must have a stop price"),
+            RejectReason::MissingVisibleQuantity => write!(f, "Iceberg orders must have a visible quantity"),
+            RejectReason::MissingTrailingStopPrice => {
+                write!(f, "Trailing stop orders must have a trailing stop price")
+            }
+            RejectReason::NonPositiveQuantity(quantity) => write!(f, "Quantity {} must be positive", quantity),
+            RejectReason::NonPositivePrice(price) => write!(f, "Price {} must be positive", price),
+            RejectReason::NonPositiveStopPrice(price) => write!(f, "Stop price {} must be positive", price),
+            RejectReason::NonPositiveVisibleQuantity(quantity) => {
+                write!(f, "Visible quantity {} must be positive", quantity)
+            }
+            RejectReason::IcebergVisibleExceedsTotal { visible, total } => {
+                write!(f, "Visible quantity {} exceeds the total {}", visible, total)
+            }
+            RejectReason::QuoteQuantityNotMarketBuy => write!(f, "Quote quantities are only accepted on market buys"),
+            RejectReason::QuoteQuantityUnfillable(quote_quantity) => {
+                write!(f, "Quote quantity {} buys nothing at the book's prices", quote_quantity)
+            }
+            RejectReason::StopPriceOnWrongSide { stop_price, last_price } => write!(
+                f,
+                "Stop price {} would trigger immediately at the last price {}",
+                stop_price, last_price
+            ),
+            RejectReason::TooManyOpenOrders { limit } => write!(f, "User already has {} open orders", limit),
+            RejectReason::QuantityBelowMinimum { quantity, minimum } => {
+                write!(f, "Quantity {} is below the minimum of {}", quantity, minimum)
+            }
+            RejectReason::NotionalBelowMinimum { notional, minimum } => {
+                write!(f, "Order notional {} is below the minimum of {}", notional, minimum)
+            }
+            RejectReason::RateLimitExceeded { max_orders_per_second } => {
+                write!(f, "Order rate exceeds {} per second", max_orders_per_second)
+            }
+            RejectReason::PriceNotOnTick { price, tick_size } => {
+                write!(f, "Price {} is not a multiple of the tick size {}", price, tick_size)
+            }
+            RejectReason::PriceOutOfRange { price, min_price, max_price } => {
+                write!(f, "Price {} is outside {}..={}", price, min_price, max_price)
+            }
+            RejectReason::TooManyPriceLevels { limit } => write!(f, "Book side already has {} price levels", limit),
+            RejectReason::PriceLevelFull { price, limit } => {
+                write!(f, "Price level {} already has {} orders", price, limit)
+            }
+            RejectReason::InsufficientBalance { asset, required, available } => {
+                write!(f, "Insufficient {} balance: {} required, {} available", asset, required, available)
+            }
+            RejectReason::ExposureLimitExceeded { side, exposure, limit } => {
+                let direction = match side {
+                    OrderSide::Buy => "long",
+                    OrderSide::Sell => "short",
+                };
+                write!(f, "Exposure of {} {} would exceed the limit of {}", exposure, direction, limit)
+            }
+            RejectReason::InsufficientMargin { required, equity } => {
+                write!(f, "Insufficient margin: {} required, equity is {}", required, equity)
+            }
+            RejectReason::CreditLimitExceeded { required, limit } => {
+                write!(f, "Credit of {} would exceed the limit of {}", required, limit)
+            }
+            RejectReason::SymbolWithoutAssets(symbol) => {
+                write!(f, "Symbol {} does not name its base and quote assets", symbol)
+            }
+            RejectReason::PriceOutOfBand { price, reference_price, band_percent } => write!(
+                f,
+                "Price {} is outside the {}% band around the reference price {}",
+                price, band_percent, reference_price
+            ),
+            RejectReason::PriceDeviationTooLarge { price, reference_price, max_deviation_percent } => write!(
+                f,
+                "Price {} is more than {}% away from the reference price {}",
+                price, max_deviation_percent, reference_price
+            ),
+            RejectReason::OrderNotionalTooLarge { notional, limit } => {
+                write!(f, "Order notional {} exceeds the limit of {}", notional, limit)
+            }
+            RejectReason::OpenNotionalTooLarge { notional, limit } => {
+                write!(f, "Open order notional {} would exceed the limit of {}", notional, limit)
+            }
+            RejectReason::KillSwitchActive(target) => write!(f, "Trading is stopped for {}", target),
+            RejectReason::DailyLimitReached(limit) => {
+                let limit = match limit {
+                    DailyLimitKind::TradedNotional => "traded notional",
+                    DailyLimitKind::Loss => "loss",
+                };
+                write!(f, "Daily {} limit reached: only cancels are accepted this session", limit)
+            }
+            RejectReason::PreTradeCheck(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EngineError::Rejected(reason) => write!(f, "{}", reason),
+            EngineError::InternalInvariantViolation(violation) => {
+                write!(f, "Internal invariant violated: {}", violation)
+            }
+            EngineError::DuplicateOrderId(order_id) => write!(f, "Order {} already exists", order_id),
+            EngineError::NotionalOverflow { symbol, price, quantity } => {
+                write!(f, "Notional of {} {} at {} overflows", quantity, symbol, price)
+            }
+            EngineError::InvalidOrder(reason) => write!(f, "{}", reason),
+            EngineError::BasketRejected { basket_id, legs } => {
+                write!(f, "Basket {} rejected:", basket_id)?;
+                for leg in legs {
+                    write!(f, " leg {}: {};", leg.order_id, leg.error)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for EngineError {}
+
+impl From<String> for EngineError {
+    fn from(reason: String) -> Self {
+        EngineError::Rejected(reason)
+    }
+}
+
+impl From<RejectReason> for EngineError {
+    fn from(reason: RejectReason) -> Self {
+        EngineError::InvalidOrder(reason)
+    }
+}
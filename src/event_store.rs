@@ -1,59 +1,325 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::sync::RwLock;
+
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use dashmap::{DashMap, DashSet};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::digest::{hex, sha256};
 use crate::events::OrderEvent;
+use crate::subscription::{event_users, EventFeed, EventFilter, EventSubscription};
+
+/// An event and its position in the store's log, counted from 0.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredEvent {
+    pub offset: u64,
+    pub event: OrderEvent,
+}
+
+/// Event timestamps from `start` up to but excluding `end`. An unset bound
+/// is open.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimeRange {
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+}
+
+impl TimeRange {
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    pub fn between(start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        Self {
+            start: Some(start),
+            end: Some(end),
+        }
+    }
+
+    pub fn since(start: DateTime<Utc>) -> Self {
+        Self {
+            start: Some(start),
+            end: None,
+        }
+    }
+
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        self.start.is_none_or(|start| at >= start) && self.end.is_none_or(|end| at < end)
+    }
+}
+
+const VERIFY_PAGE: usize = 1024;
+
+/// What `verify_integrity` found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntegrityReport {
+    pub checked: u64,
+    /// Offset of the first event whose `prev_hash` is not the hash of the
+    /// event before it.
+    pub first_broken: Option<u64>,
+    /// Hash of the last event checked. Nothing after the last event vouches
+    /// for it, so compare this with a head recorded elsewhere.
+    pub head: Option<String>,
+}
+
+impl IntegrityReport {
+    pub fn is_intact(&self) -> bool {
+        self.first_broken.is_none()
+    }
+}
 
 #[async_trait]
 pub trait EventStore: Send + Sync {
+    /// Saves the whole batch or, on error, none of it: the engine treats a
+    /// failed call as nothing having been written. Events whose `event_id`
+    /// the store already holds are skipped, so a batch retried after a
+    /// timeout is saved once.
     async fn save_events(&self, events: Vec<OrderEvent>) -> Result<(), String>;
     async fn get_events(&self, order_id: Uuid) -> Result<Vec<OrderEvent>, String>;
+    /// Every event in the order it was saved.
     async fn get_all_events(&self) -> Result<Vec<OrderEvent>, String>;
+
+    /// Up to `max` events starting at `offset`, so a consumer can resume
+    /// after the last offset it saw.
+    async fn read_from(&self, offset: u64, max: usize) -> Result<Vec<StoredEvent>, String> {
+        Ok(self
+            .get_all_events()
+            .await?
+            .into_iter()
+            .zip(0..)
+            .skip(offset as usize)
+            .take(max)
+            .map(|(event, offset)| StoredEvent { offset, event })
+            .collect())
+    }
+
+    /// The secondary lookups below return events in save order. Their
+    /// defaults scan everything; the stores in this crate keep indexes.
+    async fn get_events_by_symbol(&self, symbol: &str, range: TimeRange) -> Result<Vec<OrderEvent>, String> {
+        let mut events = self.get_all_events().await?;
+        events.retain(|e| e.symbol() == Some(symbol) && range.contains(e.timestamp()));
+        Ok(events)
+    }
+
+    /// Events concerning `user_id`'s orders, matches against them included.
+    async fn get_events_by_user(&self, user_id: Uuid, range: TimeRange) -> Result<Vec<OrderEvent>, String> {
+        let mut owners = HashMap::new();
+        let mut events = Vec::new();
+        for event in self.get_all_events().await? {
+            if let OrderEvent::OrderPlaced(placed) = &event {
+                owners.insert(placed.order_id, placed.user_id);
+            }
+            let users = event_users(&event, |order_id| owners.get(&order_id).copied());
+            if users.contains(&user_id) && range.contains(event.timestamp()) {
+                events.push(event);
+            }
+        }
+        Ok(events)
+    }
+
+    /// Events of one `OrderEvent::kind`.
+    async fn get_events_by_type(&self, kind: &str, range: TimeRange) -> Result<Vec<OrderEvent>, String> {
+        let mut events = self.get_all_events().await?;
+        events.retain(|e| e.kind() == kind && range.contains(e.timestamp()));
+        Ok(events)
+    }
+
+    /// Walks the hash chain over the events at `offsets`, checking each
+    /// links to the one before it. Only stores chaining their events since
+    /// the first one in the range pass.
+    async fn verify_integrity(&self, offsets: Range<u64>) -> Result<IntegrityReport, String> {
+        let mut prev = match offsets.start.checked_sub(1) {
+            Some(before) => self.read_from(before, 1).await?.first().map(|stored| event_hash(&stored.event)),
+            None => None,
+        };
+        let mut report = IntegrityReport {
+            checked: 0,
+            first_broken: None,
+            head: None,
+        };
+        let mut offset = offsets.start;
+        while offset < offsets.end {
+            let max = (offsets.end - offset).min(VERIFY_PAGE as u64) as usize;
+            let page = self.read_from(offset, max).await?;
+            let Some(last) = page.last() else {
+                break;
+            };
+            offset = last.offset + 1;
+            for stored in &page {
+                if report.first_broken.is_none() && stored.event.prev_hash() != prev.as_deref() {
+                    report.first_broken = Some(stored.offset);
+                }
+                prev = Some(event_hash(&stored.event));
+                report.checked += 1;
+            }
+        }
+        if report.checked > 0 {
+            report.head = prev;
+        }
+        Ok(report)
+    }
+
+    /// Events saved from now on that pass `filter`.
+    fn subscribe(&self, _filter: EventFilter) -> Result<EventSubscription, String> {
+        Err("This event store does not support subscriptions".to_string())
+    }
+}
+
+/// `events` without those `saved` says the store holds or that repeat an
+/// earlier event in the batch. Events with a nil id are always kept.
+pub(crate) fn unsaved(
+    events: Vec<OrderEvent>,
+    mut saved: impl FnMut(Uuid) -> Result<bool, String>,
+) -> Result<Vec<OrderEvent>, String> {
+    let mut batch = HashSet::new();
+    let mut kept = Vec::with_capacity(events.len());
+    for event in events {
+        let event_id = event.event_id();
+        if event_id.is_nil() || (!saved(event_id)? && batch.insert(event_id)) {
+            kept.push(event);
+        }
+    }
+    Ok(kept)
+}
+
+/// Hex SHA-256 of the event as JSON, its own `prev_hash` included.
+pub(crate) fn event_hash(event: &OrderEvent) -> String {
+    hex(&sha256(&serde_json::to_vec(event).expect("events serialize to JSON")))
+}
+
+/// Links `events` onto the chain ending at `head`, leaving `head` at the
+/// last of them.
+pub(crate) fn chain_events(events: &mut [OrderEvent], head: &mut Option<String>) {
+    for event in events {
+        *event.prev_hash_mut() = head.take();
+        *head = Some(event_hash(event));
+    }
 }
 
 pub struct InMemoryEventStore {
-    events: dashmap::DashMap<Uuid, Vec<OrderEvent>>,
+    log: RwLock<Vec<OrderEvent>>,
+    event_ids: DashSet<Uuid>,
+    by_order: DashMap<Uuid, Vec<usize>>,
+    by_symbol: DashMap<String, Vec<usize>>,
+    by_user: DashMap<Uuid, Vec<usize>>,
+    by_kind: DashMap<&'static str, Vec<usize>>,
+    /// Who placed each order, for indexing matches by user.
+    owners: DashMap<Uuid, Uuid>,
+    feed: EventFeed,
+    hash_chain: bool,
+}
+
+impl Default for InMemoryEventStore {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl InMemoryEventStore {
     pub fn new() -> Self {
         Self {
-            events: dashmap::DashMap::new(),
+            log: RwLock::new(Vec::new()),
+            event_ids: DashSet::new(),
+            by_order: DashMap::new(),
+            by_symbol: DashMap::new(),
+            by_user: DashMap::new(),
+            by_kind: DashMap::new(),
+            owners: DashMap::new(),
+            feed: EventFeed::new(),
+            hash_chain: false,
         }
     }
+
+    /// Has each saved event carry the hash of the one before it.
+    pub fn with_hash_chain(mut self) -> Self {
+        self.hash_chain = true;
+        self
+    }
+
+    fn indexed(&self, positions: Option<&Vec<usize>>, range: TimeRange) -> Vec<OrderEvent> {
+        let log = self.log.read().unwrap();
+        positions
+            .map(|positions| {
+                positions
+                    .iter()
+                    .map(|&i| &log[i])
+                    .filter(|e| range.contains(e.timestamp()))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
 }
 
 #[async_trait]
 impl EventStore for InMemoryEventStore {
     async fn save_events(&self, events: Vec<OrderEvent>) -> Result<(), String> {
+        // Readers wait for the whole batch
+        let mut log = self.log.write().unwrap();
+        let mut events = unsaved(events, |event_id| Ok(self.event_ids.contains(&event_id)))?;
+        if self.hash_chain {
+            chain_events(&mut events, &mut log.last().map(event_hash));
+        }
+        self.feed.publish(&events);
         for event in events {
-            let order_id = match &event {
-                OrderEvent::OrderPlaced(e) => e.order_id,
-                OrderEvent::OrderCanceled(e) => e.order_id,
-                OrderEvent::OrderUpdated(e) => e.order_id,
-                OrderEvent::OrderMatched(e) => e.order_id,
-                OrderEvent::OrderPartiallyFilled(e) => e.order_id,
-                OrderEvent::OrderFilled(e) => e.order_id,
-            };
-            
-            self.events
-                .entry(order_id)
-                .or_insert_with(Vec::new)
-                .push(event);
+            let position = log.len();
+            self.event_ids.insert(event.event_id());
+            if let Some(order_id) = event.order_id() {
+                self.by_order.entry(order_id).or_default().push(position);
+            }
+            if let Some(symbol) = event.symbol() {
+                self.by_symbol.entry(symbol.to_string()).or_default().push(position);
+            }
+            if let OrderEvent::OrderPlaced(placed) = &event {
+                self.owners.insert(placed.order_id, placed.user_id);
+            }
+            for user_id in event_users(&event, |order_id| self.owners.get(&order_id).map(|user_id| *user_id)) {
+                self.by_user.entry(user_id).or_default().push(position);
+            }
+            self.by_kind.entry(event.kind()).or_default().push(position);
+            log.push(event);
         }
         Ok(())
     }
 
     async fn get_events(&self, order_id: Uuid) -> Result<Vec<OrderEvent>, String> {
-        Ok(self.events
-            .get(&order_id)
-            .map(|events| events.clone())
-            .unwrap_or_default())
+        Ok(self.indexed(self.by_order.get(&order_id).as_deref(), TimeRange::all()))
     }
 
     async fn get_all_events(&self) -> Result<Vec<OrderEvent>, String> {
-        Ok(self.events
+        Ok(self.log.read().unwrap().clone())
+    }
+
+    async fn read_from(&self, offset: u64, max: usize) -> Result<Vec<StoredEvent>, String> {
+        let log = self.log.read().unwrap();
+        let start = (offset as usize).min(log.len());
+        Ok(log[start..]
             .iter()
-            .flat_map(|entry| entry.value().clone())
+            .take(max)
+            .zip(offset..)
+            .map(|(event, offset)| StoredEvent {
+                offset,
+                event: event.clone(),
+            })
             .collect())
     }
-} 
\ No newline at end of file
+
+    async fn get_events_by_symbol(&self, symbol: &str, range: TimeRange) -> Result<Vec<OrderEvent>, String> {
+        Ok(self.indexed(self.by_symbol.get(symbol).as_deref(), range))
+    }
+
+    async fn get_events_by_user(&self, user_id: Uuid, range: TimeRange) -> Result<Vec<OrderEvent>, String> {
+        Ok(self.indexed(self.by_user.get(&user_id).as_deref(), range))
+    }
+
+    async fn get_events_by_type(&self, kind: &str, range: TimeRange) -> Result<Vec<OrderEvent>, String> {
+        Ok(self.indexed(self.by_kind.get(kind).as_deref(), range))
+    }
+
+    fn subscribe(&self, filter: EventFilter) -> Result<EventSubscription, String> {
+        Ok(self.feed.subscribe(filter))
+    }
+}
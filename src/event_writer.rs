@@ -0,0 +1,195 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::{mpsc, Notify};
+use tokio::task::JoinHandle;
+
+use crate::engine::MatchingEngine;
+use crate::error::EngineError;
+use crate::events::OrderEvent;
+use crate::persistence::PersistenceFailurePolicy;
+
+const RETRY_BACKOFF: Duration = Duration::from_millis(100);
+
+/// What a command does when the event writer's queue is full.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum QueueFullPolicy {
+    /// Wait for the writer to make room.
+    #[default]
+    Block,
+    /// Refuse new commands until there is room. Commands already admitted
+    /// wait.
+    RejectCommands,
+    /// Drop the events and count them, leaving the WAL as their only
+    /// record: `recover` regenerates and saves them. Snapshots, which empty
+    /// the WAL, are refused once any are dropped. Waits like `Block` if the
+    /// engine has no WAL.
+    DropToWal,
+}
+
+/// The queue between commands and the task saving their events.
+pub(crate) struct EventWriter {
+    /// Taken on shutdown, which ends the writer once it has drained.
+    sender: Mutex<Option<mpsc::Sender<Vec<OrderEvent>>>>,
+    receiver: Mutex<Option<mpsc::Receiver<Vec<OrderEvent>>>>,
+    when_full: QueueFullPolicy,
+    /// Batches queued or being saved.
+    unsaved: AtomicUsize,
+    saved: Notify,
+    dropped_events: AtomicU64,
+}
+
+impl EventWriter {
+    fn sender(&self) -> Option<mpsc::Sender<Vec<OrderEvent>>> {
+        self.sender.lock().unwrap().clone()
+    }
+
+    fn batch_saved(&self) {
+        self.unsaved.fetch_sub(1, Ordering::SeqCst);
+        self.saved.notify_waiters();
+    }
+}
+
+impl MatchingEngine {
+    /// Saves events on a writer task instead of inline, through a queue of
+    /// up to `capacity` commands' events. Start the task with
+    /// `spawn_event_writer`. Commands under the Rollback policy still save
+    /// inline, since undoing one needs the store's answer; under
+    /// ReturnError a failed save is retried rather than returned.
+    pub fn with_async_persistence(mut self, capacity: usize, when_full: QueueFullPolicy) -> Self {
+        let (sender, receiver) = mpsc::channel(capacity.max(1));
+        self.event_writer = Some(EventWriter {
+            sender: Mutex::new(Some(sender)),
+            receiver: Mutex::new(Some(receiver)),
+            when_full,
+            unsaved: AtomicUsize::new(0),
+            saved: Notify::new(),
+            dropped_events: AtomicU64::new(0),
+        });
+        self
+    }
+
+    /// Starts the task saving queued events, which runs until
+    /// `shutdown_event_writer`.
+    pub fn spawn_event_writer(self: &Arc<Self>) -> Result<JoinHandle<()>, String> {
+        let receiver = self
+            .event_writer
+            .as_ref()
+            .ok_or("Async persistence is not enabled")?
+            .receiver
+            .lock()
+            .unwrap()
+            .take()
+            .ok_or("The event writer is already running")?;
+        Ok(tokio::spawn(self.clone().write_events(receiver)))
+    }
+
+    /// Waits until every event queued so far has been saved.
+    pub async fn flush_events(&self) {
+        let Some(writer) = &self.event_writer else {
+            return;
+        };
+        loop {
+            let saved = writer.saved.notified();
+            if writer.unsaved.load(Ordering::SeqCst) == 0 {
+                return;
+            }
+            saved.await;
+        }
+    }
+
+    /// Stops admitting commands long enough to drain the queue, then ends
+    /// the writer task. Events of later commands are saved inline.
+    pub async fn shutdown_event_writer(&self) {
+        let _gate = self.command_gate.write().await;
+        self.flush_events().await;
+        if let Some(writer) = &self.event_writer {
+            writer.sender.lock().unwrap().take();
+        }
+    }
+
+    /// Waits for queued events to be saved, failing if there is no writer
+    /// task to save them.
+    pub(crate) async fn drain_event_writer(&self) -> Result<(), String> {
+        let Some(writer) = &self.event_writer else {
+            return Ok(());
+        };
+        if writer.unsaved.load(Ordering::SeqCst) > 0 && writer.receiver.lock().unwrap().is_some() {
+            return Err("Queued events are unsaved and the event writer is not running".to_string());
+        }
+        self.flush_events().await;
+        Ok(())
+    }
+
+    /// Events dropped under `QueueFullPolicy::DropToWal`.
+    pub fn dropped_event_count(&self) -> u64 {
+        self.event_writer
+            .as_ref()
+            .map_or(0, |writer| writer.dropped_events.load(Ordering::SeqCst))
+    }
+
+    /// Admission check for `QueueFullPolicy::RejectCommands`.
+    pub(crate) fn check_event_queue(&self) -> Result<(), EngineError> {
+        let Some(writer) = &self.event_writer else {
+            return Ok(());
+        };
+        if writer.when_full != QueueFullPolicy::RejectCommands {
+            return Ok(());
+        }
+        match writer.sender() {
+            Some(sender) if sender.capacity() == 0 => {
+                Err(EngineError::Rejected("Event queue is full".to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Hands `events` to the writer task, returning false if they have to
+    /// be saved inline instead.
+    pub(crate) async fn enqueue_events(&self, events: &[OrderEvent]) -> bool {
+        let Some(writer) = &self.event_writer else {
+            return false;
+        };
+        if self.persistence_policy == PersistenceFailurePolicy::Rollback {
+            return false;
+        }
+        let Some(sender) = writer.sender() else {
+            return false;
+        };
+        writer.unsaved.fetch_add(1, Ordering::SeqCst);
+        let sent = if writer.when_full == QueueFullPolicy::DropToWal && self.wal.is_some() {
+            match sender.try_send(events.to_vec()) {
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    writer.dropped_events.fetch_add(events.len() as u64, Ordering::SeqCst);
+                    writer.batch_saved();
+                    return true;
+                }
+                sent => sent.is_ok(),
+            }
+        } else {
+            sender.send(events.to_vec()).await.is_ok()
+        };
+        if !sent {
+            // The writer only goes away on shutdown, which this command raced
+            writer.batch_saved();
+        }
+        sent
+    }
+
+    async fn write_events(self: Arc<Self>, mut receiver: mpsc::Receiver<Vec<OrderEvent>>) {
+        let Some(writer) = &self.event_writer else {
+            return;
+        };
+        while let Some(events) = receiver.recv().await {
+            // Events the halt policy adds are buffered with the batch; nobody
+            // is waiting for them here
+            while self.save_to_event_store(&mut events.clone()).await.is_err() {
+                tokio::time::sleep(RETRY_BACKOFF).await;
+            }
+            let _ = self.flush_trades().await;
+            let _ = self.flush_settlements().await;
+            writer.batch_saved();
+        }
+    }
+}
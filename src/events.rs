@@ -3,6 +3,13 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::candles::Candle;
+use crate::daily_limits::DailyLimitKind;
+use crate::fees::Liquidity;
+use crate::invariants::InvariantViolation;
+use crate::kill_switch::KillSwitchTarget;
+use crate::persistence::HaltScope;
+use crate::trading_state::SymbolState;
 use crate::types::{OrderSide, OrderStatus, OrderType};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,10 +20,289 @@ pub enum OrderEvent {
     OrderMatched(OrderMatchedEvent),
     OrderPartiallyFilled(OrderPartiallyFilledEvent),
     OrderFilled(OrderFilledEvent),
+    BracketOrderPlaced(BracketOrderPlacedEvent),
+    BracketOrderActivated(BracketOrderActivatedEvent),
+    BracketOrderCompleted(BracketOrderCompletedEvent),
+    PersistenceHalted(PersistenceHaltedEvent),
+    PersistenceResumed(PersistenceResumedEvent),
+    SyntheticTradeExecuted(SyntheticTradeExecutedEvent),
+    AuctionPriceDetermined(AuctionPriceDeterminedEvent),
+    SymbolStateChanged(SymbolStateChangedEvent),
+    CircuitBreakerTriggered(CircuitBreakerTriggeredEvent),
+    InvariantViolated(InvariantViolatedEvent),
+    OrderRejected(OrderRejectedEvent),
+    RateLimitExceeded(RateLimitExceededEvent),
+    BookLevelEvicted(BookLevelEvictedEvent),
+    CandleClosed(CandleClosedEvent),
+    KillSwitchActivated(KillSwitchActivatedEvent),
+    KillSwitchReleased(KillSwitchReleasedEvent),
+    LiquidationTriggered(LiquidationTriggeredEvent),
+    TradeBusted(TradeBustedEvent),
+    FeeCharged(FeeChargedEvent),
+    DailyLimitReached(DailyLimitReachedEvent),
+}
+
+impl OrderEvent {
+    /// Assigned by the engine when it creates the event, and what stores
+    /// dedupe saves on. Nil for events written before ids existed.
+    pub fn event_id(&self) -> Uuid {
+        match self {
+            OrderEvent::OrderPlaced(e) => e.event_id,
+            OrderEvent::OrderCanceled(e) => e.event_id,
+            OrderEvent::OrderUpdated(e) => e.event_id,
+            OrderEvent::OrderMatched(e) => e.event_id,
+            OrderEvent::OrderPartiallyFilled(e) => e.event_id,
+            OrderEvent::OrderFilled(e) => e.event_id,
+            OrderEvent::BracketOrderPlaced(e) => e.event_id,
+            OrderEvent::BracketOrderActivated(e) => e.event_id,
+            OrderEvent::BracketOrderCompleted(e) => e.event_id,
+            OrderEvent::PersistenceHalted(e) => e.event_id,
+            OrderEvent::PersistenceResumed(e) => e.event_id,
+            OrderEvent::SyntheticTradeExecuted(e) => e.event_id,
+            OrderEvent::AuctionPriceDetermined(e) => e.event_id,
+            OrderEvent::SymbolStateChanged(e) => e.event_id,
+            OrderEvent::CircuitBreakerTriggered(e) => e.event_id,
+            OrderEvent::InvariantViolated(e) => e.event_id,
+            OrderEvent::OrderRejected(e) => e.event_id,
+            OrderEvent::RateLimitExceeded(e) => e.event_id,
+            OrderEvent::BookLevelEvicted(e) => e.event_id,
+            OrderEvent::CandleClosed(e) => e.event_id,
+            OrderEvent::KillSwitchActivated(e) => e.event_id,
+            OrderEvent::KillSwitchReleased(e) => e.event_id,
+            OrderEvent::LiquidationTriggered(e) => e.event_id,
+            OrderEvent::TradeBusted(e) => e.event_id,
+            OrderEvent::FeeCharged(e) => e.event_id,
+            OrderEvent::DailyLimitReached(e) => e.event_id,
+        }
+    }
+
+    /// Hex SHA-256 of the event saved before this one, set by stores that
+    /// chain their events. None for the first event and in unchained stores.
+    pub fn prev_hash(&self) -> Option<&str> {
+        match self {
+            OrderEvent::OrderPlaced(e) => e.prev_hash.as_deref(),
+            OrderEvent::OrderCanceled(e) => e.prev_hash.as_deref(),
+            OrderEvent::OrderUpdated(e) => e.prev_hash.as_deref(),
+            OrderEvent::OrderMatched(e) => e.prev_hash.as_deref(),
+            OrderEvent::OrderPartiallyFilled(e) => e.prev_hash.as_deref(),
+            OrderEvent::OrderFilled(e) => e.prev_hash.as_deref(),
+            OrderEvent::BracketOrderPlaced(e) => e.prev_hash.as_deref(),
+            OrderEvent::BracketOrderActivated(e) => e.prev_hash.as_deref(),
+            OrderEvent::BracketOrderCompleted(e) => e.prev_hash.as_deref(),
+            OrderEvent::PersistenceHalted(e) => e.prev_hash.as_deref(),
+            OrderEvent::PersistenceResumed(e) => e.prev_hash.as_deref(),
+            OrderEvent::SyntheticTradeExecuted(e) => e.prev_hash.as_deref(),
+            OrderEvent::AuctionPriceDetermined(e) => e.prev_hash.as_deref(),
+            OrderEvent::SymbolStateChanged(e) => e.prev_hash.as_deref(),
+            OrderEvent::CircuitBreakerTriggered(e) => e.prev_hash.as_deref(),
+            OrderEvent::InvariantViolated(e) => e.prev_hash.as_deref(),
+            OrderEvent::OrderRejected(e) => e.prev_hash.as_deref(),
+            OrderEvent::RateLimitExceeded(e) => e.prev_hash.as_deref(),
+            OrderEvent::BookLevelEvicted(e) => e.prev_hash.as_deref(),
+            OrderEvent::CandleClosed(e) => e.prev_hash.as_deref(),
+            OrderEvent::KillSwitchActivated(e) => e.prev_hash.as_deref(),
+            OrderEvent::KillSwitchReleased(e) => e.prev_hash.as_deref(),
+            OrderEvent::LiquidationTriggered(e) => e.prev_hash.as_deref(),
+            OrderEvent::TradeBusted(e) => e.prev_hash.as_deref(),
+            OrderEvent::FeeCharged(e) => e.prev_hash.as_deref(),
+            OrderEvent::DailyLimitReached(e) => e.prev_hash.as_deref(),
+        }
+    }
+
+    pub(crate) fn prev_hash_mut(&mut self) -> &mut Option<String> {
+        match self {
+            OrderEvent::OrderPlaced(e) => &mut e.prev_hash,
+            OrderEvent::OrderCanceled(e) => &mut e.prev_hash,
+            OrderEvent::OrderUpdated(e) => &mut e.prev_hash,
+            OrderEvent::OrderMatched(e) => &mut e.prev_hash,
+            OrderEvent::OrderPartiallyFilled(e) => &mut e.prev_hash,
+            OrderEvent::OrderFilled(e) => &mut e.prev_hash,
+            OrderEvent::BracketOrderPlaced(e) => &mut e.prev_hash,
+            OrderEvent::BracketOrderActivated(e) => &mut e.prev_hash,
+            OrderEvent::BracketOrderCompleted(e) => &mut e.prev_hash,
+            OrderEvent::PersistenceHalted(e) => &mut e.prev_hash,
+            OrderEvent::PersistenceResumed(e) => &mut e.prev_hash,
+            OrderEvent::SyntheticTradeExecuted(e) => &mut e.prev_hash,
+            OrderEvent::AuctionPriceDetermined(e) => &mut e.prev_hash,
+            OrderEvent::SymbolStateChanged(e) => &mut e.prev_hash,
+            OrderEvent::CircuitBreakerTriggered(e) => &mut e.prev_hash,
+            OrderEvent::InvariantViolated(e) => &mut e.prev_hash,
+            OrderEvent::OrderRejected(e) => &mut e.prev_hash,
+            OrderEvent::RateLimitExceeded(e) => &mut e.prev_hash,
+            OrderEvent::BookLevelEvicted(e) => &mut e.prev_hash,
+            OrderEvent::CandleClosed(e) => &mut e.prev_hash,
+            OrderEvent::KillSwitchActivated(e) => &mut e.prev_hash,
+            OrderEvent::KillSwitchReleased(e) => &mut e.prev_hash,
+            OrderEvent::LiquidationTriggered(e) => &mut e.prev_hash,
+            OrderEvent::TradeBusted(e) => &mut e.prev_hash,
+            OrderEvent::FeeCharged(e) => &mut e.prev_hash,
+            OrderEvent::DailyLimitReached(e) => &mut e.prev_hash,
+        }
+    }
+
+    /// The order this event belongs to; operator events have none.
+    pub fn order_id(&self) -> Option<Uuid> {
+        match self {
+            OrderEvent::OrderPlaced(e) => Some(e.order_id),
+            OrderEvent::OrderCanceled(e) => Some(e.order_id),
+            OrderEvent::OrderUpdated(e) => Some(e.order_id),
+            OrderEvent::OrderMatched(e) => Some(e.order_id),
+            OrderEvent::OrderPartiallyFilled(e) => Some(e.order_id),
+            OrderEvent::OrderFilled(e) => Some(e.order_id),
+            OrderEvent::BracketOrderPlaced(e) => Some(e.entry_order_id),
+            OrderEvent::BracketOrderActivated(e) => Some(e.entry_order_id),
+            OrderEvent::BracketOrderCompleted(e) => Some(e.entry_order_id),
+            OrderEvent::SyntheticTradeExecuted(e) => Some(e.order_id),
+            OrderEvent::OrderRejected(e) => Some(e.order_id),
+            OrderEvent::TradeBusted(e) => Some(e.taker_order_id),
+            OrderEvent::FeeCharged(e) => Some(e.order_id),
+            OrderEvent::PersistenceHalted(_)
+            | OrderEvent::PersistenceResumed(_)
+            | OrderEvent::AuctionPriceDetermined(_)
+            | OrderEvent::SymbolStateChanged(_)
+            | OrderEvent::CircuitBreakerTriggered(_)
+            | OrderEvent::InvariantViolated(_)
+            | OrderEvent::RateLimitExceeded(_)
+            | OrderEvent::BookLevelEvicted(_)
+            | OrderEvent::CandleClosed(_)
+            | OrderEvent::KillSwitchActivated(_)
+            | OrderEvent::KillSwitchReleased(_)
+            | OrderEvent::LiquidationTriggered(_)
+            | OrderEvent::DailyLimitReached(_) => None,
+        }
+    }
+
+    pub fn symbol(&self) -> Option<&str> {
+        match self {
+            OrderEvent::OrderPlaced(e) => Some(&e.symbol),
+            OrderEvent::OrderCanceled(e) => Some(&e.symbol),
+            OrderEvent::OrderUpdated(e) => Some(&e.symbol),
+            OrderEvent::OrderMatched(e) => Some(&e.symbol),
+            OrderEvent::OrderPartiallyFilled(e) => Some(&e.symbol),
+            OrderEvent::OrderFilled(e) => Some(&e.symbol),
+            OrderEvent::BracketOrderPlaced(e) => Some(&e.symbol),
+            OrderEvent::BracketOrderActivated(e) => Some(&e.symbol),
+            OrderEvent::BracketOrderCompleted(e) => Some(&e.symbol),
+            OrderEvent::PersistenceHalted(e) => e.symbol.as_deref(),
+            OrderEvent::PersistenceResumed(e) => e.symbol.as_deref(),
+            OrderEvent::SyntheticTradeExecuted(e) => Some(&e.symbol),
+            OrderEvent::AuctionPriceDetermined(e) => Some(&e.symbol),
+            OrderEvent::SymbolStateChanged(e) => Some(&e.symbol),
+            OrderEvent::CircuitBreakerTriggered(e) => Some(&e.symbol),
+            OrderEvent::InvariantViolated(e) => Some(&e.symbol),
+            OrderEvent::OrderRejected(e) => Some(&e.symbol),
+            OrderEvent::RateLimitExceeded(e) => Some(&e.symbol),
+            OrderEvent::BookLevelEvicted(e) => Some(&e.symbol),
+            OrderEvent::CandleClosed(e) => Some(&e.candle.symbol),
+            OrderEvent::KillSwitchActivated(e) => e.target.symbol(),
+            OrderEvent::KillSwitchReleased(e) => e.target.symbol(),
+            OrderEvent::LiquidationTriggered(_) | OrderEvent::DailyLimitReached(_) => None,
+            OrderEvent::TradeBusted(e) => Some(&e.symbol),
+            OrderEvent::FeeCharged(e) => Some(&e.symbol),
+        }
+    }
+    /// The user the event names. Fills and matches only name their order.
+    pub fn user_id(&self) -> Option<Uuid> {
+        match self {
+            OrderEvent::OrderPlaced(e) => Some(e.user_id),
+            OrderEvent::OrderCanceled(e) => Some(e.user_id),
+            OrderEvent::OrderUpdated(e) => Some(e.user_id),
+            OrderEvent::SyntheticTradeExecuted(e) => Some(e.user_id),
+            OrderEvent::OrderRejected(e) => Some(e.user_id),
+            OrderEvent::RateLimitExceeded(e) => Some(e.user_id),
+            OrderEvent::BracketOrderPlaced(e) => Some(e.user_id),
+            OrderEvent::KillSwitchActivated(e) => e.target.user_id(),
+            OrderEvent::KillSwitchReleased(e) => e.target.user_id(),
+            OrderEvent::LiquidationTriggered(e) => Some(e.user_id),
+            OrderEvent::FeeCharged(e) => Some(e.user_id),
+            OrderEvent::DailyLimitReached(e) => Some(e.user_id),
+            OrderEvent::OrderMatched(_)
+            | OrderEvent::OrderPartiallyFilled(_)
+            | OrderEvent::OrderFilled(_)
+            | OrderEvent::BracketOrderActivated(_)
+            | OrderEvent::BracketOrderCompleted(_)
+            | OrderEvent::PersistenceHalted(_)
+            | OrderEvent::PersistenceResumed(_)
+            | OrderEvent::AuctionPriceDetermined(_)
+            | OrderEvent::SymbolStateChanged(_)
+            | OrderEvent::CircuitBreakerTriggered(_)
+            | OrderEvent::InvariantViolated(_)
+            | OrderEvent::BookLevelEvicted(_)
+            | OrderEvent::CandleClosed(_)
+            | OrderEvent::TradeBusted(_) => None,
+        }
+    }
+
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        match self {
+            OrderEvent::OrderPlaced(e) => e.timestamp,
+            OrderEvent::OrderCanceled(e) => e.timestamp,
+            OrderEvent::OrderUpdated(e) => e.timestamp,
+            OrderEvent::OrderMatched(e) => e.timestamp,
+            OrderEvent::OrderPartiallyFilled(e) => e.timestamp,
+            OrderEvent::OrderFilled(e) => e.timestamp,
+            OrderEvent::BracketOrderPlaced(e) => e.timestamp,
+            OrderEvent::BracketOrderActivated(e) => e.timestamp,
+            OrderEvent::BracketOrderCompleted(e) => e.timestamp,
+            OrderEvent::PersistenceHalted(e) => e.timestamp,
+            OrderEvent::PersistenceResumed(e) => e.timestamp,
+            OrderEvent::SyntheticTradeExecuted(e) => e.timestamp,
+            OrderEvent::AuctionPriceDetermined(e) => e.timestamp,
+            OrderEvent::SymbolStateChanged(e) => e.timestamp,
+            OrderEvent::CircuitBreakerTriggered(e) => e.timestamp,
+            OrderEvent::InvariantViolated(e) => e.timestamp,
+            OrderEvent::OrderRejected(e) => e.timestamp,
+            OrderEvent::RateLimitExceeded(e) => e.timestamp,
+            OrderEvent::BookLevelEvicted(e) => e.timestamp,
+            OrderEvent::CandleClosed(e) => e.timestamp,
+            OrderEvent::KillSwitchActivated(e) => e.timestamp,
+            OrderEvent::KillSwitchReleased(e) => e.timestamp,
+            OrderEvent::LiquidationTriggered(e) => e.timestamp,
+            OrderEvent::TradeBusted(e) => e.timestamp,
+            OrderEvent::FeeCharged(e) => e.timestamp,
+            OrderEvent::DailyLimitReached(e) => e.timestamp,
+        }
+    }
+
+    /// The variant name, as it appears in the serialized event.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            OrderEvent::OrderPlaced(_) => "OrderPlaced",
+            OrderEvent::OrderCanceled(_) => "OrderCanceled",
+            OrderEvent::OrderUpdated(_) => "OrderUpdated",
+            OrderEvent::OrderMatched(_) => "OrderMatched",
+            OrderEvent::OrderPartiallyFilled(_) => "OrderPartiallyFilled",
+            OrderEvent::OrderFilled(_) => "OrderFilled",
+            OrderEvent::BracketOrderPlaced(_) => "BracketOrderPlaced",
+            OrderEvent::BracketOrderActivated(_) => "BracketOrderActivated",
+            OrderEvent::BracketOrderCompleted(_) => "BracketOrderCompleted",
+            OrderEvent::PersistenceHalted(_) => "PersistenceHalted",
+            OrderEvent::PersistenceResumed(_) => "PersistenceResumed",
+            OrderEvent::SyntheticTradeExecuted(_) => "SyntheticTradeExecuted",
+            OrderEvent::AuctionPriceDetermined(_) => "AuctionPriceDetermined",
+            OrderEvent::SymbolStateChanged(_) => "SymbolStateChanged",
+            OrderEvent::CircuitBreakerTriggered(_) => "CircuitBreakerTriggered",
+            OrderEvent::InvariantViolated(_) => "InvariantViolated",
+            OrderEvent::OrderRejected(_) => "OrderRejected",
+            OrderEvent::RateLimitExceeded(_) => "RateLimitExceeded",
+            OrderEvent::BookLevelEvicted(_) => "BookLevelEvicted",
+            OrderEvent::CandleClosed(_) => "CandleClosed",
+            OrderEvent::KillSwitchActivated(_) => "KillSwitchActivated",
+            OrderEvent::KillSwitchReleased(_) => "KillSwitchReleased",
+            OrderEvent::LiquidationTriggered(_) => "LiquidationTriggered",
+            OrderEvent::TradeBusted(_) => "TradeBusted",
+            OrderEvent::FeeCharged(_) => "FeeCharged",
+            OrderEvent::DailyLimitReached(_) => "DailyLimitReached",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderPlacedEvent {
+    #[serde(default)]
+    pub event_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
     pub order_id: Uuid,
     pub user_id: Uuid,
     pub symbol: String,
@@ -26,28 +312,49 @@ pub struct OrderPlacedEvent {
     pub quantity: Decimal,
     pub status: OrderStatus,
     pub timestamp: DateTime<Utc>,
+    /// The order this one replaced through a cancel/replace.
+    #[serde(default)]
+    pub replaces_order_id: Option<Uuid>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderCanceledEvent {
+    #[serde(default)]
+    pub event_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
     pub order_id: Uuid,
     pub user_id: Uuid,
     pub symbol: String,
     pub timestamp: DateTime<Utc>,
+    /// The order that took this one's place through a cancel/replace.
+    #[serde(default)]
+    pub replaced_by_order_id: Option<Uuid>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderUpdatedEvent {
+    #[serde(default)]
+    pub event_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
     pub order_id: Uuid,
     pub user_id: Uuid,
     pub symbol: String,
     pub new_price: Option<Decimal>,
     pub new_quantity: Option<Decimal>,
     pub timestamp: DateTime<Utc>,
+    /// Whether the order kept its place in the queue.
+    #[serde(default)]
+    pub retained_priority: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderMatchedEvent {
+    #[serde(default)]
+    pub event_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
     pub order_id: Uuid,
     pub matched_order_id: Uuid,
     pub symbol: String,
@@ -59,6 +366,10 @@ pub struct OrderMatchedEvent {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderPartiallyFilledEvent {
+    #[serde(default)]
+    pub event_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
     pub order_id: Uuid,
     pub symbol: String,
     pub filled_quantity: Decimal,
@@ -68,8 +379,300 @@ pub struct OrderPartiallyFilledEvent {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderFilledEvent {
+    #[serde(default)]
+    pub event_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
     pub order_id: Uuid,
     pub symbol: String,
     pub filled_quantity: Decimal,
     pub timestamp: DateTime<Utc>,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BracketOrderPlacedEvent {
+    #[serde(default)]
+    pub event_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+    pub bracket_id: Uuid,
+    pub entry_order_id: Uuid,
+    pub stop_loss_order_id: Uuid,
+    pub take_profit_order_id: Uuid,
+    pub user_id: Uuid,
+    pub symbol: String,
+    pub stop_loss_price: Decimal,
+    pub take_profit_price: Decimal,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BracketOrderActivatedEvent {
+    #[serde(default)]
+    pub event_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+    pub bracket_id: Uuid,
+    pub entry_order_id: Uuid,
+    pub stop_loss_order_id: Uuid,
+    pub take_profit_order_id: Uuid,
+    pub symbol: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BracketOrderCompletedEvent {
+    #[serde(default)]
+    pub event_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+    pub bracket_id: Uuid,
+    pub entry_order_id: Uuid,
+    pub executed_order_id: Uuid,
+    pub canceled_order_id: Uuid,
+    pub symbol: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistenceHaltedEvent {
+    #[serde(default)]
+    pub event_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+    pub scope: HaltScope,
+    pub symbol: Option<String>,
+    pub reason: String,
+    pub buffered_events: usize,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistenceResumedEvent {
+    #[serde(default)]
+    pub event_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+    pub scope: HaltScope,
+    pub symbol: Option<String>,
+    pub flushed_events: usize,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyntheticTradeExecutedEvent {
+    #[serde(default)]
+    pub event_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+    pub order_id: Uuid,
+    pub user_id: Uuid,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub price: Decimal,
+    pub quantity: Decimal,
+    pub base_leg_order_id: Uuid,
+    pub quote_leg_order_id: Uuid,
+    pub leg_trade_ids: Vec<Uuid>,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuctionPriceDeterminedEvent {
+    #[serde(default)]
+    pub event_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+    pub symbol: String,
+    pub price: Decimal,
+    pub volume: Decimal,
+    pub imbalance: Decimal,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolStateChangedEvent {
+    #[serde(default)]
+    pub event_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+    pub symbol: String,
+    pub previous_state: SymbolState,
+    pub state: SymbolState,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitBreakerTriggeredEvent {
+    #[serde(default)]
+    pub event_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+    pub symbol: String,
+    pub reference_price: Decimal,
+    pub trigger_price: Decimal,
+    pub move_percent: Decimal,
+    pub halted_until: DateTime<Utc>,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvariantViolatedEvent {
+    #[serde(default)]
+    pub event_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+    pub symbol: String,
+    pub violation: InvariantViolation,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderRejectedEvent {
+    #[serde(default)]
+    pub event_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+    pub order_id: Uuid,
+    pub user_id: Uuid,
+    pub symbol: String,
+    pub reason: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitExceededEvent {
+    #[serde(default)]
+    pub event_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+    pub user_id: Uuid,
+    pub symbol: String,
+    pub max_orders_per_second: u32,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// A level was cleared to make room for a better-priced one under
+/// `BookLimitPolicy::EvictWorstLevel`. Each order also gets OrderCanceled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookLevelEvictedEvent {
+    #[serde(default)]
+    pub event_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub price: Decimal,
+    pub order_ids: Vec<Uuid>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// A bar closed on its interval's first trade after it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CandleClosedEvent {
+    #[serde(default)]
+    pub event_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+    pub candle: Candle,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Trading was stopped for `target`. Each order it canceled also gets
+/// OrderCanceled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KillSwitchActivatedEvent {
+    #[serde(default)]
+    pub event_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+    pub target: KillSwitchTarget,
+    pub reason: String,
+    pub canceled_order_ids: Vec<Uuid>,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KillSwitchReleasedEvent {
+    #[serde(default)]
+    pub event_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+    pub target: KillSwitchTarget,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// The user's equity fell below their maintenance margin. Their open
+/// orders on margined symbols are canceled and their positions in
+/// `symbols` closed at market, each by a command of its own.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidationTriggeredEvent {
+    #[serde(default)]
+    pub event_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+    pub user_id: Uuid,
+    pub equity: Decimal,
+    pub maintenance_margin: Decimal,
+    pub symbols: Vec<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// A trade was canceled after the fact; see `MatchingEngine::bust_trade`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeBustedEvent {
+    #[serde(default)]
+    pub event_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+    pub trade_id: Uuid,
+    pub symbol: String,
+    pub price: Decimal,
+    pub quantity: Decimal,
+    /// The taker's side.
+    pub side: OrderSide,
+    pub taker_order_id: Uuid,
+    pub maker_order_id: Uuid,
+    pub reason: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// What one side of a trade owes under the symbol's fee schedule; a
+/// negative amount is a rebate owed to the user.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeChargedEvent {
+    #[serde(default)]
+    pub event_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+    pub trade_id: Uuid,
+    pub order_id: Uuid,
+    pub user_id: Uuid,
+    pub symbol: String,
+    pub liquidity: Liquidity,
+    pub asset: String,
+    pub amount: Decimal,
+    /// Referrer of the taker's order, if it named one and paid a fee.
+    #[serde(default)]
+    pub referrer_id: Option<Uuid>,
+    /// Part of `amount` attributed to the referrer.
+    #[serde(default)]
+    pub referral_amount: Decimal,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// The user crossed one of their daily limits and is cancel-only until the
+/// session after `session_start`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyLimitReachedEvent {
+    #[serde(default)]
+    pub event_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+    pub user_id: Uuid,
+    pub limit: DailyLimitKind,
+    pub value: Decimal,
+    pub cap: Decimal,
+    pub session_start: DateTime<Utc>,
+    pub timestamp: DateTime<Utc>,
+}
@@ -0,0 +1,131 @@
+use std::sync::{Arc, Mutex};
+
+use dashmap::DashMap;
+use tokio::sync::broadcast;
+
+use crate::depth::L2Snapshot;
+use crate::engine::MatchingEngine;
+use crate::market_data::Ticker;
+use crate::types::Trade;
+
+/// Messages a feed subscriber may fall behind by before it starts missing
+/// them.
+const FEED_CHANNEL_CAPACITY: usize = 1024;
+
+struct TradeFeed {
+    sender: broadcast::Sender<Trade>,
+    /// Trades of the running command, published once it is done.
+    pending: Mutex<Vec<Trade>>,
+}
+
+/// A feed that publishes a value when it differs from the last one sent.
+struct ChangeFeed<T> {
+    sender: broadcast::Sender<T>,
+    last: Mutex<Option<T>>,
+}
+
+impl<T: Clone + PartialEq> ChangeFeed<T> {
+    fn new() -> Self {
+        Self {
+            sender: broadcast::channel(FEED_CHANNEL_CAPACITY).0,
+            last: Mutex::new(None),
+        }
+    }
+
+    fn publish(&self, value: T, same: impl Fn(&T, &T) -> bool) {
+        let mut last = self.last.lock().unwrap();
+        if last.as_ref().is_some_and(|last| same(last, &value)) {
+            return;
+        }
+        *last = Some(value.clone());
+        let _ = self.sender.send(value);
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct MarketFeeds {
+    trades: DashMap<String, TradeFeed>,
+    depth: DashMap<(String, usize), ChangeFeed<Arc<L2Snapshot>>>,
+    tickers: DashMap<String, ChangeFeed<Ticker>>,
+}
+
+impl MarketFeeds {
+    pub(crate) fn queue_trade(&self, trade: &Trade) {
+        if let Some(feed) = self.trades.get(&trade.symbol) {
+            if feed.sender.receiver_count() > 0 {
+                feed.pending.lock().unwrap().push(trade.clone());
+            }
+        }
+    }
+
+    /// Drops the queued trades of a command that was rolled back.
+    pub(crate) fn discard_trades(&self, symbol: &str) {
+        if let Some(feed) = self.trades.get(symbol) {
+            feed.pending.lock().unwrap().clear();
+        }
+    }
+}
+
+impl MatchingEngine {
+    /// Trades on `symbol`, published after the command that executed them.
+    /// A receiver that falls more than 1024 trades behind gets
+    /// `RecvError::Lagged`.
+    pub fn subscribe_trades(&self, symbol: &str) -> broadcast::Receiver<Trade> {
+        self.feeds
+            .trades
+            .entry(symbol.to_string())
+            .or_insert_with(|| TradeFeed {
+                sender: broadcast::channel(FEED_CHANNEL_CAPACITY).0,
+                pending: Mutex::new(Vec::new()),
+            })
+            .sender
+            .subscribe()
+    }
+
+    /// `symbol`'s top `levels` levels per side, published after each
+    /// command that changed them.
+    pub fn subscribe_depth(&self, symbol: &str, levels: usize) -> broadcast::Receiver<Arc<L2Snapshot>> {
+        self.feeds
+            .depth
+            .entry((symbol.to_string(), levels))
+            .or_insert_with(ChangeFeed::new)
+            .sender
+            .subscribe()
+    }
+
+    /// `symbol`'s ticker, published after each command that changed it.
+    pub fn subscribe_ticker(&self, symbol: &str) -> broadcast::Receiver<Ticker> {
+        self.feeds
+            .tickers
+            .entry(symbol.to_string())
+            .or_insert_with(ChangeFeed::new)
+            .sender
+            .subscribe()
+    }
+
+    /// Publishes what the last command changed on `symbols` to the feeds
+    /// someone is subscribed to.
+    pub(crate) fn publish_market_feeds(&self, symbols: &[String]) {
+        let feeds = &self.feeds;
+        for symbol in symbols {
+            if let Some(feed) = feeds.trades.get(symbol) {
+                for trade in std::mem::take(&mut *feed.pending.lock().unwrap()) {
+                    let _ = feed.sender.send(trade);
+                }
+            }
+            for feed in feeds.depth.iter().filter(|feed| feed.key().0 == *symbol) {
+                if feed.sender.receiver_count() == 0 {
+                    continue;
+                }
+                let snapshot = self.get_l2_snapshot(symbol, feed.key().1);
+                feed.publish(Arc::new(snapshot), |last, new| last.bids == new.bids && last.asks == new.asks);
+            }
+            if let Some(feed) = feeds.tickers.get(symbol) {
+                if feed.sender.receiver_count() > 0 {
+                    feed.publish(self.get_ticker(symbol), |last, new| last == new);
+                }
+            }
+            self.publish_depth_diff(symbol);
+        }
+    }
+}
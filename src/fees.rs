@@ -0,0 +1,71 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::accounts::symbol_assets;
+use crate::draws::new_event_id;
+use crate::engine::MatchingEngine;
+use crate::events::{FeeChargedEvent, OrderEvent};
+use crate::types::Trade;
+
+/// What each side of a trade in a symbol pays, in percent of the trade's
+/// notional and in its quote asset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct FeeSchedule {
+    /// Negative to pay makers a rebate.
+    pub maker_fee_percent: Decimal,
+    pub taker_fee_percent: Decimal,
+    /// Share, in percent, of the taker fee attributed to the referrer of
+    /// the taker's order.
+    #[serde(default)]
+    pub referral_percent: Decimal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Liquidity {
+    Maker,
+    Taker,
+}
+
+impl MatchingEngine {
+    /// Emits FeeCharged for each side of the trade under its symbol's fee
+    /// schedule. Fees are reported, not collected: balances are left to
+    /// whatever settles the events.
+    pub(crate) fn charge_fees(&self, trade: &Trade, events: &mut Vec<OrderEvent>) {
+        let Some(schedule) = self.symbols.get(&trade.symbol).and_then(|c| c.fees) else {
+            return;
+        };
+        let Ok((_, quote)) = symbol_assets(&trade.symbol) else {
+            return;
+        };
+        let notional = trade.price * trade.quantity;
+        let sides = [
+            (trade.taker_order_id, Liquidity::Taker, schedule.taker_fee_percent),
+            (trade.maker_order_id, Liquidity::Maker, schedule.maker_fee_percent),
+        ];
+        for (order_id, liquidity, percent) in sides {
+            let Some((user_id, referrer_id)) = self.orders.get(&order_id).map(|o| (o.user_id, o.referrer_id)) else {
+                continue;
+            };
+            let amount = notional * percent / Decimal::ONE_HUNDRED;
+            let referrer_id = referrer_id.filter(|_| liquidity == Liquidity::Taker && amount > Decimal::ZERO);
+            let referral_amount = match referrer_id {
+                Some(_) => amount * schedule.referral_percent / Decimal::ONE_HUNDRED,
+                None => Decimal::ZERO,
+            };
+            events.push(OrderEvent::FeeCharged(FeeChargedEvent {
+                event_id: new_event_id(),
+                prev_hash: None,
+                trade_id: trade.id,
+                order_id,
+                user_id,
+                symbol: trade.symbol.clone(),
+                liquidity,
+                asset: quote.to_string(),
+                amount,
+                referrer_id,
+                referral_amount,
+                timestamp: trade.created_at,
+            }));
+        }
+    }
+}
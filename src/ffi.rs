@@ -0,0 +1,300 @@
+//! A C ABI for embedding the engine in-process, declared in
+//! `include/matching_engine.h` (generated by cbindgen from this file; see
+//! `cbindgen.toml`). Ids cross as 16 raw bytes, decimals as strings, and
+//! events come back as the JSON the event store saves. Every function
+//! returns an `ME_` status; on failure `engine_last_error` says why.
+
+use std::collections::VecDeque;
+use std::ffi::{c_char, CStr};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use chrono::Utc;
+use rust_decimal::Decimal;
+use tokio::runtime::Runtime;
+use uuid::Uuid;
+
+use crate::commands::{CancelOrderCommand, OrderCommand, PlaceOrderCommand};
+use crate::engine::MatchingEngine;
+use crate::error::EngineError;
+use crate::event_store::InMemoryEventStore;
+use crate::events::OrderEvent;
+use crate::types::{OrderSide, OrderType};
+
+pub const ME_OK: i32 = 0;
+pub const ME_ERR_NULL_ARGUMENT: i32 = -1;
+pub const ME_ERR_INVALID_ARGUMENT: i32 = -2;
+pub const ME_ERR_REJECTED: i32 = -3;
+pub const ME_ERR_DUPLICATE_ORDER: i32 = -4;
+pub const ME_ERR_INTERNAL: i32 = -5;
+/// Nothing was written; the needed length, NUL included, was.
+pub const ME_ERR_BUFFER_TOO_SMALL: i32 = -6;
+
+pub const ME_SIDE_BUY: i32 = 0;
+pub const ME_SIDE_SELL: i32 = 1;
+
+pub const ME_ORDER_MARKET: i32 = 0;
+pub const ME_ORDER_LIMIT: i32 = 1;
+pub const ME_ORDER_STOP_LOSS: i32 = 2;
+pub const ME_ORDER_TAKE_PROFIT: i32 = 3;
+
+/// An engine with an in-memory event store and its own runtime. Calls on
+/// one engine may come from any thread.
+pub struct MeEngine {
+    runtime: Runtime,
+    engine: MatchingEngine,
+    /// Events of the commands run so far, until polled.
+    events: Mutex<VecDeque<OrderEvent>>,
+    last_error: Mutex<String>,
+}
+
+#[repr(C)]
+pub struct MeOrder {
+    pub order_id: [u8; 16],
+    pub user_id: [u8; 16],
+    pub symbol: *const c_char,
+    /// An `ME_SIDE_` value.
+    pub side: i32,
+    /// An `ME_ORDER_` value.
+    pub order_type: i32,
+    /// NULL for market orders.
+    pub price: *const c_char,
+    pub quantity: *const c_char,
+    /// NULL unless a stop order.
+    pub stop_price: *const c_char,
+}
+
+impl MeEngine {
+    fn fail(&self, status: i32, message: impl Into<String>) -> i32 {
+        *self.last_error.lock().unwrap() = message.into();
+        status
+    }
+
+    fn run(&self, command: OrderCommand) -> i32 {
+        match self.runtime.block_on(self.engine.handle_command(command)) {
+            Ok(events) => {
+                self.events.lock().unwrap().extend(events);
+                ME_OK
+            }
+            Err(e) => {
+                let status = match e {
+                    EngineError::DuplicateOrderId(_) => ME_ERR_DUPLICATE_ORDER,
+                    EngineError::InternalInvariantViolation(_) => ME_ERR_INTERNAL,
+                    EngineError::Rejected(_) => ME_ERR_REJECTED,
+                    _ => ME_ERR_INVALID_ARGUMENT,
+                };
+                self.fail(status, e.to_string())
+            }
+        }
+    }
+}
+
+/// A new engine, or NULL if its runtime could not be started. Free it with
+/// `engine_destroy`.
+#[no_mangle]
+pub extern "C" fn engine_create() -> *mut MeEngine {
+    let created = catch_unwind(|| {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .ok()?;
+        let engine = runtime.block_on(async { MatchingEngine::new(Box::new(InMemoryEventStore::new())) });
+        Some(Box::new(MeEngine {
+            runtime,
+            engine,
+            events: Mutex::new(VecDeque::new()),
+            last_error: Mutex::new(String::new()),
+        }))
+    });
+    match created {
+        Ok(Some(engine)) => Box::into_raw(engine),
+        _ => std::ptr::null_mut(),
+    }
+}
+
+/// # Safety
+///
+/// `engine` must come from `engine_create` and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn engine_destroy(engine: *mut MeEngine) {
+    if !engine.is_null() {
+        drop(Box::from_raw(engine));
+    }
+}
+
+/// # Safety
+///
+/// `engine` must come from `engine_create`; `order` must point to an
+/// `MeOrder` whose strings are NUL-terminated or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn engine_place_order(engine: *mut MeEngine, order: *const MeOrder) -> i32 {
+    let (Some(engine), Some(order)) = (engine.as_ref(), order.as_ref()) else {
+        return ME_ERR_NULL_ARGUMENT;
+    };
+    guarded(engine, || {
+        let command = match place_order_command(order) {
+            Ok(command) => command,
+            Err(e) => return engine.fail(ME_ERR_INVALID_ARGUMENT, e),
+        };
+        engine.run(OrderCommand::PlaceOrder(command))
+    })
+}
+
+/// # Safety
+///
+/// `engine` must come from `engine_create`; `order_id` and `user_id` must
+/// point to 16 bytes each and `symbol` to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn engine_cancel_order(
+    engine: *mut MeEngine,
+    order_id: *const u8,
+    user_id: *const u8,
+    symbol: *const c_char,
+) -> i32 {
+    let Some(engine) = engine.as_ref() else {
+        return ME_ERR_NULL_ARGUMENT;
+    };
+    if order_id.is_null() || user_id.is_null() || symbol.is_null() {
+        return ME_ERR_NULL_ARGUMENT;
+    }
+    guarded(engine, || {
+        let symbol = match string(symbol, "symbol") {
+            Ok(symbol) => symbol,
+            Err(e) => return engine.fail(ME_ERR_INVALID_ARGUMENT, e),
+        };
+        engine.run(OrderCommand::CancelOrder(CancelOrderCommand {
+            order_id: read_uuid(order_id),
+            client_order_id: None,
+            user_id: read_uuid(user_id),
+            symbol,
+            timestamp: Utc::now(),
+        }))
+    })
+}
+
+/// Writes the events of the commands run since the last poll to `buffer`
+/// as a NUL-terminated JSON array, oldest first, and forgets them. When
+/// `buffer` is too small they are kept; `length` gets the size needed
+/// either way.
+///
+/// # Safety
+///
+/// `engine` must come from `engine_create`; `buffer` must have room for
+/// `capacity` bytes and `length` may be NULL.
+#[no_mangle]
+pub unsafe extern "C" fn engine_poll_events(
+    engine: *mut MeEngine,
+    buffer: *mut c_char,
+    capacity: usize,
+    length: *mut usize,
+) -> i32 {
+    let Some(engine) = engine.as_ref() else {
+        return ME_ERR_NULL_ARGUMENT;
+    };
+    guarded(engine, || {
+        let mut events = engine.events.lock().unwrap();
+        let json = match serde_json::to_vec(&*events) {
+            Ok(json) => json,
+            Err(e) => return engine.fail(ME_ERR_INTERNAL, e.to_string()),
+        };
+        let status = write_out(&json, buffer, capacity, length);
+        if status == ME_OK {
+            events.clear();
+        }
+        status
+    })
+}
+
+/// Writes why the last failing call on `engine` failed, NUL-terminated.
+///
+/// # Safety
+///
+/// As for `engine_poll_events`.
+#[no_mangle]
+pub unsafe extern "C" fn engine_last_error(
+    engine: *mut MeEngine,
+    buffer: *mut c_char,
+    capacity: usize,
+    length: *mut usize,
+) -> i32 {
+    let Some(engine) = engine.as_ref() else {
+        return ME_ERR_NULL_ARGUMENT;
+    };
+    let message = engine.last_error.lock().unwrap().clone();
+    write_out(message.as_bytes(), buffer, capacity, length)
+}
+
+/// Runs `call`, turning a panic into `ME_ERR_INTERNAL` rather than letting
+/// it unwind into C.
+fn guarded(engine: &MeEngine, call: impl FnOnce() -> i32) -> i32 {
+    catch_unwind(AssertUnwindSafe(call)).unwrap_or_else(|_| engine.fail(ME_ERR_INTERNAL, "The engine panicked"))
+}
+
+unsafe fn place_order_command(order: &MeOrder) -> Result<PlaceOrderCommand, String> {
+    let side = match order.side {
+        ME_SIDE_BUY => OrderSide::Buy,
+        ME_SIDE_SELL => OrderSide::Sell,
+        other => return Err(format!("Unknown side {}", other)),
+    };
+    let order_type = match order.order_type {
+        ME_ORDER_MARKET => OrderType::Market,
+        ME_ORDER_LIMIT => OrderType::Limit,
+        ME_ORDER_STOP_LOSS => OrderType::StopLoss,
+        ME_ORDER_TAKE_PROFIT => OrderType::TakeProfit,
+        other => return Err(format!("Unknown order type {}", other)),
+    };
+    Ok(PlaceOrderCommand {
+        order_id: Uuid::from_bytes(order.order_id),
+        client_order_id: None,
+        user_id: Uuid::from_bytes(order.user_id),
+        symbol: string(order.symbol, "symbol")?,
+        order_type,
+        side,
+        price: optional_decimal(order.price, "price")?,
+        quantity: optional_decimal(order.quantity, "quantity")?.ok_or("quantity is required")?,
+        iceberg_visible_quantity: None,
+        stop_price: optional_decimal(order.stop_price, "stop_price")?,
+        trailing_stop_price: None,
+        displayed: true,
+        max_fills: None,
+        referrer_id: None,
+        quote_quantity: None,
+        timestamp: Utc::now(),
+    })
+}
+
+unsafe fn string(s: *const c_char, name: &str) -> Result<String, String> {
+    if s.is_null() {
+        return Err(format!("{} is required", name));
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map(str::to_string)
+        .map_err(|_| format!("{} is not UTF-8", name))
+}
+
+unsafe fn optional_decimal(s: *const c_char, name: &str) -> Result<Option<Decimal>, String> {
+    if s.is_null() {
+        return Ok(None);
+    }
+    let s = string(s, name)?;
+    Decimal::from_str(&s).map(Some).map_err(|_| format!("{} is not a decimal: {}", name, s))
+}
+
+unsafe fn read_uuid(bytes: *const u8) -> Uuid {
+    Uuid::from_bytes(*(bytes as *const [u8; 16]))
+}
+
+unsafe fn write_out(bytes: &[u8], buffer: *mut c_char, capacity: usize, length: *mut usize) -> i32 {
+    if !length.is_null() {
+        *length = bytes.len() + 1;
+    }
+    if buffer.is_null() || capacity <= bytes.len() {
+        return ME_ERR_BUFFER_TOO_SMALL;
+    }
+    std::ptr::copy_nonoverlapping(bytes.as_ptr(), buffer as *mut u8, bytes.len());
+    *buffer.add(bytes.len()) = 0;
+    ME_OK
+}
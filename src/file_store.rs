@@ -0,0 +1,480 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::archive::{read_manifest, ObjectStore, SegmentArchive};
+use crate::event_store::{chain_events, event_hash, unsaved, EventStore, StoredEvent, TimeRange};
+use crate::events::OrderEvent;
+use crate::subscription::{event_users, EventFeed, EventFilter, EventSubscription};
+
+const SEGMENT_EXTENSION: &str = "log";
+const DEFAULT_SEGMENT_BYTES: u64 = 64 * 1024 * 1024;
+
+/// When appended events are forced to disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FsyncPolicy {
+    /// Before every `save_events` returns.
+    #[default]
+    Always,
+    /// After every n batches; a crash can lose the batches since the last.
+    EveryBatches(u32),
+    /// Whenever the OS flushes its page cache.
+    Never,
+}
+
+/// Where a frame's payload sits on disk, and its event's timestamp so
+/// range queries can skip frames without reading them.
+#[derive(Debug, Clone, Copy)]
+struct FrameRef {
+    segment: u64,
+    offset: u64,
+    len: u32,
+    timestamp: DateTime<Utc>,
+}
+
+struct Log {
+    file: File,
+    segment: u64,
+    segment_len: u64,
+    unsynced: u32,
+    all: Vec<FrameRef>,
+    event_ids: HashSet<Uuid>,
+    by_order: HashMap<Uuid, Vec<usize>>,
+    by_symbol: HashMap<String, Vec<usize>>,
+    by_user: HashMap<Uuid, Vec<usize>>,
+    by_kind: HashMap<&'static str, Vec<usize>>,
+    owners: HashMap<Uuid, Uuid>,
+    /// Hash of the last event, for chaining the next.
+    head: Option<String>,
+}
+
+impl Log {
+    /// An empty log appending to the last of `segments`.
+    fn new(dir: &Path, segments: &[u64]) -> Result<Self, String> {
+        let last = segments.last().copied().unwrap_or(0);
+        Ok(Self {
+            file: open_segment(dir, last)?,
+            segment: last,
+            segment_len: 0,
+            unsynced: 0,
+            all: Vec::new(),
+            event_ids: HashSet::new(),
+            by_order: HashMap::new(),
+            by_symbol: HashMap::new(),
+            by_user: HashMap::new(),
+            by_kind: HashMap::new(),
+            owners: HashMap::new(),
+            head: None,
+        })
+    }
+
+    /// Indexes the events in `bytes`, the contents of `segment`.
+    fn load_segment(&mut self, segment: u64, bytes: &[u8], path: &Path) -> Result<(), String> {
+        let (frames, offset) = scan_frames(bytes);
+        let mut last = None;
+        for payload in frames {
+            let event: OrderEvent = serde_json::from_slice(&bytes[payload.clone()])
+                .map_err(|e| format!("Corrupt event at {}:{}: {}", path.display(), payload.start, e))?;
+            let frame = FrameRef {
+                segment,
+                offset: payload.start as u64,
+                len: payload.len() as u32,
+                timestamp: event.timestamp(),
+            };
+            self.index(frame, &event);
+            last = Some(event);
+        }
+        if let Some(event) = last {
+            self.head = Some(event_hash(&event));
+        }
+        if offset < bytes.len() {
+            if segment != self.segment {
+                return Err(format!("Truncated event log segment {}", path.display()));
+            }
+            // A crash mid-append; the batch was never acknowledged
+            truncate(path, offset as u64)?;
+        }
+        self.segment_len = offset as u64;
+        Ok(())
+    }
+
+    fn index(&mut self, frame: FrameRef, event: &OrderEvent) {
+        let position = self.all.len();
+        self.event_ids.insert(event.event_id());
+        if let Some(order_id) = event.order_id() {
+            self.by_order.entry(order_id).or_default().push(position);
+        }
+        if let Some(symbol) = event.symbol() {
+            self.by_symbol.entry(symbol.to_string()).or_default().push(position);
+        }
+        if let OrderEvent::OrderPlaced(placed) = event {
+            self.owners.insert(placed.order_id, placed.user_id);
+        }
+        for user_id in event_users(event, |order_id| self.owners.get(&order_id).copied()) {
+            self.by_user.entry(user_id).or_default().push(position);
+        }
+        self.by_kind.entry(event.kind()).or_default().push(position);
+        self.all.push(frame);
+    }
+
+    fn frames(&self, positions: Option<&Vec<usize>>, range: TimeRange) -> Vec<FrameRef> {
+        positions.map_or_else(Vec::new, |positions| {
+            positions
+                .iter()
+                .map(|&i| self.all[i])
+                .filter(|frame| range.contains(frame.timestamp))
+                .collect()
+        })
+    }
+}
+
+/// Events appended to numbered segment files in a directory, each a
+/// little-endian u32 length followed by the event as JSON. Only frame
+/// positions are kept in memory; reads go back to the segments. Opening a
+/// directory rebuilds the index and cuts off a frame torn by a crash.
+pub struct FileEventStore {
+    dir: PathBuf,
+    segment_bytes: u64,
+    fsync: FsyncPolicy,
+    log: Mutex<Log>,
+    feed: EventFeed,
+    archive: Option<Arc<SegmentArchive>>,
+    hash_chain: bool,
+}
+
+impl FileEventStore {
+    /// Opens the log in `dir`, which must not have archived segments; see
+    /// `open_with_archive` for those.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, String> {
+        let dir = dir.as_ref().to_path_buf();
+        let segments = list_segments(&dir)?;
+        if !read_manifest(&dir)?.is_empty() {
+            return Err(format!("{} has archived segments; open it with open_with_archive", dir.display()));
+        }
+        let mut log = Log::new(&dir, &segments)?;
+        for &segment in &segments {
+            let path = segment_path(&dir, segment);
+            log.load_segment(segment, &fs::read(&path).map_err(|e| e.to_string())?, &path)?;
+        }
+        Ok(Self::with_log(dir, log, None))
+    }
+
+    /// Opens the log in `dir`, moving closed segments to `store` once more
+    /// than `keep_local` of them are on disk. Segments archived earlier are
+    /// fetched back to rebuild the index, and again whenever a read needs
+    /// their events.
+    pub async fn open_with_archive(
+        dir: impl AsRef<Path>,
+        store: impl ObjectStore + 'static,
+        keep_local: usize,
+    ) -> Result<Self, String> {
+        let dir = dir.as_ref().to_path_buf();
+        let segments = list_segments(&dir)?;
+        let archive = SegmentArchive::new(Box::new(store), &dir, keep_local)?;
+        let archived = archive.archived();
+        let mut log = Log::new(&dir, &segments)?;
+        for &segment in &archived {
+            let path = PathBuf::from(segment_file(segment));
+            log.load_segment(segment, &archive.fetch(segment).await?, &path)?;
+        }
+        // A crash between archiving a segment and deleting it leaves both
+        for &segment in segments.iter().filter(|segment| !archived.contains(segment)) {
+            let path = segment_path(&dir, segment);
+            log.load_segment(segment, &fs::read(&path).map_err(|e| e.to_string())?, &path)?;
+        }
+        Ok(Self::with_log(dir, log, Some(Arc::new(archive))))
+    }
+
+    fn with_log(dir: PathBuf, log: Log, archive: Option<Arc<SegmentArchive>>) -> Self {
+        Self {
+            dir,
+            segment_bytes: DEFAULT_SEGMENT_BYTES,
+            fsync: FsyncPolicy::default(),
+            log: Mutex::new(log),
+            feed: EventFeed::new(),
+            archive,
+            hash_chain: false,
+        }
+    }
+
+    /// Size past which appends move on to a new segment. A batch is never
+    /// split across segments.
+    pub fn with_segment_bytes(mut self, bytes: u64) -> Self {
+        self.segment_bytes = bytes;
+        self
+    }
+
+    pub fn with_fsync(mut self, policy: FsyncPolicy) -> Self {
+        self.fsync = policy;
+        self
+    }
+
+    /// Has each saved event carry the hash of the one before it.
+    pub fn with_hash_chain(mut self) -> Self {
+        self.hash_chain = true;
+        self
+    }
+
+    pub fn segment_count(&self) -> u64 {
+        self.log.lock().unwrap().segment + 1
+    }
+
+    /// Segments moved to the archive, which are no longer on local disk.
+    pub fn archived_segment_count(&self) -> usize {
+        self.archive.as_ref().map_or(0, |archive| archive.archived().len())
+    }
+
+    /// Archives the closed segments due to go now, rather than waiting for
+    /// the next segment to fill up. Returns how many were archived.
+    pub async fn archive_segments(&self) -> Result<usize, String> {
+        let Some(archive) = &self.archive else {
+            return Ok(0);
+        };
+        let current = self.log.lock().unwrap().segment;
+        archive.archive_before(current).await
+    }
+
+    async fn read(&self, frames: impl Iterator<Item = FrameRef>) -> Result<Vec<OrderEvent>, String> {
+        let mut open: Option<(u64, Segment)> = None;
+        let mut events = Vec::new();
+        for frame in frames {
+            if open.as_ref().is_none_or(|(segment, _)| *segment != frame.segment) {
+                open = Some((frame.segment, self.open_for_read(frame.segment).await?));
+            }
+            let payload = match open.as_mut().expect("segment opened above") {
+                (_, Segment::Local(file)) => {
+                    let mut payload = vec![0; frame.len as usize];
+                    file.seek(SeekFrom::Start(frame.offset)).map_err(|e| e.to_string())?;
+                    file.read_exact(&mut payload).map_err(|e| e.to_string())?;
+                    payload
+                }
+                (_, Segment::Fetched(bytes)) => {
+                    let start = frame.offset as usize;
+                    bytes
+                        .get(start..start + frame.len as usize)
+                        .ok_or_else(|| format!("Archived segment {} is shorter than its index", frame.segment))?
+                        .to_vec()
+                }
+            };
+            events.push(serde_json::from_slice(&payload).map_err(|e| e.to_string())?);
+        }
+        Ok(events)
+    }
+
+    /// The local file if the segment is still on disk, otherwise its
+    /// contents fetched from the archive.
+    async fn open_for_read(&self, segment: u64) -> Result<Segment, String> {
+        match (File::open(segment_path(&self.dir, segment)), &self.archive) {
+            (Ok(file), _) => Ok(Segment::Local(file)),
+            (Err(e), Some(archive)) if e.kind() == std::io::ErrorKind::NotFound => {
+                Ok(Segment::Fetched(archive.fetch(segment).await?))
+            }
+            (Err(e), _) => Err(e.to_string()),
+        }
+    }
+}
+
+enum Segment {
+    Local(File),
+    Fetched(Vec<u8>),
+}
+
+/// Appends `payload` to `buf` behind its length, returning where the
+/// payload starts.
+pub(crate) fn push_frame(buf: &mut Vec<u8>, payload: &[u8]) -> Result<u64, String> {
+    let len = u32::try_from(payload.len()).map_err(|_| "Frame too large for the log".to_string())?;
+    buf.extend_from_slice(&len.to_le_bytes());
+    buf.extend_from_slice(payload);
+    Ok((buf.len() - payload.len()) as u64)
+}
+
+/// The payloads of the complete frames in `bytes`, and where the last of
+/// them ends. Anything after that is a torn append.
+pub(crate) fn scan_frames(bytes: &[u8]) -> (Vec<Range<usize>>, usize) {
+    let mut frames = Vec::new();
+    let mut offset = 0;
+    while let Some(header) = bytes.get(offset..offset + 4) {
+        let len = u32::from_le_bytes(header.try_into().expect("four byte header")) as usize;
+        let payload = offset + 4..offset + 4 + len;
+        if payload.end > bytes.len() {
+            break;
+        }
+        offset = payload.end;
+        frames.push(payload);
+    }
+    (frames, offset)
+}
+
+pub(crate) fn truncate(path: &Path, len: u64) -> Result<(), String> {
+    let file = OpenOptions::new().write(true).open(path).map_err(|e| e.to_string())?;
+    file.set_len(len).map_err(|e| e.to_string())?;
+    file.sync_data().map_err(|e| e.to_string())
+}
+
+/// The segments on disk in `dir`, creating it if need be.
+fn list_segments(dir: &Path) -> Result<Vec<u64>, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create event log {}: {}", dir.display(), e))?;
+    let mut segments = Vec::new();
+    for entry in fs::read_dir(dir).map_err(|e| e.to_string())? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.extension().is_some_and(|ext| ext == SEGMENT_EXTENSION) {
+            if let Some(segment) = path.file_stem().and_then(|stem| stem.to_str()?.parse::<u64>().ok()) {
+                segments.push(segment);
+            }
+        }
+    }
+    segments.sort_unstable();
+    Ok(segments)
+}
+
+/// A segment's file name, and its key once archived.
+pub(crate) fn segment_file(segment: u64) -> String {
+    format!("{:020}.{}", segment, SEGMENT_EXTENSION)
+}
+
+fn segment_path(dir: &Path, segment: u64) -> PathBuf {
+    dir.join(segment_file(segment))
+}
+
+fn open_segment(dir: &Path, segment: u64) -> Result<File, String> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(segment_path(dir, segment))
+        .map_err(|e| format!("Failed to open event log segment {}: {}", segment, e))
+}
+
+#[async_trait]
+impl EventStore for FileEventStore {
+    async fn save_events(&self, events: Vec<OrderEvent>) -> Result<(), String> {
+        let mut log = self.log.lock().unwrap();
+        let mut events = unsaved(events, |event_id| Ok(log.event_ids.contains(&event_id)))?;
+        if events.is_empty() {
+            return Ok(());
+        }
+        let mut head = log.head.clone();
+        if self.hash_chain {
+            // Only moved on once the batch is written
+            chain_events(&mut events, &mut head);
+        }
+        let mut buf = Vec::new();
+        let mut frames = Vec::with_capacity(events.len());
+        for event in &events {
+            let payload = serde_json::to_vec(event).map_err(|e| e.to_string())?;
+            let offset = push_frame(&mut buf, &payload)?;
+            frames.push((offset, payload.len() as u32));
+        }
+
+        if log.segment_len > 0 && log.segment_len + buf.len() as u64 > self.segment_bytes {
+            log.file.sync_data().map_err(|e| e.to_string())?;
+            let next = log.segment + 1;
+            log.file = open_segment(&self.dir, next)?;
+            log.segment = next;
+            log.segment_len = 0;
+            if let Some(archive) = self.archive.clone() {
+                // A failed pass is retried when the next segment closes
+                tokio::spawn(async move { archive.archive_before(next).await });
+            }
+        }
+        if let Err(e) = log.file.write_all(&buf) {
+            // Drop whatever part of the batch made it, so the next append
+            // does not follow a torn frame
+            let _ = log.file.set_len(log.segment_len);
+            return Err(e.to_string());
+        }
+        log.unsynced += 1;
+        let sync = match self.fsync {
+            FsyncPolicy::Always => true,
+            FsyncPolicy::EveryBatches(n) => log.unsynced >= n,
+            FsyncPolicy::Never => false,
+        };
+        if sync {
+            if let Err(e) = log.file.sync_data() {
+                // The batch fails, so it must not come back on reopening,
+                // nor sit under the offsets the next batch is indexed at
+                let _ = log.file.set_len(log.segment_len);
+                return Err(e.to_string());
+            }
+            log.unsynced = 0;
+        }
+
+        let (segment, base) = (log.segment, log.segment_len);
+        for ((offset, len), event) in frames.into_iter().zip(&events) {
+            let frame = FrameRef {
+                segment,
+                offset: base + offset,
+                len,
+                timestamp: event.timestamp(),
+            };
+            log.index(frame, event);
+        }
+        log.segment_len += buf.len() as u64;
+        if self.hash_chain {
+            log.head = head;
+        }
+        self.feed.publish(&events);
+        Ok(())
+    }
+
+    async fn get_events(&self, order_id: Uuid) -> Result<Vec<OrderEvent>, String> {
+        let frames = {
+            let log = self.log.lock().unwrap();
+            log.frames(log.by_order.get(&order_id), TimeRange::all())
+        };
+        self.read(frames.into_iter()).await
+    }
+
+    async fn get_all_events(&self) -> Result<Vec<OrderEvent>, String> {
+        let frames = self.log.lock().unwrap().all.clone();
+        self.read(frames.into_iter()).await
+    }
+
+    async fn read_from(&self, offset: u64, max: usize) -> Result<Vec<StoredEvent>, String> {
+        let frames: Vec<FrameRef> = {
+            let log = self.log.lock().unwrap();
+            log.all.iter().skip(offset as usize).take(max).copied().collect()
+        };
+        let events = self.read(frames.into_iter()).await?;
+        Ok(events
+            .into_iter()
+            .zip(offset..)
+            .map(|(event, offset)| StoredEvent { offset, event })
+            .collect())
+    }
+
+    async fn get_events_by_symbol(&self, symbol: &str, range: TimeRange) -> Result<Vec<OrderEvent>, String> {
+        let frames = {
+            let log = self.log.lock().unwrap();
+            log.frames(log.by_symbol.get(symbol), range)
+        };
+        self.read(frames.into_iter()).await
+    }
+
+    async fn get_events_by_user(&self, user_id: Uuid, range: TimeRange) -> Result<Vec<OrderEvent>, String> {
+        let frames = {
+            let log = self.log.lock().unwrap();
+            log.frames(log.by_user.get(&user_id), range)
+        };
+        self.read(frames.into_iter()).await
+    }
+
+    async fn get_events_by_type(&self, kind: &str, range: TimeRange) -> Result<Vec<OrderEvent>, String> {
+        let frames = {
+            let log = self.log.lock().unwrap();
+            log.frames(log.by_kind.get(kind), range)
+        };
+        self.read(frames.into_iter()).await
+    }
+
+    fn subscribe(&self, filter: EventFilter) -> Result<EventSubscription, String> {
+        Ok(self.feed.subscribe(filter))
+    }
+}
@@ -0,0 +1,802 @@
+//! FIX 4.4 order entry. `serve_fix` accepts sessions, turns
+//! NewOrderSingle, OrderCancelRequest and OrderCancelReplaceRequest into
+//! commands and reports what happens to each session's orders, passive
+//! fills included, as ExecutionReports.
+//!
+//! ClOrdIDs are the engine's client order ids and Account (1) carries the
+//! user id. Sequence numbers start at 1 on every connection, and outbound
+//! messages are not kept, so a ResendRequest is answered with a gap fill.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use rust_decimal::Decimal;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+use crate::commands::{CancelOrderCommand, CancelReplaceCommand, OrderCommand, PlaceOrderCommand};
+use crate::engine::MatchingEngine;
+use crate::events::OrderEvent;
+use crate::subscription::EventFilter;
+use crate::types::{OrderSide, OrderType};
+
+const SOH: u8 = 0x01;
+const BEGIN_STRING: &str = "FIX.4.4";
+/// Largest body accepted.
+const MAX_BODY_LENGTH: usize = 1 << 16;
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H:%M:%S%.3f";
+
+const ACCOUNT: u32 = 1;
+const AVG_PX: u32 = 6;
+const BEGIN_SEQ_NO: u32 = 7;
+const CL_ORD_ID: u32 = 11;
+const CUM_QTY: u32 = 14;
+const END_SEQ_NO: u32 = 16;
+const EXEC_ID: u32 = 17;
+const LAST_PX: u32 = 31;
+const LAST_QTY: u32 = 32;
+const MSG_SEQ_NUM: u32 = 34;
+const NEW_SEQ_NO: u32 = 36;
+const ORDER_ID: u32 = 37;
+const ORDER_QTY: u32 = 38;
+const ORD_STATUS: u32 = 39;
+const ORD_TYPE: u32 = 40;
+const ORIG_CL_ORD_ID: u32 = 41;
+const POSS_DUP_FLAG: u32 = 43;
+const PRICE: u32 = 44;
+const REF_SEQ_NUM: u32 = 45;
+const SENDER_COMP_ID: u32 = 49;
+const SENDING_TIME: u32 = 52;
+const SIDE: u32 = 54;
+const SYMBOL: u32 = 55;
+const TARGET_COMP_ID: u32 = 56;
+const TEXT: u32 = 58;
+const TIME_IN_FORCE: u32 = 59;
+const TRANSACT_TIME: u32 = 60;
+const ENCRYPT_METHOD: u32 = 98;
+const STOP_PX: u32 = 99;
+const CXL_REJ_REASON: u32 = 102;
+const ORD_REJ_REASON: u32 = 103;
+const HEART_BT_INT: u32 = 108;
+const TEST_REQ_ID: u32 = 112;
+const GAP_FILL_FLAG: u32 = 123;
+const RESET_SEQ_NUM_FLAG: u32 = 141;
+const EXEC_TYPE: u32 = 150;
+const LEAVES_QTY: u32 = 151;
+const CASH_ORDER_QTY: u32 = 152;
+const REF_MSG_TYPE: u32 = 372;
+const BUSINESS_REJECT_REASON: u32 = 380;
+const CXL_REJ_RESPONSE_TO: u32 = 434;
+
+/// One FIX message: its MsgType and the fields after it, in order.
+/// BeginString, BodyLength and CheckSum are added by `encode`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixMessage {
+    msg_type: String,
+    fields: Vec<(u32, String)>,
+}
+
+impl FixMessage {
+    pub fn new(msg_type: &str) -> Self {
+        Self {
+            msg_type: msg_type.to_string(),
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn with(mut self, tag: u32, value: impl fmt::Display) -> Self {
+        self.fields.push((tag, value.to_string()));
+        self
+    }
+
+    pub fn msg_type(&self) -> &str {
+        &self.msg_type
+    }
+
+    /// The first value of `tag`.
+    pub fn get(&self, tag: u32) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(field, _)| *field == tag)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut body = format!("35={}\x01", self.msg_type);
+        for (tag, value) in &self.fields {
+            body.push_str(&format!("{}={}\x01", tag, value));
+        }
+        let mut out = format!("8={}\x019={}\x01{}", BEGIN_STRING, body.len(), body).into_bytes();
+        let checksum = checksum(&out);
+        out.extend_from_slice(format!("10={:03}\x01", checksum).as_bytes());
+        out
+    }
+
+    /// Parses one whole message, checking its BeginString, BodyLength and
+    /// CheckSum.
+    pub fn parse(bytes: &[u8]) -> Result<Self, String> {
+        let text = std::str::from_utf8(bytes).map_err(|e| e.to_string())?;
+        let mut fields = text
+            .strip_suffix('\x01')
+            .ok_or("Message does not end with SOH")?
+            .split('\x01')
+            .map(|field| {
+                let (tag, value) = field.split_once('=').ok_or_else(|| format!("Bad field {}", field))?;
+                let tag: u32 = tag.parse().map_err(|_| format!("Bad tag {}", tag))?;
+                Ok((tag, value.to_string()))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        match fields.first() {
+            Some((8, begin_string)) if begin_string == BEGIN_STRING => {}
+            _ => return Err(format!("BeginString is not {}", BEGIN_STRING)),
+        }
+        let body_length = match fields.get(1) {
+            Some((9, length)) => length.parse::<usize>().map_err(|_| "Bad BodyLength")?,
+            _ => return Err("BodyLength missing".to_string()),
+        };
+        let Some((10, expected)) = fields.pop() else {
+            return Err("CheckSum missing".to_string());
+        };
+        let trailer = text.len() - "10=".len() - expected.len() - 1;
+        let body_start = text.find("\x019=").and_then(|at| text[at + 1..].find('\x01').map(|end| at + end + 2));
+        if body_start.is_none_or(|start| trailer.checked_sub(start) != Some(body_length)) {
+            return Err("BodyLength does not match".to_string());
+        }
+        if expected.parse::<u8>().ok() != Some(checksum(&bytes[..trailer])) {
+            return Err("CheckSum does not match".to_string());
+        }
+        match fields.get(2) {
+            Some((35, msg_type)) => Ok(Self {
+                msg_type: msg_type.clone(),
+                fields: fields.split_off(3),
+            }),
+            _ => Err("MsgType missing".to_string()),
+        }
+    }
+
+    fn required(&self, tag: u32) -> Result<&str, String> {
+        self.get(tag).ok_or_else(|| format!("Required tag {} missing", tag))
+    }
+
+    fn parsed<T: std::str::FromStr>(&self, tag: u32) -> Result<Option<T>, String> {
+        self.get(tag)
+            .map(|value| value.parse().map_err(|_| format!("Bad value {} for tag {}", value, tag)))
+            .transpose()
+    }
+
+    fn parsed_required<T: std::str::FromStr>(&self, tag: u32) -> Result<T, String> {
+        self.parsed(tag)?.ok_or_else(|| format!("Required tag {} missing", tag))
+    }
+}
+
+fn checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+}
+
+/// The command a NewOrderSingle (D), OrderCancelRequest (F) or
+/// OrderCancelReplaceRequest (G) asks for. New orders get fresh ids;
+/// cancels and replaces find their order by OrigClOrdID, or by OrderID
+/// when that is missing.
+pub fn command_from_fix(message: &FixMessage) -> Result<OrderCommand, String> {
+    let user_id = message.parsed_required(ACCOUNT)?;
+    let symbol = message.required(SYMBOL)?.to_string();
+    let timestamp = message.get(TRANSACT_TIME).map(parse_timestamp).transpose()?.unwrap_or_else(Utc::now);
+    let order_ref = || -> Result<(Uuid, Option<String>), String> {
+        let client_order_id = message.get(ORIG_CL_ORD_ID).map(str::to_string);
+        let order_id = match client_order_id {
+            Some(_) => message.parsed(ORDER_ID).ok().flatten().unwrap_or_default(),
+            None => message.parsed_required(ORDER_ID)?,
+        };
+        Ok((order_id, client_order_id))
+    };
+    match message.msg_type() {
+        "D" => {
+            let price = message.parsed(PRICE)?;
+            let stop_price = message.parsed(STOP_PX)?;
+            let order_type = match message.required(ORD_TYPE)? {
+                "1" => OrderType::Market,
+                "2" => OrderType::Limit,
+                "3" | "4" => OrderType::StopLoss,
+                other => return Err(format!("OrdType {} is not supported", other)),
+            };
+            if !matches!(message.get(TIME_IN_FORCE), None | Some("0" | "1")) {
+                return Err("Only Day and GoodTillCancel orders are supported".to_string());
+            }
+            let quote_quantity = message.parsed(CASH_ORDER_QTY)?;
+            let quantity = match quote_quantity {
+                Some(_) => message.parsed(ORDER_QTY)?.unwrap_or_default(),
+                None => message.parsed_required(ORDER_QTY)?,
+            };
+            Ok(OrderCommand::PlaceOrder(PlaceOrderCommand {
+                order_id: Uuid::new_v4(),
+                client_order_id: Some(message.required(CL_ORD_ID)?.to_string()),
+                user_id,
+                symbol,
+                order_type,
+                side: side_from(message.required(SIDE)?)?,
+                price: if message.get(ORD_TYPE) == Some("3") { None } else { price },
+                quantity,
+                iceberg_visible_quantity: None,
+                stop_price,
+                trailing_stop_price: None,
+                displayed: true,
+                max_fills: None,
+                referrer_id: None,
+                quote_quantity,
+                timestamp,
+            }))
+        }
+        "F" => {
+            let (order_id, client_order_id) = order_ref()?;
+            Ok(OrderCommand::CancelOrder(CancelOrderCommand {
+                order_id,
+                client_order_id,
+                user_id,
+                symbol,
+                timestamp,
+            }))
+        }
+        "G" => {
+            let (order_id, client_order_id) = order_ref()?;
+            Ok(OrderCommand::CancelReplace(CancelReplaceCommand {
+                order_id,
+                client_order_id,
+                user_id,
+                symbol,
+                new_order_id: Uuid::new_v4(),
+                new_client_order_id: Some(message.required(CL_ORD_ID)?.to_string()),
+                new_price: message.parsed(PRICE)?,
+                new_quantity: message.parsed_required(ORDER_QTY)?,
+                timestamp,
+            }))
+        }
+        other => Err(format!("MsgType {} is not an order message", other)),
+    }
+}
+
+fn side_from(side: &str) -> Result<OrderSide, String> {
+    match side {
+        "1" => Ok(OrderSide::Buy),
+        "2" => Ok(OrderSide::Sell),
+        other => Err(format!("Side {} is not supported", other)),
+    }
+}
+
+fn side_code(side: OrderSide) -> &'static str {
+    match side {
+        OrderSide::Buy => "1",
+        OrderSide::Sell => "2",
+    }
+}
+
+fn parse_timestamp(value: &str) -> Result<DateTime<Utc>, String> {
+    ["%Y%m%d-%H:%M:%S%.f", "%Y%m%d-%H:%M:%S"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .map(|at| at.and_utc())
+        .ok_or_else(|| format!("Bad UTCTimestamp {}", value))
+}
+
+/// Accepts FIX sessions on `listener` until it fails, as `comp_id`. Each
+/// connection must log on first, with TargetCompID set to `comp_id`. The
+/// engine's event store must support subscriptions, which reports are
+/// built from.
+pub async fn serve_fix(engine: Arc<MatchingEngine>, listener: TcpListener, comp_id: &str) -> Result<(), String> {
+    loop {
+        let (stream, _) = listener.accept().await.map_err(|e| e.to_string())?;
+        let engine = engine.clone();
+        let comp_id = comp_id.to_string();
+        tokio::spawn(async move {
+            let _ = connection(engine, stream, comp_id).await;
+        });
+    }
+}
+
+async fn connection(engine: Arc<MatchingEngine>, stream: TcpStream, comp_id: String) -> Result<(), String> {
+    // Taken before the first order, so no report is missed
+    let mut events = engine.event_store().subscribe(EventFilter::default())?;
+    let (reader, mut writer) = stream.into_split();
+    let (received, mut incoming) = mpsc::channel(64);
+    let reading = tokio::spawn(async move {
+        let mut reader = BufReader::new(reader);
+        while let Ok(Some(message)) = read_message(&mut reader).await {
+            if received.send(message).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut session = Session::new(comp_id);
+    let result = async {
+        let Some(logon) = incoming.recv().await else {
+            return Ok(());
+        };
+        let replies = session.on_logon(&logon)?;
+        write(&mut writer, &replies).await?;
+        let mut last_received = Instant::now();
+        let mut last_sent = Instant::now();
+        let mut test_request_sent = false;
+        let mut ticks = tokio::time::interval(session.heartbeat_interval.max(Duration::from_secs(1)));
+        while !session.logged_out {
+            let replies = tokio::select! {
+                message = incoming.recv() => {
+                    let Some(message) = message else {
+                        return Ok(());
+                    };
+                    (last_received, test_request_sent) = (Instant::now(), false);
+                    session.on_message(&engine, &message).await
+                }
+                event = events.recv() => match event {
+                    Ok(event) => session.report(&event),
+                    Err(RecvError::Lagged(missed)) => {
+                        session.logged_out = true;
+                        vec![session.outbound(FixMessage::new("5").with(TEXT, format!("Missed {} events", missed)))]
+                    }
+                    Err(RecvError::Closed) => return Ok(()),
+                },
+                _ = ticks.tick(), if !session.heartbeat_interval.is_zero() => {
+                    let interval = session.heartbeat_interval;
+                    if last_received.elapsed() > interval * 3 {
+                        return Err("Counterparty stopped responding".to_string());
+                    }
+                    if last_received.elapsed() > interval * 2 && !test_request_sent {
+                        test_request_sent = true;
+                        vec![session.outbound(FixMessage::new("1").with(TEST_REQ_ID, "TEST"))]
+                    } else if last_sent.elapsed() >= interval {
+                        vec![session.outbound(FixMessage::new("0"))]
+                    } else {
+                        Vec::new()
+                    }
+                }
+            };
+            if !replies.is_empty() {
+                last_sent = Instant::now();
+                write(&mut writer, &replies).await?;
+            }
+        }
+        Ok(())
+    }
+    .await;
+    reading.abort();
+    result
+}
+
+async fn write(writer: &mut (impl AsyncWriteExt + Unpin), messages: &[FixMessage]) -> Result<(), String> {
+    for message in messages {
+        writer.write_all(&message.encode()).await.map_err(|e| e.to_string())?;
+    }
+    writer.flush().await.map_err(|e| e.to_string())
+}
+
+/// Reads the bytes of the next message, or None at the end of the stream.
+async fn read_message(reader: &mut BufReader<impl AsyncRead + Unpin>) -> Result<Option<Vec<u8>>, String> {
+    let mut message = Vec::new();
+    if reader.read_until(SOH, &mut message).await.map_err(|e| e.to_string())? == 0 {
+        return Ok(None);
+    }
+    let start = message.len();
+    reader.read_until(SOH, &mut message).await.map_err(|e| e.to_string())?;
+    let body_length: usize = std::str::from_utf8(&message[start..])
+        .ok()
+        .and_then(|field| field.strip_prefix("9="))
+        .and_then(|length| length.trim_end_matches('\x01').parse().ok())
+        .filter(|length| *length <= MAX_BODY_LENGTH)
+        .ok_or("Bad BodyLength")?;
+    // The body and "10=nnn<SOH>"
+    let start = message.len();
+    message.resize(start + body_length + 7, 0);
+    reader.read_exact(&mut message[start..]).await.map_err(|e| e.to_string())?;
+    Ok(Some(message))
+}
+
+/// What a session knows about one of its orders, to fill in reports.
+#[derive(Debug, Clone)]
+struct TrackedOrder {
+    cl_ord_id: String,
+    user_id: Uuid,
+    symbol: String,
+    side: OrderSide,
+    quantity: Decimal,
+    cum_quantity: Decimal,
+    notional: Decimal,
+    /// The ClOrdID this order replaced.
+    orig_cl_ord_id: Option<String>,
+    /// The ClOrdID of a cancel request in flight.
+    canceling: Option<String>,
+}
+
+impl TrackedOrder {
+    fn new(cl_ord_id: &str, user_id: Uuid, symbol: &str, side: OrderSide, quantity: Decimal) -> Self {
+        Self {
+            cl_ord_id: cl_ord_id.to_string(),
+            user_id,
+            symbol: symbol.to_string(),
+            side,
+            quantity,
+            cum_quantity: Decimal::ZERO,
+            notional: Decimal::ZERO,
+            orig_cl_ord_id: None,
+            canceling: None,
+        }
+    }
+
+    fn leaves(&self) -> Decimal {
+        (self.quantity - self.cum_quantity).max(Decimal::ZERO)
+    }
+
+    fn ord_status(&self) -> &'static str {
+        if self.cum_quantity.is_zero() {
+            "0"
+        } else if self.leaves().is_zero() {
+            "2"
+        } else {
+            "1"
+        }
+    }
+}
+
+struct Session {
+    comp_id: String,
+    counterparty: String,
+    next_outgoing: u64,
+    next_incoming: u64,
+    heartbeat_interval: Duration,
+    orders: HashMap<Uuid, TrackedOrder>,
+    logged_out: bool,
+}
+
+impl Session {
+    fn new(comp_id: String) -> Self {
+        Self {
+            comp_id,
+            counterparty: String::new(),
+            next_outgoing: 1,
+            next_incoming: 1,
+            heartbeat_interval: Duration::ZERO,
+            orders: HashMap::new(),
+            logged_out: false,
+        }
+    }
+
+    fn on_logon(&mut self, raw: &[u8]) -> Result<Vec<FixMessage>, String> {
+        let logon = FixMessage::parse(raw)?;
+        if logon.msg_type() != "A" {
+            return Err("The first message must be a Logon".to_string());
+        }
+        if logon.get(TARGET_COMP_ID) != Some(self.comp_id.as_str()) {
+            return Err("Logon for another TargetCompID".to_string());
+        }
+        if logon.get(ENCRYPT_METHOD).is_some_and(|method| method != "0") {
+            return Err("Encryption is not supported".to_string());
+        }
+        self.counterparty = logon.required(SENDER_COMP_ID)?.to_string();
+        self.heartbeat_interval = Duration::from_secs(logon.parsed(HEART_BT_INT)?.unwrap_or(30));
+        let seq: u64 = logon.parsed_required(MSG_SEQ_NUM)?;
+        let mut replies = vec![self.outbound(
+            FixMessage::new("A")
+                .with(ENCRYPT_METHOD, 0)
+                .with(HEART_BT_INT, self.heartbeat_interval.as_secs())
+                .with(RESET_SEQ_NUM_FLAG, logon.get(RESET_SEQ_NUM_FLAG).unwrap_or("N")),
+        )];
+        if seq > self.next_incoming {
+            replies.push(self.resend_request());
+        } else {
+            self.next_incoming = seq + 1;
+        }
+        Ok(replies)
+    }
+
+    async fn on_message(&mut self, engine: &MatchingEngine, raw: &[u8]) -> Vec<FixMessage> {
+        // Garbled messages are ignored, as FIX asks
+        let Ok(message) = FixMessage::parse(raw) else {
+            return Vec::new();
+        };
+        if message.get(SENDER_COMP_ID) != Some(self.counterparty.as_str())
+            || message.get(TARGET_COMP_ID) != Some(self.comp_id.as_str())
+        {
+            return self.logout("CompIDs do not match the session");
+        }
+        let Ok(Some(seq)) = message.parsed::<u64>(MSG_SEQ_NUM) else {
+            return self.logout("MsgSeqNum missing");
+        };
+        let gap_fill = message.get(GAP_FILL_FLAG) == Some("Y");
+        if message.msg_type() == "4" && !gap_fill {
+            // A reset ignores the sequence it arrives in
+            if let Ok(Some(new_seq)) = message.parsed(NEW_SEQ_NO) {
+                self.next_incoming = new_seq;
+            }
+            return Vec::new();
+        }
+        if seq < self.next_incoming {
+            if message.get(POSS_DUP_FLAG) == Some("Y") {
+                return Vec::new();
+            }
+            return self.logout(&format!("MsgSeqNum too low, expecting {} but received {}", self.next_incoming, seq));
+        }
+        if seq > self.next_incoming {
+            return vec![self.resend_request()];
+        }
+        self.next_incoming += 1;
+
+        match message.msg_type() {
+            "0" => Vec::new(),
+            "1" => {
+                let heartbeat = FixMessage::new("0").with(TEST_REQ_ID, message.get(TEST_REQ_ID).unwrap_or_default());
+                vec![self.outbound(heartbeat)]
+            }
+            "2" => {
+                let begin = message.parsed(BEGIN_SEQ_NO).ok().flatten().unwrap_or(1);
+                let gap_fill = FixMessage::new("4")
+                    .with(GAP_FILL_FLAG, "Y")
+                    .with(NEW_SEQ_NO, self.next_outgoing);
+                vec![self.stamped(gap_fill, begin, true)]
+            }
+            "4" => {
+                if let Ok(Some(new_seq)) = message.parsed::<u64>(NEW_SEQ_NO) {
+                    self.next_incoming = self.next_incoming.max(new_seq);
+                }
+                Vec::new()
+            }
+            "5" => {
+                self.logged_out = true;
+                vec![self.outbound(FixMessage::new("5"))]
+            }
+            "D" | "F" | "G" => self.on_order_message(engine, &message).await,
+            other => {
+                let reject = FixMessage::new("j")
+                    .with(REF_SEQ_NUM, seq)
+                    .with(REF_MSG_TYPE, other)
+                    .with(BUSINESS_REJECT_REASON, 3)
+                    .with(TEXT, format!("MsgType {} is not supported", other));
+                vec![self.outbound(reject)]
+            }
+        }
+    }
+
+    /// Tracks the order a message concerns, then hands its command to the
+    /// engine. Accepted commands are reported from the events they save;
+    /// refused ones are answered here.
+    async fn on_order_message(&mut self, engine: &MatchingEngine, message: &FixMessage) -> Vec<FixMessage> {
+        let cl_ord_id = message.get(CL_ORD_ID).unwrap_or_default().to_string();
+        let command = if self.orders.values().any(|order| order.cl_ord_id == cl_ord_id) {
+            Err(format!("Duplicate ClOrdID {}", cl_ord_id))
+        } else {
+            command_from_fix(message)
+        };
+        let command = match command {
+            Ok(command) => command,
+            Err(e) => return vec![self.refusal(message, None, &e)],
+        };
+        let tracked = match &command {
+            OrderCommand::PlaceOrder(cmd) => {
+                let order = TrackedOrder::new(&cl_ord_id, cmd.user_id, &cmd.symbol, cmd.side, cmd.quantity);
+                self.orders.insert(cmd.order_id, order);
+                Some(cmd.order_id)
+            }
+            OrderCommand::CancelReplace(cmd) => {
+                let side = self.find(message).map(|(_, order)| order.side).unwrap_or(OrderSide::Buy);
+                let mut order = TrackedOrder::new(&cl_ord_id, cmd.user_id, &cmd.symbol, side, cmd.new_quantity);
+                order.orig_cl_ord_id = message.get(ORIG_CL_ORD_ID).map(str::to_string);
+                self.orders.insert(cmd.new_order_id, order);
+                Some(cmd.new_order_id)
+            }
+            OrderCommand::CancelOrder(_) => {
+                if let Some((id, _)) = self.find(message) {
+                    self.orders.get_mut(&id).unwrap().canceling = Some(cl_ord_id);
+                }
+                None
+            }
+            _ => None,
+        };
+        let Err(error) = engine.handle_command(command).await else {
+            return Vec::new();
+        };
+        if let Some(order_id) = tracked {
+            self.orders.remove(&order_id);
+        }
+        if let Some((id, _)) = self.find(message) {
+            self.orders.get_mut(&id).unwrap().canceling = None;
+        }
+        vec![self.refusal(message, tracked, &error.to_string())]
+    }
+
+    /// The tracked order a cancel or replace refers to.
+    fn find(&self, message: &FixMessage) -> Option<(Uuid, &TrackedOrder)> {
+        let order_id = message.parsed::<Uuid>(ORDER_ID).ok().flatten();
+        let orig = message.get(ORIG_CL_ORD_ID);
+        self.orders
+            .iter()
+            .find(|(id, order)| match orig {
+                Some(orig) => order.cl_ord_id == orig,
+                None => Some(**id) == order_id,
+            })
+            .map(|(id, order)| (*id, order))
+    }
+
+    /// A rejected ExecutionReport for a new order, or an OrderCancelReject
+    /// for a cancel or replace.
+    fn refusal(&mut self, message: &FixMessage, order_id: Option<Uuid>, text: &str) -> FixMessage {
+        let cl_ord_id = message.get(CL_ORD_ID).unwrap_or_default();
+        if message.msg_type() == "D" {
+            let order_id = order_id.map_or_else(|| "NONE".to_string(), |id| id.to_string());
+            let report = FixMessage::new("8")
+                .with(ORDER_ID, order_id)
+                .with(CL_ORD_ID, cl_ord_id)
+                .with(EXEC_ID, Uuid::new_v4())
+                .with(EXEC_TYPE, "8")
+                .with(ORD_STATUS, "8")
+                .with(ORD_REJ_REASON, 99)
+                .with(ACCOUNT, message.get(ACCOUNT).unwrap_or_default())
+                .with(SYMBOL, message.get(SYMBOL).unwrap_or_default())
+                .with(SIDE, message.get(SIDE).unwrap_or_default())
+                .with(ORDER_QTY, message.get(ORDER_QTY).unwrap_or("0"))
+                .with(LEAVES_QTY, 0)
+                .with(CUM_QTY, 0)
+                .with(AVG_PX, 0)
+                .with(TEXT, text);
+            return self.outbound(report);
+        }
+        let (order_id, status) = match self.find(message) {
+            Some((id, order)) => (id.to_string(), order.ord_status()),
+            None => ("NONE".to_string(), "8"),
+        };
+        let reject = FixMessage::new("9")
+            .with(ORDER_ID, order_id)
+            .with(CL_ORD_ID, cl_ord_id)
+            .with(ORIG_CL_ORD_ID, message.get(ORIG_CL_ORD_ID).unwrap_or_default())
+            .with(ORD_STATUS, status)
+            .with(CXL_REJ_RESPONSE_TO, if message.msg_type() == "F" { 1 } else { 2 })
+            .with(CXL_REJ_REASON, if status == "8" { 1 } else { 99 })
+            .with(TEXT, text);
+        self.outbound(reject)
+    }
+
+    /// ExecutionReports for what `event` did to this session's orders.
+    /// Rejections are answered when the command is refused, so rejected
+    /// events are not reported again.
+    fn report(&mut self, event: &OrderEvent) -> Vec<FixMessage> {
+        match event {
+            OrderEvent::OrderPlaced(e) if self.orders.contains_key(&e.order_id) => {
+                let exec_type = if e.replaces_order_id.is_some() { "5" } else { "0" };
+                self.execution_report(e.order_id, e.event_id.to_string(), exec_type, None, e.timestamp)
+                    .into_iter()
+                    .collect()
+            }
+            OrderEvent::OrderMatched(e) => {
+                let sides = [(e.order_id, e.event_id.to_string()), (e.matched_order_id, format!("{}-M", e.event_id))];
+                sides
+                    .into_iter()
+                    .filter_map(|(order_id, exec_id)| {
+                        let order = self.orders.get_mut(&order_id)?;
+                        order.cum_quantity += e.quantity;
+                        order.notional += e.quantity * e.price;
+                        let report = self.execution_report(order_id, exec_id, "F", Some((e.quantity, e.price)), e.timestamp);
+                        if self.orders[&order_id].leaves().is_zero() {
+                            self.orders.remove(&order_id);
+                        }
+                        report
+                    })
+                    .collect()
+            }
+            OrderEvent::OrderCanceled(e) => {
+                let Some(order) = self.orders.get(&e.order_id) else {
+                    return Vec::new();
+                };
+                if let Some(replacement) = e.replaced_by_order_id {
+                    // Reported as the replacement's Replaced; its fills carry over
+                    let (cum_quantity, notional) = (order.cum_quantity, order.notional);
+                    self.orders.remove(&e.order_id);
+                    if let Some(replacement) = self.orders.get_mut(&replacement) {
+                        replacement.cum_quantity = cum_quantity;
+                        replacement.notional = notional;
+                    }
+                    return Vec::new();
+                }
+                let report = self.execution_report(e.order_id, e.event_id.to_string(), "4", None, e.timestamp);
+                self.orders.remove(&e.order_id);
+                report.into_iter().collect()
+            }
+            OrderEvent::OrderUpdated(e) => {
+                let Some(order) = self.orders.get_mut(&e.order_id) else {
+                    return Vec::new();
+                };
+                if let Some(quantity) = e.new_quantity {
+                    order.quantity = quantity;
+                }
+                self.execution_report(e.order_id, e.event_id.to_string(), "D", None, e.timestamp)
+                    .into_iter()
+                    .collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn execution_report(
+        &mut self,
+        order_id: Uuid,
+        exec_id: String,
+        exec_type: &str,
+        fill: Option<(Decimal, Decimal)>,
+        at: DateTime<Utc>,
+    ) -> Option<FixMessage> {
+        let order = self.orders.get(&order_id)?.clone();
+        let (cl_ord_id, orig_cl_ord_id) = match (&order.canceling, exec_type) {
+            (Some(canceling), "4") => (canceling.clone(), Some(order.cl_ord_id.clone())),
+            _ => (order.cl_ord_id.clone(), order.orig_cl_ord_id.clone().filter(|_| exec_type == "5")),
+        };
+        let (ord_status, leaves) = match exec_type {
+            "4" => ("4", Decimal::ZERO),
+            _ => (order.ord_status(), order.leaves()),
+        };
+        let avg_px = if order.cum_quantity.is_zero() {
+            Decimal::ZERO
+        } else {
+            (order.notional / order.cum_quantity).normalize()
+        };
+        let mut report = FixMessage::new("8")
+            .with(ORDER_ID, order_id)
+            .with(CL_ORD_ID, cl_ord_id);
+        if let Some(orig_cl_ord_id) = orig_cl_ord_id {
+            report = report.with(ORIG_CL_ORD_ID, orig_cl_ord_id);
+        }
+        report = report
+            .with(EXEC_ID, exec_id)
+            .with(EXEC_TYPE, exec_type)
+            .with(ORD_STATUS, ord_status)
+            .with(ACCOUNT, order.user_id)
+            .with(SYMBOL, &order.symbol)
+            .with(SIDE, side_code(order.side))
+            .with(ORDER_QTY, order.quantity);
+        if let Some((quantity, price)) = fill {
+            report = report.with(LAST_QTY, quantity).with(LAST_PX, price);
+        }
+        let report = report
+            .with(LEAVES_QTY, leaves)
+            .with(CUM_QTY, order.cum_quantity)
+            .with(AVG_PX, avg_px)
+            .with(TRANSACT_TIME, at.format(TIMESTAMP_FORMAT));
+        Some(self.outbound(report))
+    }
+
+    fn resend_request(&mut self) -> FixMessage {
+        let request = FixMessage::new("2").with(BEGIN_SEQ_NO, self.next_incoming).with(END_SEQ_NO, 0);
+        self.outbound(request)
+    }
+
+    fn logout(&mut self, text: &str) -> Vec<FixMessage> {
+        self.logged_out = true;
+        vec![self.outbound(FixMessage::new("5").with(TEXT, text))]
+    }
+
+    /// `message` with the standard header, on the next outbound sequence
+    /// number.
+    fn outbound(&mut self, message: FixMessage) -> FixMessage {
+        let seq = self.next_outgoing;
+        self.next_outgoing += 1;
+        self.stamped(message, seq, false)
+    }
+
+    fn stamped(&self, message: FixMessage, seq: u64, poss_dup: bool) -> FixMessage {
+        let mut header = vec![
+            (SENDER_COMP_ID, self.comp_id.clone()),
+            (TARGET_COMP_ID, self.counterparty.clone()),
+            (MSG_SEQ_NUM, seq.to_string()),
+        ];
+        if poss_dup {
+            header.push((POSS_DUP_FLAG, "Y".to_string()));
+        }
+        header.push((SENDING_TIME, Utc::now().format(TIMESTAMP_FORMAT).to_string()));
+        header.extend(message.fields);
+        FixMessage {
+            msg_type: message.msg_type,
+            fields: header,
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+use std::mem::size_of;
+use std::ops::Add;
+
+use serde::{Deserialize, Serialize};
+
+use crate::engine::MatchingEngine;
+
+/// Approximate heap use of a book, counted from container capacities
+/// rather than measured, so allocator overhead is not included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct MemoryFootprint {
+    /// Price level structures: skip list nodes, map entries, ladder rungs.
+    pub level_bytes: usize,
+    /// Order slots and the strings the orders own.
+    pub order_bytes: usize,
+    /// Lookup tables from order id or price to where things sit.
+    pub index_bytes: usize,
+    pub levels: usize,
+    pub orders: usize,
+}
+
+impl MemoryFootprint {
+    pub fn total_bytes(&self) -> usize {
+        self.level_bytes + self.order_bytes + self.index_bytes
+    }
+}
+
+impl Add for MemoryFootprint {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            level_bytes: self.level_bytes + other.level_bytes,
+            order_bytes: self.order_bytes + other.order_bytes,
+            index_bytes: self.index_bytes + other.index_bytes,
+            levels: self.levels + other.levels,
+            orders: self.orders + other.orders,
+        }
+    }
+}
+
+/// Bytes held by a HashMap's table, one control byte per bucket.
+pub(crate) fn map_bytes<K, V>(map: &HashMap<K, V>) -> usize {
+    map.capacity() * (size_of::<(K, V)>() + 1)
+}
+
+impl MatchingEngine {
+    /// Both sides of `symbol`'s book; None until something has rested there.
+    pub fn book_memory_footprint(&self, symbol: &str) -> Option<MemoryFootprint> {
+        self.order_books.get(symbol).map(|book| book.memory_footprint())
+    }
+}
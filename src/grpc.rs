@@ -0,0 +1,302 @@
+//! gRPC on tonic: `serve_grpc` serves the `MatchingEngine` service of
+//! `proto/matching_engine.proto` and `GrpcClient` calls it. The server and
+//! client stubs are generated by tonic-build in `build.rs`, with messages
+//! encoded by `crate::codec` to the proto file's layout, so clients in
+//! other languages generate their stubs from the proto file as usual.
+
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use bytes::{Buf, BufMut};
+use tokio::net::TcpListener;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder};
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::{Channel, Server};
+use tonic::{Request, Response, Status};
+
+use crate::codec::rpc::{
+    decode_cancel_order, decode_depth, decode_depth_request, decode_event_filter, decode_events, decode_place_order,
+    encode_cancel_order, encode_depth, encode_depth_request, encode_event_filter, encode_events, encode_place_order,
+};
+use crate::codec::{decode_event, encode_event};
+use crate::commands::{CancelOrderCommand, OrderCommand, PlaceOrderCommand};
+use crate::depth::L2Snapshot;
+use crate::engine::MatchingEngine;
+use crate::error::EngineError;
+use crate::events::OrderEvent;
+use crate::subscription::EventFilter;
+
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/matching_engine.MatchingEngine.rs"));
+}
+
+use generated::matching_engine_client::MatchingEngineClient;
+use generated::matching_engine_server::{MatchingEngine as MatchingEngineService, MatchingEngineServer};
+
+/// Streams one connection carries at once.
+const MAX_CONCURRENT_STREAMS: u32 = 256;
+/// Messages queued on a subscription before it waits on the client.
+const STREAM_CAPACITY: usize = 1024;
+const DEFAULT_DEPTH_LEVELS: usize = 10;
+
+/// A client for the `MatchingEngine` gRPC service, over one HTTP/2
+/// connection that clones of it share.
+pub type GrpcClient = MatchingEngineClient<Channel>;
+
+/// `PlaceOrderResponse` and `CancelOrderResponse`: the events of the call.
+pub type Events = Vec<OrderEvent>;
+
+/// `SubscribeDepthRequest`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DepthRequest {
+    pub symbol: String,
+    /// Levels per side, zero for the server's default of ten.
+    pub levels: usize,
+}
+
+/// A message of the service, as `crate::codec` encodes it.
+pub(crate) trait ProtoMessage: Sized {
+    fn encode(&self) -> Vec<u8>;
+    fn decode(bytes: &[u8]) -> Result<Self, String>;
+}
+
+impl ProtoMessage for PlaceOrderCommand {
+    fn encode(&self) -> Vec<u8> {
+        encode_place_order(self)
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, String> {
+        decode_place_order(bytes)
+    }
+}
+
+impl ProtoMessage for CancelOrderCommand {
+    fn encode(&self) -> Vec<u8> {
+        encode_cancel_order(self)
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, String> {
+        decode_cancel_order(bytes)
+    }
+}
+
+impl ProtoMessage for Events {
+    fn encode(&self) -> Vec<u8> {
+        encode_events(self)
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, String> {
+        decode_events(bytes)
+    }
+}
+
+impl ProtoMessage for OrderEvent {
+    fn encode(&self) -> Vec<u8> {
+        encode_event(self)
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, String> {
+        decode_event(bytes)
+    }
+}
+
+impl ProtoMessage for EventFilter {
+    fn encode(&self) -> Vec<u8> {
+        encode_event_filter(self)
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, String> {
+        decode_event_filter(bytes)
+    }
+}
+
+impl ProtoMessage for DepthRequest {
+    fn encode(&self) -> Vec<u8> {
+        encode_depth_request(&self.symbol, self.levels)
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, String> {
+        let (symbol, levels) = decode_depth_request(bytes)?;
+        Ok(Self { symbol, levels })
+    }
+}
+
+impl ProtoMessage for L2Snapshot {
+    fn encode(&self) -> Vec<u8> {
+        encode_depth(self)
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, String> {
+        decode_depth(bytes)
+    }
+}
+
+/// The tonic codec of the generated stubs, encoding `T` and decoding `U`.
+pub(crate) struct ProtoCodec<T, U>(PhantomData<(T, U)>);
+
+impl<T, U> Default for ProtoCodec<T, U> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T, U> Codec for ProtoCodec<T, U>
+where
+    T: ProtoMessage + Send + 'static,
+    U: ProtoMessage + Send + 'static,
+{
+    type Encode = T;
+    type Decode = U;
+    type Encoder = ProtoCodec<T, ()>;
+    type Decoder = ProtoCodec<(), U>;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        ProtoCodec::default()
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        ProtoCodec::default()
+    }
+}
+
+impl<T: ProtoMessage> Encoder for ProtoCodec<T, ()> {
+    type Item = T;
+    type Error = Status;
+
+    fn encode(&mut self, item: T, dst: &mut EncodeBuf<'_>) -> Result<(), Status> {
+        dst.put_slice(&item.encode());
+        Ok(())
+    }
+}
+
+impl<U: ProtoMessage> Decoder for ProtoCodec<(), U> {
+    type Item = U;
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<U>, Status> {
+        let bytes = src.copy_to_bytes(src.remaining());
+        U::decode(&bytes)
+            .map(Some)
+            .map_err(|e| Status::invalid_argument(format!("Bad message: {}", e)))
+    }
+}
+
+impl From<EngineError> for Status {
+    fn from(error: EngineError) -> Self {
+        let message = error.to_string();
+        match error {
+            EngineError::DuplicateOrderId(_) => Status::already_exists(message),
+            EngineError::InternalInvariantViolation(_) => Status::internal(message),
+            EngineError::Rejected(_) => Status::failed_precondition(message),
+            EngineError::NotionalOverflow { .. } | EngineError::InvalidOrder(_) | EngineError::BasketRejected { .. } => {
+                Status::invalid_argument(message)
+            }
+        }
+    }
+}
+
+/// Serves the `MatchingEngine` gRPC service on `listener` until it fails,
+/// over cleartext HTTP/2 as gRPC clients use for plaintext targets.
+pub async fn serve_grpc(engine: Arc<MatchingEngine>, listener: TcpListener) -> Result<(), String> {
+    Server::builder()
+        .max_concurrent_streams(MAX_CONCURRENT_STREAMS)
+        .add_service(MatchingEngineServer::new(GrpcService { engine }))
+        .serve_with_incoming(TcpIncoming::from(listener).with_nodelay(Some(true)))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+struct GrpcService {
+    engine: Arc<MatchingEngine>,
+}
+
+#[tonic::async_trait]
+impl MatchingEngineService for GrpcService {
+    async fn place_order(&self, request: Request<PlaceOrderCommand>) -> Result<Response<Events>, Status> {
+        let command = OrderCommand::PlaceOrder(request.into_inner());
+        Ok(Response::new(self.engine.handle_command(command).await?))
+    }
+
+    async fn cancel_order(&self, request: Request<CancelOrderCommand>) -> Result<Response<Events>, Status> {
+        let command = OrderCommand::CancelOrder(request.into_inner());
+        Ok(Response::new(self.engine.handle_command(command).await?))
+    }
+
+    type SubscribeEventsStream = ReceiverStream<Result<OrderEvent, Status>>;
+
+    /// Streams events as the event store saves them, until the client
+    /// cancels or falls too far behind.
+    async fn subscribe_events(
+        &self,
+        request: Request<EventFilter>,
+    ) -> Result<Response<Self::SubscribeEventsStream>, Status> {
+        let mut subscription = self
+            .engine
+            .event_store()
+            .subscribe(request.into_inner())
+            .map_err(Status::unimplemented)?;
+        let (sender, receiver) = mpsc::channel(STREAM_CAPACITY);
+        tokio::spawn(async move {
+            loop {
+                let received = tokio::select! {
+                    received = subscription.recv() => received,
+                    _ = sender.closed() => return,
+                };
+                let message = match received {
+                    Ok(event) => Ok(event),
+                    Err(RecvError::Lagged(missed)) => {
+                        Err(Status::aborted(format!("Subscriber missed {} events", missed)))
+                    }
+                    Err(RecvError::Closed) => return,
+                };
+                let last = message.is_err();
+                if sender.send(message).await.is_err() || last {
+                    return;
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+
+    type SubscribeDepthStream = ReceiverStream<Result<L2Snapshot, Status>>;
+
+    /// Streams the current depth, then each change within the requested
+    /// levels. A client that falls behind skips to the latest snapshot.
+    async fn subscribe_depth(
+        &self,
+        request: Request<DepthRequest>,
+    ) -> Result<Response<Self::SubscribeDepthStream>, Status> {
+        let DepthRequest { symbol, levels } = request.into_inner();
+        if symbol.is_empty() {
+            return Err(Status::invalid_argument("Missing symbol"));
+        }
+        let levels = if levels == 0 { DEFAULT_DEPTH_LEVELS } else { levels };
+        let mut updates = self.engine.subscribe_depth(&symbol, levels);
+        let current = self.engine.get_l2_snapshot(&symbol, levels);
+        let (sender, receiver) = mpsc::channel(STREAM_CAPACITY);
+        tokio::spawn(async move {
+            if sender.send(Ok(current)).await.is_err() {
+                return;
+            }
+            loop {
+                let received = tokio::select! {
+                    received = updates.recv() => received,
+                    _ = sender.closed() => return,
+                };
+                match received {
+                    Ok(snapshot) => {
+                        if sender.send(Ok((*snapshot).clone())).await.is_err() {
+                            return;
+                        }
+                    }
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return,
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::future::Future;
+
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::engine::MatchingEngine;
+use crate::error::EngineError;
+use crate::events::OrderEvent;
+use crate::types::Order;
+
+/// Client order ids are only unique per user.
+type ClientOrderKey = (Uuid, String);
+
+/// Orders by client order id, and the events each client order id was
+/// first answered with so a resubmission gets the same answer.
+#[derive(Debug, Default)]
+pub(crate) struct ClientOrderIds {
+    orders: DashMap<ClientOrderKey, Uuid>,
+    responses: DashMap<ClientOrderKey, Response>,
+}
+
+/// A client order id as a snapshot keeps it: the order it names and the
+/// events it was first answered with.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientOrderRecord {
+    pub user_id: Uuid,
+    pub client_order_id: String,
+    pub order_id: Option<Uuid>,
+    pub response: Option<Vec<OrderEvent>>,
+}
+
+impl ClientOrderIds {
+    /// Every client order id with its order or answer. Attempts still
+    /// being handled are left out.
+    pub(crate) fn records(&self) -> Vec<ClientOrderRecord> {
+        let record = |(user_id, client_order_id): &ClientOrderKey| ClientOrderRecord {
+            user_id: *user_id,
+            client_order_id: client_order_id.clone(),
+            order_id: None,
+            response: None,
+        };
+        let mut records: HashMap<ClientOrderKey, ClientOrderRecord> = HashMap::new();
+        for entry in self.orders.iter() {
+            records.entry(entry.key().clone()).or_insert_with(|| record(entry.key())).order_id = Some(*entry.value());
+        }
+        for entry in self.responses.iter() {
+            if let Response::Answered(events) = entry.value() {
+                records.entry(entry.key().clone()).or_insert_with(|| record(entry.key())).response =
+                    Some(events.clone());
+            }
+        }
+        records.into_values().collect()
+    }
+
+    pub(crate) fn restore(&self, records: Vec<ClientOrderRecord>) {
+        for record in records {
+            let key = (record.user_id, record.client_order_id);
+            if let Some(order_id) = record.order_id {
+                self.orders.insert(key.clone(), order_id);
+            }
+            if let Some(events) = record.response {
+                self.responses.insert(key, Response::Answered(events));
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Response {
+    /// An attempt with this client order id is being handled.
+    Pending,
+    Answered(Vec<OrderEvent>),
+}
+
+impl MatchingEngine {
+    pub fn get_order_by_client_id(&self, user_id: Uuid, client_order_id: &str) -> Option<Order> {
+        let order_id = *self
+            .client_order_ids
+            .orders
+            .get(&(user_id, client_order_id.to_string()))?;
+        self.get_order(order_id)
+    }
+
+    pub(crate) fn register_client_order_id(&self, order: &Order) {
+        if let Some(client_order_id) = &order.client_order_id {
+            self.client_order_ids
+                .orders
+                .insert((order.user_id, client_order_id.clone()), order.id);
+        }
+    }
+
+    pub(crate) fn forget_client_order_id(&self, order: &Order) {
+        if let Some(client_order_id) = &order.client_order_id {
+            self.client_order_ids
+                .orders
+                .remove_if(&(order.user_id, client_order_id.clone()), |_, id| *id == order.id);
+        }
+    }
+
+    /// Runs `place` once per (user, client order id). A repeat returns the
+    /// events of the first attempt that created an order, or describes
+    /// that order if the attempt failed after creating it; rejected or
+    /// queued attempts are not remembered and may be retried. A repeat
+    /// arriving while an attempt is still being handled is rejected.
+    pub(crate) async fn place_idempotently(
+        &self,
+        user_id: Uuid,
+        client_order_id: Option<&str>,
+        place: impl Future<Output = Result<Vec<OrderEvent>, EngineError>>,
+    ) -> Result<Vec<OrderEvent>, EngineError> {
+        let Some(client_order_id) = client_order_id else {
+            return place.await;
+        };
+        let key = (user_id, client_order_id.to_string());
+        match self.client_order_ids.responses.entry(key.clone()) {
+            Entry::Occupied(entry) => {
+                return match entry.get() {
+                    Response::Answered(events) => Ok(events.clone()),
+                    Response::Pending => {
+                        Err(format!("Client order {} is already being handled", client_order_id).into())
+                    }
+                };
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(Response::Pending);
+            }
+        }
+
+        let result = match self.get_order_by_client_id(user_id, client_order_id) {
+            Some(order) => Ok(vec![self.order_placed_event(&order)]),
+            None => place.await,
+        };
+        match &result {
+            Ok(events) if self.client_order_ids.orders.contains_key(&key) => {
+                self.client_order_ids.responses.insert(key, Response::Answered(events.clone()));
+            }
+            _ => {
+                self.client_order_ids.responses.remove(&key);
+            }
+        }
+        result
+    }
+
+    /// The order a command refers to: by client order id when one is
+    /// given, otherwise by `order_id`.
+    pub(crate) fn resolve_order_ref(
+        &self,
+        user_id: Uuid,
+        client_order_id: Option<&str>,
+        order_id: Uuid,
+    ) -> Result<Uuid, String> {
+        match client_order_id {
+            Some(client_order_id) => self
+                .client_order_ids
+                .orders
+                .get(&(user_id, client_order_id.to_string()))
+                .map(|id| *id)
+                .ok_or_else(|| format!("Client order {} not found", client_order_id)),
+            None => Ok(order_id),
+        }
+    }
+}
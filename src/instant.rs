@@ -0,0 +1,29 @@
+//! `std::time::Instant`, except on wasm32-unknown-unknown, where it panics
+//! and the wall clock stands in for it.
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use std::time::Instant;
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) use wall_clock::Instant;
+
+#[cfg(target_arch = "wasm32")]
+mod wall_clock {
+    use std::time::Duration;
+
+    use chrono::{DateTime, Utc};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    pub(crate) struct Instant(DateTime<Utc>);
+
+    impl Instant {
+        pub(crate) fn now() -> Self {
+            Self(Utc::now())
+        }
+
+        /// Zero if the clock went back.
+        pub(crate) fn elapsed(&self) -> Duration {
+            (Utc::now() - self.0).to_std().unwrap_or_default()
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use std::fmt;
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::commands::OrderCommand;
+use crate::draws::new_event_id;
+use crate::engine::MatchingEngine;
+use crate::events::{InvariantViolatedEvent, OrderEvent};
+use crate::trading_state::SymbolState;
+use crate::types::OrderSide;
+
+/// Self-checks run after every `handle_command`. On by default in debug
+/// builds only.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum InvariantChecks {
+    Disabled,
+    /// Check the books the command touched; with `emit_event`, also record
+    /// an InvariantViolated event before returning the error.
+    Enabled { emit_event: bool },
+}
+
+impl Default for InvariantChecks {
+    fn default() -> Self {
+        if cfg!(debug_assertions) {
+            InvariantChecks::Enabled { emit_event: false }
+        } else {
+            InvariantChecks::Disabled
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum InvariantViolation {
+    /// Best bid at or above best ask outside of an auction.
+    CrossedBook {
+        symbol: String,
+        best_bid: Decimal,
+        best_ask: Decimal,
+    },
+    NegativeQuantity {
+        symbol: String,
+        side: OrderSide,
+        price: Decimal,
+        quantity: Decimal,
+    },
+}
+
+impl InvariantViolation {
+    pub fn symbol(&self) -> &str {
+        match self {
+            InvariantViolation::CrossedBook { symbol, .. } => symbol,
+            InvariantViolation::NegativeQuantity { symbol, .. } => symbol,
+        }
+    }
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvariantViolation::CrossedBook { symbol, best_bid, best_ask } => {
+                write!(f, "{} book is crossed: best bid {} >= best ask {}", symbol, best_bid, best_ask)
+            }
+            InvariantViolation::NegativeQuantity { symbol, side, price, quantity } => {
+                write!(f, "{} {:?} level {} has quantity {}", symbol, side, price, quantity)
+            }
+        }
+    }
+}
+
+impl MatchingEngine {
+    pub fn with_invariant_checks(mut self, checks: InvariantChecks) -> Self {
+        self.invariant_checks = checks;
+        self
+    }
+
+    /// Verifies that `symbol`'s book is neither crossed nor locked (except
+    /// during an auction) and that no resting quantity is negative.
+    pub fn check_invariants(&self, symbol: &str) -> Result<(), InvariantViolation> {
+        let Some(book) = self.order_books.get(symbol) else {
+            return Ok(());
+        };
+
+        for side in [OrderSide::Buy, OrderSide::Sell] {
+            for (price, orders) in book.iter_levels(side) {
+                let quantities: Vec<Decimal> = orders.iter().map(|o| o.remaining_quantity()).collect();
+                let total: Decimal = quantities.iter().sum();
+                if let Some(quantity) = quantities.into_iter().chain([total]).find(|q| *q < Decimal::ZERO) {
+                    return Err(InvariantViolation::NegativeQuantity {
+                        symbol: symbol.to_string(),
+                        side,
+                        price,
+                        quantity,
+                    });
+                }
+            }
+        }
+
+        if self.symbol_state(symbol) == SymbolState::AuctionOnly {
+            return Ok(());
+        }
+        if let Some((best_bid, best_ask)) = book.crossed() {
+            return Err(InvariantViolation::CrossedBook {
+                symbol: symbol.to_string(),
+                best_bid,
+                best_ask,
+            });
+        }
+        Ok(())
+    }
+
+    /// Books a command can touch: its own symbol, plus the legs of a
+    /// synthetic pair.
+    pub(crate) fn command_symbols(&self, command: &OrderCommand) -> Vec<String> {
+        let symbols: Vec<&String> = match command {
+            OrderCommand::PlaceOrder(cmd) => vec![&cmd.symbol],
+            OrderCommand::CancelOrder(cmd) => vec![&cmd.symbol],
+            OrderCommand::PlaceBracketOrder(cmd) => vec![&cmd.entry.symbol],
+            OrderCommand::CancelReplace(cmd) => vec![&cmd.symbol],
+            OrderCommand::AmendOrder(cmd) => vec![&cmd.symbol],
+            OrderCommand::PlaceBasket(cmd) => cmd.legs.iter().map(|leg| &leg.symbol).collect(),
+        };
+        let mut resolved = Vec::new();
+        for symbol in symbols {
+            let legs = match self.get_synthetic_pair(symbol) {
+                Some(pair) => vec![pair.base_leg, pair.quote_leg],
+                None => vec![symbol.clone()],
+            };
+            for leg in legs {
+                if !resolved.contains(&leg) {
+                    resolved.push(leg);
+                }
+            }
+        }
+        resolved
+    }
+
+    /// Runs the configured checks over `symbols`, recording a diagnostic
+    /// event for the first violation if asked to.
+    pub(crate) async fn enforce_invariants(&self, symbols: &[String]) -> Result<(), InvariantViolation> {
+        let InvariantChecks::Enabled { emit_event } = self.invariant_checks else {
+            return Ok(());
+        };
+        let Some(violation) = symbols.iter().find_map(|s| self.check_invariants(s).err()) else {
+            return Ok(());
+        };
+        if emit_event {
+            let mut events = vec![OrderEvent::InvariantViolated(InvariantViolatedEvent {
+                event_id: new_event_id(),
+                prev_hash: None,
+                symbol: violation.symbol().to_string(),
+                violation: violation.clone(),
+                timestamp: self.clock.now(),
+            })];
+            // The violation is reported either way
+            let _ = self.persist_events(&mut events).await;
+        }
+        Err(violation)
+    }
+}
@@ -0,0 +1,251 @@
+//! A compact binary market data format after NASDAQ ITCH, for embedders
+//! that multicast book deltas and trades. Every message is
+//!
+//! ```text
+//! length u16 | type u8 | sequence u64 | timestamp u64 (ns) | body
+//! ```
+//!
+//! big-endian, `length` counting everything after itself. Symbols travel
+//! once as a directory message ('R') that gives them a two-byte locate
+//! code, which deltas ('D') and trades ('P') then carry. Decimals are an
+//! i64 mantissa and a u8 scale.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::book_delta::BookDelta;
+use crate::types::{OrderSide, Trade};
+
+const SYMBOL_DIRECTORY: u8 = b'R';
+const BOOK_DELTA: u8 = b'D';
+const TRADE: u8 = b'P';
+/// Type, sequence and timestamp.
+const HEADER_LENGTH: usize = 17;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ItchMessage {
+    SymbolDirectory {
+        locate: u16,
+        symbol: String,
+    },
+    BookDelta(BookDelta),
+    /// A trade without the orders behind it.
+    Trade {
+        id: Uuid,
+        symbol: String,
+        price: Decimal,
+        quantity: Decimal,
+        /// The aggressor's side.
+        side: OrderSide,
+        timestamp: DateTime<Utc>,
+    },
+}
+
+/// Numbers messages without gaps and assigns symbols their locate codes.
+/// Encode everything of one feed with one encoder.
+#[derive(Debug, Default)]
+pub struct ItchEncoder {
+    next_sequence: u64,
+    locates: HashMap<String, u16>,
+}
+
+impl ItchEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The sequence number the next message gets; the first is 1.
+    pub fn next_sequence(&self) -> u64 {
+        self.next_sequence + 1
+    }
+
+    /// The delta, preceded by a directory message the first time its
+    /// symbol is seen.
+    pub fn encode_delta(&mut self, delta: &BookDelta) -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+        let locate = self.locate(&delta.symbol, delta.timestamp, &mut out)?;
+        let mut body = Vec::with_capacity(38);
+        body.extend_from_slice(&locate.to_be_bytes());
+        body.push(side_code(delta.side));
+        put_decimal(&mut body, delta.price)?;
+        put_decimal(&mut body, delta.quantity)?;
+        body.extend_from_slice(&delta.order_count.to_be_bytes());
+        body.extend_from_slice(&delta.sequence.to_be_bytes());
+        self.message(&mut out, BOOK_DELTA, delta.timestamp, &body);
+        Ok(out)
+    }
+
+    /// The trade, preceded by a directory message the first time its
+    /// symbol is seen.
+    pub fn encode_trade(&mut self, trade: &Trade) -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+        let locate = self.locate(&trade.symbol, trade.created_at, &mut out)?;
+        let mut body = Vec::with_capacity(37);
+        body.extend_from_slice(&locate.to_be_bytes());
+        body.push(side_code(trade.side));
+        put_decimal(&mut body, trade.price)?;
+        put_decimal(&mut body, trade.quantity)?;
+        body.extend_from_slice(trade.id.as_bytes());
+        self.message(&mut out, TRADE, trade.created_at, &body);
+        Ok(out)
+    }
+
+    /// Directory messages for every symbol seen so far, for consumers that
+    /// join late or lost the originals.
+    pub fn encode_directory(&mut self, at: DateTime<Utc>) -> Vec<u8> {
+        let mut symbols: Vec<(String, u16)> = self.locates.iter().map(|(s, l)| (s.clone(), *l)).collect();
+        symbols.sort_by_key(|(_, locate)| *locate);
+        let mut out = Vec::new();
+        for (symbol, locate) in symbols {
+            self.directory(&mut out, &symbol, locate, at);
+        }
+        out
+    }
+
+    fn locate(&mut self, symbol: &str, at: DateTime<Utc>, out: &mut Vec<u8>) -> Result<u16, String> {
+        if let Some(locate) = self.locates.get(symbol) {
+            return Ok(*locate);
+        }
+        if symbol.len() > u8::MAX as usize {
+            return Err(format!("Symbol {} is too long", symbol));
+        }
+        let locate = u16::try_from(self.locates.len() + 1).map_err(|_| "Out of locate codes".to_string())?;
+        self.locates.insert(symbol.to_string(), locate);
+        self.directory(out, symbol, locate, at);
+        Ok(locate)
+    }
+
+    fn directory(&mut self, out: &mut Vec<u8>, symbol: &str, locate: u16, at: DateTime<Utc>) {
+        let mut body = locate.to_be_bytes().to_vec();
+        body.push(symbol.len() as u8);
+        body.extend_from_slice(symbol.as_bytes());
+        self.message(out, SYMBOL_DIRECTORY, at, &body);
+    }
+
+    fn message(&mut self, out: &mut Vec<u8>, kind: u8, at: DateTime<Utc>, body: &[u8]) {
+        self.next_sequence += 1;
+        out.extend_from_slice(&((HEADER_LENGTH + body.len()) as u16).to_be_bytes());
+        out.push(kind);
+        out.extend_from_slice(&self.next_sequence.to_be_bytes());
+        out.extend_from_slice(&(at.timestamp_nanos_opt().unwrap_or_default() as u64).to_be_bytes());
+        out.extend_from_slice(body);
+    }
+}
+
+/// Decodes a feed, remembering its directory to resolve locate codes.
+#[derive(Debug, Default)]
+pub struct ItchDecoder {
+    symbols: HashMap<u16, String>,
+}
+
+impl ItchDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every message in `bytes` with its sequence number. Gaps are left for
+    /// the caller to notice; a delta or trade whose directory message was
+    /// lost fails to decode.
+    pub fn decode(&mut self, mut bytes: &[u8]) -> Result<Vec<(u64, ItchMessage)>, String> {
+        let mut messages = Vec::new();
+        while !bytes.is_empty() {
+            let length = u16::from_be_bytes(take(&mut bytes, 2)?.try_into().unwrap()) as usize;
+            let mut message = take(&mut bytes, length)?;
+            let kind = take(&mut message, 1)?[0];
+            let sequence = read_u64(&mut message)?;
+            let timestamp = DateTime::from_timestamp_nanos(read_u64(&mut message)? as i64);
+            let decoded = match kind {
+                SYMBOL_DIRECTORY => {
+                    let locate = read_u16(&mut message)?;
+                    let len = take(&mut message, 1)?[0] as usize;
+                    let symbol = String::from_utf8(take(&mut message, len)?.to_vec()).map_err(|e| e.to_string())?;
+                    self.symbols.insert(locate, symbol.clone());
+                    ItchMessage::SymbolDirectory { locate, symbol }
+                }
+                BOOK_DELTA => ItchMessage::BookDelta(BookDelta {
+                    symbol: self.symbol(read_u16(&mut message)?)?,
+                    side: side_from(take(&mut message, 1)?[0])?,
+                    price: read_decimal(&mut message)?,
+                    quantity: read_decimal(&mut message)?,
+                    order_count: read_u64(&mut message)?,
+                    sequence: read_u64(&mut message)?,
+                    timestamp,
+                }),
+                TRADE => {
+                    let symbol = self.symbol(read_u16(&mut message)?)?;
+                    let side = side_from(take(&mut message, 1)?[0])?;
+                    let price = read_decimal(&mut message)?;
+                    let quantity = read_decimal(&mut message)?;
+                    let id = Uuid::from_slice(take(&mut message, 16)?).map_err(|e| e.to_string())?;
+                    ItchMessage::Trade {
+                        id,
+                        symbol,
+                        price,
+                        quantity,
+                        side,
+                        timestamp,
+                    }
+                }
+                other => return Err(format!("Unknown message type {}", other as char)),
+            };
+            messages.push((sequence, decoded));
+        }
+        Ok(messages)
+    }
+
+    fn symbol(&self, locate: u16) -> Result<String, String> {
+        self.symbols
+            .get(&locate)
+            .cloned()
+            .ok_or_else(|| format!("Unknown locate code {}", locate))
+    }
+}
+
+fn side_code(side: OrderSide) -> u8 {
+    match side {
+        OrderSide::Buy => b'B',
+        OrderSide::Sell => b'S',
+    }
+}
+
+fn side_from(code: u8) -> Result<OrderSide, String> {
+    match code {
+        b'B' => Ok(OrderSide::Buy),
+        b'S' => Ok(OrderSide::Sell),
+        other => Err(format!("Unknown side {}", other as char)),
+    }
+}
+
+fn put_decimal(out: &mut Vec<u8>, value: Decimal) -> Result<(), String> {
+    let value = value.normalize();
+    let mantissa = i64::try_from(value.mantissa()).map_err(|_| format!("{} does not fit in 64 bits", value))?;
+    out.extend_from_slice(&mantissa.to_be_bytes());
+    out.push(value.scale() as u8);
+    Ok(())
+}
+
+fn read_decimal(bytes: &mut &[u8]) -> Result<Decimal, String> {
+    let mantissa = read_u64(bytes)? as i64;
+    let scale = take(bytes, 1)?[0];
+    Decimal::try_from_i128_with_scale(mantissa.into(), scale.into()).map_err(|e| e.to_string())
+}
+
+fn read_u16(bytes: &mut &[u8]) -> Result<u16, String> {
+    Ok(u16::from_be_bytes(take(bytes, 2)?.try_into().unwrap()))
+}
+
+fn read_u64(bytes: &mut &[u8]) -> Result<u64, String> {
+    Ok(u64::from_be_bytes(take(bytes, 8)?.try_into().unwrap()))
+}
+
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], String> {
+    if bytes.len() < len {
+        return Err("Truncated message".to_string());
+    }
+    let (taken, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Ok(taken)
+}
@@ -0,0 +1,125 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::commands::OrderCommand;
+use crate::draws::new_event_id;
+use crate::engine::MatchingEngine;
+use crate::error::RejectReason;
+use crate::events::{KillSwitchActivatedEvent, KillSwitchReleasedEvent, OrderEvent};
+use crate::limits::order_entries;
+
+/// What a kill switch stops trading for.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum KillSwitchTarget {
+    User(Uuid),
+    Symbol(String),
+}
+
+impl KillSwitchTarget {
+    pub fn user_id(&self) -> Option<Uuid> {
+        match self {
+            KillSwitchTarget::User(user_id) => Some(*user_id),
+            KillSwitchTarget::Symbol(_) => None,
+        }
+    }
+
+    pub fn symbol(&self) -> Option<&str> {
+        match self {
+            KillSwitchTarget::User(_) => None,
+            KillSwitchTarget::Symbol(symbol) => Some(symbol),
+        }
+    }
+}
+
+impl fmt::Display for KillSwitchTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KillSwitchTarget::User(user_id) => write!(f, "user {}", user_id),
+            KillSwitchTarget::Symbol(symbol) => write!(f, "symbol {}", symbol),
+        }
+    }
+}
+
+impl MatchingEngine {
+    /// Stops all trading for `target` at once: every open order it has is
+    /// canceled and commands entering new orders are rejected with
+    /// `KillSwitchActive` until the switch is released. Cancels are still
+    /// accepted. Waits for commands in flight to finish first.
+    pub async fn activate_kill_switch(
+        &self,
+        target: KillSwitchTarget,
+        reason: &str,
+    ) -> Result<Vec<OrderEvent>, String> {
+        let _gate = self.command_gate.write().await;
+        let now = self.clock.now();
+        self.kill_switches.insert(target.clone(), now);
+
+        let open = match &target {
+            KillSwitchTarget::User(user_id) => self.get_open_orders(*user_id),
+            KillSwitchTarget::Symbol(symbol) => self.get_open_orders_by_symbol(symbol),
+        };
+        let mut canceled = Vec::new();
+        let mut symbols: Vec<String> = Vec::new();
+        for order in open {
+            canceled.push(order.id);
+            if !symbols.contains(&order.symbol) {
+                symbols.push(order.symbol.clone());
+            }
+        }
+        let mut events = vec![OrderEvent::KillSwitchActivated(KillSwitchActivatedEvent {
+            event_id: new_event_id(),
+            prev_hash: None,
+            target,
+            reason: reason.to_string(),
+            canceled_order_ids: canceled.clone(),
+            timestamp: now,
+        })];
+        for order_id in canceled {
+            self.cancel_order(order_id, &mut events);
+        }
+        self.refresh_book_views(&symbols);
+        self.refresh_top_of_book(&symbols);
+        self.publish_market_feeds(&symbols);
+
+        self.persist_events(&mut events).await?;
+        Ok(events)
+    }
+
+    /// Lets `target` trade again. Releasing a switch that is not active is
+    /// a no-op.
+    pub async fn release_kill_switch(&self, target: KillSwitchTarget) -> Result<Vec<OrderEvent>, String> {
+        if self.kill_switches.remove(&target).is_none() {
+            return Ok(Vec::new());
+        }
+        let mut events = vec![OrderEvent::KillSwitchReleased(KillSwitchReleasedEvent {
+            event_id: new_event_id(),
+            prev_hash: None,
+            target,
+            timestamp: self.clock.now(),
+        })];
+        self.persist_events(&mut events).await?;
+        Ok(events)
+    }
+
+    /// When the switch on `target` was activated, if it is active.
+    pub fn kill_switch_activated_at(&self, target: &KillSwitchTarget) -> Option<DateTime<Utc>> {
+        self.kill_switches.get(target).map(|at| *at)
+    }
+
+    pub(crate) fn check_kill_switches(&self, command: &OrderCommand) -> Result<(), RejectReason> {
+        if self.kill_switches.is_empty() {
+            return Ok(());
+        }
+        for (user_id, symbol) in order_entries(command) {
+            for target in [KillSwitchTarget::User(user_id), KillSwitchTarget::Symbol(symbol.to_string())] {
+                if self.kill_switches.contains_key(&target) {
+                    return Err(RejectReason::KillSwitchActive(target));
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,320 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, RwLock};
+
+use async_trait::async_trait;
+use chrono::DateTime;
+use uuid::Uuid;
+
+use crate::event_store::{chain_events, event_hash, unsaved, EventStore, StoredEvent, TimeRange};
+use crate::events::OrderEvent;
+use crate::subscription::{event_users, EventFeed, EventFilter, EventSubscription};
+
+const EVENT: u8 = b'e';
+const BY_ORDER: u8 = b'o';
+const BY_SYMBOL: u8 = b's';
+const BY_USER: u8 = b'u';
+const BY_KIND: u8 = b'k';
+/// Order id to the user that placed it, for indexing matches by user.
+const OWNER: u8 = b'w';
+/// Ids of the events saved, so retried saves are skipped.
+const EVENT_ID: u8 = b'i';
+
+pub type KvPair = (Vec<u8>, Vec<u8>);
+
+/// An ordered key-value store, such as an embedded database, that
+/// `KvEventStore` can sit on: `SledKv` with the `sled` feature, or
+/// `MemoryKv`, which keeps nothing across restarts.
+pub trait KvBackend: Send + Sync {
+    /// Writes every pair or none of them.
+    fn write_batch(&self, batch: Vec<KvPair>) -> Result<(), String>;
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, String>;
+    /// Pairs whose key starts with `prefix`, in key order.
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<Vec<KvPair>, String>;
+
+    fn last_with_prefix(&self, prefix: &[u8]) -> Result<Option<KvPair>, String> {
+        Ok(self.scan_prefix(prefix)?.pop())
+    }
+
+    /// Up to `limit` pairs under `prefix` from `start` on, in key order.
+    fn scan_from(&self, prefix: &[u8], start: &[u8], limit: usize) -> Result<Vec<KvPair>, String> {
+        Ok(self
+            .scan_prefix(prefix)?
+            .into_iter()
+            .filter(|(key, _)| key.as_slice() >= start)
+            .take(limit)
+            .collect())
+    }
+}
+
+/// A `KvBackend` in a BTreeMap, for tests and for running the KV layout
+/// without a database.
+#[derive(Debug, Default)]
+pub struct MemoryKv {
+    entries: RwLock<BTreeMap<Vec<u8>, Vec<u8>>>,
+}
+
+impl MemoryKv {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl KvBackend for MemoryKv {
+    fn write_batch(&self, batch: Vec<KvPair>) -> Result<(), String> {
+        self.entries.write().unwrap().extend(batch);
+        Ok(())
+    }
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, String> {
+        Ok(self.entries.read().unwrap().get(key).cloned())
+    }
+
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<Vec<KvPair>, String> {
+        Ok(self
+            .entries
+            .read()
+            .unwrap()
+            .range(prefix.to_vec()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect())
+    }
+
+    fn last_with_prefix(&self, prefix: &[u8]) -> Result<Option<KvPair>, String> {
+        let entries = self.entries.read().unwrap();
+        let last = entries
+            .range(prefix.to_vec()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .last();
+        Ok(last.map(|(key, value)| (key.clone(), value.clone())))
+    }
+
+    fn scan_from(&self, prefix: &[u8], start: &[u8], limit: usize) -> Result<Vec<KvPair>, String> {
+        Ok(self
+            .entries
+            .read()
+            .unwrap()
+            .range(start.to_vec()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .take(limit)
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect())
+    }
+}
+
+/// Events keyed by a sequence number assigned on save, with index entries
+/// per order, symbol, user and kind pointing back at the sequence. Index
+/// entries hold the event's timestamp so range queries skip the events
+/// outside the range unread. Big-endian sequences keep every prefix scan in
+/// save order.
+pub struct KvEventStore<B> {
+    backend: B,
+    next_sequence: Mutex<u64>,
+    feed: EventFeed,
+    hash_chain: bool,
+}
+
+impl<B: KvBackend> KvEventStore<B> {
+    /// Picks up numbering after the last event already in `backend`.
+    pub fn open(backend: B) -> Result<Self, String> {
+        let last = match backend.last_with_prefix(&[EVENT])? {
+            Some((key, _)) => sequence_of(&key)?,
+            None => 0,
+        };
+        Ok(Self {
+            backend,
+            next_sequence: Mutex::new(last + 1),
+            feed: EventFeed::new(),
+            hash_chain: false,
+        })
+    }
+
+    /// Has each saved event carry the hash of the one before it.
+    pub fn with_hash_chain(mut self) -> Self {
+        self.hash_chain = true;
+        self
+    }
+
+    pub fn into_backend(self) -> B {
+        self.backend
+    }
+
+    fn owner(&self, order_id: Uuid) -> Result<Option<Uuid>, String> {
+        match self.backend.get(&owner_key(order_id))? {
+            Some(bytes) => Uuid::from_slice(&bytes).map(Some).map_err(|e| e.to_string()),
+            None => Ok(None),
+        }
+    }
+
+    /// Events an index prefix points at, in save order.
+    fn indexed(&self, prefix: &[u8], range: TimeRange) -> Result<Vec<OrderEvent>, String> {
+        let mut events = Vec::new();
+        for (key, timestamp) in self.backend.scan_prefix(prefix)? {
+            let timestamp = <[u8; 8]>::try_from(timestamp.as_slice())
+                .map_err(|_| "Malformed event store index entry".to_string())?;
+            if !range.contains(DateTime::from_timestamp_nanos(i64::from_be_bytes(timestamp))) {
+                continue;
+            }
+            let sequence = sequence_of(&key)?;
+            let bytes = self
+                .backend
+                .get(&event_key(sequence))?
+                .ok_or_else(|| format!("Index points at missing event {}", sequence))?;
+            events.push(serde_json::from_slice(&bytes).map_err(|e| e.to_string())?);
+        }
+        Ok(events)
+    }
+}
+
+fn event_key(sequence: u64) -> Vec<u8> {
+    let mut key = vec![EVENT];
+    key.extend_from_slice(&sequence.to_be_bytes());
+    key
+}
+
+fn order_prefix(order_id: Uuid) -> Vec<u8> {
+    let mut key = vec![BY_ORDER];
+    key.extend_from_slice(order_id.as_bytes());
+    key
+}
+
+/// The symbol is followed by a 0 byte so "BTC" does not match "BTC/USDT".
+fn symbol_prefix(symbol: &str) -> Vec<u8> {
+    let mut key = vec![BY_SYMBOL];
+    key.extend_from_slice(symbol.as_bytes());
+    key.push(0);
+    key
+}
+
+fn user_prefix(user_id: Uuid) -> Vec<u8> {
+    let mut key = vec![BY_USER];
+    key.extend_from_slice(user_id.as_bytes());
+    key
+}
+
+fn kind_prefix(kind: &str) -> Vec<u8> {
+    let mut key = vec![BY_KIND];
+    key.extend_from_slice(kind.as_bytes());
+    key.push(0);
+    key
+}
+
+fn event_id_key(event_id: Uuid) -> Vec<u8> {
+    let mut key = vec![EVENT_ID];
+    key.extend_from_slice(event_id.as_bytes());
+    key
+}
+
+fn owner_key(order_id: Uuid) -> Vec<u8> {
+    let mut key = vec![OWNER];
+    key.extend_from_slice(order_id.as_bytes());
+    key
+}
+
+/// Every key ends in the sequence it refers to.
+fn sequence_of(key: &[u8]) -> Result<u64, String> {
+    key.len()
+        .checked_sub(8)
+        .and_then(|start| key[start..].try_into().ok())
+        .map(u64::from_be_bytes)
+        .ok_or_else(|| "Malformed event store key".to_string())
+}
+
+#[async_trait]
+impl<B: KvBackend> EventStore for KvEventStore<B> {
+    async fn save_events(&self, events: Vec<OrderEvent>) -> Result<(), String> {
+        let mut next_sequence = self.next_sequence.lock().unwrap();
+        let mut events = unsaved(events, |event_id| Ok(self.backend.get(&event_id_key(event_id))?.is_some()))?;
+        let mut sequence = *next_sequence;
+        if self.hash_chain && !events.is_empty() {
+            let mut head = match self.backend.get(&event_key(sequence - 1))? {
+                Some(bytes) => Some(event_hash(&serde_json::from_slice(&bytes).map_err(|e| e.to_string())?)),
+                None => None,
+            };
+            chain_events(&mut events, &mut head);
+        }
+        let mut batch = Vec::new();
+        let mut owners = HashMap::new();
+        for event in &events {
+            if let OrderEvent::OrderMatched(e) = event {
+                for order_id in [e.order_id, e.matched_order_id] {
+                    if let Some(user_id) = self.owner(order_id)? {
+                        owners.insert(order_id, user_id);
+                    }
+                }
+            }
+        }
+        for event in &events {
+            let bytes = serde_json::to_vec(event).map_err(|e| e.to_string())?;
+            batch.push((event_key(sequence), bytes));
+            if !event.event_id().is_nil() {
+                batch.push((event_id_key(event.event_id()), Vec::new()));
+            }
+            if let OrderEvent::OrderPlaced(e) = event {
+                owners.insert(e.order_id, e.user_id);
+                batch.push((owner_key(e.order_id), e.user_id.as_bytes().to_vec()));
+            }
+            let mut indexes = vec![kind_prefix(event.kind())];
+            if let Some(order_id) = event.order_id() {
+                indexes.push(order_prefix(order_id));
+            }
+            if let Some(symbol) = event.symbol() {
+                indexes.push(symbol_prefix(symbol));
+            }
+            let users = event_users(event, |order_id| owners.get(&order_id).copied());
+            indexes.extend(users.into_iter().map(user_prefix));
+            let timestamp = event.timestamp().timestamp_nanos_opt().unwrap_or_default();
+            for mut key in indexes {
+                key.extend_from_slice(&sequence.to_be_bytes());
+                batch.push((key, timestamp.to_be_bytes().to_vec()));
+            }
+            sequence += 1;
+        }
+        self.backend.write_batch(batch)?;
+        *next_sequence = sequence;
+        self.feed.publish(&events);
+        Ok(())
+    }
+
+    async fn get_events(&self, order_id: Uuid) -> Result<Vec<OrderEvent>, String> {
+        self.indexed(&order_prefix(order_id), TimeRange::all())
+    }
+
+    async fn get_all_events(&self) -> Result<Vec<OrderEvent>, String> {
+        self.backend
+            .scan_prefix(&[EVENT])?
+            .into_iter()
+            .map(|(_, bytes)| serde_json::from_slice(&bytes).map_err(|e| e.to_string()))
+            .collect()
+    }
+
+    async fn get_events_by_symbol(&self, symbol: &str, range: TimeRange) -> Result<Vec<OrderEvent>, String> {
+        self.indexed(&symbol_prefix(symbol), range)
+    }
+
+    async fn get_events_by_user(&self, user_id: Uuid, range: TimeRange) -> Result<Vec<OrderEvent>, String> {
+        self.indexed(&user_prefix(user_id), range)
+    }
+
+    async fn get_events_by_type(&self, kind: &str, range: TimeRange) -> Result<Vec<OrderEvent>, String> {
+        self.indexed(&kind_prefix(kind), range)
+    }
+
+    /// Offsets are sequences less one.
+    async fn read_from(&self, offset: u64, max: usize) -> Result<Vec<StoredEvent>, String> {
+        self.backend
+            .scan_from(&[EVENT], &event_key(offset + 1), max)?
+            .into_iter()
+            .map(|(key, bytes)| {
+                Ok(StoredEvent {
+                    offset: sequence_of(&key)? - 1,
+                    event: serde_json::from_slice(&bytes).map_err(|e| e.to_string())?,
+                })
+            })
+            .collect()
+    }
+
+    fn subscribe(&self, filter: EventFilter) -> Result<EventSubscription, String> {
+        Ok(self.feed.subscribe(filter))
+    }
+}
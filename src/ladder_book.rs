@@ -0,0 +1,327 @@
+use std::collections::HashMap;
+use std::mem::size_of;
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::RejectReason;
+use crate::footprint::{map_bytes, MemoryFootprint};
+use crate::orderbook::{LevelIter, OrderBookSide};
+use crate::price_level::PriceLevel;
+use crate::types::{Order, OrderSide};
+
+/// The prices a symbol can trade at: every `tick_size` from `min_price`
+/// to `max_price` inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PriceLadder {
+    pub tick_size: Decimal,
+    pub min_price: Decimal,
+    pub max_price: Decimal,
+}
+
+impl PriceLadder {
+    pub fn new(tick_size: Decimal, min_price: Decimal, max_price: Decimal) -> Self {
+        Self {
+            tick_size,
+            min_price,
+            max_price,
+        }
+    }
+
+    /// Number of rungs on the ladder.
+    pub fn len(&self) -> usize {
+        self.index(self.max_price).map_or(0, |top| top + 1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn check(&self, price: Decimal) -> Result<(), RejectReason> {
+        if price < self.min_price || price > self.max_price {
+            return Err(RejectReason::PriceOutOfRange {
+                price,
+                min_price: self.min_price,
+                max_price: self.max_price,
+            });
+        }
+        if self.tick_size <= Decimal::ZERO || (price - self.min_price) % self.tick_size != Decimal::ZERO {
+            return Err(RejectReason::PriceNotOnTick {
+                price,
+                tick_size: self.tick_size,
+            });
+        }
+        Ok(())
+    }
+
+    fn index(&self, price: Decimal) -> Option<usize> {
+        self.check(price).ok()?;
+        ((price - self.min_price) / self.tick_size).to_usize()
+    }
+
+    fn price(&self, index: usize) -> Decimal {
+        self.min_price + self.tick_size * Decimal::from(index)
+    }
+}
+
+/// One side of a book on a fixed price ladder, levels indexed by tick
+/// offset in a flat Vec. Best price and level lookups are O(1); the
+/// symbol's validation keeps prices off the ladder from reaching it.
+#[derive(Debug, Clone)]
+pub struct LadderOrderBook {
+    ladder: PriceLadder,
+    levels: Vec<PriceLevel>,
+    /// Lowest and highest occupied rungs.
+    low: Option<usize>,
+    high: Option<usize>,
+    occupied: usize,
+    order_levels: HashMap<Uuid, usize>,
+}
+
+impl LadderOrderBook {
+    pub fn new(ladder: PriceLadder) -> Self {
+        Self {
+            ladder,
+            levels: vec![PriceLevel::new(); ladder.len()],
+            low: None,
+            high: None,
+            occupied: 0,
+            order_levels: HashMap::new(),
+        }
+    }
+
+    pub fn ladder(&self) -> PriceLadder {
+        self.ladder
+    }
+
+    /// Queues `order` on its rung. Returns false, leaving the book
+    /// untouched, if its price is not on the ladder.
+    pub fn add_order(&mut self, order: Order) -> bool {
+        let Some(index) = order.price.and_then(|price| self.ladder.index(price)) else {
+            return false;
+        };
+        let level = &mut self.levels[index];
+        if level.is_empty() {
+            self.occupied += 1;
+        }
+        self.order_levels.insert(order.id, index);
+        level.push(order);
+        self.low = Some(self.low.map_or(index, |low| low.min(index)));
+        self.high = Some(self.high.map_or(index, |high| high.max(index)));
+        true
+    }
+
+    pub fn remove_order_by_id(&mut self, order_id: Uuid) -> Option<Order> {
+        let index = *self.order_levels.get(&order_id)?;
+        let order = self.levels[index].remove(order_id)?;
+        self.order_levels.remove(&order_id);
+        if self.levels[index].is_empty() {
+            self.occupied -= 1;
+            self.vacate(index);
+        }
+        Some(order)
+    }
+
+    /// Moves the low/high marks off a rung that just emptied.
+    fn vacate(&mut self, index: usize) {
+        let (Some(low), Some(high)) = (self.low, self.high) else {
+            return;
+        };
+        if self.occupied == 0 {
+            self.low = None;
+            self.high = None;
+        } else if index == low {
+            self.low = (low + 1..=high).find(|&i| !self.levels[i].is_empty());
+        } else if index == high {
+            self.high = (low..high).rev().find(|&i| !self.levels[i].is_empty());
+        }
+    }
+
+    pub fn get_order(&self, order_id: Uuid) -> Option<&Order> {
+        let index = *self.order_levels.get(&order_id)?;
+        self.levels[index].get(order_id)
+    }
+
+    /// The best price for a book holding `side`'s orders: the highest bid
+    /// or the lowest ask.
+    pub fn get_best_price(&self, side: OrderSide) -> Option<Decimal> {
+        let index = match side {
+            OrderSide::Buy => self.high,
+            OrderSide::Sell => self.low,
+        };
+        index.map(|index| self.ladder.price(index))
+    }
+}
+
+impl OrderBookSide for LadderOrderBook {
+    fn insert(&mut self, order: Order) {
+        self.add_order(order);
+    }
+
+    fn remove(&mut self, order_id: Uuid) -> Option<Order> {
+        self.remove_order_by_id(order_id)
+    }
+
+    fn get(&self, order_id: Uuid) -> Option<&Order> {
+        self.get_order(order_id)
+    }
+
+    fn update(&mut self, order: &Order) {
+        if !order.is_open() || order.remaining_quantity() <= Decimal::ZERO {
+            self.remove_order_by_id(order.id);
+            return;
+        }
+        let Some(&index) = self.order_levels.get(&order.id) else {
+            return;
+        };
+        if let Some(resting) = self.levels[index].get_mut(order.id) {
+            *resting = order.clone();
+        }
+    }
+
+    fn level(&self, price: Decimal) -> Option<&PriceLevel> {
+        let level = &self.levels[self.ladder.index(price)?];
+        (!level.is_empty()).then_some(level)
+    }
+
+    fn iter_levels(&self) -> LevelIter<'_> {
+        let (Some(low), Some(high)) = (self.low, self.high) else {
+            return Box::new(std::iter::empty());
+        };
+        Box::new(
+            (low..=high)
+                .filter(|&index| !self.levels[index].is_empty())
+                .map(|index| (self.ladder.price(index), &self.levels[index])),
+        )
+    }
+
+    fn lowest_price(&self) -> Option<Decimal> {
+        self.get_best_price(OrderSide::Sell)
+    }
+
+    fn highest_price(&self) -> Option<Decimal> {
+        self.get_best_price(OrderSide::Buy)
+    }
+
+    fn level_count(&self) -> usize {
+        self.occupied
+    }
+
+    fn order_count(&self) -> usize {
+        self.order_levels.len()
+    }
+
+    fn retain_orders(&mut self, keep: &mut dyn FnMut(&Order) -> bool) -> Vec<Order> {
+        let (Some(low), Some(high)) = (self.low, self.high) else {
+            return Vec::new();
+        };
+        let mut removed = Vec::new();
+        for level in &mut self.levels[low..=high] {
+            removed.extend(level.retain(&mut *keep));
+        }
+        for order in &removed {
+            self.order_levels.remove(&order.id);
+        }
+        let occupied: Vec<usize> = (low..=high).filter(|&i| !self.levels[i].is_empty()).collect();
+        self.occupied = occupied.len();
+        self.low = occupied.first().copied();
+        self.high = occupied.last().copied();
+        removed
+    }
+
+    fn memory_footprint(&self) -> MemoryFootprint {
+        let mut footprint = MemoryFootprint {
+            level_bytes: self.levels.capacity() * size_of::<PriceLevel>(),
+            index_bytes: map_bytes(&self.order_levels),
+            levels: self.occupied,
+            orders: self.order_levels.len(),
+            ..MemoryFootprint::default()
+        };
+        for level in &self.levels {
+            let (orders, index) = level.heap_bytes();
+            footprint.order_bytes += orders;
+            footprint.index_bytes += index;
+        }
+        footprint
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::OrderType;
+
+    fn ladder() -> PriceLadder {
+        PriceLadder::new(Decimal::new(5, 1), Decimal::from(90), Decimal::from(110))
+    }
+
+    fn create_test_order(price: Decimal) -> Order {
+        Order::new(
+            Uuid::new_v4(),
+            "BTC/USDT".to_string(),
+            OrderType::Limit,
+            OrderSide::Buy,
+            Some(price),
+            Decimal::ONE,
+        )
+    }
+
+    #[test]
+    fn test_ladder_rejects_prices_off_tick_or_out_of_range() {
+        let ladder = ladder();
+        assert_eq!(ladder.len(), 41);
+        assert!(ladder.check(Decimal::new(1005, 1)).is_ok());
+        assert!(matches!(
+            ladder.check(Decimal::new(1002, 1)),
+            Err(RejectReason::PriceNotOnTick { .. })
+        ));
+        assert!(matches!(
+            ladder.check(Decimal::from(111)),
+            Err(RejectReason::PriceOutOfRange { .. })
+        ));
+
+        let mut book = LadderOrderBook::new(ladder);
+        assert!(!book.add_order(create_test_order(Decimal::from(120))));
+        assert_eq!(book.order_count(), 0);
+    }
+
+    #[test]
+    fn test_best_prices_track_emptied_rungs() {
+        let mut book = LadderOrderBook::new(ladder());
+        let orders: Vec<Order> = [100, 95, 105]
+            .into_iter()
+            .map(|price| create_test_order(Decimal::from(price)))
+            .collect();
+        for order in &orders {
+            book.add_order(order.clone());
+        }
+        assert_eq!(book.get_best_price(OrderSide::Buy), Some(Decimal::from(105)));
+        assert_eq!(book.get_best_price(OrderSide::Sell), Some(Decimal::from(95)));
+
+        book.remove_order_by_id(orders[2].id);
+        book.remove_order_by_id(orders[1].id);
+        assert_eq!(book.get_best_price(OrderSide::Buy), Some(Decimal::from(100)));
+        assert_eq!(book.get_best_price(OrderSide::Sell), Some(Decimal::from(100)));
+        assert_eq!(book.iter_levels().count(), 1);
+
+        book.remove_order_by_id(orders[0].id);
+        assert_eq!(book.get_best_price(OrderSide::Sell), None);
+        assert_eq!(book.level_count(), 0);
+    }
+
+    #[test]
+    fn test_retain_orders_moves_the_marks() {
+        let mut book = LadderOrderBook::new(ladder());
+        for price in [95, 100, 105] {
+            book.add_order(create_test_order(Decimal::from(price)));
+        }
+
+        let removed = book.retain_orders(&mut |o| o.price == Some(Decimal::from(100)));
+        assert_eq!(removed.len(), 2);
+        assert_eq!(book.get_best_price(OrderSide::Buy), Some(Decimal::from(100)));
+        assert_eq!(book.get_best_price(OrderSide::Sell), Some(Decimal::from(100)));
+        assert_eq!((book.level_count(), book.order_count()), (1, 1));
+    }
+}
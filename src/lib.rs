@@ -1,15 +1,201 @@
 pub mod types;
 pub mod engine;
+mod accounts;
+mod amend;
+#[cfg(feature = "server")]
+mod auth;
+mod basket;
+mod book;
+mod book_delta;
+mod book_limits;
+mod book_view;
+#[cfg(feature = "btree-book")]
+mod btree_book;
+mod bracket;
+mod candles;
+mod checkpoint;
+mod cancel_replace;
+mod circuit_breaker;
+mod config;
+mod credit;
+mod daily_limits;
+mod depth;
+mod depth_diff;
+mod digest;
+mod draws;
+mod drop_copy;
+mod archive;
+#[cfg(feature = "s3")]
+mod s3;
+mod auction;
 mod commands;
 mod events;
+mod feeds;
+mod fees;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod file_store;
+#[cfg(feature = "fix")]
+mod fix;
+mod footprint;
+#[cfg(feature = "grpc")]
+mod grpc;
+mod error;
+mod instant;
+mod itch;
+mod invariants;
+mod kv_store;
+mod ladder_book;
+mod lifecycle;
+mod limits;
+mod market_data;
 pub mod event_store;
+pub mod codec;
+mod persistence;
+#[cfg(feature = "postgres")]
+mod postgres_store;
+mod event_writer;
+mod precision;
+mod synthetic;
+mod queries;
+mod quote_orders;
+mod replay;
+mod replication;
+#[cfg(feature = "server")]
+mod rest;
+mod rollback;
+#[cfg(feature = "server")]
+mod server;
+mod simulator;
+#[cfg(feature = "sled")]
+mod sled_kv;
+mod snapshot;
+mod subscription;
+mod surveillance;
+mod idempotency;
+mod trade_bust;
+mod trade_log;
+mod trade_store;
+mod top_of_book;
+mod wal;
+#[cfg(feature = "wasm")]
+mod wasm;
+#[cfg(feature = "webhooks")]
+mod webhooks;
+mod trading_state;
+mod matching;
+mod order_size;
+mod outbox;
+mod kill_switch;
+mod margin;
+mod positions;
+mod price_band;
+mod risk;
+mod settlement;
+mod projection;
+mod middleware;
+pub mod symbols;
+pub mod metrics;
+pub mod anonymize;
 mod orderbook;
+mod price_level;
+mod sides;
 
 pub use types::{
     Order, OrderBook, OrderBookEntry, OrderSide, OrderStatus, OrderType, Trade,
 };
 pub use engine::MatchingEngine;
-pub use commands::{OrderCommand, PlaceOrderCommand, CancelOrderCommand};
-pub use events::{OrderEvent, OrderPlacedEvent, OrderMatchedEvent, OrderPartiallyFilledEvent, OrderFilledEvent};
-pub use event_store::{EventStore, InMemoryEventStore};
-pub use orderbook::SkipListOrderBook; 
\ No newline at end of file
+pub use accounts::Balance;
+pub use daily_limits::{DailyLimitKind, DailyLimits, DailyUsage};
+pub use fees::{FeeSchedule, Liquidity};
+pub use kill_switch::KillSwitchTarget;
+pub use bracket::BracketGroup;
+pub use idempotency::ClientOrderRecord;
+pub use lifecycle::TradingSchedule;
+pub use margin::{LeverageLimit, MarginSummary};
+pub use positions::{ExposureLimits, Position};
+pub use risk::{MaxOpenNotionalCheck, MaxOrderNotionalCheck, PriceDeviationCheck, RiskCheck, RiskContext};
+pub use settlement::{SettlementBatch, SettlementDirection, SettlementInstruction, SettlementSink};
+pub use error::{EngineError, LegRejection, RejectReason};
+pub use invariants::{InvariantChecks, InvariantViolation};
+pub use commands::{
+    OrderCommand, PlaceOrderCommand, CancelOrderCommand, PlaceBracketOrderCommand, CancelReplaceCommand,
+    AmendOrderCommand, PlaceBasketCommand, BasketValidation, BasketExecution, CancelRequest, PlaceRequest,
+};
+pub use events::{
+    OrderEvent, OrderPlacedEvent, OrderCanceledEvent, OrderUpdatedEvent, OrderMatchedEvent, OrderPartiallyFilledEvent,
+    OrderFilledEvent, BracketOrderPlacedEvent, BracketOrderActivatedEvent, BracketOrderCompletedEvent,
+    PersistenceHaltedEvent, PersistenceResumedEvent, SyntheticTradeExecutedEvent,
+    AuctionPriceDeterminedEvent, SymbolStateChangedEvent, CircuitBreakerTriggeredEvent,
+    InvariantViolatedEvent, OrderRejectedEvent, RateLimitExceededEvent, BookLevelEvictedEvent, CandleClosedEvent,
+    KillSwitchActivatedEvent, KillSwitchReleasedEvent, LiquidationTriggeredEvent,
+    TradeBustedEvent, FeeChargedEvent, DailyLimitReachedEvent,
+};
+pub use event_store::{EventStore, InMemoryEventStore, IntegrityReport, StoredEvent, TimeRange};
+pub use file_store::{FileEventStore, FsyncPolicy};
+pub use archive::{DirObjectStore, InMemoryObjectStore, ObjectStore};
+#[cfg(feature = "s3")]
+pub use s3::S3ObjectStore;
+#[cfg(feature = "fix")]
+pub use fix::{command_from_fix, serve_fix, FixMessage};
+#[cfg(feature = "grpc")]
+pub use grpc::{serve_grpc, DepthRequest, GrpcClient};
+#[cfg(feature = "server")]
+pub use auth::{Authenticator, StaticTokens};
+#[cfg(feature = "server")]
+pub use rest::{rest_router, serve_rest};
+#[cfg(feature = "server")]
+pub use server::{serve_websocket, websocket_router, Channel, ClientMessage, ServerMessage};
+pub use subscription::{EventFeed, EventFilter, EventSubscription};
+#[cfg(feature = "webhooks")]
+pub use webhooks::{webhook_signature, Webhook, WebhookDispatcher, WebhookRetryPolicy, WebhookTargets};
+pub use outbox::{CursorStore, EventPublisher, FileCursorStore, InMemoryCursorStore, Outbox};
+pub use projection::{spawn_projector, OpenOrdersView, ProjectedOrder, Projection, Projector, SymbolTradeTapeView, TapeEntry, UserOrderHistoryView};
+pub use kv_store::{KvBackend, KvEventStore, KvPair, MemoryKv};
+#[cfg(feature = "sled")]
+pub use sled_kv::SledKv;
+#[cfg(feature = "postgres")]
+pub use postgres_store::PostgresEventStore;
+pub use persistence::{HaltScope, PersistenceFailurePolicy};
+pub use event_writer::QueueFullPolicy;
+pub use replay::{ReplayOutput, ReplaySummary};
+pub use draws::Draws;
+pub use replication::{LocalReplica, ReplicaLink, ReplicationRecord};
+pub use synthetic::{SyntheticLegs, SyntheticPair, SyntheticQuote};
+pub use matching::{Fill, MatchPolicy, MatchingAlgorithm};
+pub use queries::OrderFilter;
+pub use amend::{Amendment, PriorityPolicy, StandardPriorityPolicy};
+pub use trade_log::{Page, Pagination};
+pub use trade_store::{FileTradeStore, InMemoryTradeStore, TradeRecord, TradeStore};
+pub use auction::AuctionResult;
+pub use depth::{DepthLevel, L2Snapshot, L3Order, L3Snapshot};
+pub use drop_copy::{DropCopyRecord, DropCopyView, Participant};
+pub use surveillance::{AlertKind, SurveillanceAlert, SurveillanceConfig, SurveillanceView};
+pub use depth_diff::{DepthDiff, VersionedDepth};
+pub use trading_state::{HaltedCommandPolicy, SymbolState};
+pub use circuit_breaker::CircuitBreakerConfig;
+pub use limits::UserLimits;
+pub use market_data::{RollingStats, Ticker};
+pub use candles::Candle;
+pub use middleware::CommandMiddleware;
+pub use config::{Clock, EngineConfig, IdGenerator, MatchingEngineBuilder, RandomIds, SelfTradePolicy, SystemClock};
+pub use anonymize::Anonymizer;
+pub use metrics::{spawn_metrics_recorder, BookSize, MetricsSnapshot, MetricsStore};
+pub use symbols::{SymbolConfig, SymbolRegistry};
+pub use book_limits::{BookLimitPolicy, BookLimits};
+pub use sides::{Asks, Bids};
+pub use footprint::MemoryFootprint;
+pub use top_of_book::TopOfBook;
+pub use wal::{WalRecord, WriteAheadLog};
+#[cfg(feature = "wasm")]
+pub use wasm::WasmEngine;
+pub use simulator::{FlowConfig, FlowMix, OrderFlow};
+pub use snapshot::{EngineSnapshot, FileSnapshotStore, InMemorySnapshotStore, SnapshotCadence, SnapshotStore};
+pub use book::{BookFactory, BookSnapshot, BookStats, FillEstimate, OrderBookOps, SeededSkipListBooks, SkipListBooks, SymbolBook};
+pub use book_delta::BookDelta;
+pub use itch::{ItchDecoder, ItchEncoder, ItchMessage};
+#[cfg(feature = "btree-book")]
+pub use btree_book::{BTreeBooks, BTreeOrderBook};
+pub use ladder_book::{LadderOrderBook, PriceLadder};
+pub use orderbook::{LevelIter, OrderBookSide, OrderIter, SkipListOrderBook};
+pub use price_level::PriceLevel; 
\ No newline at end of file
@@ -0,0 +1,173 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::engine::MatchingEngine;
+use crate::events::OrderEvent;
+use crate::symbols::SymbolConfig;
+use crate::trading_state::SymbolState;
+
+/// Trading hours of a symbol, in UTC. Outside them the symbol is `Closed`.
+/// A session with `close` before `open` runs overnight and belongs to the
+/// day it opens.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TradingSchedule {
+    pub open: NaiveTime,
+    pub close: NaiveTime,
+    /// Start of a pre-open auction, run at `open`, during which orders rest
+    /// without matching.
+    #[serde(default)]
+    pub pre_open: Option<NaiveTime>,
+    /// Days sessions open on; None opens every day.
+    #[serde(default)]
+    pub trading_days: Option<Vec<Weekday>>,
+    /// Days no session opens on.
+    #[serde(default)]
+    pub holidays: Vec<NaiveDate>,
+}
+
+impl TradingSchedule {
+    /// A session every day from `open` to `close`, without a pre-open.
+    pub fn daily(open: NaiveTime, close: NaiveTime) -> Self {
+        Self {
+            open,
+            close,
+            pre_open: None,
+            trading_days: None,
+            holidays: Vec::new(),
+        }
+    }
+
+    /// Checks the session has a length, some day to open on and a pre-open
+    /// that ends where it starts.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.open == self.close {
+            return Err(format!("Trading session opens and closes at {}", self.open));
+        }
+        if self.trading_days.as_ref().is_some_and(Vec::is_empty) {
+            return Err("Trading schedule has no trading days".to_string());
+        }
+        if let Some(pre_open) = self.pre_open {
+            if pre_open == self.open || days_since_opened(self.open, self.close, pre_open).is_some() {
+                return Err(format!("Pre-open at {} falls within the session from {}", pre_open, self.open));
+            }
+        }
+        Ok(())
+    }
+
+    fn opens_on(&self, date: NaiveDate) -> bool {
+        self.trading_days.as_ref().is_none_or(|days| days.contains(&date.weekday())) && !self.holidays.contains(&date)
+    }
+
+    fn state_at(&self, now: DateTime<Utc>) -> SymbolState {
+        let (date, time) = (now.date_naive(), now.time());
+        if let Some(opened) = days_since_opened(self.open, self.close, time) {
+            if self.opens_on(date - Duration::days(opened)) {
+                return SymbolState::Trading;
+            }
+        }
+        if let Some(pre_open) = self.pre_open {
+            // A pre-open running past midnight leads into the next day's session
+            if let Some(opened) = days_since_opened(pre_open, self.open, time) {
+                let opening = date - Duration::days(opened) + Duration::days(i64::from(pre_open > self.open));
+                if self.opens_on(opening) {
+                    return SymbolState::AuctionOnly;
+                }
+            }
+        }
+        SymbolState::Closed
+    }
+}
+
+/// Whether `time` falls within the daily window from `start` to `end`,
+/// which runs past midnight when `end` comes first, as the number of days
+/// ago the window opened.
+fn days_since_opened(start: NaiveTime, end: NaiveTime, time: NaiveTime) -> Option<i64> {
+    if start <= end {
+        (start <= time && time < end).then_some(0)
+    } else if start <= time {
+        Some(0)
+    } else {
+        (time < end).then_some(1)
+    }
+}
+
+/// The state `config` schedules its symbol to be in at `now`, if it has
+/// a listing time or trading hours.
+fn scheduled_state(config: &SymbolConfig, now: DateTime<Utc>) -> Option<SymbolState> {
+    if config.listed_at.is_some_and(|listed_at| now < listed_at) {
+        return Some(SymbolState::Closed);
+    }
+    Some(match &config.schedule {
+        None if config.listed_at.is_none() => return None,
+        None => SymbolState::Trading,
+        Some(schedule) => schedule.state_at(now),
+    })
+}
+
+impl MatchingEngine {
+    /// Moves every registered symbol into the state its listing time and
+    /// trading hours call for now. Symbols are also moved when a command
+    /// for them arrives; this is for a timer to call so the transitions
+    /// happen on time.
+    pub async fn apply_trading_schedules(&self) -> Result<Vec<OrderEvent>, String> {
+        let mut events = Vec::new();
+        for symbol in self.symbols.symbols() {
+            events.extend(self.apply_trading_schedule(&symbol).await?);
+        }
+        Ok(events)
+    }
+
+    /// Moves `symbol` into its scheduled state once per change of schedule,
+    /// so a halt within trading hours stands until the next boundary.
+    /// Opening runs the pre-open auction, or replays the commands queued
+    /// while the symbol was closed.
+    pub(crate) async fn apply_trading_schedule(&self, symbol: &str) -> Result<Vec<OrderEvent>, String> {
+        let Some(target) = self
+            .symbols
+            .get(symbol)
+            .and_then(|config| scheduled_state(&config, self.clock.now()))
+        else {
+            return Ok(Vec::new());
+        };
+        if self.symbol_state(symbol) == SymbolState::Delisted
+            || self.scheduled_states.insert(symbol.to_string(), target) == Some(target)
+        {
+            return Ok(Vec::new());
+        }
+        // Boxed because opening replays queued commands through the
+        // handlers that called us
+        match target {
+            SymbolState::Trading if self.is_in_auction(symbol) => Box::pin(self.run_auction(symbol)).await,
+            SymbolState::Trading => Box::pin(self.resume(symbol)).await,
+            SymbolState::AuctionOnly => {
+                let mut events = self.start_auction(symbol).await?;
+                events.extend(Box::pin(self.replay_queued_commands(symbol)).await);
+                Ok(events)
+            }
+            state => self.set_symbol_state(symbol, state).await,
+        }
+    }
+
+    /// Takes `symbol` off the engine for good: its open orders are
+    /// canceled, commands queued for it are dropped and every later command
+    /// for it is rejected. Waits for commands in flight to finish first.
+    pub async fn delist_symbol(&self, symbol: &str) -> Result<Vec<OrderEvent>, String> {
+        let _gate = self.command_gate.write().await;
+        if self.symbol_state(symbol) == SymbolState::Delisted {
+            return Err(format!("{} is already delisted", symbol));
+        }
+        self.queued_commands.remove(symbol);
+        let mut events = Vec::new();
+        for order in self.get_open_orders_by_symbol(symbol) {
+            self.cancel_order(order.id, &mut events);
+        }
+        self.transition_symbol_state(symbol, SymbolState::Delisted, &mut events);
+        let symbols = [symbol.to_string()];
+        self.refresh_book_views(&symbols);
+        self.refresh_top_of_book(&symbols);
+        self.publish_market_feeds(&symbols);
+
+        self.persist_events(&mut events).await?;
+        Ok(events)
+    }
+}
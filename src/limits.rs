@@ -0,0 +1,129 @@
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::commands::OrderCommand;
+use crate::draws::new_event_id;
+use crate::engine::MatchingEngine;
+use crate::error::{EngineError, RejectReason};
+use crate::events::{OrderEvent, RateLimitExceededEvent};
+
+/// Per-user limits. Unset fields fall back to the engine-wide settings in
+/// `EngineConfig`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct UserLimits {
+    pub max_open_orders: Option<usize>,
+    /// Sustained order entry rate; up to one second's worth may arrive in a
+    /// burst.
+    pub max_orders_per_second: Option<u32>,
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    refilled_at: DateTime<Utc>,
+}
+
+impl TokenBucket {
+    fn try_take(&mut self, count: usize, rate: u32, now: DateTime<Utc>) -> bool {
+        let elapsed = (now - self.refilled_at).num_microseconds().unwrap_or(i64::MAX).max(0) as f64 / 1e6;
+        self.tokens = (self.tokens + elapsed * rate as f64).min(rate as f64);
+        self.refilled_at = now;
+        if self.tokens < count as f64 {
+            return false;
+        }
+        self.tokens -= count as f64;
+        true
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct UserLimitState {
+    overrides: DashMap<Uuid, UserLimits>,
+    buckets: DashMap<Uuid, TokenBucket>,
+}
+
+impl MatchingEngine {
+    pub fn set_user_limits(&self, user_id: Uuid, limits: UserLimits) {
+        self.user_limits.overrides.insert(user_id, limits);
+        self.user_limits.buckets.remove(&user_id);
+    }
+
+    /// The limits that apply to `user_id`, overrides merged over the
+    /// engine-wide settings.
+    pub fn user_limits(&self, user_id: Uuid) -> UserLimits {
+        let overrides = self
+            .user_limits
+            .overrides
+            .get(&user_id)
+            .map(|l| *l)
+            .unwrap_or_default();
+        UserLimits {
+            max_open_orders: overrides.max_open_orders.or(self.config.max_open_orders_per_user),
+            max_orders_per_second: overrides
+                .max_orders_per_second
+                .or(self.config.max_orders_per_second),
+        }
+    }
+
+    /// Takes one token per order the command enters from each user's
+    /// bucket. A command is refused as a whole, recording RateLimitExceeded,
+    /// if any of its users is out of tokens.
+    pub(crate) async fn check_rate_limits(&self, command: &OrderCommand) -> Result<(), EngineError> {
+        let mut entries: Vec<(Uuid, &str, usize)> = Vec::new();
+        for (user_id, symbol) in order_entries(command) {
+            match entries.iter_mut().find(|(user, _, _)| *user == user_id) {
+                Some((_, _, count)) => *count += 1,
+                None => entries.push((user_id, symbol, 1)),
+            }
+        }
+
+        let now = self.clock.now();
+        for (user_id, symbol, count) in entries {
+            let Some(rate) = self.user_limits(user_id).max_orders_per_second else {
+                continue;
+            };
+            let mut bucket = self.user_limits.buckets.entry(user_id).or_insert(TokenBucket {
+                tokens: rate as f64,
+                refilled_at: now,
+            });
+            if bucket.try_take(count, rate, now) {
+                continue;
+            }
+            drop(bucket);
+
+            let mut events = vec![OrderEvent::RateLimitExceeded(RateLimitExceededEvent {
+                event_id: new_event_id(),
+                prev_hash: None,
+                user_id,
+                symbol: symbol.to_string(),
+                max_orders_per_second: rate,
+                timestamp: now,
+            })];
+            // The caller gets the rejection whether or not it could be recorded
+            let _ = self.persist_events(&mut events).await;
+            return Err(RejectReason::RateLimitExceeded {
+                max_orders_per_second: rate,
+            }
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// (user, symbol) of every order a command enters. Cancels enter none.
+pub(crate) fn order_entries(command: &OrderCommand) -> Vec<(Uuid, &str)> {
+    match command {
+        OrderCommand::PlaceOrder(cmd) => vec![(cmd.user_id, cmd.symbol.as_str())],
+        OrderCommand::CancelOrder(_) => Vec::new(),
+        OrderCommand::PlaceBracketOrder(cmd) => vec![(cmd.entry.user_id, cmd.entry.symbol.as_str())],
+        OrderCommand::CancelReplace(cmd) => vec![(cmd.user_id, cmd.symbol.as_str())],
+        OrderCommand::AmendOrder(cmd) => vec![(cmd.user_id, cmd.symbol.as_str())],
+        OrderCommand::PlaceBasket(cmd) => cmd
+            .legs
+            .iter()
+            .map(|leg| (leg.user_id, leg.symbol.as_str()))
+            .collect(),
+    }
+}
@@ -0,0 +1,324 @@
+use dashmap::DashMap;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::commands::{CancelOrderCommand, OrderCommand, PlaceOrderCommand};
+use crate::draws::new_event_id;
+use crate::engine::MatchingEngine;
+use crate::error::RejectReason;
+use crate::events::{LiquidationTriggeredEvent, OrderEvent};
+use crate::types::{OrderSide, OrderType, Trade};
+
+/// How far users may lever positions in a margined symbol.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LeverageLimit {
+    /// Notional a user may hold, open orders included, per unit of equity.
+    pub max_leverage: Decimal,
+    /// Equity under this percent of a position's notional triggers
+    /// liquidation.
+    pub maintenance_margin_percent: Decimal,
+}
+
+/// A user's margin account valued at mark prices.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct MarginSummary {
+    pub collateral: Decimal,
+    /// Collateral plus the profit or loss on every margined position.
+    pub equity: Decimal,
+    /// Equity needed to hold the positions and open orders at the symbols'
+    /// leverage limits.
+    pub initial_margin: Decimal,
+    /// Equity under which the positions are liquidated.
+    pub maintenance_margin: Decimal,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct MarginState {
+    collateral: DashMap<Uuid, Decimal>,
+    limits: DashMap<String, LeverageLimit>,
+    mark_prices: DashMap<String, Decimal>,
+    /// Quote received less quote paid, per user and symbol.
+    cash: DashMap<(Uuid, String), Decimal>,
+}
+
+impl MatchingEngine {
+    /// Trades symbols with a leverage limit on margin: users post
+    /// collateral, orders that add to a position are rejected with
+    /// `InsufficientMargin` once equity would not cover them at the limit,
+    /// and a user whose equity falls below the maintenance margin is
+    /// liquidated. Liquidation is checked after each command for the
+    /// symbols it touched and whenever a mark price is set.
+    pub fn with_margin(mut self) -> Self {
+        self.margin = Some(MarginState::default());
+        self
+    }
+
+    fn margin(&self) -> Result<&MarginState, String> {
+        self.margin.as_ref().ok_or_else(|| "Margin is not enabled".to_string())
+    }
+
+    pub fn set_leverage_limit(&self, symbol: &str, limit: LeverageLimit) -> Result<(), String> {
+        if limit.max_leverage <= Decimal::ZERO {
+            return Err(format!("Leverage must be positive, got {}", limit.max_leverage));
+        }
+        self.margin()?.limits.insert(symbol.to_string(), limit);
+        Ok(())
+    }
+
+    pub fn deposit_collateral(&self, user_id: Uuid, amount: Decimal) -> Result<Decimal, String> {
+        if amount <= Decimal::ZERO {
+            return Err(format!("Deposit amount must be positive, got {}", amount));
+        }
+        let mut collateral = self.margin()?.collateral.entry(user_id).or_default();
+        *collateral += amount;
+        Ok(*collateral)
+    }
+
+    /// Withdraws collateral the user's positions and open orders do not
+    /// need.
+    pub fn withdraw_collateral(&self, user_id: Uuid, amount: Decimal) -> Result<Decimal, String> {
+        if amount <= Decimal::ZERO {
+            return Err(format!("Withdrawal amount must be positive, got {}", amount));
+        }
+        let margin = self.margin()?;
+        let summary = self.margin_totals(margin, user_id, None);
+        let free = (summary.equity - summary.initial_margin).min(summary.collateral);
+        if amount > free {
+            return Err(format!("Withdrawal of {} exceeds the {} of collateral free", amount, free));
+        }
+        let mut collateral = margin.collateral.entry(user_id).or_default();
+        *collateral -= amount;
+        Ok(*collateral)
+    }
+
+    pub fn margin_summary(&self, user_id: Uuid) -> Result<MarginSummary, String> {
+        Ok(self.margin_totals(self.margin()?, user_id, None))
+    }
+
+    /// What margined positions are valued at: the price last set for the
+    /// symbol, otherwise its reference price.
+    pub fn mark_price(&self, symbol: &str) -> Option<Decimal> {
+        self.margin
+            .as_ref()
+            .and_then(|margin| margin.mark_prices.get(symbol).map(|p| *p))
+            .or_else(|| self.reference_price(symbol))
+    }
+
+    /// Sets the price `symbol` is marked at and liquidates the users it
+    /// leaves under their maintenance margin, returning the events of the
+    /// liquidations.
+    pub async fn set_mark_price(&self, symbol: &str, price: Decimal) -> Result<Vec<OrderEvent>, String> {
+        self.check_following().map_err(|e| e.to_string())?;
+        self.margin()?.mark_prices.insert(symbol.to_string(), price);
+        Ok(self.liquidate_breaches(&[symbol.to_string()]).await)
+    }
+
+    /// The largest the user's position in `symbol` could get, long or
+    /// short, with every open order on one side filled, counting `order`
+    /// and leaving out `excluding`.
+    fn worst_case_position(
+        &self,
+        user_id: Uuid,
+        symbol: &str,
+        order: Option<(OrderSide, Decimal)>,
+        excluding: Option<Uuid>,
+    ) -> Decimal {
+        let position = self.get_position(user_id, symbol).quantity();
+        let open: Vec<(OrderSide, Decimal)> = self
+            .get_open_orders(user_id)
+            .iter()
+            .filter(|o| o.symbol == symbol && Some(o.id) != excluding && !self.is_bracket_exit(o.id))
+            .map(|o| (o.side, o.remaining_quantity()))
+            .collect();
+        let (mut buys, mut sells) = (Decimal::ZERO, Decimal::ZERO);
+        for (side, quantity) in open.into_iter().chain(order) {
+            match side {
+                OrderSide::Buy => buys += quantity,
+                OrderSide::Sell => sells += quantity,
+            }
+        }
+        (position + buys).abs().max((position - sells).abs())
+    }
+
+    /// Values the user's margined symbols at mark. Symbols without a mark
+    /// price are left out, and so is the initial margin of `skip`.
+    fn margin_totals(&self, margin: &MarginState, user_id: Uuid, skip: Option<&str>) -> MarginSummary {
+        let collateral = margin.collateral.get(&user_id).map_or(Decimal::ZERO, |c| *c);
+        let mut summary = MarginSummary {
+            collateral,
+            equity: collateral,
+            ..Default::default()
+        };
+        let limits: Vec<(String, LeverageLimit)> =
+            margin.limits.iter().map(|entry| (entry.key().clone(), *entry.value())).collect();
+        for (symbol, limit) in limits {
+            let Some(mark) = self.mark_price(&symbol) else {
+                continue;
+            };
+            let position = self.get_position(user_id, &symbol).quantity();
+            let cash = margin.cash.get(&(user_id, symbol.clone())).map_or(Decimal::ZERO, |c| *c);
+            summary.equity += cash + position * mark;
+            summary.maintenance_margin +=
+                position.abs() * mark * limit.maintenance_margin_percent / Decimal::ONE_HUNDRED;
+            if skip != Some(symbol.as_str()) {
+                summary.initial_margin +=
+                    self.worst_case_position(user_id, &symbol, None, None) * mark / limit.max_leverage;
+            }
+        }
+        summary
+    }
+
+    /// Rejects `quantity` more on `side` if it adds to what the user could
+    /// hold of a margined symbol beyond what their equity covers.
+    /// `excluding` is an open order the command takes the place of. Orders
+    /// that only reduce a position always pass.
+    pub(crate) fn check_margin(
+        &self,
+        user_id: Uuid,
+        symbol: &str,
+        side: OrderSide,
+        quantity: Decimal,
+        price: Option<Decimal>,
+        excluding: Option<Uuid>,
+    ) -> Result<(), RejectReason> {
+        let Some(margin) = &self.margin else {
+            return Ok(());
+        };
+        let Some(limit) = margin.limits.get(symbol).map(|l| *l) else {
+            return Ok(());
+        };
+        let before = self.worst_case_position(user_id, symbol, None, None);
+        let after = self.worst_case_position(user_id, symbol, Some((side, quantity)), excluding);
+        if after <= before {
+            return Ok(());
+        }
+        let Some(mark) = self.mark_price(symbol).or(price) else {
+            return Ok(());
+        };
+        let summary = self.margin_totals(margin, user_id, Some(symbol));
+        let required = summary.initial_margin + after * mark / limit.max_leverage;
+        if required > summary.equity {
+            return Err(RejectReason::InsufficientMargin {
+                required,
+                equity: summary.equity,
+            });
+        }
+        Ok(())
+    }
+
+    /// Books the quote each side paid or received for the trade, or with
+    /// `undo` takes it back out.
+    pub(crate) fn record_margin_trade(
+        &self,
+        trade: &Trade,
+        taker_user_id: Uuid,
+        maker_user_id: Option<Uuid>,
+        undo: bool,
+    ) {
+        let Some(margin) = &self.margin else {
+            return;
+        };
+        let notional = if undo { -trade.price * trade.quantity } else { trade.price * trade.quantity };
+        let apply = |user_id: Uuid, side: OrderSide| {
+            let mut cash = margin.cash.entry((user_id, trade.symbol.clone())).or_default();
+            match side {
+                OrderSide::Buy => *cash -= notional,
+                OrderSide::Sell => *cash += notional,
+            }
+        };
+        apply(taker_user_id, trade.side);
+        if let Some(maker_user_id) = maker_user_id {
+            apply(maker_user_id, trade.side.opposite());
+        }
+    }
+
+    /// Liquidates every user with a position in one of `symbols` whose
+    /// equity is under their maintenance margin.
+    pub(crate) async fn liquidate_breaches(&self, symbols: &[String]) -> Vec<OrderEvent> {
+        let Some(margin) = self.margin.as_ref().filter(|_| !self.is_replaying()) else {
+            return Vec::new();
+        };
+        let mut users: Vec<Uuid> = Vec::new();
+        for entry in margin.cash.iter() {
+            let (user_id, symbol) = entry.key();
+            if symbols.contains(symbol) && margin.limits.contains_key(symbol) && !users.contains(user_id) {
+                users.push(*user_id);
+            }
+        }
+        let mut events = Vec::new();
+        for user_id in users {
+            let summary = self.margin_totals(margin, user_id, None);
+            if !summary.maintenance_margin.is_zero() && summary.equity < summary.maintenance_margin {
+                events.extend(self.liquidate(margin, user_id, summary).await);
+            }
+        }
+        events
+    }
+
+    /// Cancels the user's open orders on margined symbols and closes their
+    /// positions at market, each through the command log like a command
+    /// of the user's own.
+    async fn liquidate(&self, margin: &MarginState, user_id: Uuid, summary: MarginSummary) -> Vec<OrderEvent> {
+        let margined: Vec<String> = margin.limits.iter().map(|entry| entry.key().clone()).collect();
+        let positions: Vec<(String, Decimal)> = margined
+            .iter()
+            .map(|symbol| (symbol.clone(), self.get_position(user_id, symbol).quantity()))
+            .filter(|(_, quantity)| !quantity.is_zero())
+            .collect();
+        let now = self.clock.now();
+        let mut events = vec![OrderEvent::LiquidationTriggered(LiquidationTriggeredEvent {
+            event_id: new_event_id(),
+            prev_hash: None,
+            user_id,
+            equity: summary.equity,
+            maintenance_margin: summary.maintenance_margin,
+            symbols: positions.iter().map(|(symbol, _)| symbol.clone()).collect(),
+            timestamp: now,
+        })];
+        if self.persist_events(&mut events).await.is_err() {
+            return Vec::new();
+        }
+
+        let mut commands: Vec<OrderCommand> = self
+            .get_open_orders(user_id)
+            .into_iter()
+            .filter(|o| margined.contains(&o.symbol))
+            .map(|o| {
+                OrderCommand::CancelOrder(CancelOrderCommand {
+                    order_id: o.id,
+                    client_order_id: None,
+                    user_id,
+                    symbol: o.symbol,
+                    timestamp: now,
+                })
+            })
+            .collect();
+        for (symbol, quantity) in positions {
+            commands.push(OrderCommand::PlaceOrder(PlaceOrderCommand {
+                order_id: self.ids.next_id(),
+                client_order_id: None,
+                user_id,
+                symbol,
+                order_type: OrderType::Market,
+                side: if quantity > Decimal::ZERO { OrderSide::Sell } else { OrderSide::Buy },
+                price: None,
+                quantity: quantity.abs(),
+                iceberg_visible_quantity: None,
+                stop_price: None,
+                trailing_stop_price: None,
+                displayed: true,
+                max_fills: None,
+                referrer_id: None,
+                quote_quantity: None,
+                timestamp: now,
+            }));
+        }
+        for command in commands {
+            if let Ok(command_events) = self.submit_forced(command).await {
+                events.extend(command_events);
+            }
+        }
+        events
+    }
+}
@@ -0,0 +1,249 @@
+use std::collections::VecDeque;
+use std::time::Duration as WindowSize;
+
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::engine::MatchingEngine;
+use crate::types::Trade;
+
+const DEFAULT_TAPE_CAPACITY: usize = 1000;
+const DEFAULT_STATS_WINDOWS: [WindowSize; 3] = [
+    WindowSize::from_secs(60),
+    WindowSize::from_secs(300),
+    WindowSize::from_secs(3600),
+];
+
+/// A symbol's last trade price, best prices and trading over the last 24
+/// hours.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Ticker {
+    pub symbol: String,
+    pub last_price: Option<Decimal>,
+    pub best_bid: Option<Decimal>,
+    pub best_ask: Option<Decimal>,
+    /// None if nothing traded in the last 24 hours.
+    pub high_24h: Option<Decimal>,
+    pub low_24h: Option<Decimal>,
+    pub volume_24h: Decimal,
+    pub trade_count_24h: usize,
+}
+
+/// Trading on a symbol over a trailing window.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RollingStats {
+    pub symbol: String,
+    pub window: WindowSize,
+    /// None while the window has no trades.
+    pub vwap: Option<Decimal>,
+    /// Trade prices weighted by how long each stood, from the window's
+    /// first trade until now.
+    pub twap: Option<Decimal>,
+    pub trade_count: usize,
+    pub volume: Decimal,
+}
+
+/// Running sums over the trades of one window.
+#[derive(Default)]
+struct RollingWindow {
+    trades: VecDeque<Trade>,
+    volume: Decimal,
+    notional: Decimal,
+    /// Sum of each trade's price times the milliseconds until the next.
+    weighted_time: Decimal,
+}
+
+fn millis_between(from: DateTime<Utc>, to: DateTime<Utc>) -> Decimal {
+    Decimal::from((to - from).num_milliseconds().max(0))
+}
+
+impl RollingWindow {
+    fn push(&mut self, trade: &Trade) {
+        if let Some(last) = self.trades.back() {
+            self.weighted_time += last.price * millis_between(last.created_at, trade.created_at);
+        }
+        self.volume += trade.quantity;
+        self.notional += trade.price * trade.quantity;
+        self.trades.push_back(trade.clone());
+    }
+
+    fn prune(&mut self, cutoff: DateTime<Utc>) {
+        while self.trades.front().is_some_and(|trade| trade.created_at < cutoff) {
+            let Some(oldest) = self.trades.pop_front() else {
+                break;
+            };
+            if let Some(next) = self.trades.front() {
+                self.weighted_time -= oldest.price * millis_between(oldest.created_at, next.created_at);
+            }
+            self.volume -= oldest.quantity;
+            self.notional -= oldest.price * oldest.quantity;
+        }
+    }
+
+    fn rebuild(&mut self) {
+        let trades = std::mem::take(&mut self.trades);
+        *self = Self::default();
+        for trade in &trades {
+            self.push(trade);
+        }
+    }
+
+    fn stats(&self, symbol: &str, window: WindowSize, now: DateTime<Utc>) -> RollingStats {
+        let twap = match (self.trades.front(), self.trades.back()) {
+            (Some(first), Some(last)) => {
+                let span = millis_between(first.created_at, now);
+                if span.is_zero() {
+                    Some(last.price)
+                } else {
+                    Some((self.weighted_time + last.price * millis_between(last.created_at, now)) / span)
+                }
+            }
+            _ => None,
+        };
+        RollingStats {
+            symbol: symbol.to_string(),
+            window,
+            vwap: (!self.volume.is_zero()).then(|| self.notional / self.volume),
+            twap,
+            trade_count: self.trades.len(),
+            volume: self.volume,
+        }
+    }
+}
+
+#[derive(Default)]
+struct SymbolMarketData {
+    /// The latest trades, oldest first.
+    tape: VecDeque<Trade>,
+    /// Trades of the last 24 hours, oldest first. Pruned as trades arrive
+    /// and when read.
+    window: VecDeque<Trade>,
+    /// Indexed like `MarketData::stats_windows`.
+    stats: Vec<RollingWindow>,
+}
+
+impl SymbolMarketData {
+    fn prune(&mut self, now: DateTime<Utc>) {
+        let cutoff = now - Duration::hours(24);
+        while self.window.front().is_some_and(|trade| trade.created_at < cutoff) {
+            self.window.pop_front();
+        }
+    }
+}
+
+pub(crate) struct MarketData {
+    tape_capacity: usize,
+    stats_windows: Vec<WindowSize>,
+    symbols: DashMap<String, SymbolMarketData>,
+}
+
+impl Default for MarketData {
+    fn default() -> Self {
+        Self {
+            tape_capacity: DEFAULT_TAPE_CAPACITY,
+            stats_windows: DEFAULT_STATS_WINDOWS.to_vec(),
+            symbols: DashMap::new(),
+        }
+    }
+}
+
+fn window_start(now: DateTime<Utc>, window: WindowSize) -> DateTime<Utc> {
+    Duration::from_std(window)
+        .ok()
+        .and_then(|window| now.checked_sub_signed(window))
+        .unwrap_or(DateTime::<Utc>::MIN_UTC)
+}
+
+impl MarketData {
+    pub(crate) fn record(&self, trade: &Trade) {
+        let mut data = self.symbols.entry(trade.symbol.clone()).or_default();
+        data.tape.push_back(trade.clone());
+        if data.tape.len() > self.tape_capacity {
+            data.tape.pop_front();
+        }
+        data.window.push_back(trade.clone());
+        data.prune(trade.created_at);
+        data.stats.resize_with(self.stats_windows.len(), RollingWindow::default);
+        for (rolling, window) in data.stats.iter_mut().zip(&self.stats_windows) {
+            rolling.push(trade);
+            rolling.prune(window_start(trade.created_at, *window));
+        }
+    }
+
+    /// Takes rolled back trades out of the tape and the windows.
+    pub(crate) fn forget(&self, symbol: &str, trade_ids: &[Uuid]) {
+        if let Some(mut data) = self.symbols.get_mut(symbol) {
+            data.tape.retain(|trade| !trade_ids.contains(&trade.id));
+            data.window.retain(|trade| !trade_ids.contains(&trade.id));
+            for rolling in &mut data.stats {
+                rolling.trades.retain(|trade| !trade_ids.contains(&trade.id));
+                rolling.rebuild();
+            }
+        }
+    }
+}
+
+impl MatchingEngine {
+    /// Keeps the latest `capacity` trades of each symbol for
+    /// `get_recent_trades`. Defaults to 1000.
+    pub fn with_trade_tape_capacity(mut self, capacity: usize) -> Self {
+        self.market_data.tape_capacity = capacity.max(1);
+        self
+    }
+
+    /// Keeps rolling statistics over these windows instead of 1m, 5m and
+    /// 1h.
+    pub fn with_stats_windows(mut self, windows: &[WindowSize]) -> Self {
+        self.market_data.stats_windows = windows.to_vec();
+        self
+    }
+
+    /// Statistics of `symbol` over the trailing `window`, which must be one
+    /// of those configured.
+    pub fn get_stats(&self, symbol: &str, window: WindowSize) -> Option<RollingStats> {
+        let i = self.market_data.stats_windows.iter().position(|configured| *configured == window)?;
+        let now = self.clock.now();
+        let Some(mut data) = self.market_data.symbols.get_mut(symbol) else {
+            return Some(RollingWindow::default().stats(symbol, window, now));
+        };
+        let rolling = data.stats.get_mut(i)?;
+        rolling.prune(window_start(now, window));
+        Some(rolling.stats(symbol, window, now))
+    }
+
+    pub fn get_ticker(&self, symbol: &str) -> Ticker {
+        let top = self.get_top_of_book(symbol);
+        let mut ticker = Ticker {
+            symbol: symbol.to_string(),
+            last_price: self.last_prices.get(symbol).map(|price| *price),
+            best_bid: top.best_bid,
+            best_ask: top.best_ask,
+            high_24h: None,
+            low_24h: None,
+            volume_24h: Decimal::ZERO,
+            trade_count_24h: 0,
+        };
+        if let Some(mut data) = self.market_data.symbols.get_mut(symbol) {
+            data.prune(self.clock.now());
+            for trade in &data.window {
+                ticker.high_24h = Some(ticker.high_24h.map_or(trade.price, |high| high.max(trade.price)));
+                ticker.low_24h = Some(ticker.low_24h.map_or(trade.price, |low| low.min(trade.price)));
+                ticker.volume_24h += trade.quantity;
+            }
+            ticker.trade_count_24h = data.window.len();
+        }
+        ticker
+    }
+
+    /// Up to `limit` of the latest trades on `symbol`, newest first.
+    pub fn get_recent_trades(&self, symbol: &str, limit: usize) -> Vec<Trade> {
+        self.market_data
+            .symbols
+            .get(symbol)
+            .map(|data| data.tape.iter().rev().take(limit).cloned().collect())
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,201 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::config::SelfTradePolicy;
+use crate::price_level::PriceLevel;
+use crate::types::{Order, OrderSide};
+
+/// How an incoming order's quantity is shared among the makers resting at
+/// one price level.
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum MatchingAlgorithm {
+    /// Oldest order first.
+    #[default]
+    PriceTimeFifo,
+    /// Proportional to resting quantity, rounding remainders to the oldest
+    /// orders.
+    ProRata,
+    /// The oldest order is filled first, the rest is shared pro-rata.
+    ProRataWithTopOrder,
+}
+
+impl MatchingAlgorithm {
+    /// Splits `incoming` across makers with the given remaining quantities,
+    /// listed in time priority. The allocations never exceed a maker's
+    /// remaining quantity and sum to `incoming` capped at the level total.
+    pub fn allocate(&self, incoming: Decimal, makers: &[Decimal]) -> Vec<Decimal> {
+        match self {
+            MatchingAlgorithm::PriceTimeFifo => allocate_fifo(incoming, makers),
+            MatchingAlgorithm::ProRata => allocate_pro_rata(incoming, makers),
+            MatchingAlgorithm::ProRataWithTopOrder => {
+                let Some((&top, rest)) = makers.split_first() else {
+                    return Vec::new();
+                };
+                let top_fill = incoming.min(top);
+                let mut allocations = vec![top_fill];
+                allocations.extend(allocate_pro_rata(incoming - top_fill, rest));
+                allocations
+            }
+        }
+    }
+
+    /// Decides which makers at a level trade with `incoming` and for how
+    /// much. FIFO only looks at as many makers as it needs; the pro-rata
+    /// variants need the whole level.
+    pub fn level_fills(&self, level: &PriceLevel, incoming: Decimal) -> Vec<(Uuid, Decimal)> {
+        let resting = level
+            .iter()
+            .map(|o| (o.id, o.remaining_quantity()))
+            .filter(|(_, quantity)| *quantity > Decimal::ZERO);
+
+        if *self == MatchingAlgorithm::PriceTimeFifo {
+            let mut remaining = incoming;
+            let mut fills = Vec::new();
+            for (maker_id, available) in resting {
+                if remaining == Decimal::ZERO {
+                    break;
+                }
+                let fill = remaining.min(available);
+                remaining -= fill;
+                fills.push((maker_id, fill));
+            }
+            return fills;
+        }
+
+        let makers: Vec<(Uuid, Decimal)> = resting.collect();
+        let quantities: Vec<Decimal> = makers.iter().map(|(_, q)| *q).collect();
+        makers
+            .iter()
+            .zip(self.allocate(incoming, &quantities))
+            .filter(|(_, fill)| *fill > Decimal::ZERO)
+            .map(|((maker_id, _), fill)| (*maker_id, fill))
+            .collect()
+    }
+}
+
+/// Whether a taker on `side` limited to `limit_price` can trade at
+/// `price`. No limit crosses any price.
+pub(crate) fn crosses(side: OrderSide, limit_price: Option<Decimal>, price: Decimal) -> bool {
+    match (side, limit_price) {
+        (_, None) => true,
+        (OrderSide::Buy, Some(limit)) => limit >= price,
+        (OrderSide::Sell, Some(limit)) => limit <= price,
+    }
+}
+
+/// How `OrderBookSide::match_incoming` trades a taker against the book.
+#[derive(Debug, Clone, Copy)]
+pub struct MatchPolicy {
+    pub algorithm: MatchingAlgorithm,
+    /// The worst price the taker trades at; None sweeps the book.
+    pub limit_price: Option<Decimal>,
+    pub max_fills: Option<usize>,
+    /// The most the taker may spend, as price times quantity; None is no
+    /// limit.
+    pub budget: Option<Decimal>,
+    pub self_trade: SelfTradePolicy,
+    /// Stamped on every order matching touches.
+    pub now: DateTime<Utc>,
+}
+
+/// A resting order matching touched, as it stands afterwards.
+#[derive(Debug, Clone)]
+pub struct Fill {
+    pub maker: Order,
+    pub price: Decimal,
+    /// Zero for an order taken out without trading: canceled by self-trade
+    /// prevention, or found with nothing left.
+    pub quantity: Decimal,
+}
+
+fn allocate_fifo(incoming: Decimal, makers: &[Decimal]) -> Vec<Decimal> {
+    let mut remaining = incoming;
+    makers
+        .iter()
+        .map(|&available| {
+            let fill = remaining.min(available).max(Decimal::ZERO);
+            remaining -= fill;
+            fill
+        })
+        .collect()
+}
+
+fn allocate_pro_rata(incoming: Decimal, makers: &[Decimal]) -> Vec<Decimal> {
+    let total: Decimal = makers.iter().sum();
+    if total <= incoming {
+        return makers.to_vec();
+    }
+    if incoming <= Decimal::ZERO {
+        return vec![Decimal::ZERO; makers.len()];
+    }
+
+    // Shares are truncated to the finest quantity increment in play and
+    // the leftover increments go to the oldest orders
+    let scale = makers
+        .iter()
+        .map(|q| q.scale())
+        .chain([incoming.scale()])
+        .max()
+        .unwrap_or(0);
+    let increment = Decimal::new(1, scale);
+
+    let mut allocations: Vec<Decimal> = makers
+        .iter()
+        .map(|&available| {
+            // Dividing first loses precision, so only when the product overflows
+            let share = incoming
+                .checked_mul(available)
+                .map(|product| product / total)
+                .unwrap_or_else(|| available / total * incoming);
+            share.round_dp_with_strategy(scale, RoundingStrategy::ToZero)
+        })
+        .collect();
+    let mut leftover = incoming - allocations.iter().sum::<Decimal>();
+
+    while leftover > Decimal::ZERO {
+        let before = leftover;
+        for (allocation, &available) in allocations.iter_mut().zip(makers) {
+            if leftover <= Decimal::ZERO {
+                break;
+            }
+            let extra = increment.min(available - *allocation).min(leftover);
+            *allocation += extra;
+            leftover -= extra;
+        }
+        if leftover == before {
+            break;
+        }
+    }
+    allocations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quantities(values: &[i64]) -> Vec<Decimal> {
+        values.iter().map(|&v| Decimal::from(v)).collect()
+    }
+
+    #[test]
+    fn test_fifo_fills_oldest_first() {
+        let allocations = MatchingAlgorithm::PriceTimeFifo.allocate(Decimal::from(5), &quantities(&[3, 4, 2]));
+        assert_eq!(allocations, quantities(&[3, 2, 0]));
+    }
+
+    #[test]
+    fn test_pro_rata_is_proportional_with_remainder_to_oldest() {
+        let allocations = MatchingAlgorithm::ProRata.allocate(Decimal::from(10), &quantities(&[10, 20, 10]));
+        assert_eq!(allocations, quantities(&[3, 5, 2]));
+        assert_eq!(allocations.iter().sum::<Decimal>(), Decimal::from(10));
+    }
+
+    #[test]
+    fn test_pro_rata_with_top_order() {
+        let allocations =
+            MatchingAlgorithm::ProRataWithTopOrder.allocate(Decimal::from(8), &quantities(&[4, 4, 12]));
+        assert_eq!(allocations, quantities(&[4, 1, 3]));
+    }
+}
@@ -0,0 +1,252 @@
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+
+use crate::engine::MatchingEngine;
+use crate::instant::Instant;
+use crate::types::OrderSide;
+
+/// Latency samples kept between two snapshots; once full, new samples
+/// overwrite old ones round-robin.
+const MAX_LATENCY_SAMPLES: usize = 100_000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BookSize {
+    pub bid_levels: usize,
+    pub ask_levels: usize,
+    pub resting_orders: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricsSnapshot {
+    pub timestamp: DateTime<Utc>,
+    pub interval_secs: f64,
+    pub commands_per_sec: f64,
+    pub orders_per_sec: f64,
+    pub trades_per_sec: f64,
+    pub latency_p50_us: u64,
+    pub latency_p90_us: u64,
+    pub latency_p99_us: u64,
+    pub latency_max_us: u64,
+    pub book_sizes: BTreeMap<String, BookSize>,
+}
+
+struct Window {
+    started: Instant,
+    commands: u64,
+    orders: u64,
+    trades: u64,
+}
+
+pub(crate) struct EngineMetrics {
+    commands: AtomicU64,
+    orders: AtomicU64,
+    trades: AtomicU64,
+    latencies_us: Mutex<Vec<u64>>,
+    window: Mutex<Window>,
+}
+
+impl Default for EngineMetrics {
+    fn default() -> Self {
+        Self {
+            commands: AtomicU64::new(0),
+            orders: AtomicU64::new(0),
+            trades: AtomicU64::new(0),
+            latencies_us: Mutex::new(Vec::new()),
+            window: Mutex::new(Window {
+                started: Instant::now(),
+                commands: 0,
+                orders: 0,
+                trades: 0,
+            }),
+        }
+    }
+}
+
+impl EngineMetrics {
+    pub(crate) fn record_command(&self, latency: Duration) {
+        let count = self.commands.fetch_add(1, Ordering::Relaxed);
+        let sample = latency.as_micros() as u64;
+        let mut latencies = self.latencies_us.lock().unwrap();
+        if latencies.len() >= MAX_LATENCY_SAMPLES {
+            latencies[count as usize % MAX_LATENCY_SAMPLES] = sample;
+        } else {
+            latencies.push(sample);
+        }
+    }
+
+    pub(crate) fn record_order(&self) {
+        self.orders.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_trades(&self, count: usize) {
+        self.trades.fetch_add(count as u64, Ordering::Relaxed);
+    }
+}
+
+fn percentile(sorted: &[u64], pct: usize) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (sorted.len() * pct).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+impl MatchingEngine {
+    /// Rates and latency percentiles since the previous snapshot, plus the
+    /// current size of every book. Taking a snapshot starts a new window.
+    pub fn metrics_snapshot(&self) -> MetricsSnapshot {
+        let metrics = &self.metrics;
+        let mut latencies = std::mem::take(&mut *metrics.latencies_us.lock().unwrap());
+        latencies.sort_unstable();
+
+        let (commands, orders, trades) = (
+            metrics.commands.load(Ordering::Relaxed),
+            metrics.orders.load(Ordering::Relaxed),
+            metrics.trades.load(Ordering::Relaxed),
+        );
+        let mut window = metrics.window.lock().unwrap();
+        let elapsed = window.started.elapsed().as_secs_f64().max(f64::EPSILON);
+        let rate = |now: u64, before: u64| (now - before) as f64 / elapsed;
+        let (commands_per_sec, orders_per_sec, trades_per_sec) = (
+            rate(commands, window.commands),
+            rate(orders, window.orders),
+            rate(trades, window.trades),
+        );
+        *window = Window {
+            started: Instant::now(),
+            commands,
+            orders,
+            trades,
+        };
+        drop(window);
+
+        let book_sizes = self
+            .order_books
+            .iter()
+            .map(|book| {
+                let (bids, asks) = (book.side(OrderSide::Buy), book.side(OrderSide::Sell));
+                let size = BookSize {
+                    bid_levels: bids.level_count(),
+                    ask_levels: asks.level_count(),
+                    resting_orders: bids.order_count() + asks.order_count(),
+                };
+                (book.key().clone(), size)
+            })
+            .collect();
+
+        MetricsSnapshot {
+            timestamp: Utc::now(),
+            interval_secs: elapsed,
+            commands_per_sec,
+            orders_per_sec,
+            trades_per_sec,
+            latency_p50_us: percentile(&latencies, 50),
+            latency_p90_us: percentile(&latencies, 90),
+            latency_p99_us: percentile(&latencies, 99),
+            latency_max_us: latencies.last().copied().unwrap_or(0),
+            book_sizes,
+        }
+    }
+}
+
+/// Append-only time series of metrics snapshots, one JSON object per line.
+pub struct MetricsStore {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl MetricsStore {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to open metrics file {}: {}", path.display(), e))?;
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn append(&self, snapshot: &MetricsSnapshot) -> Result<(), String> {
+        let mut line = serde_json::to_string(snapshot).map_err(|e| e.to_string())?;
+        line.push('\n');
+        let mut file = self.file.lock().unwrap();
+        file.write_all(line.as_bytes()).map_err(|e| e.to_string())?;
+        file.flush().map_err(|e| e.to_string())
+    }
+
+    /// Snapshots taken within `[from, to]`, oldest first.
+    pub fn query(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<MetricsSnapshot>, String> {
+        let file = File::open(&self.path).map_err(|e| e.to_string())?;
+        let mut snapshots = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|e| e.to_string())?;
+            if line.is_empty() {
+                continue;
+            }
+            let snapshot: MetricsSnapshot = serde_json::from_str(&line).map_err(|e| e.to_string())?;
+            if snapshot.timestamp >= from && snapshot.timestamp <= to {
+                snapshots.push(snapshot);
+            }
+        }
+        Ok(snapshots)
+    }
+
+    /// Writes the snapshots within `[from, to]` as CSV. Book sizes are
+    /// summed across symbols.
+    pub fn export_csv(&self, from: DateTime<Utc>, to: DateTime<Utc>, mut out: impl Write) -> Result<(), String> {
+        writeln!(
+            out,
+            "timestamp,commands_per_sec,orders_per_sec,trades_per_sec,latency_p50_us,latency_p90_us,latency_p99_us,latency_max_us,books,price_levels,resting_orders"
+        )
+        .map_err(|e| e.to_string())?;
+        for s in self.query(from, to)? {
+            let levels: usize = s.book_sizes.values().map(|b| b.bid_levels + b.ask_levels).sum();
+            let resting: usize = s.book_sizes.values().map(|b| b.resting_orders).sum();
+            writeln!(
+                out,
+                "{},{:.3},{:.3},{:.3},{},{},{},{},{},{},{}",
+                s.timestamp.to_rfc3339(),
+                s.commands_per_sec,
+                s.orders_per_sec,
+                s.trades_per_sec,
+                s.latency_p50_us,
+                s.latency_p90_us,
+                s.latency_p99_us,
+                s.latency_max_us,
+                s.book_sizes.len(),
+                levels,
+                resting
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+/// Records a snapshot every `interval` until the returned task is aborted.
+pub fn spawn_metrics_recorder(
+    engine: Arc<MatchingEngine>,
+    store: Arc<MetricsStore>,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            let _ = store.append(&engine.metrics_snapshot());
+        }
+    })
+}
@@ -0,0 +1,23 @@
+use crate::commands::OrderCommand;
+use crate::engine::MatchingEngine;
+use crate::error::RejectReason;
+
+/// A pre-trade hook run on every command before the engine looks at it.
+/// It may rewrite the command, e.g. to map symbols or fill in defaults, or
+/// refuse it.
+pub trait CommandMiddleware: Send + Sync {
+    fn pre_handle(&self, cmd: &mut OrderCommand) -> Result<(), RejectReason>;
+}
+
+impl MatchingEngine {
+    /// Appends `middleware` to the chain. Middleware runs in the order it
+    /// was added and the first rejection stops the command.
+    pub fn with_middleware(mut self, middleware: impl CommandMiddleware + 'static) -> Self {
+        self.middleware.push(Box::new(middleware));
+        self
+    }
+
+    pub(crate) fn run_middleware(&self, command: &mut OrderCommand) -> Result<(), RejectReason> {
+        self.middleware.iter().try_for_each(|m| m.pre_handle(command))
+    }
+}
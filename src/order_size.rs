@@ -0,0 +1,38 @@
+use rust_decimal::Decimal;
+
+use crate::engine::MatchingEngine;
+use crate::error::RejectReason;
+
+impl MatchingEngine {
+    /// Rejects orders below the symbol's minimum quantity or outside its
+    /// notional limits. Notional is taken at `price`, or at the reference
+    /// price for market orders; a market order on a symbol without one is
+    /// only held to the minimum quantity.
+    pub(crate) fn check_order_size(
+        &self,
+        symbol: &str,
+        price: Option<Decimal>,
+        quantity: Decimal,
+    ) -> Result<(), RejectReason> {
+        let Some(config) = self.symbols.get(symbol) else {
+            return Ok(());
+        };
+        if let Some(minimum) = config.min_order_quantity.filter(|minimum| quantity < *minimum) {
+            return Err(RejectReason::QuantityBelowMinimum { quantity, minimum });
+        }
+        if config.min_notional.is_none() && config.max_notional.is_none() {
+            return Ok(());
+        }
+        let Some(price) = price.or_else(|| self.reference_price(symbol)) else {
+            return Ok(());
+        };
+        let notional = price.checked_mul(quantity).unwrap_or(Decimal::MAX);
+        if let Some(minimum) = config.min_notional.filter(|minimum| notional < *minimum) {
+            return Err(RejectReason::NotionalBelowMinimum { notional, minimum });
+        }
+        if let Some(limit) = config.max_notional.filter(|limit| notional > *limit) {
+            return Err(RejectReason::OrderNotionalTooLarge { notional, limit });
+        }
+        Ok(())
+    }
+}
@@ -1,176 +1,552 @@
-use rust_decimal::Decimal;
-use std::cell::RefCell;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rust_decimal::{Decimal, RoundingStrategy};
 use std::collections::HashMap;
-use std::rc::Rc;
+use std::mem::size_of;
 use uuid::Uuid;
 
-use crate::types::{Order, OrderBookEntry, OrderSide};
+use crate::config::SelfTradePolicy;
+use crate::footprint::{map_bytes, MemoryFootprint};
+use crate::matching::{crosses, Fill, MatchPolicy};
+use crate::price_level::PriceLevel;
+use crate::types::{Order, OrderBookEntry, OrderSide, OrderStatus};
 
 const MAX_LEVEL: usize = 32;
 
-type SkipNode = Rc<RefCell<Node>>;
-
-fn new_skip_node(price: Decimal, level: usize) -> SkipNode {
-    Rc::new(RefCell::new(Node::new(price, level)))
-}
+/// Index of the sentinel node every search starts from.
+const HEAD: usize = 0;
 
 #[derive(Debug, Clone)]
 struct Node {
     price: Decimal,
-    orders: Vec<Order>,
-    next: Vec<Option<SkipNode>>,
+    orders: PriceLevel,
+    /// Forward links per level, as indices into `SkipListOrderBook::nodes`.
+    next: Vec<Option<usize>>,
+    /// Back link on the bottom level, so bids can be walked from the top.
+    prev: usize,
 }
 
 impl Node {
     fn new(price: Decimal, level: usize) -> Self {
         Self {
             price,
-            orders: Vec::new(),
+            orders: PriceLevel::new(),
             next: vec![None; level],
+            prev: HEAD,
         }
     }
 }
 
-#[derive(Debug)]
+/// Skip list of price levels, one node per price. Nodes live in one Vec and
+/// link to each other by index, so the book owns all of its data and is
+/// Send + Sync. A level's node is unlinked once its last order leaves and
+/// the slot, buffers and all, is reused by the next new level, so a book
+/// that has warmed up stops allocating on insert and cancel.
+#[derive(Debug, Clone)]
 pub struct SkipListOrderBook {
-    head: SkipNode,
+    nodes: Vec<Node>,
     level: usize,
+    /// The highest-priced node, or HEAD when the list is empty.
+    tail: usize,
+    /// Slots of unlinked nodes, free for new levels.
+    free: Vec<usize>,
     size: usize,
-    price_map: HashMap<Decimal, Vec<Order>>,
+    /// The node holding each price level. Nodes own the orders; this only
+    /// points at them.
+    price_map: HashMap<Decimal, usize>,
+    /// Where each resting order sits, so it can be found by id alone.
+    order_prices: HashMap<Uuid, Decimal>,
+    /// Draws node heights. Seed it to get the same structure on replay.
+    rng: StdRng,
+}
+
+impl Default for SkipListOrderBook {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SkipListOrderBook {
     pub fn new() -> Self {
+        Self::with_rng(StdRng::from_os_rng())
+    }
+
+    /// A book whose shape depends only on the seed and the orders fed to
+    /// it, so replaying a command stream rebuilds it node for node.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(rng: StdRng) -> Self {
         Self {
-            head: new_skip_node(Decimal::MIN, MAX_LEVEL),
+            nodes: vec![Node::new(Decimal::MIN, MAX_LEVEL)],
             level: 1,
+            tail: HEAD,
+            free: Vec::new(),
             size: 0,
             price_map: HashMap::new(),
+            order_prices: HashMap::new(),
+            rng,
         }
     }
 
-    fn random_level() -> usize {
+    /// A book with room for `levels` price levels before it reallocates.
+    pub fn with_capacity(levels: usize) -> Self {
+        let mut book = Self::new();
+        book.nodes.reserve(levels);
+        book.price_map.reserve(levels);
+        book
+    }
+
+    fn random_level(&mut self) -> usize {
         let mut level = 1;
-        while level < MAX_LEVEL && rand::random::<f64>() < 0.5 {
+        while level < MAX_LEVEL && self.rng.random_bool(0.5) {
             level += 1;
         }
         level
     }
 
+    /// Queues `order` at its price level, creating the level if needed.
     pub fn add_order(&mut self, order: Order) {
         let price = order.price.unwrap_or(Decimal::MAX);
-        self.price_map
-            .entry(price)
-            .or_insert_with(Vec::new)
-            .push(order.clone());
-
-        let mut current = Some(self.head.clone());
-        let mut update = vec![self.head.clone(); MAX_LEVEL];
-        let mut level = self.level;
-
-        while level > 0 {
-            level -= 1;
-            while let Some(node) = current.clone() {
-                if let Some(next) = node.borrow().next[level].clone() {
-                    if next.borrow().price > price {
-                        break;
-                    }
-                    current = Some(next);
-                }
-            }
-            
-            update[level] = current.take().unwrap();
+        self.order_prices.insert(order.id, price);
+        if let Some(&node) = self.price_map.get(&price) {
+            self.nodes[node].orders.push(order);
+            self.size += 1;
+            return;
         }
-        
 
-        let new_level = Self::random_level();
+        let mut update = self.predecessors(price);
+
+        let new_level = self.random_level();
         if new_level > self.level {
-            for i in self.level..new_level {
-                update[i] = self.head.clone();
+            for node in update.iter_mut().take(new_level).skip(self.level) {
+                *node = HEAD;
             }
             self.level = new_level;
         }
 
-        let new_node = new_skip_node(price, new_level);
-        for i in 0..new_level {
-            new_node.borrow_mut().next[i] = update[i].borrow_mut().next[i].take();
-            update[i].borrow_mut().next[i] = Some(new_node.clone());
+        let new_index = match self.free.pop() {
+            Some(index) => {
+                let node = &mut self.nodes[index];
+                node.price = price;
+                node.next.resize(new_level, None);
+                index
+            }
+            None => {
+                self.nodes.push(Node::new(price, new_level));
+                self.nodes.len() - 1
+            }
+        };
+        for (i, prev) in update.iter().enumerate().take(new_level) {
+            let next = self.nodes[*prev].next[i].replace(new_index);
+            self.nodes[new_index].next[i] = next;
+        }
+        let node = &mut self.nodes[new_index];
+        node.prev = update[0];
+        node.orders.push(order);
+        match node.next[0] {
+            Some(next) => self.nodes[next].prev = new_index,
+            None => self.tail = new_index,
         }
+        self.price_map.insert(price, new_index);
 
         self.size += 1;
     }
 
     pub fn remove_order(&mut self, order_id: Uuid, price: Decimal) -> Option<Order> {
-        if let Some(orders) = self.price_map.get_mut(&price) {
-            if let Some(pos) = orders.iter().position(|o| o.id == order_id) {
-                let order = orders.remove(pos);
-                if orders.is_empty() {
-                    self.price_map.remove(&price);
+        let node = *self.price_map.get(&price)?;
+        let order = self.nodes[node].orders.remove(order_id)?;
+        self.order_prices.remove(&order_id);
+        if self.nodes[node].orders.is_empty() {
+            self.unlink(price);
+        }
+        self.size -= 1;
+        Some(order)
+    }
+
+    /// Takes the node at `price` out of every level it is linked on and
+    /// frees its slot.
+    fn unlink(&mut self, price: Decimal) {
+        let Some(node) = self.price_map.remove(&price) else {
+            return;
+        };
+        let update = self.predecessors(price);
+        for (level, prev) in update.iter().enumerate().take(self.nodes[node].next.len()) {
+            self.nodes[*prev].next[level] = self.nodes[node].next[level];
+        }
+        match self.nodes[node].next[0] {
+            Some(next) => self.nodes[next].prev = update[0],
+            None => self.tail = update[0],
+        }
+        while self.level > 1 && self.nodes[HEAD].next[self.level - 1].is_none() {
+            self.level -= 1;
+        }
+        // The node keeps its link and order buffers for the next level
+        // that takes the slot.
+        self.nodes[node].next.clear();
+        self.free.push(node);
+    }
+
+    pub fn remove_order_by_id(&mut self, order_id: Uuid) -> Option<Order> {
+        let price = *self.order_prices.get(&order_id)?;
+        self.remove_order(order_id, price)
+    }
+
+    pub fn get_order(&self, order_id: Uuid) -> Option<&Order> {
+        let price = self.order_prices.get(&order_id)?;
+        self.get_orders_at_price(*price)?.get(order_id)
+    }
+
+    /// Replaces the resting copy of `order`, dropping it once it has
+    /// nothing left to trade.
+    pub fn update_order(&mut self, order: &Order) {
+        let Some(&price) = self.order_prices.get(&order.id) else {
+            return;
+        };
+        if !order.is_open() || order.remaining_quantity() <= Decimal::ZERO {
+            self.remove_order(order.id, price);
+            return;
+        }
+        let Some(&node) = self.price_map.get(&price) else {
+            return;
+        };
+        if let Some(resting) = self.nodes[node].orders.get_mut(order.id) {
+            *resting = order.clone();
+        }
+    }
+
+    /// The last node before `price` on each level.
+    fn predecessors(&self, price: Decimal) -> [usize; MAX_LEVEL] {
+        let mut current = HEAD;
+        let mut update = [HEAD; MAX_LEVEL];
+        for level in (0..self.level).rev() {
+            while let Some(next) = self.nodes[current].next[level] {
+                if self.nodes[next].price >= price {
+                    break;
                 }
-                self.size -= 1;
-                return Some(order);
+                current = next;
             }
+            update[level] = current;
+        }
+        update
+    }
+
+    fn iter_nodes(&self) -> Nodes<'_> {
+        Nodes {
+            book: self,
+            front: self.nodes[HEAD].next[0],
+            back: Some(self.tail).filter(|&index| index != HEAD),
         }
-        None
     }
 
+    /// The best price for a book holding `side`'s orders: the highest bid
+    /// or the lowest ask.
     pub fn get_best_price(&self, side: OrderSide) -> Option<Decimal> {
-        match side {
-            OrderSide::Buy => {
-                let mut current = self.head.clone();
-                let mut maybe_next = current.borrow().next[0].clone();
-
-                while let Some(next) = maybe_next {
-                    if !next.borrow().orders.is_empty() {
-                        return Some(next.borrow().price);
+        let node = match side {
+            OrderSide::Buy => self.iter_nodes().next_back(),
+            OrderSide::Sell => self.iter_nodes().next(),
+        };
+        node.map(|node| node.price)
+    }
+
+    pub fn get_orders_at_price(&self, price: Decimal) -> Option<&PriceLevel> {
+        self.price_map.get(&price).map(|&node| &self.nodes[node].orders)
+    }
+
+    pub fn get_depth(&self, depth: usize) -> Vec<OrderBookEntry> {
+        self.iter_nodes()
+            .take(depth)
+            .map(|node| OrderBookEntry {
+                price: node.price,
+                quantity: node.orders.iter().map(|o| o.quantity).sum(),
+                order_count: node.orders.len() as u64,
+            })
+            .collect()
+    }
+}
+
+/// Nodes in price order from either end, skipping the sentinel.
+struct Nodes<'a> {
+    book: &'a SkipListOrderBook,
+    front: Option<usize>,
+    back: Option<usize>,
+}
+
+impl<'a> Iterator for Nodes<'a> {
+    type Item = &'a Node;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.front?;
+        if self.front == self.back {
+            (self.front, self.back) = (None, None);
+        } else {
+            self.front = self.book.nodes[index].next[0];
+        }
+        Some(&self.book.nodes[index])
+    }
+}
+
+impl DoubleEndedIterator for Nodes<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let index = self.back?;
+        if self.front == self.back {
+            (self.front, self.back) = (None, None);
+        } else {
+            self.back = Some(self.book.nodes[index].prev).filter(|&prev| prev != HEAD);
+        }
+        Some(&self.book.nodes[index])
+    }
+}
+
+/// Price levels, lowest price first; `.rev()` walks from the top.
+pub type LevelIter<'a> = Box<dyn DoubleEndedIterator<Item = (Decimal, &'a PriceLevel)> + 'a>;
+
+/// Resting orders level by level, each level in priority order.
+pub type OrderIter<'a> = Box<dyn DoubleEndedIterator<Item = &'a Order> + 'a>;
+
+/// One side of a symbol's book: resting orders by price level, each level
+/// in priority order.
+pub trait OrderBookSide {
+    fn insert(&mut self, order: Order);
+    fn remove(&mut self, order_id: Uuid) -> Option<Order>;
+    fn get(&self, order_id: Uuid) -> Option<&Order>;
+    /// Replaces the resting copy of `order`, dropping it once it is filled
+    /// or no longer open.
+    fn update(&mut self, order: &Order);
+    fn level(&self, price: Decimal) -> Option<&PriceLevel>;
+    fn iter_levels(&self) -> LevelIter<'_>;
+    fn lowest_price(&self) -> Option<Decimal>;
+    fn highest_price(&self) -> Option<Decimal>;
+    fn level_count(&self) -> usize;
+    fn order_count(&self) -> usize;
+
+    fn memory_footprint(&self) -> MemoryFootprint;
+
+    /// Removes every order `keep` rejects in one walk of the book,
+    /// dropping levels left empty. Returns the removed orders, lowest
+    /// price first.
+    fn retain_orders(&mut self, keep: &mut dyn FnMut(&Order) -> bool) -> Vec<Order>;
+
+    fn iter_orders(&self) -> OrderIter<'_> {
+        Box::new(self.iter_levels().flat_map(|(_, level)| level.iter()))
+    }
+
+    /// Trades `taker` against this side, best price first and each level
+    /// by `policy.algorithm`, filling makers and taking out the ones that
+    /// are done. The taker's fills and status are updated in place; under
+    /// `SelfTradePolicy::CancelIncoming` it comes back Canceled.
+    fn match_incoming(&mut self, taker: &mut Order, policy: &MatchPolicy) -> Vec<Fill> {
+        let mut fills: Vec<Fill> = Vec::new();
+        let mut trades = 0;
+        let mut spent = Decimal::ZERO;
+        while taker.remaining_quantity() > Decimal::ZERO {
+            let fills_left = policy.max_fills.map(|max| max.saturating_sub(trades));
+            if fills_left == Some(0) {
+                break;
+            }
+            let best = match taker.side {
+                OrderSide::Buy => self.lowest_price(),
+                OrderSide::Sell => self.highest_price(),
+            };
+            let Some(price) = best.filter(|best| crosses(taker.side, policy.limit_price, *best)) else {
+                break;
+            };
+            let Some(level) = self.level(price) else {
+                break;
+            };
+
+            if policy.self_trade != SelfTradePolicy::Allow {
+                let own: Vec<Uuid> = level
+                    .iter()
+                    .filter(|o| o.user_id == taker.user_id)
+                    .map(|o| o.id)
+                    .collect();
+                if !own.is_empty() {
+                    if policy.self_trade == SelfTradePolicy::CancelIncoming {
+                        taker.status = OrderStatus::Canceled;
+                        taker.updated_at = policy.now;
+                        break;
+                    }
+                    for order_id in own {
+                        if let Some(mut maker) = self.remove(order_id) {
+                            maker.status = OrderStatus::Canceled;
+                            maker.updated_at = policy.now;
+                            fills.push(Fill {
+                                maker,
+                                price,
+                                quantity: Decimal::ZERO,
+                            });
+                        }
                     }
-                    current = next;
-                    maybe_next = current.borrow().next[0].clone();
+                    continue;
                 }
             }
-            OrderSide::Sell => {
-                let mut current = self.head.clone();
-                let mut maybe_next = current.borrow().next[0].clone();
-                while let Some(next) = maybe_next {
-                    if !next.borrow().orders.is_empty() {
-                        return Some(next.borrow().price);
+
+            let mut wanted = taker.remaining_quantity();
+            if let Some(budget) = policy.budget {
+                // Rounded down to the taker's own precision, so rounding
+                // never takes it over budget
+                let scale = taker.quantity.scale();
+                let affordable = ((budget - spent) / price).round_dp_with_strategy(scale, RoundingStrategy::ToZero);
+                wanted = wanted.min(affordable);
+                if wanted <= Decimal::ZERO {
+                    break;
+                }
+            }
+            let mut allocations = policy.algorithm.level_fills(level, wanted);
+            if let Some(fills_left) = fills_left {
+                allocations.truncate(fills_left);
+            }
+            if allocations.is_empty() {
+                let stale: Vec<Uuid> = level.iter().map(|o| o.id).collect();
+                for order_id in stale {
+                    if let Some(maker) = self.remove(order_id) {
+                        fills.push(Fill {
+                            maker,
+                            price,
+                            quantity: Decimal::ZERO,
+                        });
                     }
-                    current = next;
-                    maybe_next = current.borrow().next[0].clone();
                 }
+                continue;
+            }
+
+            for (maker_id, quantity) in allocations {
+                let Some(mut maker) = self.get(maker_id).cloned() else {
+                    continue;
+                };
+                maker.filled_quantity += quantity;
+                maker.updated_at = policy.now;
+                maker.status = if maker.remaining_quantity() == Decimal::ZERO {
+                    OrderStatus::Filled
+                } else {
+                    OrderStatus::PartiallyFilled
+                };
+                self.update(&maker);
+
+                taker.filled_quantity += quantity;
+                taker.updated_at = policy.now;
+                taker.status = if taker.remaining_quantity() == Decimal::ZERO {
+                    OrderStatus::Filled
+                } else {
+                    OrderStatus::PartiallyFilled
+                };
+                trades += 1;
+                spent += price * quantity;
+                fills.push(Fill { maker, price, quantity });
             }
         }
-        None
+        fills
     }
 
-    pub fn get_orders_at_price(&self, price: Decimal) -> Option<&Vec<Order>> {
-        self.price_map.get(&price)
+    fn clear(&mut self) {
+        let ids: Vec<Uuid> = self.iter_orders().map(|o| o.id).collect();
+        for id in ids {
+            self.remove(id);
+        }
     }
 
-    pub fn get_depth(&self, depth: usize) -> Vec<OrderBookEntry> {
-        let mut result = Vec::new();
-        let mut current = self.head.clone();
-        let mut count = 0;
+    /// Displayed liquidity on the best `levels` levels, taking this as the
+    /// `side` of the book: bids descending, asks ascending. Levels holding
+    /// only hidden orders are left out.
+    fn get_depth_by_side(&self, side: OrderSide, levels: usize) -> Vec<OrderBookEntry> {
+        let all = match side {
+            OrderSide::Buy => Box::new(self.iter_levels().rev()),
+            OrderSide::Sell => self.iter_levels(),
+        };
+        all.filter_map(|(price, level)| {
+                let displayed: Vec<&Order> = level.iter().filter(|o| o.displayed).collect();
+                (!displayed.is_empty()).then(|| OrderBookEntry {
+                    price,
+                    quantity: displayed.iter().map(|o| o.remaining_quantity()).sum(),
+                    order_count: displayed.len() as u64,
+                })
+            })
+            .take(levels)
+            .collect()
+    }
+}
 
-        let mut maybe_next = current.borrow().next[0].clone();
-        while let Some(next) = maybe_next {
-            if count >= depth {
-                break;
-            }
-            if !next.borrow().orders.is_empty() {
-                let total_quantity = next.borrow().orders.iter().map(|o| o.quantity).sum();
-                result.push(OrderBookEntry {
-                    price: next.borrow().price,
-                    quantity: total_quantity,
-                    order_count: next.borrow().orders.len() as u64,
-                });
-                count += 1;
+impl OrderBookSide for SkipListOrderBook {
+    fn insert(&mut self, order: Order) {
+        self.add_order(order);
+    }
+
+    fn remove(&mut self, order_id: Uuid) -> Option<Order> {
+        self.remove_order_by_id(order_id)
+    }
+
+    fn get(&self, order_id: Uuid) -> Option<&Order> {
+        self.get_order(order_id)
+    }
+
+    fn update(&mut self, order: &Order) {
+        self.update_order(order);
+    }
+
+    fn level(&self, price: Decimal) -> Option<&PriceLevel> {
+        self.get_orders_at_price(price)
+    }
+
+    fn iter_levels(&self) -> LevelIter<'_> {
+        Box::new(self.iter_nodes().map(|node| (node.price, &node.orders)))
+    }
+
+    fn lowest_price(&self) -> Option<Decimal> {
+        self.get_best_price(OrderSide::Sell)
+    }
+
+    fn highest_price(&self) -> Option<Decimal> {
+        self.get_best_price(OrderSide::Buy)
+    }
+
+    fn level_count(&self) -> usize {
+        self.price_map.len()
+    }
+
+    fn retain_orders(&mut self, keep: &mut dyn FnMut(&Order) -> bool) -> Vec<Order> {
+        let mut removed = Vec::new();
+        let mut emptied = Vec::new();
+        let mut next = self.nodes[HEAD].next[0];
+        while let Some(index) = next {
+            let node = &mut self.nodes[index];
+            next = node.next[0];
+            removed.extend(node.orders.retain(&mut *keep));
+            if node.orders.is_empty() {
+                emptied.push(node.price);
             }
-            current = next;
-            maybe_next = current.borrow().next[0].clone();
         }
+        for price in emptied {
+            self.unlink(price);
+        }
+        for order in &removed {
+            self.order_prices.remove(&order.id);
+        }
+        self.size -= removed.len();
+        removed
+    }
+
+    fn memory_footprint(&self) -> MemoryFootprint {
+        let mut footprint = MemoryFootprint {
+            level_bytes: self.nodes.capacity() * size_of::<Node>() + self.free.capacity() * size_of::<usize>(),
+            index_bytes: map_bytes(&self.price_map) + map_bytes(&self.order_prices),
+            levels: self.price_map.len(),
+            orders: self.order_prices.len(),
+            ..MemoryFootprint::default()
+        };
+        // Freed nodes keep their buffers, so they count too
+        for node in &self.nodes {
+            let (orders, index) = node.orders.heap_bytes();
+            footprint.level_bytes += node.next.capacity() * size_of::<Option<usize>>();
+            footprint.order_bytes += orders;
+            footprint.index_bytes += index;
+        }
+        footprint
+    }
 
-        result
+    fn order_count(&self) -> usize {
+        self.size
     }
 }
 
@@ -180,6 +556,24 @@ mod tests {
     use crate::types::{OrderStatus, OrderType};
     use chrono::Utc;
 
+    /// Every order is reachable from exactly one linked node, and the
+    /// indexes agree with the nodes.
+    fn assert_consistent(orderbook: &SkipListOrderBook) {
+        let linked: Vec<usize> = orderbook.iter_nodes().map(|node| orderbook.price_map[&node.price]).collect();
+        assert_eq!(linked.len(), orderbook.price_map.len());
+        let mut orders = 0;
+        for index in linked {
+            let node = &orderbook.nodes[index];
+            assert!(!node.orders.is_empty());
+            for order in node.orders.iter() {
+                assert_eq!(orderbook.order_prices.get(&order.id), Some(&node.price));
+                orders += 1;
+            }
+        }
+        assert_eq!(orders, orderbook.order_prices.len());
+        assert_eq!(orders, orderbook.size);
+    }
+
     fn create_test_order(price: Decimal) -> Order {
         Order {
             id: Uuid::new_v4(),
@@ -196,6 +590,11 @@ mod tests {
             iceberg_visible_quantity: None,
             stop_price: None,
             trailing_stop_price: None,
+            client_order_id: None,
+            displayed: true,
+            max_fills: None,
+            referrer_id: None,
+            quote_quantity: None,
         }
     }
 
@@ -213,6 +612,19 @@ mod tests {
         assert_eq!(orderbook.size, 0);
     }
 
+    #[test]
+    fn test_remove_order_by_id() {
+        let mut orderbook = SkipListOrderBook::new();
+        let order = create_test_order(Decimal::from(150));
+        let order_id = order.id;
+        orderbook.add_order(order);
+
+        assert_eq!(orderbook.get_order(order_id).map(|o| o.id), Some(order_id));
+        assert_eq!(orderbook.remove_order_by_id(order_id).map(|o| o.id), Some(order_id));
+        assert!(orderbook.remove_order_by_id(order_id).is_none());
+        assert_eq!(orderbook.get_best_price(OrderSide::Sell), None);
+    }
+
     #[test]
     fn test_get_best_price() {
         let mut orderbook = SkipListOrderBook::new();
@@ -222,8 +634,278 @@ mod tests {
         orderbook.add_order(order1);
         orderbook.add_order(order2);
 
-        let best_price = orderbook.get_best_price(OrderSide::Buy);
-        assert_eq!(best_price, Some(Decimal::from(100)));
+        assert_eq!(orderbook.get_best_price(OrderSide::Buy), Some(Decimal::from(200)));
+        assert_eq!(orderbook.get_best_price(OrderSide::Sell), Some(Decimal::from(100)));
+    }
+
+    #[test]
+    fn test_best_price_skips_emptied_levels() {
+        let mut orderbook = SkipListOrderBook::new();
+        let orders: Vec<Order> = [300, 100, 200, 400]
+            .into_iter()
+            .map(|price| create_test_order(Decimal::from(price)))
+            .collect();
+        for order in &orders {
+            orderbook.add_order(order.clone());
+        }
+
+        orderbook.remove_order(orders[3].id, Decimal::from(400));
+        orderbook.remove_order(orders[1].id, Decimal::from(100));
+        assert_eq!(orderbook.get_best_price(OrderSide::Buy), Some(Decimal::from(300)));
+        assert_eq!(orderbook.get_best_price(OrderSide::Sell), Some(Decimal::from(200)));
+        assert_eq!(
+            orderbook.iter_levels().map(|(price, _)| price).collect::<Vec<_>>(),
+            [Decimal::from(200), Decimal::from(300)]
+        );
+    }
+
+    #[test]
+    fn test_levels_iterate_from_both_ends() {
+        let mut orderbook = SkipListOrderBook::new();
+        for price in [300, 100, 200, 100] {
+            orderbook.add_order(create_test_order(Decimal::from(price)));
+        }
+
+        let mut levels = orderbook.iter_levels().map(|(price, _)| price);
+        assert_eq!(levels.next(), Some(Decimal::from(100)));
+        assert_eq!(levels.next_back(), Some(Decimal::from(300)));
+        assert_eq!(levels.next_back(), Some(Decimal::from(200)));
+        assert_eq!(levels.next(), None);
+
+        assert_eq!(orderbook.iter_orders().count(), 4);
+        assert_eq!(
+            orderbook.iter_orders().next_back().and_then(|o| o.price),
+            Some(Decimal::from(300))
+        );
+    }
+
+    #[test]
+    fn test_seeded_books_are_built_identically() {
+        let orders: Vec<Order> = (0..64).map(|i| create_test_order(Decimal::from(100 + i))).collect();
+        let build = || {
+            let mut orderbook = SkipListOrderBook::with_seed(7);
+            for order in &orders {
+                orderbook.add_order(order.clone());
+            }
+            for order in orders.iter().step_by(5) {
+                orderbook.remove_order_by_id(order.id);
+            }
+            orderbook
+        };
+        let (first, second) = (build(), build());
+
+        assert_eq!(first.level, second.level);
+        let links = |orderbook: &SkipListOrderBook| -> Vec<Vec<Option<usize>>> {
+            orderbook.nodes.iter().map(|node| node.next.clone()).collect()
+        };
+        assert_eq!(links(&first), links(&second));
+    }
+
+    #[test]
+    fn test_book_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SkipListOrderBook>();
+    }
+
+    #[test]
+    fn test_orders_at_a_price_share_one_level() {
+        let mut orderbook = SkipListOrderBook::new();
+        let hidden = Order {
+            displayed: false,
+            ..create_test_order(Decimal::from(100))
+        };
+        let first = create_test_order(Decimal::from(100));
+        let second = create_test_order(Decimal::from(100));
+        let ids = [first.id, second.id, hidden.id];
+        orderbook.insert(hidden);
+        orderbook.insert(first);
+        orderbook.insert(second);
+
+        let levels: Vec<_> = orderbook.iter_levels().collect();
+        assert_eq!(levels.len(), 1);
+        assert_eq!(levels[0].1.iter().map(|o| o.id).collect::<Vec<_>>(), ids);
+
+        for id in ids {
+            orderbook.remove(id);
+        }
+        assert!(orderbook.iter_levels().next().is_none());
+        assert_eq!(orderbook.lowest_price(), None);
+    }
+
+    #[test]
+    fn test_emptied_levels_are_unlinked_and_reused() {
+        let mut orderbook = SkipListOrderBook::new();
+        let resting = create_test_order(Decimal::from(100));
+        orderbook.add_order(resting);
+        for i in 0..50 {
+            let order = create_test_order(Decimal::from(200 + i));
+            let order_id = order.id;
+            orderbook.add_order(order);
+            orderbook.remove_order(order_id, Decimal::from(200 + i));
+        }
+
+        assert_eq!(orderbook.nodes.len(), 3);
+        assert_eq!(orderbook.iter_nodes().count(), 1);
+        assert_eq!(orderbook.get_best_price(OrderSide::Buy), Some(Decimal::from(100)));
+    }
+
+    #[test]
+    fn test_reused_nodes_keep_their_order_buffers() {
+        let mut orderbook = SkipListOrderBook::with_capacity(4);
+        let orders: Vec<Order> = (0..8).map(|_| create_test_order(Decimal::from(100))).collect();
+        for order in &orders {
+            orderbook.add_order(order.clone());
+        }
+        for order in &orders {
+            orderbook.remove_order_by_id(order.id);
+        }
+        let node = orderbook.free[0];
+        let capacity = orderbook.nodes[node].orders.capacity();
+        assert!(capacity >= orders.len());
+
+        let order = create_test_order(Decimal::from(150));
+        orderbook.add_order(order.clone());
+        assert_eq!(orderbook.price_map.get(&Decimal::from(150)), Some(&node));
+        assert_eq!(orderbook.nodes[node].orders.capacity(), capacity);
+        assert_eq!(orderbook.get_order(order.id).map(|o| o.id), Some(order.id));
+    }
+
+    #[test]
+    fn test_nodes_are_the_single_source_of_orders() {
+        let mut orderbook = SkipListOrderBook::new();
+        let orders: Vec<Order> = (0..40)
+            .map(|i| create_test_order(Decimal::from(100 + i % 7)))
+            .collect();
+        for order in &orders {
+            orderbook.add_order(order.clone());
+        }
+        assert_consistent(&orderbook);
+
+        for order in orders.iter().step_by(3) {
+            orderbook.remove_order_by_id(order.id);
+        }
+        let filled = Order {
+            filled_quantity: Decimal::new(5, 1),
+            ..orders[1].clone()
+        };
+        orderbook.update_order(&filled);
+        assert_consistent(&orderbook);
+        assert_eq!(orderbook.get_order(filled.id).map(|o| o.filled_quantity), Some(filled.filled_quantity));
+        let level = orderbook.level(Decimal::from(101)).unwrap();
+        assert!(std::ptr::eq(level.get(filled.id).unwrap(), orderbook.get_order(filled.id).unwrap()));
+
+        for order in &orders {
+            orderbook.remove_order_by_id(order.id);
+        }
+        assert_consistent(&orderbook);
+        assert!(orderbook.price_map.is_empty());
+    }
+
+    fn policy(limit_price: Option<Decimal>) -> MatchPolicy {
+        MatchPolicy {
+            algorithm: crate::matching::MatchingAlgorithm::PriceTimeFifo,
+            limit_price,
+            max_fills: None,
+            budget: None,
+            self_trade: SelfTradePolicy::Allow,
+            now: Utc::now(),
+        }
+    }
+
+    fn taker(quantity: Decimal) -> Order {
+        Order {
+            side: OrderSide::Sell,
+            quantity,
+            ..create_test_order(Decimal::ZERO)
+        }
+    }
+
+    #[test]
+    fn test_match_incoming_walks_levels_best_first() {
+        let mut bids = SkipListOrderBook::new();
+        let orders: Vec<Order> = [99, 101, 100, 101]
+            .into_iter()
+            .map(|price| create_test_order(Decimal::from(price)))
+            .collect();
+        for order in &orders {
+            bids.insert(order.clone());
+        }
+
+        let mut incoming = taker(Decimal::new(25, 1));
+        let fills = bids.match_incoming(&mut incoming, &policy(Some(Decimal::from(100))));
+        let filled: Vec<(Uuid, Decimal, Decimal)> = fills.iter().map(|f| (f.maker.id, f.price, f.quantity)).collect();
+        assert_eq!(
+            filled,
+            [
+                (orders[1].id, Decimal::from(101), Decimal::ONE),
+                (orders[3].id, Decimal::from(101), Decimal::ONE),
+                (orders[2].id, Decimal::from(100), Decimal::new(5, 1)),
+            ]
+        );
+        assert_eq!(incoming.status, OrderStatus::Filled);
+        assert_eq!(fills[0].maker.status, OrderStatus::Filled);
+        assert_eq!(bids.get(orders[2].id).map(|o| o.remaining_quantity()), Some(Decimal::new(5, 1)));
+        assert_eq!(bids.highest_price(), Some(Decimal::from(100)));
+
+        let mut incoming = taker(Decimal::from(5));
+        bids.match_incoming(&mut incoming, &policy(Some(Decimal::from(100))));
+        assert_eq!(incoming.remaining_quantity(), Decimal::new(45, 1));
+        assert_eq!(bids.highest_price(), Some(Decimal::from(99)));
+    }
+
+    #[test]
+    fn test_match_incoming_self_trade_and_fill_limits() {
+        let mut bids = SkipListOrderBook::new();
+        let own = create_test_order(Decimal::from(101));
+        let other = create_test_order(Decimal::from(100));
+        bids.insert(own.clone());
+        bids.insert(other.clone());
+        bids.insert(create_test_order(Decimal::from(100)));
+
+        let mut incoming = Order {
+            user_id: own.user_id,
+            ..taker(Decimal::from(3))
+        };
+        let fills = bids.match_incoming(
+            &mut incoming,
+            &MatchPolicy {
+                self_trade: SelfTradePolicy::CancelResting,
+                max_fills: Some(1),
+                ..policy(None)
+            },
+        );
+        assert_eq!(fills.len(), 2);
+        assert_eq!((fills[0].maker.id, fills[0].quantity), (own.id, Decimal::ZERO));
+        assert_eq!(fills[0].maker.status, OrderStatus::Canceled);
+        assert_eq!((fills[1].maker.id, fills[1].quantity), (other.id, Decimal::ONE));
+        assert_eq!(incoming.remaining_quantity(), Decimal::from(2));
+        assert_eq!(bids.order_count(), 1);
+    }
+
+    #[test]
+    fn test_retain_orders_drops_matches_and_empty_levels() {
+        let mut orderbook = SkipListOrderBook::new();
+        let user = Uuid::new_v4();
+        let orders: Vec<Order> = (0..12)
+            .map(|i| Order {
+                user_id: if i % 3 == 0 { user } else { Uuid::new_v4() },
+                ..create_test_order(Decimal::from(100 + i % 4))
+            })
+            .collect();
+        for order in &orders {
+            orderbook.add_order(order.clone());
+        }
+
+        let removed = orderbook.retain_orders(&mut |o| o.user_id != user);
+        assert_eq!(removed.len(), 4);
+        assert!(removed.iter().all(|o| o.user_id == user));
+        assert_consistent(&orderbook);
+
+        let removed = orderbook.retain_orders(&mut |o| o.price != Some(Decimal::from(101)));
+        assert_eq!(removed.len(), 2);
+        assert!(orderbook.level(Decimal::from(101)).is_none());
+        assert_eq!(orderbook.level_count(), 3);
+        assert_consistent(&orderbook);
     }
 
     #[test]
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::task::JoinHandle;
+
+use crate::engine::MatchingEngine;
+use crate::event_store::StoredEvent;
+use crate::subscription::{wait_for_events, EventFilter};
+
+const DEFAULT_BATCH_SIZE: usize = 256;
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+const DEFAULT_RETRY_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Somewhere saved events go once the event store has them, such as a
+/// message bus, a webhook or a socket broadcaster.
+#[async_trait]
+pub trait EventPublisher: Send + Sync {
+    /// Names the publisher's cursor, so it has to stay the same across
+    /// restarts.
+    fn name(&self) -> &str;
+    /// Delivers a batch. A failed batch is delivered again, as is one whose
+    /// cursor was not saved before a restart, so publishers can see an
+    /// event more than once.
+    async fn publish(&self, events: &[StoredEvent]) -> Result<(), String>;
+}
+
+/// Each publisher's cursor: the offset of the next event it should get.
+pub trait CursorStore: Send + Sync {
+    /// 0 for a publisher that has no cursor yet.
+    fn load(&self, publisher: &str) -> Result<u64, String>;
+    fn save(&self, publisher: &str, offset: u64) -> Result<(), String>;
+}
+
+#[derive(Debug, Default)]
+pub struct InMemoryCursorStore {
+    cursors: Mutex<HashMap<String, u64>>,
+}
+
+impl InMemoryCursorStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl CursorStore for InMemoryCursorStore {
+    fn load(&self, publisher: &str) -> Result<u64, String> {
+        Ok(self.cursors.lock().unwrap().get(publisher).copied().unwrap_or(0))
+    }
+
+    fn save(&self, publisher: &str, offset: u64) -> Result<(), String> {
+        self.cursors.lock().unwrap().insert(publisher.to_string(), offset);
+        Ok(())
+    }
+}
+
+/// One file per publisher in a directory, replaced by rename on each save.
+pub struct FileCursorStore {
+    dir: PathBuf,
+}
+
+impl FileCursorStore {
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, String> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create cursor dir {}: {}", dir.display(), e))?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, publisher: &str) -> PathBuf {
+        self.dir.join(format!("{}.cursor", publisher))
+    }
+}
+
+impl CursorStore for FileCursorStore {
+    fn load(&self, publisher: &str) -> Result<u64, String> {
+        match fs::read_to_string(self.path(publisher)) {
+            Ok(offset) => offset.trim().parse().map_err(|_| format!("Corrupt cursor for {}", publisher)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    fn save(&self, publisher: &str, offset: u64) -> Result<(), String> {
+        let staged = self.dir.join(format!("{}.cursor.tmp", publisher));
+        let mut file = File::create(&staged).map_err(|e| e.to_string())?;
+        write!(file, "{}", offset).map_err(|e| e.to_string())?;
+        file.sync_data().map_err(|e| e.to_string())?;
+        fs::rename(&staged, self.path(publisher)).map_err(|e| e.to_string())
+    }
+}
+
+/// Delivers the event store's log to publishers, each from its own cursor
+/// on its own task, so a slow or failing publisher holds up neither the
+/// others nor matching. Delivery is at least once.
+pub struct Outbox {
+    cursors: Arc<dyn CursorStore>,
+    publishers: Vec<Arc<dyn EventPublisher>>,
+    batch_size: usize,
+    poll_interval: Duration,
+    retry_backoff: Duration,
+}
+
+impl Outbox {
+    pub fn new(cursors: impl CursorStore + 'static) -> Self {
+        Self {
+            cursors: Arc::new(cursors),
+            publishers: Vec::new(),
+            batch_size: DEFAULT_BATCH_SIZE,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            retry_backoff: DEFAULT_RETRY_BACKOFF,
+        }
+    }
+
+    pub fn with_publisher(mut self, publisher: impl EventPublisher + 'static) -> Self {
+        self.publishers.push(Arc::new(publisher));
+        self
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// How often an idle publisher checks the log when the store has no
+    /// subscriptions to wake it.
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// The wait before retrying a failed read, publish or cursor load.
+    pub fn with_retry_backoff(mut self, backoff: Duration) -> Self {
+        self.retry_backoff = backoff;
+        self
+    }
+
+    /// Starts one delivery task per publisher, running until aborted.
+    pub fn spawn(self, engine: Arc<MatchingEngine>) -> Vec<JoinHandle<()>> {
+        self.publishers
+            .iter()
+            .map(|publisher| {
+                let delivery = Delivery {
+                    engine: engine.clone(),
+                    cursors: self.cursors.clone(),
+                    publisher: publisher.clone(),
+                    batch_size: self.batch_size,
+                    poll_interval: self.poll_interval,
+                    retry_backoff: self.retry_backoff,
+                };
+                tokio::spawn(delivery.run())
+            })
+            .collect()
+    }
+}
+
+struct Delivery {
+    engine: Arc<MatchingEngine>,
+    cursors: Arc<dyn CursorStore>,
+    publisher: Arc<dyn EventPublisher>,
+    batch_size: usize,
+    poll_interval: Duration,
+    retry_backoff: Duration,
+}
+
+impl Delivery {
+    async fn run(self) {
+        let name = self.publisher.name().to_string();
+        let mut cursor = loop {
+            match self.cursors.load(&name) {
+                Ok(cursor) => break cursor,
+                Err(_) => tokio::time::sleep(self.retry_backoff).await,
+            }
+        };
+        // Only a wake-up; the events themselves are read from the store
+        let mut wakeups = self.engine.event_store().subscribe(EventFilter::default()).ok();
+
+        loop {
+            let batch = match self.engine.event_store().read_from(cursor, self.batch_size).await {
+                Ok(batch) => batch,
+                Err(_) => {
+                    tokio::time::sleep(self.retry_backoff).await;
+                    continue;
+                }
+            };
+            let Some(last) = batch.last() else {
+                wait_for_events(&mut wakeups, self.poll_interval).await;
+                continue;
+            };
+            if self.publisher.publish(&batch).await.is_err() {
+                tokio::time::sleep(self.retry_backoff).await;
+                continue;
+            }
+            cursor = last.offset + 1;
+            // A cursor that fails to save only means a redelivery after a
+            // restart
+            let _ = self.cursors.save(&name, cursor);
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::{BTreeSet, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use dashmap::DashSet;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::draws::new_event_id;
+use crate::engine::MatchingEngine;
+use crate::events::{OrderEvent, PersistenceHaltedEvent, PersistenceResumedEvent};
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum HaltScope {
+    Symbol,
+    Engine,
+}
+
+/// What the engine does when the event store rejects a batch. In-memory
+/// state has already changed at that point, so the events are either handed
+/// back as an error or buffered until the store recovers.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum PersistenceFailurePolicy {
+    #[default]
+    ReturnError,
+    /// Buffer the events, halt the affected symbols (or everything) and
+    /// retry the buffer before admitting further commands. Once the buffer
+    /// holds `retry_queue_capacity` events the halt widens to the engine.
+    Halt {
+        scope: HaltScope,
+        retry_queue_capacity: usize,
+    },
+    /// Return the error and undo what the failed command did to the books,
+    /// orders and trades of its symbols, saving each command's events in
+    /// one call regardless of `event_batch_size`. Costs a copy of the
+    /// touched books per command, and only covers commands run through
+    /// `handle_command`.
+    Rollback,
+}
+
+#[derive(Default)]
+pub(crate) struct PersistenceState {
+    retry_queue: Mutex<VecDeque<OrderEvent>>,
+    halted_symbols: DashSet<String>,
+    engine_halted: AtomicBool,
+}
+
+impl MatchingEngine {
+    pub fn with_persistence_policy(mut self, policy: PersistenceFailurePolicy) -> Self {
+        self.persistence_policy = policy;
+        self
+    }
+
+    pub fn is_persistence_halted(&self, symbol: &str) -> bool {
+        self.persistence.engine_halted.load(Ordering::SeqCst)
+            || self.persistence.halted_symbols.contains(symbol)
+    }
+
+    pub async fn buffered_event_count(&self) -> usize {
+        self.persistence.retry_queue.lock().await.len()
+    }
+
+    /// Flushes buffered events to the store and lifts persistence halts,
+    /// returning the resume events that were emitted.
+    pub async fn retry_persistence(&self) -> Result<Vec<OrderEvent>, String> {
+        let mut queue = self.persistence.retry_queue.lock().await;
+        self.flush_retry_queue(&mut queue).await
+    }
+
+    /// Admission check run before a command touches `symbol`. A halted
+    /// symbol gets one recovery attempt; the command is rejected if the
+    /// store is still unavailable. The resume events returned are already
+    /// saved, so callers hand them back without persisting them again.
+    pub(crate) async fn check_persistence_halt(&self, symbol: &str) -> Result<Vec<OrderEvent>, String> {
+        if !self.is_persistence_halted(symbol) {
+            return Ok(Vec::new());
+        }
+        self.retry_persistence()
+            .await
+            .map_err(|_| format!("Trading on {} is halted: event store unavailable", symbol))
+    }
+
+    pub(crate) async fn persist_events(&self, events: &mut Vec<OrderEvent>) -> Result<(), String> {
+        let closed_candles = self.take_closed_candles();
+        if self.is_replaying() {
+            return Ok(());
+        }
+        self.log_draws()?;
+        events.extend(closed_candles);
+        let settlement = self.settlement_batch(events);
+        if self.enqueue_events(events).await {
+            self.queue_settlement(settlement).await;
+            return Ok(());
+        }
+        self.save_to_event_store(events).await?;
+        self.queue_settlement(settlement).await;
+        // The events are the command's record; trades and settlements a
+        // failed save leaves behind go out with the next command's
+        let _ = self.flush_trades().await;
+        let _ = self.flush_settlements().await;
+        Ok(())
+    }
+
+    pub(crate) async fn save_to_event_store(&self, events: &mut Vec<OrderEvent>) -> Result<(), String> {
+        let (scope, capacity) = match self.persistence_policy {
+            PersistenceFailurePolicy::ReturnError => {
+                return self.save_batched(events.clone()).await;
+            }
+            PersistenceFailurePolicy::Rollback => {
+                return self.event_store.save_events(events.clone()).await;
+            }
+            PersistenceFailurePolicy::Halt {
+                scope,
+                retry_queue_capacity,
+            } => (scope, retry_queue_capacity),
+        };
+
+        let mut queue = self.persistence.retry_queue.lock().await;
+        let reason = if queue.is_empty() {
+            match self.save_batched(events.clone()).await {
+                Ok(()) => return Ok(()),
+                Err(reason) => {
+                    queue.extend(events.iter().cloned());
+                    reason
+                }
+            }
+        } else {
+            // Earlier buffered events must reach the store first
+            queue.extend(events.iter().cloned());
+            match self.flush_retry_queue(&mut queue).await {
+                Ok(resumed) => {
+                    events.extend(resumed);
+                    return Ok(());
+                }
+                Err(reason) => reason,
+            }
+        };
+
+        let symbols: BTreeSet<String> = events
+            .iter()
+            .filter_map(|e| e.symbol().map(str::to_string))
+            .collect();
+        let scope = if queue.len() >= capacity {
+            HaltScope::Engine
+        } else {
+            scope
+        };
+        let halted = self.halt_for_persistence(scope, symbols, &reason, queue.len());
+        queue.extend(halted.iter().cloned());
+        events.extend(halted);
+        Ok(())
+    }
+
+    fn halt_for_persistence(
+        &self,
+        scope: HaltScope,
+        symbols: BTreeSet<String>,
+        reason: &str,
+        buffered_events: usize,
+    ) -> Vec<OrderEvent> {
+        let halted_event = |symbol: Option<String>| {
+            OrderEvent::PersistenceHalted(PersistenceHaltedEvent {
+                event_id: new_event_id(),
+                prev_hash: None,
+                scope,
+                symbol,
+                reason: reason.to_string(),
+                buffered_events,
+                timestamp: self.clock.now(),
+            })
+        };
+
+        match scope {
+            HaltScope::Engine => {
+                if self.persistence.engine_halted.swap(true, Ordering::SeqCst) {
+                    Vec::new()
+                } else {
+                    vec![halted_event(None)]
+                }
+            }
+            HaltScope::Symbol => symbols
+                .into_iter()
+                .filter(|symbol| self.persistence.halted_symbols.insert(symbol.clone()))
+                .map(|symbol| halted_event(Some(symbol)))
+                .collect(),
+        }
+    }
+
+    /// Hands `events` to the store in batches of the configured size.
+    async fn save_batched(&self, events: Vec<OrderEvent>) -> Result<(), String> {
+        let Some(batch_size) = self.config.event_batch_size.filter(|size| *size > 0) else {
+            return self.event_store.save_events(events).await;
+        };
+        for batch in events.chunks(batch_size) {
+            self.event_store.save_events(batch.to_vec()).await?;
+        }
+        Ok(())
+    }
+
+    async fn flush_retry_queue(&self, queue: &mut VecDeque<OrderEvent>) -> Result<Vec<OrderEvent>, String> {
+        let flushed_events = queue.len();
+        if flushed_events > 0 {
+            self.save_batched(queue.iter().cloned().collect()).await?;
+            queue.clear();
+        }
+
+        let mut resumed = Vec::new();
+        let resumed_event = |scope, symbol| {
+            OrderEvent::PersistenceResumed(PersistenceResumedEvent {
+                event_id: new_event_id(),
+                prev_hash: None,
+                scope,
+                symbol,
+                flushed_events,
+                timestamp: self.clock.now(),
+            })
+        };
+        if self.persistence.engine_halted.swap(false, Ordering::SeqCst) {
+            resumed.push(resumed_event(HaltScope::Engine, None));
+        }
+        let symbols: Vec<String> = self
+            .persistence
+            .halted_symbols
+            .iter()
+            .map(|s| s.key().clone())
+            .collect();
+        for symbol in symbols {
+            self.persistence.halted_symbols.remove(&symbol);
+            resumed.push(resumed_event(HaltScope::Symbol, Some(symbol)));
+        }
+
+        if !resumed.is_empty() && self.event_store.save_events(resumed.clone()).await.is_err() {
+            queue.extend(resumed.iter().cloned());
+        }
+        Ok(resumed)
+    }
+}
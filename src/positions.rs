@@ -0,0 +1,141 @@
+use std::collections::BTreeMap;
+
+use dashmap::DashMap;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::engine::MatchingEngine;
+use crate::error::RejectReason;
+use crate::types::{OrderSide, Trade};
+
+/// What a user has bought and sold of one symbol.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    pub bought: Decimal,
+    pub sold: Decimal,
+}
+
+impl Position {
+    /// Net position: positive long, negative short.
+    pub fn quantity(&self) -> Decimal {
+        self.bought - self.sold
+    }
+}
+
+/// The furthest a user may be long or short a symbol, counting every open
+/// order as filled. Unset is unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExposureLimits {
+    pub max_long: Option<Decimal>,
+    pub max_short: Option<Decimal>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct PositionState {
+    positions: DashMap<(Uuid, String), Position>,
+    symbol_limits: DashMap<String, ExposureLimits>,
+    user_limits: DashMap<(Uuid, String), ExposureLimits>,
+}
+
+impl MatchingEngine {
+    /// Limits for every user on `symbol` without limits of their own.
+    pub fn set_exposure_limits(&self, symbol: &str, limits: ExposureLimits) {
+        self.positions.symbol_limits.insert(symbol.to_string(), limits);
+    }
+
+    pub fn set_user_exposure_limits(&self, user_id: Uuid, symbol: &str, limits: ExposureLimits) {
+        self.positions.user_limits.insert((user_id, symbol.to_string()), limits);
+    }
+
+    /// The limits that apply to `user_id` on `symbol`, the user's merged
+    /// over the symbol's.
+    pub fn exposure_limits(&self, user_id: Uuid, symbol: &str) -> ExposureLimits {
+        let symbol_limits = self.positions.symbol_limits.get(symbol).map(|l| *l).unwrap_or_default();
+        let user_limits = self
+            .positions
+            .user_limits
+            .get(&(user_id, symbol.to_string()))
+            .map(|l| *l)
+            .unwrap_or_default();
+        ExposureLimits {
+            max_long: user_limits.max_long.or(symbol_limits.max_long),
+            max_short: user_limits.max_short.or(symbol_limits.max_short),
+        }
+    }
+
+    pub fn get_position(&self, user_id: Uuid, symbol: &str) -> Position {
+        self.positions
+            .positions
+            .get(&(user_id, symbol.to_string()))
+            .map(|p| *p)
+            .unwrap_or_default()
+    }
+
+    /// Every symbol the user has traded, by name.
+    pub fn get_positions(&self, user_id: Uuid) -> BTreeMap<String, Position> {
+        self.positions
+            .positions
+            .iter()
+            .filter(|entry| entry.key().0 == user_id)
+            .map(|entry| (entry.key().1.clone(), *entry.value()))
+            .collect()
+    }
+
+    /// Rejects `quantity` more on `side` if the user's position and every
+    /// open order of theirs on that side, all filled, would exceed the
+    /// limit. `excluding` is an open order the command takes the place of.
+    /// Bracket exits only ever reduce a position and are not counted.
+    pub(crate) fn check_exposure(
+        &self,
+        user_id: Uuid,
+        symbol: &str,
+        side: OrderSide,
+        quantity: Decimal,
+        excluding: Option<Uuid>,
+    ) -> Result<(), RejectReason> {
+        let limits = self.exposure_limits(user_id, symbol);
+        let Some(limit) = (match side {
+            OrderSide::Buy => limits.max_long,
+            OrderSide::Sell => limits.max_short,
+        }) else {
+            return Ok(());
+        };
+        let open: Decimal = self
+            .get_open_orders(user_id)
+            .iter()
+            .filter(|o| o.symbol == symbol && o.side == side && Some(o.id) != excluding)
+            .filter(|o| !self.is_bracket_exit(o.id))
+            .map(|o| o.remaining_quantity())
+            .sum();
+        let position = self.get_position(user_id, symbol).quantity();
+        let held = match side {
+            OrderSide::Buy => position,
+            OrderSide::Sell => -position,
+        };
+        let exposure = held + open + quantity;
+        if exposure > limit {
+            return Err(RejectReason::ExposureLimitExceeded { side, exposure, limit });
+        }
+        Ok(())
+    }
+
+    /// Adds the trade to the positions of both sides, or with `undo` takes
+    /// it back out.
+    pub(crate) fn record_position(&self, trade: &Trade, taker_user_id: Uuid, maker_user_id: Option<Uuid>, undo: bool) {
+        let quantity = if undo { -trade.quantity } else { trade.quantity };
+        let apply = |user_id: Uuid, side: OrderSide| {
+            let mut position = self.positions.positions.entry((user_id, trade.symbol.clone())).or_default();
+            match side {
+                OrderSide::Buy => position.bought += quantity,
+                OrderSide::Sell => position.sold += quantity,
+            }
+        };
+        apply(taker_user_id, trade.side);
+        if let Some(maker_user_id) = maker_user_id {
+            apply(maker_user_id, trade.side.opposite());
+        }
+        self.record_margin_trade(trade, taker_user_id, maker_user_id, undo);
+        self.record_session_trade(trade, taker_user_id, maker_user_id, undo);
+    }
+}
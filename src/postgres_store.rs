@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use sqlx::postgres::{PgConnectOptions, PgPool, PgPoolOptions};
+use sqlx::types::Json;
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::event_store::{chain_events, event_hash, unsaved, EventStore, StoredEvent, TimeRange};
+use crate::events::OrderEvent;
+use crate::subscription::{event_users, EventFeed, EventFilter, EventSubscription};
+
+const SCHEMA: &str = include_str!("../sql/postgres/events.sql");
+const MAX_CONNECTIONS: u32 = 8;
+/// Advisory lock key held by each save, so saves from several stores on
+/// one database number their events one after another.
+const SAVE_LOCK: i64 = 0x6f72_6465_725f_6576;
+
+/// Events in PostgreSQL, in the tables of `sql/postgres/events.sql`: one
+/// row per event numbered by a sequence the store assigns, with columns
+/// and indexes for the order, symbol, kind and users of each. A batch is
+/// saved in one transaction.
+pub struct PostgresEventStore {
+    pool: PgPool,
+    /// Held while saving, so subscribers see batches in save order.
+    saving: tokio::sync::Mutex<()>,
+    feed: EventFeed,
+    hash_chain: bool,
+}
+
+impl PostgresEventStore {
+    /// Connects to the database at `url`, a `postgres://` URL, creating the
+    /// tables if they are missing.
+    pub async fn connect(url: &str) -> Result<Self, String> {
+        let options = PgConnectOptions::from_str(url).map_err(|e| e.to_string())?;
+        let pool = PgPoolOptions::new()
+            .max_connections(MAX_CONNECTIONS)
+            .connect_with(options)
+            .await
+            .map_err(|e| e.to_string())?;
+        Self::open(pool).await
+    }
+
+    /// Uses `pool`, creating the tables if they are missing.
+    pub async fn open(pool: PgPool) -> Result<Self, String> {
+        sqlx::raw_sql(SCHEMA).execute(&pool).await.map_err(|e| e.to_string())?;
+        Ok(Self {
+            pool,
+            saving: tokio::sync::Mutex::new(()),
+            feed: EventFeed::new(),
+            hash_chain: false,
+        })
+    }
+
+    /// Has each saved event carry the hash of the one before it.
+    pub fn with_hash_chain(mut self) -> Self {
+        self.hash_chain = true;
+        self
+    }
+
+    /// Events of the rows `condition` selects, with `$1` bound to `key`,
+    /// in save order.
+    async fn indexed<K>(&self, condition: &str, key: K, range: TimeRange) -> Result<Vec<OrderEvent>, String>
+    where
+        K: for<'q> sqlx::Encode<'q, sqlx::Postgres> + sqlx::Type<sqlx::Postgres> + Send + 'static,
+    {
+        let query = format!(
+            "SELECT payload FROM order_events WHERE {} \
+             AND ($2::timestamptz IS NULL OR timestamp >= $2) AND ($3::timestamptz IS NULL OR timestamp < $3) \
+             ORDER BY sequence",
+            condition
+        );
+        let rows: Vec<Json<OrderEvent>> = sqlx::query_scalar(&query)
+            .bind(key)
+            .bind(range.start)
+            .bind(range.end)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(rows.into_iter().map(|Json(event)| event).collect())
+    }
+}
+
+#[async_trait]
+impl EventStore for PostgresEventStore {
+    async fn save_events(&self, events: Vec<OrderEvent>) -> Result<(), String> {
+        let _saving = self.saving.lock().await;
+        let mut tx = self.pool.begin().await.map_err(|e| e.to_string())?;
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(SAVE_LOCK)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        let last: Option<i64> = sqlx::query_scalar("SELECT MAX(sequence) FROM order_events")
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        let next_sequence = last.unwrap_or(0) + 1;
+
+        let ids: Vec<Uuid> = events.iter().map(OrderEvent::event_id).filter(|id| !id.is_nil()).collect();
+        let saved: Vec<Uuid> = sqlx::query_scalar("SELECT event_id FROM order_events WHERE event_id = ANY($1)")
+            .bind(&ids)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        let mut events = unsaved(events, |event_id| Ok(saved.contains(&event_id)))?;
+        if events.is_empty() {
+            return Ok(());
+        }
+        if self.hash_chain {
+            let last: Option<Json<OrderEvent>> =
+                sqlx::query_scalar("SELECT payload FROM order_events WHERE sequence = $1")
+                    .bind(next_sequence - 1)
+                    .fetch_optional(&mut *tx)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            let mut head = last.map(|Json(event)| event_hash(&event));
+            chain_events(&mut events, &mut head);
+        }
+
+        // Owners of the orders matched, for indexing matches by user
+        let matched: Vec<Uuid> = events
+            .iter()
+            .filter_map(|event| match event {
+                OrderEvent::OrderMatched(e) => Some([e.order_id, e.matched_order_id]),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+        let mut owners: HashMap<Uuid, Uuid> = HashMap::new();
+        if !matched.is_empty() {
+            let rows = sqlx::query(
+                "SELECT e.order_id, u.user_id FROM order_events e \
+                 JOIN order_event_users u ON u.sequence = e.sequence \
+                 WHERE e.kind = 'OrderPlaced' AND e.order_id = ANY($1)",
+            )
+            .bind(&matched)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+            for row in rows {
+                owners.insert(row.get("order_id"), row.get("user_id"));
+            }
+        }
+
+        for (sequence, event) in (next_sequence..).zip(&events) {
+            if let OrderEvent::OrderPlaced(e) = event {
+                owners.insert(e.order_id, e.user_id);
+            }
+            let event_id = Some(event.event_id()).filter(|id| !id.is_nil());
+            sqlx::query(
+                "INSERT INTO order_events (sequence, event_id, prev_hash, order_id, symbol, kind, timestamp, payload) \
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+            )
+            .bind(sequence)
+            .bind(event_id)
+            .bind(event.prev_hash())
+            .bind(event.order_id())
+            .bind(event.symbol())
+            .bind(event.kind())
+            .bind(event.timestamp())
+            .bind(Json(event))
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+            let users: Vec<Uuid> = event_users(event, |order_id| owners.get(&order_id).copied())
+                .into_iter()
+                .collect();
+            if !users.is_empty() {
+                sqlx::query(
+                    "INSERT INTO order_event_users (user_id, sequence) SELECT user_id, $2 FROM UNNEST($1::uuid[]) \
+                     AS users (user_id) ON CONFLICT DO NOTHING",
+                )
+                .bind(&users)
+                .bind(sequence)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+            }
+        }
+        tx.commit().await.map_err(|e| e.to_string())?;
+        self.feed.publish(&events);
+        Ok(())
+    }
+
+    async fn get_events(&self, order_id: Uuid) -> Result<Vec<OrderEvent>, String> {
+        self.indexed("order_id = $1", order_id, TimeRange::all()).await
+    }
+
+    async fn get_all_events(&self) -> Result<Vec<OrderEvent>, String> {
+        let rows: Vec<Json<OrderEvent>> = sqlx::query_scalar("SELECT payload FROM order_events ORDER BY sequence")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(rows.into_iter().map(|Json(event)| event).collect())
+    }
+
+    async fn get_events_by_symbol(&self, symbol: &str, range: TimeRange) -> Result<Vec<OrderEvent>, String> {
+        self.indexed("symbol = $1", symbol.to_string(), range).await
+    }
+
+    async fn get_events_by_user(&self, user_id: Uuid, range: TimeRange) -> Result<Vec<OrderEvent>, String> {
+        let condition = "sequence IN (SELECT sequence FROM order_event_users WHERE user_id = $1)";
+        self.indexed(condition, user_id, range).await
+    }
+
+    async fn get_events_by_type(&self, kind: &str, range: TimeRange) -> Result<Vec<OrderEvent>, String> {
+        self.indexed("kind = $1", kind.to_string(), range).await
+    }
+
+    /// Offsets are sequences less one.
+    async fn read_from(&self, offset: u64, max: usize) -> Result<Vec<StoredEvent>, String> {
+        let query = "SELECT sequence, payload FROM order_events WHERE sequence > $1 ORDER BY sequence LIMIT $2";
+        let rows = sqlx::query(query)
+            .bind(offset as i64)
+            .bind(max as i64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let Json(event) = row.get("payload");
+                StoredEvent {
+                    offset: row.get::<i64, _>("sequence") as u64 - 1,
+                    event,
+                }
+            })
+            .collect())
+    }
+
+    fn subscribe(&self, filter: EventFilter) -> Result<EventSubscription, String> {
+        Ok(self.feed.subscribe(filter))
+    }
+}
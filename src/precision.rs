@@ -0,0 +1,77 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+
+use crate::commands::PlaceOrderCommand;
+use crate::engine::MatchingEngine;
+use crate::error::EngineError;
+use crate::types::OrderSide;
+
+/// `price * quantity`, or NotionalOverflow where Decimal would overflow.
+pub(crate) fn checked_notional(symbol: &str, price: Decimal, quantity: Decimal) -> Result<Decimal, EngineError> {
+    price
+        .checked_mul(quantity)
+        .ok_or_else(|| EngineError::NotionalOverflow {
+            symbol: symbol.to_string(),
+            price,
+            quantity,
+        })
+}
+
+impl MatchingEngine {
+    /// Rounds a new order to its symbol's price and quantity scales and
+    /// makes sure its notional is representable. Limit prices round away
+    /// from the market, so an order never trades worse than it asked;
+    /// quantities round down.
+    pub(crate) fn normalize_order(&self, cmd: &mut PlaceOrderCommand) -> Result<(), EngineError> {
+        self.size_quote_order(cmd)?;
+        cmd.price = cmd.price.map(|p| self.normalize_limit_price(&cmd.symbol, cmd.side, p));
+        cmd.stop_price = cmd.stop_price.map(|p| self.normalize_trigger_price(&cmd.symbol, p));
+        cmd.trailing_stop_price = cmd
+            .trailing_stop_price
+            .map(|p| self.normalize_trigger_price(&cmd.symbol, p));
+        cmd.quantity = self.normalize_quantity(&cmd.symbol, cmd.quantity)?;
+        if let Some(visible) = cmd.iceberg_visible_quantity {
+            cmd.iceberg_visible_quantity = Some(self.normalize_quantity(&cmd.symbol, visible)?);
+        }
+
+        for price in [cmd.price, cmd.stop_price].into_iter().flatten() {
+            checked_notional(&cmd.symbol, price, cmd.quantity)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn normalize_limit_price(&self, symbol: &str, side: OrderSide, price: Decimal) -> Decimal {
+        let Some(scale) = self.symbols.get(symbol).and_then(|c| c.price_scale) else {
+            return price;
+        };
+        let strategy = match side {
+            OrderSide::Buy => RoundingStrategy::ToNegativeInfinity,
+            OrderSide::Sell => RoundingStrategy::ToPositiveInfinity,
+        };
+        price.round_dp_with_strategy(scale, strategy)
+    }
+
+    pub(crate) fn normalize_trigger_price(&self, symbol: &str, price: Decimal) -> Decimal {
+        match self.symbols.get(symbol).and_then(|c| c.price_scale) {
+            Some(scale) => price.round_dp_with_strategy(scale, RoundingStrategy::MidpointAwayFromZero),
+            None => price,
+        }
+    }
+
+    /// Truncates `quantity` to the symbol's scale, rejecting a positive
+    /// quantity that would round away to nothing.
+    pub(crate) fn normalize_quantity(&self, symbol: &str, quantity: Decimal) -> Result<Decimal, EngineError> {
+        let Some(scale) = self.symbols.get(symbol).and_then(|c| c.quantity_scale) else {
+            return Ok(quantity);
+        };
+        let rounded = quantity.round_dp_with_strategy(scale, RoundingStrategy::ToZero);
+        if quantity > Decimal::ZERO && rounded == Decimal::ZERO {
+            return Err(EngineError::Rejected(format!(
+                "Quantity {} is below the {} increment of {}",
+                quantity,
+                Decimal::new(1, scale),
+                symbol
+            )));
+        }
+        Ok(rounded)
+    }
+}
@@ -0,0 +1,39 @@
+use rust_decimal::Decimal;
+
+use crate::engine::MatchingEngine;
+use crate::error::RejectReason;
+use crate::types::OrderSide;
+
+impl MatchingEngine {
+    /// What price bands are measured from: the last trade, or the midpoint
+    /// of the book if the symbol has not traded.
+    pub fn reference_price(&self, symbol: &str) -> Option<Decimal> {
+        if let Some(last_price) = self.last_prices.get(symbol) {
+            return Some(*last_price);
+        }
+        let book = self.order_books.get(symbol)?;
+        Some((book.best(OrderSide::Buy)? + book.best(OrderSide::Sell)?) / Decimal::TWO)
+    }
+
+    /// Rejects a limit price outside the symbol's band around the
+    /// reference price. Symbols without a band or a reference price accept
+    /// any price.
+    pub(crate) fn check_price_band(&self, symbol: &str, price: Option<Decimal>) -> Result<(), RejectReason> {
+        let (Some(price), Some(band_percent)) = (price, self.symbols.get(symbol).and_then(|c| c.price_band_percent))
+        else {
+            return Ok(());
+        };
+        let Some(reference_price) = self.reference_price(symbol) else {
+            return Ok(());
+        };
+        let width = reference_price * band_percent / Decimal::ONE_HUNDRED;
+        if price < reference_price - width || price > reference_price + width {
+            return Err(RejectReason::PriceOutOfBand {
+                price,
+                reference_price,
+                band_percent,
+            });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+use std::mem::size_of;
+
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::footprint::map_bytes;
+use crate::types::Order;
+
+#[derive(Debug, Clone)]
+struct Slot {
+    order: Order,
+    prev: Option<usize>,
+    next: Option<usize>,
+}
+
+/// Orders resting at one price, in priority order: displayed orders first,
+/// then hidden ones, each oldest first. A doubly linked list over a slab,
+/// with an id index so cancels come out in O(1).
+#[derive(Debug, Clone, Default)]
+pub struct PriceLevel {
+    slots: Vec<Option<Slot>>,
+    free: Vec<usize>,
+    head: Option<usize>,
+    tail: Option<usize>,
+    /// Everything from here to the tail is hidden.
+    first_hidden: Option<usize>,
+    index: HashMap<Uuid, usize>,
+}
+
+impl PriceLevel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Orders the level can hold before it reallocates. Emptying a level
+    /// keeps its buffers.
+    pub fn capacity(&self) -> usize {
+        self.slots.capacity()
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    pub fn contains(&self, order_id: Uuid) -> bool {
+        self.index.contains_key(&order_id)
+    }
+
+    /// Queues `order` behind everything displayed, and behind hidden orders
+    /// too if it is hidden itself.
+    pub fn push(&mut self, order: Order) {
+        let displayed = order.displayed;
+        let next = if displayed { self.first_hidden } else { None };
+        let prev = match next {
+            Some(next) => self.slot(next).prev,
+            None => self.tail,
+        };
+
+        let order_id = order.id;
+        let slot = Slot { order, prev, next };
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.slots[index] = Some(slot);
+                index
+            }
+            None => {
+                self.slots.push(Some(slot));
+                self.slots.len() - 1
+            }
+        };
+        match prev {
+            Some(prev) => self.slot_mut(prev).next = Some(index),
+            None => self.head = Some(index),
+        }
+        match next {
+            Some(next) => self.slot_mut(next).prev = Some(index),
+            None => self.tail = Some(index),
+        }
+        if !displayed && self.first_hidden.is_none() {
+            self.first_hidden = Some(index);
+        }
+        self.index.insert(order_id, index);
+    }
+
+    pub fn peek_front(&self) -> Option<&Order> {
+        self.head.map(|head| &self.slot(head).order)
+    }
+
+    pub fn pop_front(&mut self) -> Option<Order> {
+        let head = self.head?;
+        let order_id = self.slot(head).order.id;
+        self.remove(order_id)
+    }
+
+    pub fn get(&self, order_id: Uuid) -> Option<&Order> {
+        self.index.get(&order_id).map(|&index| &self.slot(index).order)
+    }
+
+    /// Mutable access to a queued order. Its id, price and visibility
+    /// decide where it sits and must not change.
+    pub fn get_mut(&mut self, order_id: Uuid) -> Option<&mut Order> {
+        let index = *self.index.get(&order_id)?;
+        Some(&mut self.slot_mut(index).order)
+    }
+
+    pub fn remove(&mut self, order_id: Uuid) -> Option<Order> {
+        let index = self.index.remove(&order_id)?;
+        let slot = self.slots[index].take()?;
+        match slot.prev {
+            Some(prev) => self.slot_mut(prev).next = slot.next,
+            None => self.head = slot.next,
+        }
+        match slot.next {
+            Some(next) => self.slot_mut(next).prev = slot.prev,
+            None => self.tail = slot.prev,
+        }
+        if self.first_hidden == Some(index) {
+            self.first_hidden = slot.next;
+        }
+        if self.index.is_empty() {
+            self.slots.clear();
+            self.free.clear();
+        } else {
+            self.free.push(index);
+        }
+        Some(slot.order)
+    }
+
+    /// Removes every order `keep` rejects, returning them in priority
+    /// order.
+    pub fn retain(&mut self, mut keep: impl FnMut(&Order) -> bool) -> Vec<Order> {
+        let doomed: Vec<Uuid> = self.iter().filter(|o| !keep(o)).map(|o| o.id).collect();
+        doomed.into_iter().filter_map(|order_id| self.remove(order_id)).collect()
+    }
+
+    /// Orders in priority order.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            level: self,
+            front: self.head,
+            back: self.tail,
+        }
+    }
+
+    /// Heap bytes behind the level's orders and its id index.
+    pub(crate) fn heap_bytes(&self) -> (usize, usize) {
+        let strings: usize = self
+            .iter()
+            .map(|o| o.symbol.capacity() + o.client_order_id.as_ref().map_or(0, |id| id.capacity()))
+            .sum();
+        let orders = self.slots.capacity() * size_of::<Option<Slot>>() + self.free.capacity() * size_of::<usize>();
+        (orders + strings, map_bytes(&self.index))
+    }
+
+    pub fn total_quantity(&self) -> Decimal {
+        self.iter().map(|o| o.remaining_quantity()).sum()
+    }
+
+    fn slot(&self, index: usize) -> &Slot {
+        self.slots[index].as_ref().expect("linked slot is occupied")
+    }
+
+    fn slot_mut(&mut self, index: usize) -> &mut Slot {
+        self.slots[index].as_mut().expect("linked slot is occupied")
+    }
+}
+
+/// A level's orders in priority order, from either end.
+pub struct Iter<'a> {
+    level: &'a PriceLevel,
+    front: Option<usize>,
+    back: Option<usize>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a Order;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.front?;
+        let slot = self.level.slot(index);
+        if self.front == self.back {
+            (self.front, self.back) = (None, None);
+        } else {
+            self.front = slot.next;
+        }
+        Some(&slot.order)
+    }
+}
+
+impl DoubleEndedIterator for Iter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let index = self.back?;
+        let slot = self.level.slot(index);
+        if self.front == self.back {
+            (self.front, self.back) = (None, None);
+        } else {
+            self.back = slot.prev;
+        }
+        Some(&slot.order)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{OrderSide, OrderStatus, OrderType};
+    use chrono::Utc;
+
+    fn create_test_order(displayed: bool) -> Order {
+        Order {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            symbol: "BTC/USDT".to_string(),
+            order_type: OrderType::Limit,
+            side: OrderSide::Buy,
+            price: Some(Decimal::from(100)),
+            quantity: Decimal::from(1),
+            filled_quantity: Decimal::ZERO,
+            status: OrderStatus::Active,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            iceberg_visible_quantity: None,
+            stop_price: None,
+            trailing_stop_price: None,
+            client_order_id: None,
+            displayed,
+            max_fills: None,
+            referrer_id: None,
+            quote_quantity: None,
+        }
+    }
+
+    #[test]
+    fn test_pop_front_is_fifo() {
+        let mut level = PriceLevel::new();
+        let orders: Vec<Order> = (0..3).map(|_| create_test_order(true)).collect();
+        for order in &orders {
+            level.push(order.clone());
+        }
+
+        assert_eq!(level.peek_front().map(|o| o.id), Some(orders[0].id));
+        for order in &orders {
+            assert_eq!(level.pop_front().map(|o| o.id), Some(order.id));
+        }
+        assert!(level.pop_front().is_none());
+    }
+
+    #[test]
+    fn test_displayed_orders_queue_ahead_of_hidden() {
+        let mut level = PriceLevel::new();
+        let hidden = create_test_order(false);
+        let first = create_test_order(true);
+        let second = create_test_order(true);
+        let later_hidden = create_test_order(false);
+        for order in [&hidden, &first, &later_hidden, &second] {
+            level.push(order.clone());
+        }
+
+        let ids: Vec<Uuid> = level.iter().map(|o| o.id).collect();
+        assert_eq!(ids, [first.id, second.id, hidden.id, later_hidden.id]);
+
+        level.remove(hidden.id);
+        let third = create_test_order(true);
+        level.push(third.clone());
+        let ids: Vec<Uuid> = level.iter().map(|o| o.id).collect();
+        assert_eq!(ids, [first.id, second.id, third.id, later_hidden.id]);
+    }
+
+    #[test]
+    fn test_remove_by_id_keeps_the_rest_in_order() {
+        let mut level = PriceLevel::new();
+        let orders: Vec<Order> = (0..4).map(|_| create_test_order(true)).collect();
+        for order in &orders {
+            level.push(order.clone());
+        }
+
+        assert_eq!(level.remove(orders[1].id).map(|o| o.id), Some(orders[1].id));
+        assert!(level.remove(orders[1].id).is_none());
+        assert_eq!(level.len(), 3);
+        let ids: Vec<Uuid> = level.iter().map(|o| o.id).collect();
+        assert_eq!(ids, [orders[0].id, orders[2].id, orders[3].id]);
+        assert_eq!(level.total_quantity(), Decimal::from(3));
+    }
+}
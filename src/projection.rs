@@ -0,0 +1,363 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::engine::MatchingEngine;
+use crate::event_store::EventStore;
+use crate::events::{OrderEvent, OrderPlacedEvent};
+use crate::subscription::{wait_for_events, EventFilter};
+use crate::types::{OrderSide, OrderStatus, OrderType};
+
+const CATCH_UP_PAGE: usize = 512;
+
+/// A read model folded from the event log.
+pub trait Projection: Send + Sync {
+    fn apply(&mut self, event: &OrderEvent);
+    /// Forgets everything applied, ahead of a rebuild.
+    fn reset(&mut self);
+}
+
+struct Projected<P> {
+    projection: P,
+    /// Offset of the next event to apply.
+    offset: u64,
+}
+
+/// A projection and how far into the event log it has got.
+pub struct Projector<P> {
+    state: RwLock<Projected<P>>,
+}
+
+impl<P: Projection> Projector<P> {
+    pub fn new(projection: P) -> Self {
+        Self {
+            state: RwLock::new(Projected { projection, offset: 0 }),
+        }
+    }
+
+    pub fn read<R>(&self, f: impl FnOnce(&P) -> R) -> R {
+        f(&self.state.read().unwrap().projection)
+    }
+
+    pub fn offset(&self) -> u64 {
+        self.state.read().unwrap().offset
+    }
+
+    /// Applies the events saved since the last one applied, returning how
+    /// many there were.
+    pub async fn catch_up(&self, store: &dyn EventStore) -> Result<usize, String> {
+        let mut applied = 0;
+        loop {
+            let offset = self.offset();
+            let page = store.read_from(offset, CATCH_UP_PAGE).await?;
+            if page.is_empty() {
+                return Ok(applied);
+            }
+            let mut state = self.state.write().unwrap();
+            // Someone else caught up or rebuilt while the page was read
+            if state.offset != offset {
+                continue;
+            }
+            for stored in &page {
+                state.projection.apply(&stored.event);
+            }
+            state.offset += page.len() as u64;
+            applied += page.len();
+        }
+    }
+
+    /// Starts over from the first event in `store`.
+    pub async fn rebuild(&self, store: &dyn EventStore) -> Result<usize, String> {
+        {
+            let mut state = self.state.write().unwrap();
+            state.projection.reset();
+            state.offset = 0;
+        }
+        self.catch_up(store).await
+    }
+}
+
+/// Keeps `projector` caught up with `engine`'s event store until the
+/// returned task is aborted, checking at least every `poll_interval`.
+pub fn spawn_projector<P: Projection + 'static>(
+    engine: Arc<MatchingEngine>,
+    projector: Arc<Projector<P>>,
+    poll_interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut wakeups = engine.event_store().subscribe(EventFilter::default()).ok();
+        loop {
+            let _ = projector.catch_up(engine.event_store()).await;
+            wait_for_events(&mut wakeups, poll_interval).await;
+        }
+    })
+}
+
+/// An order as the projections see it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectedOrder {
+    pub order_id: Uuid,
+    pub user_id: Uuid,
+    pub symbol: String,
+    pub order_type: OrderType,
+    pub side: OrderSide,
+    pub price: Option<Decimal>,
+    pub quantity: Decimal,
+    pub filled_quantity: Decimal,
+    pub status: OrderStatus,
+    pub placed_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ProjectedOrder {
+    fn fill(&mut self, quantity: Decimal, at: DateTime<Utc>) {
+        self.filled_quantity += quantity;
+        self.status = if self.filled_quantity >= self.quantity {
+            OrderStatus::Filled
+        } else {
+            OrderStatus::PartiallyFilled
+        };
+        self.updated_at = at;
+    }
+
+    fn is_open(&self) -> bool {
+        !matches!(
+            self.status,
+            OrderStatus::Filled | OrderStatus::Canceled | OrderStatus::Rejected
+        )
+    }
+}
+
+/// Applies fills, amends and cancels in `event` to the orders in `orders`
+/// it concerns. Rejections are left out: they are of orders never placed,
+/// or of a reused id whose order stands.
+fn update_orders(orders: &mut HashMap<Uuid, ProjectedOrder>, event: &OrderEvent) {
+    match event {
+        OrderEvent::OrderMatched(e) => {
+            for order_id in [e.order_id, e.matched_order_id] {
+                if let Some(order) = orders.get_mut(&order_id) {
+                    order.fill(e.quantity, e.timestamp);
+                }
+            }
+        }
+        OrderEvent::OrderUpdated(e) => {
+            if let Some(order) = orders.get_mut(&e.order_id) {
+                order.price = e.new_price.or(order.price);
+                order.quantity = e.new_quantity.unwrap_or(order.quantity);
+                if order.filled_quantity >= order.quantity {
+                    order.status = OrderStatus::Filled;
+                }
+                order.updated_at = e.timestamp;
+            }
+        }
+        OrderEvent::OrderCanceled(e) => {
+            if let Some(order) = orders.get_mut(&e.order_id) {
+                order.status = OrderStatus::Canceled;
+                order.updated_at = e.timestamp;
+            }
+        }
+        OrderEvent::OrderFilled(e) => {
+            if let Some(order) = orders.get_mut(&e.order_id) {
+                order.filled_quantity = order.quantity;
+                order.status = OrderStatus::Filled;
+                order.updated_at = e.timestamp;
+            }
+        }
+        _ => {}
+    }
+}
+
+fn projected(e: &OrderPlacedEvent) -> ProjectedOrder {
+    ProjectedOrder {
+        order_id: e.order_id,
+        user_id: e.user_id,
+        symbol: e.symbol.clone(),
+        order_type: e.order_type,
+        side: e.side,
+        price: e.price,
+        quantity: e.quantity,
+        filled_quantity: Decimal::ZERO,
+        status: e.status,
+        placed_at: e.timestamp,
+        updated_at: e.timestamp,
+    }
+}
+
+/// Orders still working, by user and by symbol. Market orders never rest,
+/// so they are left out.
+#[derive(Debug, Default)]
+pub struct OpenOrdersView {
+    orders: HashMap<Uuid, ProjectedOrder>,
+}
+
+impl OpenOrdersView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, order_id: Uuid) -> Option<&ProjectedOrder> {
+        self.orders.get(&order_id)
+    }
+
+    /// Oldest first.
+    pub fn by_user(&self, user_id: Uuid) -> Vec<&ProjectedOrder> {
+        self.sorted(|order| order.user_id == user_id)
+    }
+
+    /// Oldest first.
+    pub fn by_symbol(&self, symbol: &str) -> Vec<&ProjectedOrder> {
+        self.sorted(|order| order.symbol == symbol)
+    }
+
+    pub fn len(&self) -> usize {
+        self.orders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+
+    fn sorted(&self, f: impl Fn(&ProjectedOrder) -> bool) -> Vec<&ProjectedOrder> {
+        let mut orders: Vec<_> = self.orders.values().filter(|order| f(order)).collect();
+        orders.sort_by_key(|order| (order.placed_at, order.order_id));
+        orders
+    }
+}
+
+impl Projection for OpenOrdersView {
+    fn apply(&mut self, event: &OrderEvent) {
+        if let OrderEvent::OrderPlaced(e) = event {
+            if e.order_type != OrderType::Market {
+                self.orders.insert(e.order_id, projected(e));
+            }
+            return;
+        }
+        update_orders(&mut self.orders, event);
+        let maker = match event {
+            OrderEvent::OrderMatched(e) => Some(e.matched_order_id),
+            _ => None,
+        };
+        for order_id in event.order_id().into_iter().chain(maker) {
+            if self.orders.get(&order_id).is_some_and(|order| !order.is_open()) {
+                self.orders.remove(&order_id);
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.orders.clear();
+    }
+}
+
+/// Every order each user has placed, in placement order, with where it
+/// ended up.
+#[derive(Debug, Default)]
+pub struct UserOrderHistoryView {
+    orders: HashMap<Uuid, ProjectedOrder>,
+    by_user: HashMap<Uuid, Vec<Uuid>>,
+}
+
+impl UserOrderHistoryView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, order_id: Uuid) -> Option<&ProjectedOrder> {
+        self.orders.get(&order_id)
+    }
+
+    pub fn history(&self, user_id: Uuid) -> Vec<&ProjectedOrder> {
+        self.by_user
+            .get(&user_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|order_id| self.orders.get(order_id))
+            .collect()
+    }
+}
+
+impl Projection for UserOrderHistoryView {
+    fn apply(&mut self, event: &OrderEvent) {
+        if let OrderEvent::OrderPlaced(e) = event {
+            if !self.orders.contains_key(&e.order_id) {
+                self.by_user.entry(e.user_id).or_default().push(e.order_id);
+                self.orders.insert(e.order_id, projected(e));
+            }
+            return;
+        }
+        update_orders(&mut self.orders, event);
+    }
+
+    fn reset(&mut self) {
+        self.orders.clear();
+        self.by_user.clear();
+    }
+}
+
+/// One print on a trade tape.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TapeEntry {
+    pub price: Decimal,
+    pub quantity: Decimal,
+    /// The taker's side.
+    pub side: OrderSide,
+    pub taker_order_id: Uuid,
+    pub maker_order_id: Uuid,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// The most recent matches on each symbol, up to `capacity` per symbol.
+#[derive(Debug)]
+pub struct SymbolTradeTapeView {
+    capacity: usize,
+    tapes: HashMap<String, VecDeque<TapeEntry>>,
+}
+
+impl SymbolTradeTapeView {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            tapes: HashMap::new(),
+        }
+    }
+
+    /// Newest first.
+    pub fn recent(&self, symbol: &str, limit: usize) -> Vec<&TapeEntry> {
+        self.tapes
+            .get(symbol)
+            .into_iter()
+            .flat_map(|tape| tape.iter().rev())
+            .take(limit)
+            .collect()
+    }
+}
+
+impl Projection for SymbolTradeTapeView {
+    fn apply(&mut self, event: &OrderEvent) {
+        let OrderEvent::OrderMatched(e) = event else {
+            return;
+        };
+        let tape = self.tapes.entry(e.symbol.clone()).or_default();
+        if tape.len() == self.capacity {
+            tape.pop_front();
+        }
+        tape.push_back(TapeEntry {
+            price: e.price,
+            quantity: e.quantity,
+            side: e.side,
+            taker_order_id: e.order_id,
+            maker_order_id: e.matched_order_id,
+            timestamp: e.timestamp,
+        });
+    }
+
+    fn reset(&mut self) {
+        self.tapes.clear();
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use uuid::Uuid;
+
+use crate::engine::MatchingEngine;
+use crate::types::{Order, OrderSide, OrderStatus};
+
+/// Predicates for `get_orders_filtered`; unset fields match everything.
+/// The time range applies to `created_at` and is inclusive.
+#[derive(Debug, Clone, Default)]
+pub struct OrderFilter {
+    pub user_id: Option<Uuid>,
+    pub symbol: Option<String>,
+    pub statuses: Option<Vec<OrderStatus>>,
+    pub side: Option<OrderSide>,
+    pub created_from: Option<DateTime<Utc>>,
+    pub created_to: Option<DateTime<Utc>>,
+}
+
+impl OrderFilter {
+    pub fn matches(&self, order: &Order) -> bool {
+        self.user_id.is_none_or(|u| order.user_id == u)
+            && self.symbol.as_ref().is_none_or(|s| order.symbol == *s)
+            && self.statuses.as_ref().is_none_or(|s| s.contains(&order.status))
+            && self.side.is_none_or(|s| order.side == s)
+            && self.created_from.is_none_or(|t| order.created_at >= t)
+            && self.created_to.is_none_or(|t| order.created_at <= t)
+    }
+
+    fn only_open(&self) -> bool {
+        self.statuses.as_ref().is_some_and(|statuses| {
+            statuses
+                .iter()
+                .all(|s| matches!(s, OrderStatus::Pending | OrderStatus::Active | OrderStatus::PartiallyFilled))
+        })
+    }
+}
+
+/// Order ids by user and by symbol, so queries only visit the orders they
+/// can return. Every order stays in the full indexes; the open indexes
+/// drop it once it is filled or canceled.
+#[derive(Debug, Default)]
+pub(crate) struct OrderIndex {
+    by_user: DashMap<Uuid, Vec<Uuid>>,
+    by_symbol: DashMap<String, Vec<Uuid>>,
+    open_by_user: DashMap<Uuid, HashSet<Uuid>>,
+    open_by_symbol: DashMap<String, HashSet<Uuid>>,
+}
+
+impl OrderIndex {
+    pub(crate) fn insert(&self, order: &Order) {
+        self.by_user.entry(order.user_id).or_default().push(order.id);
+        self.by_symbol.entry(order.symbol.clone()).or_default().push(order.id);
+        if order.is_open() {
+            self.open_by_user.entry(order.user_id).or_default().insert(order.id);
+            self.open_by_symbol.entry(order.symbol.clone()).or_default().insert(order.id);
+        }
+    }
+
+    pub(crate) fn open_order_count(&self, user_id: Uuid) -> usize {
+        self.open_by_user.get(&user_id).map(|ids| ids.len()).unwrap_or(0)
+    }
+
+    pub(crate) fn symbol_order_count(&self, symbol: &str) -> usize {
+        self.by_symbol.get(symbol).map_or(0, |ids| ids.len())
+    }
+
+    pub(crate) fn open_symbol_orders(&self, symbol: &str) -> Vec<Uuid> {
+        self.open_by_symbol
+            .get(symbol)
+            .map(|ids| ids.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Forgets the orders indexed on `symbol` after the first `len`,
+    /// returning their ids.
+    pub(crate) fn truncate_symbol(&self, symbol: &str, len: usize) -> Vec<Uuid> {
+        let Some(mut ids) = self.by_symbol.get_mut(symbol) else {
+            return Vec::new();
+        };
+        let len = len.min(ids.len());
+        let removed = ids.split_off(len);
+        drop(ids);
+        if let Some(mut open) = self.open_by_symbol.get_mut(symbol) {
+            open.retain(|id| !removed.contains(id));
+        }
+        for mut entry in self.by_user.iter_mut() {
+            entry.retain(|id| !removed.contains(id));
+        }
+        for mut entry in self.open_by_user.iter_mut() {
+            entry.retain(|id| !removed.contains(id));
+        }
+        removed
+    }
+
+    /// Puts `order` back in the open indexes.
+    pub(crate) fn reopen(&self, order: &Order) {
+        self.open_by_user.entry(order.user_id).or_default().insert(order.id);
+        self.open_by_symbol.entry(order.symbol.clone()).or_default().insert(order.id);
+    }
+
+    /// Drops `order` from the open indexes.
+    pub(crate) fn close(&self, order: &Order) {
+        if let Some(mut ids) = self.open_by_user.get_mut(&order.user_id) {
+            ids.remove(&order.id);
+        }
+        if let Some(mut ids) = self.open_by_symbol.get_mut(&order.symbol) {
+            ids.remove(&order.id);
+        }
+    }
+}
+
+impl MatchingEngine {
+    /// Open orders of `user_id` across all symbols, oldest first.
+    pub fn get_open_orders(&self, user_id: Uuid) -> Vec<Order> {
+        let ids = self
+            .order_index
+            .open_by_user
+            .get(&user_id)
+            .map(|ids| ids.iter().copied().collect())
+            .unwrap_or_default();
+        self.collect_orders(ids, |o| o.is_open())
+    }
+
+    /// Open orders on `symbol`, oldest first.
+    pub fn get_open_orders_by_symbol(&self, symbol: &str) -> Vec<Order> {
+        let ids = self
+            .order_index
+            .open_by_symbol
+            .get(symbol)
+            .map(|ids| ids.iter().copied().collect())
+            .unwrap_or_default();
+        self.collect_orders(ids, |o| o.is_open())
+    }
+
+    /// Orders matching `filter`, oldest first. A user or symbol in the
+    /// filter narrows the search to that index; only a filter with neither
+    /// scans every order.
+    pub fn get_orders_filtered(&self, filter: &OrderFilter) -> Vec<Order> {
+        let index = &self.order_index;
+        let ids: Vec<Uuid> = match (&filter.user_id, &filter.symbol, filter.only_open()) {
+            (Some(user_id), _, true) => index
+                .open_by_user
+                .get(user_id)
+                .map(|ids| ids.iter().copied().collect())
+                .unwrap_or_default(),
+            (Some(user_id), _, false) => index.by_user.get(user_id).map(|ids| ids.clone()).unwrap_or_default(),
+            (None, Some(symbol), true) => index
+                .open_by_symbol
+                .get(symbol)
+                .map(|ids| ids.iter().copied().collect())
+                .unwrap_or_default(),
+            (None, Some(symbol), false) => index.by_symbol.get(symbol).map(|ids| ids.clone()).unwrap_or_default(),
+            (None, None, _) => self.orders.iter().map(|o| *o.key()).collect(),
+        };
+        self.collect_orders(ids, |o| filter.matches(o))
+    }
+
+    fn collect_orders(&self, ids: Vec<Uuid>, keep: impl Fn(&Order) -> bool) -> Vec<Order> {
+        let mut orders: Vec<Order> = ids
+            .into_iter()
+            .filter_map(|id| self.get_order(id))
+            .filter(|o| keep(o))
+            .collect();
+        orders.sort_by_key(|o| o.created_at);
+        orders
+    }
+}
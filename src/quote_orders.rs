@@ -0,0 +1,40 @@
+use rust_decimal::Decimal;
+
+use crate::commands::PlaceOrderCommand;
+use crate::engine::MatchingEngine;
+use crate::error::RejectReason;
+use crate::types::{OrderSide, OrderType};
+
+/// Decimal places a quote order's quantity is cut to on symbols without a
+/// quantity scale.
+const DEFAULT_QUOTE_ORDER_SCALE: u32 = 8;
+
+impl MatchingEngine {
+    /// Replaces the quantity of a market buy entered by quote with what its
+    /// quote quantity buys walking the asks. Matching then holds it to the
+    /// quote quantity, so it spends no more even if the book has moved.
+    pub(crate) fn size_quote_order(&self, cmd: &mut PlaceOrderCommand) -> Result<(), RejectReason> {
+        let Some(quote_quantity) = cmd.quote_quantity else {
+            return Ok(());
+        };
+        if cmd.order_type != OrderType::Market || cmd.side != OrderSide::Buy {
+            return Err(RejectReason::QuoteQuantityNotMarketBuy);
+        }
+        if quote_quantity <= Decimal::ZERO {
+            return Err(RejectReason::NonPositiveQuantity(quote_quantity));
+        }
+        let scale = self
+            .symbols
+            .get(&cmd.symbol)
+            .and_then(|c| c.quantity_scale)
+            .unwrap_or(DEFAULT_QUOTE_ORDER_SCALE);
+        cmd.quantity = self
+            .order_books
+            .get(&cmd.symbol)
+            .map_or(Decimal::ZERO, |book| book.fill_for_notional(cmd.side, quote_quantity, scale).quantity);
+        if cmd.quantity.is_zero() {
+            return Err(RejectReason::QuoteQuantityUnfillable(quote_quantity));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+use std::io::{BufRead, Write};
+
+use serde::Serialize;
+
+use crate::commands::OrderCommand;
+use crate::engine::MatchingEngine;
+use crate::events::OrderEvent;
+use crate::types::Trade;
+
+/// What a replay writes for each command it runs.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ReplayOutput {
+    #[default]
+    Events,
+    Trades,
+    EventsAndTrades,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ReplaySummary {
+    pub commands: usize,
+    pub events: usize,
+    pub trades: usize,
+    /// Commands the engine refused.
+    pub errors: usize,
+}
+
+/// One line of replay output, tagged like the events themselves.
+#[derive(Serialize)]
+enum ReplayRecord<'a> {
+    Trade(&'a Trade),
+    Error { line: usize, error: String },
+}
+
+impl MatchingEngine {
+    /// Runs a JSON Lines log of `OrderCommand`s through the engine and
+    /// writes what each produced as JSON Lines, in order. A command the
+    /// engine refuses is written as an `Error` record with its line
+    /// number and the replay goes on; a line that is not a command stops
+    /// it.
+    pub async fn replay_jsonl(
+        &self,
+        input: impl BufRead,
+        mut output: impl Write,
+        mode: ReplayOutput,
+    ) -> Result<ReplaySummary, String> {
+        let mut summary = ReplaySummary::default();
+        for (number, line) in input.lines().enumerate() {
+            let line = line.map_err(|e| e.to_string())?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let command: OrderCommand = serde_json::from_str(&line)
+                .map_err(|e| format!("Line {}: {}", number + 1, e))?;
+            summary.commands += 1;
+
+            let trade_counts = self.trade_log.trade_counts();
+            let events = match self.handle_command(command).await {
+                Ok(events) => events,
+                Err(e) => {
+                    summary.errors += 1;
+                    write_line(&mut output, &ReplayRecord::Error { line: number + 1, error: e.to_string() })?;
+                    continue;
+                }
+            };
+            if mode != ReplayOutput::Trades {
+                summary.events += events.len();
+                for event in &events {
+                    write_line::<OrderEvent>(&mut output, event)?;
+                }
+            }
+            if mode != ReplayOutput::Events {
+                let trades = self.trade_log.trades_since(&trade_counts);
+                summary.trades += trades.len();
+                for trade in &trades {
+                    write_line(&mut output, &ReplayRecord::Trade(trade))?;
+                }
+            }
+        }
+        output.flush().map_err(|e| e.to_string())?;
+        Ok(summary)
+    }
+}
+
+fn write_line<T: Serialize>(output: &mut impl Write, record: &T) -> Result<(), String> {
+    serde_json::to_writer(&mut *output, record).map_err(|e| e.to_string())?;
+    output.write_all(b"\n").map_err(|e| e.to_string())
+}
@@ -0,0 +1,362 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+use crate::commands::OrderCommand;
+use crate::draws::Draws;
+use crate::engine::MatchingEngine;
+use crate::error::EngineError;
+use crate::events::OrderEvent;
+
+/// A command the leader ran, the events it produced and the ids and times
+/// it drew, numbered in the order the leader logged them. Commands the
+/// leader rejected are shipped too, with no events, since a rejection can
+/// still change state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicationRecord {
+    pub sequence: u64,
+    pub command: OrderCommand,
+    pub events: Vec<OrderEvent>,
+    #[serde(default)]
+    pub draws: Draws,
+    #[serde(default)]
+    pub rejected: bool,
+}
+
+/// Carries records from a leader to its follower. A record is shipped
+/// again until `replicate` succeeds, so followers must tolerate repeats.
+#[async_trait]
+pub trait ReplicaLink: Send + Sync {
+    async fn replicate(&self, record: &ReplicationRecord) -> Result<(), String>;
+}
+
+/// A follower in the same process.
+pub struct LocalReplica {
+    follower: Arc<MatchingEngine>,
+}
+
+impl LocalReplica {
+    pub fn new(follower: Arc<MatchingEngine>) -> Self {
+        Self { follower }
+    }
+}
+
+#[async_trait]
+impl ReplicaLink for LocalReplica {
+    async fn replicate(&self, record: &ReplicationRecord) -> Result<(), String> {
+        self.follower.apply_replicated(record.clone()).await
+    }
+}
+
+/// Records kept for the replica before client commands are refused.
+const DEFAULT_BACKLOG_LIMIT: usize = 10_000;
+/// How long shipping waits after the replica refuses a record.
+const RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Default)]
+struct Backlog {
+    /// The last sequence handed out to a command.
+    next_sequence: u64,
+    /// The last sequence the replica acknowledged.
+    acknowledged: u64,
+    /// Records of finished commands not yet acknowledged. Commands finish
+    /// out of order, so a record only ships once those before it have.
+    records: BTreeMap<u64, ReplicationRecord>,
+}
+
+/// Ships records to the replica in sequence order, off the command path.
+struct Shipper {
+    replica: Box<dyn ReplicaLink>,
+    backlog: Mutex<Backlog>,
+    /// Held while records are in flight, so they go out one at a time.
+    sending: tokio::sync::Mutex<()>,
+    queued: Notify,
+    last_error: Mutex<Option<String>>,
+}
+
+impl Shipper {
+    /// Ships records until the next one has not finished or the replica
+    /// refuses one, which stays queued.
+    async fn ship(&self) -> Result<(), String> {
+        let _sending = self.sending.lock().await;
+        loop {
+            let record = {
+                let backlog = self.backlog.lock().unwrap();
+                match backlog.records.get(&(backlog.acknowledged + 1)) {
+                    Some(record) => record.clone(),
+                    None => return Ok(()),
+                }
+            };
+            if let Err(e) = self.replica.replicate(&record).await {
+                *self.last_error.lock().unwrap() = Some(e.clone());
+                return Err(e);
+            }
+            *self.last_error.lock().unwrap() = None;
+            let mut backlog = self.backlog.lock().unwrap();
+            backlog.records.remove(&record.sequence);
+            backlog.acknowledged = record.sequence;
+        }
+    }
+}
+
+pub(crate) struct Replication {
+    shipper: Option<Arc<Shipper>>,
+    shipping: Mutex<Option<JoinHandle<()>>>,
+    backlog_limit: usize,
+    following: AtomicBool,
+    /// Set while a follower re-runs a leader's command.
+    applying: AtomicBool,
+    last_applied: tokio::sync::Mutex<u64>,
+    /// Why the follower stopped applying records, once it has.
+    diverged: Mutex<Option<String>>,
+}
+
+impl Default for Replication {
+    fn default() -> Self {
+        Self {
+            shipper: None,
+            shipping: Mutex::new(None),
+            backlog_limit: DEFAULT_BACKLOG_LIMIT,
+            following: AtomicBool::new(false),
+            applying: AtomicBool::new(false),
+            last_applied: tokio::sync::Mutex::new(0),
+            diverged: Mutex::new(None),
+        }
+    }
+}
+
+impl Drop for Replication {
+    fn drop(&mut self) {
+        if let Some(shipping) = self.shipping.get_mut().unwrap().take() {
+            shipping.abort();
+        }
+    }
+}
+
+/// Ships whatever is queued each time a record finishes, retrying while
+/// the replica refuses.
+async fn ship_continuously(shipper: Arc<Shipper>) {
+    loop {
+        shipper.queued.notified().await;
+        if shipper.ship().await.is_err() {
+            tokio::time::sleep(RETRY_INTERVAL).await;
+            shipper.queued.notify_one();
+        }
+    }
+}
+
+/// `events` as the leader and follower compare them, leaving out the hash
+/// links each store sets on its own copy.
+fn comparable(events: &[OrderEvent]) -> Vec<serde_json::Value> {
+    events
+        .iter()
+        .map(|event| {
+            let mut event = event.clone();
+            *event.prev_hash_mut() = None;
+            serde_json::to_value(event).expect("events serialize")
+        })
+        .collect()
+}
+
+impl MatchingEngine {
+    /// Ships every command this engine runs, with its events, to `replica`.
+    /// Records go out in the background, in the order the commands were
+    /// logged.
+    pub fn with_replica(mut self, replica: Box<dyn ReplicaLink>) -> Self {
+        self.replication.shipper = Some(Arc::new(Shipper {
+            replica,
+            backlog: Mutex::new(Backlog::default()),
+            sending: tokio::sync::Mutex::new(()),
+            queued: Notify::new(),
+            last_error: Mutex::new(None),
+        }));
+        self
+    }
+
+    /// Refuses client commands while `limit` records wait for the replica;
+    /// 10,000 by default.
+    pub fn with_replication_backlog_limit(mut self, limit: usize) -> Self {
+        self.replication.backlog_limit = limit;
+        self
+    }
+
+    /// Makes this engine a hot standby: it rejects client commands and
+    /// applies records from its leader until promoted.
+    pub fn as_follower(self) -> Self {
+        self.replication.following.store(true, Ordering::SeqCst);
+        self
+    }
+
+    pub fn is_following(&self) -> bool {
+        self.replication.following.load(Ordering::SeqCst)
+    }
+
+    /// The sequence of the last record applied from the leader.
+    pub async fn last_applied_sequence(&self) -> u64 {
+        *self.replication.last_applied.lock().await
+    }
+
+    /// Fails over to this follower, which accepts client commands from
+    /// here on. Returns the last sequence applied, so the caller can tell
+    /// which of the old leader's records never arrived.
+    pub async fn promote(&self) -> u64 {
+        let last_applied = self.replication.last_applied.lock().await;
+        self.replication.following.store(false, Ordering::SeqCst);
+        *last_applied
+    }
+
+    /// Records logged for the replica but not yet acknowledged, including
+    /// those of commands still running.
+    pub fn replication_backlog(&self) -> usize {
+        let Some(shipper) = &self.replication.shipper else {
+            return 0;
+        };
+        let backlog = shipper.backlog.lock().unwrap();
+        (backlog.next_sequence - backlog.acknowledged) as usize
+    }
+
+    /// Why the replica last refused a record, until one is accepted.
+    pub fn replication_error(&self) -> Option<String> {
+        let shipper = self.replication.shipper.as_ref()?;
+        shipper.last_error.lock().unwrap().clone()
+    }
+
+    /// Ships the backlog now rather than waiting on the background
+    /// shipping, stopping at the first record the replica refuses.
+    pub async fn flush_replication(&self) -> Result<(), String> {
+        match &self.replication.shipper {
+            Some(shipper) => shipper.ship().await,
+            None => Ok(()),
+        }
+    }
+
+    /// Applies a record from the leader: saves its events to this engine's
+    /// store and re-runs its command with the leader's draws to bring the
+    /// books and trades to where the leader's events put them. The events
+    /// the run produces must be the leader's; if they are not, or the
+    /// leader rejected the command and this engine does not, the follower
+    /// has diverged and refuses this and every later record. Records
+    /// already applied are skipped; a gap in the sequence is an error.
+    pub async fn apply_replicated(&self, record: ReplicationRecord) -> Result<(), String> {
+        if !self.is_following() {
+            return Err("Only a follower applies replicated commands".to_string());
+        }
+        let mut last_applied = self.replication.last_applied.lock().await;
+        if let Some(divergence) = self.replication_divergence() {
+            return Err(divergence);
+        }
+        if record.sequence <= *last_applied {
+            return Ok(());
+        }
+        if record.sequence != *last_applied + 1 {
+            return Err(format!(
+                "Replication gap: expected sequence {}, got {}",
+                *last_applied + 1,
+                record.sequence
+            ));
+        }
+        if !record.events.is_empty() {
+            self.event_store.save_events(record.events.clone()).await?;
+        }
+        let _admitted = self.command_gate.read().await;
+        let sequence = self.log_command(&record.command).map_err(|e| e.to_string())?;
+        self.replication.applying.store(true, Ordering::SeqCst);
+        let (result, _) = self.run_logged(sequence, record.draws, self.process_command(record.command)).await;
+        self.replication.applying.store(false, Ordering::SeqCst);
+        let divergence = match result {
+            Ok(_) if record.rejected => Some("the leader rejected it and the follower did not".to_string()),
+            Ok(events) if comparable(&events) != comparable(&record.events) => {
+                Some("its events differ from the leader's".to_string())
+            }
+            Err(e) if !record.rejected => Some(format!("the follower rejected it: {}", e)),
+            _ => None,
+        };
+        if let Some(divergence) = divergence {
+            let divergence = format!("Follower diverged at sequence {}: {}", record.sequence, divergence);
+            *self.replication.diverged.lock().unwrap() = Some(divergence.clone());
+            return Err(divergence);
+        }
+        *last_applied = record.sequence;
+        Ok(())
+    }
+
+    /// Why this follower stopped applying the leader's records, if it has.
+    /// Its state is then behind `last_applied_sequence` only by the record
+    /// that diverged.
+    pub fn replication_divergence(&self) -> Option<String> {
+        self.replication.diverged.lock().unwrap().clone()
+    }
+
+    /// Admission check refusing client commands on a follower.
+    pub(crate) fn check_following(&self) -> Result<(), EngineError> {
+        if self.is_following() {
+            return Err(EngineError::Rejected("Engine is a follower".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Admission check refusing client commands while the replica is too
+    /// far behind.
+    pub(crate) fn check_replication_backlog(&self) -> Result<(), EngineError> {
+        if self.replication.shipper.is_some() && self.replication_backlog() >= self.replication.backlog_limit {
+            return Err(EngineError::Rejected("Replication backlog is full".to_string()));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn is_replicating(&self) -> bool {
+        self.replication.shipper.is_some()
+    }
+
+    /// Whether the running command is being re-run rather than executed for
+    /// the first time, so its events and trades are already recorded.
+    pub(crate) fn is_replaying(&self) -> bool {
+        self.is_replaying_wal() || self.replication.applying.load(Ordering::SeqCst)
+    }
+
+    /// Logs `command` and numbers it for the replica in the same step, so
+    /// records ship in the order commands were logged.
+    pub(crate) fn log_replicated(&self, command: &OrderCommand) -> Result<(Option<u64>, Option<u64>), EngineError> {
+        let Some(shipper) = &self.replication.shipper else {
+            return Ok((self.log_command(command)?, None));
+        };
+        let mut backlog = shipper.backlog.lock().unwrap();
+        let sequence = self.log_command(command)?;
+        backlog.next_sequence += 1;
+        Ok((sequence, Some(backlog.next_sequence)))
+    }
+
+    /// Queues the record of the command numbered `shipped` and wakes the
+    /// background shipping. A record the replica refuses stays queued and
+    /// goes out ahead of the next one.
+    pub(crate) fn replicate(
+        &self,
+        shipped: u64,
+        command: OrderCommand,
+        result: &Result<Vec<OrderEvent>, EngineError>,
+        draws: Draws,
+    ) {
+        let Some(shipper) = &self.replication.shipper else {
+            return;
+        };
+        let record = ReplicationRecord {
+            sequence: shipped,
+            command,
+            events: result.as_ref().cloned().unwrap_or_default(),
+            draws,
+            rejected: result.is_err(),
+        };
+        shipper.backlog.lock().unwrap().records.insert(shipped, record);
+        let mut shipping = self.replication.shipping.lock().unwrap();
+        if shipping.is_none() {
+            *shipping = Some(tokio::spawn(ship_continuously(shipper.clone())));
+        }
+        shipper.queued.notify_one();
+    }
+}
@@ -0,0 +1,242 @@
+//! REST order management on axum, with JSON bodies mapped onto the
+//! engine's commands and queries. See `serve_rest` for the routes.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::body::Bytes;
+use axum::extract::{DefaultBodyLimit, Path, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::{delete, get, post};
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+use uuid::Uuid;
+
+use crate::auth::{bearer_token, Authenticator};
+use crate::commands::{CancelOrderCommand, OrderCommand, PlaceRequest};
+use crate::engine::MatchingEngine;
+use crate::error::EngineError;
+use crate::queries::OrderFilter;
+use crate::trade_log::Pagination;
+
+/// Largest request body accepted.
+const MAX_BODY_SIZE: usize = 1 << 20;
+const DEFAULT_DEPTH_LEVELS: usize = 20;
+const DEFAULT_PAGE_SIZE: usize = 100;
+
+#[derive(Clone)]
+struct Api {
+    engine: Arc<MatchingEngine>,
+    auth: Arc<dyn Authenticator>,
+}
+
+struct Response {
+    status: StatusCode,
+    body: Value,
+}
+
+impl Response {
+    fn ok(body: impl Serialize) -> Self {
+        Self::with_status(StatusCode::OK, body)
+    }
+
+    fn with_status(status: StatusCode, body: impl Serialize) -> Self {
+        Self {
+            status,
+            body: serde_json::to_value(body).expect("responses serialize"),
+        }
+    }
+
+    fn error(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: json!({ "error": message.into() }),
+        }
+    }
+
+    fn from_engine_error(error: EngineError) -> Self {
+        let status = match error {
+            EngineError::DuplicateOrderId(_) => StatusCode::CONFLICT,
+            EngineError::InternalInvariantViolation(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            EngineError::Rejected(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            EngineError::NotionalOverflow { .. } | EngineError::InvalidOrder(_) | EngineError::BasketRejected { .. } => {
+                StatusCode::BAD_REQUEST
+            }
+        };
+        Self::error(status, error.to_string())
+    }
+}
+
+impl IntoResponse for Response {
+    fn into_response(self) -> axum::response::Response {
+        (self.status, Json(self.body)).into_response()
+    }
+}
+
+/// Serves the order management API on `listener` until it fails:
+///
+/// - `POST /orders` places the order in the body, as the WebSocket server
+///   takes it, and returns its events
+/// - `DELETE /orders/{id}` cancels an order
+/// - `GET /orders?symbol=&status=&side=` lists the caller's orders;
+///   `status` takes a comma separated list
+/// - `GET /orderbook/{symbol}?levels=` returns L2 depth
+/// - `GET /trades?symbol=&since=&limit=` lists a symbol's trades, and
+///   `GET /trades?offset=&limit=` the caller's
+///
+/// Routes that act on or read a user's orders and trades take an
+/// `Authorization: Bearer` token, which `auth` maps to the user; orders
+/// can only be placed, canceled and listed by the user they belong to.
+/// Book depth and a symbol's trades are public. There is no TLS; put a
+/// terminating proxy in front of it.
+pub async fn serve_rest(
+    engine: Arc<MatchingEngine>,
+    listener: TcpListener,
+    auth: Arc<dyn Authenticator>,
+) -> Result<(), String> {
+    axum::serve(listener, rest_router(engine, auth)).await.map_err(|e| e.to_string())
+}
+
+/// The routes `serve_rest` serves, to mount alongside others.
+pub fn rest_router(engine: Arc<MatchingEngine>, auth: Arc<dyn Authenticator>) -> Router {
+    Router::new()
+        .route("/orders", post(place_order).get(list_orders))
+        .route("/orders/{id}", delete(cancel_order))
+        .route("/orderbook/{*symbol}", get(order_book))
+        .route("/trades", get(list_trades))
+        .method_not_allowed_fallback(|| async { Response::error(StatusCode::METHOD_NOT_ALLOWED, "Method not allowed") })
+        .fallback(|| async { Response::error(StatusCode::NOT_FOUND, "Not found") })
+        .layer(DefaultBodyLimit::max(MAX_BODY_SIZE))
+        .with_state(Api { engine, auth })
+}
+
+/// The user the request's bearer token belongs to.
+fn caller(api: &Api, headers: &HeaderMap) -> Result<Uuid, Response> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(bearer_token)
+        .and_then(|token| api.auth.authenticate(token))
+        .ok_or_else(|| Response::error(StatusCode::UNAUTHORIZED, "Missing or invalid bearer token"))
+}
+
+/// Refuses a request naming a user other than the caller.
+fn check_user(caller: Uuid, user_id: Uuid) -> Result<(), Response> {
+    if user_id != caller {
+        return Err(Response::error(StatusCode::FORBIDDEN, format!("Not authorized for user {}", user_id)));
+    }
+    Ok(())
+}
+
+async fn place_order(State(api): State<Api>, headers: HeaderMap, body: Bytes) -> Result<Response, Response> {
+    let caller = caller(&api, &headers)?;
+    let request: PlaceRequest = serde_json::from_slice(&body)
+        .map_err(|e| Response::error(StatusCode::BAD_REQUEST, format!("Bad order: {}", e)))?;
+    check_user(caller, request.user_id)?;
+    match api.engine.handle_command(OrderCommand::PlaceOrder(request.into())).await {
+        Ok(events) => Ok(Response::with_status(StatusCode::CREATED, events)),
+        Err(e) => Err(Response::from_engine_error(e)),
+    }
+}
+
+async fn cancel_order(
+    State(api): State<Api>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Response, Response> {
+    let caller = caller(&api, &headers)?;
+    let order_id: Uuid = id
+        .parse()
+        .map_err(|_| Response::error(StatusCode::BAD_REQUEST, format!("Bad order id {}", id)))?;
+    let Some(order) = api.engine.get_order(order_id) else {
+        return Err(Response::error(StatusCode::NOT_FOUND, format!("Order {} not found", order_id)));
+    };
+    check_user(caller, order.user_id)?;
+    let command = CancelOrderCommand {
+        order_id,
+        client_order_id: None,
+        user_id: caller,
+        symbol: order.symbol,
+        timestamp: Utc::now(),
+    };
+    match api.engine.handle_command(OrderCommand::CancelOrder(command)).await {
+        Ok(events) => Ok(Response::ok(events)),
+        Err(e) => Err(Response::from_engine_error(e)),
+    }
+}
+
+async fn list_orders(
+    State(api): State<Api>,
+    headers: HeaderMap,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<Response, Response> {
+    let caller = caller(&api, &headers)?;
+    if let Some(user_id) = parse_param(&query, "user_id")? {
+        check_user(caller, user_id)?;
+    }
+    let statuses = match query.get("status") {
+        Some(statuses) => Some(
+            statuses
+                .split(',')
+                .map(|status| parse_enum(status, "status"))
+                .collect::<Result<_, _>>()?,
+        ),
+        None => None,
+    };
+    let filter = OrderFilter {
+        user_id: Some(caller),
+        symbol: query.get("symbol").cloned(),
+        statuses,
+        side: query.get("side").map(|side| parse_enum(side, "side")).transpose()?,
+        ..OrderFilter::default()
+    };
+    Ok(Response::ok(api.engine.get_orders_filtered(&filter)))
+}
+
+async fn list_trades(
+    State(api): State<Api>,
+    headers: HeaderMap,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<Response, Response> {
+    let limit = parse_param(&query, "limit")?.unwrap_or(DEFAULT_PAGE_SIZE);
+    if let Some(symbol) = query.get("symbol") {
+        let since: Option<DateTime<Utc>> = parse_param(&query, "since")?;
+        return Ok(Response::ok(api.engine.get_trades_by_symbol(symbol, since, limit)));
+    }
+    let caller = caller(&api, &headers)?;
+    if let Some(user_id) = parse_param(&query, "user_id")? {
+        check_user(caller, user_id)?;
+    }
+    let offset = parse_param(&query, "offset")?.unwrap_or(0);
+    Ok(Response::ok(api.engine.get_trades_by_user(caller, Pagination::new(offset, limit))))
+}
+
+async fn order_book(
+    State(api): State<Api>,
+    Path(symbol): Path<String>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<Response, Response> {
+    let levels = parse_param(&query, "levels")?.unwrap_or(DEFAULT_DEPTH_LEVELS);
+    Ok(Response::ok(api.engine.get_l2_snapshot(&symbol, levels)))
+}
+
+fn parse_param<T: std::str::FromStr>(query: &HashMap<String, String>, name: &str) -> Result<Option<T>, Response> {
+    query
+        .get(name)
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| Response::error(StatusCode::BAD_REQUEST, format!("Bad {} {}", name, value)))
+        })
+        .transpose()
+}
+
+/// Parses a unit enum variant by its serialized name.
+fn parse_enum<T: serde::de::DeserializeOwned>(value: &str, name: &str) -> Result<T, Response> {
+    serde_json::from_value(Value::String(value.to_string()))
+        .map_err(|_| Response::error(StatusCode::BAD_REQUEST, format!("Bad {} {}", name, value)))
+}
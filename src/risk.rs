@@ -0,0 +1,154 @@
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::commands::PlaceOrderCommand;
+use crate::engine::MatchingEngine;
+use crate::error::RejectReason;
+use crate::positions::Position;
+use crate::types::OrderSide;
+
+/// What a `RiskCheck` sees of the market and of the user placing an order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskContext {
+    pub last_price: Option<Decimal>,
+    pub best_bid: Option<Decimal>,
+    pub best_ask: Option<Decimal>,
+    /// Price times remaining quantity over the user's open orders on every
+    /// symbol, stop orders at their stop price. Market orders never rest
+    /// and are not counted.
+    pub open_notional: Decimal,
+    pub position: Position,
+}
+
+impl RiskContext {
+    /// The last trade price, or the midpoint if the symbol has not traded.
+    pub fn reference_price(&self) -> Option<Decimal> {
+        self.last_price
+            .or_else(|| Some((self.best_bid? + self.best_ask?) / Decimal::TWO))
+    }
+}
+
+/// A pre-trade check run on every new order after validation and before it
+/// reaches the book.
+#[async_trait]
+pub trait RiskCheck: Send + Sync {
+    async fn check(&self, cmd: &PlaceOrderCommand, ctx: &RiskContext) -> Result<(), RejectReason>;
+}
+
+/// The order's notional: its limit price, or the reference price for
+/// market orders, times its quantity.
+fn order_notional(cmd: &PlaceOrderCommand, ctx: &RiskContext) -> Option<Decimal> {
+    cmd.price.or(ctx.reference_price())?.checked_mul(cmd.quantity)
+}
+
+/// Rejects limit orders priced further than `max_deviation_percent` from
+/// the reference price. Orders on a symbol with no reference pass.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PriceDeviationCheck {
+    pub max_deviation_percent: Decimal,
+}
+
+#[async_trait]
+impl RiskCheck for PriceDeviationCheck {
+    async fn check(&self, cmd: &PlaceOrderCommand, ctx: &RiskContext) -> Result<(), RejectReason> {
+        let (Some(price), Some(reference_price)) = (cmd.price, ctx.reference_price()) else {
+            return Ok(());
+        };
+        let deviation = (price - reference_price).abs() / reference_price * Decimal::ONE_HUNDRED;
+        if deviation > self.max_deviation_percent {
+            return Err(RejectReason::PriceDeviationTooLarge {
+                price,
+                reference_price,
+                max_deviation_percent: self.max_deviation_percent,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Rejects orders whose notional exceeds `max_notional`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MaxOrderNotionalCheck {
+    pub max_notional: Decimal,
+}
+
+#[async_trait]
+impl RiskCheck for MaxOrderNotionalCheck {
+    async fn check(&self, cmd: &PlaceOrderCommand, ctx: &RiskContext) -> Result<(), RejectReason> {
+        let notional = order_notional(cmd, ctx).unwrap_or(Decimal::MAX);
+        if notional > self.max_notional {
+            return Err(RejectReason::OrderNotionalTooLarge {
+                notional,
+                limit: self.max_notional,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Rejects orders that would take the notional of the user's open orders
+/// beyond `max_open_notional`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MaxOpenNotionalCheck {
+    pub max_open_notional: Decimal,
+}
+
+#[async_trait]
+impl RiskCheck for MaxOpenNotionalCheck {
+    async fn check(&self, cmd: &PlaceOrderCommand, ctx: &RiskContext) -> Result<(), RejectReason> {
+        let notional = order_notional(cmd, ctx)
+            .and_then(|notional| notional.checked_add(ctx.open_notional))
+            .unwrap_or(Decimal::MAX);
+        if notional > self.max_open_notional {
+            return Err(RejectReason::OpenNotionalTooLarge {
+                notional,
+                limit: self.max_open_notional,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl MatchingEngine {
+    /// Appends `check` to the risk checks. Checks run in the order they
+    /// were added and the first rejection refuses the order.
+    pub fn with_risk_check(mut self, check: impl RiskCheck + 'static) -> Self {
+        self.risk_checks.push(Box::new(check));
+        self
+    }
+
+    /// The context `cmd` is checked in. `replacing` is an open order the
+    /// command takes the place of, left out of the open notional.
+    pub fn risk_context(&self, cmd: &PlaceOrderCommand, replacing: Option<Uuid>) -> RiskContext {
+        let best = |side| self.order_books.get(&cmd.symbol).and_then(|book| book.best(side));
+        RiskContext {
+            last_price: self.last_prices.get(&cmd.symbol).map(|price| *price),
+            best_bid: best(OrderSide::Buy),
+            best_ask: best(OrderSide::Sell),
+            open_notional: self
+                .get_open_orders(cmd.user_id)
+                .iter()
+                .filter(|order| Some(order.id) != replacing)
+                .filter_map(|order| Some(order.price.or(order.stop_price)? * order.remaining_quantity()))
+                .sum(),
+            position: self.get_position(cmd.user_id, &cmd.symbol),
+        }
+    }
+
+    pub(crate) async fn run_risk_checks(
+        &self,
+        cmd: &PlaceOrderCommand,
+        replacing: Option<Uuid>,
+    ) -> Result<(), RejectReason> {
+        if self.risk_checks.is_empty() {
+            return Ok(());
+        }
+        let ctx = self.risk_context(cmd, replacing);
+        for check in &self.risk_checks {
+            check.check(cmd, &ctx).await?;
+        }
+        Ok(())
+    }
+}
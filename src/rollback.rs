@@ -0,0 +1,112 @@
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::book::BookSnapshot;
+use crate::candles::SymbolCandles;
+use crate::engine::MatchingEngine;
+use crate::persistence::PersistenceFailurePolicy;
+use crate::types::{Order, OrderSide};
+
+/// What a symbol looked like before a command, for undoing it.
+pub(crate) struct SymbolCheckpoint {
+    symbol: String,
+    book: Option<BookSnapshot>,
+    open_orders: Vec<Order>,
+    stop_orders: Option<Vec<Uuid>>,
+    last_price: Option<Decimal>,
+    order_count: usize,
+    trade_count: usize,
+    candles: Option<SymbolCandles>,
+}
+
+impl MatchingEngine {
+    pub(crate) fn rolls_back_on_failure(&self) -> bool {
+        self.persistence_policy == PersistenceFailurePolicy::Rollback
+    }
+
+    pub(crate) fn checkpoint_symbols(&self, symbols: &[String]) -> Vec<SymbolCheckpoint> {
+        symbols
+            .iter()
+            .map(|symbol| SymbolCheckpoint {
+                symbol: symbol.clone(),
+                book: self.order_books.get(symbol).map(|book| book.snapshot()),
+                open_orders: self
+                    .order_index
+                    .open_symbol_orders(symbol)
+                    .into_iter()
+                    .filter_map(|order_id| self.get_order(order_id))
+                    .collect(),
+                stop_orders: self.stop_orders.get(symbol).map(|parked| parked.clone()),
+                last_price: self.last_prices.get(symbol).map(|price| *price),
+                order_count: self.order_index.symbol_order_count(symbol),
+                trade_count: self.trade_log.symbol_trade_count(symbol),
+                candles: self.candles.checkpoint(symbol),
+            })
+            .collect()
+    }
+
+    /// Puts each symbol's books, orders, trades and stops back as they were
+    /// checkpointed. Book deltas already published are not taken back.
+    pub(crate) fn roll_back(&self, checkpoints: Vec<SymbolCheckpoint>) {
+        for checkpoint in checkpoints {
+            let symbol = &checkpoint.symbol;
+            // Trades first, while the orders they settled are still there
+            let undone: Vec<Uuid> = self
+                .trade_log
+                .truncate_symbol(symbol, checkpoint.trade_count)
+                .into_iter()
+                .map(|trade| {
+                    let user = |order_id| self.orders.get(&order_id).map(|o| o.user_id);
+                    if let Some(taker_user_id) = user(trade.taker_order_id) {
+                        let maker_user_id = user(trade.maker_order_id);
+                        self.record_position(&trade, taker_user_id, maker_user_id, true);
+                        self.unsettle_trade(&trade, taker_user_id, maker_user_id);
+                    }
+                    trade.id
+                })
+                .collect();
+            for order_id in self.order_index.truncate_symbol(symbol, checkpoint.order_count) {
+                if let Some((_, order)) = self.orders.remove(&order_id) {
+                    self.forget_client_order_id(&order);
+                }
+                self.release_hold(order_id);
+            }
+            for trade_id in &undone {
+                self.trades.remove(trade_id);
+            }
+            self.discard_pending_trades(&undone);
+            self.market_data.forget(symbol, &undone);
+            self.candles.restore(symbol, checkpoint.candles);
+            self.feeds.discard_trades(symbol);
+            for order in checkpoint.open_orders {
+                self.order_index.reopen(&order);
+                self.sync_hold(&order);
+                self.orders.insert(order.id, order);
+            }
+
+            match checkpoint.book {
+                Some(snapshot) => {
+                    let mut book = self.book_mut(symbol);
+                    for (side, orders) in [(OrderSide::Buy, snapshot.bids), (OrderSide::Sell, snapshot.asks)] {
+                        let book = book.side_mut(side);
+                        book.clear();
+                        for order in orders {
+                            book.insert(order);
+                        }
+                    }
+                }
+                None => {
+                    self.order_books.remove(symbol);
+                }
+            }
+            match checkpoint.stop_orders {
+                Some(parked) => self.stop_orders.insert(symbol.clone(), parked),
+                None => self.stop_orders.remove(symbol).map(|(_, parked)| parked),
+            };
+            match checkpoint.last_price {
+                Some(price) => self.last_prices.insert(symbol.clone(), price),
+                None => self.last_prices.remove(symbol).map(|(_, price)| price),
+            };
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use async_trait::async_trait;
+use object_store::aws::{AmazonS3, AmazonS3Builder};
+use object_store::path::Path;
+use object_store::{ObjectStore as _, PutPayload};
+
+use crate::archive::ObjectStore;
+
+/// A bucket on S3 or an S3-compatible service, through the `object_store`
+/// crate's client, which signs requests with SigV4 and speaks HTTPS.
+pub struct S3ObjectStore {
+    bucket: AmazonS3,
+}
+
+impl S3ObjectStore {
+    /// The bucket `bucket` at `endpoint`, an `https://` URL, addressed path
+    /// style as S3-compatible services expect.
+    pub fn new(endpoint: &str, bucket: &str, region: &str, access_key: &str, secret_key: &str) -> Result<Self, String> {
+        if !endpoint.starts_with("https://") {
+            return Err(format!("Unsupported S3 endpoint {}: only https:// is spoken", endpoint));
+        }
+        Self::from_builder(
+            AmazonS3Builder::new()
+                .with_endpoint(endpoint)
+                .with_bucket_name(bucket)
+                .with_region(region)
+                .with_access_key_id(access_key)
+                .with_secret_access_key(secret_key)
+                .with_virtual_hosted_style_request(false),
+        )
+    }
+
+    /// A bucket configured on `builder`, for AWS itself with credentials
+    /// from the environment or instance metadata, or for settings `new`
+    /// does not take.
+    pub fn from_builder(builder: AmazonS3Builder) -> Result<Self, String> {
+        let bucket = builder.build().map_err(|e| e.to_string())?;
+        Ok(Self { bucket })
+    }
+}
+
+#[async_trait]
+impl ObjectStore for S3ObjectStore {
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<(), String> {
+        self.bucket
+            .put(&Path::from(key), PutPayload::from(bytes))
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        let object = match self.bucket.get(&Path::from(key)).await {
+            Ok(object) => object,
+            Err(object_store::Error::NotFound { .. }) => return Ok(None),
+            Err(e) => return Err(e.to_string()),
+        };
+        let bytes = object.bytes().await.map_err(|e| e.to_string())?;
+        Ok(Some(bytes.to_vec()))
+    }
+}
@@ -0,0 +1,264 @@
+//! WebSocket market data and order entry on axum. `serve_websocket`
+//! accepts connections, streams the depth, trades and ticker channels a
+//! client subscribes to, and turns its place and cancel messages into
+//! commands for the user the connection authenticated as.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::auth::{bearer_token, Authenticator};
+use crate::commands::{CancelRequest, OrderCommand, PlaceRequest};
+use crate::depth::L2Snapshot;
+use crate::engine::MatchingEngine;
+use crate::events::OrderEvent;
+use crate::market_data::Ticker;
+use crate::types::Trade;
+
+/// Largest message a client may send.
+const MAX_MESSAGE_SIZE: usize = 1 << 20;
+/// Messages queued for a client before its feeds wait on it.
+const OUTGOING_CAPACITY: usize = 1024;
+/// Subscriptions one connection may hold at once.
+const MAX_SUBSCRIPTIONS: usize = 64;
+const DEFAULT_DEPTH_LEVELS: usize = 10;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Channel {
+    Depth,
+    Trades,
+    Ticker,
+}
+
+/// What a client sends, tagged by `op`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Subscribe {
+        channel: Channel,
+        symbol: String,
+        /// Levels per side of a depth subscription.
+        #[serde(default)]
+        levels: Option<usize>,
+    },
+    Unsubscribe {
+        channel: Channel,
+        symbol: String,
+    },
+    Place(PlaceRequest),
+    Cancel(CancelRequest),
+}
+
+/// What the server sends, tagged by `type`. A subscription starts with the
+/// current depth or ticker, then follows the engine's feeds.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Subscribed { channel: Channel, symbol: String },
+    Unsubscribed { channel: Channel, symbol: String },
+    Trade { data: Trade },
+    Depth { data: L2Snapshot },
+    Ticker { data: Ticker },
+    /// The events of a place or cancel.
+    Events { data: Vec<OrderEvent> },
+    Error { message: String },
+}
+
+impl ServerMessage {
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("server messages serialize")
+    }
+}
+
+#[derive(Clone)]
+struct Api {
+    engine: Arc<MatchingEngine>,
+    auth: Arc<dyn Authenticator>,
+}
+
+/// Accepts WebSocket connections on `listener` until it fails. Clients
+/// subscribe to depth, trade and ticker channels and place and cancel
+/// orders with JSON text messages.
+///
+/// A client authenticates when it connects, with an `Authorization:
+/// Bearer` header or, for browsers, which cannot set one, a `token` query
+/// parameter; `auth` maps the token to the user, and the connection only
+/// places and cancels that user's orders. There is no TLS; put a
+/// terminating proxy in front of it.
+pub async fn serve_websocket(
+    engine: Arc<MatchingEngine>,
+    listener: TcpListener,
+    auth: Arc<dyn Authenticator>,
+) -> Result<(), String> {
+    axum::serve(listener, websocket_router(engine, auth)).await.map_err(|e| e.to_string())
+}
+
+/// The WebSocket endpoint `serve_websocket` serves at `/`, to mount
+/// alongside other routes.
+pub fn websocket_router(engine: Arc<MatchingEngine>, auth: Arc<dyn Authenticator>) -> Router {
+    Router::new().route("/", get(upgrade)).with_state(Api { engine, auth })
+}
+
+async fn upgrade(
+    State(api): State<Api>,
+    headers: HeaderMap,
+    Query(query): Query<HashMap<String, String>>,
+    ws: WebSocketUpgrade,
+) -> axum::response::Response {
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(bearer_token)
+        .or(query.get("token").map(String::as_str));
+    let Some(user_id) = token.and_then(|token| api.auth.authenticate(token)) else {
+        return (StatusCode::UNAUTHORIZED, "Missing or invalid bearer token").into_response();
+    };
+    ws.max_message_size(MAX_MESSAGE_SIZE)
+        .on_upgrade(move |socket| connection(api.engine, user_id, socket))
+}
+
+async fn connection(engine: Arc<MatchingEngine>, user_id: Uuid, mut socket: WebSocket) {
+    let (outgoing, mut queued) = mpsc::channel(OUTGOING_CAPACITY);
+    let mut subscriptions = HashMap::new();
+    loop {
+        let message = tokio::select! {
+            received = socket.recv() => {
+                let request = match received {
+                    Some(Ok(Message::Text(text))) => serde_json::from_str::<ClientMessage>(&text),
+                    Some(Ok(Message::Binary(_))) => {
+                        let reply = ServerMessage::Error {
+                            message: "Messages are JSON text".to_string(),
+                        };
+                        if socket.send(Message::Text(reply.to_json().into())).await.is_err() {
+                            break;
+                        }
+                        continue;
+                    }
+                    // Pings are answered by the socket itself
+                    Some(Ok(Message::Ping(_) | Message::Pong(_))) => continue,
+                    Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                };
+                let reply = match request {
+                    Ok(request) => handle_message(&engine, user_id, request, &outgoing, &mut subscriptions).await,
+                    Err(e) => ServerMessage::Error {
+                        message: format!("Bad message: {}", e),
+                    },
+                };
+                reply.to_json()
+            }
+            Some(message) = queued.recv() => message,
+        };
+        if socket.send(Message::Text(message.into())).await.is_err() {
+            break;
+        }
+    }
+    for (_, feed) in subscriptions {
+        feed.abort();
+    }
+}
+
+async fn handle_message(
+    engine: &Arc<MatchingEngine>,
+    user_id: Uuid,
+    request: ClientMessage,
+    outgoing: &mpsc::Sender<String>,
+    subscriptions: &mut HashMap<(Channel, String), JoinHandle<()>>,
+) -> ServerMessage {
+    let command = match request {
+        ClientMessage::Subscribe { channel, symbol, levels } => {
+            let key = (channel, symbol.clone());
+            if subscriptions.len() >= MAX_SUBSCRIPTIONS && !subscriptions.contains_key(&key) {
+                return ServerMessage::Error {
+                    message: format!("At most {} subscriptions per connection", MAX_SUBSCRIPTIONS),
+                };
+            }
+            let feed = subscribe(engine, channel, &symbol, levels.unwrap_or(DEFAULT_DEPTH_LEVELS), outgoing.clone());
+            if let Some(previous) = subscriptions.insert(key, feed) {
+                previous.abort();
+            }
+            return ServerMessage::Subscribed { channel, symbol };
+        }
+        ClientMessage::Unsubscribe { channel, symbol } => {
+            if let Some(feed) = subscriptions.remove(&(channel, symbol.clone())) {
+                feed.abort();
+            }
+            return ServerMessage::Unsubscribed { channel, symbol };
+        }
+        ClientMessage::Place(request) if request.user_id == user_id => OrderCommand::PlaceOrder(request.into()),
+        ClientMessage::Cancel(request) if request.user_id == user_id => OrderCommand::CancelOrder(request.into()),
+        ClientMessage::Place(PlaceRequest { user_id, .. }) | ClientMessage::Cancel(CancelRequest { user_id, .. }) => {
+            return ServerMessage::Error {
+                message: format!("Not authorized for user {}", user_id),
+            };
+        }
+    };
+    match engine.handle_command(command).await {
+        Ok(events) => ServerMessage::Events { data: events },
+        Err(e) => ServerMessage::Error { message: e.to_string() },
+    }
+}
+
+/// Starts forwarding a feed to the client, beginning with the current
+/// depth or ticker.
+fn subscribe(
+    engine: &MatchingEngine,
+    channel: Channel,
+    symbol: &str,
+    levels: usize,
+    outgoing: mpsc::Sender<String>,
+) -> JoinHandle<()> {
+    match channel {
+        Channel::Trades => tokio::spawn(forward(engine.subscribe_trades(symbol), None, outgoing, |data| {
+            ServerMessage::Trade { data }
+        })),
+        Channel::Depth => {
+            let current = Arc::new(engine.get_l2_snapshot(symbol, levels));
+            tokio::spawn(forward(engine.subscribe_depth(symbol, levels), Some(current), outgoing, |data| {
+                ServerMessage::Depth { data: (*data).clone() }
+            }))
+        }
+        Channel::Ticker => {
+            let current = engine.get_ticker(symbol);
+            tokio::spawn(forward(engine.subscribe_ticker(symbol), Some(current), outgoing, |data| {
+                ServerMessage::Ticker { data }
+            }))
+        }
+    }
+}
+
+async fn forward<T: Clone>(
+    mut feed: broadcast::Receiver<T>,
+    current: Option<T>,
+    outgoing: mpsc::Sender<String>,
+    wrap: fn(T) -> ServerMessage,
+) {
+    if let Some(current) = current {
+        if outgoing.send(wrap(current).to_json()).await.is_err() {
+            return;
+        }
+    }
+    loop {
+        let message = match feed.recv().await {
+            Ok(value) => wrap(value),
+            Err(broadcast::error::RecvError::Lagged(missed)) => ServerMessage::Error {
+                message: format!("Missed {} messages", missed),
+            },
+            Err(broadcast::error::RecvError::Closed) => return,
+        };
+        if outgoing.send(message.to_json()).await.is_err() {
+            return;
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::accounts::symbol_assets;
+use crate::engine::MatchingEngine;
+use crate::events::OrderEvent;
+use crate::types::OrderSide;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SettlementDirection {
+    Debit,
+    Credit,
+}
+
+/// Moves `amount` of `asset` out of or into the user's account.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SettlementInstruction {
+    pub user_id: Uuid,
+    pub asset: String,
+    pub direction: SettlementDirection,
+    pub amount: Decimal,
+}
+
+/// What the fills of one command move, netted per user and asset. Users
+/// whose fills cancel out get no instruction for the asset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SettlementBatch {
+    pub batch_id: Uuid,
+    pub fill_count: usize,
+    pub instructions: Vec<SettlementInstruction>,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[async_trait]
+pub trait SettlementSink: Send + Sync {
+    async fn settle(&self, batch: SettlementBatch) -> Result<(), String>;
+}
+
+/// The engine's settlement sink and the batches not yet delivered to it.
+pub(crate) struct Settlement {
+    sink: Box<dyn SettlementSink>,
+    pending: tokio::sync::Mutex<Vec<SettlementBatch>>,
+}
+
+impl MatchingEngine {
+    /// Sends `sink` a batch of settlement instructions for every command
+    /// that executed trades and every trade busted, once the events are
+    /// saved. Fills on symbols not named `BASE/QUOTE` are left out.
+    pub fn with_settlement_sink(mut self, sink: impl SettlementSink + 'static) -> Self {
+        self.settlement = Some(Settlement {
+            sink: Box::new(sink),
+            pending: tokio::sync::Mutex::new(Vec::new()),
+        });
+        self
+    }
+
+    /// Delivers the batches still waiting for the settlement sink, oldest
+    /// first. Batches a failed delivery left behind are also retried after
+    /// the next command.
+    pub async fn flush_settlements(&self) -> Result<(), String> {
+        let Some(settlement) = &self.settlement else {
+            return Ok(());
+        };
+        let mut pending = settlement.pending.lock().await;
+        while let Some(batch) = pending.first() {
+            settlement.sink.settle(batch.clone()).await?;
+            pending.remove(0);
+        }
+        Ok(())
+    }
+
+    /// Nets the fills among a command's events into a batch, busted trades
+    /// counting as fills taken back.
+    pub(crate) fn settlement_batch(&self, events: &[OrderEvent]) -> Option<SettlementBatch> {
+        self.settlement.as_ref()?;
+        let mut net: BTreeMap<(Uuid, String), Decimal> = BTreeMap::new();
+        let mut fill_count = 0;
+        for event in events {
+            // A busted trade moves everything back
+            let (symbol, taker, maker, side, price, quantity) = match event {
+                OrderEvent::OrderMatched(e) => (&e.symbol, e.order_id, e.matched_order_id, e.side, e.price, e.quantity),
+                OrderEvent::TradeBusted(e) => {
+                    (&e.symbol, e.taker_order_id, e.maker_order_id, e.side, e.price, -e.quantity)
+                }
+                _ => continue,
+            };
+            let Ok((base, quote)) = symbol_assets(symbol) else {
+                continue;
+            };
+            let user = |order_id: Uuid| self.orders.get(&order_id).map(|o| o.user_id);
+            let buyer_and_seller = match side {
+                OrderSide::Buy => (user(taker), user(maker)),
+                OrderSide::Sell => (user(maker), user(taker)),
+            };
+            let notional = price * quantity;
+            if let (Some(buyer), _) = buyer_and_seller {
+                *net.entry((buyer, base.to_string())).or_default() += quantity;
+                *net.entry((buyer, quote.to_string())).or_default() -= notional;
+            }
+            if let (_, Some(seller)) = buyer_and_seller {
+                *net.entry((seller, base.to_string())).or_default() -= quantity;
+                *net.entry((seller, quote.to_string())).or_default() += notional;
+            }
+            fill_count += 1;
+        }
+        if fill_count == 0 {
+            return None;
+        }
+        let instructions = net
+            .into_iter()
+            .filter(|(_, amount)| !amount.is_zero())
+            .map(|((user_id, asset), amount)| SettlementInstruction {
+                user_id,
+                asset,
+                direction: if amount.is_sign_negative() {
+                    SettlementDirection::Debit
+                } else {
+                    SettlementDirection::Credit
+                },
+                amount: amount.abs(),
+            })
+            .collect();
+        Some(SettlementBatch {
+            batch_id: Uuid::new_v4(),
+            fill_count,
+            instructions,
+            timestamp: self.clock.now(),
+        })
+    }
+
+    pub(crate) async fn queue_settlement(&self, batch: Option<SettlementBatch>) {
+        if let (Some(settlement), Some(batch)) = (&self.settlement, batch) {
+            settlement.pending.lock().await.push(batch);
+        }
+    }
+}
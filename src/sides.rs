@@ -0,0 +1,87 @@
+use std::ops::{Deref, DerefMut};
+
+use rust_decimal::Decimal;
+
+use crate::orderbook::{LevelIter, OrderBookSide};
+
+/// Buy orders kept in an `S`. The highest price is best.
+#[derive(Debug, Clone, Default)]
+pub struct Bids<S>(pub S);
+
+/// Sell orders kept in an `S`. The lowest price is best.
+#[derive(Debug, Clone, Default)]
+pub struct Asks<S>(pub S);
+
+impl<S: OrderBookSide> Bids<S> {
+    /// Whether `a` outranks `b` as a bid.
+    pub fn is_better(a: Decimal, b: Decimal) -> bool {
+        a > b
+    }
+
+    pub fn best(&self) -> Option<Decimal> {
+        self.0.highest_price()
+    }
+
+    pub fn worst(&self) -> Option<Decimal> {
+        self.0.lowest_price()
+    }
+
+    /// Levels best first.
+    pub fn levels(&self) -> LevelIter<'_> {
+        Box::new(self.0.iter_levels().rev())
+    }
+
+    /// The best bid and ask if the bid has reached the ask.
+    pub fn crosses<T: OrderBookSide>(&self, asks: &Asks<T>) -> Option<(Decimal, Decimal)> {
+        let (bid, ask) = (self.best()?, asks.best()?);
+        (bid >= ask).then_some((bid, ask))
+    }
+}
+
+impl<S: OrderBookSide> Asks<S> {
+    /// Whether `a` outranks `b` as an ask.
+    pub fn is_better(a: Decimal, b: Decimal) -> bool {
+        a < b
+    }
+
+    pub fn best(&self) -> Option<Decimal> {
+        self.0.lowest_price()
+    }
+
+    pub fn worst(&self) -> Option<Decimal> {
+        self.0.highest_price()
+    }
+
+    /// Levels best first.
+    pub fn levels(&self) -> LevelIter<'_> {
+        self.0.iter_levels()
+    }
+}
+
+impl<S> Deref for Bids<S> {
+    type Target = S;
+
+    fn deref(&self) -> &S {
+        &self.0
+    }
+}
+
+impl<S> DerefMut for Bids<S> {
+    fn deref_mut(&mut self) -> &mut S {
+        &mut self.0
+    }
+}
+
+impl<S> Deref for Asks<S> {
+    type Target = S;
+
+    fn deref(&self) -> &S {
+        &self.0
+    }
+}
+
+impl<S> DerefMut for Asks<S> {
+    fn deref_mut(&mut self) -> &mut S {
+        &mut self.0
+    }
+}
@@ -0,0 +1,197 @@
+use chrono::{DateTime, Duration, Utc};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::commands::{CancelOrderCommand, OrderCommand, PlaceOrderCommand};
+use crate::types::{OrderSide, OrderType};
+
+/// Most placed orders an `OrderFlow` remembers as cancel targets.
+const MAX_CANCEL_TARGETS: usize = 10_000;
+
+/// Relative weights of the commands an `OrderFlow` generates.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FlowMix {
+    pub limit: u32,
+    pub market: u32,
+    pub stop: u32,
+    pub cancel: u32,
+}
+
+impl Default for FlowMix {
+    fn default() -> Self {
+        Self {
+            limit: 60,
+            market: 10,
+            stop: 5,
+            cancel: 25,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowConfig {
+    pub symbol: String,
+    /// Mean arrivals per second of simulated time. Gaps between arrivals
+    /// are exponentially distributed, so arrivals form a Poisson process.
+    pub arrival_rate: f64,
+    pub start_price: Decimal,
+    pub tick_size: Decimal,
+    /// Most ticks the reference price moves, either way, between two
+    /// arrivals.
+    pub max_step_ticks: i64,
+    /// Most ticks a limit or stop order is placed away from the reference
+    /// price. A fifth of that range falls on the far side, so some limit
+    /// orders cross the book.
+    pub max_offset_ticks: i64,
+    /// Orders are for one to `max_lots` lots.
+    pub lot_size: Decimal,
+    pub max_lots: i64,
+    pub users: usize,
+    pub mix: FlowMix,
+}
+
+impl Default for FlowConfig {
+    fn default() -> Self {
+        Self {
+            symbol: "BTC/USDT".to_string(),
+            arrival_rate: 1000.0,
+            start_price: Decimal::from(100),
+            tick_size: Decimal::new(1, 2),
+            max_step_ticks: 5,
+            max_offset_ticks: 50,
+            lot_size: Decimal::new(1, 1),
+            max_lots: 20,
+            users: 50,
+            mix: FlowMix::default(),
+        }
+    }
+}
+
+/// An endless stream of synthetic commands on one symbol around a
+/// reference price that follows a random walk. Timestamps are simulated
+/// and start at creation.
+pub struct OrderFlow {
+    config: FlowConfig,
+    rng: StdRng,
+    users: Vec<Uuid>,
+    /// Orders placed that may still be open, with their users.
+    placed: Vec<(Uuid, Uuid)>,
+    price: Decimal,
+    now: DateTime<Utc>,
+}
+
+impl OrderFlow {
+    pub fn new(config: FlowConfig) -> Self {
+        Self::with_rng(config, StdRng::from_os_rng())
+    }
+
+    /// The same seed and config give the same commands, timestamps aside.
+    pub fn with_seed(config: FlowConfig, seed: u64) -> Self {
+        Self::with_rng(config, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(config: FlowConfig, mut rng: StdRng) -> Self {
+        let users = (0..config.users.max(1))
+            .map(|_| uuid::Builder::from_random_bytes(rng.random()).into_uuid())
+            .collect();
+        Self {
+            price: config.start_price,
+            config,
+            rng,
+            users,
+            placed: Vec::new(),
+            now: Utc::now(),
+        }
+    }
+
+    /// The reference price as of the latest arrival.
+    pub fn price(&self) -> Decimal {
+        self.price
+    }
+
+    /// Simulated time of the latest arrival.
+    pub fn now(&self) -> DateTime<Utc> {
+        self.now
+    }
+
+    pub fn next_command(&mut self) -> OrderCommand {
+        let gap = -(1.0 - self.rng.random::<f64>()).ln() / self.config.arrival_rate;
+        self.now += Duration::nanoseconds((gap * 1e9) as i64);
+        let step = self.rng.random_range(-self.config.max_step_ticks..=self.config.max_step_ticks);
+        self.price = (self.price + self.config.tick_size * Decimal::from(step)).max(self.config.tick_size);
+
+        let mix = self.config.mix;
+        let mut pick = self.rng.random_range(0..(mix.limit + mix.market + mix.stop + mix.cancel).max(1));
+        if pick >= mix.limit + mix.market + mix.stop && !self.placed.is_empty() {
+            let (order_id, user_id) = self.placed.swap_remove(self.rng.random_range(0..self.placed.len()));
+            return OrderCommand::CancelOrder(CancelOrderCommand {
+                order_id,
+                client_order_id: None,
+                user_id,
+                symbol: self.config.symbol.clone(),
+                timestamp: self.now,
+            });
+        }
+        pick %= (mix.limit + mix.market + mix.stop).max(1);
+
+        let side = if self.rng.random_bool(0.5) { OrderSide::Buy } else { OrderSide::Sell };
+        let (order_type, price, stop_price) = if pick < mix.limit {
+            let offset = self.rng.random_range(-self.config.max_offset_ticks / 5..=self.config.max_offset_ticks);
+            (OrderType::Limit, Some(self.away_from_price(side, offset)), None)
+        } else if pick < mix.limit + mix.market {
+            (OrderType::Market, None, None)
+        } else {
+            // Buy stops above the price, sell stops below it.
+            let offset = self.rng.random_range(1..=self.config.max_offset_ticks.max(1));
+            (OrderType::StopLoss, None, Some(self.away_from_price(side, -offset)))
+        };
+
+        let order_id = uuid::Builder::from_random_bytes(self.rng.random()).into_uuid();
+        let user_id = self.users[self.rng.random_range(0..self.users.len())];
+        if order_type != OrderType::Market {
+            if self.placed.len() >= MAX_CANCEL_TARGETS {
+                self.placed.swap_remove(0);
+            }
+            self.placed.push((order_id, user_id));
+        }
+        OrderCommand::PlaceOrder(PlaceOrderCommand {
+            order_id,
+            client_order_id: None,
+            user_id,
+            symbol: self.config.symbol.clone(),
+            order_type,
+            side,
+            price,
+            quantity: self.config.lot_size * Decimal::from(self.rng.random_range(1..=self.config.max_lots.max(1))),
+            iceberg_visible_quantity: None,
+            stop_price,
+            trailing_stop_price: None,
+            displayed: true,
+            max_fills: None,
+            referrer_id: None,
+            quote_quantity: None,
+            timestamp: self.now,
+        })
+    }
+
+    /// `ticks` below the reference price for buys, above it for sells, and
+    /// never below one tick.
+    fn away_from_price(&self, side: OrderSide, ticks: i64) -> Decimal {
+        let distance = self.config.tick_size * Decimal::from(ticks);
+        let price = match side {
+            OrderSide::Buy => self.price - distance,
+            OrderSide::Sell => self.price + distance,
+        };
+        price.max(self.config.tick_size)
+    }
+}
+
+impl Iterator for OrderFlow {
+    type Item = OrderCommand;
+
+    fn next(&mut self) -> Option<OrderCommand> {
+        Some(self.next_command())
+    }
+}
@@ -0,0 +1,66 @@
+use std::path::Path;
+
+use crate::kv_store::{KvBackend, KvPair};
+
+/// A `KvBackend` on a sled database in a directory, for a `KvEventStore`
+/// that survives restarts without a database server. Each batch is
+/// applied atomically and flushed to disk before `write_batch` returns.
+pub struct SledKv {
+    db: sled::Db,
+}
+
+impl SledKv {
+    /// Opens the database in `dir`, creating it if it does not exist.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, String> {
+        let db = sled::open(dir).map_err(|e| e.to_string())?;
+        Ok(Self { db })
+    }
+}
+
+fn pair((key, value): (sled::IVec, sled::IVec)) -> KvPair {
+    (key.to_vec(), value.to_vec())
+}
+
+impl KvBackend for SledKv {
+    fn write_batch(&self, batch: Vec<KvPair>) -> Result<(), String> {
+        let mut writes = sled::Batch::default();
+        for (key, value) in batch {
+            writes.insert(key, value);
+        }
+        self.db.apply_batch(writes).map_err(|e| e.to_string())?;
+        self.db.flush().map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, String> {
+        self.db
+            .get(key)
+            .map(|value| value.map(|value| value.to_vec()))
+            .map_err(|e| e.to_string())
+    }
+
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<Vec<KvPair>, String> {
+        self.db
+            .scan_prefix(prefix)
+            .map(|entry| entry.map(pair).map_err(|e| e.to_string()))
+            .collect()
+    }
+
+    fn last_with_prefix(&self, prefix: &[u8]) -> Result<Option<KvPair>, String> {
+        self.db
+            .scan_prefix(prefix)
+            .next_back()
+            .transpose()
+            .map(|entry| entry.map(pair))
+            .map_err(|e| e.to_string())
+    }
+
+    fn scan_from(&self, prefix: &[u8], start: &[u8], limit: usize) -> Result<Vec<KvPair>, String> {
+        self.db
+            .range(start..)
+            .take_while(|entry| entry.as_ref().map_or(true, |(key, _)| key.starts_with(prefix)))
+            .take(limit)
+            .map(|entry| entry.map(pair).map_err(|e| e.to_string()))
+            .collect()
+    }
+}
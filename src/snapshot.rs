@@ -0,0 +1,317 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::book::BookSnapshot;
+use crate::bracket::BracketGroup;
+use crate::commands::OrderCommand;
+use crate::engine::MatchingEngine;
+use crate::idempotency::ClientOrderRecord;
+use crate::instant::Instant;
+use crate::kill_switch::KillSwitchTarget;
+use crate::trading_state::SymbolState;
+use crate::types::Order;
+
+const SNAPSHOT_EXTENSION: &str = "json";
+
+/// Orders, brackets, client order ids and the states of symbols as of
+/// `sequence`, the last logged command the snapshot includes (0 without a
+/// WAL). Trades are not part of it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineSnapshot {
+    pub sequence: u64,
+    pub taken_at: DateTime<Utc>,
+    pub books: BTreeMap<String, BookSnapshot>,
+    /// Parked stop orders per symbol, in trigger order.
+    pub stop_orders: BTreeMap<String, Vec<Order>>,
+    pub last_prices: BTreeMap<String, Decimal>,
+    /// Orders neither resting nor parked: finished ones, so their ids stay
+    /// taken, and bracket exits waiting on their entry.
+    #[serde(default)]
+    pub orders: Vec<Order>,
+    #[serde(default)]
+    pub brackets: Vec<BracketGroup>,
+    #[serde(default)]
+    pub client_orders: Vec<ClientOrderRecord>,
+    #[serde(default)]
+    pub symbol_states: BTreeMap<String, SymbolState>,
+    /// The state each symbol's trading schedule last moved it into.
+    #[serde(default)]
+    pub scheduled_states: BTreeMap<String, SymbolState>,
+    /// Commands held per symbol until it trades again, oldest first.
+    #[serde(default)]
+    pub queued_commands: BTreeMap<String, Vec<OrderCommand>>,
+    /// Active kill switches, with when each was activated.
+    #[serde(default)]
+    pub kill_switches: Vec<(KillSwitchTarget, DateTime<Utc>)>,
+}
+
+#[async_trait]
+pub trait SnapshotStore: Send + Sync {
+    async fn save_snapshot(&self, snapshot: &EngineSnapshot) -> Result<(), String>;
+    async fn latest_snapshot(&self) -> Result<Option<EngineSnapshot>, String>;
+}
+
+#[derive(Default)]
+pub struct InMemorySnapshotStore {
+    latest: Mutex<Option<EngineSnapshot>>,
+}
+
+impl InMemorySnapshotStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl SnapshotStore for InMemorySnapshotStore {
+    async fn save_snapshot(&self, snapshot: &EngineSnapshot) -> Result<(), String> {
+        *self.latest.lock().unwrap() = Some(snapshot.clone());
+        Ok(())
+    }
+
+    async fn latest_snapshot(&self) -> Result<Option<EngineSnapshot>, String> {
+        Ok(self.latest.lock().unwrap().clone())
+    }
+}
+
+/// One JSON file per snapshot, named by sequence. Each is written to a
+/// side file and renamed into place, and older ones are deleted once the
+/// new one is down.
+pub struct FileSnapshotStore {
+    dir: PathBuf,
+}
+
+impl FileSnapshotStore {
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, String> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create snapshot dir {}: {}", dir.display(), e))?;
+        Ok(Self { dir })
+    }
+
+    fn snapshot_files(&self) -> Result<Vec<(u64, u64, PathBuf)>, String> {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&self.dir).map_err(|e| e.to_string())? {
+            let path = entry.map_err(|e| e.to_string())?.path();
+            if path.extension().is_none_or(|ext| ext != SNAPSHOT_EXTENSION) {
+                continue;
+            }
+            let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+            if let Some((sequence, taken_at)) = stem.split_once('-') {
+                if let (Ok(sequence), Ok(taken_at)) = (sequence.parse(), taken_at.parse()) {
+                    files.push((sequence, taken_at, path));
+                }
+            }
+        }
+        files.sort_unstable();
+        Ok(files)
+    }
+}
+
+#[async_trait]
+impl SnapshotStore for FileSnapshotStore {
+    async fn save_snapshot(&self, snapshot: &EngineSnapshot) -> Result<(), String> {
+        let bytes = serde_json::to_vec(snapshot).map_err(|e| e.to_string())?;
+        // The timestamp tells apart snapshots taken without new commands
+        let name = format!(
+            "{:020}-{:020}.{}",
+            snapshot.sequence,
+            snapshot.taken_at.timestamp_nanos_opt().unwrap_or_default().max(0),
+            SNAPSHOT_EXTENSION
+        );
+        let staged = self.dir.join(format!("{}.tmp", name));
+        let mut file = File::create(&staged).map_err(|e| e.to_string())?;
+        file.write_all(&bytes).map_err(|e| e.to_string())?;
+        file.sync_data().map_err(|e| e.to_string())?;
+        let path = self.dir.join(name);
+        fs::rename(&staged, &path).map_err(|e| e.to_string())?;
+
+        for (_, _, older) in self.snapshot_files()? {
+            if older != path {
+                let _ = fs::remove_file(older);
+            }
+        }
+        Ok(())
+    }
+
+    async fn latest_snapshot(&self) -> Result<Option<EngineSnapshot>, String> {
+        let Some((_, _, path)) = self.snapshot_files()?.pop() else {
+            return Ok(None);
+        };
+        let bytes = fs::read(&path).map_err(|e| e.to_string())?;
+        serde_json::from_slice(&bytes).map(Some).map_err(|e| e.to_string())
+    }
+}
+
+/// When the engine snapshots itself. Either trigger is enough; with
+/// neither set, snapshots are only taken through `take_snapshot`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SnapshotCadence {
+    pub every_commands: Option<u64>,
+    pub every: Option<Duration>,
+}
+
+pub(crate) struct SnapshotState {
+    pub(crate) store: Box<dyn SnapshotStore>,
+    cadence: SnapshotCadence,
+    commands_since: AtomicU64,
+    last_taken: Mutex<Instant>,
+}
+
+impl SnapshotState {
+    fn due(&self) -> bool {
+        let commands = self.commands_since.load(Ordering::SeqCst);
+        self.cadence.every_commands.is_some_and(|every| commands >= every)
+            || self
+                .cadence
+                .every
+                .is_some_and(|every| commands > 0 && self.last_taken.lock().unwrap().elapsed() >= every)
+    }
+}
+
+impl MatchingEngine {
+    /// Snapshots into `store` on `cadence`, checked after each command run
+    /// through `handle_command`. With a WAL, each snapshot also empties it.
+    pub fn with_snapshots(mut self, store: impl SnapshotStore + 'static, cadence: SnapshotCadence) -> Self {
+        self.snapshots = Some(SnapshotState {
+            store: Box::new(store),
+            cadence,
+            commands_since: AtomicU64::new(0),
+            last_taken: Mutex::new(Instant::now()),
+        });
+        self
+    }
+
+    /// Saves a snapshot once commands in flight have finished and their
+    /// queued events are saved, then empties the WAL it covers. Refused
+    /// while events dropped from the queue have only the WAL to regenerate
+    /// them.
+    pub async fn take_snapshot(&self) -> Result<EngineSnapshot, String> {
+        let snapshots = self.snapshots.as_ref().ok_or("No snapshot store configured")?;
+        let _gate = self.command_gate.write().await;
+        self.drain_event_writer().await?;
+        if self.wal.is_some() && self.dropped_event_count() > 0 {
+            return Err(format!(
+                "{} dropped events are only in the WAL; recover them before snapshotting",
+                self.dropped_event_count()
+            ));
+        }
+        let snapshot = self.capture_snapshot();
+        snapshots.store.save_snapshot(&snapshot).await?;
+        snapshots.commands_since.store(0, Ordering::SeqCst);
+        *snapshots.last_taken.lock().unwrap() = Instant::now();
+        if let Some(wal) = &self.wal {
+            wal.clear()?;
+        }
+        Ok(snapshot)
+    }
+
+    /// Counts a command and snapshots if the cadence says so. A snapshot
+    /// that fails is tried again after the next command.
+    pub(crate) async fn snapshot_if_due(&self) {
+        let Some(snapshots) = &self.snapshots else {
+            return;
+        };
+        snapshots.commands_since.fetch_add(1, Ordering::SeqCst);
+        if snapshots.due() {
+            let _ = self.take_snapshot().await;
+        }
+    }
+
+    fn capture_snapshot(&self) -> EngineSnapshot {
+        let mut snapshot = EngineSnapshot {
+            sequence: self.wal.as_ref().map_or(0, |wal| wal.last_sequence()),
+            taken_at: self.clock.now(),
+            books: BTreeMap::new(),
+            stop_orders: BTreeMap::new(),
+            last_prices: BTreeMap::new(),
+            orders: Vec::new(),
+            brackets: self.brackets.iter().map(|group| group.value().clone()).collect(),
+            client_orders: self.client_order_ids.records(),
+            symbol_states: self.symbol_states.iter().map(|s| (s.key().clone(), *s.value())).collect(),
+            scheduled_states: self.scheduled_states.iter().map(|s| (s.key().clone(), *s.value())).collect(),
+            queued_commands: self
+                .queued_commands
+                .iter()
+                .map(|queue| (queue.key().clone(), queue.value().iter().cloned().collect()))
+                .collect(),
+            kill_switches: self.kill_switches.iter().map(|s| (s.key().clone(), *s.value())).collect(),
+        };
+        for book in self.order_books.iter() {
+            snapshot.books.insert(book.key().clone(), book.snapshot());
+        }
+        for parked in self.stop_orders.iter() {
+            let orders: Vec<Order> = parked.iter().filter_map(|id| self.get_order(*id)).collect();
+            if !orders.is_empty() {
+                snapshot.stop_orders.insert(parked.key().clone(), orders);
+            }
+        }
+        let kept: HashSet<_> = snapshot
+            .books
+            .values()
+            .flat_map(|book| book.bids.iter().chain(&book.asks))
+            .chain(snapshot.stop_orders.values().flatten())
+            .map(|order| order.id)
+            .collect();
+        snapshot.orders = self
+            .orders
+            .iter()
+            .filter(|order| !kept.contains(order.key()))
+            .map(|order| order.value().clone())
+            .collect();
+        snapshot.orders.sort_by_key(|order| order.created_at);
+        for price in self.last_prices.iter() {
+            snapshot.last_prices.insert(price.key().clone(), *price.value());
+        }
+        snapshot
+    }
+
+    pub(crate) fn load_snapshot(&self, snapshot: EngineSnapshot) -> Result<(), String> {
+        for (symbol, book) in &snapshot.books {
+            let bytes = serde_json::to_vec(book).map_err(|e| e.to_string())?;
+            self.restore_book(symbol, &bytes)?;
+        }
+        for order in snapshot.stop_orders.values().flatten() {
+            self.orders.insert(order.id, order.clone());
+            self.order_index.insert(order);
+            self.register_client_order_id(order);
+            self.park_stop_order(order);
+        }
+        for order in &snapshot.orders {
+            self.orders.insert(order.id, order.clone());
+            self.order_index.insert(order);
+        }
+        for (symbol, price) in snapshot.last_prices {
+            self.last_prices.insert(symbol, price);
+        }
+        for group in snapshot.brackets {
+            for order_id in [group.entry_order_id, group.stop_loss_order_id, group.take_profit_order_id] {
+                self.order_brackets.insert(order_id, group.bracket_id);
+            }
+            self.brackets.insert(group.bracket_id, group);
+        }
+        self.client_order_ids.restore(snapshot.client_orders);
+        for (symbol, state) in snapshot.symbol_states {
+            self.symbol_states.insert(symbol, state);
+        }
+        for (symbol, state) in snapshot.scheduled_states {
+            self.scheduled_states.insert(symbol, state);
+        }
+        for (symbol, commands) in snapshot.queued_commands {
+            self.queued_commands.insert(symbol, commands.into());
+        }
+        for (target, activated_at) in snapshot.kill_switches {
+            self.kill_switches.insert(target, activated_at);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,186 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use dashmap::DashMap;
+use rust_decimal::Decimal;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+
+use crate::events::OrderEvent;
+
+const FEED_CHANNEL_CAPACITY: usize = 4096;
+
+/// Which saved events a subscription receives. Unset fields match
+/// everything.
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    pub symbol: Option<String>,
+    /// Events of the user's orders, fills and matches included.
+    pub user_id: Option<Uuid>,
+    /// `OrderEvent::kind` names.
+    pub kinds: Option<Vec<String>>,
+}
+
+impl EventFilter {
+    fn matches(&self, event: &OrderEvent, users: &[Uuid]) -> bool {
+        self.symbol.as_ref().is_none_or(|symbol| event.symbol() == Some(symbol))
+            && self.user_id.is_none_or(|wanted| users.contains(&wanted))
+            && self.kinds.as_ref().is_none_or(|kinds| kinds.iter().any(|kind| kind == event.kind()))
+    }
+}
+
+/// An event with the users whose orders it concerns, resolved on publish.
+#[derive(Debug)]
+struct Published {
+    event: OrderEvent,
+    users: Vec<Uuid>,
+}
+
+/// Fans saved events out to subscribers. Stores call `publish` once a batch
+/// is durable. Matches only carry order ids, so the feed remembers each
+/// order's user from the event that placed it until matches fill the order
+/// or it is canceled.
+#[derive(Debug)]
+pub struct EventFeed {
+    sender: broadcast::Sender<Arc<Published>>,
+    owners: DashMap<Uuid, Owner>,
+}
+
+/// The user of a live order, with how much of it has matched.
+#[derive(Debug, Clone, Copy)]
+struct Owner {
+    user_id: Uuid,
+    quantity: Decimal,
+    filled: Decimal,
+}
+
+impl Default for EventFeed {
+    fn default() -> Self {
+        Self {
+            sender: broadcast::channel(FEED_CHANNEL_CAPACITY).0,
+            owners: DashMap::new(),
+        }
+    }
+}
+
+impl EventFeed {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe(&self, filter: EventFilter) -> EventSubscription {
+        EventSubscription {
+            receiver: self.sender.subscribe(),
+            filter,
+        }
+    }
+
+    /// How many orders the feed is remembering the user of.
+    pub fn tracked_orders(&self) -> usize {
+        self.owners.len()
+    }
+
+    pub fn publish(&self, events: &[OrderEvent]) {
+        for event in events {
+            let users = event_users(event, |order_id| self.owners.get(&order_id).map(|owner| owner.user_id));
+            match event {
+                OrderEvent::OrderPlaced(e) => {
+                    let owner = Owner {
+                        user_id: e.user_id,
+                        quantity: e.quantity,
+                        filled: Decimal::ZERO,
+                    };
+                    self.owners.insert(e.order_id, owner);
+                }
+                OrderEvent::OrderUpdated(e) => {
+                    if let (Some(mut owner), Some(quantity)) = (self.owners.get_mut(&e.order_id), e.new_quantity) {
+                        owner.quantity = quantity;
+                    }
+                }
+                OrderEvent::OrderMatched(e) => {
+                    self.record_fill(e.order_id, e.quantity);
+                    self.record_fill(e.matched_order_id, e.quantity);
+                }
+                // Rejections are never an order's last event: rejected
+                // orders were never placed, and a reused id names a live one
+                OrderEvent::OrderFilled(_) | OrderEvent::OrderCanceled(_) => {
+                    if let Some(order_id) = event.order_id() {
+                        self.owners.remove(&order_id);
+                    }
+                }
+                _ => {}
+            }
+            if self.sender.receiver_count() > 0 {
+                let _ = self.sender.send(Arc::new(Published {
+                    event: event.clone(),
+                    users,
+                }));
+            }
+        }
+    }
+
+    /// Forgets the user of `order_id` once matches leave nothing of it.
+    fn record_fill(&self, order_id: Uuid, quantity: Decimal) {
+        let filled = self.owners.get_mut(&order_id).is_some_and(|mut owner| {
+            owner.filled += quantity;
+            owner.filled >= owner.quantity
+        });
+        if filled {
+            self.owners.remove(&order_id);
+        }
+    }
+}
+
+/// The users whose orders `event` concerns, with `owner` giving the user
+/// that placed an order for events that only name the order.
+pub(crate) fn event_users(event: &OrderEvent, owner: impl Fn(Uuid) -> Option<Uuid>) -> Vec<Uuid> {
+    let mut users = Vec::new();
+    match event {
+        OrderEvent::OrderMatched(e) => {
+            users.extend(owner(e.order_id));
+            users.extend(owner(e.matched_order_id));
+            // A user trading against themselves
+            users.dedup();
+        }
+        _ => users.extend(event.user_id().or_else(|| event.order_id().and_then(owner))),
+    }
+    users
+}
+
+/// Events saved after the subscription was taken, filtered. A subscriber
+/// that falls more than a few thousand events behind gets
+/// `RecvError::Lagged` and should catch up with `EventStore::read_from`.
+pub struct EventSubscription {
+    receiver: broadcast::Receiver<Arc<Published>>,
+    filter: EventFilter,
+}
+
+impl EventSubscription {
+    pub async fn recv(&mut self) -> Result<OrderEvent, RecvError> {
+        loop {
+            let published = self.receiver.recv().await?;
+            if self.filter.matches(&published.event, &published.users) {
+                return Ok(published.event.clone());
+            }
+        }
+    }
+}
+
+/// Waits until something new is saved, as `wakeups` reports it, or until
+/// `poll_interval` has passed. Without a subscription, or once it closes,
+/// only the interval is waited out.
+pub(crate) async fn wait_for_events(wakeups: &mut Option<EventSubscription>, poll_interval: Duration) {
+    let Some(subscription) = wakeups else {
+        tokio::time::sleep(poll_interval).await;
+        return;
+    };
+    tokio::select! {
+        received = subscription.recv() => {
+            if matches!(received, Err(RecvError::Closed)) {
+                *wakeups = None;
+            }
+        }
+        _ = tokio::time::sleep(poll_interval) => {}
+    }
+}
@@ -0,0 +1,252 @@
+use std::collections::{HashMap, VecDeque};
+
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::events::OrderEvent;
+use crate::projection::Projection;
+use crate::types::OrderSide;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AlertKind {
+    /// A user's order traded against another of their own.
+    WashTrade,
+    /// A burst of orders canceled soon after being placed, unfilled.
+    Spoofing,
+    /// Aggressive trades that pushed the price one way, then a trade the
+    /// other way by the same user.
+    MomentumIgnition,
+}
+
+/// A pattern for compliance to review.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SurveillanceAlert {
+    /// Position among the view's alerts, from 1.
+    pub sequence: u64,
+    pub kind: AlertKind,
+    pub user_id: Uuid,
+    pub symbol: String,
+    /// Ids of the events the pattern was found in, oldest first.
+    pub evidence: Vec<Uuid>,
+    /// When the last of those events happened.
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Thresholds for the patterns `SurveillanceView` flags.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SurveillanceConfig {
+    /// Orders canceled unfilled sooner than this after being placed count
+    /// toward spoofing.
+    pub min_order_lifetime: Duration,
+    /// How many such cancels by a user on a symbol within `spoofing_window`
+    /// raise an alert.
+    pub spoofing_cancels: usize,
+    pub spoofing_window: Duration,
+    /// How far, in percent, a user's aggressive trades must move the price
+    /// within `momentum_window` for a trade of theirs the other way to
+    /// raise an alert.
+    pub momentum_move_percent: Decimal,
+    pub momentum_window: Duration,
+}
+
+impl Default for SurveillanceConfig {
+    fn default() -> Self {
+        Self {
+            min_order_lifetime: Duration::seconds(1),
+            spoofing_cancels: 5,
+            spoofing_window: Duration::seconds(10),
+            momentum_move_percent: Decimal::TWO,
+            momentum_window: Duration::seconds(60),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PlacedOrder {
+    user_id: Uuid,
+    placed_at: DateTime<Utc>,
+    event_id: Uuid,
+    traded: bool,
+}
+
+/// An order canceled unfilled soon after it was placed.
+#[derive(Debug)]
+struct QuickCancel {
+    canceled_at: DateTime<Utc>,
+    /// Its placed and canceled events.
+    evidence: [Uuid; 2],
+}
+
+/// Aggressive trades of a user in one direction.
+#[derive(Debug)]
+struct Run {
+    side: OrderSide,
+    first_price: Decimal,
+    last_price: Decimal,
+    started_at: DateTime<Utc>,
+    evidence: Vec<Uuid>,
+}
+
+impl Run {
+    fn moved_percent(&self) -> Decimal {
+        let moved = match self.side {
+            OrderSide::Buy => self.last_price - self.first_price,
+            OrderSide::Sell => self.first_price - self.last_price,
+        };
+        moved / self.first_price * Decimal::ONE_HUNDRED
+    }
+}
+
+/// Flags wash trades, spoofing and momentum ignition in the event log.
+/// The patterns are heuristics over each user's own events: alerts call
+/// for review, not action.
+#[derive(Debug, Default)]
+pub struct SurveillanceView {
+    config: SurveillanceConfig,
+    orders: HashMap<Uuid, PlacedOrder>,
+    quick_cancels: HashMap<(Uuid, String), VecDeque<QuickCancel>>,
+    runs: HashMap<(Uuid, String), Run>,
+    alerts: Vec<SurveillanceAlert>,
+}
+
+impl SurveillanceView {
+    pub fn new(config: SurveillanceConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// The alerts after `sequence`, oldest first; 0 for all of them.
+    pub fn alerts_since(&self, sequence: u64) -> &[SurveillanceAlert] {
+        &self.alerts[(sequence as usize).min(self.alerts.len())..]
+    }
+
+    fn raise(&mut self, kind: AlertKind, user_id: Uuid, symbol: &str, evidence: Vec<Uuid>, timestamp: DateTime<Utc>) {
+        self.alerts.push(SurveillanceAlert {
+            sequence: self.alerts.len() as u64 + 1,
+            kind,
+            user_id,
+            symbol: symbol.to_string(),
+            evidence,
+            timestamp,
+        });
+    }
+
+    fn on_cancel(&mut self, order_id: Uuid, symbol: &str, event_id: Uuid, timestamp: DateTime<Utc>) {
+        let Some(order) = self.orders.remove(&order_id) else {
+            return;
+        };
+        if order.traded || timestamp - order.placed_at >= self.config.min_order_lifetime {
+            return;
+        }
+        let key = (order.user_id, symbol.to_string());
+        let cancels = self.quick_cancels.entry(key).or_default();
+        cancels.push_back(QuickCancel {
+            canceled_at: timestamp,
+            evidence: [order.event_id, event_id],
+        });
+        while cancels.front().is_some_and(|c| timestamp - c.canceled_at > self.config.spoofing_window) {
+            cancels.pop_front();
+        }
+        if cancels.len() >= self.config.spoofing_cancels {
+            let evidence = cancels.drain(..).flat_map(|c| c.evidence).collect();
+            self.raise(AlertKind::Spoofing, order.user_id, symbol, evidence, timestamp);
+        }
+    }
+
+    /// Tracks the user's run of aggressive trades and flags a fill against
+    /// its direction once the run has moved the price far enough.
+    fn on_fill(
+        &mut self,
+        user_id: Uuid,
+        symbol: &str,
+        side: OrderSide,
+        aggressive: bool,
+        price: Decimal,
+        event: &OrderEvent,
+    ) {
+        let (event_id, timestamp) = (event.event_id(), event.timestamp());
+        let key = (user_id, symbol.to_string());
+        let window = self.config.momentum_window;
+        if self.runs.get(&key).is_some_and(|run| timestamp - run.started_at > window) {
+            self.runs.remove(&key);
+        }
+        if let Some(run) = self.runs.get(&key).filter(|run| run.side != side) {
+            if run.moved_percent() >= self.config.momentum_move_percent {
+                let mut evidence = self.runs.remove(&key).map(|run| run.evidence).unwrap_or_default();
+                evidence.push(event_id);
+                self.raise(AlertKind::MomentumIgnition, user_id, symbol, evidence, timestamp);
+                return;
+            }
+        }
+        if !aggressive {
+            return;
+        }
+        match self.runs.get_mut(&key).filter(|run| run.side == side) {
+            Some(run) => {
+                run.last_price = price;
+                run.evidence.push(event_id);
+            }
+            None => {
+                self.runs.insert(key, Run {
+                    side,
+                    first_price: price,
+                    last_price: price,
+                    started_at: timestamp,
+                    evidence: vec![event_id],
+                });
+            }
+        }
+    }
+}
+
+impl Projection for SurveillanceView {
+    fn apply(&mut self, event: &OrderEvent) {
+        match event {
+            OrderEvent::OrderPlaced(e) => {
+                self.orders.insert(e.order_id, PlacedOrder {
+                    user_id: e.user_id,
+                    placed_at: e.timestamp,
+                    event_id: e.event_id,
+                    traded: false,
+                });
+            }
+            OrderEvent::OrderCanceled(e) => self.on_cancel(e.order_id, &e.symbol, e.event_id, e.timestamp),
+            OrderEvent::OrderFilled(e) => {
+                self.orders.remove(&e.order_id);
+            }
+            OrderEvent::OrderMatched(e) => {
+                let mut owner = |order_id: Uuid| {
+                    self.orders.get_mut(&order_id).map(|order| {
+                        order.traded = true;
+                        (order.user_id, order.event_id)
+                    })
+                };
+                let (taker, maker) = (owner(e.order_id), owner(e.matched_order_id));
+                if let (Some((taker, taker_placed)), Some((maker, maker_placed))) = (taker, maker) {
+                    if taker == maker {
+                        let evidence = vec![maker_placed, taker_placed, e.event_id];
+                        self.raise(AlertKind::WashTrade, taker, &e.symbol, evidence, e.timestamp);
+                    }
+                }
+                if let Some((taker, _)) = taker {
+                    self.on_fill(taker, &e.symbol, e.side, true, e.price, event);
+                }
+                if let Some((maker, _)) = maker {
+                    self.on_fill(maker, &e.symbol, e.side.opposite(), false, e.price, event);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn reset(&mut self) {
+        self.orders.clear();
+        self.quick_cancels.clear();
+        self.runs.clear();
+        self.alerts.clear();
+    }
+}
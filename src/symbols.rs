@@ -0,0 +1,111 @@
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::circuit_breaker::CircuitBreakerConfig;
+use crate::fees::FeeSchedule;
+use crate::ladder_book::PriceLadder;
+use crate::lifecycle::TradingSchedule;
+use crate::matching::MatchingAlgorithm;
+use crate::synthetic::SyntheticLegs;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SymbolConfig {
+    pub symbol: String,
+    pub matching_algorithm: MatchingAlgorithm,
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+    /// Decimal places prices are rounded to; None leaves them as given.
+    #[serde(default)]
+    pub price_scale: Option<u32>,
+    /// Decimal places quantities are truncated to; None leaves them as given.
+    #[serde(default)]
+    pub quantity_scale: Option<u32>,
+    /// Fixed price grid. Set, the symbol's book is a `LadderOrderBook` and
+    /// prices off the ladder are rejected. Takes effect if registered
+    /// before the first order rests.
+    #[serde(default)]
+    pub ladder: Option<PriceLadder>,
+    /// Limit orders priced more than this percentage away from the
+    /// reference price are rejected with `PriceOutOfBand`. None accepts any
+    /// price.
+    #[serde(default)]
+    pub price_band_percent: Option<Decimal>,
+    /// Smallest quantity an order may have.
+    #[serde(default)]
+    pub min_order_quantity: Option<Decimal>,
+    /// Bounds on price times quantity; market orders are valued at the
+    /// reference price.
+    #[serde(default)]
+    pub min_notional: Option<Decimal>,
+    #[serde(default)]
+    pub max_notional: Option<Decimal>,
+    /// Fees reported on each trade; None charges nothing.
+    #[serde(default)]
+    pub fees: Option<FeeSchedule>,
+    /// When the symbol opens for trading; it is `Closed` until then.
+    #[serde(default)]
+    pub listed_at: Option<DateTime<Utc>>,
+    /// Trading hours and days; None trades around the clock.
+    #[serde(default)]
+    pub schedule: Option<TradingSchedule>,
+    /// Routes the symbol's orders through two legs instead of a book of
+    /// its own.
+    #[serde(default)]
+    pub synthetic: Option<SyntheticLegs>,
+}
+
+impl SymbolConfig {
+    pub fn new(symbol: &str) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            matching_algorithm: MatchingAlgorithm::default(),
+            circuit_breaker: None,
+            price_scale: None,
+            quantity_scale: None,
+            ladder: None,
+            price_band_percent: None,
+            min_order_quantity: None,
+            min_notional: None,
+            max_notional: None,
+            fees: None,
+            listed_at: None,
+            schedule: None,
+            synthetic: None,
+        }
+    }
+}
+
+/// Per-symbol trading configuration. Symbols that were never registered
+/// trade with `SymbolConfig::new` defaults.
+#[derive(Debug, Default)]
+pub struct SymbolRegistry {
+    configs: DashMap<String, SymbolConfig>,
+}
+
+impl SymbolRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `config`, replacing any earlier config of its symbol.
+    pub fn register(&self, config: SymbolConfig) -> Result<(), String> {
+        if let Some(schedule) = &config.schedule {
+            schedule.validate().map_err(|e| format!("{}: {}", config.symbol, e))?;
+        }
+        self.configs.insert(config.symbol.clone(), config);
+        Ok(())
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<SymbolConfig> {
+        self.configs.get(symbol).map(|c| c.clone())
+    }
+
+    pub fn get_or_default(&self, symbol: &str) -> SymbolConfig {
+        self.get(symbol).unwrap_or_else(|| SymbolConfig::new(symbol))
+    }
+
+    pub fn symbols(&self) -> Vec<String> {
+        self.configs.iter().map(|c| c.key().clone()).collect()
+    }
+}
@@ -0,0 +1,256 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::commands::PlaceOrderCommand;
+use crate::draws::new_event_id;
+use crate::engine::MatchingEngine;
+use crate::events::{OrderEvent, SyntheticTradeExecutedEvent};
+use crate::trading_state::SymbolState;
+use crate::types::{Order, OrderSide, OrderStatus, OrderType};
+
+/// A pair without its own book, e.g. ETH/BTC, executed through two legs that
+/// share a common quote currency, e.g. ETH/USDT and BTC/USDT.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyntheticPair {
+    pub symbol: String,
+    pub base_leg: String,
+    pub quote_leg: String,
+}
+
+impl SyntheticPair {
+    pub fn new(symbol: &str, base_leg: &str, quote_leg: &str) -> Result<Self, String> {
+        let split = |s: &str| {
+            s.split_once('/')
+                .map(|(base, quote)| (base.to_string(), quote.to_string()))
+                .ok_or_else(|| format!("Symbol {} is not of the form BASE/QUOTE", s))
+        };
+        let (base, quote) = split(symbol)?;
+        let (base_leg_base, base_leg_quote) = split(base_leg)?;
+        let (quote_leg_base, quote_leg_quote) = split(quote_leg)?;
+
+        if base_leg_base != base || quote_leg_base != quote || base_leg_quote != quote_leg_quote {
+            return Err(format!(
+                "{} cannot be derived from {} and {}",
+                symbol, base_leg, quote_leg
+            ));
+        }
+        Ok(Self {
+            symbol: symbol.to_string(),
+            base_leg: base_leg.to_string(),
+            quote_leg: quote_leg.to_string(),
+        })
+    }
+}
+
+/// The legs a symbol registered with `SymbolConfig::synthetic` routes
+/// through instead of trading on a book of its own.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyntheticLegs {
+    pub base_leg: String,
+    pub quote_leg: String,
+}
+
+/// Executable terms for a synthetic order against the current books.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyntheticQuote {
+    pub symbol: String,
+    pub side: OrderSide,
+    pub quantity: Decimal,
+    /// Synthetic price in quote-leg base units per synthetic base unit.
+    pub price: Decimal,
+    /// Amount of the shared currency moved through the base leg.
+    pub intermediate_amount: Decimal,
+    /// Quantity traded on the quote leg.
+    pub quote_leg_quantity: Decimal,
+}
+
+enum SweepTarget {
+    Base(Decimal),
+    Quote(Decimal),
+}
+
+impl MatchingEngine {
+    pub fn register_synthetic_pair(&self, pair: SyntheticPair) {
+        self.synthetic_pairs.insert(pair.symbol.clone(), pair);
+    }
+
+    pub fn get_synthetic_pair(&self, symbol: &str) -> Option<SyntheticPair> {
+        self.synthetic_route(symbol).ok().flatten()
+    }
+
+    /// The pair orders on `symbol` are routed through, either registered
+    /// directly or configured in the symbol registry. Fails if the
+    /// configured legs cannot derive the symbol.
+    pub(crate) fn synthetic_route(&self, symbol: &str) -> Result<Option<SyntheticPair>, String> {
+        if let Some(pair) = self.synthetic_pairs.get(symbol) {
+            return Ok(Some(pair.clone()));
+        }
+        match self.symbols.get(symbol).and_then(|config| config.synthetic) {
+            Some(legs) => SyntheticPair::new(symbol, &legs.base_leg, &legs.quote_leg).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Prices a synthetic order by walking both leg books without touching
+    /// them. Returns None if either leg cannot fill completely.
+    pub fn quote_synthetic(&self, symbol: &str, side: OrderSide, quantity: Decimal) -> Option<SyntheticQuote> {
+        let pair = self.get_synthetic_pair(symbol)?;
+        if quantity <= Decimal::ZERO {
+            return None;
+        }
+
+        let (intermediate_amount, quote_leg_quantity) = match side {
+            // Buy the base asset, then raise the shared currency by selling
+            // the quote asset
+            OrderSide::Buy => {
+                let (_, cost) = self.sweep(&pair.base_leg, OrderSide::Buy, SweepTarget::Base(quantity))?;
+                let (sold, _) = self.sweep(&pair.quote_leg, OrderSide::Sell, SweepTarget::Quote(cost))?;
+                (cost, sold)
+            }
+            // Sell the base asset, then spend the proceeds on the quote asset
+            OrderSide::Sell => {
+                let (_, proceeds) = self.sweep(&pair.base_leg, OrderSide::Sell, SweepTarget::Base(quantity))?;
+                let (bought, _) = self.sweep(&pair.quote_leg, OrderSide::Buy, SweepTarget::Quote(proceeds))?;
+                (proceeds, bought)
+            }
+        };
+
+        Some(SyntheticQuote {
+            symbol: symbol.to_string(),
+            side,
+            quantity,
+            price: quote_leg_quantity / quantity,
+            intermediate_amount,
+            quote_leg_quantity,
+        })
+    }
+
+    /// Walks the book a taker on `side` would hit until `target` is met,
+    /// returning (base quantity, quote notional). None if liquidity runs out
+    /// or the notional overflows.
+    fn sweep(&self, symbol: &str, side: OrderSide, target: SweepTarget) -> Option<(Decimal, Decimal)> {
+        let book = self.order_books.get(symbol)?;
+        let levels = book.iter_levels(side.opposite());
+
+        let (mut base, mut notional) = (Decimal::ZERO, Decimal::ZERO);
+        for (price, level) in levels {
+            let available = level.total_quantity();
+            let wanted = match target {
+                SweepTarget::Base(quantity) => quantity - base,
+                SweepTarget::Quote(amount) => (amount - notional) / price,
+            };
+            let take = wanted.min(available);
+            base += take;
+            notional = notional.checked_add(take.checked_mul(price)?)?;
+            if take == wanted {
+                return Some((base, notional));
+            }
+        }
+        None
+    }
+
+    /// Executes an order on a synthetic symbol as two market legs. The
+    /// order is all-or-nothing: nothing trades unless both legs can fill
+    /// completely and, for limit orders, the synthetic price is acceptable.
+    pub(crate) async fn handle_synthetic_order(
+        &self,
+        pair: SyntheticPair,
+        cmd: PlaceOrderCommand,
+        mut events: Vec<OrderEvent>,
+    ) -> Result<Vec<OrderEvent>, String> {
+        if !matches!(cmd.order_type, OrderType::Market | OrderType::Limit) {
+            return Err("Synthetic pairs only accept market and limit orders".to_string());
+        }
+        if self.accounts.is_some() {
+            return Err("Synthetic pairs are not accepted while accounts are kept".to_string());
+        }
+        for leg in [&pair.base_leg, &pair.quote_leg] {
+            events.extend(self.lift_expired_circuit_breaker(leg).await?);
+            let state = self.symbol_state(leg);
+            if state != SymbolState::Trading {
+                return Err(format!("Leg {} of {} is {:?}", leg, pair.symbol, state));
+            }
+        }
+        let mut resumed = self.check_persistence_halt(&pair.base_leg).await?;
+        resumed.extend(self.check_persistence_halt(&pair.quote_leg).await?);
+
+        let quote = self
+            .quote_synthetic(&pair.symbol, cmd.side, cmd.quantity)
+            .ok_or_else(|| format!("Insufficient leg liquidity for {}", pair.symbol))?;
+        if let Some(limit) = cmd.price {
+            let acceptable = match cmd.side {
+                OrderSide::Buy => quote.price <= limit,
+                OrderSide::Sell => quote.price >= limit,
+            };
+            if !acceptable {
+                return Err(format!(
+                    "Synthetic price {} for {} is outside the limit {}",
+                    quote.price, pair.symbol, limit
+                ));
+            }
+        }
+
+        let mut order = Self::order_from_command(&cmd);
+        self.store_order(&order, &mut events);
+
+        let (base_leg_side, quote_leg_side) = match cmd.side {
+            OrderSide::Buy => (OrderSide::Buy, OrderSide::Sell),
+            OrderSide::Sell => (OrderSide::Sell, OrderSide::Buy),
+        };
+        let base_leg = self.leg_order(&cmd, &pair.base_leg, base_leg_side, cmd.quantity);
+        let quote_leg = self.leg_order(&cmd, &pair.quote_leg, quote_leg_side, quote.quote_leg_quantity);
+        let (base_leg_order_id, quote_leg_order_id) = (base_leg.id, quote_leg.id);
+
+        let mut leg_trade_ids = Vec::new();
+        for leg in [base_leg, quote_leg] {
+            let leg_id = leg.id;
+            let trades = self.submit_order(leg, &mut events);
+            leg_trade_ids.extend(
+                trades
+                    .iter()
+                    .filter(|t| t.taker_order_id == leg_id)
+                    .map(|t| t.id),
+            );
+        }
+
+        order.filled_quantity = order.quantity;
+        order.status = OrderStatus::Filled;
+        order.updated_at = self.clock.now();
+        self.orders.insert(order.id, order.clone());
+        self.order_index.close(&order);
+
+        events.push(OrderEvent::SyntheticTradeExecuted(SyntheticTradeExecutedEvent {
+            event_id: new_event_id(),
+            prev_hash: None,
+            order_id: order.id,
+            user_id: order.user_id,
+            symbol: pair.symbol.clone(),
+            side: order.side,
+            price: quote.price,
+            quantity: order.quantity,
+            base_leg_order_id,
+            quote_leg_order_id,
+            leg_trade_ids,
+            timestamp: order.updated_at,
+        }));
+
+        self.persist_events(&mut events).await?;
+
+        Ok(resumed.into_iter().chain(events).collect())
+    }
+
+    fn leg_order(&self, cmd: &PlaceOrderCommand, symbol: &str, side: OrderSide, quantity: Decimal) -> Order {
+        let mut leg = Order::new(
+            cmd.user_id,
+            symbol.to_string(),
+            OrderType::Market,
+            side,
+            None,
+            quantity,
+        );
+        leg.id = self.ids.next_id();
+        leg.created_at = cmd.timestamp;
+        leg.updated_at = cmd.timestamp;
+        leg
+    }
+}
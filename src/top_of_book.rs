@@ -0,0 +1,102 @@
+use dashmap::DashMap;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+
+use crate::engine::MatchingEngine;
+use crate::types::OrderSide;
+
+/// Best displayed bid and ask of a symbol and the quantity at each.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TopOfBook {
+    pub symbol: String,
+    pub best_bid: Option<Decimal>,
+    pub bid_quantity: Decimal,
+    pub best_ask: Option<Decimal>,
+    pub ask_quantity: Decimal,
+}
+
+impl TopOfBook {
+    fn empty(symbol: &str) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            best_bid: None,
+            bid_quantity: Decimal::ZERO,
+            best_ask: None,
+            ask_quantity: Decimal::ZERO,
+        }
+    }
+}
+
+type TopOfBookListener = Box<dyn Fn(&TopOfBook) + Send + Sync>;
+
+#[derive(Default)]
+pub(crate) struct TopOfBookFeed {
+    senders: DashMap<String, watch::Sender<TopOfBook>>,
+    listeners: Vec<TopOfBookListener>,
+}
+
+impl MatchingEngine {
+    /// Calls `listener` whenever a command moves a symbol's best bid or ask
+    /// price or the quantity there. It runs on the command's task, so it
+    /// should hand work off rather than do it.
+    pub fn with_top_of_book_listener(mut self, listener: impl Fn(&TopOfBook) + Send + Sync + 'static) -> Self {
+        self.top_of_book.listeners.push(Box::new(listener));
+        self
+    }
+
+    /// `symbol`'s top of book, updated as `with_top_of_book_listener`
+    /// describes.
+    pub fn watch_top_of_book(&self, symbol: &str) -> watch::Receiver<TopOfBook> {
+        if let Some(sender) = self.top_of_book.senders.get(symbol) {
+            return sender.subscribe();
+        }
+        let top = self.get_top_of_book(symbol);
+        self.top_of_book
+            .senders
+            .entry(symbol.to_string())
+            .or_insert_with(|| watch::channel(top).0)
+            .subscribe()
+    }
+
+    pub fn get_top_of_book(&self, symbol: &str) -> TopOfBook {
+        let top = |side| {
+            let level = self.get_depth_by_side(symbol, side, 1).pop();
+            (level.as_ref().map(|l| l.price), level.map_or(Decimal::ZERO, |l| l.quantity))
+        };
+        let (best_bid, bid_quantity) = top(OrderSide::Buy);
+        let (best_ask, ask_quantity) = top(OrderSide::Sell);
+        TopOfBook {
+            symbol: symbol.to_string(),
+            best_bid,
+            bid_quantity,
+            best_ask,
+            ask_quantity,
+        }
+    }
+
+    pub(crate) fn refresh_top_of_book(&self, symbols: &[String]) {
+        let feed = &self.top_of_book;
+        for symbol in symbols {
+            if feed.listeners.is_empty() && !feed.senders.contains_key(symbol) {
+                continue;
+            }
+            let top = self.get_top_of_book(symbol);
+            // A symbol seen for the first time had an empty book before
+            let changed = feed
+                .senders
+                .entry(symbol.clone())
+                .or_insert_with(|| watch::channel(TopOfBook::empty(symbol)).0)
+                .send_if_modified(|current| {
+                    if *current == top {
+                        return false;
+                    }
+                    *current = top.clone();
+                    true
+                });
+            if changed {
+                feed.listeners.iter().for_each(|listener| listener(&top));
+            }
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::draws::new_event_id;
+use crate::engine::MatchingEngine;
+use crate::events::{OrderEvent, TradeBustedEvent};
+
+impl MatchingEngine {
+    /// Cancels a clearly erroneous trade: balances, positions and margin
+    /// move back as if it had not executed, and a settlement sink gets the
+    /// reversing instructions. Balances can go negative if what the trade
+    /// paid out has been spent. The trade itself is kept, marked busted;
+    /// its orders keep their fills and the market data it made stands.
+    /// Waits for commands in flight to finish first.
+    pub async fn bust_trade(&self, trade_id: Uuid, reason: &str) -> Result<Vec<OrderEvent>, String> {
+        self.check_following().map_err(|e| e.to_string())?;
+        let _gate = self.command_gate.write().await;
+        let trade = self
+            .trades
+            .get(&trade_id)
+            .map(|t| t.clone())
+            .ok_or_else(|| format!("Trade {} not found", trade_id))?;
+        if self.busted_trades.contains_key(&trade_id) {
+            return Err(format!("Trade {} is already busted", trade_id));
+        }
+        let now = self.clock.now();
+        self.busted_trades.insert(trade_id, now);
+
+        let user = |order_id: Uuid| self.orders.get(&order_id).map(|o| o.user_id);
+        if let Some(taker_user_id) = user(trade.taker_order_id) {
+            let maker_user_id = user(trade.maker_order_id);
+            self.record_position(&trade, taker_user_id, maker_user_id, true);
+            self.unsettle_trade(&trade, taker_user_id, maker_user_id);
+        }
+        let mut events = vec![OrderEvent::TradeBusted(TradeBustedEvent {
+            event_id: new_event_id(),
+            prev_hash: None,
+            trade_id,
+            symbol: trade.symbol,
+            price: trade.price,
+            quantity: trade.quantity,
+            side: trade.side,
+            taker_order_id: trade.taker_order_id,
+            maker_order_id: trade.maker_order_id,
+            reason: reason.to_string(),
+            timestamp: now,
+        })];
+        self.persist_events(&mut events).await?;
+        Ok(events)
+    }
+
+    /// When the trade was busted, if it was.
+    pub fn trade_busted_at(&self, trade_id: Uuid) -> Option<DateTime<Utc>> {
+        self.busted_trades.get(&trade_id).map(|at| *at)
+    }
+}
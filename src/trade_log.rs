@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::engine::MatchingEngine;
+use crate::types::Trade;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pagination {
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl Pagination {
+    pub fn new(offset: usize, limit: usize) -> Self {
+        Self { offset, limit }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: usize,
+    /// Offset of the next page, if there is one.
+    pub next_offset: Option<usize>,
+}
+
+/// Trades in execution order, per symbol and per participating user.
+#[derive(Debug, Default)]
+pub(crate) struct TradeLog {
+    by_symbol: DashMap<String, Vec<Trade>>,
+    by_user: DashMap<Uuid, Vec<Uuid>>,
+}
+
+impl TradeLog {
+    pub(crate) fn append(&self, trade: &Trade, taker_user_id: Uuid, maker_user_id: Option<Uuid>) {
+        self.by_symbol.entry(trade.symbol.clone()).or_default().push(trade.clone());
+        self.by_user.entry(taker_user_id).or_default().push(trade.id);
+        if let Some(maker_user_id) = maker_user_id.filter(|m| *m != taker_user_id) {
+            self.by_user.entry(maker_user_id).or_default().push(trade.id);
+        }
+    }
+
+    pub(crate) fn symbol_trade_count(&self, symbol: &str) -> usize {
+        self.by_symbol.get(symbol).map_or(0, |trades| trades.len())
+    }
+
+    pub(crate) fn trade_counts(&self) -> HashMap<String, usize> {
+        self.by_symbol.iter().map(|entry| (entry.key().clone(), entry.len())).collect()
+    }
+
+    /// The trades appended since `counts` was taken, oldest first.
+    pub(crate) fn trades_since(&self, counts: &HashMap<String, usize>) -> Vec<Trade> {
+        let mut trades: Vec<Trade> = self
+            .by_symbol
+            .iter()
+            .flat_map(|entry| {
+                let seen = counts.get(entry.key()).copied().unwrap_or(0).min(entry.len());
+                entry[seen..].to_vec()
+            })
+            .collect();
+        trades.sort_by_key(|trade| trade.created_at);
+        trades
+    }
+
+    /// Forgets the trades on `symbol` after the first `len`, returning
+    /// them.
+    pub(crate) fn truncate_symbol(&self, symbol: &str, len: usize) -> Vec<Trade> {
+        let Some(mut trades) = self.by_symbol.get_mut(symbol) else {
+            return Vec::new();
+        };
+        let len = len.min(trades.len());
+        let removed = trades.split_off(len);
+        drop(trades);
+        for mut entry in self.by_user.iter_mut() {
+            entry.retain(|id| !removed.iter().any(|trade| trade.id == *id));
+        }
+        removed
+    }
+}
+
+impl MatchingEngine {
+    /// Up to `limit` trades on `symbol` executed at or after `since`,
+    /// oldest first.
+    pub fn get_trades_by_symbol(&self, symbol: &str, since: Option<DateTime<Utc>>, limit: usize) -> Vec<Trade> {
+        let Some(trades) = self.trade_log.by_symbol.get(symbol) else {
+            return Vec::new();
+        };
+        let start = since.map_or(0, |since| trades.partition_point(|t| t.created_at < since));
+        trades[start..].iter().take(limit).cloned().collect()
+    }
+
+    /// Trades `user_id` took part in on either side, oldest first.
+    pub fn get_trades_by_user(&self, user_id: Uuid, pagination: Pagination) -> Page<Trade> {
+        let ids = self
+            .trade_log
+            .by_user
+            .get(&user_id)
+            .map(|ids| ids.clone())
+            .unwrap_or_default();
+        let total = ids.len();
+        let items: Vec<Trade> = ids
+            .iter()
+            .skip(pagination.offset)
+            .take(pagination.limit)
+            .filter_map(|id| self.get_trade(*id))
+            .collect();
+        let end = pagination.offset.saturating_add(pagination.limit);
+        Page {
+            items,
+            total,
+            next_offset: (end < total).then_some(end),
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::engine::MatchingEngine;
+use crate::event_store::TimeRange;
+use crate::file_store::{push_frame, scan_frames, truncate};
+use crate::types::Trade;
+
+/// A trade with the users on both sides, as the trade store keeps it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeRecord {
+    pub trade: Trade,
+    pub taker_user_id: Uuid,
+    /// None if the maker order was no longer known when the trade executed.
+    pub maker_user_id: Option<Uuid>,
+}
+
+#[async_trait]
+pub trait TradeStore: Send + Sync {
+    /// Saves the whole batch or none of it. Trades already saved are
+    /// skipped.
+    async fn save_trades(&self, trades: Vec<TradeRecord>) -> Result<(), String>;
+    /// Trades on `symbol` in save order.
+    async fn get_trades_by_symbol(&self, symbol: &str, range: TimeRange) -> Result<Vec<TradeRecord>, String>;
+    /// Trades `user_id` took either side of, in save order.
+    async fn get_trades_by_user(&self, user_id: Uuid, range: TimeRange) -> Result<Vec<TradeRecord>, String>;
+}
+
+#[derive(Debug, Default)]
+struct TradeIndex {
+    records: Vec<TradeRecord>,
+    ids: HashSet<Uuid>,
+    by_symbol: HashMap<String, Vec<usize>>,
+    by_user: HashMap<Uuid, Vec<usize>>,
+}
+
+impl TradeIndex {
+    /// Drops trades already indexed or repeated within the batch.
+    fn unsaved(&self, trades: Vec<TradeRecord>) -> Vec<TradeRecord> {
+        let mut batch = HashSet::new();
+        trades
+            .into_iter()
+            .filter(|record| !self.ids.contains(&record.trade.id) && batch.insert(record.trade.id))
+            .collect()
+    }
+
+    fn push(&mut self, record: TradeRecord) {
+        let position = self.records.len();
+        self.ids.insert(record.trade.id);
+        self.by_symbol.entry(record.trade.symbol.clone()).or_default().push(position);
+        self.by_user.entry(record.taker_user_id).or_default().push(position);
+        if let Some(maker_user_id) = record.maker_user_id.filter(|m| *m != record.taker_user_id) {
+            self.by_user.entry(maker_user_id).or_default().push(position);
+        }
+        self.records.push(record);
+    }
+
+    fn get(&self, positions: Option<&Vec<usize>>, range: TimeRange) -> Vec<TradeRecord> {
+        positions
+            .into_iter()
+            .flatten()
+            .map(|&i| &self.records[i])
+            .filter(|record| range.contains(record.trade.created_at))
+            .cloned()
+            .collect()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct InMemoryTradeStore {
+    index: RwLock<TradeIndex>,
+}
+
+impl InMemoryTradeStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl TradeStore for InMemoryTradeStore {
+    async fn save_trades(&self, trades: Vec<TradeRecord>) -> Result<(), String> {
+        let mut index = self.index.write().unwrap();
+        for record in index.unsaved(trades) {
+            index.push(record);
+        }
+        Ok(())
+    }
+
+    async fn get_trades_by_symbol(&self, symbol: &str, range: TimeRange) -> Result<Vec<TradeRecord>, String> {
+        let index = self.index.read().unwrap();
+        Ok(index.get(index.by_symbol.get(symbol), range))
+    }
+
+    async fn get_trades_by_user(&self, user_id: Uuid, range: TimeRange) -> Result<Vec<TradeRecord>, String> {
+        let index = self.index.read().unwrap();
+        Ok(index.get(index.by_user.get(&user_id), range))
+    }
+}
+
+/// Trades appended to one file as length-prefixed JSON, synced before each
+/// save returns. The file is read back into memory on open, cutting off a
+/// record torn by a crash.
+pub struct FileTradeStore {
+    path: PathBuf,
+    file: Mutex<File>,
+    index: RwLock<TradeIndex>,
+}
+
+impl FileTradeStore {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to open trade store {}: {}", path.display(), e))?;
+        let bytes = fs::read(&path).map_err(|e| e.to_string())?;
+        let (frames, end) = scan_frames(&bytes);
+        if end < bytes.len() {
+            truncate(&path, end as u64)?;
+        }
+        let mut index = TradeIndex::default();
+        for frame in frames {
+            index.push(serde_json::from_slice(&bytes[frame]).map_err(|e| e.to_string())?);
+        }
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+            index: RwLock::new(index),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[async_trait]
+impl TradeStore for FileTradeStore {
+    async fn save_trades(&self, trades: Vec<TradeRecord>) -> Result<(), String> {
+        let mut file = self.file.lock().unwrap();
+        let trades = self.index.read().unwrap().unsaved(trades);
+        if trades.is_empty() {
+            return Ok(());
+        }
+        let mut buf = Vec::new();
+        for record in &trades {
+            push_frame(&mut buf, &serde_json::to_vec(record).map_err(|e| e.to_string())?)?;
+        }
+        let len = file.metadata().map_err(|e| e.to_string())?.len();
+        if let Err(e) = file.write_all(&buf).and_then(|_| file.sync_data()) {
+            let _ = file.set_len(len);
+            return Err(e.to_string());
+        }
+        let mut index = self.index.write().unwrap();
+        for record in trades {
+            index.push(record);
+        }
+        Ok(())
+    }
+
+    async fn get_trades_by_symbol(&self, symbol: &str, range: TimeRange) -> Result<Vec<TradeRecord>, String> {
+        let index = self.index.read().unwrap();
+        Ok(index.get(index.by_symbol.get(symbol), range))
+    }
+
+    async fn get_trades_by_user(&self, user_id: Uuid, range: TimeRange) -> Result<Vec<TradeRecord>, String> {
+        let index = self.index.read().unwrap();
+        Ok(index.get(index.by_user.get(&user_id), range))
+    }
+}
+
+/// The engine's trade store and the trades executed but not yet saved to it.
+pub(crate) struct TradeCapture {
+    store: Box<dyn TradeStore>,
+    pending: Mutex<Vec<TradeRecord>>,
+}
+
+impl MatchingEngine {
+    /// Saves every trade to `store` as well, after the events of the
+    /// command that executed it.
+    pub fn with_trade_store(mut self, store: impl TradeStore + 'static) -> Self {
+        self.trade_capture = Some(TradeCapture {
+            store: Box::new(store),
+            pending: Mutex::new(Vec::new()),
+        });
+        self
+    }
+
+    pub fn trade_store(&self) -> Option<&dyn TradeStore> {
+        self.trade_capture.as_ref().map(|capture| capture.store.as_ref())
+    }
+
+    /// Saves the trades still waiting for the trade store. Trades a failed
+    /// save left behind are also retried after the next command.
+    pub async fn flush_trades(&self) -> Result<(), String> {
+        let Some(capture) = &self.trade_capture else {
+            return Ok(());
+        };
+        let trades = std::mem::take(&mut *capture.pending.lock().unwrap());
+        if trades.is_empty() {
+            return Ok(());
+        }
+        if let Err(e) = capture.store.save_trades(trades.clone()).await {
+            let mut pending = capture.pending.lock().unwrap();
+            let later = std::mem::replace(&mut *pending, trades);
+            pending.extend(later);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Queues `trade` for the trade store. Trades re-executed by a WAL
+    /// replay or a follower are left out, like the replay's events.
+    pub(crate) fn capture_trade(&self, trade: &Trade, taker_user_id: Uuid, maker_user_id: Option<Uuid>) {
+        if self.is_replaying() {
+            return;
+        }
+        if let Some(capture) = &self.trade_capture {
+            capture.pending.lock().unwrap().push(TradeRecord {
+                trade: trade.clone(),
+                taker_user_id,
+                maker_user_id,
+            });
+        }
+    }
+
+    /// Forgets pending trades a rollback undid.
+    pub(crate) fn discard_pending_trades(&self, trade_ids: &[Uuid]) {
+        if let Some(capture) = &self.trade_capture {
+            capture
+                .pending
+                .lock()
+                .unwrap()
+                .retain(|record| !trade_ids.contains(&record.trade.id));
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+
+use crate::commands::OrderCommand;
+use crate::draws::new_event_id;
+use crate::engine::MatchingEngine;
+use crate::events::{OrderEvent, SymbolStateChangedEvent};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SymbolState {
+    #[default]
+    Trading,
+    /// No order entry or cancellation.
+    Halted,
+    /// Orders rest without matching until the auction is run. Market orders
+    /// are rejected.
+    AuctionOnly,
+    /// Only cancellations are accepted.
+    CancelOnly,
+    /// Outside the symbol's trading hours or before its listing time. Only
+    /// cancellations are accepted; other commands are rejected or queued
+    /// as while halted.
+    Closed,
+    /// Taken off the engine by `delist_symbol`; every command is rejected.
+    Delisted,
+}
+
+/// What happens to commands a symbol's state does not admit.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum HaltedCommandPolicy {
+    #[default]
+    Reject,
+    /// Hold the commands and replay them in arrival order on `resume`.
+    Queue,
+}
+
+/// Whether a command may go ahead now.
+pub(crate) enum Admission {
+    Accepted,
+    Queued,
+}
+
+impl MatchingEngine {
+    pub fn with_halted_command_policy(mut self, policy: HaltedCommandPolicy) -> Self {
+        self.halted_command_policy = policy;
+        self
+    }
+
+    pub fn symbol_state(&self, symbol: &str) -> SymbolState {
+        self.symbol_states.get(symbol).map(|s| *s).unwrap_or_default()
+    }
+
+    pub async fn halt(&self, symbol: &str) -> Result<Vec<OrderEvent>, String> {
+        self.set_symbol_state(symbol, SymbolState::Halted).await
+    }
+
+    /// Returns `symbol` to continuous trading and replays any commands that
+    /// were queued while it was halted. An auction must be ended with
+    /// `run_auction` instead, so the book is uncrossed first.
+    pub async fn resume(&self, symbol: &str) -> Result<Vec<OrderEvent>, String> {
+        if self.symbol_state(symbol) == SymbolState::AuctionOnly {
+            return Err(format!("{} is in auction: run the auction to resume trading", symbol));
+        }
+        let mut events = self.set_symbol_state(symbol, SymbolState::Trading).await?;
+        self.reset_circuit_breaker(symbol);
+        events.extend(self.replay_queued_commands(symbol).await);
+        Ok(events)
+    }
+
+    pub(crate) async fn replay_queued_commands(&self, symbol: &str) -> Vec<OrderEvent> {
+        let queued = self
+            .queued_commands
+            .remove(symbol)
+            .map(|(_, commands)| commands)
+            .unwrap_or_default();
+        let mut events = Vec::new();
+        for command in queued {
+            // A queued command that fails now is dropped like any rejection.
+            // It was rate limited on arrival.
+            if let Ok(replayed) = self.process_command(command).await {
+                events.extend(replayed);
+            }
+        }
+        events
+    }
+
+    /// Moves `symbol` into `state`, emitting SymbolStateChanged. Setting the
+    /// current state again is a no-op. A delisted symbol stays delisted.
+    pub async fn set_symbol_state(&self, symbol: &str, state: SymbolState) -> Result<Vec<OrderEvent>, String> {
+        if self.symbol_state(symbol) == SymbolState::Delisted && state != SymbolState::Delisted {
+            return Err(format!("{} is delisted", symbol));
+        }
+        let mut events = Vec::new();
+        self.transition_symbol_state(symbol, state, &mut events);
+        self.persist_events(&mut events).await?;
+        Ok(events)
+    }
+
+    pub(crate) fn transition_symbol_state(&self, symbol: &str, state: SymbolState, events: &mut Vec<OrderEvent>) {
+        let previous = self
+            .symbol_states
+            .insert(symbol.to_string(), state)
+            .unwrap_or_default();
+        if previous == state {
+            return;
+        }
+        events.push(OrderEvent::SymbolStateChanged(SymbolStateChangedEvent {
+            event_id: new_event_id(),
+            prev_hash: None,
+            symbol: symbol.to_string(),
+            previous_state: previous,
+            state,
+            timestamp: self.clock.now(),
+        }));
+    }
+
+    /// Checks a command against the symbol's state, first lifting an
+    /// expired circuit-breaker halt and applying the symbol's trading
+    /// schedule. `command` is only called when the command has to be
+    /// queued. Returns the events of any transition.
+    pub(crate) async fn admit_command(
+        &self,
+        symbol: &str,
+        is_cancel: bool,
+        command: impl FnOnce() -> OrderCommand,
+    ) -> Result<(Admission, Vec<OrderEvent>), String> {
+        let mut events = self.lift_expired_circuit_breaker(symbol).await?;
+        events.extend(self.apply_trading_schedule(symbol).await?);
+        let state = self.symbol_state(symbol);
+        let admitted = match state {
+            SymbolState::Trading | SymbolState::AuctionOnly => true,
+            SymbolState::CancelOnly | SymbolState::Closed => is_cancel,
+            SymbolState::Halted => false,
+            SymbolState::Delisted => return Err(format!("{} is delisted", symbol)),
+        };
+        if admitted {
+            return Ok((Admission::Accepted, events));
+        }
+
+        match self.halted_command_policy {
+            HaltedCommandPolicy::Reject => Err(format!("{} is {:?}: command rejected", symbol, state)),
+            HaltedCommandPolicy::Queue => {
+                self.queued_commands
+                    .entry(symbol.to_string())
+                    .or_default()
+                    .push_back(command());
+                Ok((Admission::Queued, events))
+            }
+        }
+    }
+
+    pub fn queued_command_count(&self, symbol: &str) -> usize {
+        self.queued_commands.get(symbol).map(|q| q.len()).unwrap_or(0)
+    }
+}
@@ -19,6 +19,15 @@ pub enum OrderSide {
     Sell,
 }
 
+impl OrderSide {
+    pub fn opposite(self) -> Self {
+        match self {
+            OrderSide::Buy => OrderSide::Sell,
+            OrderSide::Sell => OrderSide::Buy,
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub enum OrderStatus {
     Pending,
@@ -45,6 +54,25 @@ pub struct Order {
     pub iceberg_visible_quantity: Option<Decimal>,
     pub stop_price: Option<Decimal>,
     pub trailing_stop_price: Option<Decimal>,
+    #[serde(default)]
+    pub client_order_id: Option<String>,
+    /// Hidden orders rest without showing in the book and match after the
+    /// displayed orders at their price.
+    #[serde(default = "displayed_by_default")]
+    pub displayed: bool,
+    /// Stop matching as a taker after this many executions.
+    #[serde(default)]
+    pub max_fills: Option<u32>,
+    /// Account a share of the order's taker fees is attributed to.
+    #[serde(default)]
+    pub referrer_id: Option<Uuid>,
+    /// The most a market buy entered by quote may spend.
+    #[serde(default)]
+    pub quote_quantity: Option<Decimal>,
+}
+
+pub(crate) fn displayed_by_default() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,7 +94,7 @@ pub struct OrderBook {
     pub asks: Vec<OrderBookEntry>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct OrderBookEntry {
     pub price: Decimal,
     pub quantity: Decimal,
@@ -98,8 +126,24 @@ impl Order {
             iceberg_visible_quantity: None,
             stop_price: None,
             trailing_stop_price: None,
+            client_order_id: None,
+            displayed: true,
+            max_fills: None,
+            referrer_id: None,
+            quote_quantity: None,
         }
     }
+
+    pub fn remaining_quantity(&self) -> Decimal {
+        self.quantity - self.filled_quantity
+    }
+
+    pub fn is_open(&self) -> bool {
+        matches!(
+            self.status,
+            OrderStatus::Pending | OrderStatus::Active | OrderStatus::PartiallyFilled
+        )
+    }
 }
 
 impl OrderBook {
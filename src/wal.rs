@@ -0,0 +1,345 @@
+use std::fs::{self, File, OpenOptions};
+use std::future::Future;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::commands::OrderCommand;
+use crate::draws::{drawn_so_far, record_draws, Draws};
+use crate::engine::MatchingEngine;
+use crate::error::EngineError;
+use crate::events::OrderEvent;
+use crate::file_store::{push_frame, scan_frames, truncate, FsyncPolicy};
+
+const LOG_FILE: &str = "wal.log";
+/// The sequence of the last record dropped by a snapshot, so numbering
+/// carries on from it once the log is empty.
+const BASE_FILE: &str = "wal.base";
+
+/// A command as it was admitted, numbered in admission order, with the
+/// ids and times it drew and whether the engine rejected it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalRecord {
+    pub sequence: u64,
+    pub command: OrderCommand,
+    #[serde(default, skip_serializing_if = "Draws::is_empty")]
+    pub draws: Draws,
+    /// Read from the command's rejection record, which follows it.
+    #[serde(skip)]
+    pub rejected: bool,
+}
+
+/// What the command logged as `sequence` has drawn so far. Logged after
+/// the command, since it is only known once the command runs, and each
+/// time the command saves events, so the store never holds events whose
+/// draws the log has lost.
+#[derive(Debug, Serialize, Deserialize)]
+struct DrawsRecord {
+    sequence: u64,
+    draws: Draws,
+}
+
+/// Marks the command logged as `rejected` as one the engine rejected.
+/// Recovery skips it: the state that rejected it, such as a kill switch
+/// or a halt, is not in the log, and the command could go through.
+#[derive(Debug, Serialize, Deserialize)]
+struct RejectedRecord {
+    rejected: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Frame {
+    Command(Box<WalRecord>),
+    Draws(DrawsRecord),
+    Rejected(RejectedRecord),
+}
+
+tokio::task_local! {
+    /// The sequence of the logged command running on this task.
+    static LOGGED: u64;
+}
+
+struct LogFile {
+    file: File,
+    /// Bytes of complete frames in the file.
+    len: u64,
+    next_sequence: u64,
+    unsynced: u32,
+}
+
+/// Commands logged before the engine acts on them, so `recover` can
+/// rebuild the engine after a crash. Taking a snapshot empties the log.
+pub struct WriteAheadLog {
+    dir: PathBuf,
+    fsync: FsyncPolicy,
+    log: Mutex<LogFile>,
+    replaying: AtomicBool,
+}
+
+impl WriteAheadLog {
+    /// Opens the log in `dir`, cutting off a record torn by a crash.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, String> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create WAL {}: {}", dir.display(), e))?;
+        let path = dir.join(LOG_FILE);
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to open WAL {}: {}", path.display(), e))?;
+
+        let wal = Self {
+            dir,
+            fsync: FsyncPolicy::default(),
+            log: Mutex::new(LogFile {
+                file,
+                len: 0,
+                next_sequence: 1,
+                unsynced: 0,
+            }),
+            replaying: AtomicBool::new(false),
+        };
+        let last = match wal.records()?.last() {
+            Some(record) => record.sequence,
+            None => wal.base()?,
+        };
+        let len = fs::metadata(&path).map_err(|e| e.to_string())?.len();
+        let mut log = wal.log.lock().unwrap();
+        log.next_sequence = last + 1;
+        log.len = len;
+        drop(log);
+        Ok(wal)
+    }
+
+    pub fn with_fsync(mut self, policy: FsyncPolicy) -> Self {
+        self.fsync = policy;
+        self
+    }
+
+    /// Every record in the log, oldest first. A last frame that does not
+    /// parse was torn by a crash mid-write and is cut off with the rest of
+    /// the torn append.
+    pub fn records(&self) -> Result<Vec<WalRecord>, String> {
+        let path = self.dir.join(LOG_FILE);
+        let bytes = fs::read(&path).map_err(|e| e.to_string())?;
+        let (frames, mut end) = scan_frames(&bytes);
+        let count = frames.len();
+        let mut records: Vec<WalRecord> = Vec::new();
+        for (i, frame) in frames.into_iter().enumerate() {
+            let start = frame.start - 4;
+            let frame = match serde_json::from_slice(&bytes[frame]) {
+                Ok(frame) => frame,
+                Err(_) if i + 1 == count => {
+                    end = start;
+                    break;
+                }
+                Err(e) => return Err(e.to_string()),
+            };
+            match frame {
+                Frame::Command(record) => records.push(*record),
+                // Each draws record covers everything drawn before it
+                Frame::Draws(drawn) => {
+                    if let Some(record) = records.iter_mut().rev().find(|r| r.sequence == drawn.sequence) {
+                        record.draws = drawn.draws;
+                    }
+                }
+                Frame::Rejected(outcome) => {
+                    if let Some(record) = records.iter_mut().rev().find(|r| r.sequence == outcome.rejected) {
+                        record.rejected = true;
+                    }
+                }
+            }
+        }
+        if end < bytes.len() {
+            truncate(&path, end as u64)?;
+        }
+        Ok(records)
+    }
+
+    fn base(&self) -> Result<u64, String> {
+        match fs::read_to_string(self.dir.join(BASE_FILE)) {
+            Ok(base) => base.trim().parse().map_err(|_| format!("Corrupt {}", BASE_FILE)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    fn append(&self, command: &OrderCommand) -> Result<u64, String> {
+        let mut log = self.log.lock().unwrap();
+        let record = WalRecord {
+            sequence: log.next_sequence,
+            command: command.clone(),
+            draws: Draws::default(),
+            rejected: false,
+        };
+        Self::write_frame(&mut log, &record, self.fsync)?;
+        log.next_sequence += 1;
+        Ok(record.sequence)
+    }
+
+    fn append_draws(&self, sequence: u64, draws: Draws) -> Result<(), String> {
+        if draws.is_empty() {
+            return Ok(());
+        }
+        let mut log = self.log.lock().unwrap();
+        Self::write_frame(&mut log, &DrawsRecord { sequence, draws }, self.fsync)
+    }
+
+    fn append_rejected(&self, sequence: u64) -> Result<(), String> {
+        let mut log = self.log.lock().unwrap();
+        Self::write_frame(&mut log, &RejectedRecord { rejected: sequence }, self.fsync)
+    }
+
+    fn write_frame(log: &mut LogFile, record: &impl Serialize, fsync: FsyncPolicy) -> Result<(), String> {
+        let mut buf = Vec::new();
+        push_frame(&mut buf, &serde_json::to_vec(record).map_err(|e| e.to_string())?)?;
+        if let Err(e) = log.file.write_all(&buf) {
+            // Drop whatever part of the frame made it, so the next append
+            // does not follow a torn frame
+            let _ = log.file.set_len(log.len);
+            return Err(e.to_string());
+        }
+        log.unsynced += 1;
+        let sync = match fsync {
+            FsyncPolicy::Always => true,
+            FsyncPolicy::EveryBatches(n) => log.unsynced >= n,
+            FsyncPolicy::Never => false,
+        };
+        if sync {
+            if let Err(e) = log.file.sync_data() {
+                // The caller treats the frame as not logged
+                let _ = log.file.set_len(log.len);
+                return Err(e.to_string());
+            }
+            log.unsynced = 0;
+        }
+        log.len += buf.len() as u64;
+        Ok(())
+    }
+
+    /// The sequence of the last logged command.
+    pub(crate) fn last_sequence(&self) -> u64 {
+        self.log.lock().unwrap().next_sequence - 1
+    }
+
+    /// Drops every record once a snapshot covers them.
+    pub(crate) fn clear(&self) -> Result<(), String> {
+        let mut log = self.log.lock().unwrap();
+        let base = self.dir.join(BASE_FILE);
+        let staged = self.dir.join(format!("{}.tmp", BASE_FILE));
+        let mut file = File::create(&staged).map_err(|e| e.to_string())?;
+        write!(file, "{}", log.next_sequence - 1).map_err(|e| e.to_string())?;
+        file.sync_data().map_err(|e| e.to_string())?;
+        fs::rename(&staged, base).map_err(|e| e.to_string())?;
+        log.file.set_len(0).map_err(|e| e.to_string())?;
+        log.file.sync_data().map_err(|e| e.to_string())?;
+        log.len = 0;
+        log.unsynced = 0;
+        Ok(())
+    }
+}
+
+impl MatchingEngine {
+    /// Logs every command that passes the middleware and rate limits to
+    /// `wal` before it is processed.
+    pub fn with_wal(mut self, wal: WriteAheadLog) -> Self {
+        self.wal = Some(wal);
+        self
+    }
+
+    /// Logs `command` ahead of processing it, returning its sequence.
+    pub(crate) fn log_command(&self, command: &OrderCommand) -> Result<Option<u64>, EngineError> {
+        let Some(wal) = &self.wal else {
+            return Ok(None);
+        };
+        wal.append(command)
+            .map(Some)
+            .map_err(|e| EngineError::Rejected(format!("Failed to log command: {}", e)))
+    }
+
+    /// Runs the command logged as `sequence`, recording what it draws and
+    /// logging that, and whether it was rejected, once it is done.
+    pub(crate) async fn run_logged<T, F: Future<Output = Result<T, EngineError>>>(
+        &self,
+        sequence: Option<u64>,
+        replay: Draws,
+        run: F,
+    ) -> (F::Output, Draws) {
+        let Some(sequence) = sequence else {
+            return record_draws(replay, run).await;
+        };
+        let (output, draws) = record_draws(replay, LOGGED.scope(sequence, run)).await;
+        // Draws behind saved events were logged before the save, so a
+        // failure here only loses state no event records
+        if let Some(wal) = &self.wal {
+            let _ = wal.append_draws(sequence, draws.clone());
+            if output.is_err() {
+                let _ = wal.append_rejected(sequence);
+            }
+        }
+        (output, draws)
+    }
+
+    /// Logs what the running command has drawn, ahead of saving its events.
+    pub(crate) fn log_draws(&self) -> Result<(), String> {
+        let (Some(wal), Ok(sequence), Some(draws)) = (&self.wal, LOGGED.try_with(|s| *s), drawn_so_far()) else {
+            return Ok(());
+        };
+        wal.append_draws(sequence, draws)
+    }
+
+    pub(crate) fn is_replaying_wal(&self) -> bool {
+        self.wal.as_ref().is_some_and(|wal| wal.replaying.load(Ordering::SeqCst))
+    }
+
+    /// Rebuilds a fresh engine from the latest snapshot and the commands
+    /// logged after it, returning the events the replay produced. Commands
+    /// are replayed with the ids and times they drew, so the events match
+    /// those saved before the crash, and saving them fills in only the ones
+    /// the store never got, such as events dropped from a full writer
+    /// queue. Replayed commands skip the middleware and rate limits, which
+    /// they passed when first logged, and commands the engine rejected are
+    /// not replayed.
+    pub async fn recover(&self) -> Result<Vec<OrderEvent>, String> {
+        if self.wal.is_none() && self.snapshots.is_none() {
+            return Err("No WAL or snapshot store configured".to_string());
+        }
+        let _gate = self.command_gate.write().await;
+        if !self.orders.is_empty() {
+            return Err("Recovery needs an engine without orders".to_string());
+        }
+
+        let mut sequence = 0;
+        if let Some(snapshots) = &self.snapshots {
+            if let Some(snapshot) = snapshots.store.latest_snapshot().await? {
+                sequence = snapshot.sequence;
+                self.load_snapshot(snapshot)?;
+            }
+        }
+        let Some(wal) = &self.wal else {
+            return Ok(Vec::new());
+        };
+
+        let records = wal.records()?;
+        wal.replaying.store(true, Ordering::SeqCst);
+        let mut events = Vec::new();
+        for record in records {
+            if record.sequence <= sequence || record.rejected {
+                continue;
+            }
+            let (replayed, _) = record_draws(record.draws, self.process_command(record.command)).await;
+            if let Ok(replayed) = replayed {
+                events.extend(replayed);
+            }
+        }
+        wal.replaying.store(false, Ordering::SeqCst);
+        if !events.is_empty() {
+            self.event_store.save_events(events.clone()).await?;
+        }
+        Ok(events)
+    }
+}
@@ -0,0 +1,89 @@
+//! The engine for JavaScript, linked for wasm32-unknown-unknown with
+//! `--features wasm --crate-type cdylib` and bound by the wasm-bindgen CLI.
+//! Requests and results cross as the same JSON the REST front end speaks;
+//! errors come back as strings.
+
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+use uuid::Uuid;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+use crate::commands::{CancelRequest, OrderCommand, PlaceRequest};
+use crate::engine::MatchingEngine;
+use crate::event_store::InMemoryEventStore;
+use crate::events::OrderEvent;
+
+/// An engine with an in-memory event store.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub struct WasmEngine {
+    engine: MatchingEngine,
+}
+
+impl Default for WasmEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl WasmEngine {
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen(constructor))]
+    pub fn new() -> Self {
+        Self { engine: MatchingEngine::new(Box::new(InMemoryEventStore::new())) }
+    }
+
+    /// Places a `PlaceRequest` and returns the events as a JSON array.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen(js_name = placeOrder))]
+    pub fn place_order(&self, request: &str) -> Result<String, String> {
+        let request: PlaceRequest = serde_json::from_str(request).map_err(|e| e.to_string())?;
+        self.run(OrderCommand::PlaceOrder(request.into()))
+    }
+
+    /// Cancels by a `CancelRequest` and returns the events as a JSON array.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen(js_name = cancelOrder))]
+    pub fn cancel_order(&self, request: &str) -> Result<String, String> {
+        let request: CancelRequest = serde_json::from_str(request).map_err(|e| e.to_string())?;
+        self.run(OrderCommand::CancelOrder(request.into()))
+    }
+
+    /// The order as JSON, or `undefined` if the engine does not know it.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen(js_name = getOrder))]
+    pub fn get_order(&self, order_id: &str) -> Result<Option<String>, String> {
+        let order_id = Uuid::parse_str(order_id).map_err(|e| e.to_string())?;
+        Ok(self.engine.get_order(order_id).map(|order| to_json(&order)))
+    }
+
+    /// The aggregated book as a JSON `L2Snapshot`.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen(js_name = getDepth))]
+    pub fn get_depth(&self, symbol: &str, levels: usize) -> String {
+        to_json(&self.engine.get_l2_snapshot(symbol, levels))
+    }
+
+    /// The latest trades, newest first, as a JSON array.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen(js_name = getRecentTrades))]
+    pub fn get_recent_trades(&self, symbol: &str, limit: usize) -> String {
+        to_json(&self.engine.get_recent_trades(symbol, limit))
+    }
+
+    fn run(&self, command: OrderCommand) -> Result<String, String> {
+        let events: Vec<OrderEvent> = now_or_never(self.engine.handle_command(command))?.map_err(|e| e.to_string())?;
+        Ok(to_json(&events))
+    }
+}
+
+/// There is no runtime to wait on in the browser. With an in-memory store
+/// nothing the engine awaits is ever pending, so one poll finishes it.
+fn now_or_never<F: Future>(future: F) -> Result<F::Output, String> {
+    let mut future = pin!(future);
+    match future.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+        Poll::Ready(output) => Ok(output),
+        Poll::Pending => Err("The engine could not finish the command without waiting".to_string()),
+    }
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_string(value).expect("engine types serialize")
+}
@@ -0,0 +1,400 @@
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::Utc;
+use dashmap::DashMap;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::{redirect, Client, Url};
+use serde_json::json;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{mpsc, Semaphore};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::digest::{hex, hmac_sha256};
+use crate::engine::MatchingEngine;
+use crate::events::OrderEvent;
+use crate::subscription::EventFilter;
+
+/// How long one delivery attempt may take, connecting included.
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+/// Deliveries waiting for an attempt. Once it is full, events back up in
+/// the webhooks' subscriptions, and those that fall behind are counted in
+/// `missed_events`.
+const QUEUE_CAPACITY: usize = 4096;
+/// Delivery attempts in flight at once.
+const MAX_CONCURRENT_DELIVERIES: usize = 64;
+
+/// How often, and how patiently, a failed delivery is retried.
+#[derive(Debug, Clone, Copy)]
+pub struct WebhookRetryPolicy {
+    /// Attempts in all, the first included.
+    pub max_attempts: u32,
+    /// The wait after the first failure, doubled after each one after.
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for WebhookRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 8,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(300),
+        }
+    }
+}
+
+impl WebhookRetryPolicy {
+    fn backoff(&self, failed_attempts: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(failed_attempts.saturating_sub(1)))
+            .min(self.max_backoff)
+    }
+}
+
+/// Which hosts webhooks may post to. By default any host with a public
+/// address, so a registered URL cannot reach services on the venue's own
+/// network.
+#[derive(Debug, Clone, Default)]
+pub struct WebhookTargets {
+    /// The only hosts URLs may name, if set.
+    pub allowed_hosts: Option<Vec<String>>,
+    /// Lets URLs reach loopback, private, link-local and other non-public
+    /// addresses.
+    pub allow_private_addresses: bool,
+}
+
+/// A callback URL a user registered.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Webhook {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub url: String,
+    /// `OrderEvent::kind` names; None for every event of the user's orders.
+    pub kinds: Option<Vec<String>>,
+}
+
+struct Registration {
+    webhook: Webhook,
+    forwarder: JoinHandle<()>,
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        self.forwarder.abort();
+    }
+}
+
+/// Where a webhook posts to, what it signs with and the client that
+/// posts.
+struct Endpoint {
+    webhook_id: Uuid,
+    url: Url,
+    secret: String,
+    client: Client,
+}
+
+struct Delivery {
+    endpoint: Arc<Endpoint>,
+    id: Uuid,
+    body: Vec<u8>,
+    attempts: u32,
+}
+
+/// Posts users' saved order events to the URLs they registered, as JSON
+/// signed with a per-webhook secret, over HTTPS or HTTP. Deliveries go
+/// through a bounded queue and are retried with exponential backoff, so a
+/// slow or failing endpoint holds up neither the others nor matching; one
+/// still failing after the last attempt is dropped and counted.
+pub struct WebhookDispatcher {
+    engine: Arc<MatchingEngine>,
+    registrations: DashMap<Uuid, Registration>,
+    targets: WebhookTargets,
+    client: Client,
+    queue: mpsc::Sender<Delivery>,
+    worker: JoinHandle<()>,
+    failed: Arc<AtomicU64>,
+    missed: Arc<AtomicU64>,
+    retries: Arc<Mutex<Vec<JoinHandle<()>>>>,
+}
+
+impl WebhookDispatcher {
+    /// Starts the delivery queue, which runs until the dispatcher is
+    /// dropped.
+    pub fn new(engine: Arc<MatchingEngine>, retry_policy: WebhookRetryPolicy) -> Self {
+        let (queue, deliveries) = mpsc::channel(QUEUE_CAPACITY);
+        let failed = Arc::new(AtomicU64::new(0));
+        let retries = Arc::new(Mutex::new(Vec::new()));
+        let worker = tokio::spawn(deliver(deliveries, queue.clone(), retry_policy, failed.clone(), retries.clone()));
+        let targets = WebhookTargets::default();
+        Self {
+            engine,
+            registrations: DashMap::new(),
+            client: http_client(&targets),
+            targets,
+            queue,
+            worker,
+            failed,
+            missed: Arc::new(AtomicU64::new(0)),
+            retries,
+        }
+    }
+
+    /// Restricts the hosts webhooks registered from here on may post to.
+    pub fn with_targets(mut self, targets: WebhookTargets) -> Self {
+        self.client = http_client(&targets);
+        self.targets = targets;
+        self
+    }
+
+    /// Posts events of `user_id`'s orders, fills included, to `url` from
+    /// now on, optionally only those of `kinds`. `url` is an `https://` or
+    /// `http://` URL whose host the dispatcher's targets allow.
+    pub fn register(&self, user_id: Uuid, url: &str, secret: &str, kinds: Option<Vec<String>>) -> Result<Uuid, String> {
+        let url = parse_url(url, &self.targets)?;
+        let webhook = Webhook {
+            id: Uuid::new_v4(),
+            user_id,
+            url: url.to_string(),
+            kinds: kinds.clone(),
+        };
+        let mut subscription = self.engine.event_store().subscribe(EventFilter {
+            symbol: None,
+            user_id: Some(user_id),
+            kinds,
+        })?;
+        let endpoint = Arc::new(Endpoint {
+            webhook_id: webhook.id,
+            url,
+            secret: secret.to_string(),
+            client: self.client.clone(),
+        });
+        let (queue, missed) = (self.queue.clone(), self.missed.clone());
+        let forwarder = tokio::spawn(async move {
+            loop {
+                let event = match subscription.recv().await {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(skipped)) => {
+                        missed.fetch_add(skipped, Ordering::Relaxed);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => return,
+                };
+                let delivery = Delivery {
+                    endpoint: endpoint.clone(),
+                    id: Uuid::new_v4(),
+                    body: payload(&endpoint, &event),
+                    attempts: 0,
+                };
+                if queue.send(delivery).await.is_err() {
+                    return;
+                }
+            }
+        });
+        let id = webhook.id;
+        self.registrations.insert(id, Registration { webhook, forwarder });
+        Ok(id)
+    }
+
+    /// Stops posting to a webhook; deliveries already queued still go out.
+    pub fn unregister(&self, webhook_id: Uuid) -> bool {
+        self.registrations.remove(&webhook_id).is_some()
+    }
+
+    pub fn webhooks(&self, user_id: Uuid) -> Vec<Webhook> {
+        self.registrations
+            .iter()
+            .filter(|r| r.webhook.user_id == user_id)
+            .map(|r| r.webhook.clone())
+            .collect()
+    }
+
+    /// Deliveries given up on after the last attempt.
+    pub fn failed_deliveries(&self) -> u64 {
+        self.failed.load(Ordering::Relaxed)
+    }
+
+    /// Events never queued for delivery because a webhook's subscription
+    /// fell behind the event store while the queue was full.
+    pub fn missed_events(&self) -> u64 {
+        self.missed.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for WebhookDispatcher {
+    fn drop(&mut self) {
+        self.worker.abort();
+        for retry in self.retries.lock().unwrap().drain(..) {
+            retry.abort();
+        }
+    }
+}
+
+/// The signature a delivery carries in `X-Webhook-Signature`, for receivers
+/// to check: the hex HMAC-SHA256, keyed with the webhook's secret, of the
+/// `X-Webhook-Timestamp` value, a dot and the body.
+pub fn webhook_signature(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut signed = format!("{}.", timestamp).into_bytes();
+    signed.extend_from_slice(body);
+    format!("sha256={}", hex(&hmac_sha256(secret.as_bytes(), &signed)))
+}
+
+fn payload(endpoint: &Endpoint, event: &OrderEvent) -> Vec<u8> {
+    json!({
+        "webhook_id": endpoint.webhook_id,
+        "kind": event.kind(),
+        "event": event,
+    })
+    .to_string()
+    .into_bytes()
+}
+
+/// Takes deliveries off the queue, each attempt on its own task and at
+/// most `MAX_CONCURRENT_DELIVERIES` at once; a failed one goes back on the
+/// queue once its backoff has passed.
+async fn deliver(
+    mut deliveries: mpsc::Receiver<Delivery>,
+    queue: mpsc::Sender<Delivery>,
+    retry_policy: WebhookRetryPolicy,
+    failed: Arc<AtomicU64>,
+    retries: Arc<Mutex<Vec<JoinHandle<()>>>>,
+) {
+    let slots = Arc::new(Semaphore::new(MAX_CONCURRENT_DELIVERIES));
+    while let Some(mut delivery) = deliveries.recv().await {
+        let slot = slots.clone().acquire_owned().await.expect("the semaphore is never closed");
+        let (queue, failed) = (queue.clone(), failed.clone());
+        let attempt = tokio::spawn(async move {
+            let posted = post(&delivery).await;
+            drop(slot);
+            if posted.is_ok() {
+                return;
+            }
+            delivery.attempts += 1;
+            if delivery.attempts >= retry_policy.max_attempts {
+                failed.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+            tokio::time::sleep(retry_policy.backoff(delivery.attempts)).await;
+            let _ = queue.send(delivery).await;
+        });
+        let mut running = retries.lock().unwrap();
+        running.retain(|task| !task.is_finished());
+        running.push(attempt);
+    }
+}
+
+async fn post(delivery: &Delivery) -> Result<(), String> {
+    let endpoint = &delivery.endpoint;
+    let timestamp = Utc::now().timestamp();
+    let response = endpoint
+        .client
+        .post(endpoint.url.clone())
+        .header("Content-Type", "application/json")
+        .header("X-Webhook-Id", endpoint.webhook_id.to_string())
+        .header("X-Webhook-Delivery", delivery.id.to_string())
+        .header("X-Webhook-Timestamp", timestamp.to_string())
+        .header("X-Webhook-Signature", webhook_signature(&endpoint.secret, timestamp, &delivery.body))
+        .body(delivery.body.clone())
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    match response.status() {
+        status if status.is_success() => Ok(()),
+        status => Err(format!("Webhook answered {}", status.as_u16())),
+    }
+}
+
+/// The client deliveries are posted with. It does not follow redirects,
+/// and unless `targets` allows private addresses it only connects to
+/// public ones, checked on each connection so a host cannot be pointed
+/// at the venue's network after it is registered.
+fn http_client(targets: &WebhookTargets) -> Client {
+    let mut builder = Client::builder().timeout(DELIVERY_TIMEOUT).redirect(redirect::Policy::none());
+    if !targets.allow_private_addresses {
+        builder = builder.dns_resolver(Arc::new(PublicAddresses));
+    }
+    builder.build().expect("the webhook HTTP client builds")
+}
+
+/// Resolves hosts to their public addresses only.
+struct PublicAddresses;
+
+impl Resolve for PublicAddresses {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let public: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| is_public(addr.ip()))
+                .collect();
+            if public.is_empty() {
+                return Err(format!("{} has no public address", host).into());
+            }
+            Ok(Box::new(public.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Whether `ip` is reachable on the public internet, rather than loopback,
+/// private, link-local, shared, documentation or otherwise reserved.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                || a == 0
+                || (a == 100 && (64..128).contains(&b))
+                || a >= 240)
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_unspecified()
+                    || ip.is_loopback()
+                    || ip.is_multicast()
+                    || (first & 0xfe00) == 0xfc00
+                    || (first & 0xffc0) == 0xfe80
+                    || first == 0x2001 && ip.segments()[1] == 0x0db8)
+            }
+        },
+    }
+}
+
+/// Checks a URL to register: `https` or `http`, free of control
+/// characters, naming a host `targets` allows.
+fn parse_url(url: &str, targets: &WebhookTargets) -> Result<Url, String> {
+    if url.chars().any(char::is_control) {
+        return Err(format!("Webhook URL {:?} holds control characters", url));
+    }
+    let parsed = Url::parse(url).map_err(|e| format!("Bad webhook URL {}: {}", url, e))?;
+    if !matches!(parsed.scheme(), "https" | "http") {
+        return Err(format!("Unsupported webhook URL {}: only https:// and http:// are spoken", url));
+    }
+    if !parsed.username().is_empty() || parsed.password().is_some() {
+        return Err(format!("Webhook URL {} carries credentials", url));
+    }
+    let Some(host) = parsed.host_str() else {
+        return Err(format!("No host in {}", url));
+    };
+    if let Some(allowed) = &targets.allowed_hosts {
+        if !allowed.iter().any(|allowed| allowed.eq_ignore_ascii_case(host)) {
+            return Err(format!("Webhooks may not post to {}", host));
+        }
+    }
+    // Names are checked as they resolve, addresses here
+    let address = host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>().ok();
+    if address.is_some_and(|ip| !is_public(ip)) && !targets.allow_private_addresses {
+        return Err(format!("Webhooks may not post to {}, a non-public address", host));
+    }
+    Ok(parsed)
+}
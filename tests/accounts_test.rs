@@ -0,0 +1,114 @@
+use chrono::Utc;
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderStatus, OrderType}, Balance,
+    CancelOrderCommand, EngineError, OrderCommand, PlaceOrderCommand, RejectReason,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(user_id: Uuid, side: OrderSide, price: Decimal, quantity: Decimal) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(user_id, "BTC/USDT", OrderType::Limit, side, Some(price), quantity)
+}
+
+fn balance(available: i64, held: i64) -> Balance {
+    Balance {
+        available: Decimal::from(available),
+        held: Decimal::from(held),
+    }
+}
+
+fn funded_engine(users: &[(Uuid, &str, i64)]) -> MatchingEngine {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new())).with_accounts();
+    for (user_id, asset, amount) in users {
+        engine.deposit(*user_id, asset, Decimal::from(*amount)).unwrap();
+    }
+    engine
+}
+
+#[tokio::test]
+async fn test_orders_hold_funds_settle_on_fills_and_release_on_cancel() {
+    let (buyer, seller) = (Uuid::new_v4(), Uuid::new_v4());
+    let engine = funded_engine(&[(buyer, "USDT", 1000), (seller, "BTC", 5)]);
+
+    let bid = create_test_order_cmd(buyer, OrderSide::Buy, Decimal::from(100), Decimal::from(4));
+    let bid_id = bid.order_id;
+    engine.handle_command(OrderCommand::PlaceOrder(bid)).await.unwrap();
+    assert_eq!(engine.get_balance(buyer, "USDT").unwrap(), balance(600, 400));
+
+    let ask = create_test_order_cmd(seller, OrderSide::Sell, Decimal::from(100), Decimal::from(3));
+    engine.handle_command(OrderCommand::PlaceOrder(ask)).await.unwrap();
+    assert_eq!(engine.get_balance(buyer, "USDT").unwrap(), balance(600, 100));
+    assert_eq!(engine.get_balance(buyer, "BTC").unwrap(), balance(3, 0));
+    assert_eq!(engine.get_balance(seller, "BTC").unwrap(), balance(2, 0));
+    assert_eq!(engine.get_balance(seller, "USDT").unwrap(), balance(300, 0));
+
+    engine
+        .handle_command(OrderCommand::CancelOrder(CancelOrderCommand {
+            order_id: bid_id,
+            client_order_id: None,
+            user_id: buyer,
+            symbol: "BTC/USDT".to_string(),
+            timestamp: Utc::now(),
+        }))
+        .await
+        .unwrap();
+    let balances = engine.get_balances(buyer).unwrap();
+    assert_eq!(balances["USDT"], balance(700, 0));
+    assert_eq!(balances["BTC"].total(), Decimal::from(3));
+    assert!(engine.withdraw(buyer, "USDT", Decimal::from(701)).is_err());
+    assert_eq!(engine.withdraw(buyer, "USDT", Decimal::from(700)).unwrap(), balance(0, 0));
+}
+
+#[tokio::test]
+async fn test_orders_beyond_the_available_balance_are_rejected() {
+    let user_id = Uuid::new_v4();
+    let engine = funded_engine(&[(user_id, "USDT", 500)]);
+
+    let first = create_test_order_cmd(user_id, OrderSide::Buy, Decimal::from(100), Decimal::from(3));
+    engine.handle_command(OrderCommand::PlaceOrder(first)).await.unwrap();
+    let second = create_test_order_cmd(user_id, OrderSide::Buy, Decimal::from(100), Decimal::from(3));
+    let result = engine.handle_command(OrderCommand::PlaceOrder(second)).await;
+    assert_eq!(
+        result.unwrap_err(),
+        EngineError::InvalidOrder(RejectReason::InsufficientBalance {
+            asset: "USDT".to_string(),
+            required: Decimal::from(300),
+            available: Decimal::from(200),
+        })
+    );
+
+    let sell = create_test_order_cmd(user_id, OrderSide::Sell, Decimal::from(100), Decimal::ONE);
+    let result = engine.handle_command(OrderCommand::PlaceOrder(sell)).await;
+    assert!(matches!(
+        result,
+        Err(EngineError::InvalidOrder(RejectReason::InsufficientBalance { asset, .. })) if asset == "BTC"
+    ));
+    assert_eq!(engine.get_balance(user_id, "USDT").unwrap(), balance(200, 300));
+}
+
+#[tokio::test]
+async fn test_market_buy_spends_no_more_than_it_held() {
+    let (buyer, seller) = (Uuid::new_v4(), Uuid::new_v4());
+    let engine = funded_engine(&[(buyer, "USDT", 250), (seller, "BTC", 10)]);
+    for price in [100, 150] {
+        let ask = create_test_order_cmd(seller, OrderSide::Sell, Decimal::from(price), Decimal::ONE);
+        engine.handle_command(OrderCommand::PlaceOrder(ask)).await.unwrap();
+    }
+
+    let mut buy = create_test_order_cmd(buyer, OrderSide::Buy, Decimal::ZERO, Decimal::from(2));
+    buy.order_type = OrderType::Market;
+    buy.price = None;
+    let buy_id = buy.order_id;
+    engine.handle_command(OrderCommand::PlaceOrder(buy)).await.unwrap();
+    assert_eq!(engine.get_order(buy_id).unwrap().status, OrderStatus::Filled);
+    assert_eq!(engine.get_balance(buyer, "USDT").unwrap(), balance(0, 0));
+    assert_eq!(engine.get_balance(buyer, "BTC").unwrap(), balance(2, 0));
+
+    let ask = create_test_order_cmd(seller, OrderSide::Sell, Decimal::from(100), Decimal::ONE);
+    engine.handle_command(OrderCommand::PlaceOrder(ask)).await.unwrap();
+    let mut buy = create_test_order_cmd(buyer, OrderSide::Buy, Decimal::ZERO, Decimal::ONE);
+    buy.order_type = OrderType::Market;
+    buy.price = None;
+    let result = engine.handle_command(OrderCommand::PlaceOrder(buy)).await;
+    assert!(matches!(result, Err(EngineError::InvalidOrder(RejectReason::InsufficientBalance { .. }))));
+}
@@ -0,0 +1,96 @@
+use chrono::Utc;
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{Order, OrderSide, OrderStatus, OrderType},
+    AmendOrderCommand, Amendment, OrderCommand, OrderEvent, PlaceOrderCommand, PriorityPolicy,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(price: Decimal, quantity: Decimal, side: OrderSide) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), "BTC/USDT", OrderType::Limit, side, Some(price), quantity)
+}
+
+fn amend_cmd(order: &PlaceOrderCommand, price: Option<i64>, quantity: Option<i64>) -> OrderCommand {
+    OrderCommand::AmendOrder(AmendOrderCommand {
+        order_id: order.order_id,
+        client_order_id: None,
+        user_id: order.user_id,
+        symbol: order.symbol.clone(),
+        new_price: price.map(Decimal::from),
+        new_quantity: quantity.map(Decimal::from),
+        timestamp: Utc::now(),
+    })
+}
+
+/// Places two asks at 100 and returns them oldest first.
+async fn two_resting_asks(engine: &MatchingEngine) -> (PlaceOrderCommand, PlaceOrderCommand) {
+    let first = create_test_order_cmd(Decimal::from(100), Decimal::from(5), OrderSide::Sell);
+    let second = create_test_order_cmd(Decimal::from(100), Decimal::from(5), OrderSide::Sell);
+    engine.handle_place_order(first.clone()).await.unwrap();
+    engine.handle_place_order(second.clone()).await.unwrap();
+    (first, second)
+}
+
+async fn first_maker(engine: &MatchingEngine) -> Uuid {
+    let events = engine
+        .handle_place_order(create_test_order_cmd(Decimal::from(100), Decimal::ONE, OrderSide::Buy))
+        .await
+        .unwrap();
+    events.iter().find_map(|e| match e {
+        OrderEvent::OrderMatched(m) => Some(m.matched_order_id),
+        _ => None,
+    }).unwrap()
+}
+
+#[tokio::test]
+async fn test_quantity_decrease_keeps_priority() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let (first, _) = two_resting_asks(&engine).await;
+
+    let events = engine.handle_command(amend_cmd(&first, None, Some(3))).await.unwrap();
+    assert!(matches!(&events[..], [OrderEvent::OrderUpdated(u)] if u.retained_priority));
+    assert_eq!(engine.get_order(first.order_id).unwrap().quantity, Decimal::from(3));
+    assert_eq!(first_maker(&engine).await, first.order_id);
+}
+
+#[tokio::test]
+async fn test_quantity_increase_goes_to_back_of_queue() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let (first, second) = two_resting_asks(&engine).await;
+
+    let events = engine.handle_command(amend_cmd(&first, None, Some(8))).await.unwrap();
+    assert!(matches!(&events[..], [OrderEvent::OrderUpdated(u)] if !u.retained_priority));
+    assert_eq!(first_maker(&engine).await, second.order_id);
+}
+
+#[tokio::test]
+async fn test_price_change_matches_when_crossing() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let bid = create_test_order_cmd(Decimal::from(98), Decimal::from(2), OrderSide::Buy);
+    engine.handle_place_order(bid.clone()).await.unwrap();
+    let (first, _) = two_resting_asks(&engine).await;
+
+    let events = engine.handle_command(amend_cmd(&first, Some(98), None)).await.unwrap();
+    assert!(events.iter().any(|e| matches!(e, OrderEvent::OrderMatched(m) if m.matched_order_id == bid.order_id)));
+    let amended = engine.get_order(first.order_id).unwrap();
+    assert_eq!(amended.status, OrderStatus::PartiallyFilled);
+    assert_eq!(engine.get_order_book("BTC/USDT").unwrap().asks[0].price, Decimal::from(98));
+}
+
+struct KeepOnIncrease;
+
+impl PriorityPolicy for KeepOnIncrease {
+    fn retains_priority(&self, _order: &Order, _amendment: &Amendment) -> bool {
+        true
+    }
+}
+
+#[tokio::test]
+async fn test_custom_policy() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new())).with_priority_policy(KeepOnIncrease);
+    let (first, _) = two_resting_asks(&engine).await;
+
+    engine.handle_command(amend_cmd(&first, None, Some(8))).await.unwrap();
+    assert_eq!(first_maker(&engine).await, first.order_id);
+    assert!(engine.handle_command(amend_cmd(&first, None, Some(1))).await.is_err());
+}
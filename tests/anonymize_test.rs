@@ -0,0 +1,169 @@
+use chrono::{Duration, Utc};
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, Anonymizer, CancelOrderCommand, EventStore,
+    KillSwitchActivatedEvent, KillSwitchTarget, OrderCommand, OrderEvent, OrderSide, OrderType, Participant,
+    PlaceOrderCommand,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(user_id: Uuid, side: OrderSide, price: Decimal, quantity: Decimal) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(user_id, "BTC/USDT", OrderType::Limit, side, Some(price), quantity)
+}
+
+#[test]
+fn test_pseudonyms_are_consistent_and_timing_preserved() {
+    let user_id = Uuid::new_v4();
+    let place = create_test_order_cmd(user_id, OrderSide::Buy, Decimal::from(100), Decimal::from(2));
+    let cancel = CancelOrderCommand {
+        order_id: place.order_id,
+        client_order_id: None,
+        user_id,
+        symbol: place.symbol.clone(),
+        timestamp: place.timestamp + Duration::seconds(5),
+    };
+
+    let mut anonymizer = Anonymizer::with_seed(7);
+    anonymizer.time_shift = Duration::days(-30);
+    let (OrderCommand::PlaceOrder(anon_place), OrderCommand::CancelOrder(anon_cancel)) = (
+        anonymizer.anonymize_command(&OrderCommand::PlaceOrder(place.clone())).unwrap(),
+        anonymizer.anonymize_command(&OrderCommand::CancelOrder(cancel)).unwrap(),
+    ) else {
+        panic!("Command kinds must be preserved");
+    };
+
+    assert_ne!(anon_place.user_id, user_id);
+    assert_ne!(anon_place.order_id, place.order_id);
+    assert_eq!(anon_place.user_id, anon_cancel.user_id);
+    assert_eq!(anon_place.order_id, anon_cancel.order_id);
+    assert_eq!(anon_place.price, place.price);
+    assert_eq!(anon_place.quantity, place.quantity);
+    assert_eq!(anon_place.timestamp, place.timestamp - Duration::days(30));
+    assert_eq!(anon_cancel.timestamp - anon_place.timestamp, Duration::seconds(5));
+}
+
+#[test]
+fn test_jsonl_log_with_seed_is_reproducible() {
+    let user_id = Uuid::new_v4();
+    let log: String = [Decimal::from(100), Decimal::from(101)]
+        .into_iter()
+        .map(|price| {
+            let cmd = OrderCommand::PlaceOrder(create_test_order_cmd(user_id, OrderSide::Sell, price, Decimal::ONE));
+            serde_json::to_string(&cmd).unwrap() + "\n"
+        })
+        .collect();
+
+    let run = |keep_ids: bool| {
+        let mut anonymizer = Anonymizer::with_seed(42);
+        anonymizer.remap_ids = !keep_ids;
+        let mut out = Vec::new();
+        assert_eq!(anonymizer.anonymize_jsonl(log.as_bytes(), &mut out).unwrap(), 2);
+        String::from_utf8(out).unwrap()
+    };
+
+    let first = run(false);
+    assert_eq!(first, run(false));
+    assert!(!first.contains(&user_id.to_string()));
+
+    // Order ids survive when only users are pseudonymized
+    let kept = run(true);
+    let original: OrderCommand = serde_json::from_str(log.lines().next().unwrap()).unwrap();
+    let rewritten: OrderCommand = serde_json::from_str(kept.lines().next().unwrap()).unwrap();
+    match (original, rewritten) {
+        (OrderCommand::PlaceOrder(a), OrderCommand::PlaceOrder(b)) => {
+            assert_eq!(a.order_id, b.order_id);
+            assert_ne!(a.user_id, b.user_id);
+        }
+        _ => panic!("Command kinds must be preserved"),
+    }
+}
+
+#[test]
+fn test_user_targets_are_pseudonymized() {
+    let user_id = Uuid::new_v4();
+    let event = OrderEvent::KillSwitchActivated(KillSwitchActivatedEvent {
+        event_id: Uuid::new_v4(),
+        prev_hash: None,
+        target: KillSwitchTarget::User(user_id),
+        reason: "runaway algo".to_string(),
+        canceled_order_ids: Vec::new(),
+        timestamp: Utc::now(),
+    });
+    let mut anonymizer = Anonymizer::new();
+    let anonymized = serde_json::to_string(&anonymizer.anonymize_event(&event).unwrap()).unwrap();
+    assert!(!anonymized.contains(&user_id.to_string()));
+
+    let mut participant = serde_json::to_value(Participant::User(user_id)).unwrap();
+    anonymizer.rewrite_value(&mut participant);
+    let Participant::User(pseudonym) = serde_json::from_value(participant).unwrap() else {
+        panic!("Participant kinds must be preserved");
+    };
+    assert_ne!(pseudonym, user_id);
+    let OrderEvent::KillSwitchActivated(anonymized) = serde_json::from_str(&anonymized).unwrap() else {
+        panic!("Event kinds must be preserved");
+    };
+    assert_eq!(anonymized.target, KillSwitchTarget::User(pseudonym));
+}
+
+#[test]
+fn test_referrers_are_pseudonymized_even_without_id_remapping() {
+    let referrer = Uuid::new_v4();
+    let place = PlaceOrderCommand {
+        referrer_id: Some(referrer),
+        ..create_test_order_cmd(Uuid::new_v4(), OrderSide::Buy, Decimal::from(100), Decimal::ONE)
+    };
+    let mut anonymizer = Anonymizer::new();
+    anonymizer.remap_ids = false;
+    let command = OrderCommand::PlaceOrder(place.clone());
+    let OrderCommand::PlaceOrder(anonymized) = anonymizer.anonymize_command(&command).unwrap() else {
+        panic!("Command kinds must be preserved");
+    };
+    assert_eq!(anonymized.order_id, place.order_id);
+    assert!(anonymized.referrer_id.is_some_and(|id| id != referrer));
+}
+
+#[tokio::test]
+async fn test_hash_chain_is_rebuilt_over_anonymized_events() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new().with_hash_chain()));
+    for side in [OrderSide::Sell, OrderSide::Buy] {
+        let cmd = create_test_order_cmd(Uuid::new_v4(), side, Decimal::from(100), Decimal::ONE);
+        engine.handle_command(OrderCommand::PlaceOrder(cmd)).await.unwrap();
+    }
+    let events = engine.event_store().get_all_events().await.unwrap();
+
+    let mut anonymizer = Anonymizer::new();
+    let anonymized: Vec<OrderEvent> = events.iter().map(|e| anonymizer.anonymize_event(e).unwrap()).collect();
+    assert_eq!(anonymized[0].prev_hash(), None);
+    for (original, anonymized) in events.iter().zip(&anonymized).skip(1) {
+        assert!(anonymized.prev_hash().is_some());
+        assert_ne!(anonymized.prev_hash(), original.prev_hash());
+    }
+    let store = InMemoryEventStore::new();
+    store.save_events(anonymized.clone()).await.unwrap();
+    let report = store.verify_integrity(0..u64::MAX).await.unwrap();
+    assert!(report.is_intact());
+    assert_eq!(report.checked, anonymized.len() as u64);
+}
+
+#[test]
+fn test_fields_not_kept_are_blanked() {
+    let mut command = serde_json::to_value(OrderCommand::PlaceOrder(create_test_order_cmd(
+        Uuid::new_v4(),
+        OrderSide::Buy,
+        Decimal::from(100),
+        Decimal::ONE,
+    )))
+    .unwrap();
+    command["PlaceOrder"]["desk_note"] = serde_json::json!("call Alice on 555-0100");
+    command["PlaceOrder"]["desk_tags"] = serde_json::json!(["whale"]);
+    command["PlaceOrder"]["account_number"] = serde_json::json!(12345678);
+
+    let mut anonymizer = Anonymizer::new();
+    anonymizer.rewrite_value(&mut command);
+    let fields = &command["PlaceOrder"];
+    assert_eq!(fields["desk_note"], "");
+    assert_eq!(fields["desk_tags"], serde_json::json!([""]));
+    assert!(fields["account_number"].is_null());
+    assert_eq!(fields["symbol"], "BTC/USDT");
+    assert_eq!(fields["side"], "Buy");
+}
@@ -0,0 +1,186 @@
+use std::fs;
+
+use matching_engine::{
+    engine::MatchingEngine, types::{OrderSide, OrderType},
+    DirObjectStore, EventStore, FileEventStore, InMemoryObjectStore, OrderCommand, OrderEvent, PlaceOrderCommand,
+    TimeRange,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(side: OrderSide, price: Decimal) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), "BTC/USDT", OrderType::Limit, side, Some(price), Decimal::ONE)
+}
+
+fn event_ids(events: &[OrderEvent]) -> Vec<Uuid> {
+    events.iter().map(|e| e.event_id()).collect()
+}
+
+fn local_segments(dir: &std::path::Path) -> usize {
+    fs::read_dir(dir)
+        .unwrap()
+        .filter(|entry| entry.as_ref().unwrap().path().extension().is_some_and(|ext| ext == "log"))
+        .count()
+}
+
+#[tokio::test]
+async fn test_closed_segments_move_to_the_archive() {
+    let dir = std::env::temp_dir().join(format!("events-{}", Uuid::new_v4()));
+    let store = FileEventStore::open_with_archive(&dir, InMemoryObjectStore::new(), 1).await.unwrap();
+    let engine = MatchingEngine::new(Box::new(store.with_segment_bytes(1)));
+    let mut placed = Vec::new();
+    for price in [100, 101, 102, 103, 104] {
+        let cmd = create_test_order_cmd(OrderSide::Buy, Decimal::from(price));
+        placed.push(cmd.order_id);
+        engine.handle_command(OrderCommand::PlaceOrder(cmd)).await.unwrap();
+    }
+    let written = engine.event_store().get_all_events().await.unwrap();
+
+    // Archived in the background as segments close, keeping one closed
+    // segment besides the one being appended to
+    for _ in 0..200 {
+        if local_segments(&dir) == 2 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(local_segments(&dir), 2);
+
+    let events = engine.event_store();
+    assert_eq!(event_ids(&events.get_all_events().await.unwrap()), event_ids(&written));
+    assert_eq!(event_ids(&events.get_events(placed[0]).await.unwrap()), event_ids(&written[..1]));
+    let tail = events.read_from(3, 10).await.unwrap();
+    assert_eq!(tail.iter().map(|e| e.offset).collect::<Vec<_>>(), vec![3, 4]);
+    let all = events.get_events_by_symbol("BTC/USDT", TimeRange::all()).await.unwrap();
+    assert_eq!(all.len(), 5);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_archived_segments_are_fetched_back_on_reopen() {
+    let dir = std::env::temp_dir().join(format!("events-{}", Uuid::new_v4()));
+    let bucket = std::env::temp_dir().join(format!("bucket-{}", Uuid::new_v4()));
+    let store = FileEventStore::open(&dir).unwrap().with_segment_bytes(1);
+    for price in [100, 101, 102] {
+        let event = MatchingEngine::new(Box::new(matching_engine::event_store::InMemoryEventStore::new()))
+            .handle_command(OrderCommand::PlaceOrder(create_test_order_cmd(OrderSide::Sell, Decimal::from(price))))
+            .await
+            .unwrap();
+        store.save_events(event).await.unwrap();
+    }
+    let written = store.get_all_events().await.unwrap();
+    drop(store);
+
+    // Archiving can start on a log that was written without an archive
+    let store = FileEventStore::open_with_archive(&dir, DirObjectStore::open(&bucket).unwrap(), 0).await.unwrap();
+    assert_eq!(store.archive_segments().await.unwrap(), 2);
+    assert_eq!(store.archived_segment_count(), 2);
+    assert_eq!(local_segments(&dir), 1);
+    drop(store);
+
+    assert!(FileEventStore::open(&dir).is_err());
+    let store = FileEventStore::open_with_archive(&dir, DirObjectStore::open(&bucket).unwrap(), 0).await.unwrap();
+    assert_eq!(event_ids(&store.get_all_events().await.unwrap()), event_ids(&written));
+    assert_eq!(store.segment_count(), 3);
+
+    fs::remove_dir_all(dir).unwrap();
+    fs::remove_dir_all(bucket).unwrap();
+}
+
+
+#[cfg(feature = "s3")]
+mod s3 {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use matching_engine::{ObjectStore, S3ObjectStore};
+    use object_store::aws::AmazonS3Builder;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    type Requests = Arc<Mutex<Vec<String>>>;
+
+    /// Serves PUTs and GETs from memory, recording each request's head
+    /// with its header names lowercased.
+    async fn fake_s3() -> (String, Requests) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Requests::default();
+        let seen = requests.clone();
+        tokio::spawn(async move {
+            let mut objects: HashMap<String, Vec<u8>> = HashMap::new();
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0; 4096];
+                let (head, body) = loop {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                    if let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                        let head: Vec<String> = String::from_utf8_lossy(&request[..end])
+                            .lines()
+                            .map(|line| match line.split_once(": ") {
+                                Some((name, value)) => format!("{}: {}", name.to_lowercase(), value),
+                                None => line.to_string(),
+                            })
+                            .collect();
+                        let head = head.join("\r\n");
+                        let len: usize = head
+                            .lines()
+                            .find_map(|line| line.strip_prefix("content-length: "))
+                            .map_or(0, |len| len.parse().unwrap());
+                        while request.len() < end + 4 + len {
+                            let n = socket.read(&mut buf).await.unwrap();
+                            request.extend_from_slice(&buf[..n]);
+                        }
+                        break (head, request[end + 4..end + 4 + len].to_vec());
+                    }
+                };
+                let mut line = head.split_whitespace();
+                let (method, path) = (line.next().unwrap().to_string(), line.next().unwrap().to_string());
+                seen.lock().unwrap().push(head);
+                let found = "HTTP/1.1 200 OK\r\nConnection: close\r\nETag: \"1\"\r\n\
+                             Last-Modified: Thu, 15 Oct 2026 10:00:00 GMT\r\nContent-Length:";
+                let response = match (method.as_str(), objects.get(&path)) {
+                    ("PUT", _) => {
+                        objects.insert(path, body);
+                        format!("{} 0\r\n\r\n", found).into_bytes()
+                    }
+                    ("GET", Some(object)) => {
+                        let mut response = format!("{} {}\r\n\r\n", found, object.len()).into_bytes();
+                        response.extend_from_slice(object);
+                        response
+                    }
+                    _ => b"HTTP/1.1 404 Not Found\r\nConnection: close\r\nContent-Length: 0\r\n\r\n".to_vec(),
+                };
+                socket.write_all(&response).await.unwrap();
+            }
+        });
+        (endpoint, requests)
+    }
+
+    #[tokio::test]
+    async fn test_objects_round_trip_through_signed_requests() {
+        let (endpoint, requests) = fake_s3().await;
+        let builder = AmazonS3Builder::new()
+            .with_endpoint(&endpoint)
+            .with_allow_http(true)
+            .with_bucket_name("events")
+            .with_region("us-east-1")
+            .with_access_key_id("AKID")
+            .with_secret_access_key("secret");
+        let store = S3ObjectStore::from_builder(builder).unwrap();
+        store.put("00000000000000000000.log", b"abc".to_vec()).await.unwrap();
+        assert_eq!(store.get("00000000000000000000.log").await.unwrap(), Some(b"abc".to_vec()));
+        assert_eq!(store.get("missing").await.unwrap(), None);
+
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].starts_with("PUT /events/00000000000000000000.log HTTP/1.1"));
+        assert!(requests[1].contains("authorization: AWS4-HMAC-SHA256 Credential=AKID/"));
+        assert!(requests[1].contains("/us-east-1/s3/aws4_request"));
+        // Credentials only go out over HTTPS unless a builder allows otherwise
+        assert!(S3ObjectStore::new(&endpoint, "events", "us-east-1", "AKID", "secret").is_err());
+        assert!(S3ObjectStore::new("https://s3.example.com", "events", "us-east-1", "AKID", "secret").is_ok());
+    }
+}
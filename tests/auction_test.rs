@@ -0,0 +1,61 @@
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType},
+    OrderEvent, PlaceOrderCommand,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(price: Decimal, quantity: Decimal, side: OrderSide) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), "BTC/USDT", OrderType::Limit, side, Some(price), quantity)
+}
+
+#[tokio::test]
+async fn test_auction_accumulates_then_uncrosses_at_single_price() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    engine.start_auction("BTC/USDT").await.unwrap();
+
+    for (price, quantity, side) in [
+        (102, 3, OrderSide::Buy),
+        (101, 2, OrderSide::Buy),
+        (99, 2, OrderSide::Sell),
+        (100, 2, OrderSide::Sell),
+        (103, 5, OrderSide::Sell),
+    ] {
+        let events = engine
+            .handle_place_order(create_test_order_cmd(Decimal::from(price), Decimal::from(quantity), side))
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1); // OrderPlaced only, nothing matches
+    }
+
+    // 4 executable at both 100 and 101 with a buy surplus, so the higher wins
+    let indicative = engine.indicative_auction_price("BTC/USDT").unwrap();
+    assert_eq!(indicative.volume, Decimal::from(4));
+    assert_eq!(indicative.price, Decimal::from(101));
+
+    let events = engine.run_auction("BTC/USDT").await.unwrap();
+    assert!(matches!(&events[0], OrderEvent::AuctionPriceDetermined(a) if a.price == Decimal::from(101)));
+    let matched: Vec<_> = events.iter().filter_map(|e| match e {
+        OrderEvent::OrderMatched(m) => Some(m),
+        _ => None,
+    }).collect();
+    assert!(matched.iter().all(|m| m.price == Decimal::from(101)));
+    assert_eq!(matched.iter().map(|m| m.quantity).sum::<Decimal>(), Decimal::from(4));
+
+    assert!(!engine.is_in_auction("BTC/USDT"));
+    let order_book = engine.get_order_book("BTC/USDT").unwrap();
+    assert_eq!(order_book.bids[0].price, Decimal::from(101));
+    assert_eq!(order_book.bids[0].quantity, Decimal::from(1));
+    assert_eq!(order_book.asks[0].price, Decimal::from(103));
+}
+
+#[tokio::test]
+async fn test_auction_rejects_market_orders() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    engine.start_auction("BTC/USDT").await.unwrap();
+
+    let mut market_buy = create_test_order_cmd(Decimal::ZERO, Decimal::from(1), OrderSide::Buy);
+    market_buy.order_type = OrderType::Market;
+    market_buy.price = None;
+    assert!(engine.handle_place_order(market_buy).await.is_err());
+}
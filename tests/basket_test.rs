@@ -0,0 +1,110 @@
+use chrono::Utc;
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderStatus, OrderType},
+    BasketExecution, BasketValidation, EngineError, OrderCommand, OrderEvent, PlaceBasketCommand,
+    PlaceOrderCommand, RejectReason,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(symbol: &str, price: Decimal, quantity: Decimal, side: OrderSide) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), symbol, OrderType::Limit, side, Some(price), quantity)
+}
+
+fn basket(legs: Vec<PlaceOrderCommand>, validation: BasketValidation, execution: BasketExecution) -> OrderCommand {
+    OrderCommand::PlaceBasket(PlaceBasketCommand {
+        basket_id: Uuid::new_v4(),
+        legs,
+        validation,
+        execution,
+        timestamp: Utc::now(),
+    })
+}
+
+#[tokio::test]
+async fn test_all_or_nothing_rejects_whole_basket_with_leg_reasons() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let good = create_test_order_cmd("BTC/USDT", Decimal::from(100), Decimal::ONE, OrderSide::Buy);
+    let bad = create_test_order_cmd("ETH/USDT", Decimal::from(10), Decimal::ZERO, OrderSide::Buy);
+
+    let result = engine
+        .handle_command(basket(
+            vec![good.clone(), bad.clone()],
+            BasketValidation::AllOrNothing,
+            BasketExecution::Independent,
+        ))
+        .await;
+
+    let Err(EngineError::BasketRejected { legs, .. }) = result else {
+        panic!("expected the basket to be rejected, got {:?}", result);
+    };
+    assert_eq!(legs.len(), 1);
+    assert_eq!(legs[0].order_id, bad.order_id);
+    assert_eq!(legs[0].error, EngineError::InvalidOrder(RejectReason::NonPositiveQuantity(Decimal::ZERO)));
+    assert!(engine.get_order(good.order_id).is_none());
+}
+
+#[tokio::test]
+async fn test_best_effort_places_valid_legs() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let good = create_test_order_cmd("BTC/USDT", Decimal::from(100), Decimal::ONE, OrderSide::Buy);
+    let bad = create_test_order_cmd("ETH/USDT", Decimal::ZERO, Decimal::ONE, OrderSide::Buy);
+
+    let events = engine
+        .handle_command(basket(
+            vec![good.clone(), bad.clone()],
+            BasketValidation::BestEffort,
+            BasketExecution::Independent,
+        ))
+        .await
+        .unwrap();
+
+    assert!(engine.get_order(good.order_id).unwrap().is_open());
+    assert!(engine.get_order(bad.order_id).is_none());
+    assert!(events
+        .iter()
+        .any(|e| matches!(e, OrderEvent::OrderRejected(r) if r.order_id == bad.order_id)));
+}
+
+#[tokio::test]
+async fn test_duplicate_ids_within_basket_are_rejected() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let first = create_test_order_cmd("BTC/USDT", Decimal::from(100), Decimal::ONE, OrderSide::Buy);
+    let second = PlaceOrderCommand {
+        order_id: first.order_id,
+        ..create_test_order_cmd("ETH/USDT", Decimal::from(10), Decimal::ONE, OrderSide::Buy)
+    };
+
+    let result = engine
+        .handle_command(basket(vec![first, second], BasketValidation::AllOrNothing, BasketExecution::Independent))
+        .await;
+    assert!(matches!(result, Err(EngineError::BasketRejected { .. })));
+}
+
+#[tokio::test]
+async fn test_contingent_execution_stops_at_unfilled_leg() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    engine
+        .handle_place_order(create_test_order_cmd("BTC/USDT", Decimal::from(100), Decimal::ONE, OrderSide::Sell))
+        .await
+        .unwrap();
+
+    let fills = create_test_order_cmd("BTC/USDT", Decimal::from(100), Decimal::ONE, OrderSide::Buy);
+    let rests = create_test_order_cmd("ETH/USDT", Decimal::from(10), Decimal::ONE, OrderSide::Buy);
+    let dropped = create_test_order_cmd("SOL/USDT", Decimal::from(1), Decimal::ONE, OrderSide::Buy);
+    let events = engine
+        .handle_command(basket(
+            vec![fills.clone(), rests.clone(), dropped.clone()],
+            BasketValidation::AllOrNothing,
+            BasketExecution::Contingent,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(engine.get_order(fills.order_id).unwrap().status, OrderStatus::Filled);
+    assert!(engine.get_order(rests.order_id).unwrap().is_open());
+    assert!(engine.get_order(dropped.order_id).is_none());
+    assert!(events
+        .iter()
+        .any(|e| matches!(e, OrderEvent::OrderRejected(r) if r.order_id == dropped.order_id)));
+}
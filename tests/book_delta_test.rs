@@ -0,0 +1,63 @@
+use chrono::Utc;
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType}, BookDelta,
+    CancelOrderCommand, OrderCommand, PlaceOrderCommand,
+};
+use rust_decimal::Decimal;
+use tokio::sync::broadcast::error::TryRecvError;
+use uuid::Uuid;
+
+fn create_test_order_cmd(side: OrderSide, price: Decimal, quantity: Decimal) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), "BTC/USDT", OrderType::Limit, side, Some(price), quantity)
+}
+
+fn level(delta: &BookDelta) -> (OrderSide, Decimal, Decimal, u64, u64) {
+    (delta.side, delta.price, delta.quantity, delta.order_count, delta.sequence)
+}
+
+#[tokio::test]
+async fn test_deltas_follow_adds_fills_and_cancels() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let mut deltas = engine.subscribe_book_deltas();
+
+    let ask = create_test_order_cmd(OrderSide::Sell, Decimal::from(100), Decimal::from(3));
+    engine.handle_place_order(ask.clone()).await.unwrap();
+    engine
+        .handle_place_order(create_test_order_cmd(OrderSide::Buy, Decimal::from(100), Decimal::ONE))
+        .await
+        .unwrap();
+    engine
+        .handle_command(OrderCommand::CancelOrder(CancelOrderCommand {
+            order_id: ask.order_id,
+            client_order_id: None,
+            user_id: ask.user_id,
+            symbol: ask.symbol.clone(),
+            timestamp: Utc::now(),
+        }))
+        .await
+        .unwrap();
+
+    let received: Vec<_> = std::iter::from_fn(|| deltas.try_recv().ok()).map(|d| level(&d)).collect();
+    assert_eq!(
+        received,
+        [
+            (OrderSide::Sell, Decimal::from(100), Decimal::from(3), 1, 1),
+            (OrderSide::Sell, Decimal::from(100), Decimal::from(2), 1, 2),
+            (OrderSide::Sell, Decimal::from(100), Decimal::ZERO, 0, 3),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_hidden_orders_publish_nothing() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let mut deltas = engine.subscribe_book_deltas();
+
+    let hidden = PlaceOrderCommand {
+        displayed: false,
+        ..create_test_order_cmd(OrderSide::Buy, Decimal::from(99), Decimal::ONE)
+    };
+    engine.handle_place_order(hidden).await.unwrap();
+
+    assert_eq!(deltas.try_recv().unwrap_err(), TryRecvError::Empty);
+}
@@ -0,0 +1,98 @@
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderStatus, OrderType},
+    BookLimitPolicy, BookLimits, EngineConfig, EngineError, OrderEvent, PlaceOrderCommand, RejectReason,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(side: OrderSide, price: Decimal) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), "BTC/USDT", OrderType::Limit, side, Some(price), Decimal::ONE)
+}
+
+fn create_engine(book_limits: BookLimits) -> MatchingEngine {
+    MatchingEngine::builder(Box::new(InMemoryEventStore::new()))
+        .config(EngineConfig {
+            book_limits,
+            ..EngineConfig::default()
+        })
+        .build()
+}
+
+#[tokio::test]
+async fn test_orders_past_the_limits_are_rejected() {
+    let engine = create_engine(BookLimits {
+        max_levels_per_side: Some(2),
+        max_orders_per_level: Some(2),
+        policy: BookLimitPolicy::Reject,
+    });
+    for price in [100, 99, 100] {
+        engine
+            .handle_place_order(create_test_order_cmd(OrderSide::Buy, Decimal::from(price)))
+            .await
+            .unwrap();
+    }
+
+    let result = engine.handle_place_order(create_test_order_cmd(OrderSide::Buy, Decimal::from(101))).await;
+    assert_eq!(
+        result.unwrap_err(),
+        EngineError::InvalidOrder(RejectReason::TooManyPriceLevels { limit: 2 })
+    );
+    let result = engine.handle_place_order(create_test_order_cmd(OrderSide::Buy, Decimal::from(100))).await;
+    assert_eq!(
+        result.unwrap_err(),
+        EngineError::InvalidOrder(RejectReason::PriceLevelFull {
+            price: Decimal::from(100),
+            limit: 2
+        })
+    );
+
+    // The other side has room of its own
+    engine
+        .handle_place_order(create_test_order_cmd(OrderSide::Sell, Decimal::from(105)))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_worst_level_is_evicted_for_a_better_one() {
+    let engine = create_engine(BookLimits {
+        max_levels_per_side: Some(2),
+        max_orders_per_level: None,
+        policy: BookLimitPolicy::EvictWorstLevel,
+    });
+    let worst = create_test_order_cmd(OrderSide::Sell, Decimal::from(103));
+    for cmd in [
+        create_test_order_cmd(OrderSide::Sell, Decimal::from(101)),
+        worst.clone(),
+    ] {
+        engine.handle_place_order(cmd).await.unwrap();
+    }
+
+    let events = engine
+        .handle_place_order(create_test_order_cmd(OrderSide::Sell, Decimal::from(102)))
+        .await
+        .unwrap();
+    assert!(events
+        .iter()
+        .any(|e| matches!(e, OrderEvent::OrderCanceled(c) if c.order_id == worst.order_id)));
+    let evicted = events.iter().find_map(|e| match e {
+        OrderEvent::BookLevelEvicted(e) => Some(e),
+        _ => None,
+    });
+    assert_eq!(evicted.map(|e| (e.price, e.order_ids.clone())), Some((Decimal::from(103), vec![worst.order_id])));
+    assert_eq!(engine.get_order(worst.order_id).unwrap().status, OrderStatus::Canceled);
+
+    let asks: Vec<Decimal> = engine
+        .get_depth_by_side("BTC/USDT", OrderSide::Sell, 10)
+        .iter()
+        .map(|e| e.price)
+        .collect();
+    assert_eq!(asks, [Decimal::from(101), Decimal::from(102)]);
+
+    // Nothing is evicted for an order that would be the worst level itself
+    let result = engine.handle_place_order(create_test_order_cmd(OrderSide::Sell, Decimal::from(104))).await;
+    assert_eq!(
+        result.unwrap_err(),
+        EngineError::InvalidOrder(RejectReason::TooManyPriceLevels { limit: 2 })
+    );
+}
@@ -0,0 +1,213 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{Order, OrderSide, OrderType},
+    symbols::SymbolConfig, Asks, Bids, BookFactory, EngineError, OrderBookOps, PlaceOrderCommand, PriceLadder, RejectReason,
+    SkipListOrderBook, SymbolBook,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(side: OrderSide, price: Decimal, quantity: Decimal) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), "BTC/USDT", OrderType::Limit, side, Some(price), quantity)
+}
+
+/// Skip list books that count how many were built.
+#[derive(Clone, Default)]
+struct CountingBooks(Arc<AtomicUsize>);
+
+impl BookFactory for CountingBooks {
+    fn new_book(&self, _symbol: &str) -> Box<dyn OrderBookOps> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Box::new(SymbolBook::<SkipListOrderBook>::default())
+    }
+}
+
+#[tokio::test]
+async fn test_engine_builds_books_through_the_factory() {
+    let factory = CountingBooks::default();
+    let engine = MatchingEngine::builder(Box::new(InMemoryEventStore::new()))
+        .book_factory(factory.clone())
+        .build();
+
+    engine
+        .handle_place_order(create_test_order_cmd(OrderSide::Sell, Decimal::from(101), Decimal::ONE))
+        .await
+        .unwrap();
+    engine
+        .handle_place_order(create_test_order_cmd(OrderSide::Buy, Decimal::from(99), Decimal::ONE))
+        .await
+        .unwrap();
+    engine
+        .handle_place_order(create_test_order_cmd(OrderSide::Buy, Decimal::from(101), Decimal::from(2)))
+        .await
+        .unwrap();
+
+    assert_eq!(factory.0.load(Ordering::SeqCst), 1);
+    let book = engine.get_order_book("BTC/USDT").unwrap();
+    let bids: Vec<(Decimal, Decimal)> = book.bids.iter().map(|e| (e.price, e.quantity)).collect();
+    assert_eq!(bids, [(Decimal::from(101), Decimal::ONE), (Decimal::from(99), Decimal::ONE)]);
+    assert!(book.asks.is_empty());
+}
+
+#[test]
+fn test_match_against_respects_the_limit() {
+    let mut book = SymbolBook::<SkipListOrderBook>::default();
+    book.add(Order::new(
+        Uuid::new_v4(),
+        "BTC/USDT".to_string(),
+        OrderType::Limit,
+        OrderSide::Sell,
+        Some(Decimal::from(100)),
+        Decimal::ONE,
+    ));
+
+    assert_eq!(book.match_against(OrderSide::Buy, Some(Decimal::from(100))), Some(Decimal::from(100)));
+    assert_eq!(book.match_against(OrderSide::Buy, None), Some(Decimal::from(100)));
+    assert_eq!(book.match_against(OrderSide::Buy, Some(Decimal::from(99))), None);
+    assert_eq!(book.match_against(OrderSide::Sell, None), None);
+}
+
+#[test]
+fn test_bids_and_asks_rank_their_own_way() {
+    let mut book = SymbolBook::<SkipListOrderBook>::default();
+    let order = |side, price| {
+        Order::new(Uuid::new_v4(), "BTC/USDT".to_string(), OrderType::Limit, side, Some(Decimal::from(price)), Decimal::ONE)
+    };
+    for (side, price) in [(OrderSide::Buy, 98), (OrderSide::Buy, 99), (OrderSide::Sell, 101), (OrderSide::Sell, 103)] {
+        book.add(order(side, price));
+    }
+
+    assert_eq!((book.bids().best(), book.bids().worst()), (Some(Decimal::from(99)), Some(Decimal::from(98))));
+    assert_eq!((book.asks().best(), book.asks().worst()), (Some(Decimal::from(101)), Some(Decimal::from(103))));
+    assert_eq!(book.bids().levels().next().map(|(price, _)| price), Some(Decimal::from(99)));
+    assert!(Bids::<SkipListOrderBook>::is_better(Decimal::from(99), Decimal::from(98)));
+    assert!(Asks::<SkipListOrderBook>::is_better(Decimal::from(101), Decimal::from(103)));
+    assert_eq!(book.crossed(), None);
+
+    book.add(order(OrderSide::Buy, 101));
+    assert_eq!(book.crossed(), Some((Decimal::from(101), Decimal::from(101))));
+}
+
+#[cfg(feature = "btree-book")]
+#[tokio::test]
+async fn test_btree_books_match_like_skip_lists() {
+    use matching_engine::{BTreeBooks, SkipListBooks};
+
+    let btree = MatchingEngine::builder(Box::new(InMemoryEventStore::new()))
+        .book_factory(BTreeBooks)
+        .build();
+    let skip_list = MatchingEngine::builder(Box::new(InMemoryEventStore::new()))
+        .book_factory(SkipListBooks)
+        .build();
+
+    let commands = [
+        create_test_order_cmd(OrderSide::Sell, Decimal::from(103), Decimal::from(2)),
+        create_test_order_cmd(OrderSide::Sell, Decimal::from(101), Decimal::ONE),
+        create_test_order_cmd(OrderSide::Sell, Decimal::from(101), Decimal::from(3)),
+        create_test_order_cmd(OrderSide::Buy, Decimal::from(98), Decimal::ONE),
+        create_test_order_cmd(OrderSide::Buy, Decimal::from(102), Decimal::from(2)),
+    ];
+    for cmd in commands {
+        btree.handle_place_order(cmd.clone()).await.unwrap();
+        skip_list.handle_place_order(cmd).await.unwrap();
+    }
+
+    let (a, b) = (btree.get_order_book("BTC/USDT").unwrap(), skip_list.get_order_book("BTC/USDT").unwrap());
+    assert_eq!(a.bids, b.bids);
+    assert_eq!(a.asks, b.asks);
+    assert_eq!(a.asks[0].price, Decimal::from(101));
+    assert_eq!(a.asks[0].quantity, Decimal::from(2));
+}
+
+#[tokio::test]
+async fn test_laddered_symbol_rejects_off_tick_prices_and_matches() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    engine.symbols().register(SymbolConfig {
+        ladder: Some(PriceLadder::new(Decimal::new(5, 1), Decimal::from(50), Decimal::from(150))),
+        ..SymbolConfig::new("BTC/USDT")
+    }).unwrap();
+
+    let result = engine
+        .handle_place_order(create_test_order_cmd(OrderSide::Sell, Decimal::new(1002, 1), Decimal::ONE))
+        .await;
+    assert_eq!(
+        result.unwrap_err(),
+        EngineError::InvalidOrder(RejectReason::PriceNotOnTick {
+            price: Decimal::new(1002, 1),
+            tick_size: Decimal::new(5, 1),
+        })
+    );
+
+    engine
+        .handle_place_order(create_test_order_cmd(OrderSide::Sell, Decimal::new(1005, 1), Decimal::ONE))
+        .await
+        .unwrap();
+    engine
+        .handle_place_order(create_test_order_cmd(OrderSide::Sell, Decimal::from(100), Decimal::ONE))
+        .await
+        .unwrap();
+    engine
+        .handle_place_order(create_test_order_cmd(OrderSide::Buy, Decimal::from(101), Decimal::new(15, 1)))
+        .await
+        .unwrap();
+
+    let book = engine.get_order_book("BTC/USDT").unwrap();
+    assert!(book.bids.is_empty());
+    assert_eq!(book.asks.len(), 1);
+    assert_eq!(book.asks[0].price, Decimal::new(1005, 1));
+    assert_eq!(book.asks[0].quantity, Decimal::new(5, 1));
+}
+
+#[tokio::test]
+async fn test_checkpoint_restores_book_and_priorities() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let first = create_test_order_cmd(OrderSide::Sell, Decimal::from(101), Decimal::ONE);
+    let second = create_test_order_cmd(OrderSide::Sell, Decimal::from(101), Decimal::from(2));
+    let bid = create_test_order_cmd(OrderSide::Buy, Decimal::from(99), Decimal::from(3));
+    for cmd in [first.clone(), second.clone(), bid.clone()] {
+        engine.handle_place_order(cmd).await.unwrap();
+    }
+    let checkpoint = engine.checkpoint_book("BTC/USDT").unwrap();
+
+    let restored = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    restored.restore_book("BTC/USDT", &checkpoint).unwrap();
+    assert_eq!(restored.get_l3_snapshot("BTC/USDT").asks, engine.get_l3_snapshot("BTC/USDT").asks);
+    assert_eq!(restored.get_order(bid.order_id).map(|o| o.remaining_quantity()), Some(Decimal::from(3)));
+    assert!(restored.restore_book("BTC/USDT", &checkpoint).is_err());
+
+    let taker = create_test_order_cmd(OrderSide::Buy, Decimal::from(101), Decimal::ONE);
+    restored.handle_place_order(taker).await.unwrap();
+    assert_eq!(restored.get_order(first.order_id).map(|o| o.remaining_quantity()), Some(Decimal::ZERO));
+    assert_eq!(restored.get_order(second.order_id).map(|o| o.remaining_quantity()), Some(Decimal::from(2)));
+}
+
+#[tokio::test]
+async fn test_memory_footprint_tracks_book_growth() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    assert!(engine.book_memory_footprint("BTC/USDT").is_none());
+
+    engine
+        .handle_place_order(create_test_order_cmd(OrderSide::Buy, Decimal::from(99), Decimal::ONE))
+        .await
+        .unwrap();
+    let small = engine.book_memory_footprint("BTC/USDT").unwrap();
+    for price in 80..99 {
+        engine
+            .handle_place_order(create_test_order_cmd(OrderSide::Buy, Decimal::from(price), Decimal::ONE))
+            .await
+            .unwrap();
+    }
+    engine
+        .handle_place_order(create_test_order_cmd(OrderSide::Sell, Decimal::from(101), Decimal::ONE))
+        .await
+        .unwrap();
+
+    let large = engine.book_memory_footprint("BTC/USDT").unwrap();
+    assert_eq!((small.levels, small.orders), (1, 1));
+    assert_eq!((large.levels, large.orders), (21, 21));
+    assert!(large.level_bytes > small.level_bytes);
+    assert!(large.order_bytes > small.order_bytes);
+    assert!(large.total_bytes() > small.total_bytes());
+}
@@ -0,0 +1,74 @@
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderStatus, OrderType},
+    OrderCommand, OrderEvent, PlaceBracketOrderCommand, PlaceOrderCommand,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(price: Decimal, quantity: Decimal, side: OrderSide) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), "BTC/USDT", OrderType::Limit, side, Some(price), quantity)
+}
+
+fn create_bracket_cmd(entry: PlaceOrderCommand, stop_loss: Decimal, take_profit: Decimal) -> PlaceBracketOrderCommand {
+    PlaceBracketOrderCommand {
+        bracket_id: Uuid::new_v4(),
+        entry,
+        stop_loss_order_id: Uuid::new_v4(),
+        stop_loss_price: stop_loss,
+        take_profit_order_id: Uuid::new_v4(),
+        take_profit_price: take_profit,
+    }
+}
+
+#[tokio::test]
+async fn test_bracket_children_wait_for_entry_fill() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+
+    let entry = create_test_order_cmd(Decimal::from(100), Decimal::from(1), OrderSide::Buy);
+    let bracket = create_bracket_cmd(entry, Decimal::from(90), Decimal::from(110));
+    let (sl_id, tp_id) = (bracket.stop_loss_order_id, bracket.take_profit_order_id);
+    let events = engine.handle_command(OrderCommand::PlaceBracketOrder(bracket)).await.unwrap();
+    assert!(events.iter().any(|e| matches!(e, OrderEvent::BracketOrderPlaced(_))));
+
+    // A print through the take-profit must not trigger a dormant child
+    let _ = engine.handle_place_order(create_test_order_cmd(Decimal::from(115), Decimal::from(1), OrderSide::Sell)).await.unwrap();
+    let _ = engine.handle_place_order(create_test_order_cmd(Decimal::from(115), Decimal::from(1), OrderSide::Buy)).await.unwrap();
+    assert_eq!(engine.get_order(sl_id).unwrap().status, OrderStatus::Pending);
+    assert_eq!(engine.get_order(tp_id).unwrap().status, OrderStatus::Pending);
+
+    // Filling the entry activates both exits
+    let events = engine.handle_place_order(create_test_order_cmd(Decimal::from(100), Decimal::from(1), OrderSide::Sell)).await.unwrap();
+    assert!(events.iter().any(|e| matches!(e, OrderEvent::BracketOrderActivated(_))));
+}
+
+#[tokio::test]
+async fn test_bracket_take_profit_cancels_stop_loss() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+
+    let entry = create_test_order_cmd(Decimal::from(100), Decimal::from(1), OrderSide::Buy);
+    let bracket = create_bracket_cmd(entry, Decimal::from(90), Decimal::from(110));
+    let (bracket_id, sl_id, tp_id) = (bracket.bracket_id, bracket.stop_loss_order_id, bracket.take_profit_order_id);
+    let _ = engine.handle_command(OrderCommand::PlaceBracketOrder(bracket)).await.unwrap();
+    let _ = engine.handle_place_order(create_test_order_cmd(Decimal::from(100), Decimal::from(1), OrderSide::Sell)).await.unwrap();
+
+    // Resting bid for the take-profit to sell into, then a print at 110
+    let _ = engine.handle_place_order(create_test_order_cmd(Decimal::from(109), Decimal::from(1), OrderSide::Buy)).await.unwrap();
+    let _ = engine.handle_place_order(create_test_order_cmd(Decimal::from(110), Decimal::from(1), OrderSide::Sell)).await.unwrap();
+    let events = engine.handle_place_order(create_test_order_cmd(Decimal::from(110), Decimal::from(1), OrderSide::Buy)).await.unwrap();
+
+    assert_eq!(engine.get_order(tp_id).unwrap().status, OrderStatus::Filled);
+    assert_eq!(engine.get_order(sl_id).unwrap().status, OrderStatus::Canceled);
+    assert!(events.iter().any(|e| matches!(
+        e,
+        OrderEvent::BracketOrderCompleted(c) if c.bracket_id == bracket_id && c.canceled_order_id == sl_id
+    )));
+}
+
+#[tokio::test]
+async fn test_bracket_rejects_unordered_prices() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+
+    let entry = create_test_order_cmd(Decimal::from(100), Decimal::from(1), OrderSide::Buy);
+    let bracket = create_bracket_cmd(entry, Decimal::from(110), Decimal::from(90));
+    assert!(engine.handle_command(OrderCommand::PlaceBracketOrder(bracket)).await.is_err());
+}
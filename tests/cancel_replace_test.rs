@@ -0,0 +1,75 @@
+use chrono::Utc;
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderStatus, OrderType},
+    CancelReplaceCommand, OrderCommand, OrderEvent, PlaceOrderCommand,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(user_id: Uuid, price: Decimal, quantity: Decimal, side: OrderSide) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(user_id, "BTC/USDT", OrderType::Limit, side, Some(price), quantity)
+}
+
+fn replace_cmd(original: &PlaceOrderCommand, price: Decimal, quantity: Decimal) -> CancelReplaceCommand {
+    CancelReplaceCommand {
+        order_id: original.order_id,
+        client_order_id: None,
+        user_id: original.user_id,
+        symbol: original.symbol.clone(),
+        new_order_id: Uuid::new_v4(),
+        new_client_order_id: None,
+        new_price: Some(price),
+        new_quantity: quantity,
+        timestamp: Utc::now(),
+    }
+}
+
+#[tokio::test]
+async fn test_cancel_replace_links_orders() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let user_id = Uuid::new_v4();
+    let original = create_test_order_cmd(user_id, Decimal::from(100), Decimal::from(5), OrderSide::Sell);
+    engine.handle_place_order(original.clone()).await.unwrap();
+
+    // 2 of 5 fill before the amend
+    engine
+        .handle_place_order(create_test_order_cmd(Uuid::new_v4(), Decimal::from(100), Decimal::from(2), OrderSide::Buy))
+        .await
+        .unwrap();
+
+    let cmd = replace_cmd(&original, Decimal::from(105), Decimal::from(4));
+    let events = engine.handle_command(OrderCommand::CancelReplace(cmd.clone())).await.unwrap();
+    assert!(matches!(&events[0], OrderEvent::OrderCanceled(c)
+        if c.order_id == original.order_id && c.replaced_by_order_id == Some(cmd.new_order_id)));
+    assert!(matches!(&events[1], OrderEvent::OrderPlaced(p)
+        if p.order_id == cmd.new_order_id && p.replaces_order_id == Some(original.order_id)));
+
+    // The 2 already filled count against the new total of 4
+    let replacement = engine.get_order(cmd.new_order_id).unwrap();
+    assert_eq!(replacement.quantity, Decimal::from(2));
+    assert_eq!(replacement.status, OrderStatus::Active);
+    assert_eq!(engine.get_order(original.order_id).unwrap().status, OrderStatus::Canceled);
+    let open: Vec<Uuid> = engine.get_open_orders(user_id).iter().map(|o| o.id).collect();
+    assert_eq!(open, vec![cmd.new_order_id]);
+    let book = engine.get_order_book("BTC/USDT").unwrap();
+    assert_eq!(book.asks.len(), 1);
+    assert_eq!(book.asks[0].price, Decimal::from(105));
+}
+
+#[tokio::test]
+async fn test_invalid_replacement_leaves_original_untouched() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let original = create_test_order_cmd(Uuid::new_v4(), Decimal::from(100), Decimal::from(5), OrderSide::Buy);
+    engine.handle_place_order(original.clone()).await.unwrap();
+
+    let mut unpriced = replace_cmd(&original, Decimal::from(101), Decimal::from(5));
+    unpriced.new_price = None;
+    let reused_id = CancelReplaceCommand {
+        new_order_id: original.order_id,
+        ..replace_cmd(&original, Decimal::from(101), Decimal::from(5))
+    };
+    for cmd in [unpriced, reused_id, replace_cmd(&original, Decimal::from(101), Decimal::ZERO)] {
+        assert!(engine.handle_command(OrderCommand::CancelReplace(cmd)).await.is_err());
+    }
+    assert_eq!(engine.get_order(original.order_id).unwrap().status, OrderStatus::Active);
+}
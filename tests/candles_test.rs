@@ -0,0 +1,138 @@
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType},
+    Candle, Clock, EventStore, OrderCommand, OrderEvent, PersistenceFailurePolicy, PlaceOrderCommand, TimeRange,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+const MINUTE: Duration = Duration::from_secs(60);
+
+fn create_test_order_cmd(side: OrderSide, price: i64, quantity: i64) -> OrderCommand {
+    OrderCommand::PlaceOrder(PlaceOrderCommand::new(
+        Uuid::new_v4(),
+        "BTC/USDT",
+        OrderType::Limit,
+        side,
+        Some(Decimal::from(price)),
+        Decimal::from(quantity),
+    ))
+}
+
+#[derive(Clone)]
+struct ManualClock(Arc<AtomicI64>);
+
+impl ManualClock {
+    fn set(&self, at: DateTime<Utc>) {
+        self.0.store(at.timestamp_millis(), Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc.timestamp_millis_opt(self.0.load(Ordering::SeqCst)).unwrap()
+    }
+}
+
+fn at(minute: u32, second: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 1, 12, minute, second).unwrap()
+}
+
+struct FlakyEventStore {
+    inner: InMemoryEventStore,
+    available: Arc<AtomicBool>,
+}
+
+#[async_trait]
+impl EventStore for FlakyEventStore {
+    async fn save_events(&self, events: Vec<OrderEvent>) -> Result<(), String> {
+        if !self.available.load(Ordering::SeqCst) {
+            return Err("store offline".to_string());
+        }
+        self.inner.save_events(events).await
+    }
+
+    async fn get_events(&self, order_id: Uuid) -> Result<Vec<OrderEvent>, String> {
+        self.inner.get_events(order_id).await
+    }
+
+    async fn get_all_events(&self) -> Result<Vec<OrderEvent>, String> {
+        self.inner.get_all_events().await
+    }
+}
+
+fn create_engine() -> (MatchingEngine, ManualClock, Arc<AtomicBool>) {
+    let clock = ManualClock(Arc::new(AtomicI64::new(at(0, 0).timestamp_millis())));
+    let available = Arc::new(AtomicBool::new(true));
+    let store = FlakyEventStore { inner: InMemoryEventStore::new(), available: available.clone() };
+    let engine = MatchingEngine::builder(Box::new(store))
+        .clock(clock.clone())
+        .build()
+        .with_candle_intervals(&[MINUTE]);
+    (engine, clock, available)
+}
+
+async fn trade(engine: &MatchingEngine, price: i64, quantity: i64) -> Vec<OrderEvent> {
+    engine.handle_command(create_test_order_cmd(OrderSide::Sell, price, quantity)).await.unwrap();
+    engine.handle_command(create_test_order_cmd(OrderSide::Buy, price, quantity)).await.unwrap()
+}
+
+fn closed_candles(events: &[OrderEvent]) -> Vec<Candle> {
+    events
+        .iter()
+        .filter_map(|e| match e {
+            OrderEvent::CandleClosed(closed) => Some(closed.candle.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_trades_build_bars_closed_by_the_next_interval() {
+    let (engine, clock, _) = create_engine();
+    for (second, price, quantity) in [(5, 100, 1), (20, 104, 2), (40, 98, 1), (59, 101, 3)] {
+        clock.set(at(0, second));
+        assert!(closed_candles(&trade(&engine, price, quantity).await).is_empty());
+    }
+
+    clock.set(at(2, 10));
+    let closed = closed_candles(&trade(&engine, 110, 1).await);
+    assert_eq!(closed.len(), 1);
+    let bar = &closed[0];
+    assert_eq!(bar.open_time, at(0, 0));
+    assert_eq!((bar.open, bar.high, bar.low, bar.close), (Decimal::from(100), Decimal::from(104), Decimal::from(98), Decimal::from(101)));
+    assert_eq!((bar.volume, bar.trade_count), (Decimal::from(7), 4));
+
+    let candles = engine.get_candles("BTC/USDT", MINUTE, TimeRange::all());
+    assert_eq!(candles.iter().map(|c| c.open_time).collect::<Vec<_>>(), vec![at(0, 0), at(2, 0)]);
+    assert_eq!(candles[1].close, Decimal::from(110));
+    let recent = engine.get_candles("BTC/USDT", MINUTE, TimeRange::since(at(1, 0)));
+    assert_eq!(recent.len(), 1);
+    assert!(engine.get_candles("BTC/USDT", Duration::from_secs(1), TimeRange::all()).is_empty());
+
+    let saved = engine.event_store().get_all_events().await.unwrap();
+    assert_eq!(closed_candles(&saved), closed);
+}
+
+#[tokio::test]
+async fn test_rolled_back_trades_leave_bars_untouched() {
+    let (engine, clock, available) = create_engine();
+    let engine = engine.with_persistence_policy(PersistenceFailurePolicy::Rollback);
+    clock.set(at(0, 5));
+    trade(&engine, 100, 1).await;
+    clock.set(at(1, 5));
+    engine.handle_command(create_test_order_cmd(OrderSide::Sell, 120, 1)).await.unwrap();
+
+    available.store(false, Ordering::SeqCst);
+    let result = engine.handle_command(create_test_order_cmd(OrderSide::Buy, 120, 1)).await;
+    assert!(result.is_err());
+
+    let candles = engine.get_candles("BTC/USDT", MINUTE, TimeRange::all());
+    assert_eq!(candles.len(), 1);
+    assert_eq!(candles[0].close, Decimal::from(100));
+}
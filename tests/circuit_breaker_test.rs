@@ -0,0 +1,73 @@
+use chrono::Duration;
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderStatus, OrderType},
+    CircuitBreakerConfig, OrderEvent, PlaceOrderCommand, SymbolConfig, SymbolState,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(price: Decimal, quantity: Decimal, side: OrderSide) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), "BTC/USDT", OrderType::Limit, side, Some(price), quantity)
+}
+
+fn engine_with_breaker(cooldown: Duration) -> MatchingEngine {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    engine.symbols().register(SymbolConfig {
+        circuit_breaker: Some(CircuitBreakerConfig {
+            max_move_percent: Decimal::from(10),
+            window: Duration::minutes(5),
+            cooldown,
+        }),
+        ..SymbolConfig::new("BTC/USDT")
+    }).unwrap();
+    engine
+}
+
+async fn trade_at(engine: &MatchingEngine, price: i64) -> Vec<OrderEvent> {
+    engine
+        .handle_place_order(create_test_order_cmd(Decimal::from(price), Decimal::ONE, OrderSide::Sell))
+        .await
+        .unwrap();
+    engine
+        .handle_place_order(create_test_order_cmd(Decimal::from(price), Decimal::ONE, OrderSide::Buy))
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_large_move_halts_symbol() {
+    let engine = engine_with_breaker(Duration::minutes(1));
+    trade_at(&engine, 100).await;
+    let events = trade_at(&engine, 109).await;
+    assert!(!events.iter().any(|e| matches!(e, OrderEvent::CircuitBreakerTriggered(_))));
+
+    let events = trade_at(&engine, 111).await;
+    let triggered = events.iter().find_map(|e| match e {
+        OrderEvent::CircuitBreakerTriggered(t) => Some(t),
+        _ => None,
+    }).unwrap();
+    assert_eq!(triggered.reference_price, Decimal::from(100));
+    assert_eq!(triggered.trigger_price, Decimal::from(111));
+    assert!(events.iter().any(|e| matches!(e, OrderEvent::SymbolStateChanged(s) if s.state == SymbolState::Halted)));
+    assert_eq!(engine.symbol_state("BTC/USDT"), SymbolState::Halted);
+    assert!(engine.circuit_breaker_halted_until("BTC/USDT").is_some());
+
+    let rejected = create_test_order_cmd(Decimal::from(111), Decimal::ONE, OrderSide::Buy);
+    assert!(engine.handle_place_order(rejected).await.is_err());
+}
+
+#[tokio::test]
+async fn test_symbol_resumes_after_cooldown() {
+    let engine = engine_with_breaker(Duration::zero());
+    trade_at(&engine, 100).await;
+    trade_at(&engine, 120).await;
+    assert_eq!(engine.symbol_state("BTC/USDT"), SymbolState::Halted);
+
+    // The cool-down has elapsed, so the next command reopens the symbol and
+    // the reference price starts over
+    let cmd = create_test_order_cmd(Decimal::from(130), Decimal::ONE, OrderSide::Sell);
+    let events = engine.handle_place_order(cmd.clone()).await.unwrap();
+    assert!(matches!(&events[0], OrderEvent::SymbolStateChanged(s) if s.state == SymbolState::Trading));
+    assert_eq!(engine.get_order(cmd.order_id).unwrap().status, OrderStatus::Active);
+    assert_eq!(engine.circuit_breaker_halted_until("BTC/USDT"), None);
+}
@@ -0,0 +1,186 @@
+use chrono::Utc;
+use matching_engine::{
+    codec::{decode_command, decode_event, decode_trade, encode_command, encode_event, encode_trade},
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType},
+    AmendOrderCommand, BasketExecution, BasketValidation, Candle, CandleClosedEvent, CancelOrderCommand,
+    CancelReplaceCommand, FeeChargedEvent, InvariantViolatedEvent, InvariantViolation, KillSwitchActivatedEvent,
+    KillSwitchTarget, Liquidity, OrderCommand, OrderEvent, PlaceBasketCommand, PlaceBracketOrderCommand,
+    PlaceOrderCommand,
+};
+use rust_decimal::Decimal;
+use std::time::Duration;
+use uuid::Uuid;
+
+fn create_test_order_cmd(side: OrderSide, price: Decimal) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), "BTC/USDT", OrderType::Limit, side, Some(price), Decimal::new(15, 1))
+}
+
+fn json<T: serde::Serialize>(value: &T) -> serde_json::Value {
+    serde_json::to_value(value).unwrap()
+}
+
+#[test]
+fn test_commands_round_trip() {
+    let mut place = create_test_order_cmd(OrderSide::Sell, Decimal::new(10125, 2));
+    place.client_order_id = Some("abc-1".to_string());
+    place.displayed = false;
+    place.max_fills = Some(3);
+    let cancel = CancelOrderCommand {
+        order_id: place.order_id,
+        client_order_id: None,
+        user_id: place.user_id,
+        symbol: place.symbol.clone(),
+        timestamp: Utc::now(),
+    };
+
+    for command in [OrderCommand::PlaceOrder(place), OrderCommand::CancelOrder(cancel)] {
+        let decoded = decode_command(&encode_command(&command)).unwrap();
+        assert_eq!(json(&decoded), json(&command));
+    }
+}
+
+#[test]
+fn test_every_command_round_trips() {
+    let entry = create_test_order_cmd(OrderSide::Buy, Decimal::from(100));
+    let user_id = entry.user_id;
+    let commands = [
+        OrderCommand::PlaceBracketOrder(PlaceBracketOrderCommand {
+            bracket_id: Uuid::new_v4(),
+            entry: entry.clone(),
+            stop_loss_order_id: Uuid::new_v4(),
+            stop_loss_price: Decimal::from(95),
+            take_profit_order_id: Uuid::new_v4(),
+            take_profit_price: Decimal::from(110),
+        }),
+        OrderCommand::CancelReplace(CancelReplaceCommand {
+            order_id: entry.order_id,
+            client_order_id: None,
+            user_id,
+            symbol: entry.symbol.clone(),
+            new_order_id: Uuid::new_v4(),
+            new_client_order_id: Some("abc-2".to_string()),
+            new_price: Some(Decimal::new(9950, 2)),
+            new_quantity: Decimal::from(2),
+            timestamp: Utc::now(),
+        }),
+        OrderCommand::AmendOrder(AmendOrderCommand {
+            order_id: entry.order_id,
+            client_order_id: Some("abc-1".to_string()),
+            user_id,
+            symbol: entry.symbol.clone(),
+            new_price: None,
+            new_quantity: Some(Decimal::ONE),
+            timestamp: Utc::now(),
+        }),
+        OrderCommand::PlaceBasket(PlaceBasketCommand {
+            basket_id: Uuid::new_v4(),
+            legs: vec![entry.clone(), create_test_order_cmd(OrderSide::Sell, Decimal::from(101))],
+            validation: BasketValidation::BestEffort,
+            execution: BasketExecution::Contingent,
+            timestamp: Utc::now(),
+        }),
+    ];
+
+    for command in commands {
+        let decoded = decode_command(&encode_command(&command)).unwrap();
+        assert_eq!(json(&decoded), json(&command));
+    }
+}
+
+#[test]
+fn test_events_with_nested_and_repeated_fields_round_trip() {
+    let user_id = Uuid::new_v4();
+    let events = [
+        OrderEvent::FeeCharged(FeeChargedEvent {
+            event_id: Uuid::new_v4(),
+            prev_hash: Some("ab12".to_string()),
+            trade_id: Uuid::new_v4(),
+            order_id: Uuid::new_v4(),
+            user_id,
+            symbol: "BTC/USDT".to_string(),
+            liquidity: Liquidity::Taker,
+            asset: "USDT".to_string(),
+            amount: Decimal::new(25, 2),
+            referrer_id: Some(Uuid::new_v4()),
+            referral_amount: Decimal::new(5, 2),
+            timestamp: Utc::now(),
+        }),
+        OrderEvent::KillSwitchActivated(KillSwitchActivatedEvent {
+            event_id: Uuid::new_v4(),
+            prev_hash: None,
+            target: KillSwitchTarget::User(user_id),
+            reason: "desk limit".to_string(),
+            canceled_order_ids: vec![Uuid::new_v4(), Uuid::new_v4()],
+            timestamp: Utc::now(),
+        }),
+        OrderEvent::InvariantViolated(InvariantViolatedEvent {
+            event_id: Uuid::new_v4(),
+            prev_hash: None,
+            symbol: "BTC/USDT".to_string(),
+            violation: InvariantViolation::NegativeQuantity {
+                symbol: "BTC/USDT".to_string(),
+                side: OrderSide::Sell,
+                price: Decimal::from(100),
+                quantity: Decimal::from(-1),
+            },
+            timestamp: Utc::now(),
+        }),
+        OrderEvent::CandleClosed(CandleClosedEvent {
+            event_id: Uuid::new_v4(),
+            prev_hash: None,
+            candle: Candle {
+                symbol: "BTC/USDT".to_string(),
+                interval: Duration::from_secs(60),
+                open_time: Utc::now(),
+                open: Decimal::from(100),
+                high: Decimal::from(105),
+                low: Decimal::from(99),
+                close: Decimal::from(104),
+                volume: Decimal::new(35, 1),
+                trade_count: 4,
+            },
+            timestamp: Utc::now(),
+        }),
+    ];
+
+    for event in &events {
+        let decoded = decode_event(&encode_event(event)).unwrap();
+        assert_eq!(json(&decoded), json(event));
+    }
+}
+
+#[tokio::test]
+async fn test_engine_events_and_trades_round_trip() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let sell = create_test_order_cmd(OrderSide::Sell, Decimal::from(100));
+    let buy = create_test_order_cmd(OrderSide::Buy, Decimal::from(100));
+    let mut events = Vec::new();
+    for cmd in [sell, buy] {
+        events.extend(engine.handle_command(OrderCommand::PlaceOrder(cmd)).await.unwrap());
+    }
+    assert!(events.iter().any(|e| e.kind() == "OrderMatched"));
+
+    for event in &events {
+        let decoded = decode_event(&encode_event(event)).unwrap();
+        assert_eq!(json(&decoded), json(event));
+    }
+
+    let trades = engine.get_trades_by_symbol("BTC/USDT", None, 10);
+    assert_eq!(trades.len(), 1);
+    let decoded = decode_trade(&encode_trade(&trades[0])).unwrap();
+    assert_eq!(json(&decoded), json(&trades[0]));
+}
+
+#[test]
+fn test_decoding_skips_unknown_fields_and_rejects_truncation() {
+    let command = OrderCommand::PlaceOrder(create_test_order_cmd(OrderSide::Buy, Decimal::from(100)));
+    let bytes = encode_command(&command);
+
+    // Field 15, varint 7, appended inside the PlaceOrder message
+    let mut extended = bytes.clone();
+    extended.extend_from_slice(&[15 << 3, 7]);
+    extended[1] += 2;
+    assert_eq!(json(&decode_command(&extended).unwrap()), json(&command));
+
+    assert!(decode_command(&bytes[..bytes.len() - 3]).is_err());
+}
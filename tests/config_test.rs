@@ -0,0 +1,201 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderStatus, OrderType},
+    Clock, EngineConfig, EngineError, EventStore, IdGenerator, OrderEvent, PlaceOrderCommand, RejectReason,
+    SelfTradePolicy,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(user_id: Uuid, price: Option<Decimal>, quantity: Decimal, side: OrderSide) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(
+        user_id,
+        "BTC/USDT",
+        if price.is_some() { OrderType::Limit } else { OrderType::Market },
+        side,
+        price,
+        quantity,
+    )
+}
+
+fn engine_with(config: EngineConfig) -> MatchingEngine {
+    MatchingEngine::builder(Box::new(InMemoryEventStore::new()))
+        .config(config)
+        .build()
+}
+
+struct FixedClock(DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+struct SequentialIds(AtomicU64);
+
+impl IdGenerator for SequentialIds {
+    fn next_id(&self) -> Uuid {
+        Uuid::from_u128(self.0.fetch_add(1, Ordering::SeqCst) as u128)
+    }
+}
+
+struct CountingEventStore {
+    inner: InMemoryEventStore,
+    calls: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl EventStore for CountingEventStore {
+    async fn save_events(&self, events: Vec<OrderEvent>) -> Result<(), String> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        self.inner.save_events(events).await
+    }
+
+    async fn get_events(&self, order_id: Uuid) -> Result<Vec<OrderEvent>, String> {
+        self.inner.get_events(order_id).await
+    }
+
+    async fn get_all_events(&self) -> Result<Vec<OrderEvent>, String> {
+        self.inner.get_all_events().await
+    }
+}
+
+#[tokio::test]
+async fn test_clock_and_id_providers_are_used_for_trades() {
+    let now = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+    let engine = MatchingEngine::builder(Box::new(InMemoryEventStore::new()))
+        .clock(FixedClock(now))
+        .id_generator(SequentialIds(AtomicU64::new(1)))
+        .build();
+    engine
+        .handle_place_order(create_test_order_cmd(Uuid::new_v4(), Some(Decimal::from(100)), Decimal::ONE, OrderSide::Sell))
+        .await
+        .unwrap();
+    engine
+        .handle_place_order(create_test_order_cmd(Uuid::new_v4(), None, Decimal::ONE, OrderSide::Buy))
+        .await
+        .unwrap();
+
+    let trade = engine.get_trade(Uuid::from_u128(1)).unwrap();
+    assert_eq!(trade.created_at, now);
+}
+
+#[tokio::test]
+async fn test_self_trade_cancels_resting_order() {
+    let engine = engine_with(EngineConfig {
+        self_trade_policy: SelfTradePolicy::CancelResting,
+        ..EngineConfig::default()
+    });
+    let user = Uuid::new_v4();
+    let own = create_test_order_cmd(user, Some(Decimal::from(100)), Decimal::ONE, OrderSide::Sell);
+    let other = create_test_order_cmd(Uuid::new_v4(), Some(Decimal::from(101)), Decimal::ONE, OrderSide::Sell);
+    engine.handle_place_order(own.clone()).await.unwrap();
+    engine.handle_place_order(other.clone()).await.unwrap();
+
+    let buy = create_test_order_cmd(user, Some(Decimal::from(101)), Decimal::ONE, OrderSide::Buy);
+    engine.handle_place_order(buy.clone()).await.unwrap();
+
+    assert_eq!(engine.get_order(own.order_id).unwrap().status, OrderStatus::Canceled);
+    assert_eq!(engine.get_order(other.order_id).unwrap().status, OrderStatus::Filled);
+    assert_eq!(engine.get_order(buy.order_id).unwrap().status, OrderStatus::Filled);
+}
+
+#[tokio::test]
+async fn test_self_trade_cancels_incoming_order() {
+    let engine = engine_with(EngineConfig {
+        self_trade_policy: SelfTradePolicy::CancelIncoming,
+        ..EngineConfig::default()
+    });
+    let user = Uuid::new_v4();
+    let own = create_test_order_cmd(user, Some(Decimal::from(100)), Decimal::ONE, OrderSide::Sell);
+    engine.handle_place_order(own.clone()).await.unwrap();
+
+    let buy = create_test_order_cmd(user, Some(Decimal::from(100)), Decimal::ONE, OrderSide::Buy);
+    let events = engine.handle_place_order(buy.clone()).await.unwrap();
+
+    assert!(events
+        .iter()
+        .any(|e| matches!(e, OrderEvent::OrderCanceled(c) if c.order_id == buy.order_id)));
+    assert_eq!(engine.get_order(buy.order_id).unwrap().status, OrderStatus::Canceled);
+    assert!(engine.get_order(own.order_id).unwrap().is_open());
+    assert!(engine.get_order_book("BTC/USDT").unwrap().bids.is_empty());
+}
+
+#[tokio::test]
+async fn test_market_protection_limits_sweep() {
+    let engine = engine_with(EngineConfig {
+        market_protection_percent: Some(Decimal::from(5)),
+        ..EngineConfig::default()
+    });
+    for price in [100, 104, 110] {
+        engine
+            .handle_place_order(create_test_order_cmd(Uuid::new_v4(), Some(Decimal::from(price)), Decimal::ONE, OrderSide::Sell))
+            .await
+            .unwrap();
+    }
+
+    let buy = create_test_order_cmd(Uuid::new_v4(), None, Decimal::from(3), OrderSide::Buy);
+    engine.handle_place_order(buy.clone()).await.unwrap();
+
+    let order = engine.get_order(buy.order_id).unwrap();
+    assert_eq!(order.filled_quantity, Decimal::from(2));
+    assert_eq!(order.status, OrderStatus::Canceled);
+    assert_eq!(engine.get_order_book("BTC/USDT").unwrap().asks[0].price, Decimal::from(110));
+}
+
+#[tokio::test]
+async fn test_max_open_orders_per_user() {
+    let engine = engine_with(EngineConfig {
+        max_open_orders_per_user: Some(2),
+        ..EngineConfig::default()
+    });
+    let user = Uuid::new_v4();
+    for price in [100, 101] {
+        engine
+            .handle_place_order(create_test_order_cmd(user, Some(Decimal::from(price)), Decimal::ONE, OrderSide::Sell))
+            .await
+            .unwrap();
+    }
+
+    let result = engine
+        .handle_place_order(create_test_order_cmd(user, Some(Decimal::from(102)), Decimal::ONE, OrderSide::Sell))
+        .await;
+    assert_eq!(
+        result.unwrap_err(),
+        EngineError::InvalidOrder(RejectReason::TooManyOpenOrders { limit: 2 })
+    );
+    assert!(engine
+        .handle_place_order(create_test_order_cmd(Uuid::new_v4(), Some(Decimal::from(102)), Decimal::ONE, OrderSide::Sell))
+        .await
+        .is_ok());
+}
+
+#[tokio::test]
+async fn test_events_are_saved_in_batches() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let store = CountingEventStore {
+        inner: InMemoryEventStore::new(),
+        calls: calls.clone(),
+    };
+    let engine = MatchingEngine::builder(Box::new(store))
+        .config(EngineConfig {
+            event_batch_size: Some(1),
+            ..EngineConfig::default()
+        })
+        .build();
+    engine
+        .handle_place_order(create_test_order_cmd(Uuid::new_v4(), Some(Decimal::from(100)), Decimal::ONE, OrderSide::Sell))
+        .await
+        .unwrap();
+    let events = engine
+        .handle_place_order(create_test_order_cmd(Uuid::new_v4(), None, Decimal::ONE, OrderSide::Buy))
+        .await
+        .unwrap();
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1 + events.len());
+}
@@ -0,0 +1,76 @@
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType}, EngineError, OrderCommand,
+    OrderEvent, PlaceOrderCommand, RejectReason,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(user_id: Uuid, side: OrderSide, price: i64, quantity: i64) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(
+        user_id,
+        "BTC/USDT",
+        OrderType::Limit,
+        side,
+        Some(Decimal::from(price)),
+        Decimal::from(quantity),
+    )
+}
+
+async fn place(
+    engine: &MatchingEngine,
+    user_id: Uuid,
+    side: OrderSide,
+    price: i64,
+    quantity: i64,
+) -> Result<Uuid, EngineError> {
+    let cmd = create_test_order_cmd(user_id, side, price, quantity);
+    let order_id = cmd.order_id;
+    engine.handle_command(OrderCommand::PlaceOrder(cmd)).await.map(|_| order_id)
+}
+
+#[tokio::test]
+async fn test_orders_beyond_the_credit_limit_are_rejected() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+    engine.set_credit_limit(alice, Some(Decimal::from(1000))).await.unwrap();
+    place(&engine, alice, OrderSide::Buy, 100, 5).await.unwrap();
+
+    let result = place(&engine, alice, OrderSide::Buy, 100, 6).await;
+    assert_eq!(
+        result.unwrap_err(),
+        EngineError::InvalidOrder(RejectReason::CreditLimitExceeded {
+            required: Decimal::from(1100),
+            limit: Decimal::from(1000),
+        })
+    );
+    place(&engine, bob, OrderSide::Buy, 100, 6).await.unwrap();
+
+    engine.set_credit_limit(alice, Some(Decimal::from(2000))).await.unwrap();
+    place(&engine, alice, OrderSide::Buy, 100, 6).await.unwrap();
+    assert_eq!(engine.credit_used(alice), Decimal::from(1100));
+    engine.set_credit_limit(alice, None).await.unwrap();
+    place(&engine, alice, OrderSide::Buy, 100, 100).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_lowering_the_limit_culls_the_worst_priced_orders_first() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new())).with_credit_culling();
+    let alice = Uuid::new_v4();
+    let near_bid = place(&engine, alice, OrderSide::Buy, 100, 2).await.unwrap();
+    let far_bid = place(&engine, alice, OrderSide::Buy, 90, 2).await.unwrap();
+    let ask = place(&engine, alice, OrderSide::Sell, 120, 2).await.unwrap();
+    assert_eq!(engine.credit_used(alice), Decimal::from(620));
+
+    let events = engine.set_credit_limit(alice, Some(Decimal::from(300))).await.unwrap();
+    let canceled: Vec<Uuid> = events
+        .iter()
+        .filter_map(|e| match e {
+            OrderEvent::OrderCanceled(e) => Some(e.order_id),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(canceled, vec![far_bid, near_bid]);
+    let open: Vec<Uuid> = engine.get_open_orders(alice).iter().map(|o| o.id).collect();
+    assert_eq!(open, vec![ask]);
+    assert_eq!(engine.credit_used(alice), Decimal::from(240));
+}
@@ -0,0 +1,123 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, NaiveTime, TimeZone, Utc};
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType},
+    CancelOrderCommand, Clock, DailyLimitKind, DailyLimits, EngineError, OrderCommand, OrderEvent, PlaceOrderCommand,
+    RejectReason,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(user_id: Uuid, side: OrderSide, price: i64) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(user_id, "BTC/USDT", OrderType::Limit, side, Some(Decimal::from(price)), Decimal::ONE)
+}
+
+/// A clock that only moves when told to.
+#[derive(Clone)]
+struct ManualClock(Arc<AtomicI64>);
+
+impl ManualClock {
+    fn advance(&self, by: Duration) {
+        self.0.fetch_add(by.num_milliseconds(), Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc.timestamp_millis_opt(self.0.load(Ordering::SeqCst)).unwrap()
+    }
+}
+
+fn create_engine() -> (MatchingEngine, ManualClock) {
+    let start = Utc.with_ymd_and_hms(2026, 1, 5, 10, 0, 0).unwrap();
+    let clock = ManualClock(Arc::new(AtomicI64::new(start.timestamp_millis())));
+    let engine = MatchingEngine::builder(Box::new(InMemoryEventStore::new()))
+        .clock(clock.clone())
+        .build()
+        .with_daily_limits(NaiveTime::from_hms_opt(8, 0, 0).unwrap());
+    (engine, clock)
+}
+
+async fn place(engine: &MatchingEngine, cmd: PlaceOrderCommand) -> Result<Vec<OrderEvent>, EngineError> {
+    engine.handle_command(OrderCommand::PlaceOrder(cmd)).await
+}
+
+#[tokio::test]
+async fn test_crossing_the_notional_cap_leaves_the_user_cancel_only_until_the_next_session() {
+    let (engine, clock) = create_engine();
+    let (user, maker) = (Uuid::new_v4(), Uuid::new_v4());
+    let limits = DailyLimits {
+        max_traded_notional: Some(Decimal::from(150)),
+        max_loss: None,
+    };
+    engine.set_daily_limits(user, limits).unwrap();
+    let resting = create_test_order_cmd(user, OrderSide::Buy, 50);
+    place(&engine, resting.clone()).await.unwrap();
+
+    place(&engine, create_test_order_cmd(maker, OrderSide::Sell, 100)).await.unwrap();
+    place(&engine, create_test_order_cmd(maker, OrderSide::Sell, 100)).await.unwrap();
+    let events = place(&engine, create_test_order_cmd(user, OrderSide::Buy, 100)).await.unwrap();
+    assert!(!events.iter().any(|e| matches!(e, OrderEvent::DailyLimitReached(_))));
+    // The second fill puts the user over
+    let events = place(&engine, create_test_order_cmd(user, OrderSide::Buy, 100)).await.unwrap();
+    let reached: Vec<_> = events
+        .iter()
+        .filter_map(|e| match e {
+            OrderEvent::DailyLimitReached(e) => Some(e),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(reached.len(), 1);
+    assert_eq!((reached[0].user_id, reached[0].limit), (user, DailyLimitKind::TradedNotional));
+    assert_eq!((reached[0].value, reached[0].cap), (Decimal::from(200), Decimal::from(150)));
+
+    let result = place(&engine, create_test_order_cmd(user, OrderSide::Sell, 200)).await;
+    assert_eq!(
+        result.unwrap_err(),
+        EngineError::InvalidOrder(RejectReason::DailyLimitReached(DailyLimitKind::TradedNotional))
+    );
+    let cancel = OrderCommand::CancelOrder(CancelOrderCommand {
+        order_id: resting.order_id,
+        client_order_id: None,
+        user_id: user,
+        symbol: resting.symbol,
+        timestamp: Utc::now(),
+    });
+    engine.handle_command(cancel).await.unwrap();
+
+    // Sessions begin at 08:00: 07:59 the next day is still the same one
+    clock.advance(Duration::hours(21) + Duration::minutes(59));
+    assert!(place(&engine, create_test_order_cmd(user, OrderSide::Sell, 200)).await.is_err());
+    clock.advance(Duration::minutes(1));
+    place(&engine, create_test_order_cmd(user, OrderSide::Sell, 200)).await.unwrap();
+    assert_eq!(engine.daily_usage(user).unwrap().traded_notional, Decimal::ZERO);
+}
+
+#[tokio::test]
+async fn test_losses_at_the_mark_count_toward_the_loss_cap() {
+    let (engine, _clock) = create_engine();
+    let (user, maker, other) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    let limits = DailyLimits {
+        max_traded_notional: None,
+        max_loss: Some(Decimal::from(5)),
+    };
+    engine.set_daily_limits(user, limits).unwrap();
+    place(&engine, create_test_order_cmd(maker, OrderSide::Sell, 100)).await.unwrap();
+    place(&engine, create_test_order_cmd(user, OrderSide::Buy, 100)).await.unwrap();
+    assert_eq!(engine.daily_usage(user).unwrap().loss, Decimal::ZERO);
+
+    // Others trading lower moves the mark against the user's long
+    place(&engine, create_test_order_cmd(maker, OrderSide::Sell, 90)).await.unwrap();
+    place(&engine, create_test_order_cmd(other, OrderSide::Buy, 90)).await.unwrap();
+    let usage = engine.daily_usage(user).unwrap();
+    assert_eq!((usage.loss, usage.reached), (Decimal::from(10), None));
+
+    let result = place(&engine, create_test_order_cmd(user, OrderSide::Sell, 90)).await;
+    assert_eq!(
+        result.unwrap_err(),
+        EngineError::InvalidOrder(RejectReason::DailyLimitReached(DailyLimitKind::Loss))
+    );
+    assert_eq!(engine.daily_usage(user).unwrap().reached, Some(DailyLimitKind::Loss));
+}
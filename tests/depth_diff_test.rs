@@ -0,0 +1,86 @@
+use chrono::Utc;
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType}, AmendOrderCommand,
+    OrderCommand, PlaceOrderCommand,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(side: OrderSide, price: i64, quantity: i64) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(
+        Uuid::new_v4(),
+        "BTC/USDT",
+        OrderType::Limit,
+        side,
+        Some(Decimal::from(price)),
+        Decimal::from(quantity),
+    )
+}
+
+async fn place(engine: &MatchingEngine, side: OrderSide, price: i64, quantity: i64) -> PlaceOrderCommand {
+    let order = create_test_order_cmd(side, price, quantity);
+    engine.handle_command(OrderCommand::PlaceOrder(order.clone())).await.unwrap();
+    order
+}
+
+#[tokio::test]
+async fn test_clients_join_mid_stream_and_reconcile() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    place(&engine, OrderSide::Buy, 99, 1).await;
+    let mut diffs = engine.subscribe_depth_diffs("BTC/USDT");
+    let resting = place(&engine, OrderSide::Sell, 101, 2).await;
+
+    let mut depth = engine.get_versioned_depth("BTC/USDT");
+    assert_eq!(depth.last_update_id, 1);
+    assert_eq!((depth.book.bids.len(), depth.book.asks.len()), (1, 1));
+
+    place(&engine, OrderSide::Sell, 100, 3).await;
+    place(&engine, OrderSide::Buy, 101, 2).await;
+    engine
+        .handle_command(OrderCommand::AmendOrder(AmendOrderCommand {
+            order_id: resting.order_id,
+            client_order_id: None,
+            user_id: resting.user_id,
+            symbol: resting.symbol.clone(),
+            new_price: None,
+            new_quantity: Some(Decimal::from(1)),
+            timestamp: Utc::now()
+        }))
+        .await
+        .unwrap();
+
+    // The first diff is already in the snapshot
+    let mut applied = Vec::new();
+    while let Ok(diff) = diffs.try_recv() {
+        depth.apply(&diff).unwrap();
+        applied.push((diff.first_update_id, diff.last_update_id));
+    }
+    assert_eq!(applied, vec![(1, 1), (2, 2), (3, 3), (4, 4)]);
+    assert_eq!(depth, engine.get_versioned_depth("BTC/USDT"));
+    assert_eq!(depth.book.bids, engine.get_l2_snapshot("BTC/USDT", 10).bids);
+    assert_eq!(depth.book.asks, engine.get_l2_snapshot("BTC/USDT", 10).asks);
+    let asks: Vec<(Decimal, Decimal)> = depth.book.asks.iter().map(|l| (l.price, l.quantity)).collect();
+    assert_eq!(asks, vec![(Decimal::from(100), Decimal::from(1)), (Decimal::from(101), Decimal::from(1))]);
+}
+
+#[tokio::test]
+async fn test_missed_diffs_are_replayed_from_the_buffer() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    assert!(engine.depth_diffs_since("BTC/USDT", 0).is_none());
+    let mut stale = engine.get_versioned_depth("BTC/USDT");
+    place(&engine, OrderSide::Buy, 99, 1).await;
+    place(&engine, OrderSide::Buy, 98, 1).await;
+    place(&engine, OrderSide::Sell, 99, 1).await;
+
+    let latest = engine.depth_diffs_since("BTC/USDT", 1).unwrap();
+    assert_eq!(latest.len(), 2);
+    assert!(stale.apply(&latest[0]).unwrap_err().starts_with("Missed updates 1 to 1"));
+
+    for diff in engine.depth_diffs_since("BTC/USDT", stale.last_update_id).unwrap() {
+        stale.apply(&diff).unwrap();
+    }
+    assert_eq!(stale.last_update_id, 3);
+    assert!(stale.book.asks.is_empty());
+    assert_eq!(stale.book.bids[0].price, Decimal::from(98));
+    assert!(engine.depth_diffs_since("BTC/USDT", 3).unwrap().is_empty());
+}
@@ -0,0 +1,168 @@
+use std::sync::{Arc, Mutex};
+
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType}, OrderCommand,
+    PlaceOrderCommand,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(side: OrderSide, price: Decimal, quantity: Decimal) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), "BTC/USDT", OrderType::Limit, side, Some(price), quantity)
+}
+
+async fn create_engine() -> MatchingEngine {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let orders = [
+        (OrderSide::Buy, 99, 1),
+        (OrderSide::Buy, 98, 2),
+        (OrderSide::Buy, 99, 3),
+        (OrderSide::Buy, 97, 5),
+        (OrderSide::Sell, 101, 1),
+        (OrderSide::Sell, 102, 4),
+    ];
+    for (side, price, quantity) in orders {
+        engine
+            .handle_place_order(create_test_order_cmd(side, Decimal::from(price), Decimal::from(quantity)))
+            .await
+            .unwrap();
+    }
+    engine
+}
+
+#[tokio::test]
+async fn test_depth_by_side_is_best_first() {
+    let engine = create_engine().await;
+
+    let bids = engine.get_depth_by_side("BTC/USDT", OrderSide::Buy, 2);
+    let bids: Vec<(Decimal, Decimal, u64)> = bids.iter().map(|e| (e.price, e.quantity, e.order_count)).collect();
+    assert_eq!(bids, [(Decimal::from(99), Decimal::from(4), 2), (Decimal::from(98), Decimal::from(2), 1)]);
+
+    let asks = engine.get_depth_by_side("BTC/USDT", OrderSide::Sell, 10);
+    assert_eq!(asks.iter().map(|e| e.price).collect::<Vec<_>>(), [Decimal::from(101), Decimal::from(102)]);
+    assert!(engine.get_depth_by_side("ETH/USDT", OrderSide::Sell, 10).is_empty());
+}
+
+#[tokio::test]
+async fn test_l2_snapshot_with_cumulative_quantities() {
+    let engine = create_engine().await;
+
+    let snapshot = engine.get_l2_snapshot("BTC/USDT", 3);
+    assert_eq!(snapshot.bids.len(), 3);
+    assert!(snapshot.bids.iter().all(|level| level.cumulative_quantity.is_none()));
+
+    let snapshot = snapshot.with_cumulative();
+    let bids: Vec<Option<Decimal>> = snapshot.bids.iter().map(|l| l.cumulative_quantity).collect();
+    assert_eq!(bids, [Some(Decimal::from(4)), Some(Decimal::from(6)), Some(Decimal::from(11))]);
+    let asks: Vec<Option<Decimal>> = snapshot.asks.iter().map(|l| l.cumulative_quantity).collect();
+    assert_eq!(asks, [Some(Decimal::from(1)), Some(Decimal::from(5))]);
+}
+
+#[tokio::test]
+async fn test_l3_snapshot_lists_orders_in_priority() {
+    let engine = create_engine().await;
+    let hidden = PlaceOrderCommand {
+        displayed: false,
+        ..create_test_order_cmd(OrderSide::Buy, Decimal::from(99), Decimal::from(7))
+    };
+    engine.handle_place_order(hidden.clone()).await.unwrap();
+
+    let snapshot = engine.get_l3_snapshot("BTC/USDT");
+    let bids: Vec<(Decimal, Decimal, usize, bool)> = snapshot
+        .bids
+        .iter()
+        .map(|o| (o.price, o.remaining_quantity, o.priority, o.displayed))
+        .collect();
+    assert_eq!(
+        bids,
+        [
+            (Decimal::from(99), Decimal::from(1), 0, true),
+            (Decimal::from(99), Decimal::from(3), 1, true),
+            (Decimal::from(99), Decimal::from(7), 2, false),
+            (Decimal::from(98), Decimal::from(2), 0, true),
+            (Decimal::from(97), Decimal::from(5), 0, true),
+        ]
+    );
+    assert_eq!(snapshot.bids[2].order_id, hidden.order_id);
+    assert_eq!(snapshot.asks.len(), 2);
+}
+
+#[tokio::test]
+async fn test_volume_and_cost_queries_leave_the_book_alone() {
+    let engine = create_engine().await;
+
+    assert_eq!(engine.volume_within("BTC/USDT", OrderSide::Sell, Decimal::from(98)), Decimal::from(6));
+    assert_eq!(engine.volume_within("BTC/USDT", OrderSide::Buy, Decimal::from(100)), Decimal::ZERO);
+    assert_eq!(engine.volume_within("BTC/USDT", OrderSide::Buy, Decimal::from(102)), Decimal::from(5));
+
+    let estimate = engine.cost_to_fill("BTC/USDT", OrderSide::Buy, Decimal::from(3)).unwrap();
+    assert_eq!(estimate.quantity, Decimal::from(3));
+    assert_eq!(estimate.notional, Decimal::from(101 + 2 * 102));
+    assert_eq!(estimate.average_price, Some(Decimal::from(305) / Decimal::from(3)));
+
+    let estimate = engine.cost_to_fill("BTC/USDT", OrderSide::Buy, Decimal::from(10)).unwrap();
+    assert_eq!(estimate.quantity, Decimal::from(5));
+    assert_eq!(engine.get_depth_by_side("BTC/USDT", OrderSide::Sell, 10).len(), 2);
+}
+
+#[tokio::test]
+async fn test_book_stats() {
+    let engine = create_engine().await;
+
+    let stats = engine.get_book_stats("BTC/USDT", 1).unwrap();
+    assert_eq!(stats.best_bid, Some(Decimal::from(99)));
+    assert_eq!(stats.best_ask, Some(Decimal::from(101)));
+    assert_eq!(stats.spread, Some(Decimal::from(2)));
+    assert_eq!(stats.midpoint, Some(Decimal::from(100)));
+    assert_eq!(stats.microprice, Some(Decimal::new(1006, 1)));
+    assert_eq!(stats.imbalance, Some(Decimal::new(6, 1)));
+
+    let stats = engine.get_book_stats("BTC/USDT", 10).unwrap();
+    assert_eq!((stats.bid_depth, stats.ask_depth), (Decimal::from(11), Decimal::from(5)));
+    assert_eq!(stats.imbalance, Some(Decimal::new(375, 3)));
+    assert!(engine.get_book_stats("ETH/USDT", 10).is_none());
+}
+
+#[tokio::test]
+async fn test_book_view_follows_commands() {
+    let engine = create_engine().await.with_book_view_depth(1);
+    let mut view = engine.book_view("BTC/USDT");
+    let first = view.borrow_and_update().clone();
+    assert_eq!(first.bids.len(), 1);
+    assert_eq!(first.asks[0].price, Decimal::from(101));
+
+    let cmd = create_test_order_cmd(OrderSide::Sell, Decimal::from(100), Decimal::ONE);
+    engine.handle_command(OrderCommand::PlaceOrder(cmd)).await.unwrap();
+    assert!(view.has_changed().unwrap());
+    assert_eq!(view.borrow_and_update().asks[0].price, Decimal::from(100));
+    // Earlier snapshots stay as they were
+    assert_eq!(first.asks[0].price, Decimal::from(101));
+
+    let cmd = create_test_order_cmd(OrderSide::Sell, Decimal::from(105), Decimal::ONE);
+    engine.handle_command(OrderCommand::PlaceOrder(cmd)).await.unwrap();
+    assert!(!view.has_changed().unwrap());
+}
+
+#[tokio::test]
+async fn test_top_of_book_fires_only_on_best_level_changes() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let recorder = seen.clone();
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()))
+        .with_top_of_book_listener(move |top| recorder.lock().unwrap().push((top.best_bid, top.bid_quantity)));
+    let top = engine.watch_top_of_book("BTC/USDT");
+
+    for (price, quantity) in [(99, 1), (98, 1), (99, 2), (97, 5)] {
+        let cmd = create_test_order_cmd(OrderSide::Buy, Decimal::from(price), Decimal::from(quantity));
+        engine.handle_command(OrderCommand::PlaceOrder(cmd)).await.unwrap();
+    }
+
+    assert_eq!(
+        *seen.lock().unwrap(),
+        [
+            (Some(Decimal::from(99)), Decimal::ONE),
+            (Some(Decimal::from(99)), Decimal::from(3)),
+        ]
+    );
+    assert_eq!(top.borrow().bid_quantity, Decimal::from(3));
+    assert_eq!(top.borrow().best_ask, None);
+}
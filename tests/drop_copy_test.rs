@@ -0,0 +1,87 @@
+use chrono::Utc;
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType}, CancelOrderCommand,
+    DropCopyView, OrderCommand, OrderEvent, Participant, PlaceOrderCommand, Projector,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(user_id: Uuid, side: OrderSide, price: Decimal, quantity: Decimal) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(user_id, "BTC/USDT", OrderType::Limit, side, Some(price), quantity)
+}
+
+async fn place(engine: &MatchingEngine, user_id: Uuid, side: OrderSide, price: i64, quantity: i64) -> Uuid {
+    let cmd = create_test_order_cmd(user_id, side, Decimal::from(price), Decimal::from(quantity));
+    let order_id = cmd.order_id;
+    engine.handle_command(OrderCommand::PlaceOrder(cmd)).await.unwrap();
+    order_id
+}
+
+fn kinds(view: &DropCopyView, participant: &Participant, since: u64) -> Vec<&'static str> {
+    view.records_since(participant, since).iter().map(|r| r.event.kind()).collect()
+}
+
+#[tokio::test]
+async fn test_maker_and_taker_each_get_their_executions() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let (maker, taker, bystander) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    let ask = place(&engine, maker, OrderSide::Sell, 100, 2).await;
+    place(&engine, bystander, OrderSide::Buy, 90, 1).await;
+    let bid = place(&engine, taker, OrderSide::Buy, 100, 1).await;
+    engine
+        .handle_command(OrderCommand::CancelOrder(CancelOrderCommand {
+            order_id: ask,
+            client_order_id: None,
+            user_id: maker,
+            symbol: "BTC/USDT".to_string(),
+            timestamp: Utc::now(),
+        }))
+        .await
+        .unwrap();
+
+    let projector = Projector::new(DropCopyView::new());
+    projector.catch_up(engine.event_store()).await.unwrap();
+    projector.read(|view| {
+        let maker_copy = view.records_since(&Participant::User(maker), 0);
+        assert!(maker_copy.iter().all(|r| r.user_id == maker && r.order_id == ask));
+        assert!(kinds(view, &Participant::User(maker), 0).contains(&"OrderMatched"));
+        assert_eq!(maker_copy.last().unwrap().event.kind(), "OrderCanceled");
+        let sequences: Vec<u64> = maker_copy.iter().map(|r| r.sequence).collect();
+        assert_eq!(sequences, (1..=maker_copy.len() as u64).collect::<Vec<_>>());
+
+        let taker_copy = view.records_since(&Participant::User(taker), 0);
+        assert!(taker_copy.iter().all(|r| r.order_id == bid));
+        let matched = taker_copy.iter().find(|r| r.event.kind() == "OrderMatched").unwrap();
+        assert!(matches!(&matched.event, OrderEvent::OrderMatched(e) if e.matched_order_id == ask));
+
+        assert_eq!(kinds(view, &Participant::User(bystander), 0), ["OrderPlaced"]);
+        assert!(view.records_since(&Participant::User(maker), maker_copy.len() as u64).is_empty());
+    });
+}
+
+#[tokio::test]
+async fn test_firm_copy_covers_its_users_and_survives_a_rebuild() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let (alice, bob, outsider) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    place(&engine, alice, OrderSide::Sell, 100, 1).await;
+    place(&engine, bob, OrderSide::Buy, 100, 1).await;
+    place(&engine, outsider, OrderSide::Buy, 99, 1).await;
+
+    let firm = Participant::Firm("ACME".to_string());
+    let projector = Projector::new(DropCopyView::new().with_firm("ACME", &[alice, bob]));
+    projector.catch_up(engine.event_store()).await.unwrap();
+    let ids = |view: &DropCopyView| {
+        let copy = view.records_since(&firm, 0);
+        copy.iter().map(|r| (r.sequence, r.event.event_id())).collect::<Vec<_>>()
+    };
+    let before = projector.read(|view| {
+        let copy = view.records_since(&firm, 0);
+        assert!(copy.iter().all(|r| r.user_id == alice || r.user_id == bob));
+        // The match between the firm's users shows up once for each side
+        assert_eq!(copy.iter().filter(|r| r.event.kind() == "OrderMatched").count(), 2);
+        ids(view)
+    });
+
+    projector.rebuild(engine.event_store()).await.unwrap();
+    assert_eq!(projector.read(ids), before);
+}
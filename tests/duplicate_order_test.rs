@@ -0,0 +1,67 @@
+use chrono::Utc;
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType},
+    CancelReplaceCommand, EngineError, OrderCommand, OrderEvent, PlaceOrderCommand,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(price: Decimal, side: OrderSide) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), "BTC/USDT", OrderType::Limit, side, Some(price), Decimal::ONE)
+}
+
+#[tokio::test]
+async fn test_reused_order_id_is_rejected() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let first = create_test_order_cmd(Decimal::from(100), OrderSide::Buy);
+    engine.handle_place_order(first.clone()).await.unwrap();
+
+    let reuse = PlaceOrderCommand {
+        order_id: first.order_id,
+        ..create_test_order_cmd(Decimal::from(200), OrderSide::Sell)
+    };
+    let result = engine.handle_place_order(reuse.clone()).await;
+    assert_eq!(result.unwrap_err(), EngineError::DuplicateOrderId(first.order_id));
+
+    let order = engine.get_order(first.order_id).unwrap();
+    assert_eq!(order.user_id, first.user_id);
+    assert_eq!(order.side, OrderSide::Buy);
+    assert!(engine.get_order_book("BTC/USDT").unwrap().asks.is_empty());
+
+    let events = engine.event_store().get_events(first.order_id).await.unwrap();
+    let rejected = events
+        .iter()
+        .find_map(|e| match e {
+            OrderEvent::OrderRejected(e) => Some(e),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(rejected.user_id, reuse.user_id);
+}
+
+#[tokio::test]
+async fn test_cancel_replace_onto_existing_id_is_rejected() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let original = create_test_order_cmd(Decimal::from(100), OrderSide::Buy);
+    let other = create_test_order_cmd(Decimal::from(99), OrderSide::Buy);
+    engine.handle_place_order(original.clone()).await.unwrap();
+    engine.handle_place_order(other.clone()).await.unwrap();
+
+    let result = engine
+        .handle_command(OrderCommand::CancelReplace(CancelReplaceCommand {
+            order_id: original.order_id,
+            client_order_id: None,
+            user_id: original.user_id,
+            symbol: original.symbol.clone(),
+            new_order_id: other.order_id,
+            new_client_order_id: None,
+            new_price: Some(Decimal::from(101)),
+            new_quantity: Decimal::ONE,
+            timestamp: Utc::now(),
+        }))
+        .await;
+
+    assert_eq!(result.unwrap_err(), EngineError::DuplicateOrderId(other.order_id));
+    assert!(engine.get_order(original.order_id).unwrap().is_open());
+    assert_eq!(engine.get_order(other.order_id).unwrap().price, Some(Decimal::from(99)));
+}
@@ -0,0 +1,311 @@
+use chrono::Utc;
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType},
+    EventFeed, EventFilter, EventStore, FileEventStore, KvEventStore, MemoryKv, OrderCommand, OrderEvent,
+    PlaceOrderCommand, TimeRange,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(side: OrderSide, price: Decimal) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), "BTC/USDT", OrderType::Limit, side, Some(price), Decimal::ONE)
+}
+
+fn event_ids(events: &[OrderEvent]) -> Vec<Option<Uuid>> {
+    events.iter().map(|e| e.order_id()).collect()
+}
+
+/// Reads the whole log back two events at a time, as a consumer resuming
+/// from its last offset would.
+async fn assert_offsets_follow_save_order(store: Box<dyn EventStore>) {
+    let engine = MatchingEngine::new(store);
+    let orders = [
+        create_test_order_cmd(OrderSide::Buy, Decimal::from(99)),
+        create_test_order_cmd(OrderSide::Sell, Decimal::from(101)),
+        create_test_order_cmd(OrderSide::Buy, Decimal::from(101)),
+    ];
+    for cmd in &orders {
+        engine.handle_command(OrderCommand::PlaceOrder(cmd.clone())).await.unwrap();
+    }
+    let all = engine.event_store().get_all_events().await.unwrap();
+    assert_eq!(all[0].order_id(), Some(orders[0].order_id));
+
+    let mut streamed = Vec::new();
+    loop {
+        let page = engine.event_store().read_from(streamed.len() as u64, 2).await.unwrap();
+        if page.is_empty() {
+            break;
+        }
+        for stored in page {
+            assert_eq!(stored.offset, streamed.len() as u64);
+            streamed.push(stored.event);
+        }
+    }
+    assert_eq!(event_ids(&streamed), event_ids(&all));
+    assert!(engine.event_store().read_from(all.len() as u64 + 5, 2).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_in_memory_offsets() {
+    assert_offsets_follow_save_order(Box::new(InMemoryEventStore::new())).await;
+}
+
+#[tokio::test]
+async fn test_file_offsets() {
+    let dir = std::env::temp_dir().join(format!("events-{}", Uuid::new_v4()));
+    assert_offsets_follow_save_order(Box::new(FileEventStore::open(&dir).unwrap())).await;
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_kv_offsets() {
+    assert_offsets_follow_save_order(Box::new(KvEventStore::open(MemoryKv::new()).unwrap())).await;
+}
+
+/// Two users trade BTC, then the seller places an ETH order after `cut`.
+async fn assert_secondary_indexes(store: Box<dyn EventStore>) {
+    let engine = MatchingEngine::new(store);
+    let start = Utc::now();
+    let sell = create_test_order_cmd(OrderSide::Sell, Decimal::from(100));
+    let buy = create_test_order_cmd(OrderSide::Buy, Decimal::from(100));
+    for cmd in [&sell, &buy] {
+        engine.handle_command(OrderCommand::PlaceOrder(cmd.clone())).await.unwrap();
+    }
+    tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    let cut = Utc::now();
+    let mut eth = create_test_order_cmd(OrderSide::Buy, Decimal::from(10));
+    eth.symbol = "ETH/USDT".to_string();
+    eth.user_id = sell.user_id;
+    engine.handle_command(OrderCommand::PlaceOrder(eth.clone())).await.unwrap();
+
+    let store = engine.event_store();
+    let all = store.get_all_events().await.unwrap();
+    let matches: Vec<OrderEvent> = all.iter().filter(|e| e.kind() == "OrderMatched").cloned().collect();
+    assert_eq!(matches.len(), 1);
+
+    let seller = store.get_events_by_user(sell.user_id, TimeRange::all()).await.unwrap();
+    assert_eq!(
+        event_ids(&seller),
+        vec![Some(sell.order_id), Some(buy.order_id), Some(eth.order_id)]
+    );
+    let buyer = store.get_events_by_user(buy.user_id, TimeRange::all()).await.unwrap();
+    assert_eq!(event_ids(&buyer), vec![Some(buy.order_id), Some(buy.order_id)]);
+    assert_eq!(event_ids(&store.get_events_by_type("OrderMatched", TimeRange::all()).await.unwrap()), event_ids(&matches));
+
+    let later = store.get_events_by_user(sell.user_id, TimeRange::since(cut)).await.unwrap();
+    assert_eq!(event_ids(&later), vec![Some(eth.order_id)]);
+    let earlier = store.get_events_by_user(sell.user_id, TimeRange::between(start, cut)).await.unwrap();
+    assert_eq!(earlier.len(), 2);
+    let btc = store.get_events_by_symbol("BTC/USDT", TimeRange::since(cut)).await.unwrap();
+    assert!(btc.is_empty());
+    let placed = store.get_events_by_type("OrderPlaced", TimeRange::between(start, cut)).await.unwrap();
+    assert_eq!(event_ids(&placed), vec![Some(sell.order_id), Some(buy.order_id)]);
+}
+
+#[tokio::test]
+async fn test_in_memory_secondary_indexes() {
+    assert_secondary_indexes(Box::new(InMemoryEventStore::new())).await;
+}
+
+#[tokio::test]
+async fn test_file_secondary_indexes() {
+    let dir = std::env::temp_dir().join(format!("events-{}", Uuid::new_v4()));
+    assert_secondary_indexes(Box::new(FileEventStore::open(&dir).unwrap())).await;
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_kv_secondary_indexes() {
+    assert_secondary_indexes(Box::new(KvEventStore::open(MemoryKv::new()).unwrap())).await;
+}
+
+/// Saves a batch, then retries it alongside a new event, as persistence
+/// would after a timeout.
+async fn assert_retried_saves_are_skipped(store: Box<dyn EventStore>) {
+    let source = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let mut events = Vec::new();
+    for side in [OrderSide::Sell, OrderSide::Buy] {
+        let cmd = create_test_order_cmd(side, Decimal::from(100));
+        events.extend(source.handle_command(OrderCommand::PlaceOrder(cmd)).await.unwrap());
+    }
+    let (first, rest) = events.split_at(1);
+
+    store.save_events(first.to_vec()).await.unwrap();
+    store.save_events(events.clone()).await.unwrap();
+    store.save_events(rest.to_vec()).await.unwrap();
+    let saved = store.get_all_events().await.unwrap();
+    assert_eq!(
+        saved.iter().map(|e| e.event_id()).collect::<Vec<_>>(),
+        events.iter().map(|e| e.event_id()).collect::<Vec<_>>()
+    );
+    assert!(saved.iter().all(|e| !e.event_id().is_nil()));
+}
+
+#[tokio::test]
+async fn test_in_memory_saves_are_idempotent() {
+    assert_retried_saves_are_skipped(Box::new(InMemoryEventStore::new())).await;
+}
+
+#[tokio::test]
+async fn test_file_saves_are_idempotent() {
+    let dir = std::env::temp_dir().join(format!("events-{}", Uuid::new_v4()));
+    assert_retried_saves_are_skipped(Box::new(FileEventStore::open(&dir).unwrap())).await;
+    // The ids come back with the index
+    let reopened = FileEventStore::open(&dir).unwrap();
+    let saved = reopened.get_all_events().await.unwrap();
+    reopened.save_events(saved.clone()).await.unwrap();
+    assert_eq!(reopened.get_all_events().await.unwrap().len(), saved.len());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_kv_saves_are_idempotent() {
+    assert_retried_saves_are_skipped(Box::new(KvEventStore::open(MemoryKv::new()).unwrap())).await;
+}
+
+#[tokio::test]
+async fn test_subscriptions_filter_new_events() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let sell = create_test_order_cmd(OrderSide::Sell, Decimal::from(100));
+    let by_user = EventFilter {
+        user_id: Some(sell.user_id),
+        ..EventFilter::default()
+    };
+    let mut user_events = engine.event_store().subscribe(by_user).unwrap();
+    let mut placed = engine
+        .event_store()
+        .subscribe(EventFilter {
+            kinds: Some(vec!["OrderPlaced".to_string()]),
+            ..EventFilter::default()
+        })
+        .unwrap();
+    let mut other_symbol = engine
+        .event_store()
+        .subscribe(EventFilter {
+            symbol: Some("ETH/USDT".to_string()),
+            ..EventFilter::default()
+        })
+        .unwrap();
+
+    let buy = create_test_order_cmd(OrderSide::Buy, Decimal::from(100));
+    engine.handle_command(OrderCommand::PlaceOrder(sell.clone())).await.unwrap();
+    engine.handle_command(OrderCommand::PlaceOrder(buy.clone())).await.unwrap();
+
+    // The seller sees their order placed and then hit by someone else
+    let first = user_events.recv().await.unwrap();
+    assert_eq!((first.kind(), first.order_id()), ("OrderPlaced", Some(sell.order_id)));
+    let mut kinds = Vec::new();
+    while let Ok(Ok(event)) = tokio::time::timeout(std::time::Duration::from_millis(50), user_events.recv()).await {
+        kinds.push(event.kind());
+    }
+    assert!(kinds.contains(&"OrderMatched"));
+    assert!(!kinds.contains(&"OrderPlaced"));
+
+    assert_eq!(placed.recv().await.unwrap().order_id(), Some(sell.order_id));
+    assert_eq!(placed.recv().await.unwrap().order_id(), Some(buy.order_id));
+    assert!(tokio::time::timeout(std::time::Duration::from_millis(50), other_symbol.recv()).await.is_err());
+}
+
+#[tokio::test]
+async fn test_subscriptions_follow_orders_whose_id_was_reused() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let sell = create_test_order_cmd(OrderSide::Sell, Decimal::from(100));
+    let by_user = EventFilter {
+        user_id: Some(sell.user_id),
+        ..EventFilter::default()
+    };
+    let mut user_events = engine.event_store().subscribe(by_user).unwrap();
+    engine.handle_command(OrderCommand::PlaceOrder(sell.clone())).await.unwrap();
+    assert!(engine.handle_command(OrderCommand::PlaceOrder(sell.clone())).await.is_err());
+    let buy = create_test_order_cmd(OrderSide::Buy, Decimal::from(100));
+    engine.handle_command(OrderCommand::PlaceOrder(buy)).await.unwrap();
+
+    let mut kinds = Vec::new();
+    while let Ok(Ok(event)) = tokio::time::timeout(std::time::Duration::from_millis(50), user_events.recv()).await {
+        kinds.push(event.kind());
+    }
+    assert_eq!(kinds, ["OrderPlaced", "OrderRejected", "OrderMatched"]);
+}
+
+#[tokio::test]
+async fn test_feed_forgets_orders_once_matches_fill_them() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let mut sell = create_test_order_cmd(OrderSide::Sell, Decimal::from(100));
+    sell.quantity = Decimal::from(2);
+    let feed = EventFeed::new();
+    for cmd in [sell, create_test_order_cmd(OrderSide::Buy, Decimal::from(100))] {
+        feed.publish(&engine.handle_command(OrderCommand::PlaceOrder(cmd)).await.unwrap());
+    }
+    // The buy is filled, half of the sell is left
+    assert_eq!(feed.tracked_orders(), 1);
+
+    let buy = create_test_order_cmd(OrderSide::Buy, Decimal::from(100));
+    feed.publish(&engine.handle_command(OrderCommand::PlaceOrder(buy)).await.unwrap());
+    assert_eq!(feed.tracked_orders(), 0);
+}
+
+/// Trades across several commands, retrying one save, and checks the
+/// chain holds over the whole log and over part of it.
+async fn assert_hash_chain_is_intact(store: Box<dyn EventStore>) -> usize {
+    let engine = MatchingEngine::new(store);
+    let mut events = Vec::new();
+    for (side, price) in [(OrderSide::Sell, 100), (OrderSide::Sell, 101), (OrderSide::Buy, 101)] {
+        let cmd = create_test_order_cmd(side, Decimal::from(price));
+        events.extend(engine.handle_command(OrderCommand::PlaceOrder(cmd)).await.unwrap());
+    }
+    let store = engine.event_store();
+    store.save_events(events).await.unwrap();
+
+    let saved = store.get_all_events().await.unwrap();
+    assert_eq!(saved[0].prev_hash(), None);
+    assert!(saved[1..].iter().all(|e| e.prev_hash().is_some()));
+    let report = store.verify_integrity(0..u64::MAX).await.unwrap();
+    assert!(report.is_intact());
+    assert_eq!(report.checked, saved.len() as u64);
+    let part = store.verify_integrity(1..3).await.unwrap();
+    assert_eq!((part.checked, part.first_broken), (2, None));
+    assert_ne!(part.head, report.head);
+    saved.len()
+}
+
+#[tokio::test]
+async fn test_in_memory_hash_chain() {
+    assert_hash_chain_is_intact(Box::new(InMemoryEventStore::new().with_hash_chain())).await;
+    // Nothing links events saved without a chain
+    let unchained = Box::new(InMemoryEventStore::new());
+    let engine = MatchingEngine::new(unchained);
+    for side in [OrderSide::Sell, OrderSide::Buy] {
+        let cmd = create_test_order_cmd(side, Decimal::from(100));
+        engine.handle_command(OrderCommand::PlaceOrder(cmd)).await.unwrap();
+    }
+    let report = engine.event_store().verify_integrity(0..u64::MAX).await.unwrap();
+    assert_eq!(report.first_broken, Some(1));
+}
+
+#[tokio::test]
+async fn test_file_hash_chain_detects_tampering() {
+    let dir = std::env::temp_dir().join(format!("events-{}", Uuid::new_v4()));
+    let saved = assert_hash_chain_is_intact(Box::new(FileEventStore::open(&dir).unwrap().with_hash_chain())).await;
+    // Reopening picks the chain up from the last event on disk
+    assert_hash_chain_is_intact(Box::new(FileEventStore::open(&dir).unwrap().with_hash_chain())).await;
+
+    let segment = std::fs::read_dir(&dir).unwrap().next().unwrap().unwrap().path();
+    let bytes = std::fs::read(&segment).unwrap();
+    let needle = b"\"quantity\":\"1\"";
+    let at = bytes.windows(needle.len()).position(|w| w == needle).unwrap() + needle.len() - 2;
+    let mut tampered = bytes.clone();
+    tampered[at] = b'7';
+    std::fs::write(&segment, tampered).unwrap();
+
+    let store = FileEventStore::open(&dir).unwrap();
+    let report = store.verify_integrity(0..u64::MAX).await.unwrap();
+    assert_eq!(report.checked, 2 * saved as u64);
+    let broken = report.first_broken.unwrap();
+    assert_eq!(store.verify_integrity(0..broken).await.unwrap().first_broken, None);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_kv_hash_chain() {
+    assert_hash_chain_is_intact(Box::new(KvEventStore::open(MemoryKv::new()).unwrap().with_hash_chain())).await;
+}
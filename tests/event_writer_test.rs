@@ -0,0 +1,110 @@
+use std::sync::Arc;
+
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType},
+    InMemorySnapshotStore, OrderCommand, OrderEvent, PlaceOrderCommand, QueueFullPolicy, SnapshotCadence,
+    WriteAheadLog,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(side: OrderSide, price: Decimal) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), "BTC/USDT", OrderType::Limit, side, Some(price), Decimal::ONE)
+}
+
+async fn place(engine: &MatchingEngine, side: OrderSide, price: i64) -> Result<Vec<OrderEvent>, String> {
+    let cmd = create_test_order_cmd(side, Decimal::from(price));
+    engine.handle_command(OrderCommand::PlaceOrder(cmd)).await.map_err(|e| e.to_string())
+}
+
+async fn saved_ids(engine: &MatchingEngine) -> Vec<Uuid> {
+    engine.event_store().get_all_events().await.unwrap().iter().map(|e| e.event_id()).collect()
+}
+
+#[tokio::test]
+async fn test_writer_saves_events_in_command_order() {
+    let engine = Arc::new(
+        MatchingEngine::new(Box::new(InMemoryEventStore::new())).with_async_persistence(4, QueueFullPolicy::Block),
+    );
+    let writer = engine.spawn_event_writer().unwrap();
+    assert!(engine.spawn_event_writer().is_err());
+
+    let mut returned = Vec::new();
+    for (side, price) in [(OrderSide::Sell, 100), (OrderSide::Sell, 101), (OrderSide::Buy, 101)] {
+        returned.extend(place(&engine, side, price).await.unwrap());
+    }
+    engine.flush_events().await;
+    assert_eq!(saved_ids(&engine).await, returned.iter().map(|e| e.event_id()).collect::<Vec<_>>());
+
+    engine.shutdown_event_writer().await;
+    writer.await.unwrap();
+    // Saved inline from here on
+    let inline = place(&engine, OrderSide::Buy, 90).await.unwrap();
+    assert_eq!(saved_ids(&engine).await.last(), inline.last().map(|e| e.event_id()).as_ref());
+}
+
+#[tokio::test]
+async fn test_full_queue_rejects_commands() {
+    let engine = Arc::new(
+        MatchingEngine::new(Box::new(InMemoryEventStore::new()))
+            .with_async_persistence(1, QueueFullPolicy::RejectCommands),
+    );
+    place(&engine, OrderSide::Sell, 100).await.unwrap();
+    assert!(place(&engine, OrderSide::Sell, 101).await.unwrap_err().contains("Event queue is full"));
+    assert!(engine.event_store().get_all_events().await.unwrap().is_empty());
+
+    engine.spawn_event_writer().unwrap();
+    engine.flush_events().await;
+    assert_eq!(saved_ids(&engine).await.len(), 1);
+    place(&engine, OrderSide::Sell, 101).await.unwrap();
+    engine.flush_events().await;
+    assert_eq!(saved_ids(&engine).await.len(), 2);
+}
+
+#[tokio::test]
+async fn test_full_queue_drops_events_covered_by_the_wal() {
+    let dir = std::env::temp_dir().join(format!("writer-{}", Uuid::new_v4()));
+    let engine = Arc::new(
+        MatchingEngine::new(Box::new(InMemoryEventStore::new()))
+            .with_wal(WriteAheadLog::open(&dir).unwrap())
+            .with_async_persistence(1, QueueFullPolicy::DropToWal),
+    );
+    let kept = place(&engine, OrderSide::Sell, 100).await.unwrap();
+    let dropped = place(&engine, OrderSide::Sell, 101).await.unwrap();
+    assert_eq!(engine.dropped_event_count(), dropped.len() as u64);
+
+    engine.spawn_event_writer().unwrap();
+    engine.flush_events().await;
+    assert_eq!(saved_ids(&engine).await, kept.iter().map(|e| e.event_id()).collect::<Vec<_>>());
+    assert_eq!(WriteAheadLog::open(&dir).unwrap().records().unwrap().len(), 2);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_snapshots_wait_for_queued_events_and_keep_dropped_ones_in_the_wal() {
+    let dir = std::env::temp_dir().join(format!("writer-{}", Uuid::new_v4()));
+    let engine = Arc::new(
+        MatchingEngine::new(Box::new(InMemoryEventStore::new()))
+            .with_wal(WriteAheadLog::open(&dir).unwrap())
+            .with_snapshots(InMemorySnapshotStore::new(), SnapshotCadence::default())
+            .with_async_persistence(1, QueueFullPolicy::DropToWal),
+    );
+    let kept = place(&engine, OrderSide::Sell, 100).await.unwrap();
+    let dropped = place(&engine, OrderSide::Sell, 101).await.unwrap();
+    assert!(engine.take_snapshot().await.unwrap_err().contains("not running"));
+
+    engine.spawn_event_writer().unwrap();
+    assert!(engine.take_snapshot().await.unwrap_err().contains("dropped"));
+    assert_eq!(saved_ids(&engine).await, kept.iter().map(|e| e.event_id()).collect::<Vec<_>>());
+    assert_eq!(WriteAheadLog::open(&dir).unwrap().records().unwrap().len(), 2);
+
+    // Recovery regenerates the dropped events with their original ids
+    let recovered =
+        MatchingEngine::new(Box::new(InMemoryEventStore::new())).with_wal(WriteAheadLog::open(&dir).unwrap());
+    recovered.recover().await.unwrap();
+    let all: Vec<Uuid> = kept.iter().chain(&dropped).map(|e| e.event_id()).collect();
+    assert_eq!(saved_ids(&recovered).await, all);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,68 @@
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType},
+    OrderCommand, PlaceOrderCommand,
+};
+use rust_decimal::Decimal;
+use tokio::sync::broadcast::error::TryRecvError;
+use uuid::Uuid;
+
+fn create_test_order_cmd(symbol: &str, side: OrderSide, price: i64, quantity: i64) -> OrderCommand {
+    OrderCommand::PlaceOrder(PlaceOrderCommand::new(
+        Uuid::new_v4(),
+        symbol,
+        OrderType::Limit,
+        side,
+        Some(Decimal::from(price)),
+        Decimal::from(quantity),
+    ))
+}
+
+#[tokio::test]
+async fn test_trade_feed_publishes_a_symbols_trades() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let mut trades = engine.subscribe_trades("BTC/USDT");
+
+    engine.handle_command(create_test_order_cmd("BTC/USDT", OrderSide::Sell, 100, 1)).await.unwrap();
+    engine.handle_command(create_test_order_cmd("BTC/USDT", OrderSide::Sell, 101, 1)).await.unwrap();
+    engine.handle_command(create_test_order_cmd("ETH/USDT", OrderSide::Sell, 10, 1)).await.unwrap();
+    engine.handle_command(create_test_order_cmd("ETH/USDT", OrderSide::Buy, 10, 1)).await.unwrap();
+    assert_eq!(trades.try_recv().unwrap_err(), TryRecvError::Empty);
+
+    engine.handle_command(create_test_order_cmd("BTC/USDT", OrderSide::Buy, 101, 2)).await.unwrap();
+    let prices = [trades.try_recv().unwrap().price, trades.try_recv().unwrap().price];
+    assert_eq!(prices, [Decimal::from(100), Decimal::from(101)]);
+    assert_eq!(trades.try_recv().unwrap_err(), TryRecvError::Empty);
+}
+
+#[tokio::test]
+async fn test_depth_feed_publishes_only_changes_within_its_levels() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let mut depth = engine.subscribe_depth("BTC/USDT", 1);
+
+    engine.handle_command(create_test_order_cmd("BTC/USDT", OrderSide::Sell, 100, 1)).await.unwrap();
+    let snapshot = depth.try_recv().unwrap();
+    assert_eq!(snapshot.asks.len(), 1);
+    assert_eq!(snapshot.asks[0].price, Decimal::from(100));
+
+    // Behind the top level
+    engine.handle_command(create_test_order_cmd("BTC/USDT", OrderSide::Sell, 105, 1)).await.unwrap();
+    assert_eq!(depth.try_recv().unwrap_err(), TryRecvError::Empty);
+
+    engine.handle_command(create_test_order_cmd("BTC/USDT", OrderSide::Buy, 90, 3)).await.unwrap();
+    let snapshot = depth.try_recv().unwrap();
+    assert_eq!(snapshot.bids[0].quantity, Decimal::from(3));
+}
+
+#[tokio::test]
+async fn test_ticker_feed_follows_trades_and_quotes() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let mut tickers = engine.subscribe_ticker("BTC/USDT");
+
+    engine.handle_command(create_test_order_cmd("BTC/USDT", OrderSide::Sell, 100, 2)).await.unwrap();
+    assert_eq!(tickers.try_recv().unwrap().best_ask, Some(Decimal::from(100)));
+
+    engine.handle_command(create_test_order_cmd("BTC/USDT", OrderSide::Buy, 100, 1)).await.unwrap();
+    let ticker = tickers.try_recv().unwrap();
+    assert_eq!((ticker.last_price, ticker.volume_24h), (Some(Decimal::from(100)), Decimal::ONE));
+    assert_eq!(tickers.try_recv().unwrap_err(), TryRecvError::Empty);
+}
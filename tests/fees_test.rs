@@ -0,0 +1,85 @@
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, symbols::SymbolConfig, types::{OrderSide, OrderType},
+    FeeChargedEvent, FeeSchedule, Liquidity, OrderCommand, OrderEvent, PlaceOrderCommand,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(user_id: Uuid, side: OrderSide, quantity: i64, referrer_id: Option<Uuid>) -> PlaceOrderCommand {
+    PlaceOrderCommand {
+        referrer_id,
+        ..PlaceOrderCommand::new(
+            user_id,
+            "BTC/USDT",
+            OrderType::Limit,
+            side,
+            Some(Decimal::from(1000)),
+            Decimal::from(quantity),
+        )
+    }
+}
+
+fn engine_with_fees() -> MatchingEngine {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    engine.symbols().register(SymbolConfig {
+        fees: Some(FeeSchedule {
+            maker_fee_percent: Decimal::new(-1, 2),
+            taker_fee_percent: Decimal::new(5, 2),
+            referral_percent: Decimal::from(20),
+        }),
+        ..SymbolConfig::new("BTC/USDT")
+    }).unwrap();
+    engine
+}
+
+fn fees(events: &[OrderEvent]) -> Vec<FeeChargedEvent> {
+    events
+        .iter()
+        .filter_map(|e| match e {
+            OrderEvent::FeeCharged(fee) => Some(fee.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_takers_pay_fees_and_makers_earn_rebates() {
+    let engine = engine_with_fees();
+    let (maker, taker) = (Uuid::new_v4(), Uuid::new_v4());
+    let resting = create_test_order_cmd(maker, OrderSide::Sell, 2, None);
+    let events = engine.handle_command(OrderCommand::PlaceOrder(resting.clone())).await.unwrap();
+    assert!(fees(&events).is_empty());
+
+    let incoming = create_test_order_cmd(taker, OrderSide::Buy, 2, None);
+    let events = engine.handle_command(OrderCommand::PlaceOrder(incoming.clone())).await.unwrap();
+    let charged = fees(&events);
+    assert_eq!(charged.len(), 2);
+    assert_eq!(charged[0].trade_id, charged[1].trade_id);
+    assert_eq!((charged[0].liquidity, charged[0].user_id), (Liquidity::Taker, taker));
+    assert_eq!((charged[0].asset.as_str(), charged[0].amount), ("USDT", Decimal::ONE));
+    assert_eq!((charged[1].liquidity, charged[1].order_id), (Liquidity::Maker, resting.order_id));
+    assert_eq!(charged[1].amount, Decimal::new(-2, 1));
+
+    // Symbols without a schedule charge nothing
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    engine.handle_command(OrderCommand::PlaceOrder(resting)).await.unwrap();
+    let events = engine.handle_command(OrderCommand::PlaceOrder(incoming)).await.unwrap();
+    assert!(fees(&events).is_empty());
+}
+
+#[tokio::test]
+async fn test_referrers_are_attributed_a_share_of_taker_fees() {
+    let engine = engine_with_fees();
+    let (maker, taker, referrer) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    let resting = create_test_order_cmd(maker, OrderSide::Sell, 2, Some(referrer));
+    engine.handle_command(OrderCommand::PlaceOrder(resting)).await.unwrap();
+    let incoming = create_test_order_cmd(taker, OrderSide::Buy, 2, Some(referrer));
+    let events = engine.handle_command(OrderCommand::PlaceOrder(incoming)).await.unwrap();
+
+    let charged = fees(&events);
+    assert_eq!(charged[0].referrer_id, Some(referrer));
+    assert_eq!(charged[0].referral_amount, Decimal::new(2, 1));
+    // A maker's referrer gets nothing from the rebate
+    assert_eq!(charged[1].referrer_id, None);
+    assert_eq!(charged[1].referral_amount, Decimal::ZERO);
+}
@@ -0,0 +1,101 @@
+#![cfg(feature = "ffi")]
+
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+
+use matching_engine::ffi::*;
+use serde_json::Value;
+use uuid::Uuid;
+
+struct Strings {
+    symbol: CString,
+    price: CString,
+    quantity: CString,
+}
+
+fn order(strings: &Strings, user_id: Uuid, side: i32) -> MeOrder {
+    MeOrder {
+        order_id: *Uuid::new_v4().as_bytes(),
+        user_id: *user_id.as_bytes(),
+        symbol: strings.symbol.as_ptr(),
+        side,
+        order_type: ME_ORDER_LIMIT,
+        price: strings.price.as_ptr(),
+        quantity: strings.quantity.as_ptr(),
+        stop_price: ptr::null(),
+    }
+}
+
+unsafe fn poll(engine: *mut MeEngine) -> Vec<Value> {
+    let mut length = 0;
+    assert_eq!(engine_poll_events(engine, ptr::null_mut(), 0, &mut length), ME_ERR_BUFFER_TOO_SMALL);
+    let mut buffer = vec![0 as c_char; length];
+    assert_eq!(engine_poll_events(engine, buffer.as_mut_ptr(), buffer.len(), ptr::null_mut()), ME_OK);
+    serde_json::from_str(CStr::from_ptr(buffer.as_ptr()).to_str().unwrap()).unwrap()
+}
+
+unsafe fn last_error(engine: *mut MeEngine) -> String {
+    let mut buffer = [0 as c_char; 256];
+    assert_eq!(engine_last_error(engine, buffer.as_mut_ptr(), buffer.len(), ptr::null_mut()), ME_OK);
+    CStr::from_ptr(buffer.as_ptr()).to_string_lossy().into_owned()
+}
+
+#[test]
+fn test_orders_are_matched_through_the_c_abi() {
+    let strings = Strings {
+        symbol: CString::new("BTC/USDT").unwrap(),
+        price: CString::new("100.5").unwrap(),
+        quantity: CString::new("2").unwrap(),
+    };
+    let (buyer, seller) = (Uuid::new_v4(), Uuid::new_v4());
+    unsafe {
+        let engine = engine_create();
+        assert!(!engine.is_null());
+
+        let bid = order(&strings, buyer, ME_SIDE_BUY);
+        assert_eq!(engine_place_order(engine, &bid), ME_OK);
+        assert_eq!(engine_place_order(engine, &bid), ME_ERR_DUPLICATE_ORDER);
+        assert!(last_error(engine).contains(&Uuid::from_bytes(bid.order_id).to_string()));
+        let mut ask = order(&strings, seller, ME_SIDE_SELL);
+        ask.quantity = CString::new("1").unwrap().into_raw();
+        assert_eq!(engine_place_order(engine, &ask), ME_OK);
+        drop(CString::from_raw(ask.quantity as *mut c_char));
+
+        let events = poll(engine);
+        let kinds: Vec<&str> = events.iter().map(|e| e.as_object().unwrap().keys().next().unwrap().as_str()).collect();
+        assert_eq!(kinds[..3], ["OrderPlaced", "OrderPlaced", "OrderMatched"]);
+        assert_eq!(events[2]["OrderMatched"]["price"], "100.5");
+        assert!(poll(engine).is_empty());
+
+        let symbol = strings.symbol.as_ptr();
+        assert_eq!(engine_cancel_order(engine, bid.order_id.as_ptr(), bid.user_id.as_ptr(), symbol), ME_OK);
+        assert!(poll(engine)[0].get("OrderCanceled").is_some());
+        assert_ne!(engine_cancel_order(engine, bid.order_id.as_ptr(), bid.user_id.as_ptr(), symbol), ME_OK);
+
+        engine_destroy(engine);
+    }
+}
+
+#[test]
+fn test_bad_arguments_are_refused() {
+    let strings = Strings {
+        symbol: CString::new("BTC/USDT").unwrap(),
+        price: CString::new("not a number").unwrap(),
+        quantity: CString::new("1").unwrap(),
+    };
+    unsafe {
+        let engine = engine_create();
+        assert_eq!(engine_place_order(ptr::null_mut(), ptr::null()), ME_ERR_NULL_ARGUMENT);
+        assert_eq!(engine_place_order(engine, ptr::null()), ME_ERR_NULL_ARGUMENT);
+
+        let bad_price = order(&strings, Uuid::new_v4(), ME_SIDE_BUY);
+        assert_eq!(engine_place_order(engine, &bad_price), ME_ERR_INVALID_ARGUMENT);
+        assert_eq!(last_error(engine), "price is not a decimal: not a number");
+
+        let mut bad_side = order(&strings, Uuid::new_v4(), 7);
+        bad_side.price = ptr::null();
+        assert_eq!(engine_place_order(engine, &bad_side), ME_ERR_INVALID_ARGUMENT);
+        assert_eq!(last_error(engine), "Unknown side 7");
+        engine_destroy(engine);
+    }
+}
@@ -0,0 +1,90 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+
+use matching_engine::{
+    engine::MatchingEngine, types::{OrderSide, OrderType},
+    EventStore, FileEventStore, FsyncPolicy, OrderCommand, OrderEvent, PlaceOrderCommand, TimeRange,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(side: OrderSide, price: Decimal, quantity: Decimal) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), "BTC/USDT", OrderType::Limit, side, Some(price), quantity)
+}
+
+fn event_ids(events: &[OrderEvent]) -> Vec<Option<Uuid>> {
+    events.iter().map(|e| e.order_id()).collect()
+}
+
+#[tokio::test]
+async fn test_events_survive_reopening_the_store() {
+    let dir = std::env::temp_dir().join(format!("events-{}", Uuid::new_v4()));
+    let engine = MatchingEngine::new(Box::new(FileEventStore::open(&dir).unwrap()));
+    let sell = create_test_order_cmd(OrderSide::Sell, Decimal::from(100), Decimal::from(2));
+    let buy = create_test_order_cmd(OrderSide::Buy, Decimal::from(100), Decimal::from(1));
+    engine.handle_command(OrderCommand::PlaceOrder(sell.clone())).await.unwrap();
+    engine.handle_command(OrderCommand::PlaceOrder(buy.clone())).await.unwrap();
+    let written = engine.event_store().get_all_events().await.unwrap();
+    let sell_events = engine.event_store().get_events(sell.order_id).await.unwrap();
+    assert!(!sell_events.is_empty());
+    drop(engine);
+
+    let store = FileEventStore::open(&dir).unwrap();
+    let read = store.get_all_events().await.unwrap();
+    assert_eq!(event_ids(&read), event_ids(&written));
+    assert_eq!(event_ids(&store.get_events(sell.order_id).await.unwrap()), event_ids(&sell_events));
+    assert!(store.get_events(Uuid::new_v4()).await.unwrap().is_empty());
+    assert_eq!(event_ids(&store.get_events_by_symbol("BTC/USDT", TimeRange::all()).await.unwrap()), event_ids(&written));
+    assert!(store.get_events_by_symbol("ETH/USDT", TimeRange::all()).await.unwrap().is_empty());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_appends_roll_over_to_new_segments() {
+    let dir = std::env::temp_dir().join(format!("events-{}", Uuid::new_v4()));
+    let engine = MatchingEngine::new(Box::new(
+        FileEventStore::open(&dir).unwrap().with_segment_bytes(1).with_fsync(FsyncPolicy::EveryBatches(2)),
+    ));
+    for price in [100, 101, 102] {
+        let cmd = create_test_order_cmd(OrderSide::Buy, Decimal::from(price), Decimal::ONE);
+        engine.handle_command(OrderCommand::PlaceOrder(cmd)).await.unwrap();
+    }
+    let written = engine.event_store().get_all_events().await.unwrap();
+    drop(engine);
+
+    let store = FileEventStore::open(&dir).unwrap();
+    assert_eq!(store.segment_count(), 3);
+    assert_eq!(event_ids(&store.get_all_events().await.unwrap()), event_ids(&written));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_torn_frame_is_cut_off_on_open() {
+    let dir = std::env::temp_dir().join(format!("events-{}", Uuid::new_v4()));
+    let engine = MatchingEngine::new(Box::new(FileEventStore::open(&dir).unwrap()));
+    let cmd = create_test_order_cmd(OrderSide::Buy, Decimal::from(100), Decimal::ONE);
+    engine.handle_command(OrderCommand::PlaceOrder(cmd.clone())).await.unwrap();
+    let written = engine.event_store().get_all_events().await.unwrap();
+    drop(engine);
+
+    // A crash halfway through the next append
+    let segment = fs::read_dir(&dir).unwrap().next().unwrap().unwrap().path();
+    let mut file = OpenOptions::new().append(true).open(&segment).unwrap();
+    file.write_all(&[200, 0, 0, 0, b'{']).unwrap();
+    drop(file);
+
+    let store = FileEventStore::open(&dir).unwrap();
+    assert_eq!(event_ids(&store.get_all_events().await.unwrap()), event_ids(&written));
+    let sell = create_test_order_cmd(OrderSide::Sell, Decimal::from(100), Decimal::ONE);
+    let engine = MatchingEngine::new(Box::new(store));
+    engine.handle_command(OrderCommand::PlaceOrder(sell)).await.unwrap();
+    let all = engine.event_store().get_all_events().await.unwrap();
+    assert!(all.len() > written.len());
+    assert_eq!(event_ids(&all[..written.len()]), event_ids(&written));
+    drop(engine);
+    assert!(FileEventStore::open(&dir).is_ok());
+
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,171 @@
+#![cfg(feature = "fix")]
+
+use std::sync::Arc;
+
+use matching_engine::{engine::MatchingEngine, event_store::InMemoryEventStore, serve_fix, FixMessage};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use uuid::Uuid;
+
+struct Client {
+    stream: BufReader<TcpStream>,
+    next_seq: u64,
+}
+
+impl Client {
+    async fn connect(engine: Arc<MatchingEngine>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { serve_fix(engine, listener, "ENGINE").await });
+        let mut client = Self {
+            stream: BufReader::new(TcpStream::connect(addr).await.unwrap()),
+            next_seq: 1,
+        };
+        let logon = client.message("A").with(98, 0).with(108, 0);
+        client.send(logon).await;
+        let logon = client.receive().await;
+        assert_eq!(logon.msg_type(), "A");
+        assert_eq!(logon.get(56), Some("CLIENT"));
+        client
+    }
+
+    /// A message with the standard header, on the next sequence number.
+    fn message(&mut self, msg_type: &str) -> FixMessage {
+        self.next_seq += 1;
+        header(msg_type, self.next_seq - 1)
+    }
+
+    async fn send(&mut self, message: FixMessage) {
+        self.stream.get_mut().write_all(&message.encode()).await.unwrap();
+    }
+
+    async fn receive(&mut self) -> FixMessage {
+        let mut bytes = Vec::new();
+        loop {
+            let start = bytes.len();
+            self.stream.read_until(0x01, &mut bytes).await.unwrap();
+            if bytes[start..].starts_with(b"10=") {
+                return FixMessage::parse(&bytes).unwrap();
+            }
+        }
+    }
+
+    async fn is_closed(&mut self) -> bool {
+        let mut rest = Vec::new();
+        self.stream.read_to_end(&mut rest).await.is_ok()
+    }
+}
+
+fn header(msg_type: &str, seq: u64) -> FixMessage {
+    FixMessage::new(msg_type).with(49, "CLIENT").with(56, "ENGINE").with(34, seq)
+}
+
+fn new_order(message: FixMessage, user_id: Uuid, cl_ord_id: &str, side: &str, price: &str, quantity: &str) -> FixMessage {
+    message
+        .with(1, user_id)
+        .with(11, cl_ord_id)
+        .with(55, "BTC/USDT")
+        .with(54, side)
+        .with(38, quantity)
+        .with(40, 2)
+        .with(44, price)
+}
+
+#[tokio::test]
+async fn test_orders_are_reported_through_their_lifecycle() {
+    let engine = Arc::new(MatchingEngine::new(Box::new(InMemoryEventStore::new())));
+    let mut client = Client::connect(engine.clone()).await;
+    let (buyer, seller) = (Uuid::new_v4(), Uuid::new_v4());
+
+    let message = new_order(client.message("D"), buyer, "B1", "1", "100", "3");
+    client.send(message).await;
+    let new = client.receive().await;
+    assert_eq!((new.msg_type(), new.get(150), new.get(39)), ("8", Some("0"), Some("0")));
+    assert_eq!((new.get(11), new.get(151)), (Some("B1"), Some("3")));
+    let buy_id: Uuid = new.get(37).unwrap().parse().unwrap();
+    assert_eq!(engine.get_order(buy_id).unwrap().client_order_id.as_deref(), Some("B1"));
+
+    let message = new_order(client.message("D"), seller, "S1", "2", "100", "1");
+    client.send(message).await;
+    assert_eq!(client.receive().await.get(150), Some("0"));
+    let taker = client.receive().await;
+    assert_eq!((taker.get(11), taker.get(150), taker.get(39)), (Some("S1"), Some("F"), Some("2")));
+    assert_eq!((taker.get(32), taker.get(31)), (Some("1"), Some("100")));
+    let maker = client.receive().await;
+    assert_eq!((maker.get(11), maker.get(39), maker.get(14), maker.get(151)), (Some("B1"), Some("1"), Some("1"), Some("2")));
+
+    let replace = client
+        .message("G")
+        .with(1, buyer)
+        .with(11, "B2")
+        .with(41, "B1")
+        .with(55, "BTC/USDT")
+        .with(54, 1)
+        .with(38, 5)
+        .with(44, 99);
+    client.send(replace).await;
+    let replaced = client.receive().await;
+    assert_eq!((replaced.get(150), replaced.get(11), replaced.get(41)), (Some("5"), Some("B2"), Some("B1")));
+    assert_eq!((replaced.get(14), replaced.get(151)), (Some("1"), Some("4")));
+
+    let cancel = |message: FixMessage, cl_ord_id| {
+        message.with(1, buyer).with(11, cl_ord_id).with(41, "B2").with(55, "BTC/USDT").with(54, 1)
+    };
+    let message = cancel(client.message("F"), "B3");
+    client.send(message).await;
+    let canceled = client.receive().await;
+    assert_eq!((canceled.get(150), canceled.get(39)), (Some("4"), Some("4")));
+    assert_eq!((canceled.get(11), canceled.get(41)), (Some("B3"), Some("B2")));
+
+    let message = cancel(client.message("F"), "B4");
+    client.send(message).await;
+    let reject = client.receive().await;
+    assert_eq!((reject.msg_type(), reject.get(434)), ("9", Some("1")));
+}
+
+#[tokio::test]
+async fn test_refused_orders_are_rejected() {
+    let engine = Arc::new(MatchingEngine::new(Box::new(InMemoryEventStore::new())));
+    let mut client = Client::connect(engine).await;
+
+    let message = new_order(client.message("D"), Uuid::new_v4(), "X1", "1", "100", "0");
+    client.send(message).await;
+    let rejected = client.receive().await;
+    assert_eq!((rejected.get(150), rejected.get(39), rejected.get(11)), (Some("8"), Some("8"), Some("X1")));
+
+    let message = new_order(client.message("D"), Uuid::new_v4(), "X2", "1", "100", "1").with(59, 3);
+    client.send(message).await;
+    assert!(client.receive().await.get(58).unwrap().contains("GoodTillCancel"));
+}
+
+#[tokio::test]
+async fn test_session_messages_are_answered() {
+    let engine = Arc::new(MatchingEngine::new(Box::new(InMemoryEventStore::new())));
+    let mut client = Client::connect(engine).await;
+
+    let test_request = client.message("1").with(112, "PING");
+    client.send(test_request).await;
+    let heartbeat = client.receive().await;
+    assert_eq!((heartbeat.msg_type(), heartbeat.get(112)), ("0", Some("PING")));
+
+    let resend_request = client.message("2").with(7, 1).with(16, 0);
+    client.send(resend_request).await;
+    let gap_fill = client.receive().await;
+    assert_eq!((gap_fill.msg_type(), gap_fill.get(34), gap_fill.get(123)), ("4", Some("1"), Some("Y")));
+    assert_eq!(gap_fill.get(36), Some("3"));
+
+    let unsupported = client.message("Q");
+    client.send(unsupported).await;
+    assert_eq!(client.receive().await.msg_type(), "j");
+
+    // Skips a number: the engine asks for it again
+    client.send(header("0", 10)).await;
+    let resend = client.receive().await;
+    assert_eq!((resend.msg_type(), resend.get(7)), ("2", Some("5")));
+
+    client.send(header("0", 2)).await;
+    let logout = client.receive().await;
+    assert_eq!(logout.msg_type(), "5");
+    assert!(logout.get(58).unwrap().starts_with("MsgSeqNum too low"));
+    assert!(client.is_closed().await);
+}
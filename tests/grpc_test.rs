@@ -0,0 +1,99 @@
+#![cfg(feature = "grpc")]
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, serve_grpc, types::{OrderSide, OrderType},
+    CancelOrderCommand, DepthRequest, EventFilter, GrpcClient, OrderEvent, PlaceOrderCommand,
+};
+use rust_decimal::Decimal;
+use tokio::net::TcpListener;
+use tonic::Code;
+use uuid::Uuid;
+
+fn create_test_order_cmd(symbol: &str, side: OrderSide, price: i64, quantity: i64) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(
+        Uuid::new_v4(),
+        symbol,
+        OrderType::Limit,
+        side,
+        Some(Decimal::from(price)),
+        Decimal::from(quantity),
+    )
+}
+
+async fn connect() -> (Arc<MatchingEngine>, GrpcClient) {
+    let engine = Arc::new(MatchingEngine::new(Box::new(InMemoryEventStore::new())));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(serve_grpc(engine.clone(), listener));
+    (engine, GrpcClient::connect(format!("http://{}", addr)).await.unwrap())
+}
+
+fn depth_request(symbol: &str, levels: usize) -> DepthRequest {
+    DepthRequest {
+        symbol: symbol.to_string(),
+        levels,
+    }
+}
+
+#[tokio::test]
+async fn test_orders_are_placed_and_canceled() {
+    let (engine, mut client) = connect().await;
+    let order = create_test_order_cmd("BTC/USDT", OrderSide::Buy, 100, 2);
+
+    let events = client.place_order(order.clone()).await.unwrap().into_inner();
+    assert!(matches!(&events[0], OrderEvent::OrderPlaced(e) if e.order_id == order.order_id));
+    assert_eq!(engine.get_order(order.order_id).unwrap().quantity, Decimal::from(2));
+
+    let duplicate = client.place_order(order.clone()).await.unwrap_err();
+    assert_eq!(duplicate.code(), Code::AlreadyExists);
+
+    let cancel = CancelOrderCommand {
+        order_id: order.order_id,
+        client_order_id: None,
+        user_id: order.user_id,
+        symbol: order.symbol.clone(),
+        timestamp: Utc::now()
+    };
+    let events = client.cancel_order(cancel.clone()).await.unwrap().into_inner();
+    assert!(matches!(&events[0], OrderEvent::OrderCanceled(e) if e.order_id == order.order_id));
+    assert_ne!(client.cancel_order(cancel).await.unwrap_err().code(), Code::Ok);
+}
+
+#[tokio::test]
+async fn test_event_subscriptions_follow_their_filter() {
+    let (_engine, mut client) = connect().await;
+    let filter = EventFilter {
+        symbol: Some("ETH/USDT".to_string()),
+        ..EventFilter::default()
+    };
+    let mut events = client.subscribe_events(filter).await.unwrap().into_inner();
+
+    client.place_order(create_test_order_cmd("BTC/USDT", OrderSide::Sell, 100, 1)).await.unwrap();
+    let order = create_test_order_cmd("ETH/USDT", OrderSide::Sell, 10, 1);
+    client.place_order(order.clone()).await.unwrap();
+
+    let event = events.message().await.unwrap().unwrap();
+    assert_eq!(event.symbol(), Some("ETH/USDT"));
+    assert_eq!(event.order_id(), Some(order.order_id));
+}
+
+#[tokio::test]
+async fn test_depth_subscriptions_start_from_the_current_book() {
+    let (_engine, mut client) = connect().await;
+    client.place_order(create_test_order_cmd("BTC/USDT", OrderSide::Buy, 99, 1)).await.unwrap();
+
+    let mut depth = client.subscribe_depth(depth_request("BTC/USDT", 5)).await.unwrap().into_inner();
+    let snapshot = depth.message().await.unwrap().unwrap();
+    assert_eq!(snapshot.bids[0].price, Decimal::from(99));
+    assert!(snapshot.asks.is_empty());
+
+    client.place_order(create_test_order_cmd("BTC/USDT", OrderSide::Sell, 101, 3)).await.unwrap();
+    let snapshot = depth.message().await.unwrap().unwrap();
+    assert_eq!(snapshot.asks[0].quantity, Decimal::from(3));
+
+    let missing = client.subscribe_depth(depth_request("", 5)).await.err().unwrap();
+    assert_eq!(missing.code(), Code::InvalidArgument);
+}
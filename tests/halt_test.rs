@@ -0,0 +1,76 @@
+use chrono::Utc;
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderStatus, OrderType},
+    CancelOrderCommand, HaltedCommandPolicy, OrderCommand, OrderEvent, PlaceOrderCommand, SymbolState,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(price: Decimal, quantity: Decimal, side: OrderSide) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), "BTC/USDT", OrderType::Limit, side, Some(price), quantity)
+}
+
+#[tokio::test]
+async fn test_halt_rejects_commands_until_resumed() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let resting = create_test_order_cmd(Decimal::from(100), Decimal::ONE, OrderSide::Sell);
+    engine.handle_place_order(resting.clone()).await.unwrap();
+
+    let events = engine.halt("BTC/USDT").await.unwrap();
+    assert!(matches!(&events[..], [OrderEvent::SymbolStateChanged(e)]
+        if e.previous_state == SymbolState::Trading && e.state == SymbolState::Halted));
+    assert!(engine.halt("BTC/USDT").await.unwrap().is_empty());
+
+    let buy = create_test_order_cmd(Decimal::from(100), Decimal::ONE, OrderSide::Buy);
+    assert!(engine.handle_place_order(buy.clone()).await.is_err());
+    let cancel = CancelOrderCommand {
+        order_id: resting.order_id,
+        client_order_id: None,
+        user_id: resting.user_id,
+        symbol: resting.symbol.clone(),
+        timestamp: Utc::now(),
+    };
+    assert!(engine.handle_command(OrderCommand::CancelOrder(cancel.clone())).await.is_err());
+
+    // Cancel-only admits cancellations but still no new orders
+    engine.set_symbol_state("BTC/USDT", SymbolState::CancelOnly).await.unwrap();
+    assert!(engine.handle_place_order(buy.clone()).await.is_err());
+    engine.handle_command(OrderCommand::CancelOrder(cancel)).await.unwrap();
+    assert_eq!(engine.get_order(resting.order_id).unwrap().status, OrderStatus::Canceled);
+
+    let events = engine.resume("BTC/USDT").await.unwrap();
+    assert!(matches!(&events[..], [OrderEvent::SymbolStateChanged(e)] if e.state == SymbolState::Trading));
+    engine.handle_place_order(buy).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_queued_commands_replay_on_resume() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()))
+        .with_halted_command_policy(HaltedCommandPolicy::Queue);
+    engine.halt("BTC/USDT").await.unwrap();
+
+    let sell = create_test_order_cmd(Decimal::from(100), Decimal::from(2), OrderSide::Sell);
+    let buy = create_test_order_cmd(Decimal::from(100), Decimal::ONE, OrderSide::Buy);
+    for cmd in [sell.clone(), buy.clone()] {
+        assert!(engine.handle_command(OrderCommand::PlaceOrder(cmd)).await.unwrap().is_empty());
+    }
+    assert_eq!(engine.queued_command_count("BTC/USDT"), 2);
+    assert!(engine.get_order(sell.order_id).is_none());
+
+    let events = engine.resume("BTC/USDT").await.unwrap();
+    assert!(matches!(events[0], OrderEvent::SymbolStateChanged(_)));
+    assert!(events.iter().any(|e| matches!(e, OrderEvent::OrderMatched(m) if m.order_id == buy.order_id)));
+    assert_eq!(engine.queued_command_count("BTC/USDT"), 0);
+    assert_eq!(engine.get_order(sell.order_id).unwrap().status, OrderStatus::PartiallyFilled);
+}
+
+#[tokio::test]
+async fn test_resume_refuses_to_skip_auction() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    engine.start_auction("BTC/USDT").await.unwrap();
+    assert_eq!(engine.symbol_state("BTC/USDT"), SymbolState::AuctionOnly);
+    assert!(engine.resume("BTC/USDT").await.is_err());
+
+    let events = engine.run_auction("BTC/USDT").await.unwrap();
+    assert!(matches!(events.last(), Some(OrderEvent::SymbolStateChanged(e)) if e.state == SymbolState::Trading));
+}
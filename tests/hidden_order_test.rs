@@ -0,0 +1,51 @@
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderStatus, OrderType},
+    OrderEvent, PlaceOrderCommand,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(price: Decimal, quantity: Decimal, side: OrderSide, displayed: bool) -> PlaceOrderCommand {
+    PlaceOrderCommand {
+        displayed,
+        ..PlaceOrderCommand::new(Uuid::new_v4(), "BTC/USDT", OrderType::Limit, side, Some(price), quantity)
+    }
+}
+
+#[tokio::test]
+async fn test_hidden_orders_stay_out_of_the_book() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    for (price, displayed) in [(100, false), (99, true), (99, false)] {
+        engine
+            .handle_place_order(create_test_order_cmd(Decimal::from(price), Decimal::from(2), OrderSide::Buy, displayed))
+            .await
+            .unwrap();
+    }
+
+    let book = engine.get_order_book("BTC/USDT").unwrap();
+    assert_eq!(book.bids.len(), 1);
+    assert_eq!(book.bids[0].price, Decimal::from(99));
+    assert_eq!(book.bids[0].quantity, Decimal::from(2));
+    assert_eq!(book.bids[0].order_count, 1);
+}
+
+#[tokio::test]
+async fn test_hidden_orders_match_after_displayed_at_same_price() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let hidden = create_test_order_cmd(Decimal::from(100), Decimal::from(2), OrderSide::Sell, false);
+    let displayed = create_test_order_cmd(Decimal::from(100), Decimal::from(2), OrderSide::Sell, true);
+    engine.handle_place_order(hidden.clone()).await.unwrap();
+    engine.handle_place_order(displayed.clone()).await.unwrap();
+
+    // The displayed order arrived later but still trades first
+    let events = engine
+        .handle_place_order(create_test_order_cmd(Decimal::from(100), Decimal::from(3), OrderSide::Buy, true))
+        .await
+        .unwrap();
+    let makers: Vec<(Uuid, Decimal)> = events.iter().filter_map(|e| match e {
+        OrderEvent::OrderMatched(m) => Some((m.matched_order_id, m.quantity)),
+        _ => None,
+    }).collect();
+    assert_eq!(makers, vec![(displayed.order_id, Decimal::from(2)), (hidden.order_id, Decimal::ONE)]);
+    assert_eq!(engine.get_order(hidden.order_id).unwrap().status, OrderStatus::PartiallyFilled);
+}
@@ -0,0 +1,100 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderStatus, OrderType},
+    CancelOrderCommand, EngineError, EventStore, OrderCommand, OrderEvent, PlaceOrderCommand,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(user_id: Uuid, client_order_id: &str, price: Decimal, side: OrderSide) -> PlaceOrderCommand {
+    PlaceOrderCommand {
+        client_order_id: Some(client_order_id.to_string()),
+        ..PlaceOrderCommand::new(user_id, "BTC/USDT", OrderType::Limit, side, Some(price), Decimal::ONE)
+    }
+}
+
+#[tokio::test]
+async fn test_resubmission_returns_original_events() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let user_id = Uuid::new_v4();
+    let cmd = create_test_order_cmd(user_id, "abc-1", Decimal::from(100), OrderSide::Buy);
+    let first = engine.handle_place_order(cmd.clone()).await.unwrap();
+
+    // A retry with a fresh order id is still the same client order
+    let retry = PlaceOrderCommand { order_id: Uuid::new_v4(), ..cmd.clone() };
+    let second = engine.handle_command(OrderCommand::PlaceOrder(retry.clone())).await.unwrap();
+    assert_eq!(first.len(), second.len());
+    assert!(matches!(&second[0], OrderEvent::OrderPlaced(p) if p.order_id == cmd.order_id));
+    assert!(engine.get_order(retry.order_id).is_none());
+    assert_eq!(engine.get_open_orders(user_id).len(), 1);
+
+    // The same client order id from another user is a different order
+    let other = create_test_order_cmd(Uuid::new_v4(), "abc-1", Decimal::from(100), OrderSide::Buy);
+    engine.handle_place_order(other.clone()).await.unwrap();
+    assert!(engine.get_order(other.order_id).is_some());
+}
+
+#[tokio::test]
+async fn test_rejected_attempt_can_be_retried() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let user_id = Uuid::new_v4();
+    let mut cmd = create_test_order_cmd(user_id, "abc-2", Decimal::from(100), OrderSide::Buy);
+    cmd.price = None;
+    assert!(engine.handle_place_order(cmd.clone()).await.is_err());
+
+    cmd.price = Some(Decimal::from(100));
+    engine.handle_place_order(cmd.clone()).await.unwrap();
+    assert_eq!(engine.get_order_by_client_id(user_id, "abc-2").unwrap().id, cmd.order_id);
+}
+
+struct OfflineEventStore;
+
+#[async_trait]
+impl EventStore for OfflineEventStore {
+    async fn save_events(&self, _events: Vec<OrderEvent>) -> Result<(), String> {
+        Err("offline".to_string())
+    }
+
+    async fn get_events(&self, _order_id: Uuid) -> Result<Vec<OrderEvent>, String> {
+        Ok(Vec::new())
+    }
+
+    async fn get_all_events(&self) -> Result<Vec<OrderEvent>, String> {
+        Ok(Vec::new())
+    }
+}
+
+#[tokio::test]
+async fn test_retry_after_a_failed_save_does_not_place_a_second_order() {
+    let engine = MatchingEngine::new(Box::new(OfflineEventStore));
+    let user_id = Uuid::new_v4();
+    let cmd = create_test_order_cmd(user_id, "abc-4", Decimal::from(100), OrderSide::Buy);
+    let result = engine.handle_place_order(cmd.clone()).await;
+    assert_eq!(result.unwrap_err(), EngineError::Rejected("offline".to_string()));
+
+    let retry = PlaceOrderCommand { order_id: Uuid::new_v4(), ..cmd.clone() };
+    let events = engine.handle_place_order(retry).await.unwrap();
+    assert!(matches!(&events[..], [OrderEvent::OrderPlaced(p)] if p.order_id == cmd.order_id));
+    assert_eq!(engine.get_open_orders(user_id).len(), 1);
+}
+
+#[tokio::test]
+async fn test_cancel_by_client_order_id() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let user_id = Uuid::new_v4();
+    let cmd = create_test_order_cmd(user_id, "abc-3", Decimal::from(100), OrderSide::Sell);
+    engine.handle_place_order(cmd.clone()).await.unwrap();
+
+    let cancel = |client_order_id: &str| CancelOrderCommand {
+        order_id: Uuid::nil(),
+        client_order_id: Some(client_order_id.to_string()),
+        user_id,
+        symbol: "BTC/USDT".to_string(),
+        timestamp: Utc::now(),
+    };
+    assert!(engine.handle_command(OrderCommand::CancelOrder(cancel("unknown"))).await.is_err());
+    let events = engine.handle_command(OrderCommand::CancelOrder(cancel("abc-3"))).await.unwrap();
+    assert!(matches!(&events[..], [OrderEvent::OrderCanceled(c)] if c.order_id == cmd.order_id));
+    assert_eq!(engine.get_order(cmd.order_id).unwrap().status, OrderStatus::Canceled);
+}
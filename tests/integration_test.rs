@@ -1,22 +1,9 @@
-use chrono::Utc;
-use matching_engine::{engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType}, PlaceOrderCommand};
+use matching_engine::{engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType}, MatchingAlgorithm, PlaceOrderCommand, SymbolConfig};
 use rust_decimal::Decimal;
 use uuid::Uuid;
 
 fn create_test_order_cmd(price: Decimal, quantity: Decimal, side: OrderSide) -> PlaceOrderCommand {
-    PlaceOrderCommand {
-        order_id: Uuid::new_v4(),
-        user_id: Uuid::new_v4(),
-        symbol: "BTC/USDT".to_string(),
-        order_type: OrderType::Limit,
-        side,
-        price: Some(price),
-        quantity,
-        iceberg_visible_quantity: None,
-        stop_price: None,
-        trailing_stop_price: None,
-        timestamp: Utc::now()
-    }
+    PlaceOrderCommand::new(Uuid::new_v4(), "BTC/USDT", OrderType::Limit, side, Some(price), quantity)
 }
 
 #[tokio::test]
@@ -40,7 +27,7 @@ async fn test_basic_matching() {
         OrderSide::Sell,
     );
     let sell_events = engine.handle_place_order(sell_order).await.unwrap();
-    assert_eq!(sell_events.len(), 1); // OrderPlaced and OrderMatched events
+    assert_eq!(sell_events.len(), 2); // OrderPlaced and OrderMatched events
 
     // Verify order book is empty
     let order_book = engine.get_order_book("BTC/USDT").unwrap();
@@ -139,4 +126,30 @@ async fn test_market_order() {
     let order_book = engine.get_order_book("BTC/USDT").unwrap();
     assert!(order_book.bids.is_empty());
     assert!(order_book.asks.is_empty());
-} 
\ No newline at end of file
+} 
+
+#[tokio::test]
+async fn test_pro_rata_matching() {
+    let event_store = Box::new(InMemoryEventStore::new());
+    let engine = MatchingEngine::new(event_store);
+    engine.symbols().register(SymbolConfig {
+        matching_algorithm: MatchingAlgorithm::ProRata,
+        ..SymbolConfig::new("BTC/USDT")
+    }).unwrap();
+
+    // Two resting bids at the same price, the older one smaller
+    let small_bid = create_test_order_cmd(Decimal::from(100), Decimal::from(2), OrderSide::Buy);
+    let large_bid = create_test_order_cmd(Decimal::from(100), Decimal::from(6), OrderSide::Buy);
+    let (small_id, large_id) = (small_bid.order_id, large_bid.order_id);
+    let _ = engine.handle_place_order(small_bid).await.unwrap();
+    let _ = engine.handle_place_order(large_bid).await.unwrap();
+
+    // FIFO would fill the small bid completely and the large one by 2;
+    // pro-rata splits 1:3
+    let sell_order = create_test_order_cmd(Decimal::from(100), Decimal::from(4), OrderSide::Sell);
+    let sell_events = engine.handle_place_order(sell_order).await.unwrap();
+    assert_eq!(sell_events.len(), 3); // OrderPlaced and two OrderMatched events
+
+    assert_eq!(engine.get_order(small_id).unwrap().filled_quantity, Decimal::from(1));
+    assert_eq!(engine.get_order(large_id).unwrap().filled_quantity, Decimal::from(3));
+}
@@ -0,0 +1,64 @@
+use chrono::Utc;
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType},
+    CancelOrderCommand, EngineError, InvariantChecks, InvariantViolation, OrderCommand, OrderEvent,
+    PlaceOrderCommand, SymbolState,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(price: Decimal, side: OrderSide) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), "BTC/USDT", OrderType::Limit, side, Some(price), Decimal::ONE)
+}
+
+/// Leaves a crossed book in continuous trading by halting an auction and
+/// forcing the symbol back to Trading without uncrossing it.
+async fn crossed_engine(checks: InvariantChecks) -> (MatchingEngine, PlaceOrderCommand) {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new())).with_invariant_checks(checks);
+    engine.start_auction("BTC/USDT").await.unwrap();
+    let bid = create_test_order_cmd(Decimal::from(101), OrderSide::Buy);
+    engine.handle_command(OrderCommand::PlaceOrder(bid.clone())).await.unwrap();
+    engine
+        .handle_command(OrderCommand::PlaceOrder(create_test_order_cmd(Decimal::from(100), OrderSide::Sell)))
+        .await
+        .unwrap();
+    assert!(engine.check_invariants("BTC/USDT").is_ok());
+
+    engine.halt("BTC/USDT").await.unwrap();
+    engine.set_symbol_state("BTC/USDT", SymbolState::Trading).await.unwrap();
+    (engine, bid)
+}
+
+#[tokio::test]
+async fn test_crossed_book_is_reported_after_command() {
+    let (engine, bid) = crossed_engine(InvariantChecks::Enabled { emit_event: true }).await;
+    let cancel = CancelOrderCommand {
+        order_id: bid.order_id,
+        client_order_id: None,
+        user_id: Uuid::new_v4(),
+        symbol: bid.symbol.clone(),
+        timestamp: Utc::now(),
+    };
+
+    // The command itself is rejected, but the checker still runs
+    let err = engine.handle_command(OrderCommand::CancelOrder(cancel)).await.unwrap_err();
+    let EngineError::InternalInvariantViolation(violation) = err else {
+        panic!("Expected an invariant violation, got {:?}", err);
+    };
+    assert_eq!(violation, InvariantViolation::CrossedBook {
+        symbol: "BTC/USDT".to_string(),
+        best_bid: Decimal::from(101),
+        best_ask: Decimal::from(100),
+    });
+
+    let events = engine.event_store().get_all_events().await.unwrap();
+    assert!(events.iter().any(|e| matches!(e, OrderEvent::InvariantViolated(v) if v.violation == violation)));
+}
+
+#[tokio::test]
+async fn test_disabled_checks_stay_silent() {
+    let (engine, _) = crossed_engine(InvariantChecks::Disabled).await;
+    let cmd = create_test_order_cmd(Decimal::from(50), OrderSide::Buy);
+    assert!(engine.handle_command(OrderCommand::PlaceOrder(cmd)).await.is_ok());
+    assert!(engine.check_invariants("BTC/USDT").is_err());
+}
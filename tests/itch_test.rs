@@ -0,0 +1,83 @@
+use chrono::Utc;
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType}, ItchDecoder,
+    ItchEncoder, ItchMessage, OrderCommand, PlaceOrderCommand,
+};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+use uuid::Uuid;
+
+fn create_test_order_cmd(symbol: &str, side: OrderSide, price: &str, quantity: i64) -> OrderCommand {
+    OrderCommand::PlaceOrder(PlaceOrderCommand::new(
+        Uuid::new_v4(),
+        symbol,
+        OrderType::Limit,
+        side,
+        Some(Decimal::from_str(price).unwrap()),
+        Decimal::from(quantity),
+    ))
+}
+
+#[tokio::test]
+async fn test_deltas_and_trades_round_trip() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let mut deltas = engine.subscribe_book_deltas();
+    let mut trades = engine.subscribe_trades("BTC/USDT");
+
+    engine.handle_command(create_test_order_cmd("BTC/USDT", OrderSide::Sell, "100.25", 3)).await.unwrap();
+    engine.handle_command(create_test_order_cmd("BTC/USDT", OrderSide::Buy, "100.25", 1)).await.unwrap();
+
+    let mut encoder = ItchEncoder::new();
+    let mut decoder = ItchDecoder::new();
+    let ask = deltas.recv().await.unwrap();
+    let fill = deltas.recv().await.unwrap();
+    let trade = trades.recv().await.unwrap();
+
+    let mut feed = encoder.encode_delta(&ask).unwrap();
+    feed.extend(encoder.encode_delta(&fill).unwrap());
+    feed.extend(encoder.encode_trade(&trade).unwrap());
+    let messages = decoder.decode(&feed).unwrap();
+
+    let sequences: Vec<u64> = messages.iter().map(|(sequence, _)| *sequence).collect();
+    assert_eq!(sequences, vec![1, 2, 3, 4]);
+    assert_eq!(encoder.next_sequence(), 5);
+    assert_eq!(messages[0].1, ItchMessage::SymbolDirectory { locate: 1, symbol: "BTC/USDT".to_string() });
+    assert_eq!(messages[1].1, ItchMessage::BookDelta(ask));
+    assert_eq!(messages[2].1, ItchMessage::BookDelta(fill));
+    assert_eq!(
+        messages[3].1,
+        ItchMessage::Trade {
+            id: trade.id,
+            symbol: trade.symbol.clone(),
+            price: Decimal::from_str("100.25").unwrap(),
+            quantity: Decimal::from(1),
+            side: OrderSide::Buy,
+            timestamp: trade.created_at,
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_late_consumers_need_the_directory() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let mut deltas = engine.subscribe_book_deltas();
+    engine.handle_command(create_test_order_cmd("BTC/USDT", OrderSide::Buy, "99", 1)).await.unwrap();
+    engine.handle_command(create_test_order_cmd("ETH/USDT", OrderSide::Buy, "10", 1)).await.unwrap();
+    engine.handle_command(create_test_order_cmd("ETH/USDT", OrderSide::Buy, "9", 1)).await.unwrap();
+
+    let mut encoder = ItchEncoder::new();
+    encoder.encode_delta(&deltas.recv().await.unwrap()).unwrap();
+    encoder.encode_delta(&deltas.recv().await.unwrap()).unwrap();
+    let missed_directory = encoder.encode_delta(&deltas.recv().await.unwrap()).unwrap();
+
+    let mut decoder = ItchDecoder::new();
+    assert_eq!(decoder.decode(&missed_directory).unwrap_err(), "Unknown locate code 2");
+
+    let directory = decoder.decode(&encoder.encode_directory(Utc::now())).unwrap();
+    assert_eq!(directory.len(), 2);
+    assert!(matches!(&directory[1].1, ItchMessage::SymbolDirectory { locate: 2, symbol } if symbol == "ETH/USDT"));
+    let messages = decoder.decode(&missed_directory).unwrap();
+    assert!(matches!(&messages[0], (5, ItchMessage::BookDelta(delta)) if delta.price == Decimal::from(9)));
+
+    assert_eq!(decoder.decode(&missed_directory[..10]).unwrap_err(), "Truncated message");
+}
@@ -0,0 +1,78 @@
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderStatus, OrderType}, EngineError,
+    KillSwitchTarget, OrderCommand, OrderEvent, PlaceOrderCommand, RejectReason,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(user_id: Uuid, symbol: &str, side: OrderSide, price: i64) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(user_id, symbol, OrderType::Limit, side, Some(Decimal::from(price)), Decimal::ONE)
+}
+
+async fn place(
+    engine: &MatchingEngine,
+    user_id: Uuid,
+    symbol: &str,
+    side: OrderSide,
+    price: i64,
+) -> Result<Uuid, EngineError> {
+    let cmd = create_test_order_cmd(user_id, symbol, side, price);
+    let order_id = cmd.order_id;
+    engine.handle_command(OrderCommand::PlaceOrder(cmd)).await.map(|_| order_id)
+}
+
+#[tokio::test]
+async fn test_user_kill_switch_cancels_everything_and_blocks_new_orders() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let (rogue, other) = (Uuid::new_v4(), Uuid::new_v4());
+    let btc = place(&engine, rogue, "BTC/USDT", OrderSide::Buy, 100).await.unwrap();
+    let eth = place(&engine, rogue, "ETH/USDT", OrderSide::Sell, 10).await.unwrap();
+    let resting = place(&engine, other, "BTC/USDT", OrderSide::Buy, 99).await.unwrap();
+
+    let events = engine
+        .activate_kill_switch(KillSwitchTarget::User(rogue), "runaway algo")
+        .await
+        .unwrap();
+    let OrderEvent::KillSwitchActivated(activated) = &events[0] else {
+        panic!("expected KillSwitchActivated, got {:?}", events[0]);
+    };
+    assert_eq!(activated.reason, "runaway algo");
+    assert_eq!(activated.canceled_order_ids.len(), 2);
+    assert_eq!(events.iter().filter(|e| matches!(e, OrderEvent::OrderCanceled(_))).count(), 2);
+    for order_id in [btc, eth] {
+        assert_eq!(engine.get_order(order_id).unwrap().status, OrderStatus::Canceled);
+    }
+    assert!(engine.get_order(resting).unwrap().is_open());
+    assert!(engine.kill_switch_activated_at(&KillSwitchTarget::User(rogue)).is_some());
+
+    let result = place(&engine, rogue, "BTC/USDT", OrderSide::Buy, 100).await;
+    assert_eq!(
+        result.unwrap_err(),
+        EngineError::InvalidOrder(RejectReason::KillSwitchActive(KillSwitchTarget::User(rogue)))
+    );
+    place(&engine, other, "BTC/USDT", OrderSide::Buy, 98).await.unwrap();
+
+    let events = engine.release_kill_switch(KillSwitchTarget::User(rogue)).await.unwrap();
+    assert!(matches!(&events[..], [OrderEvent::KillSwitchReleased(_)]));
+    assert!(engine.release_kill_switch(KillSwitchTarget::User(rogue)).await.unwrap().is_empty());
+    place(&engine, rogue, "BTC/USDT", OrderSide::Buy, 100).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_symbol_kill_switch_clears_the_book() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+    place(&engine, alice, "BTC/USDT", OrderSide::Buy, 100).await.unwrap();
+    place(&engine, bob, "BTC/USDT", OrderSide::Sell, 110).await.unwrap();
+    let eth = place(&engine, alice, "ETH/USDT", OrderSide::Buy, 10).await.unwrap();
+
+    let symbol = KillSwitchTarget::Symbol("BTC/USDT".to_string());
+    engine.activate_kill_switch(symbol.clone(), "bad reference data").await.unwrap();
+    let book = engine.get_l2_snapshot("BTC/USDT", 10);
+    assert!(book.bids.is_empty() && book.asks.is_empty());
+    assert!(engine.get_order(eth).unwrap().is_open());
+
+    let result = place(&engine, bob, "BTC/USDT", OrderSide::Sell, 110).await;
+    assert_eq!(result.unwrap_err(), EngineError::InvalidOrder(RejectReason::KillSwitchActive(symbol)));
+    place(&engine, bob, "ETH/USDT", OrderSide::Sell, 11).await.unwrap();
+}
@@ -0,0 +1,94 @@
+use matching_engine::{
+    engine::MatchingEngine, types::{OrderSide, OrderType},
+    EventStore, KvEventStore, MemoryKv, OrderCommand, OrderEvent, PlaceOrderCommand, TimeRange,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(symbol: &str, side: OrderSide, price: Decimal) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), symbol, OrderType::Limit, side, Some(price), Decimal::ONE)
+}
+
+fn event_ids(events: &[OrderEvent]) -> Vec<Option<Uuid>> {
+    events.iter().map(|e| e.order_id()).collect()
+}
+
+#[tokio::test]
+async fn test_kv_store_indexes_by_order_and_symbol() {
+    let store = KvEventStore::open(MemoryKv::new()).unwrap();
+    let sell = create_test_order_cmd("BTC/USDT", OrderSide::Sell, Decimal::from(100));
+    let buy = create_test_order_cmd("BTC/USDT", OrderSide::Buy, Decimal::from(100));
+    let eth = create_test_order_cmd("ETH/USDT", OrderSide::Buy, Decimal::from(10));
+
+    let engine = MatchingEngine::new(Box::new(store));
+    for cmd in [&sell, &eth, &buy] {
+        engine.handle_command(OrderCommand::PlaceOrder(cmd.clone())).await.unwrap();
+    }
+    let store = engine.event_store();
+    let all = store.get_all_events().await.unwrap();
+    assert_eq!(all.first().and_then(|e| e.order_id()), Some(sell.order_id));
+
+    let sell_events = store.get_events(sell.order_id).await.unwrap();
+    assert!(!sell_events.is_empty());
+    assert!(sell_events.iter().all(|e| e.order_id() == Some(sell.order_id)));
+
+    let eth_events = store.get_events_by_symbol("ETH/USDT", TimeRange::all()).await.unwrap();
+    assert_eq!(event_ids(&eth_events), vec![Some(eth.order_id)]);
+    let btc_events = store.get_events_by_symbol("BTC/USDT", TimeRange::all()).await.unwrap();
+    assert_eq!(btc_events.len() + eth_events.len(), all.len());
+    assert!(store.get_events_by_symbol("BTC", TimeRange::all()).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_reopened_kv_store_continues_the_sequence() {
+    let mut written = Vec::new();
+    let mut orders = Vec::new();
+    for price in [99, 98] {
+        let cmd = create_test_order_cmd("BTC/USDT", OrderSide::Buy, Decimal::from(price));
+        let engine = MatchingEngine::new(Box::new(KvEventStore::open(MemoryKv::new()).unwrap()));
+        engine.handle_command(OrderCommand::PlaceOrder(cmd.clone())).await.unwrap();
+        written.push(engine.event_store().get_all_events().await.unwrap());
+        orders.push(cmd);
+    }
+
+    let store = KvEventStore::open(MemoryKv::new()).unwrap();
+    store.save_events(written[0].clone()).await.unwrap();
+    let store = KvEventStore::open(store.into_backend()).unwrap();
+    store.save_events(written[1].clone()).await.unwrap();
+    // Already saved before the reopen
+    store.save_events(written[0].clone()).await.unwrap();
+
+    let all = store.get_all_events().await.unwrap();
+    assert_eq!(event_ids(&all), event_ids(&written.concat()));
+    assert_eq!(store.get_events(orders[1].order_id).await.unwrap().len(), written[1].len());
+    let offsets: Vec<u64> = store.read_from(0, 10).await.unwrap().iter().map(|e| e.offset).collect();
+    assert_eq!(offsets, (0..all.len() as u64).collect::<Vec<_>>());
+}
+
+#[cfg(feature = "sled")]
+#[tokio::test]
+async fn test_sled_store_survives_a_restart() {
+    use matching_engine::SledKv;
+
+    let dir = std::env::temp_dir().join(format!("kv-{}", Uuid::new_v4()));
+    let sell = create_test_order_cmd("BTC/USDT", OrderSide::Sell, Decimal::from(100));
+    let buy = create_test_order_cmd("BTC/USDT", OrderSide::Buy, Decimal::from(100));
+    let written = {
+        let engine = MatchingEngine::new(Box::new(KvEventStore::open(SledKv::open(&dir).unwrap()).unwrap()));
+        engine.handle_command(OrderCommand::PlaceOrder(sell.clone())).await.unwrap();
+        engine.handle_command(OrderCommand::PlaceOrder(buy.clone())).await.unwrap();
+        engine.event_store().get_all_events().await.unwrap()
+    };
+
+    let store = KvEventStore::open(SledKv::open(&dir).unwrap()).unwrap();
+    assert_eq!(event_ids(&store.get_all_events().await.unwrap()), event_ids(&written));
+    let seller_events = store.get_events_by_user(sell.user_id, TimeRange::all()).await.unwrap();
+    assert!(seller_events.iter().any(|e| matches!(e, OrderEvent::OrderMatched(_))));
+    let eth = create_test_order_cmd("ETH/USDT", OrderSide::Buy, Decimal::from(10));
+    let engine = MatchingEngine::new(Box::new(store));
+    engine.handle_command(OrderCommand::PlaceOrder(eth)).await.unwrap();
+    let offsets: Vec<u64> = engine.event_store().read_from(0, 100).await.unwrap().iter().map(|e| e.offset).collect();
+    assert_eq!(offsets, (0..written.len() as u64 + 1).collect::<Vec<_>>());
+    drop(engine);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
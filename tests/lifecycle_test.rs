@@ -0,0 +1,192 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, symbols::SymbolConfig, types::{OrderSide, OrderType},
+    Clock, HaltedCommandPolicy, OrderCommand, OrderEvent, PlaceOrderCommand, SymbolState, TradingSchedule,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(side: OrderSide, price: i64) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), "BTC/USDT", OrderType::Limit, side, Some(Decimal::from(price)), Decimal::ONE)
+}
+
+/// A clock that only moves when told to.
+#[derive(Clone)]
+struct ManualClock(Arc<AtomicI64>);
+
+impl ManualClock {
+    fn advance(&self, by: Duration) {
+        self.0.fetch_add(by.num_milliseconds(), Ordering::SeqCst);
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        Utc.timestamp_millis_opt(self.0.load(Ordering::SeqCst)).unwrap()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        ManualClock::now(self)
+    }
+}
+
+fn create_engine(hour: u32) -> (MatchingEngine, ManualClock) {
+    let start = Utc.with_ymd_and_hms(2026, 1, 5, hour, 0, 0).unwrap();
+    let clock = ManualClock(Arc::new(AtomicI64::new(start.timestamp_millis())));
+    let engine = MatchingEngine::builder(Box::new(InMemoryEventStore::new()))
+        .clock(clock.clone())
+        .build();
+    (engine, clock)
+}
+
+async fn place(engine: &MatchingEngine, side: OrderSide, price: i64) -> Result<Vec<OrderEvent>, String> {
+    let cmd = create_test_order_cmd(side, price);
+    engine.handle_command(OrderCommand::PlaceOrder(cmd)).await.map_err(|e| e.to_string())
+}
+
+fn states(events: &[OrderEvent]) -> Vec<SymbolState> {
+    events
+        .iter()
+        .filter_map(|e| match e {
+            OrderEvent::SymbolStateChanged(e) => Some(e.state),
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_symbols_follow_their_trading_hours() {
+    let (engine, clock) = create_engine(8);
+    let engine = engine.with_halted_command_policy(HaltedCommandPolicy::Queue);
+    let time = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+    engine.symbols().register(SymbolConfig {
+        schedule: Some(TradingSchedule {
+            pre_open: Some(time(8, 30)),
+            ..TradingSchedule::daily(time(9, 0), time(17, 0))
+        }),
+        ..SymbolConfig::new("BTC/USDT")
+    }).unwrap();
+
+    // Orders before the pre-open are queued
+    let events = place(&engine, OrderSide::Buy, 101).await.unwrap();
+    assert_eq!(states(&events), vec![SymbolState::Closed]);
+    assert_eq!(engine.queued_command_count("BTC/USDT"), 1);
+
+    // The pre-open takes the queued order into the auction
+    clock.advance(Duration::minutes(45));
+    let events = place(&engine, OrderSide::Sell, 99).await.unwrap();
+    assert_eq!(states(&events), vec![SymbolState::AuctionOnly]);
+    assert_eq!(engine.queued_command_count("BTC/USDT"), 0);
+    assert_eq!(engine.get_open_orders_by_symbol("BTC/USDT").len(), 2);
+
+    clock.advance(Duration::minutes(15));
+    let events = engine.apply_trading_schedules().await.unwrap();
+    assert_eq!(states(&events), vec![SymbolState::Trading]);
+    assert!(events.iter().any(|e| matches!(e, OrderEvent::AuctionPriceDetermined(_))));
+    assert!(engine.get_open_orders_by_symbol("BTC/USDT").is_empty());
+
+    // A halt during the day stands until the close
+    engine.halt("BTC/USDT").await.unwrap();
+    assert!(engine.apply_trading_schedules().await.unwrap().is_empty());
+    assert_eq!(engine.symbol_state("BTC/USDT"), SymbolState::Halted);
+    clock.advance(Duration::hours(8));
+    let events = engine.apply_trading_schedules().await.unwrap();
+    assert_eq!(states(&events), vec![SymbolState::Closed]);
+}
+
+#[tokio::test]
+async fn test_overnight_sessions_open_on_trading_days_only() {
+    // Monday evening
+    let (engine, clock) = create_engine(20);
+    let time = |hour| NaiveTime::from_hms_opt(hour, 0, 0).unwrap();
+    engine.symbols().register(SymbolConfig {
+        schedule: Some(TradingSchedule {
+            trading_days: Some(vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri]),
+            holidays: vec![NaiveDate::from_ymd_opt(2026, 1, 7).unwrap()],
+            ..TradingSchedule::daily(time(22), time(6))
+        }),
+        ..SymbolConfig::new("BTC/USDT")
+    }).unwrap();
+
+    let (closed, trading) = (vec![SymbolState::Closed], vec![SymbolState::Trading]);
+    let steps = [
+        (0, &closed),
+        // Monday's session runs into Tuesday morning
+        (2, &trading),
+        (4, &vec![]),
+        (4, &closed),
+        (16, &trading),
+        (8, &closed),
+        // Wednesday is a holiday
+        (16, &vec![]),
+        (24, &trading),
+        (8, &closed),
+        (16, &trading),
+        (8, &closed),
+        // No sessions open over the weekend
+        (16, &vec![]),
+        (24, &vec![]),
+        (24, &trading),
+    ];
+    for (hours, expected) in steps {
+        clock.advance(Duration::hours(hours));
+        let events = engine.apply_trading_schedules().await.unwrap();
+        assert_eq!(&states(&events), expected, "at {}", clock.now());
+    }
+}
+
+#[test]
+fn test_schedules_are_checked_on_registration() {
+    let time = |hour| NaiveTime::from_hms_opt(hour, 0, 0).unwrap();
+    let register = |schedule| {
+        let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+        engine.symbols().register(SymbolConfig {
+            schedule: Some(schedule),
+            ..SymbolConfig::new("BTC/USDT")
+        })
+    };
+    assert!(register(TradingSchedule::daily(time(9), time(9))).is_err());
+    assert!(register(TradingSchedule {
+        trading_days: Some(Vec::new()),
+        ..TradingSchedule::daily(time(9), time(17))
+    })
+    .is_err());
+    for (pre_open, valid) in [(8, true), (9, false), (12, false)] {
+        let schedule = TradingSchedule {
+            pre_open: Some(time(pre_open)),
+            ..TradingSchedule::daily(time(9), time(17))
+        };
+        assert_eq!(register(schedule).is_ok(), valid, "pre-open at {}", pre_open);
+    }
+    // Overnight, the pre-open sits in the day
+    let overnight = TradingSchedule {
+        pre_open: Some(time(21)),
+        ..TradingSchedule::daily(time(22), time(6))
+    };
+    assert!(register(overnight).is_ok());
+}
+
+#[tokio::test]
+async fn test_symbols_open_at_listing_and_reject_everything_once_delisted() {
+    let (engine, clock) = create_engine(12);
+    engine.symbols().register(SymbolConfig {
+        listed_at: Some(clock.now() + Duration::hours(1)),
+        ..SymbolConfig::new("BTC/USDT")
+    }).unwrap();
+    let rejected = place(&engine, OrderSide::Buy, 100).await.unwrap_err();
+    assert_eq!(rejected, "BTC/USDT is Closed: command rejected");
+
+    clock.advance(Duration::hours(1));
+    let events = place(&engine, OrderSide::Buy, 100).await.unwrap();
+    assert_eq!(states(&events), vec![SymbolState::Trading]);
+
+    let events = engine.delist_symbol("BTC/USDT").await.unwrap();
+    assert!(matches!(&events[..], [OrderEvent::OrderCanceled(_), OrderEvent::SymbolStateChanged(e)]
+        if e.state == SymbolState::Delisted));
+    assert_eq!(place(&engine, OrderSide::Buy, 100).await.unwrap_err(), "BTC/USDT is delisted");
+    assert!(engine.resume("BTC/USDT").await.is_err());
+    assert!(engine.delist_symbol("BTC/USDT").await.is_err());
+}
@@ -0,0 +1,108 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType},
+    Clock, EngineConfig, EngineError, OrderCommand, OrderEvent, PlaceOrderCommand, RejectReason, UserLimits,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(user_id: Uuid, price: Decimal) -> OrderCommand {
+    OrderCommand::PlaceOrder(PlaceOrderCommand::new(
+        user_id,
+        "BTC/USDT",
+        OrderType::Limit,
+        OrderSide::Buy,
+        Some(price),
+        Decimal::ONE,
+    ))
+}
+
+/// A clock that only moves when told to.
+#[derive(Clone)]
+struct ManualClock(Arc<AtomicI64>);
+
+impl ManualClock {
+    fn advance(&self, by: Duration) {
+        self.0.fetch_add(by.num_milliseconds(), Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc.timestamp_millis_opt(self.0.load(Ordering::SeqCst)).unwrap()
+    }
+}
+
+fn create_engine(config: EngineConfig) -> (MatchingEngine, ManualClock) {
+    let clock = ManualClock(Arc::new(AtomicI64::new(1_700_000_000_000)));
+    let engine = MatchingEngine::builder(Box::new(InMemoryEventStore::new()))
+        .config(config)
+        .clock(clock.clone())
+        .build();
+    (engine, clock)
+}
+
+#[tokio::test]
+async fn test_order_rate_is_limited_and_refills() {
+    let (engine, clock) = create_engine(EngineConfig {
+        max_orders_per_second: Some(2),
+        ..EngineConfig::default()
+    });
+    let user = Uuid::new_v4();
+
+    engine.handle_command(create_test_order_cmd(user, Decimal::from(100))).await.unwrap();
+    engine.handle_command(create_test_order_cmd(user, Decimal::from(100))).await.unwrap();
+    let result = engine.handle_command(create_test_order_cmd(user, Decimal::from(100))).await;
+    assert_eq!(
+        result.unwrap_err(),
+        EngineError::InvalidOrder(RejectReason::RateLimitExceeded { max_orders_per_second: 2 })
+    );
+    let recorded = engine.event_store().get_all_events().await.unwrap();
+    assert!(recorded
+        .iter()
+        .any(|e| matches!(e, OrderEvent::RateLimitExceeded(r) if r.user_id == user)));
+
+    // Other users have their own bucket
+    engine
+        .handle_command(create_test_order_cmd(Uuid::new_v4(), Decimal::from(100)))
+        .await
+        .unwrap();
+
+    clock.advance(Duration::milliseconds(500));
+    engine.handle_command(create_test_order_cmd(user, Decimal::from(100))).await.unwrap();
+    assert!(engine.handle_command(create_test_order_cmd(user, Decimal::from(100))).await.is_err());
+}
+
+#[tokio::test]
+async fn test_user_override_replaces_global_limits() {
+    let (engine, _) = create_engine(EngineConfig {
+        max_orders_per_second: Some(1),
+        max_open_orders_per_user: Some(1),
+        ..EngineConfig::default()
+    });
+    let user = Uuid::new_v4();
+    engine.set_user_limits(
+        user,
+        UserLimits {
+            max_open_orders: Some(3),
+            max_orders_per_second: Some(10),
+        },
+    );
+
+    for _ in 0..3 {
+        engine.handle_command(create_test_order_cmd(user, Decimal::from(100))).await.unwrap();
+    }
+    let result = engine.handle_command(create_test_order_cmd(user, Decimal::from(100))).await;
+    assert_eq!(
+        result.unwrap_err(),
+        EngineError::InvalidOrder(RejectReason::TooManyOpenOrders { limit: 3 })
+    );
+
+    let other = Uuid::new_v4();
+    assert_eq!(engine.user_limits(other).max_orders_per_second, Some(1));
+    engine.handle_command(create_test_order_cmd(other, Decimal::from(100))).await.unwrap();
+    assert!(engine.handle_command(create_test_order_cmd(other, Decimal::from(100))).await.is_err());
+}
@@ -0,0 +1,99 @@
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType}, EngineError,
+    LeverageLimit, OrderCommand, OrderEvent, PlaceOrderCommand, RejectReason,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(user_id: Uuid, side: OrderSide, price: i64, quantity: i64) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(
+        user_id,
+        "BTC/USDT",
+        OrderType::Limit,
+        side,
+        Some(Decimal::from(price)),
+        Decimal::from(quantity),
+    )
+}
+
+async fn place(
+    engine: &MatchingEngine,
+    user_id: Uuid,
+    side: OrderSide,
+    price: i64,
+    quantity: i64,
+) -> Result<Vec<OrderEvent>, EngineError> {
+    let cmd = create_test_order_cmd(user_id, side, price, quantity);
+    engine.handle_command(OrderCommand::PlaceOrder(cmd)).await
+}
+
+fn margin_engine() -> MatchingEngine {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new())).with_margin();
+    let limit = LeverageLimit {
+        max_leverage: Decimal::from(5),
+        maintenance_margin_percent: Decimal::from(10),
+    };
+    engine.set_leverage_limit("BTC/USDT", limit).unwrap();
+    engine
+}
+
+#[tokio::test]
+async fn test_orders_adding_to_a_position_need_margin() {
+    let engine = margin_engine();
+    let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+    engine.deposit_collateral(alice, Decimal::from(1000)).unwrap();
+    engine.deposit_collateral(bob, Decimal::from(10_000)).unwrap();
+    place(&engine, bob, OrderSide::Sell, 100, 50).await.unwrap();
+
+    let result = place(&engine, alice, OrderSide::Buy, 100, 60).await;
+    assert_eq!(
+        result.unwrap_err(),
+        EngineError::InvalidOrder(RejectReason::InsufficientMargin {
+            required: Decimal::from(1200),
+            equity: Decimal::from(1000),
+        })
+    );
+    place(&engine, alice, OrderSide::Buy, 100, 50).await.unwrap();
+    let summary = engine.margin_summary(alice).unwrap();
+    assert_eq!(summary.equity, Decimal::from(1000));
+    assert_eq!(summary.initial_margin, Decimal::from(1000));
+    assert_eq!(summary.maintenance_margin, Decimal::from(500));
+    assert!(engine.withdraw_collateral(alice, Decimal::ONE).is_err());
+
+    // Reducing the position needs nothing more
+    place(&engine, alice, OrderSide::Sell, 101, 20).await.unwrap();
+    assert!(place(&engine, alice, OrderSide::Buy, 99, 1).await.is_err());
+}
+
+#[tokio::test]
+async fn test_breaching_maintenance_margin_liquidates_the_user() {
+    let engine = margin_engine();
+    let (alice, bob, carol) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    engine.deposit_collateral(alice, Decimal::from(1200)).unwrap();
+    for user_id in [bob, carol] {
+        engine.deposit_collateral(user_id, Decimal::from(10_000)).unwrap();
+    }
+    place(&engine, bob, OrderSide::Sell, 100, 50).await.unwrap();
+    place(&engine, alice, OrderSide::Buy, 100, 50).await.unwrap();
+    place(&engine, alice, OrderSide::Buy, 80, 1).await.unwrap();
+    place(&engine, carol, OrderSide::Buy, 84, 50).await.unwrap();
+
+    // Equity 450 against a maintenance margin of 425
+    assert!(engine.set_mark_price("BTC/USDT", Decimal::from(85)).await.unwrap().is_empty());
+
+    let events = engine.set_mark_price("BTC/USDT", Decimal::from(84)).await.unwrap();
+    let OrderEvent::LiquidationTriggered(liquidation) = &events[0] else {
+        panic!("expected LiquidationTriggered, got {:?}", events[0]);
+    };
+    assert_eq!(liquidation.user_id, alice);
+    assert_eq!(liquidation.equity, Decimal::from(400));
+    assert_eq!(liquidation.maintenance_margin, Decimal::from(420));
+    assert_eq!(liquidation.symbols, vec!["BTC/USDT".to_string()]);
+    assert!(events.iter().any(|e| matches!(e, OrderEvent::OrderCanceled(_))));
+    assert!(events.iter().any(|e| matches!(e, OrderEvent::OrderMatched(_))));
+
+    assert!(engine.get_open_orders(alice).is_empty());
+    assert!(engine.get_position(alice, "BTC/USDT").quantity().is_zero());
+    assert_eq!(engine.get_position(carol, "BTC/USDT").quantity(), Decimal::from(50));
+    assert_eq!(engine.margin_summary(alice).unwrap().equity, Decimal::from(400));
+}
@@ -0,0 +1,117 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType},
+    Clock, OrderCommand, PlaceOrderCommand,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(side: OrderSide, price: i64, quantity: i64) -> OrderCommand {
+    OrderCommand::PlaceOrder(PlaceOrderCommand::new(
+        Uuid::new_v4(),
+        "BTC/USDT",
+        OrderType::Limit,
+        side,
+        Some(Decimal::from(price)),
+        Decimal::from(quantity),
+    ))
+}
+
+#[derive(Clone)]
+struct ManualClock(Arc<AtomicI64>);
+
+impl ManualClock {
+    fn advance(&self, by: Duration) {
+        self.0.fetch_add(by.num_milliseconds(), Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc.timestamp_millis_opt(self.0.load(Ordering::SeqCst)).unwrap()
+    }
+}
+
+async fn trade(engine: &MatchingEngine, price: i64, quantity: i64) {
+    engine.handle_command(create_test_order_cmd(OrderSide::Sell, price, quantity)).await.unwrap();
+    engine.handle_command(create_test_order_cmd(OrderSide::Buy, price, quantity)).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_ticker_tracks_last_price_range_and_volume() {
+    let clock = ManualClock(Arc::new(AtomicI64::new(1_700_000_000_000)));
+    let engine = MatchingEngine::builder(Box::new(InMemoryEventStore::new())).clock(clock.clone()).build();
+
+    trade(&engine, 90, 5).await;
+    clock.advance(Duration::hours(12));
+    trade(&engine, 110, 1).await;
+    trade(&engine, 100, 2).await;
+    engine.handle_command(create_test_order_cmd(OrderSide::Buy, 95, 1)).await.unwrap();
+    engine.handle_command(create_test_order_cmd(OrderSide::Sell, 105, 1)).await.unwrap();
+
+    let ticker = engine.get_ticker("BTC/USDT");
+    assert_eq!(ticker.last_price, Some(Decimal::from(100)));
+    assert_eq!((ticker.best_bid, ticker.best_ask), (Some(Decimal::from(95)), Some(Decimal::from(105))));
+    assert_eq!((ticker.high_24h, ticker.low_24h), (Some(Decimal::from(110)), Some(Decimal::from(90))));
+    assert_eq!((ticker.volume_24h, ticker.trade_count_24h), (Decimal::from(8), 3));
+
+    // The first trade ages out of the window
+    clock.advance(Duration::hours(13));
+    let ticker = engine.get_ticker("BTC/USDT");
+    assert_eq!((ticker.high_24h, ticker.low_24h), (Some(Decimal::from(110)), Some(Decimal::from(100))));
+    assert_eq!((ticker.volume_24h, ticker.trade_count_24h), (Decimal::from(3), 2));
+    assert_eq!(ticker.last_price, Some(Decimal::from(100)));
+
+    clock.advance(Duration::hours(24));
+    let ticker = engine.get_ticker("BTC/USDT");
+    assert_eq!((ticker.high_24h, ticker.volume_24h), (None, Decimal::ZERO));
+    assert!(engine.get_ticker("ETH/USDT").last_price.is_none());
+}
+
+#[tokio::test]
+async fn test_recent_trades_are_newest_first_and_capped() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new())).with_trade_tape_capacity(2);
+    for price in [100, 101, 102] {
+        trade(&engine, price, 1).await;
+    }
+
+    let prices: Vec<Decimal> = engine.get_recent_trades("BTC/USDT", 10).iter().map(|t| t.price).collect();
+    assert_eq!(prices, vec![Decimal::from(102), Decimal::from(101)]);
+    assert_eq!(engine.get_recent_trades("BTC/USDT", 1).len(), 1);
+    assert!(engine.get_recent_trades("ETH/USDT", 10).is_empty());
+}
+
+#[tokio::test]
+async fn test_rolling_stats_over_a_window() {
+    let clock = ManualClock(Arc::new(AtomicI64::new(1_700_000_000_000)));
+    let minute = std::time::Duration::from_secs(60);
+    let engine = MatchingEngine::builder(Box::new(InMemoryEventStore::new()))
+        .clock(clock.clone())
+        .build()
+        .with_stats_windows(&[minute]);
+
+    trade(&engine, 100, 3).await;
+    clock.advance(Duration::seconds(30));
+    trade(&engine, 110, 1).await;
+    clock.advance(Duration::seconds(10));
+
+    let stats = engine.get_stats("BTC/USDT", minute).unwrap();
+    assert_eq!((stats.trade_count, stats.volume), (2, Decimal::from(4)));
+    assert_eq!(stats.vwap, Some(Decimal::new(1025, 1)));
+    // 100 stood for 30s and 110 for 10s
+    assert_eq!(stats.twap, Some(Decimal::new(1025, 1)));
+
+    // The first trade leaves the window
+    clock.advance(Duration::seconds(25));
+    let stats = engine.get_stats("BTC/USDT", minute).unwrap();
+    assert_eq!((stats.trade_count, stats.vwap, stats.twap), (1, Some(Decimal::from(110)), Some(Decimal::from(110))));
+
+    clock.advance(Duration::minutes(1));
+    let stats = engine.get_stats("BTC/USDT", minute).unwrap();
+    assert_eq!((stats.trade_count, stats.volume, stats.vwap), (0, Decimal::ZERO, None));
+    assert!(engine.get_stats("BTC/USDT", std::time::Duration::from_secs(5)).is_none());
+    assert_eq!(engine.get_stats("ETH/USDT", minute).unwrap().trade_count, 0);
+}
@@ -0,0 +1,55 @@
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderStatus, OrderType},
+    OrderEvent, PlaceOrderCommand,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(price: Decimal, quantity: Decimal, side: OrderSide, max_fills: Option<u32>) -> PlaceOrderCommand {
+    PlaceOrderCommand {
+        max_fills,
+        ..PlaceOrderCommand::new(Uuid::new_v4(), "BTC/USDT", OrderType::Limit, side, Some(price), quantity)
+    }
+}
+
+async fn engine_with_asks(prices: &[i64]) -> MatchingEngine {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    for price in prices {
+        engine
+            .handle_place_order(create_test_order_cmd(Decimal::from(*price), Decimal::ONE, OrderSide::Sell, None))
+            .await
+            .unwrap();
+    }
+    engine
+}
+
+fn fill_count(events: &[OrderEvent]) -> usize {
+    events.iter().filter(|e| matches!(e, OrderEvent::OrderMatched(_))).count()
+}
+
+#[tokio::test]
+async fn test_crossing_remainder_is_canceled_after_fill_limit() {
+    let engine = engine_with_asks(&[100, 100, 101]).await;
+    let buy = create_test_order_cmd(Decimal::from(102), Decimal::from(5), OrderSide::Buy, Some(2));
+    let events = engine.handle_place_order(buy.clone()).await.unwrap();
+
+    assert_eq!(fill_count(&events), 2);
+    let order = engine.get_order(buy.order_id).unwrap();
+    assert_eq!(order.filled_quantity, Decimal::from(2));
+    assert_eq!(order.status, OrderStatus::Canceled);
+    assert!(engine.get_order_book("BTC/USDT").unwrap().bids.is_empty());
+    assert!(engine.check_invariants("BTC/USDT").is_ok());
+}
+
+#[tokio::test]
+async fn test_non_crossing_remainder_rests() {
+    let engine = engine_with_asks(&[100, 101]).await;
+    let buy = create_test_order_cmd(Decimal::from(100), Decimal::from(3), OrderSide::Buy, Some(1));
+    let events = engine.handle_place_order(buy.clone()).await.unwrap();
+
+    assert_eq!(fill_count(&events), 1);
+    assert_eq!(engine.get_order(buy.order_id).unwrap().status, OrderStatus::PartiallyFilled);
+    let book = engine.get_order_book("BTC/USDT").unwrap();
+    assert_eq!(book.bids[0].price, Decimal::from(100));
+    assert_eq!(book.bids[0].quantity, Decimal::from(2));
+}
@@ -0,0 +1,43 @@
+use chrono::Duration;
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType},
+    MetricsStore, OrderCommand, PlaceOrderCommand,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(price: Decimal, quantity: Decimal, side: OrderSide) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), "BTC/USDT", OrderType::Limit, side, Some(price), quantity)
+}
+
+#[tokio::test]
+async fn test_metrics_snapshots_round_trip_through_store() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    for price in [100, 101, 102] {
+        let cmd = create_test_order_cmd(Decimal::from(price), Decimal::from(1), OrderSide::Buy);
+        engine.handle_command(OrderCommand::PlaceOrder(cmd)).await.unwrap();
+    }
+
+    let snapshot = engine.metrics_snapshot();
+    assert!(snapshot.commands_per_sec > 0.0);
+    assert_eq!(snapshot.book_sizes["BTC/USDT"].bid_levels, 3);
+    assert_eq!(snapshot.book_sizes["BTC/USDT"].resting_orders, 3);
+
+    // The next window starts empty
+    assert_eq!(engine.metrics_snapshot().commands_per_sec, 0.0);
+
+    let path = std::env::temp_dir().join(format!("metrics-{}.jsonl", Uuid::new_v4()));
+    let store = MetricsStore::open(&path).unwrap();
+    store.append(&snapshot).unwrap();
+
+    let from = snapshot.timestamp - Duration::seconds(1);
+    let to = snapshot.timestamp + Duration::seconds(1);
+    assert_eq!(store.query(from, to).unwrap(), vec![snapshot.clone()]);
+    assert!(store.query(to, to + Duration::seconds(1)).unwrap().is_empty());
+
+    let mut csv = Vec::new();
+    store.export_csv(from, to, &mut csv).unwrap();
+    assert_eq!(String::from_utf8(csv).unwrap().lines().count(), 2);
+
+    std::fs::remove_file(path).unwrap();
+}
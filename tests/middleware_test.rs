@@ -0,0 +1,62 @@
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType},
+    CommandMiddleware, EngineError, OrderCommand, PlaceOrderCommand, RejectReason,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(symbol: &str, quantity: Decimal) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), symbol, OrderType::Limit, OrderSide::Buy, Some(Decimal::from(100)), quantity)
+}
+
+/// Maps vendor symbology onto the engine's.
+struct SymbolRewrite;
+
+impl CommandMiddleware for SymbolRewrite {
+    fn pre_handle(&self, cmd: &mut OrderCommand) -> Result<(), RejectReason> {
+        if let OrderCommand::PlaceOrder(place) = cmd {
+            if place.symbol == "XBTUSDT" {
+                place.symbol = "BTC/USDT".to_string();
+            }
+        }
+        Ok(())
+    }
+}
+
+struct MaxQuantity(Decimal);
+
+impl CommandMiddleware for MaxQuantity {
+    fn pre_handle(&self, cmd: &mut OrderCommand) -> Result<(), RejectReason> {
+        match cmd {
+            OrderCommand::PlaceOrder(place) if place.quantity > self.0 => {
+                Err(RejectReason::PreTradeCheck(format!("{} exceeds the fat-finger limit", place.quantity)))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_middleware_can_rewrite_commands() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new())).with_middleware(SymbolRewrite);
+    let cmd = create_test_order_cmd("XBTUSDT", Decimal::ONE);
+    engine.handle_command(OrderCommand::PlaceOrder(cmd.clone())).await.unwrap();
+
+    assert_eq!(engine.get_order(cmd.order_id).unwrap().symbol, "BTC/USDT");
+    assert!(engine.get_order_book("XBTUSDT").is_none());
+}
+
+#[tokio::test]
+async fn test_middleware_rejection_stops_the_chain() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()))
+        .with_middleware(MaxQuantity(Decimal::from(10)))
+        .with_middleware(SymbolRewrite);
+    let cmd = create_test_order_cmd("XBTUSDT", Decimal::from(50));
+    let result = engine.handle_command(OrderCommand::PlaceOrder(cmd.clone())).await;
+
+    assert_eq!(
+        result.unwrap_err(),
+        EngineError::InvalidOrder(RejectReason::PreTradeCheck("50 exceeds the fat-finger limit".to_string()))
+    );
+    assert!(engine.get_order(cmd.order_id).is_none());
+}
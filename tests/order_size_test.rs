@@ -0,0 +1,57 @@
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, symbols::SymbolConfig, types::{OrderSide, OrderType},
+    EngineError, OrderCommand, PlaceOrderCommand, RejectReason,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(side: OrderSide, price: Decimal, quantity: Decimal) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), "BTC/USDT", OrderType::Limit, side, Some(price), quantity)
+}
+
+#[tokio::test]
+async fn test_orders_outside_the_symbol_size_limits_are_rejected() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    engine.symbols().register(SymbolConfig {
+        min_order_quantity: Some(Decimal::new(1, 2)),
+        min_notional: Some(Decimal::from(10)),
+        max_notional: Some(Decimal::from(100_000)),
+        ..SymbolConfig::new("BTC/USDT")
+    }).unwrap();
+    let place = |price: Decimal, quantity: Decimal| {
+        let cmd = create_test_order_cmd(OrderSide::Buy, price, quantity);
+        engine.handle_command(OrderCommand::PlaceOrder(cmd))
+    };
+
+    let result = place(Decimal::from(50_000), Decimal::new(1, 3)).await;
+    assert_eq!(
+        result.unwrap_err(),
+        EngineError::InvalidOrder(RejectReason::QuantityBelowMinimum {
+            quantity: Decimal::new(1, 3),
+            minimum: Decimal::new(1, 2),
+        })
+    );
+    let result = place(Decimal::from(500), Decimal::new(1, 2)).await;
+    assert_eq!(
+        result.unwrap_err(),
+        EngineError::InvalidOrder(RejectReason::NotionalBelowMinimum {
+            notional: Decimal::from(5),
+            minimum: Decimal::from(10),
+        })
+    );
+    let result = place(Decimal::from(50_000), Decimal::from(3)).await;
+    assert!(matches!(result, Err(EngineError::InvalidOrder(RejectReason::OrderNotionalTooLarge { .. }))));
+    place(Decimal::from(50_000), Decimal::from(2)).await.unwrap();
+
+    let ask = create_test_order_cmd(OrderSide::Sell, Decimal::from(50_000), Decimal::ONE);
+    engine.handle_command(OrderCommand::PlaceOrder(ask)).await.unwrap();
+
+    // Valued at the last trade price
+    let mut market = create_test_order_cmd(OrderSide::Sell, Decimal::ZERO, Decimal::from(3));
+    market.order_type = OrderType::Market;
+    market.price = None;
+    let result = engine.handle_command(OrderCommand::PlaceOrder(market.clone())).await;
+    assert!(matches!(result, Err(EngineError::InvalidOrder(RejectReason::OrderNotionalTooLarge { .. }))));
+    market.quantity = Decimal::ONE;
+    engine.handle_command(OrderCommand::PlaceOrder(market)).await.unwrap();
+}
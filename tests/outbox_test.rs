@@ -0,0 +1,136 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType},
+    EventPublisher, FileCursorStore, InMemoryCursorStore, OrderCommand, Outbox, PlaceOrderCommand,
+    StoredEvent,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(side: OrderSide, price: Decimal) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), "BTC/USDT", OrderType::Limit, side, Some(price), Decimal::ONE)
+}
+
+/// Records deliveries, failing the first `failures` batches.
+#[derive(Clone)]
+struct Recorder {
+    name: String,
+    delivered: Arc<Mutex<Vec<StoredEvent>>>,
+    failures: Arc<AtomicUsize>,
+}
+
+impl Recorder {
+    fn new(name: &str, failures: usize) -> Self {
+        Self {
+            name: name.to_string(),
+            delivered: Arc::new(Mutex::new(Vec::new())),
+            failures: Arc::new(AtomicUsize::new(failures)),
+        }
+    }
+
+    fn offsets(&self) -> Vec<u64> {
+        self.delivered.lock().unwrap().iter().map(|e| e.offset).collect()
+    }
+
+    async fn wait_for(&self, count: usize) {
+        for _ in 0..200 {
+            if self.delivered.lock().unwrap().len() >= count {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("{} got {} of {} events", self.name, self.delivered.lock().unwrap().len(), count);
+    }
+}
+
+#[async_trait]
+impl EventPublisher for Recorder {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn publish(&self, events: &[StoredEvent]) -> Result<(), String> {
+        if self.failures.load(Ordering::SeqCst) > 0 {
+            self.failures.fetch_sub(1, Ordering::SeqCst);
+            return Err("broker unavailable".to_string());
+        }
+        self.delivered.lock().unwrap().extend_from_slice(events);
+        Ok(())
+    }
+}
+
+/// Never finishes a delivery.
+struct Stuck;
+
+#[async_trait]
+impl EventPublisher for Stuck {
+    fn name(&self) -> &str {
+        "stuck"
+    }
+
+    async fn publish(&self, _events: &[StoredEvent]) -> Result<(), String> {
+        std::future::pending().await
+    }
+}
+
+async fn place(engine: &MatchingEngine, count: usize) {
+    for i in 0..count {
+        let cmd = create_test_order_cmd(OrderSide::Buy, Decimal::from(90 + i as i64));
+        engine.handle_command(OrderCommand::PlaceOrder(cmd)).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_failed_batches_are_retried_and_a_stuck_publisher_blocks_nothing() {
+    let engine = Arc::new(MatchingEngine::new(Box::new(InMemoryEventStore::new())));
+    let recorder = Recorder::new("bus", 2);
+    let tasks = Outbox::new(InMemoryCursorStore::new())
+        .with_publisher(recorder.clone())
+        .with_publisher(Stuck)
+        .with_batch_size(2)
+        .with_retry_backoff(Duration::from_millis(5))
+        .spawn(engine.clone());
+
+    place(&engine, 5).await;
+    let saved = engine.event_store().get_all_events().await.unwrap().len();
+    recorder.wait_for(saved).await;
+    assert_eq!(recorder.offsets(), (0..saved as u64).collect::<Vec<_>>());
+
+    for task in tasks {
+        task.abort();
+    }
+}
+
+#[tokio::test]
+async fn test_delivery_resumes_from_the_saved_cursor() {
+    let dir = std::env::temp_dir().join(format!("cursors-{}", Uuid::new_v4()));
+    let engine = Arc::new(MatchingEngine::new(Box::new(InMemoryEventStore::new())));
+    place(&engine, 3).await;
+
+    let first = Recorder::new("bus", 0);
+    let tasks = Outbox::new(FileCursorStore::open(&dir).unwrap())
+        .with_publisher(first.clone())
+        .spawn(engine.clone());
+    first.wait_for(3).await;
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    for task in tasks {
+        task.abort();
+    }
+
+    place(&engine, 2).await;
+    let second = Recorder::new("bus", 0);
+    let tasks = Outbox::new(FileCursorStore::open(&dir).unwrap())
+        .with_publisher(second.clone())
+        .spawn(engine.clone());
+    second.wait_for(2).await;
+    assert_eq!(second.offsets(), vec![3, 4]);
+
+    for task in tasks {
+        task.abort();
+    }
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,158 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType},
+    EngineError, EventStore, HaltScope, OrderCommand, OrderEvent, PersistenceFailurePolicy, PlaceOrderCommand,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+struct FlakyEventStore {
+    inner: InMemoryEventStore,
+    available: Arc<AtomicBool>,
+    /// PersistenceResumed events handed to the store, before the inner
+    /// store skips ids it already holds.
+    resumes_saved: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl EventStore for FlakyEventStore {
+    async fn save_events(&self, events: Vec<OrderEvent>) -> Result<(), String> {
+        if !self.available.load(Ordering::SeqCst) {
+            return Err("store offline".to_string());
+        }
+        let resumes = events.iter().filter(|e| matches!(e, OrderEvent::PersistenceResumed(_))).count();
+        self.resumes_saved.fetch_add(resumes, Ordering::SeqCst);
+        self.inner.save_events(events).await
+    }
+
+    async fn get_events(&self, order_id: Uuid) -> Result<Vec<OrderEvent>, String> {
+        self.inner.get_events(order_id).await
+    }
+
+    async fn get_all_events(&self) -> Result<Vec<OrderEvent>, String> {
+        self.inner.get_all_events().await
+    }
+}
+
+fn create_test_order_cmd(symbol: &str, price: Decimal, side: OrderSide) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), symbol, OrderType::Limit, side, Some(price), Decimal::from(1))
+}
+
+fn create_engine(scope: HaltScope) -> (MatchingEngine, Arc<AtomicBool>) {
+    let (engine, available, _) = create_counting_engine(scope);
+    (engine, available)
+}
+
+fn create_counting_engine(scope: HaltScope) -> (MatchingEngine, Arc<AtomicBool>, Arc<AtomicUsize>) {
+    let available = Arc::new(AtomicBool::new(true));
+    let resumes_saved = Arc::new(AtomicUsize::new(0));
+    let store = FlakyEventStore {
+        inner: InMemoryEventStore::new(),
+        available: available.clone(),
+        resumes_saved: resumes_saved.clone(),
+    };
+    let engine = MatchingEngine::new(Box::new(store)).with_persistence_policy(PersistenceFailurePolicy::Halt {
+        scope,
+        retry_queue_capacity: 100,
+    });
+    (engine, available, resumes_saved)
+}
+
+#[tokio::test]
+async fn test_default_policy_returns_store_error() {
+    let available = Arc::new(AtomicBool::new(false));
+    let store = FlakyEventStore { inner: InMemoryEventStore::new(), available, resumes_saved: Default::default() };
+    let engine = MatchingEngine::new(Box::new(store));
+
+    let result = engine.handle_place_order(create_test_order_cmd("BTC/USDT", Decimal::from(100), OrderSide::Buy)).await;
+    assert_eq!(result.unwrap_err(), EngineError::Rejected("store offline".to_string()));
+}
+
+#[tokio::test]
+async fn test_store_failure_halts_symbol_until_recovery() {
+    let (engine, available) = create_engine(HaltScope::Symbol);
+
+    available.store(false, Ordering::SeqCst);
+    let events = engine.handle_place_order(create_test_order_cmd("BTC/USDT", Decimal::from(100), OrderSide::Buy)).await.unwrap();
+    assert!(events.iter().any(|e| matches!(e, OrderEvent::PersistenceHalted(h) if h.symbol.as_deref() == Some("BTC/USDT"))));
+    assert!(engine.is_persistence_halted("BTC/USDT"));
+    assert!(!engine.is_persistence_halted("ETH/USDT"));
+
+    // Still offline: the halted symbol rejects new commands
+    let rejected = engine.handle_place_order(create_test_order_cmd("BTC/USDT", Decimal::from(101), OrderSide::Buy)).await;
+    assert!(rejected.is_err());
+
+    available.store(true, Ordering::SeqCst);
+    let events = engine.handle_place_order(create_test_order_cmd("BTC/USDT", Decimal::from(101), OrderSide::Buy)).await.unwrap();
+    assert!(matches!(events[0], OrderEvent::PersistenceResumed(_)));
+    assert!(!engine.is_persistence_halted("BTC/USDT"));
+    assert_eq!(engine.buffered_event_count().await, 0);
+}
+
+#[tokio::test]
+async fn test_resume_events_are_saved_once() {
+    let (engine, available, resumes_saved) = create_counting_engine(HaltScope::Symbol);
+
+    let (halting, resuming) = (
+        create_test_order_cmd("BTC/USDT", Decimal::from(100), OrderSide::Buy),
+        create_test_order_cmd("BTC/USDT", Decimal::from(101), OrderSide::Buy),
+    );
+    available.store(false, Ordering::SeqCst);
+    engine.handle_place_order(halting).await.unwrap();
+    available.store(true, Ordering::SeqCst);
+    let events = engine.handle_place_order(resuming).await.unwrap();
+
+    assert!(matches!(events[0], OrderEvent::PersistenceResumed(_)));
+    assert_eq!(resumes_saved.load(Ordering::SeqCst), 1);
+    let stored = engine.event_store().get_all_events().await.unwrap();
+    assert_eq!(stored.iter().filter(|e| matches!(e, OrderEvent::PersistenceResumed(_))).count(), 1);
+}
+
+#[tokio::test]
+async fn test_engine_scope_halts_every_symbol() {
+    let (engine, available) = create_engine(HaltScope::Engine);
+
+    available.store(false, Ordering::SeqCst);
+    let _ = engine.handle_place_order(create_test_order_cmd("BTC/USDT", Decimal::from(100), OrderSide::Buy)).await.unwrap();
+    assert!(engine.is_persistence_halted("ETH/USDT"));
+    assert!(engine.retry_persistence().await.is_err());
+
+    available.store(true, Ordering::SeqCst);
+    let resumed = engine.retry_persistence().await.unwrap();
+    assert_eq!(resumed.len(), 1);
+    assert!(!engine.is_persistence_halted("ETH/USDT"));
+}
+
+#[tokio::test]
+async fn test_rollback_policy_undoes_the_failed_command() {
+    let available = Arc::new(AtomicBool::new(true));
+    let store = FlakyEventStore {
+        inner: InMemoryEventStore::new(),
+        available: available.clone(),
+        resumes_saved: Default::default(),
+    };
+    let engine = MatchingEngine::new(Box::new(store)).with_persistence_policy(PersistenceFailurePolicy::Rollback);
+    let sell = create_test_order_cmd("BTC/USDT", Decimal::from(100), OrderSide::Sell);
+    engine.handle_command(OrderCommand::PlaceOrder(sell.clone())).await.unwrap();
+    let book = engine.get_order_book("BTC/USDT").unwrap();
+
+    available.store(false, Ordering::SeqCst);
+    let buy = create_test_order_cmd("BTC/USDT", Decimal::from(100), OrderSide::Buy);
+    let result = engine.handle_command(OrderCommand::PlaceOrder(buy.clone())).await;
+    assert_eq!(result.unwrap_err(), EngineError::Rejected("store offline".to_string()));
+
+    assert!(engine.get_order(buy.order_id).is_none());
+    assert_eq!(engine.get_order(sell.order_id).unwrap().filled_quantity, Decimal::ZERO);
+    assert_eq!(engine.get_order_book("BTC/USDT").unwrap().asks, book.asks);
+    assert!(engine.get_trades_by_symbol("BTC/USDT", None, 10).is_empty());
+    assert_eq!(engine.get_open_orders(buy.user_id).len(), 0);
+
+    // The same order goes through once the store is back
+    available.store(true, Ordering::SeqCst);
+    engine.handle_command(OrderCommand::PlaceOrder(buy.clone())).await.unwrap();
+    assert_eq!(engine.get_trades_by_symbol("BTC/USDT", None, 10).len(), 1);
+    assert!(engine.get_order_book("BTC/USDT").unwrap().asks.is_empty());
+}
@@ -0,0 +1,83 @@
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType}, EngineError,
+    ExposureLimits, OrderCommand, PlaceOrderCommand, Position, RejectReason,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(user_id: Uuid, side: OrderSide, price: Decimal, quantity: Decimal) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(user_id, "BTC/USDT", OrderType::Limit, side, Some(price), quantity)
+}
+
+async fn place(
+    engine: &MatchingEngine,
+    user_id: Uuid,
+    side: OrderSide,
+    price: i64,
+    quantity: i64,
+) -> Result<(), EngineError> {
+    let cmd = create_test_order_cmd(user_id, side, Decimal::from(price), Decimal::from(quantity));
+    engine.handle_command(OrderCommand::PlaceOrder(cmd)).await.map(|_| ())
+}
+
+#[tokio::test]
+async fn test_fills_update_both_sides_positions() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+    place(&engine, alice, OrderSide::Sell, 100, 3).await.unwrap();
+    place(&engine, bob, OrderSide::Buy, 100, 3).await.unwrap();
+    place(&engine, bob, OrderSide::Sell, 110, 1).await.unwrap();
+    place(&engine, alice, OrderSide::Buy, 110, 1).await.unwrap();
+
+    let alice_position = engine.get_position(alice, "BTC/USDT");
+    assert_eq!(alice_position.quantity(), Decimal::from(-2));
+    assert_eq!(
+        engine.get_position(bob, "BTC/USDT"),
+        Position {
+            bought: Decimal::from(3),
+            sold: Decimal::ONE,
+        }
+    );
+    assert_eq!(engine.get_positions(alice).len(), 1);
+    assert_eq!(engine.get_position(Uuid::new_v4(), "BTC/USDT"), Position::default());
+}
+
+#[tokio::test]
+async fn test_orders_beyond_exposure_limits_are_rejected() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    engine.set_exposure_limits(
+        "BTC/USDT",
+        ExposureLimits {
+            max_long: Some(Decimal::from(5)),
+            max_short: Some(Decimal::from(2)),
+        },
+    );
+    let (trader, counterparty, whale) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    place(&engine, counterparty, OrderSide::Sell, 100, 1).await.unwrap();
+    place(&engine, trader, OrderSide::Buy, 100, 3).await.unwrap();
+
+    // Long 1, with 2 more bid
+    let result = place(&engine, trader, OrderSide::Buy, 90, 3).await;
+    assert_eq!(
+        result.unwrap_err(),
+        EngineError::InvalidOrder(RejectReason::ExposureLimitExceeded {
+            side: OrderSide::Buy,
+            exposure: Decimal::from(6),
+            limit: Decimal::from(5),
+        })
+    );
+    place(&engine, trader, OrderSide::Buy, 90, 2).await.unwrap();
+    place(&engine, trader, OrderSide::Sell, 120, 3).await.unwrap();
+    assert!(place(&engine, trader, OrderSide::Sell, 120, 1).await.is_err());
+
+    engine.set_user_exposure_limits(
+        whale,
+        "BTC/USDT",
+        ExposureLimits {
+            max_long: None,
+            max_short: Some(Decimal::from(100)),
+        },
+    );
+    place(&engine, whale, OrderSide::Sell, 130, 50).await.unwrap();
+    assert!(place(&engine, whale, OrderSide::Buy, 80, 6).await.is_err());
+}
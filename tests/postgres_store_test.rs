@@ -0,0 +1,97 @@
+#![cfg(feature = "postgres")]
+
+use std::str::FromStr;
+
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType},
+    EventStore, OrderCommand, OrderEvent, PlaceOrderCommand, PostgresEventStore, TimeRange,
+};
+use rust_decimal::Decimal;
+use sqlx::postgres::{PgConnectOptions, PgPool, PgPoolOptions};
+use uuid::Uuid;
+
+fn create_test_order_cmd(symbol: &str, side: OrderSide, price: Decimal) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), symbol, OrderType::Limit, side, Some(price), Decimal::ONE)
+}
+
+fn event_ids(events: &[OrderEvent]) -> Vec<Option<Uuid>> {
+    events.iter().map(|e| e.order_id()).collect()
+}
+
+/// A pool on a schema of its own in the database at `POSTGRES_TEST_URL`,
+/// or None when that is unset and the test is skipped.
+async fn isolated_pool() -> Option<PgPool> {
+    let Ok(url) = std::env::var("POSTGRES_TEST_URL") else {
+        eprintln!("POSTGRES_TEST_URL is not set, skipping");
+        return None;
+    };
+    let schema = format!("events_{}", Uuid::new_v4().simple());
+    let admin = PgPool::connect(&url).await.unwrap();
+    sqlx::query(&format!("CREATE SCHEMA {}", schema)).execute(&admin).await.unwrap();
+    let options = PgConnectOptions::from_str(&url).unwrap().options([("search_path", schema.as_str())]);
+    Some(PgPoolOptions::new().connect_with(options).await.unwrap())
+}
+
+#[tokio::test]
+async fn test_postgres_store_indexes_by_order_symbol_and_user() {
+    let Some(pool) = isolated_pool().await else {
+        return;
+    };
+    let sell = create_test_order_cmd("BTC/USDT", OrderSide::Sell, Decimal::from(100));
+    let buy = create_test_order_cmd("BTC/USDT", OrderSide::Buy, Decimal::from(100));
+    let eth = create_test_order_cmd("ETH/USDT", OrderSide::Buy, Decimal::from(10));
+
+    let engine = MatchingEngine::new(Box::new(PostgresEventStore::open(pool).await.unwrap()));
+    for cmd in [&sell, &eth, &buy] {
+        engine.handle_command(OrderCommand::PlaceOrder(cmd.clone())).await.unwrap();
+    }
+    let store = engine.event_store();
+    let all = store.get_all_events().await.unwrap();
+    assert_eq!(all.first().and_then(|e| e.order_id()), Some(sell.order_id));
+
+    let sell_events = store.get_events(sell.order_id).await.unwrap();
+    assert!(!sell_events.is_empty());
+    assert!(sell_events.iter().all(|e| e.order_id() == Some(sell.order_id)));
+
+    let eth_events = store.get_events_by_symbol("ETH/USDT", TimeRange::all()).await.unwrap();
+    assert_eq!(event_ids(&eth_events), vec![Some(eth.order_id)]);
+    let btc_events = store.get_events_by_symbol("BTC/USDT", TimeRange::all()).await.unwrap();
+    assert_eq!(btc_events.len() + eth_events.len(), all.len());
+    let later = TimeRange::since(all.last().unwrap().timestamp() + chrono::Duration::seconds(1));
+    assert!(store.get_events_by_symbol("BTC/USDT", later).await.unwrap().is_empty());
+
+    // The seller's order is matched by the buyer's, so the match is theirs too
+    let seller_events = store.get_events_by_user(sell.user_id, TimeRange::all()).await.unwrap();
+    assert!(seller_events.iter().any(|e| matches!(e, OrderEvent::OrderMatched(_))));
+    let matched = store.get_events_by_type("OrderMatched", TimeRange::all()).await.unwrap();
+    assert!(!matched.is_empty());
+}
+
+#[tokio::test]
+async fn test_reopened_postgres_store_continues_the_chain() {
+    let Some(pool) = isolated_pool().await else {
+        return;
+    };
+    let mut written = Vec::new();
+    for price in [99, 98] {
+        let cmd = create_test_order_cmd("BTC/USDT", OrderSide::Buy, Decimal::from(price));
+        let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+        engine.handle_command(OrderCommand::PlaceOrder(cmd)).await.unwrap();
+        written.push(engine.event_store().get_all_events().await.unwrap());
+    }
+
+    let store = PostgresEventStore::open(pool.clone()).await.unwrap().with_hash_chain();
+    store.save_events(written[0].clone()).await.unwrap();
+    let store = PostgresEventStore::open(pool).await.unwrap().with_hash_chain();
+    store.save_events(written[1].clone()).await.unwrap();
+    // Already saved before the reopen
+    store.save_events(written[0].clone()).await.unwrap();
+
+    let all = store.get_all_events().await.unwrap();
+    assert_eq!(event_ids(&all), event_ids(&written.concat()));
+    let offsets: Vec<u64> = store.read_from(0, 10).await.unwrap().iter().map(|e| e.offset).collect();
+    assert_eq!(offsets, (0..all.len() as u64).collect::<Vec<_>>());
+    let report = store.verify_integrity(0..all.len() as u64).await.unwrap();
+    assert!(report.is_intact());
+    assert_eq!(report.checked, all.len() as u64);
+}
@@ -0,0 +1,66 @@
+use std::str::FromStr;
+
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType},
+    EngineError, PlaceOrderCommand, SymbolConfig,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn dec(value: &str) -> Decimal {
+    Decimal::from_str(value).unwrap()
+}
+
+fn create_test_order_cmd(price: Decimal, quantity: Decimal, side: OrderSide) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), "BTC/USDT", OrderType::Limit, side, Some(price), quantity)
+}
+
+fn create_engine() -> MatchingEngine {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    engine.symbols().register(SymbolConfig {
+        price_scale: Some(2),
+        quantity_scale: Some(3),
+        ..SymbolConfig::new("BTC/USDT")
+    }).unwrap();
+    engine
+}
+
+#[tokio::test]
+async fn test_prices_round_away_from_the_market() {
+    let engine = create_engine();
+    let buy = create_test_order_cmd(dec("100.019"), dec("1.23456"), OrderSide::Buy);
+    let sell = create_test_order_cmd(dec("101.011"), dec("1"), OrderSide::Sell);
+    engine.handle_place_order(buy.clone()).await.unwrap();
+    engine.handle_place_order(sell.clone()).await.unwrap();
+
+    let buy = engine.get_order(buy.order_id).unwrap();
+    assert_eq!(buy.price, Some(dec("100.01")));
+    assert_eq!(buy.quantity, dec("1.234"));
+    assert_eq!(engine.get_order(sell.order_id).unwrap().price, Some(dec("101.02")));
+}
+
+#[tokio::test]
+async fn test_quantity_below_increment_is_rejected() {
+    let engine = create_engine();
+    let result = engine
+        .handle_place_order(create_test_order_cmd(dec("100"), dec("0.0004"), OrderSide::Buy))
+        .await;
+    assert!(matches!(result, Err(EngineError::Rejected(_))));
+}
+
+#[tokio::test]
+async fn test_notional_overflow_is_a_typed_error() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let cmd = create_test_order_cmd(Decimal::MAX, dec("2"), OrderSide::Sell);
+    let result = engine.handle_place_order(cmd.clone()).await;
+
+    assert_eq!(
+        result.unwrap_err(),
+        EngineError::NotionalOverflow {
+            symbol: "BTC/USDT".to_string(),
+            price: Decimal::MAX,
+            quantity: dec("2"),
+        }
+    );
+    assert!(engine.get_order(cmd.order_id).is_none());
+}
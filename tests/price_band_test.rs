@@ -0,0 +1,81 @@
+use chrono::Utc;
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, symbols::SymbolConfig, types::{OrderSide, OrderType},
+    AmendOrderCommand, EngineError, OrderCommand, PlaceOrderCommand, RejectReason,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(side: OrderSide, price: Decimal, quantity: Decimal) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), "BTC/USDT", OrderType::Limit, side, Some(price), quantity)
+}
+
+fn banded_engine() -> MatchingEngine {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    engine.symbols().register(SymbolConfig {
+        price_band_percent: Some(Decimal::from(5)),
+        ..SymbolConfig::new("BTC/USDT")
+    }).unwrap();
+    engine
+}
+
+async fn place(engine: &MatchingEngine, side: OrderSide, price: i64) -> Result<PlaceOrderCommand, EngineError> {
+    let cmd = create_test_order_cmd(side, Decimal::from(price), Decimal::ONE);
+    engine.handle_command(OrderCommand::PlaceOrder(cmd.clone())).await.map(|_| cmd)
+}
+
+#[tokio::test]
+async fn test_band_follows_the_midpoint_then_the_last_trade() {
+    let engine = banded_engine();
+    // Nothing to measure from yet
+    place(&engine, OrderSide::Buy, 90).await.unwrap();
+    place(&engine, OrderSide::Sell, 110).await.unwrap();
+    assert_eq!(engine.reference_price("BTC/USDT"), Some(Decimal::from(100)));
+
+    let result = place(&engine, OrderSide::Buy, 94).await;
+    assert_eq!(
+        result.unwrap_err(),
+        EngineError::InvalidOrder(RejectReason::PriceOutOfBand {
+            price: Decimal::from(94),
+            reference_price: Decimal::from(100),
+            band_percent: Decimal::from(5),
+        })
+    );
+    place(&engine, OrderSide::Buy, 95).await.unwrap();
+
+    place(&engine, OrderSide::Sell, 105).await.unwrap();
+    place(&engine, OrderSide::Buy, 105).await.unwrap();
+    assert_eq!(engine.reference_price("BTC/USDT"), Some(Decimal::from(105)));
+    assert!(place(&engine, OrderSide::Sell, 111).await.is_err());
+    place(&engine, OrderSide::Sell, 110).await.unwrap();
+    place(&engine, OrderSide::Sell, 1000).await.unwrap_err();
+
+    let mut market = create_test_order_cmd(OrderSide::Buy, Decimal::ZERO, Decimal::ONE);
+    market.order_type = OrderType::Market;
+    market.price = None;
+    engine.handle_command(OrderCommand::PlaceOrder(market)).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_amendments_stay_within_the_band() {
+    let engine = banded_engine();
+    place(&engine, OrderSide::Sell, 100).await.unwrap();
+    place(&engine, OrderSide::Buy, 100).await.unwrap();
+    let bid = place(&engine, OrderSide::Buy, 98).await.unwrap();
+
+    let amend = |price: i64| {
+        OrderCommand::AmendOrder(AmendOrderCommand {
+            order_id: bid.order_id,
+            client_order_id: None,
+            user_id: bid.user_id,
+            symbol: bid.symbol.clone(),
+            new_price: Some(Decimal::from(price)),
+            new_quantity: None,
+            timestamp: Utc::now(),
+        })
+    };
+    let result = engine.handle_command(amend(90)).await;
+    assert!(matches!(result, Err(EngineError::InvalidOrder(RejectReason::PriceOutOfBand { .. }))));
+    engine.handle_command(amend(96)).await.unwrap();
+    assert_eq!(engine.get_order(bid.order_id).unwrap().price, Some(Decimal::from(96)));
+}
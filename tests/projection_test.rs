@@ -0,0 +1,110 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, spawn_projector, types::{OrderSide, OrderStatus, OrderType},
+    CancelOrderCommand, OpenOrdersView, OrderCommand, PlaceOrderCommand, Projector, SymbolTradeTapeView,
+    UserOrderHistoryView,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(user_id: Uuid, side: OrderSide, price: Decimal, quantity: Decimal) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(user_id, "BTC/USDT", OrderType::Limit, side, Some(price), quantity)
+}
+
+async fn place(engine: &MatchingEngine, user_id: Uuid, side: OrderSide, price: i64, quantity: i64) -> Uuid {
+    let cmd = create_test_order_cmd(user_id, side, Decimal::from(price), Decimal::from(quantity));
+    let order_id = cmd.order_id;
+    engine.handle_command(OrderCommand::PlaceOrder(cmd)).await.unwrap();
+    order_id
+}
+
+#[tokio::test]
+async fn test_open_orders_follow_the_event_stream() {
+    let engine = Arc::new(MatchingEngine::new(Box::new(InMemoryEventStore::new())));
+    let projector = Arc::new(Projector::new(OpenOrdersView::new()));
+    let task = spawn_projector(engine.clone(), projector.clone(), Duration::from_millis(10));
+    let (seller, buyer) = (Uuid::new_v4(), Uuid::new_v4());
+
+    let ask = place(&engine, seller, OrderSide::Sell, 100, 3).await;
+    let canceled = place(&engine, seller, OrderSide::Sell, 105, 1).await;
+    let bid = place(&engine, buyer, OrderSide::Buy, 100, 1).await;
+    engine
+        .handle_command(OrderCommand::CancelOrder(CancelOrderCommand {
+            order_id: canceled,
+            client_order_id: None,
+            user_id: seller,
+            symbol: "BTC/USDT".to_string(),
+            timestamp: Utc::now(),
+        }))
+        .await
+        .unwrap();
+
+    let logged = engine.event_store().read_from(0, 100).await.unwrap().len() as u64;
+    for _ in 0..200 {
+        if projector.offset() == logged {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    task.abort();
+
+    projector.read(|view| {
+        assert_eq!(view.len(), 1);
+        let open = view.get(ask).unwrap();
+        assert_eq!(open.filled_quantity, Decimal::ONE);
+        assert_eq!(open.status, OrderStatus::PartiallyFilled);
+        assert!(view.get(bid).is_none() && view.get(canceled).is_none());
+        assert_eq!(view.by_user(seller).len(), 1);
+        assert!(view.by_user(buyer).is_empty());
+        assert_eq!(view.by_symbol("BTC/USDT").len(), 1);
+    });
+}
+
+#[tokio::test]
+async fn test_rebuild_matches_incremental_catch_up() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let history = Projector::new(UserOrderHistoryView::new());
+    let (seller, buyer) = (Uuid::new_v4(), Uuid::new_v4());
+
+    let ask = place(&engine, seller, OrderSide::Sell, 100, 2).await;
+    history.catch_up(engine.event_store()).await.unwrap();
+    let bid = place(&engine, buyer, OrderSide::Buy, 100, 2).await;
+    place(&engine, buyer, OrderSide::Buy, 99, 1).await;
+    history.catch_up(engine.event_store()).await.unwrap();
+    let live = history.read(|view| view.history(buyer).into_iter().cloned().collect::<Vec<_>>());
+
+    assert_eq!(history.rebuild(engine.event_store()).await.unwrap(), history.offset() as usize);
+    history.read(|view| {
+        assert_eq!(view.history(buyer).into_iter().cloned().collect::<Vec<_>>(), live);
+        assert_eq!(view.get(ask).unwrap().status, OrderStatus::Filled);
+        assert_eq!(view.get(bid).unwrap().status, OrderStatus::Filled);
+        assert_eq!(view.history(seller).len(), 1);
+    });
+    assert_eq!(live.len(), 2);
+    assert_eq!(live[1].filled_quantity, Decimal::ZERO);
+}
+
+#[tokio::test]
+async fn test_trade_tape_keeps_the_latest_prints() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let tape = Projector::new(SymbolTradeTapeView::new(2));
+    let (seller, buyer) = (Uuid::new_v4(), Uuid::new_v4());
+    for price in [100, 101, 102] {
+        let ask = place(&engine, seller, OrderSide::Sell, price, 1).await;
+        let bid = place(&engine, buyer, OrderSide::Buy, price, 1).await;
+        tape.catch_up(engine.event_store()).await.unwrap();
+        tape.read(|view| {
+            let last = view.recent("BTC/USDT", 1)[0];
+            assert_eq!((last.taker_order_id, last.maker_order_id), (bid, ask));
+        });
+    }
+
+    tape.read(|view| {
+        let prices: Vec<Decimal> = view.recent("BTC/USDT", 10).iter().map(|e| e.price).collect();
+        assert_eq!(prices, vec![Decimal::from(102), Decimal::from(101)]);
+        assert!(view.recent("ETH/USDT", 10).is_empty());
+    });
+}
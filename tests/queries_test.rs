@@ -0,0 +1,85 @@
+use chrono::{Duration, Utc};
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderStatus, OrderType},
+    CancelOrderCommand, OrderCommand, OrderFilter, PlaceOrderCommand,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(user_id: Uuid, symbol: &str, price: Decimal, side: OrderSide) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(user_id, symbol, OrderType::Limit, side, Some(price), Decimal::ONE)
+}
+
+#[tokio::test]
+async fn test_open_orders_track_fills_and_cancels() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let alice = Uuid::new_v4();
+    let bob = Uuid::new_v4();
+
+    let btc_bid = create_test_order_cmd(alice, "BTC/USDT", Decimal::from(100), OrderSide::Buy);
+    let eth_bid = create_test_order_cmd(alice, "ETH/USDT", Decimal::from(10), OrderSide::Buy);
+    let btc_ask = create_test_order_cmd(alice, "BTC/USDT", Decimal::from(110), OrderSide::Sell);
+    for cmd in [&btc_bid, &eth_bid, &btc_ask] {
+        engine.handle_place_order(cmd.clone()).await.unwrap();
+    }
+    assert_eq!(engine.get_open_orders(alice).len(), 3);
+    assert_eq!(engine.get_open_orders_by_symbol("BTC/USDT").len(), 2);
+
+    // Bob fills the BTC bid, then Alice cancels the ETH bid
+    engine
+        .handle_place_order(create_test_order_cmd(bob, "BTC/USDT", Decimal::from(100), OrderSide::Sell))
+        .await
+        .unwrap();
+    engine
+        .handle_command(OrderCommand::CancelOrder(CancelOrderCommand {
+            order_id: eth_bid.order_id,
+            client_order_id: None,
+            user_id: alice,
+            symbol: eth_bid.symbol.clone(),
+            timestamp: Utc::now(),
+        }))
+        .await
+        .unwrap();
+
+    let open: Vec<Uuid> = engine.get_open_orders(alice).iter().map(|o| o.id).collect();
+    assert_eq!(open, vec![btc_ask.order_id]);
+    assert!(engine.get_open_orders(bob).is_empty());
+    assert!(engine.get_open_orders_by_symbol("ETH/USDT").is_empty());
+}
+
+#[tokio::test]
+async fn test_filtered_orders() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let user_id = Uuid::new_v4();
+    let started = Utc::now();
+
+    let mut placed = Vec::new();
+    for (i, side) in [OrderSide::Buy, OrderSide::Sell, OrderSide::Buy].into_iter().enumerate() {
+        let mut cmd = create_test_order_cmd(user_id, "BTC/USDT", Decimal::from(100 + 20 * i as i64), side);
+        cmd.timestamp = started + Duration::seconds(i as i64);
+        engine.handle_place_order(cmd.clone()).await.unwrap();
+        placed.push(cmd.order_id);
+    }
+    // The last buy at 140 crosses the ask at 120
+    let filled = engine.get_orders_filtered(&OrderFilter {
+        symbol: Some("BTC/USDT".to_string()),
+        statuses: Some(vec![OrderStatus::Filled]),
+        ..Default::default()
+    });
+    assert_eq!(filled.iter().map(|o| o.id).collect::<Vec<_>>(), vec![placed[1], placed[2]]);
+
+    let buys = engine.get_orders_filtered(&OrderFilter {
+        user_id: Some(user_id),
+        side: Some(OrderSide::Buy),
+        created_from: Some(started + Duration::seconds(1)),
+        ..Default::default()
+    });
+    assert_eq!(buys.iter().map(|o| o.id).collect::<Vec<_>>(), vec![placed[2]]);
+
+    let open = engine.get_orders_filtered(&OrderFilter {
+        user_id: Some(user_id),
+        statuses: Some(vec![OrderStatus::Active]),
+        ..Default::default()
+    });
+    assert_eq!(open.iter().map(|o| o.id).collect::<Vec<_>>(), vec![placed[0]]);
+}
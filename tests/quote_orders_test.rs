@@ -0,0 +1,90 @@
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, symbols::SymbolConfig,
+    types::{OrderSide, OrderStatus, OrderType}, EngineError, OrderCommand, OrderEvent, PlaceOrderCommand, RejectReason,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(side: OrderSide, price: Option<Decimal>, quantity: Decimal) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(
+        Uuid::new_v4(),
+        "BTC/USDT",
+        if price.is_some() { OrderType::Limit } else { OrderType::Market },
+        side,
+        price,
+        quantity,
+    )
+}
+
+fn quote_buy(quote_quantity: i64) -> PlaceOrderCommand {
+    PlaceOrderCommand {
+        quote_quantity: Some(Decimal::from(quote_quantity)),
+        ..create_test_order_cmd(OrderSide::Buy, None, Decimal::ZERO)
+    }
+}
+
+async fn engine_with_asks(config: SymbolConfig) -> MatchingEngine {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    engine.symbols().register(config).unwrap();
+    for (price, quantity) in [(100, 1), (110, 2), (120, 5)] {
+        let ask = create_test_order_cmd(OrderSide::Sell, Some(Decimal::from(price)), Decimal::from(quantity));
+        engine.handle_command(OrderCommand::PlaceOrder(ask)).await.unwrap();
+    }
+    engine
+}
+
+fn fills(events: &[OrderEvent]) -> Vec<(Decimal, Decimal)> {
+    events
+        .iter()
+        .filter_map(|e| match e {
+            OrderEvent::OrderMatched(e) => Some((e.price, e.quantity)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_market_buys_by_quote_spend_at_most_the_quote_quantity() {
+    let engine = engine_with_asks(SymbolConfig {
+        quantity_scale: Some(2),
+        ..SymbolConfig::new("BTC/USDT")
+    })
+    .await;
+    let cmd = quote_buy(300);
+    let events = engine.handle_command(OrderCommand::PlaceOrder(cmd.clone())).await.unwrap();
+    // The 200 left after the first level buys 1.81 at 110, cut to two places
+    assert_eq!(
+        fills(&events),
+        vec![(Decimal::from(100), Decimal::ONE), (Decimal::from(110), Decimal::new(181, 2))]
+    );
+    let order = engine.get_order(cmd.order_id).unwrap();
+    assert_eq!((order.quantity, order.status), (Decimal::new(281, 2), OrderStatus::Filled));
+
+    // Without a quantity scale the quantity is cut to eight places
+    let engine = engine_with_asks(SymbolConfig::new("BTC/USDT")).await;
+    let events = engine.handle_command(OrderCommand::PlaceOrder(quote_buy(300))).await.unwrap();
+    let spent: Decimal = fills(&events).iter().map(|(price, quantity)| price * quantity).sum();
+    assert_eq!(fills(&events)[1].1, Decimal::new(181818181, 8));
+    assert!(spent <= Decimal::from(300) && spent > Decimal::new(29999, 2));
+}
+
+#[tokio::test]
+async fn test_quote_quantities_are_only_accepted_on_market_buys_that_can_fill() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let result = engine.handle_command(OrderCommand::PlaceOrder(quote_buy(300))).await;
+    assert_eq!(
+        result.unwrap_err(),
+        EngineError::InvalidOrder(RejectReason::QuoteQuantityUnfillable(Decimal::from(300)))
+    );
+
+    let limit = PlaceOrderCommand {
+        quote_quantity: Some(Decimal::from(300)),
+        ..create_test_order_cmd(OrderSide::Buy, Some(Decimal::from(100)), Decimal::ONE)
+    };
+    let result = engine.handle_command(OrderCommand::PlaceOrder(limit)).await;
+    assert_eq!(result.unwrap_err(), EngineError::InvalidOrder(RejectReason::QuoteQuantityNotMarketBuy));
+
+    let engine = engine_with_asks(SymbolConfig::new("BTC/USDT")).await;
+    let result = engine.handle_command(OrderCommand::PlaceOrder(quote_buy(0))).await;
+    assert_eq!(result.unwrap_err(), EngineError::InvalidOrder(RejectReason::NonPositiveQuantity(Decimal::ZERO)));
+}
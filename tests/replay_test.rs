@@ -0,0 +1,78 @@
+use chrono::Utc;
+use matching_engine::{
+    CancelOrderCommand, InMemoryEventStore, MatchingEngine, OrderCommand, OrderSide, OrderType, PlaceOrderCommand,
+    ReplayOutput,
+};
+use rust_decimal::Decimal;
+use serde_json::Value;
+use uuid::Uuid;
+
+fn create_test_order_cmd(side: OrderSide, price: Decimal, quantity: Decimal) -> OrderCommand {
+    OrderCommand::PlaceOrder(PlaceOrderCommand::new(
+        Uuid::new_v4(),
+        "BTC/USDT",
+        OrderType::Limit,
+        side,
+        Some(price),
+        quantity,
+    ))
+}
+
+fn jsonl(commands: &[OrderCommand]) -> String {
+    commands.iter().map(|c| serde_json::to_string(c).unwrap() + "\n").collect()
+}
+
+fn records(output: &[u8]) -> Vec<Value> {
+    String::from_utf8_lossy(output).lines().map(|line| serde_json::from_str(line).unwrap()).collect()
+}
+
+#[tokio::test]
+async fn test_replay_writes_events_and_trades_per_command() {
+    let commands = [
+        create_test_order_cmd(OrderSide::Buy, Decimal::from(100), Decimal::from(2)),
+        create_test_order_cmd(OrderSide::Sell, Decimal::from(100), Decimal::from(1)),
+        OrderCommand::CancelOrder(CancelOrderCommand {
+            order_id: Uuid::new_v4(),
+            client_order_id: None,
+            user_id: Uuid::new_v4(),
+            symbol: "BTC/USDT".to_string(),
+            timestamp: Utc::now(),
+        }),
+    ];
+    let input = jsonl(&commands) + "\n";
+
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let mut output = Vec::new();
+    let summary = engine
+        .replay_jsonl(input.as_bytes(), &mut output, ReplayOutput::EventsAndTrades)
+        .await
+        .unwrap();
+    assert_eq!((summary.commands, summary.trades, summary.errors), (3, 1, 1));
+
+    let records = records(&output);
+    assert_eq!(records.len(), summary.events + 2);
+    let trade = records.iter().position(|r| r.get("Trade").is_some()).unwrap();
+    assert!(records[trade - 1].get("OrderMatched").is_some());
+    assert_eq!(records[trade]["Trade"]["price"], "100");
+    assert_eq!(records.last().unwrap()["Error"]["line"], 3);
+}
+
+#[tokio::test]
+async fn test_replay_of_trades_only_and_bad_lines() {
+    let commands = [
+        create_test_order_cmd(OrderSide::Sell, Decimal::from(101), Decimal::from(1)),
+        create_test_order_cmd(OrderSide::Buy, Decimal::from(102), Decimal::from(1)),
+    ];
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let mut output = Vec::new();
+    let summary = engine
+        .replay_jsonl(jsonl(&commands).as_bytes(), &mut output, ReplayOutput::Trades)
+        .await
+        .unwrap();
+    assert_eq!((summary.events, summary.trades), (0, 1));
+    assert_eq!(records(&output)[0]["Trade"]["price"], "101");
+
+    let input = jsonl(&commands[..1]) + "not a command\n";
+    let err = engine.replay_jsonl(input.as_bytes(), Vec::new(), ReplayOutput::Events).await.unwrap_err();
+    assert!(err.starts_with("Line 2:"));
+}
@@ -0,0 +1,197 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType},
+    LocalReplica, OrderCommand, OrderEvent, PlaceOrderCommand, ReplicaLink, ReplicationRecord,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(side: OrderSide, price: Decimal) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), "BTC/USDT", OrderType::Limit, side, Some(price), Decimal::ONE)
+}
+
+async fn place(engine: &MatchingEngine, side: OrderSide, price: i64) -> Result<Vec<OrderEvent>, String> {
+    let cmd = create_test_order_cmd(side, Decimal::from(price));
+    engine.handle_command(OrderCommand::PlaceOrder(cmd)).await.map_err(|e| e.to_string())
+}
+
+async fn saved_ids(engine: &MatchingEngine) -> Vec<Uuid> {
+    engine.event_store().get_all_events().await.unwrap().iter().map(|e| e.event_id()).collect()
+}
+
+fn standby() -> Arc<MatchingEngine> {
+    Arc::new(MatchingEngine::new(Box::new(InMemoryEventStore::new())).as_follower())
+}
+
+#[tokio::test]
+async fn test_follower_mirrors_leader() {
+    let follower = standby();
+    let leader = MatchingEngine::new(Box::new(InMemoryEventStore::new()))
+        .with_replica(Box::new(LocalReplica::new(follower.clone())));
+
+    for (side, price) in [(OrderSide::Sell, 100), (OrderSide::Sell, 101), (OrderSide::Buy, 100), (OrderSide::Buy, 99)] {
+        place(&leader, side, price).await.unwrap();
+    }
+    leader.flush_replication().await.unwrap();
+
+    assert_eq!(follower.last_applied_sequence().await, 4);
+    assert_eq!(saved_ids(&follower).await, saved_ids(&leader).await);
+    let leader_book = leader.get_order_book("BTC/USDT").unwrap();
+    let follower_book = follower.get_order_book("BTC/USDT").unwrap();
+    assert_eq!(format!("{:?}", follower_book.bids), format!("{:?}", leader_book.bids));
+    assert_eq!(format!("{:?}", follower_book.asks), format!("{:?}", leader_book.asks));
+}
+
+#[tokio::test]
+async fn test_follower_rejects_commands_until_promoted() {
+    let follower = standby();
+    let leader = MatchingEngine::new(Box::new(InMemoryEventStore::new()))
+        .with_replica(Box::new(LocalReplica::new(follower.clone())));
+    place(&leader, OrderSide::Sell, 100).await.unwrap();
+    leader.flush_replication().await.unwrap();
+
+    assert!(place(&follower, OrderSide::Buy, 100).await.unwrap_err().contains("follower"));
+    assert_eq!(follower.promote().await, 1);
+    assert!(!follower.is_following());
+
+    let events = place(&follower, OrderSide::Buy, 100).await.unwrap();
+    assert!(events.iter().any(|e| matches!(e, OrderEvent::OrderMatched(_))));
+}
+
+#[tokio::test]
+async fn test_promoted_follower_knows_the_leaders_trades() {
+    let follower = standby();
+    let leader = MatchingEngine::new(Box::new(InMemoryEventStore::new()))
+        .with_replica(Box::new(LocalReplica::new(follower.clone())));
+    place(&leader, OrderSide::Sell, 100).await.unwrap();
+    place(&leader, OrderSide::Buy, 100).await.unwrap();
+    leader.flush_replication().await.unwrap();
+
+    let trades = leader.get_trades_by_symbol("BTC/USDT", None, 10);
+    let follower_trades = follower.get_trades_by_symbol("BTC/USDT", None, 10);
+    assert_eq!(format!("{:?}", follower_trades), format!("{:?}", trades));
+    follower.promote().await;
+    let events = follower.bust_trade(trades[0].id, "erroneous").await.unwrap();
+    assert!(matches!(&events[..], [OrderEvent::TradeBusted(e)] if e.trade_id == trades[0].id));
+}
+
+/// Keeps the records it is sent.
+#[derive(Default)]
+struct RecordingReplica {
+    records: Arc<Mutex<Vec<ReplicationRecord>>>,
+}
+
+#[async_trait]
+impl ReplicaLink for RecordingReplica {
+    async fn replicate(&self, record: &ReplicationRecord) -> Result<(), String> {
+        self.records.lock().unwrap().push(record.clone());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_apply_skips_repeats_and_refuses_gaps() {
+    let replica = RecordingReplica::default();
+    let records = replica.records.clone();
+    let leader = MatchingEngine::new(Box::new(InMemoryEventStore::new())).with_replica(Box::new(replica));
+    place(&leader, OrderSide::Sell, 100).await.unwrap();
+    leader.flush_replication().await.unwrap();
+    let record = records.lock().unwrap()[0].clone();
+
+    let follower = standby();
+    follower.apply_replicated(record.clone()).await.unwrap();
+    follower.apply_replicated(record.clone()).await.unwrap();
+    let skipping = ReplicationRecord { sequence: 3, ..record };
+    assert!(follower.apply_replicated(skipping).await.unwrap_err().contains("gap"));
+    assert_eq!(follower.last_applied_sequence().await, 1);
+    assert_eq!(follower.get_order_book("BTC/USDT").unwrap().asks.len(), 1);
+}
+
+/// Refuses records while `down` is set.
+struct FlakyReplica {
+    follower: LocalReplica,
+    down: Arc<AtomicBool>,
+}
+
+#[async_trait]
+impl ReplicaLink for FlakyReplica {
+    async fn replicate(&self, record: &ReplicationRecord) -> Result<(), String> {
+        if self.down.load(Ordering::SeqCst) {
+            return Err("link down".to_string());
+        }
+        self.follower.replicate(record).await
+    }
+}
+
+#[tokio::test]
+async fn test_refused_records_are_shipped_again() {
+    let follower = standby();
+    let down = Arc::new(AtomicBool::new(true));
+    let leader = MatchingEngine::new(Box::new(InMemoryEventStore::new())).with_replica(Box::new(FlakyReplica {
+        follower: LocalReplica::new(follower.clone()),
+        down: down.clone(),
+    }));
+    place(&leader, OrderSide::Sell, 100).await.unwrap();
+    place(&leader, OrderSide::Sell, 101).await.unwrap();
+    assert_eq!(leader.replication_backlog(), 2);
+    assert!(leader.flush_replication().await.is_err());
+    assert_eq!(leader.replication_error().as_deref(), Some("link down"));
+    assert_eq!(follower.last_applied_sequence().await, 0);
+
+    down.store(false, Ordering::SeqCst);
+    leader.flush_replication().await.unwrap();
+    assert_eq!(leader.replication_backlog(), 0);
+    assert_eq!(leader.replication_error(), None);
+    assert_eq!(follower.last_applied_sequence().await, 2);
+    assert_eq!(saved_ids(&follower).await, saved_ids(&leader).await);
+}
+
+#[tokio::test]
+async fn test_follower_stops_at_a_record_it_cannot_reproduce() {
+    let follower = standby();
+    let order = create_test_order_cmd(OrderSide::Sell, Decimal::from(100));
+    let leader = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let events = leader.handle_command(OrderCommand::PlaceOrder(order.clone())).await.unwrap();
+    // The leader says it rejected an order the follower accepts
+    let record = ReplicationRecord {
+        sequence: 1,
+        command: OrderCommand::PlaceOrder(order.clone()),
+        events: Vec::new(),
+        draws: Default::default(),
+        rejected: true,
+    };
+
+    assert!(follower.apply_replicated(record).await.unwrap_err().contains("diverged at sequence 1"));
+    assert!(follower.replication_divergence().is_some());
+    let record = ReplicationRecord {
+        sequence: 1,
+        command: OrderCommand::PlaceOrder(order),
+        events,
+        draws: Default::default(),
+        rejected: false,
+    };
+    assert!(follower.apply_replicated(record).await.unwrap_err().contains("diverged"));
+    assert_eq!(follower.promote().await, 0);
+}
+
+#[tokio::test]
+async fn test_commands_are_refused_while_the_backlog_is_full() {
+    let follower = standby();
+    let down = Arc::new(AtomicBool::new(true));
+    let leader = MatchingEngine::new(Box::new(InMemoryEventStore::new()))
+        .with_replica(Box::new(FlakyReplica {
+            follower: LocalReplica::new(follower.clone()),
+            down: down.clone(),
+        }))
+        .with_replication_backlog_limit(2);
+    place(&leader, OrderSide::Sell, 100).await.unwrap();
+    place(&leader, OrderSide::Sell, 101).await.unwrap();
+    assert!(place(&leader, OrderSide::Sell, 102).await.unwrap_err().contains("backlog is full"));
+
+    down.store(false, Ordering::SeqCst);
+    leader.flush_replication().await.unwrap();
+    place(&leader, OrderSide::Sell, 102).await.unwrap();
+}
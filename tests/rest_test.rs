@@ -0,0 +1,167 @@
+#![cfg(feature = "server")]
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use matching_engine::{engine::MatchingEngine, event_store::InMemoryEventStore, serve_rest, StaticTokens};
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use uuid::Uuid;
+
+/// Starts a server where each of `users` authenticates with its own id as
+/// the token.
+async fn start(users: &[Uuid]) -> SocketAddr {
+    let engine = Arc::new(MatchingEngine::new(Box::new(InMemoryEventStore::new())));
+    let tokens = users.iter().fold(StaticTokens::new(), |tokens, user| tokens.with_token(user.to_string(), *user));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(serve_rest(engine, listener, Arc::new(tokens)));
+    addr
+}
+
+async fn request(addr: SocketAddr, method: &str, target: &str, body: Option<Value>) -> (u16, Value) {
+    let user = body.as_ref().and_then(|body| body["user_id"].as_str()).map(str::to_string);
+    request_as(addr, user.as_deref(), method, target, body).await
+}
+
+async fn request_as(
+    addr: SocketAddr,
+    token: Option<&str>,
+    method: &str,
+    target: &str,
+    body: Option<Value>,
+) -> (u16, Value) {
+    let body = body.map(|body| body.to_string()).unwrap_or_default();
+    let authorization = token.map(|token| format!("Authorization: Bearer {}\r\n", token)).unwrap_or_default();
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let request = format!(
+        "{} {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n{}Content-Length: {}\r\n\r\n{}",
+        method,
+        target,
+        authorization,
+        body.len(),
+        body
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+    (status, serde_json::from_str(body).unwrap_or(Value::Null))
+}
+
+fn order(user_id: Uuid, side: &str, price: &str) -> Value {
+    json!({
+        "user_id": user_id,
+        "symbol": "BTC/USDT",
+        "order_type": "Limit",
+        "side": side,
+        "price": price,
+        "quantity": "2"
+    })
+}
+
+#[tokio::test]
+async fn test_orders_are_placed_listed_and_canceled() {
+    let user = Uuid::new_v4();
+    let addr = start(&[user]).await;
+    let token = user.to_string();
+
+    let (status, events) = request(addr, "POST", "/orders", Some(order(user, "Buy", "100"))).await;
+    assert_eq!(status, 201);
+    let order_id = events[0]["OrderPlaced"]["order_id"].as_str().unwrap().to_string();
+
+    let (status, orders) = request_as(addr, Some(&token), "GET", "/orders?status=Pending,Active", None).await;
+    assert_eq!(status, 200);
+    assert_eq!(orders.as_array().unwrap().len(), 1);
+
+    let (status, book) = request(addr, "GET", "/orderbook/BTC%2FUSDT?levels=5", None).await;
+    assert_eq!(status, 200);
+    assert_eq!(book["bids"][0]["price"], "100");
+
+    let (status, events) = request_as(addr, Some(&token), "DELETE", &format!("/orders/{}", order_id), None).await;
+    assert_eq!(status, 200);
+    assert!(events[0]["OrderCanceled"].is_object());
+    let (_, book) = request(addr, "GET", "/orderbook/BTC/USDT", None).await;
+    assert_eq!(book["bids"], json!([]));
+
+    let (status, _) = request_as(addr, Some(&token), "DELETE", &format!("/orders/{}", Uuid::new_v4()), None).await;
+    assert_eq!(status, 404);
+}
+
+#[tokio::test]
+async fn test_trades_are_listed_by_symbol_and_user() {
+    let (seller, buyer) = (Uuid::new_v4(), Uuid::new_v4());
+    let addr = start(&[seller, buyer]).await;
+    request(addr, "POST", "/orders", Some(order(seller, "Sell", "100"))).await;
+    request(addr, "POST", "/orders", Some(order(buyer, "Buy", "100"))).await;
+
+    let (status, trades) = request(addr, "GET", "/trades?symbol=BTC/USDT&limit=10", None).await;
+    assert_eq!(status, 200);
+    assert_eq!(trades[0]["price"], "100");
+    let (_, page) = request_as(addr, Some(&buyer.to_string()), "GET", "/trades", None).await;
+    assert_eq!(page["total"], 1);
+
+    assert_eq!(request(addr, "GET", "/trades", None).await.0, 401);
+    assert_eq!(request(addr, "GET", "/trades?symbol=BTC/USDT&limit=many", None).await.0, 400);
+    assert_eq!(request(addr, "PUT", "/orders", None).await.0, 405);
+    assert_eq!(request(addr, "GET", "/positions", None).await.0, 404);
+}
+
+#[tokio::test]
+async fn test_rejected_orders_report_errors() {
+    let user = Uuid::new_v4();
+    let addr = start(&[user]).await;
+    let token = user.to_string();
+    let (status, body) = request_as(addr, Some(&token), "POST", "/orders", Some(json!({"symbol": "BTC/USDT"}))).await;
+    assert_eq!(status, 400);
+    assert!(body["error"].as_str().unwrap().starts_with("Bad order"));
+
+    let mut zero = order(user, "Buy", "100");
+    zero["quantity"] = json!("0");
+    let (status, body) = request(addr, "POST", "/orders", Some(zero)).await;
+    assert_eq!(status, 400);
+    assert!(body["error"].is_string());
+}
+
+#[tokio::test]
+async fn test_callers_only_act_on_their_own_orders() {
+    let (owner, other) = (Uuid::new_v4(), Uuid::new_v4());
+    let addr = start(&[owner, other]).await;
+    let (_, events) = request(addr, "POST", "/orders", Some(order(owner, "Buy", "100"))).await;
+    let order_id = events[0]["OrderPlaced"]["order_id"].as_str().unwrap().to_string();
+    let target = format!("/orders/{}", order_id);
+    let (owner_token, other_token) = (owner.to_string(), other.to_string());
+
+    assert_eq!(request_as(addr, None, "DELETE", &target, None).await.0, 401);
+    assert_eq!(request_as(addr, Some("forged"), "DELETE", &target, None).await.0, 401);
+    assert_eq!(request_as(addr, Some(&other_token), "DELETE", &target, None).await.0, 403);
+    let placed = request_as(addr, Some(&other_token), "POST", "/orders", Some(order(owner, "Buy", "99"))).await;
+    assert_eq!(placed.0, 403);
+    let listed = format!("/orders?user_id={}", owner);
+    assert_eq!(request_as(addr, Some(&other_token), "GET", &listed, None).await.0, 403);
+
+    let (_, orders) = request_as(addr, Some(&other_token), "GET", "/orders", None).await;
+    assert_eq!(orders, json!([]));
+    assert_eq!(request_as(addr, Some(&owner_token), "DELETE", &target, None).await.0, 200);
+}
+
+#[tokio::test]
+async fn test_chunked_bodies_are_read() {
+    let user = Uuid::new_v4();
+    let addr = start(&[user]).await;
+    let body = order(user, "Buy", "100").to_string();
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let request = format!(
+        "POST /orders HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nAuthorization: Bearer {}\r\n\
+         Transfer-Encoding: chunked\r\n\r\n{:x}\r\n{}\r\n0\r\n\r\n",
+        user,
+        body.len(),
+        body
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 201"));
+}
@@ -0,0 +1,105 @@
+use async_trait::async_trait;
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType}, EngineError,
+    MaxOpenNotionalCheck, MaxOrderNotionalCheck, OrderCommand, PlaceOrderCommand, PriceDeviationCheck, RejectReason,
+    RiskCheck, RiskContext,
+};
+use rust_decimal::Decimal;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use uuid::Uuid;
+
+fn create_test_order_cmd(user_id: Uuid, side: OrderSide, price: Decimal, quantity: Decimal) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(user_id, "BTC/USDT", OrderType::Limit, side, Some(price), quantity)
+}
+
+async fn place(
+    engine: &MatchingEngine,
+    user_id: Uuid,
+    side: OrderSide,
+    price: i64,
+    quantity: i64,
+) -> Result<(), EngineError> {
+    let cmd = create_test_order_cmd(user_id, side, Decimal::from(price), Decimal::from(quantity));
+    engine.handle_command(OrderCommand::PlaceOrder(cmd)).await.map(|_| ())
+}
+
+#[tokio::test]
+async fn test_builtin_checks_reject_outliers() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()))
+        .with_risk_check(PriceDeviationCheck {
+            max_deviation_percent: Decimal::from(10),
+        })
+        .with_risk_check(MaxOrderNotionalCheck {
+            max_notional: Decimal::from(1000),
+        })
+        .with_risk_check(MaxOpenNotionalCheck {
+            max_open_notional: Decimal::from(1500),
+        });
+    let (maker, trader) = (Uuid::new_v4(), Uuid::new_v4());
+    // No reference price yet, so any price passes the deviation check
+    place(&engine, maker, OrderSide::Sell, 100, 1).await.unwrap();
+    place(&engine, trader, OrderSide::Buy, 100, 1).await.unwrap();
+
+    let result = place(&engine, trader, OrderSide::Buy, 89, 1).await;
+    assert_eq!(
+        result.unwrap_err(),
+        EngineError::InvalidOrder(RejectReason::PriceDeviationTooLarge {
+            price: Decimal::from(89),
+            reference_price: Decimal::from(100),
+            max_deviation_percent: Decimal::from(10),
+        })
+    );
+    let result = place(&engine, trader, OrderSide::Buy, 95, 11).await;
+    assert!(matches!(result, Err(EngineError::InvalidOrder(RejectReason::OrderNotionalTooLarge { .. }))));
+
+    place(&engine, trader, OrderSide::Buy, 95, 10).await.unwrap();
+    let result = place(&engine, trader, OrderSide::Buy, 95, 6).await;
+    assert_eq!(
+        result.unwrap_err(),
+        EngineError::InvalidOrder(RejectReason::OpenNotionalTooLarge {
+            notional: Decimal::from(1520),
+            limit: Decimal::from(1500),
+        })
+    );
+    place(&engine, Uuid::new_v4(), OrderSide::Buy, 95, 6).await.unwrap();
+}
+
+struct BlockUser {
+    user_id: Uuid,
+    calls: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl RiskCheck for BlockUser {
+    async fn check(&self, cmd: &PlaceOrderCommand, ctx: &RiskContext) -> Result<(), RejectReason> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        assert!(ctx.open_notional >= Decimal::ZERO);
+        if cmd.user_id == self.user_id {
+            return Err(RejectReason::PreTradeCheck(format!("User {} is blocked", cmd.user_id)));
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_custom_checks_run_in_order_until_one_rejects() {
+    let (blocked, other) = (Uuid::new_v4(), Uuid::new_v4());
+    let calls = Arc::new(AtomicUsize::new(0));
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()))
+        .with_risk_check(BlockUser {
+            user_id: blocked,
+            calls: calls.clone(),
+        })
+        .with_risk_check(MaxOrderNotionalCheck {
+            max_notional: Decimal::from(50),
+        });
+
+    let result = place(&engine, blocked, OrderSide::Buy, 100, 1).await;
+    assert_eq!(result.unwrap_err().to_string(), format!("User {} is blocked", blocked));
+    let result = place(&engine, other, OrderSide::Buy, 100, 1).await;
+    assert!(matches!(result, Err(EngineError::InvalidOrder(RejectReason::OrderNotionalTooLarge { .. }))));
+    place(&engine, other, OrderSide::Buy, 40, 1).await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    assert!(engine.get_open_orders(blocked).is_empty());
+}
@@ -0,0 +1,191 @@
+#![cfg(feature = "server")]
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use matching_engine::{engine::MatchingEngine, event_store::InMemoryEventStore, serve_websocket, StaticTokens};
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use uuid::Uuid;
+
+struct Client {
+    stream: BufReader<TcpStream>,
+}
+
+/// Starts a server where each of `users` authenticates with its id as the
+/// token.
+async fn start(engine: Arc<MatchingEngine>, users: &[Uuid]) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let tokens = users.iter().fold(StaticTokens::new(), |tokens, user| tokens.with_token(user.to_string(), *user));
+    tokio::spawn(serve_websocket(engine, listener, Arc::new(tokens)));
+    addr
+}
+
+/// Sends an upgrade request and returns the response head.
+async fn handshake(stream: &mut BufReader<TcpStream>, method: &str, target: &str, authorization: &str) -> String {
+    // The key and accept value from RFC 6455
+    let request = format!(
+        "{} {} HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n{}\
+         Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n",
+        method, target, authorization
+    );
+    stream.get_mut().write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    loop {
+        let mut line = String::new();
+        stream.read_line(&mut line).await.unwrap();
+        if line == "\r\n" || line.is_empty() {
+            break;
+        }
+        response.push_str(&line);
+    }
+    response
+}
+
+impl Client {
+    async fn connect(addr: SocketAddr, user: Uuid) -> Self {
+        let mut stream = BufReader::new(TcpStream::connect(addr).await.unwrap());
+        let response = handshake(&mut stream, "GET", "/", &format!("Authorization: Bearer {}\r\n", user)).await;
+        assert!(response.starts_with("HTTP/1.1 101"));
+        assert!(response.to_lowercase().contains("sec-websocket-accept: s3pplmbitxaq9kygzzhzrbk+xoo="));
+        Self { stream }
+    }
+
+    async fn send(&mut self, message: Value) {
+        let payload = message.to_string().into_bytes();
+        let mask = [0x12, 0x34, 0x56, 0x78];
+        let mut frame = vec![0x81];
+        if payload.len() < 126 {
+            frame.push(0x80 | payload.len() as u8);
+        } else {
+            frame.push(0x80 | 126);
+            frame.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+        }
+        frame.extend_from_slice(&mask);
+        frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        self.stream.get_mut().write_all(&frame).await.unwrap();
+    }
+
+    async fn receive(&mut self) -> Value {
+        let mut head = [0u8; 2];
+        self.stream.read_exact(&mut head).await.unwrap();
+        assert_eq!(head[0], 0x81);
+        let len = match head[1] {
+            126 => self.stream.read_u16().await.unwrap() as usize,
+            127 => self.stream.read_u64().await.unwrap() as usize,
+            len => len as usize,
+        };
+        let mut payload = vec![0u8; len];
+        self.stream.read_exact(&mut payload).await.unwrap();
+        serde_json::from_slice(&payload).unwrap()
+    }
+}
+
+fn place(user_id: Uuid, side: &str, price: &str) -> Value {
+    json!({
+        "op": "place",
+        "user_id": user_id,
+        "symbol": "BTC/USDT",
+        "order_type": "Limit",
+        "side": side,
+        "price": price,
+        "quantity": "1"
+    })
+}
+
+#[tokio::test]
+async fn test_clients_trade_and_follow_feeds() {
+    let engine = Arc::new(MatchingEngine::new(Box::new(InMemoryEventStore::new())));
+    let (user, buyer) = (Uuid::new_v4(), Uuid::new_v4());
+    let addr = start(engine.clone(), &[user, buyer]).await;
+    let mut client = Client::connect(addr, user).await;
+
+    client.send(json!({"op": "subscribe", "channel": "depth", "symbol": "BTC/USDT", "levels": 5})).await;
+    assert_eq!(client.receive().await["type"], "subscribed");
+    let depth = client.receive().await;
+    assert_eq!(depth["type"], "depth");
+    assert_eq!(depth["data"]["asks"], json!([]));
+
+    client.send(json!({"op": "subscribe", "channel": "trades", "symbol": "BTC/USDT"})).await;
+    assert_eq!(client.receive().await["type"], "subscribed");
+
+    client.send(place(user, "Sell", "100")).await;
+    let mut received = [client.receive().await, client.receive().await];
+    received.sort_by_key(|message| message["type"].as_str().unwrap().to_string());
+    assert_eq!(received[0]["type"], "depth");
+    assert_eq!(received[0]["data"]["asks"][0]["price"], "100");
+    assert_eq!(received[1]["type"], "events");
+    assert_eq!(received[1]["data"][0]["OrderPlaced"]["user_id"], json!(user));
+
+    client.send(json!({"op": "unsubscribe", "channel": "depth", "symbol": "BTC/USDT"})).await;
+    assert_eq!(client.receive().await["type"], "unsubscribed");
+    let mut buying = Client::connect(addr, buyer).await;
+    buying.send(place(buyer, "Buy", "100")).await;
+    assert_eq!(buying.receive().await["type"], "events");
+    let trade = client.receive().await;
+    assert_eq!(trade["type"], "trade");
+    assert_eq!(trade["data"]["price"], "100");
+    assert_eq!(engine.get_trades_by_symbol("BTC/USDT", None, 10).len(), 1);
+}
+
+#[tokio::test]
+async fn test_bad_messages_and_rejections_are_reported() {
+    let engine = Arc::new(MatchingEngine::new(Box::new(InMemoryEventStore::new())));
+    let user = Uuid::new_v4();
+    let mut client = Client::connect(start(engine, &[user]).await, user).await;
+
+    client.send(json!({"op": "launch"})).await;
+    let reply = client.receive().await;
+    assert_eq!(reply["type"], "error");
+    assert!(reply["message"].as_str().unwrap().starts_with("Bad message"));
+
+    client
+        .send(json!({"op": "cancel", "order_id": Uuid::new_v4(), "user_id": user, "symbol": "BTC/USDT"}))
+        .await;
+    assert_eq!(client.receive().await["type"], "error");
+}
+
+#[tokio::test]
+async fn test_connections_act_only_for_their_user() {
+    let engine = Arc::new(MatchingEngine::new(Box::new(InMemoryEventStore::new())));
+    let user = Uuid::new_v4();
+    let addr = start(engine.clone(), &[user]).await;
+
+    let mut stream = BufReader::new(TcpStream::connect(addr).await.unwrap());
+    assert!(handshake(&mut stream, "GET", "/", "").await.starts_with("HTTP/1.1 401"));
+    let mut stream = BufReader::new(TcpStream::connect(addr).await.unwrap());
+    let forged = handshake(&mut stream, "GET", "/", "Authorization: Bearer forged\r\n").await;
+    assert!(forged.starts_with("HTTP/1.1 401"));
+    let mut stream = BufReader::new(TcpStream::connect(addr).await.unwrap());
+    let posted = handshake(&mut stream, "POST", "/", &format!("Authorization: Bearer {}\r\n", user)).await;
+    assert!(!posted.starts_with("HTTP/1.1 101"));
+    // Browsers pass the token in the query
+    let mut stream = BufReader::new(TcpStream::connect(addr).await.unwrap());
+    let queried = handshake(&mut stream, "GET", &format!("/?token={}", user), "").await;
+    assert!(queried.starts_with("HTTP/1.1 101"));
+
+    let mut client = Client::connect(addr, user).await;
+    client.send(place(Uuid::new_v4(), "Buy", "100")).await;
+    let reply = client.receive().await;
+    assert_eq!(reply["type"], "error");
+    assert!(reply["message"].as_str().unwrap().starts_with("Not authorized"));
+    assert!(engine.get_open_orders_by_symbol("BTC/USDT").is_empty());
+}
+
+#[tokio::test]
+async fn test_subscriptions_per_connection_are_capped() {
+    let engine = Arc::new(MatchingEngine::new(Box::new(InMemoryEventStore::new())));
+    let user = Uuid::new_v4();
+    let mut client = Client::connect(start(engine, &[user]).await, user).await;
+
+    for i in 0..64 {
+        client.send(json!({"op": "subscribe", "channel": "trades", "symbol": format!("SYM{}/USDT", i)})).await;
+        assert_eq!(client.receive().await["type"], "subscribed");
+    }
+    client.send(json!({"op": "subscribe", "channel": "trades", "symbol": "ONE/MORE"})).await;
+    assert_eq!(client.receive().await["type"], "error");
+    client.send(json!({"op": "subscribe", "channel": "trades", "symbol": "SYM0/USDT"})).await;
+    assert_eq!(client.receive().await["type"], "subscribed");
+}
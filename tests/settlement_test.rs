@@ -0,0 +1,109 @@
+use async_trait::async_trait;
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType}, OrderCommand,
+    PlaceOrderCommand, SettlementBatch, SettlementDirection, SettlementInstruction, SettlementSink,
+};
+use rust_decimal::Decimal;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+fn create_test_order_cmd(user_id: Uuid, side: OrderSide, price: i64, quantity: i64) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(
+        user_id,
+        "BTC/USDT",
+        OrderType::Limit,
+        side,
+        Some(Decimal::from(price)),
+        Decimal::from(quantity),
+    )
+}
+
+#[derive(Clone, Default)]
+struct Recorder {
+    batches: Arc<Mutex<Vec<SettlementBatch>>>,
+    down: Arc<AtomicBool>,
+}
+
+#[async_trait]
+impl SettlementSink for Recorder {
+    async fn settle(&self, batch: SettlementBatch) -> Result<(), String> {
+        if self.down.load(Ordering::SeqCst) {
+            return Err("clearing unavailable".to_string());
+        }
+        self.batches.lock().unwrap().push(batch);
+        Ok(())
+    }
+}
+
+async fn place(engine: &MatchingEngine, user_id: Uuid, side: OrderSide, price: i64, quantity: i64) {
+    let cmd = create_test_order_cmd(user_id, side, price, quantity);
+    engine.handle_command(OrderCommand::PlaceOrder(cmd)).await.unwrap();
+}
+
+fn instruction(user_id: Uuid, asset: &str, direction: SettlementDirection, amount: i64) -> SettlementInstruction {
+    SettlementInstruction {
+        user_id,
+        asset: asset.to_string(),
+        direction,
+        amount: Decimal::from(amount),
+    }
+}
+
+#[tokio::test]
+async fn test_fills_of_one_command_are_netted_per_user_and_asset() {
+    let sink = Recorder::default();
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new())).with_settlement_sink(sink.clone());
+    let (alice, bob, carol) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    place(&engine, alice, OrderSide::Sell, 100, 1).await;
+    place(&engine, alice, OrderSide::Sell, 101, 2).await;
+    place(&engine, bob, OrderSide::Sell, 102, 1).await;
+    assert!(sink.batches.lock().unwrap().is_empty());
+
+    place(&engine, carol, OrderSide::Buy, 102, 4).await;
+    let batches = sink.batches.lock().unwrap().clone();
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].fill_count, 3);
+    let mut expected = vec![
+        instruction(alice, "BTC", SettlementDirection::Debit, 3),
+        instruction(alice, "USDT", SettlementDirection::Credit, 302),
+        instruction(bob, "BTC", SettlementDirection::Debit, 1),
+        instruction(bob, "USDT", SettlementDirection::Credit, 102),
+        instruction(carol, "BTC", SettlementDirection::Credit, 4),
+        instruction(carol, "USDT", SettlementDirection::Debit, 404),
+    ];
+    expected.sort_by_key(|i| (i.user_id, i.asset.clone()));
+    assert_eq!(batches[0].instructions, expected);
+
+    // A user trading with themselves moves nothing
+    place(&engine, alice, OrderSide::Sell, 110, 1).await;
+    place(&engine, alice, OrderSide::Buy, 110, 1).await;
+    let batches = sink.batches.lock().unwrap().clone();
+    assert_eq!(batches.len(), 2);
+    assert_eq!(batches[1].fill_count, 1);
+    assert!(batches[1].instructions.is_empty());
+}
+
+#[tokio::test]
+async fn test_undelivered_batches_are_retried_in_order() {
+    let sink = Recorder::default();
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new())).with_settlement_sink(sink.clone());
+    let (maker, taker) = (Uuid::new_v4(), Uuid::new_v4());
+    sink.down.store(true, Ordering::SeqCst);
+    place(&engine, maker, OrderSide::Sell, 100, 1).await;
+    place(&engine, taker, OrderSide::Buy, 100, 1).await;
+    place(&engine, maker, OrderSide::Sell, 105, 1).await;
+    place(&engine, taker, OrderSide::Buy, 105, 1).await;
+    assert!(engine.flush_settlements().await.is_err());
+
+    sink.down.store(false, Ordering::SeqCst);
+    engine.flush_settlements().await.unwrap();
+    let batches = sink.batches.lock().unwrap().clone();
+    let paid: Vec<Decimal> = batches
+        .iter()
+        .flat_map(|b| &b.instructions)
+        .filter(|i| i.user_id == taker && i.asset == "USDT")
+        .map(|i| i.amount)
+        .collect();
+    assert_eq!(paid, vec![Decimal::from(100), Decimal::from(105)]);
+}
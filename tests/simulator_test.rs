@@ -0,0 +1,71 @@
+use matching_engine::{
+    FlowConfig, FlowMix, InMemoryEventStore, MatchingEngine, OrderCommand, OrderFlow, OrderType,
+};
+use rust_decimal::Decimal;
+
+#[test]
+fn test_flow_is_reproducible_and_follows_the_config() {
+    let config = FlowConfig {
+        arrival_rate: 100.0,
+        ..FlowConfig::default()
+    };
+    let strip = |command: OrderCommand| match command {
+        OrderCommand::PlaceOrder(mut cmd) => {
+            cmd.timestamp = Default::default();
+            serde_json::to_string(&cmd).unwrap()
+        }
+        OrderCommand::CancelOrder(mut cmd) => {
+            cmd.timestamp = Default::default();
+            serde_json::to_string(&cmd).unwrap()
+        }
+        other => panic!("unexpected command {:?}", other),
+    };
+    let first: Vec<String> = OrderFlow::with_seed(config.clone(), 11).take(200).map(strip).collect();
+    let second: Vec<String> = OrderFlow::with_seed(config.clone(), 11).take(200).map(strip).collect();
+    assert_eq!(first, second);
+
+    let mut flow = OrderFlow::with_seed(config.clone(), 5);
+    let start = flow.now();
+    let commands: Vec<OrderCommand> = flow.by_ref().take(5000).collect();
+    let mean_gap = (flow.now() - start).num_microseconds().unwrap() as f64 / 5000.0;
+    assert!((8_000.0..12_000.0).contains(&mean_gap), "mean gap {}us", mean_gap);
+
+    let count = |order_type| {
+        commands
+            .iter()
+            .filter(|c| matches!(c, OrderCommand::PlaceOrder(cmd) if cmd.order_type == order_type))
+            .count()
+    };
+    assert!(count(OrderType::Limit) > count(OrderType::Market));
+    assert!(count(OrderType::StopLoss) > 0);
+    assert!(commands.iter().any(|c| matches!(c, OrderCommand::CancelOrder(_))));
+    for command in &commands {
+        if let OrderCommand::PlaceOrder(cmd) = command {
+            assert!(cmd.quantity > Decimal::ZERO && cmd.quantity <= config.lot_size * Decimal::from(config.max_lots));
+            assert!(cmd.price.is_none_or(|p| p >= config.tick_size && (p / config.tick_size).fract().is_zero()));
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_engine_stays_consistent_under_synthetic_flow() {
+    let config = FlowConfig {
+        mix: FlowMix {
+            limit: 50,
+            market: 20,
+            stop: 10,
+            cancel: 20,
+        },
+        ..FlowConfig::default()
+    };
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let mut accepted = 0;
+    for command in OrderFlow::with_seed(config, 42).take(3000) {
+        if engine.handle_command(command).await.is_ok() {
+            accepted += 1;
+        }
+    }
+    assert!(accepted > 2000);
+    assert!(!engine.get_recent_trades("BTC/USDT", 1).is_empty());
+    engine.check_invariants("BTC/USDT").unwrap();
+}
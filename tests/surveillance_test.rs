@@ -0,0 +1,101 @@
+use chrono::Utc;
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType}, AlertKind,
+    CancelOrderCommand, OrderCommand, OrderEvent, PlaceOrderCommand, Projector, SurveillanceConfig, SurveillanceView,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(user_id: Uuid, side: OrderSide, price: i64, quantity: i64) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(
+        user_id,
+        "BTC/USDT",
+        OrderType::Limit,
+        side,
+        Some(Decimal::from(price)),
+        Decimal::from(quantity),
+    )
+}
+
+async fn place(
+    engine: &MatchingEngine,
+    user_id: Uuid,
+    side: OrderSide,
+    price: i64,
+    quantity: i64,
+) -> (Uuid, Vec<OrderEvent>) {
+    let cmd = create_test_order_cmd(user_id, side, price, quantity);
+    let order_id = cmd.order_id;
+    (order_id, engine.handle_command(OrderCommand::PlaceOrder(cmd)).await.unwrap())
+}
+
+async fn cancel(engine: &MatchingEngine, user_id: Uuid, order_id: Uuid) {
+    let cmd = CancelOrderCommand {
+        order_id,
+        client_order_id: None,
+        user_id,
+        symbol: "BTC/USDT".to_string(),
+        timestamp: Utc::now(),
+    };
+    engine.handle_command(OrderCommand::CancelOrder(cmd)).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_wash_trades_and_quick_cancels_raise_alerts() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let (washer, spoofer) = (Uuid::new_v4(), Uuid::new_v4());
+    place(&engine, washer, OrderSide::Sell, 100, 1).await;
+    let (_, events) = place(&engine, washer, OrderSide::Buy, 100, 1).await;
+    let matched = events.iter().find(|e| matches!(e, OrderEvent::OrderMatched(_))).unwrap();
+    for _ in 0..3 {
+        let (order_id, _) = place(&engine, spoofer, OrderSide::Buy, 99, 50).await;
+        cancel(&engine, spoofer, order_id).await;
+    }
+
+    let projector = Projector::new(SurveillanceView::new(SurveillanceConfig {
+        spoofing_cancels: 3,
+        ..SurveillanceConfig::default()
+    }));
+    projector.catch_up(engine.event_store()).await.unwrap();
+    projector.read(|view| {
+        let alerts = view.alerts_since(0);
+        assert_eq!(alerts.len(), 2);
+        assert_eq!((alerts[0].kind, alerts[0].user_id), (AlertKind::WashTrade, washer));
+        assert_eq!(alerts[0].evidence.last(), Some(&matched.event_id()));
+        assert_eq!((alerts[1].kind, alerts[1].user_id), (AlertKind::Spoofing, spoofer));
+        assert_eq!(alerts[1].evidence.len(), 6);
+        assert_eq!(alerts[1].sequence, 2);
+        assert!(view.alerts_since(2).is_empty());
+    });
+}
+
+#[tokio::test]
+async fn test_momentum_ignition_needs_a_move_then_a_reversal() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let (igniter, maker, bystander) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    for price in [100, 101, 102, 103] {
+        place(&engine, maker, OrderSide::Sell, price, 1).await;
+    }
+    place(&engine, maker, OrderSide::Buy, 90, 1).await;
+    // 1% moves are not enough
+    place(&engine, bystander, OrderSide::Buy, 101, 2).await;
+    place(&engine, bystander, OrderSide::Sell, 90, 1).await;
+
+    for price in [104, 105, 106] {
+        place(&engine, maker, OrderSide::Sell, price, 1).await;
+    }
+    place(&engine, maker, OrderSide::Buy, 100, 1).await;
+    place(&engine, igniter, OrderSide::Buy, 106, 4).await;
+    let (_, events) = place(&engine, igniter, OrderSide::Sell, 100, 1).await;
+    let reversal = events.iter().find(|e| matches!(e, OrderEvent::OrderMatched(_))).unwrap();
+
+    let projector = Projector::new(SurveillanceView::new(SurveillanceConfig::default()));
+    projector.catch_up(engine.event_store()).await.unwrap();
+    projector.read(|view| {
+        let alerts = view.alerts_since(0);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!((alerts[0].kind, alerts[0].user_id), (AlertKind::MomentumIgnition, igniter));
+        assert_eq!(alerts[0].evidence.len(), 5);
+        assert_eq!(alerts[0].evidence.last(), Some(&reversal.event_id()));
+    });
+}
@@ -0,0 +1,108 @@
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, symbols::SymbolConfig,
+    types::{OrderSide, OrderStatus, OrderType}, OrderEvent, PlaceOrderCommand, SyntheticLegs, SyntheticPair,
+};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+use uuid::Uuid;
+
+fn create_test_order_cmd(symbol: &str, price: Option<Decimal>, quantity: Decimal, side: OrderSide) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(
+        Uuid::new_v4(),
+        symbol,
+        if price.is_some() { OrderType::Limit } else { OrderType::Market },
+        side,
+        price,
+        quantity,
+    )
+}
+
+async fn create_engine() -> MatchingEngine {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    engine.register_synthetic_pair(SyntheticPair::new("ETH/BTC", "ETH/USDT", "BTC/USDT").unwrap());
+
+    engine.handle_place_order(create_test_order_cmd("ETH/USDT", Some(Decimal::from(2000)), Decimal::from(5), OrderSide::Sell)).await.unwrap();
+    engine.handle_place_order(create_test_order_cmd("BTC/USDT", Some(Decimal::from(40000)), Decimal::from(1), OrderSide::Buy)).await.unwrap();
+    engine
+}
+
+#[test]
+fn test_synthetic_pair_requires_shared_currency() {
+    assert!(SyntheticPair::new("ETH/BTC", "ETH/USDT", "BTC/USDC").is_err());
+    assert!(SyntheticPair::new("ETH/BTC", "BTC/USDT", "ETH/USDT").is_err());
+}
+
+#[tokio::test]
+async fn test_synthetic_buy_executes_both_legs() {
+    let engine = create_engine().await;
+
+    let quote = engine.quote_synthetic("ETH/BTC", OrderSide::Buy, Decimal::from(1)).unwrap();
+    assert_eq!(quote.price, Decimal::from_str("0.05").unwrap());
+    assert_eq!(quote.intermediate_amount, Decimal::from(2000));
+
+    let cmd = create_test_order_cmd("ETH/BTC", None, Decimal::from(1), OrderSide::Buy);
+    let order_id = cmd.order_id;
+    let events = engine.handle_place_order(cmd).await.unwrap();
+
+    let synthetic = events.iter().find_map(|e| match e {
+        OrderEvent::SyntheticTradeExecuted(s) => Some(s.clone()),
+        _ => None,
+    }).unwrap();
+    assert_eq!(synthetic.leg_trade_ids.len(), 2);
+    assert_eq!(synthetic.price, Decimal::from_str("0.05").unwrap());
+    assert_eq!(engine.get_order(order_id).unwrap().status, OrderStatus::Filled);
+
+    let eth_book = engine.get_order_book("ETH/USDT").unwrap();
+    assert_eq!(eth_book.asks[0].quantity, Decimal::from(4));
+    let btc_book = engine.get_order_book("BTC/USDT").unwrap();
+    assert_eq!(btc_book.bids[0].quantity, Decimal::from_str("0.95").unwrap());
+}
+
+#[tokio::test]
+async fn test_synthetic_order_is_all_or_nothing() {
+    let engine = create_engine().await;
+
+    // 30 ETH would need 60k USDT from a BTC/USDT book that only holds 40k
+    let cmd = create_test_order_cmd("ETH/BTC", None, Decimal::from(30), OrderSide::Buy);
+    assert!(engine.handle_place_order(cmd).await.is_err());
+
+    // Limit below the synthetic price is refused as well
+    let cmd = create_test_order_cmd("ETH/BTC", Some(Decimal::from_str("0.04").unwrap()), Decimal::from(1), OrderSide::Buy);
+    assert!(engine.handle_place_order(cmd).await.is_err());
+
+    let eth_book = engine.get_order_book("ETH/USDT").unwrap();
+    assert_eq!(eth_book.asks[0].quantity, Decimal::from(5));
+}
+
+#[tokio::test]
+async fn test_symbols_configured_as_synthetic_route_through_their_legs() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let legs = |base_leg: &str, quote_leg: &str| SyntheticLegs {
+        base_leg: base_leg.to_string(),
+        quote_leg: quote_leg.to_string(),
+    };
+    engine.symbols().register(SymbolConfig {
+        synthetic: Some(legs("ETH/USDT", "BTC/USDT")),
+        ..SymbolConfig::new("ETH/BTC")
+    }).unwrap();
+    engine.handle_place_order(create_test_order_cmd("ETH/USDT", Some(Decimal::from(2000)), Decimal::from(5), OrderSide::Buy)).await.unwrap();
+    engine.handle_place_order(create_test_order_cmd("BTC/USDT", Some(Decimal::from(40000)), Decimal::from(1), OrderSide::Sell)).await.unwrap();
+    assert_eq!(engine.get_synthetic_pair("ETH/BTC").unwrap().quote_leg, "BTC/USDT");
+
+    let cmd = create_test_order_cmd("ETH/BTC", None, Decimal::from(2), OrderSide::Sell);
+    let events = engine.handle_place_order(cmd).await.unwrap();
+    let synthetic = events.iter().find_map(|e| match e {
+        OrderEvent::SyntheticTradeExecuted(s) => Some(s.clone()),
+        _ => None,
+    }).unwrap();
+    assert_eq!((synthetic.price, synthetic.leg_trade_ids.len()), (Decimal::from_str("0.05").unwrap(), 2));
+    assert!(engine.get_order_book("ETH/BTC").is_none());
+
+    // Legs that cannot derive the symbol are refused rather than ignored
+    engine.symbols().register(SymbolConfig {
+        synthetic: Some(legs("BTC/USDT", "ETH/USDT")),
+        ..SymbolConfig::new("ETH/BTC")
+    }).unwrap();
+    let cmd = create_test_order_cmd("ETH/BTC", None, Decimal::from(1), OrderSide::Sell);
+    assert!(engine.handle_place_order(cmd).await.is_err());
+}
@@ -0,0 +1,78 @@
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType}, Balance, OrderCommand,
+    OrderEvent, Pagination, PlaceOrderCommand,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(user_id: Uuid, side: OrderSide, price: i64, quantity: i64) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(
+        user_id,
+        "BTC/USDT",
+        OrderType::Limit,
+        side,
+        Some(Decimal::from(price)),
+        Decimal::from(quantity),
+    )
+}
+
+async fn place(engine: &MatchingEngine, user_id: Uuid, side: OrderSide, price: i64, quantity: i64) -> Vec<OrderEvent> {
+    let cmd = create_test_order_cmd(user_id, side, price, quantity);
+    engine.handle_command(OrderCommand::PlaceOrder(cmd)).await.unwrap()
+}
+
+fn available(amount: i64) -> Balance {
+    Balance {
+        available: Decimal::from(amount),
+        held: Decimal::ZERO,
+    }
+}
+
+#[tokio::test]
+async fn test_busting_a_trade_reverses_balances_and_positions() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new())).with_accounts();
+    let (buyer, seller) = (Uuid::new_v4(), Uuid::new_v4());
+    engine.deposit(buyer, "USDT", Decimal::from(1000)).unwrap();
+    engine.deposit(seller, "BTC", Decimal::from(5)).unwrap();
+    place(&engine, seller, OrderSide::Sell, 10, 5).await;
+    place(&engine, buyer, OrderSide::Buy, 10, 2).await;
+    let trade = engine.get_trades_by_user(buyer, Pagination::new(0, 10)).items[0].clone();
+    assert_eq!(engine.get_position(buyer, "BTC/USDT").quantity(), Decimal::TWO);
+
+    let events = engine.bust_trade(trade.id, "fat finger").await.unwrap();
+    let OrderEvent::TradeBusted(busted) = &events[0] else {
+        panic!("expected TradeBusted, got {:?}", events[0]);
+    };
+    assert_eq!((busted.trade_id, busted.reason.as_str()), (trade.id, "fat finger"));
+    assert!(engine.trade_busted_at(trade.id).is_some());
+    assert_eq!(engine.get_trade(trade.id).unwrap().quantity, Decimal::TWO);
+
+    assert_eq!(engine.get_balance(buyer, "USDT").unwrap(), available(1000));
+    assert_eq!(engine.get_balance(buyer, "BTC").unwrap(), available(0));
+    assert_eq!(engine.get_balance(seller, "USDT").unwrap(), available(0));
+    assert_eq!(
+        engine.get_balance(seller, "BTC").unwrap(),
+        Balance {
+            available: Decimal::TWO,
+            held: Decimal::from(3),
+        }
+    );
+    assert!(engine.get_position(buyer, "BTC/USDT").quantity().is_zero());
+    assert!(engine.get_position(seller, "BTC/USDT").quantity().is_zero());
+}
+
+#[tokio::test]
+async fn test_a_trade_can_only_be_busted_once() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let (buyer, seller) = (Uuid::new_v4(), Uuid::new_v4());
+    assert!(engine.bust_trade(Uuid::new_v4(), "unknown").await.is_err());
+    place(&engine, seller, OrderSide::Sell, 10, 1).await;
+    place(&engine, buyer, OrderSide::Buy, 10, 1).await;
+    let trade = engine.get_trades_by_user(buyer, Pagination::new(0, 10)).items[0].clone();
+
+    engine.bust_trade(trade.id, "off market").await.unwrap();
+    let result = engine.bust_trade(trade.id, "off market").await;
+    assert_eq!(result.unwrap_err(), format!("Trade {} is already busted", trade.id));
+    let stored = engine.event_store().read_from(0, 100).await.unwrap();
+    assert_eq!(stored.last().unwrap().event.kind(), "TradeBusted");
+}
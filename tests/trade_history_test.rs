@@ -0,0 +1,54 @@
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType},
+    Pagination, PlaceOrderCommand,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(user_id: Uuid, price: Decimal, side: OrderSide) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(user_id, "BTC/USDT", OrderType::Limit, side, Some(price), Decimal::ONE)
+}
+
+async fn trade(engine: &MatchingEngine, maker: Uuid, taker: Uuid, price: i64) {
+    let price = Decimal::from(price);
+    engine.handle_place_order(create_test_order_cmd(maker, price, OrderSide::Sell)).await.unwrap();
+    engine.handle_place_order(create_test_order_cmd(taker, price, OrderSide::Buy)).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_trades_by_symbol_since() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let (maker, taker) = (Uuid::new_v4(), Uuid::new_v4());
+    for price in [100, 101, 102] {
+        trade(&engine, maker, taker, price).await;
+    }
+
+    let all = engine.get_trades_by_symbol("BTC/USDT", None, 10);
+    let prices: Vec<Decimal> = all.iter().map(|t| t.price).collect();
+    assert_eq!(prices, vec![Decimal::from(100), Decimal::from(101), Decimal::from(102)]);
+
+    let recent = engine.get_trades_by_symbol("BTC/USDT", Some(all[1].created_at), 1);
+    assert_eq!(recent.len(), 1);
+    assert_eq!(recent[0].id, all[1].id);
+    assert!(engine.get_trades_by_symbol("ETH/USDT", None, 10).is_empty());
+}
+
+#[tokio::test]
+async fn test_trades_by_user_paginates_both_sides() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let user = Uuid::new_v4();
+    trade(&engine, user, Uuid::new_v4(), 100).await;
+    trade(&engine, Uuid::new_v4(), user, 101).await;
+    trade(&engine, user, Uuid::new_v4(), 102).await;
+    trade(&engine, Uuid::new_v4(), Uuid::new_v4(), 103).await;
+
+    let first = engine.get_trades_by_user(user, Pagination::new(0, 2));
+    assert_eq!(first.total, 3);
+    assert_eq!(first.items.iter().map(|t| t.price).collect::<Vec<_>>(), vec![Decimal::from(100), Decimal::from(101)]);
+    assert_eq!(first.next_offset, Some(2));
+
+    let second = engine.get_trades_by_user(user, Pagination::new(2, 2));
+    assert_eq!(second.items.len(), 1);
+    assert_eq!(second.items[0].price, Decimal::from(102));
+    assert_eq!(second.next_offset, None);
+}
@@ -0,0 +1,114 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType},
+    FileTradeStore, InMemoryTradeStore, OrderCommand, PlaceOrderCommand, TimeRange, TradeRecord, TradeStore,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(user_id: Uuid, side: OrderSide, price: Decimal) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(user_id, "BTC/USDT", OrderType::Limit, side, Some(price), Decimal::ONE)
+}
+
+fn trade_ids(records: &[TradeRecord]) -> Vec<Uuid> {
+    records.iter().map(|r| r.trade.id).collect()
+}
+
+/// Crosses `count` pairs of orders between two users.
+async fn trade(engine: &MatchingEngine, seller: Uuid, buyer: Uuid, count: usize) {
+    for _ in 0..count {
+        for (user_id, side) in [(seller, OrderSide::Sell), (buyer, OrderSide::Buy)] {
+            let cmd = create_test_order_cmd(user_id, side, Decimal::from(100));
+            engine.handle_command(OrderCommand::PlaceOrder(cmd)).await.unwrap();
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_trades_are_captured_by_symbol_and_user() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new())).with_trade_store(InMemoryTradeStore::new());
+    let (seller, buyer) = (Uuid::new_v4(), Uuid::new_v4());
+    trade(&engine, seller, buyer, 2).await;
+    let cut = Utc::now();
+    tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    trade(&engine, seller, buyer, 1).await;
+
+    let store = engine.trade_store().unwrap();
+    let executed = engine.get_trades_by_symbol("BTC/USDT", None, 10);
+    let captured = store.get_trades_by_symbol("BTC/USDT", TimeRange::all()).await.unwrap();
+    assert_eq!(trade_ids(&captured), executed.iter().map(|t| t.id).collect::<Vec<_>>());
+    assert!(captured.iter().all(|r| r.taker_user_id == buyer && r.maker_user_id == Some(seller)));
+
+    for user_id in [seller, buyer] {
+        assert_eq!(store.get_trades_by_user(user_id, TimeRange::all()).await.unwrap().len(), 3);
+    }
+    let later = store.get_trades_by_user(seller, TimeRange::since(cut)).await.unwrap();
+    assert_eq!(trade_ids(&later), vec![executed[2].id]);
+    assert!(store.get_trades_by_user(Uuid::new_v4(), TimeRange::all()).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_file_trade_store_survives_reopening() {
+    let path = std::env::temp_dir().join(format!("trades-{}.log", Uuid::new_v4()));
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()))
+        .with_trade_store(FileTradeStore::open(&path).unwrap());
+    let (seller, buyer) = (Uuid::new_v4(), Uuid::new_v4());
+    trade(&engine, seller, buyer, 2).await;
+    let saved = engine.trade_store().unwrap().get_trades_by_symbol("BTC/USDT", TimeRange::all()).await.unwrap();
+    drop(engine);
+
+    let store = FileTradeStore::open(&path).unwrap();
+    let read = store.get_trades_by_user(buyer, TimeRange::all()).await.unwrap();
+    assert_eq!(trade_ids(&read), trade_ids(&saved));
+    store.save_trades(saved.clone()).await.unwrap();
+    assert_eq!(store.get_trades_by_symbol("BTC/USDT", TimeRange::all()).await.unwrap().len(), 2);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+struct FlakyTradeStore {
+    inner: Arc<InMemoryTradeStore>,
+    down: Arc<AtomicBool>,
+}
+
+#[async_trait]
+impl TradeStore for FlakyTradeStore {
+    async fn save_trades(&self, trades: Vec<TradeRecord>) -> Result<(), String> {
+        if self.down.load(Ordering::SeqCst) {
+            return Err("trade store unavailable".to_string());
+        }
+        self.inner.save_trades(trades).await
+    }
+
+    async fn get_trades_by_symbol(&self, symbol: &str, range: TimeRange) -> Result<Vec<TradeRecord>, String> {
+        self.inner.get_trades_by_symbol(symbol, range).await
+    }
+
+    async fn get_trades_by_user(&self, user_id: Uuid, range: TimeRange) -> Result<Vec<TradeRecord>, String> {
+        self.inner.get_trades_by_user(user_id, range).await
+    }
+}
+
+#[tokio::test]
+async fn test_unsaved_trades_are_retried() {
+    let down = Arc::new(AtomicBool::new(true));
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new())).with_trade_store(FlakyTradeStore {
+        inner: Arc::new(InMemoryTradeStore::new()),
+        down: down.clone(),
+    });
+    let (seller, buyer) = (Uuid::new_v4(), Uuid::new_v4());
+    trade(&engine, seller, buyer, 2).await;
+    let store = engine.trade_store().unwrap();
+    assert!(store.get_trades_by_user(seller, TimeRange::all()).await.unwrap().is_empty());
+    assert!(engine.flush_trades().await.is_err());
+
+    down.store(false, Ordering::SeqCst);
+    engine.flush_trades().await.unwrap();
+    let captured = store.get_trades_by_symbol("BTC/USDT", TimeRange::all()).await.unwrap();
+    let executed: Vec<Uuid> = engine.get_trades_by_symbol("BTC/USDT", None, 10).iter().map(|t| t.id).collect();
+    assert_eq!(trade_ids(&captured), executed);
+}
@@ -0,0 +1,115 @@
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderType},
+    EngineError, PlaceBracketOrderCommand, PlaceOrderCommand, RejectReason,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+fn create_test_order_cmd(order_type: OrderType, price: Option<Decimal>, quantity: Decimal) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), "BTC/USDT", order_type, OrderSide::Sell, price, quantity)
+}
+
+async fn rejection(engine: &MatchingEngine, cmd: PlaceOrderCommand) -> RejectReason {
+    match engine.handle_place_order(cmd).await {
+        Err(EngineError::InvalidOrder(reason)) => reason,
+        other => panic!("expected a validation error, got {:?}", other),
+    }
+}
+
+fn limit(price: i64, quantity: i64) -> PlaceOrderCommand {
+    create_test_order_cmd(OrderType::Limit, Some(Decimal::from(price)), Decimal::from(quantity))
+}
+
+#[tokio::test]
+async fn test_non_positive_quantity_and_price_are_rejected() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+
+    assert_eq!(rejection(&engine, limit(100, 0)).await, RejectReason::NonPositiveQuantity(Decimal::ZERO));
+    assert_eq!(rejection(&engine, limit(100, -1)).await, RejectReason::NonPositiveQuantity(Decimal::from(-1)));
+    assert_eq!(rejection(&engine, limit(0, 1)).await, RejectReason::NonPositivePrice(Decimal::ZERO));
+    assert_eq!(rejection(&engine, limit(-5, 1)).await, RejectReason::NonPositivePrice(Decimal::from(-5)));
+    assert!(engine.get_order_book("BTC/USDT").is_none());
+}
+
+#[tokio::test]
+async fn test_missing_terms_are_rejected() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+
+    let market = create_test_order_cmd(OrderType::Market, Some(Decimal::ONE), Decimal::ONE);
+    assert_eq!(rejection(&engine, market).await, RejectReason::MarketOrderWithPrice);
+    let unpriced = create_test_order_cmd(OrderType::Limit, None, Decimal::ONE);
+    assert_eq!(rejection(&engine, unpriced).await, RejectReason::MissingPrice);
+    let stop = create_test_order_cmd(OrderType::StopLoss, None, Decimal::ONE);
+    assert_eq!(rejection(&engine, stop).await, RejectReason::MissingStopPrice);
+    let iceberg = create_test_order_cmd(OrderType::Iceberg, Some(Decimal::ONE), Decimal::ONE);
+    assert_eq!(rejection(&engine, iceberg).await, RejectReason::MissingVisibleQuantity);
+    let trailing = create_test_order_cmd(OrderType::TrailingStop, None, Decimal::ONE);
+    assert_eq!(rejection(&engine, trailing).await, RejectReason::MissingTrailingStopPrice);
+}
+
+#[tokio::test]
+async fn test_iceberg_visible_quantity_is_bounded() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let iceberg = |visible: i64| PlaceOrderCommand {
+        iceberg_visible_quantity: Some(Decimal::from(visible)),
+        ..create_test_order_cmd(OrderType::Iceberg, Some(Decimal::from(100)), Decimal::from(10))
+    };
+
+    assert_eq!(
+        rejection(&engine, iceberg(11)).await,
+        RejectReason::IcebergVisibleExceedsTotal {
+            visible: Decimal::from(11),
+            total: Decimal::from(10),
+        }
+    );
+    assert_eq!(rejection(&engine, iceberg(0)).await, RejectReason::NonPositiveVisibleQuantity(Decimal::ZERO));
+    assert!(engine.handle_place_order(iceberg(10)).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_stop_price_must_not_trigger_immediately() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    engine.handle_place_order(limit(100, 1)).await.unwrap();
+    let buy = PlaceOrderCommand {
+        side: OrderSide::Buy,
+        ..limit(100, 1)
+    };
+    engine.handle_place_order(buy).await.unwrap();
+
+    let stop_loss = |stop_price: i64| PlaceOrderCommand {
+        stop_price: Some(Decimal::from(stop_price)),
+        ..create_test_order_cmd(OrderType::StopLoss, None, Decimal::ONE)
+    };
+    assert_eq!(
+        rejection(&engine, stop_loss(101)).await,
+        RejectReason::StopPriceOnWrongSide {
+            stop_price: Decimal::from(101),
+            last_price: Decimal::from(100),
+        }
+    );
+    assert_eq!(rejection(&engine, stop_loss(-1)).await, RejectReason::NonPositiveStopPrice(Decimal::from(-1)));
+    assert!(engine.handle_place_order(stop_loss(95)).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_bracket_exit_prices_must_be_positive() {
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new()));
+    let entry = PlaceOrderCommand {
+        side: OrderSide::Buy,
+        ..limit(100, 1)
+    };
+    let result = engine
+        .handle_place_bracket_order(PlaceBracketOrderCommand {
+            bracket_id: Uuid::new_v4(),
+            entry,
+            stop_loss_order_id: Uuid::new_v4(),
+            take_profit_order_id: Uuid::new_v4(),
+            stop_loss_price: Decimal::from(-10),
+            take_profit_price: Decimal::from(110),
+        })
+        .await;
+    assert_eq!(
+        result.unwrap_err(),
+        EngineError::InvalidOrder(RejectReason::NonPositiveStopPrice(Decimal::from(-10)))
+    );
+}
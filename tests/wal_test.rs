@@ -0,0 +1,241 @@
+use matching_engine::{
+    engine::MatchingEngine, event_store::InMemoryEventStore, types::{OrderSide, OrderStatus, OrderType},
+    FileSnapshotStore, KillSwitchTarget, OrderCommand, OrderEvent, PlaceBracketOrderCommand, PlaceOrderCommand,
+    SnapshotCadence, SnapshotStore, SymbolState, WriteAheadLog,
+};
+use rust_decimal::Decimal;
+use std::io::Write;
+use uuid::Uuid;
+
+fn create_test_order_cmd(side: OrderSide, price: Decimal, quantity: Decimal) -> PlaceOrderCommand {
+    PlaceOrderCommand::new(Uuid::new_v4(), "BTC/USDT", OrderType::Limit, side, Some(price), quantity)
+}
+
+fn engine_with_wal(dir: &std::path::Path) -> MatchingEngine {
+    MatchingEngine::new(Box::new(InMemoryEventStore::new())).with_wal(WriteAheadLog::open(dir).unwrap())
+}
+
+#[tokio::test]
+async fn test_recover_replays_logged_commands() {
+    let dir = std::env::temp_dir().join(format!("wal-{}", Uuid::new_v4()));
+    let engine = engine_with_wal(&dir);
+    let sell = create_test_order_cmd(OrderSide::Sell, Decimal::from(100), Decimal::from(3));
+    let buy = create_test_order_cmd(OrderSide::Buy, Decimal::from(100), Decimal::from(1));
+    let bid = create_test_order_cmd(OrderSide::Buy, Decimal::from(99), Decimal::from(2));
+    for cmd in [&sell, &buy, &bid] {
+        engine.handle_command(OrderCommand::PlaceOrder(cmd.clone())).await.unwrap();
+    }
+    let book = engine.get_order_book("BTC/USDT").unwrap();
+    drop(engine);
+
+    let engine = engine_with_wal(&dir);
+    let replayed = engine.recover().await.unwrap();
+    assert!(!replayed.is_empty());
+    // Saved to a store that lost them
+    assert_eq!(engine.event_store().get_all_events().await.unwrap().len(), replayed.len());
+
+    let recovered = engine.get_order_book("BTC/USDT").unwrap();
+    assert_eq!((recovered.bids, recovered.asks), (book.bids, book.asks));
+    let sell_order = engine.get_order(sell.order_id).unwrap();
+    assert_eq!(sell_order.filled_quantity, Decimal::ONE);
+    assert_eq!(engine.get_order(buy.order_id).unwrap().status, OrderStatus::Filled);
+    assert!(engine.recover().await.is_err());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_recovered_trades_and_events_keep_their_ids_and_times() {
+    let dir = std::env::temp_dir().join(format!("wal-{}", Uuid::new_v4()));
+    let engine = engine_with_wal(&dir);
+    let sell = create_test_order_cmd(OrderSide::Sell, Decimal::from(100), Decimal::ONE);
+    let buy = create_test_order_cmd(OrderSide::Buy, Decimal::from(100), Decimal::ONE);
+    for cmd in [&sell, &buy] {
+        engine.handle_command(OrderCommand::PlaceOrder(cmd.clone())).await.unwrap();
+    }
+    let trades = engine.get_trades_by_symbol("BTC/USDT", None, 10);
+    let saved: Vec<Uuid> = engine.event_store().get_all_events().await.unwrap().iter().map(|e| e.event_id()).collect();
+    drop(engine);
+
+    let engine = engine_with_wal(&dir);
+    let replayed: Vec<Uuid> = engine.recover().await.unwrap().iter().map(|e| e.event_id()).collect();
+    assert_eq!(replayed, saved);
+    let recovered = engine.get_trades_by_symbol("BTC/USDT", None, 10);
+    assert_eq!(format!("{:?}", recovered), format!("{:?}", trades));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+fn engine_with_snapshots(dir: &std::path::Path, cadence: SnapshotCadence) -> MatchingEngine {
+    engine_with_wal(dir).with_snapshots(FileSnapshotStore::open(dir.join("snapshots")).unwrap(), cadence)
+}
+
+#[tokio::test]
+async fn test_snapshot_empties_the_log() {
+    let dir = std::env::temp_dir().join(format!("wal-{}", Uuid::new_v4()));
+    let engine = engine_with_snapshots(&dir, SnapshotCadence::default());
+    let sell = create_test_order_cmd(OrderSide::Sell, Decimal::from(101), Decimal::from(2));
+    let stop = PlaceOrderCommand {
+        order_type: OrderType::StopLoss,
+        price: None,
+        stop_price: Some(Decimal::from(90)),
+        ..create_test_order_cmd(OrderSide::Sell, Decimal::ZERO, Decimal::ONE)
+    };
+    engine.handle_command(OrderCommand::PlaceOrder(sell.clone())).await.unwrap();
+    engine.handle_command(OrderCommand::PlaceOrder(stop.clone())).await.unwrap();
+    assert_eq!(engine.take_snapshot().await.unwrap().sequence, 2);
+
+    let buy = create_test_order_cmd(OrderSide::Buy, Decimal::from(101), Decimal::ONE);
+    engine.handle_command(OrderCommand::PlaceOrder(buy.clone())).await.unwrap();
+    drop(engine);
+
+    let records = WriteAheadLog::open(&dir).unwrap().records().unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].sequence, 3);
+
+    let engine = engine_with_snapshots(&dir, SnapshotCadence::default());
+    engine.recover().await.unwrap();
+    assert_eq!(engine.get_order(sell.order_id).unwrap().filled_quantity, Decimal::ONE);
+    assert_eq!(engine.get_order(buy.order_id).unwrap().status, OrderStatus::Filled);
+    assert!(engine.get_order(stop.order_id).unwrap().is_open());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_snapshots_follow_the_command_cadence() {
+    let dir = std::env::temp_dir().join(format!("wal-{}", Uuid::new_v4()));
+    let cadence = SnapshotCadence {
+        every_commands: Some(2),
+        every: None,
+    };
+    let engine = engine_with_snapshots(&dir, cadence);
+    let orders: Vec<PlaceOrderCommand> = [97, 98, 99]
+        .into_iter()
+        .map(|price| create_test_order_cmd(OrderSide::Buy, Decimal::from(price), Decimal::ONE))
+        .collect();
+    for cmd in &orders {
+        engine.handle_command(OrderCommand::PlaceOrder(cmd.clone())).await.unwrap();
+    }
+    drop(engine);
+
+    let snapshot = FileSnapshotStore::open(dir.join("snapshots")).unwrap().latest_snapshot().await.unwrap().unwrap();
+    assert_eq!(snapshot.sequence, 2);
+    assert_eq!(snapshot.books["BTC/USDT"].bids.len(), 2);
+    assert_eq!(WriteAheadLog::open(&dir).unwrap().records().unwrap().len(), 1);
+
+    // Numbering carries on past the emptied log
+    let engine = engine_with_snapshots(&dir, cadence);
+    engine.recover().await.unwrap();
+    assert!(orders.iter().all(|cmd| engine.get_order(cmd.order_id).is_some()));
+    let next = create_test_order_cmd(OrderSide::Buy, Decimal::from(96), Decimal::ONE);
+    engine.handle_command(OrderCommand::PlaceOrder(next)).await.unwrap();
+    let records = WriteAheadLog::open(&dir).unwrap().records().unwrap();
+    assert_eq!(records.iter().map(|r| r.sequence).collect::<Vec<_>>(), [3, 4]);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_restored_snapshot_keeps_brackets_client_order_ids_and_switches() {
+    let dir = std::env::temp_dir().join(format!("wal-{}", Uuid::new_v4()));
+    let engine = engine_with_snapshots(&dir, SnapshotCadence::default());
+    let sell = create_test_order_cmd(OrderSide::Sell, Decimal::from(100), Decimal::ONE);
+    let buy = PlaceOrderCommand {
+        client_order_id: Some("buy-1".to_string()),
+        ..create_test_order_cmd(OrderSide::Buy, Decimal::from(100), Decimal::ONE)
+    };
+    let bracket = PlaceBracketOrderCommand {
+        bracket_id: Uuid::new_v4(),
+        entry: create_test_order_cmd(OrderSide::Buy, Decimal::from(99), Decimal::ONE),
+        stop_loss_order_id: Uuid::new_v4(),
+        stop_loss_price: Decimal::from(90),
+        take_profit_order_id: Uuid::new_v4(),
+        take_profit_price: Decimal::from(110),
+    };
+    engine.handle_command(OrderCommand::PlaceOrder(sell)).await.unwrap();
+    let answered = engine.handle_command(OrderCommand::PlaceOrder(buy.clone())).await.unwrap();
+    engine.handle_command(OrderCommand::PlaceBracketOrder(bracket.clone())).await.unwrap();
+    let killed = create_test_order_cmd(OrderSide::Sell, Decimal::from(120), Decimal::ONE);
+    engine.activate_kill_switch(KillSwitchTarget::User(killed.user_id), "test").await.unwrap();
+    engine.halt("ETH/USDT").await.unwrap();
+    engine.take_snapshot().await.unwrap();
+    drop(engine);
+
+    let engine = engine_with_snapshots(&dir, SnapshotCadence::default());
+    engine.recover().await.unwrap();
+    // A resubmission gets the first answer, and the filled order's id stays taken
+    let resubmitted = engine.handle_command(OrderCommand::PlaceOrder(buy.clone())).await.unwrap();
+    let event_ids = |events: &[OrderEvent]| events.iter().map(|e| e.event_id()).collect::<Vec<_>>();
+    assert_eq!(event_ids(&resubmitted), event_ids(&answered));
+    let reused = PlaceOrderCommand {
+        client_order_id: None,
+        ..buy
+    };
+    assert!(engine.handle_command(OrderCommand::PlaceOrder(reused)).await.is_err());
+
+    assert!(engine.handle_command(OrderCommand::PlaceOrder(killed)).await.is_err());
+    assert_eq!(engine.symbol_state("ETH/USDT"), SymbolState::Halted);
+
+    // Filling the entry still arms its exits
+    let fill = create_test_order_cmd(OrderSide::Sell, Decimal::from(99), Decimal::ONE);
+    let events = engine.handle_command(OrderCommand::PlaceOrder(fill)).await.unwrap();
+    assert!(events.iter().any(|e| matches!(e, OrderEvent::BracketOrderActivated(_))));
+    assert!(engine.get_order(bracket.stop_loss_order_id).unwrap().is_open());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_recover_skips_commands_that_were_rejected() {
+    let dir = std::env::temp_dir().join(format!("wal-{}", Uuid::new_v4()));
+    let engine = engine_with_wal(&dir);
+    let sell = create_test_order_cmd(OrderSide::Sell, Decimal::from(100), Decimal::ONE);
+    let buy = create_test_order_cmd(OrderSide::Buy, Decimal::from(100), Decimal::ONE);
+    engine.handle_command(OrderCommand::PlaceOrder(sell.clone())).await.unwrap();
+    // The kill switch is not logged, so only the rejection keeps the buy
+    // from trading on replay
+    engine.activate_kill_switch(KillSwitchTarget::User(buy.user_id), "test").await.unwrap();
+    assert!(engine.handle_command(OrderCommand::PlaceOrder(buy.clone())).await.is_err());
+    drop(engine);
+
+    let records = WriteAheadLog::open(&dir).unwrap().records().unwrap();
+    assert_eq!(records.iter().map(|r| r.rejected).collect::<Vec<_>>(), [false, true]);
+
+    let engine = engine_with_wal(&dir);
+    engine.recover().await.unwrap();
+    assert!(engine.get_order(buy.order_id).is_none());
+    assert!(engine.get_order(sell.order_id).unwrap().is_open());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_open_cuts_off_a_garbled_last_frame() {
+    let dir = std::env::temp_dir().join(format!("wal-{}", Uuid::new_v4()));
+    let engine = engine_with_wal(&dir);
+    let bid = create_test_order_cmd(OrderSide::Buy, Decimal::from(99), Decimal::ONE);
+    engine.handle_command(OrderCommand::PlaceOrder(bid.clone())).await.unwrap();
+    drop(engine);
+
+    let path = dir.join("wal.log");
+    let logged = std::fs::metadata(&path).unwrap().len();
+    let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(&4u32.to_le_bytes()).unwrap();
+    file.write_all(&[0; 4]).unwrap();
+    drop(file);
+
+    let wal = WriteAheadLog::open(&dir).unwrap();
+    assert_eq!(wal.records().unwrap().len(), 1);
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), logged);
+
+    let engine = MatchingEngine::new(Box::new(InMemoryEventStore::new())).with_wal(wal);
+    engine.recover().await.unwrap();
+    assert!(engine.get_order(bid.order_id).unwrap().is_open());
+    let next = create_test_order_cmd(OrderSide::Buy, Decimal::from(98), Decimal::ONE);
+    engine.handle_command(OrderCommand::PlaceOrder(next)).await.unwrap();
+    let records = WriteAheadLog::open(&dir).unwrap().records().unwrap();
+    assert_eq!(records.iter().map(|r| r.sequence).collect::<Vec<_>>(), [1, 2]);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
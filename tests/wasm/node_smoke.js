@@ -0,0 +1,28 @@
+// Drives the wasm build from Node through the wasm-bindgen glue in the
+// directory given as the first argument.
+const assert = require('node:assert');
+const path = require('node:path');
+
+const { WasmEngine } = require(path.resolve(process.argv[2], 'matching_engine.js'));
+
+const engine = new WasmEngine();
+const order = (userId, side, price, quantity) => JSON.stringify({
+  user_id: userId,
+  symbol: 'BTC/USDT',
+  order_type: 'Limit',
+  side,
+  price,
+  quantity,
+});
+const buyer = '6f1e3b52-0c1a-4c39-9d4e-1f5d1c2a9b01';
+const seller = '0b7e9f6a-5d2c-4e8a-a3b1-7c9d2e4f6a10';
+
+const placed = JSON.parse(engine.placeOrder(order(buyer, 'Buy', '100', '2')));
+const orderId = placed[0].OrderPlaced.order_id;
+const matched = JSON.parse(engine.placeOrder(order(seller, 'Sell', '100', '1')));
+assert.strictEqual(matched[1].OrderMatched.price, '100');
+assert.strictEqual(JSON.parse(engine.getOrder(orderId)).filled_quantity, '1');
+assert.strictEqual(JSON.parse(engine.getDepth('BTC/USDT', 10)).bids[0].quantity, '1');
+assert.strictEqual(JSON.parse(engine.getRecentTrades('BTC/USDT', 10)).length, 1);
+assert.throws(() => engine.placeOrder('{'));
+console.log('wasm engine matched orders under Node');
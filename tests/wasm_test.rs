@@ -0,0 +1,50 @@
+#![cfg(feature = "wasm")]
+
+use matching_engine::WasmEngine;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+fn place(engine: &WasmEngine, user_id: Uuid, side: &str, price: &str, quantity: &str) -> Vec<Value> {
+    let request = json!({
+        "user_id": user_id,
+        "symbol": "BTC/USDT",
+        "order_type": "Limit",
+        "side": side,
+        "price": price,
+        "quantity": quantity
+    });
+    serde_json::from_str(&engine.place_order(&request.to_string()).unwrap()).unwrap()
+}
+
+#[test]
+fn test_orders_are_matched_without_a_runtime() {
+    let engine = WasmEngine::new();
+    let (buyer, seller) = (Uuid::new_v4(), Uuid::new_v4());
+
+    let placed = place(&engine, buyer, "Buy", "100", "2");
+    let order_id = placed[0]["OrderPlaced"]["order_id"].as_str().unwrap().to_string();
+    let matched = place(&engine, seller, "Sell", "100", "1");
+    assert_eq!(matched[1]["OrderMatched"]["price"], "100");
+
+    let order: Value = serde_json::from_str(&engine.get_order(&order_id).unwrap().unwrap()).unwrap();
+    assert_eq!(order["filled_quantity"], "1");
+    let depth: Value = serde_json::from_str(&engine.get_depth("BTC/USDT", 10)).unwrap();
+    assert_eq!(depth["bids"][0]["quantity"], "1");
+    let trades: Value = serde_json::from_str(&engine.get_recent_trades("BTC/USDT", 10)).unwrap();
+    assert_eq!(trades.as_array().unwrap().len(), 1);
+
+    let cancel = json!({"order_id": order_id, "user_id": buyer, "symbol": "BTC/USDT"});
+    let canceled: Value = serde_json::from_str(&engine.cancel_order(&cancel.to_string()).unwrap()).unwrap();
+    assert!(canceled[0].get("OrderCanceled").is_some());
+}
+
+#[test]
+fn test_bad_requests_come_back_as_errors() {
+    let engine = WasmEngine::new();
+    assert!(engine.place_order("{").is_err());
+    assert!(engine.get_order("not an id").is_err());
+    assert_eq!(engine.get_order(&Uuid::new_v4().to_string()), Ok(None));
+
+    let cancel = json!({"order_id": Uuid::new_v4(), "user_id": Uuid::new_v4(), "symbol": "BTC/USDT"});
+    assert!(engine.cancel_order(&cancel.to_string()).is_err());
+}